@@ -45,12 +45,18 @@ function constructServerUrl(urlSuffix: string) {
 
 export const platform = {
 	platform: 'tauri',
-	getThumbnailUrlByThumbKey: (keyParts) =>
+	getThumbnailUrlByThumbKey: (keyParts, size) =>
 		constructServerUrl(
-			`/thumbnail/${keyParts.map((i) => encodeURIComponent(i)).join('/')}.webp`
+			`/thumbnail/${keyParts.map((i) => encodeURIComponent(i)).join('/')}.webp${
+				size ? `?size=${size}` : ''
+			}`
 		),
 	getFileUrl: (libraryId, locationLocalId, filePathId) =>
 		constructServerUrl(`/file/${libraryId}/${locationLocalId}/${filePathId}`),
+	getTranscodeUrl: (libraryId, locationLocalId, filePathId) =>
+		constructServerUrl(`/transcode/${libraryId}/${locationLocalId}/${filePathId}`),
+	getMediaTrackUrl: (libraryId, locationLocalId, filePathId, track) =>
+		constructServerUrl(`/tracks/${libraryId}/${locationLocalId}/${filePathId}/${track}`),
 	getFileUrlByPath: (path) =>
 		constructServerUrl(`/local-file-by-path/${encodeURIComponent(path)}`),
 	getRemoteRspcEndpoint: (remote_identity) => ({
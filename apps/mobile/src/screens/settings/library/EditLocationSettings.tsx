@@ -54,7 +54,9 @@ const EditLocationSettingsScreen = ({
 			sync_preview_media: data.syncPreviewMedia,
 			generate_preview_media: data.generatePreviewMedia,
 			hidden: data.hidden,
-			indexer_rules_ids: []
+			indexer_rules_ids: [],
+			symlink_policy: null,
+			desktop_tags_policy: null
 		})
 	);
 
@@ -1,4 +1,4 @@
-use std::{borrow::Cow, path::Path};
+use std::{borrow::Cow, net::SocketAddr, path::Path, time::Duration};
 
 use libp2p::identity::Keypair;
 use serde::{Deserialize, Serialize};
@@ -19,6 +19,68 @@ pub struct Config {
 	// Private/public keypair to use for the relay.
 	#[serde(with = "keypair")]
 	pub keypair: Keypair,
+	// Which nodes are allowed to use the relay.
+	#[serde(default)]
+	pub auth: AuthConfig,
+	// Limits on what each node can use of the relay.
+	#[serde(default)]
+	pub quotas: QuotaConfig,
+	// Address to serve the metrics and authorization HTTP endpoints on. Disabled if not set.
+	// This is plain HTTP, so it must sit behind a reverse proxy terminating TLS as nodes only send
+	// the shared secret over `https`.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub http_addr: Option<SocketAddr>,
+}
+
+/// If neither an allowlist nor a shared secret is configured the relay is open to every node.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AuthConfig {
+	// Identities of the nodes which are allowed to use the relay.
+	#[serde(default, skip_serializing_if = "Vec::is_empty")]
+	pub allowed_identities: Vec<String>,
+	// Secret which a node can present to `POST /authorize` to be allowed to use the relay.
+	// Nodes present it along with their identity each time they pull the relay configuration, as long
+	// as it's set in their `relay_authorizations` along with the `https` URL `http_addr` is served at.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub shared_secret: Option<String>,
+}
+
+impl AuthConfig {
+	pub fn is_open(&self) -> bool {
+		self.allowed_identities.is_empty() && self.shared_secret.is_none()
+	}
+}
+
+/// The defaults match the defaults of `libp2p::relay::Config`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct QuotaConfig {
+	// Maximum number of circuits a node can have open at once.
+	pub max_circuits_per_identity: usize,
+	// Maximum number of bytes relayed through a single circuit before it's closed.
+	pub max_circuit_bytes: u64,
+	// Maximum number of seconds a single circuit can stay open.
+	pub max_circuit_duration_secs: u64,
+	// Maximum number of circuits a node can open per day. Unlimited if not set.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub max_circuits_per_day: Option<u64>,
+}
+
+impl Default for QuotaConfig {
+	fn default() -> Self {
+		Self {
+			max_circuits_per_identity: 4,
+			max_circuit_bytes: 1 << 17,
+			max_circuit_duration_secs: 2 * 60,
+			max_circuits_per_day: None,
+		}
+	}
+}
+
+impl QuotaConfig {
+	pub fn max_circuit_duration(&self) -> Duration {
+		Duration::from_secs(self.max_circuit_duration_secs)
+	}
 }
 
 impl Config {
@@ -32,6 +94,9 @@ impl Config {
 			p2p_secret,
 			port: None,
 			keypair: Keypair::generate_ed25519(),
+			auth: AuthConfig::default(),
+			quotas: QuotaConfig::default(),
+			http_addr: None,
 		};
 		std::fs::write(path, serde_json::to_string_pretty(&config)?)?;
 		Ok(config)
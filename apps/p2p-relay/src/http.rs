@@ -0,0 +1,110 @@
+//! The HTTP server exposing Prometheus metrics and authorization with the shared secret.
+//!
+//! It doesn't terminate TLS itself, so it's expected to be put behind a reverse proxy which does.
+
+use std::{net::SocketAddr, sync::Arc};
+
+use axum::{
+	extract::State,
+	http::{header, HeaderMap, StatusCode},
+	routing::{get, post},
+	Json, Router,
+};
+use prometheus_client::{encoding::text::encode, registry::Registry};
+use serde::Deserialize;
+use tracing::{error, info, warn};
+
+use crate::limits::{identity_to_peer_id, Limits};
+
+struct AppState {
+	registry: Registry,
+	limits: Arc<Limits>,
+	shared_secret: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct AuthorizeRequest {
+	identity: String,
+}
+
+pub async fn serve(
+	addr: SocketAddr,
+	registry: Registry,
+	limits: Arc<Limits>,
+	shared_secret: Option<String>,
+) {
+	let app = Router::new()
+		.route("/metrics", get(metrics))
+		.route("/authorize", post(authorize))
+		.with_state(Arc::new(AppState {
+			registry,
+			limits,
+			shared_secret,
+		}));
+
+	info!("Serving metrics on 'http://{addr}/metrics'");
+	if let Err(err) = axum::Server::bind(&addr)
+		.serve(app.into_make_service())
+		.await
+	{
+		error!("HTTP server on '{addr}' failed: {err}");
+	}
+}
+
+async fn metrics(State(state): State<Arc<AppState>>) -> (StatusCode, HeaderMap, String) {
+	let mut headers = HeaderMap::new();
+	let mut body = String::new();
+	if let Err(err) = encode(&mut body, &state.registry) {
+		error!("Failed to encode metrics: {err}");
+		return (StatusCode::INTERNAL_SERVER_ERROR, headers, String::new());
+	}
+
+	headers.insert(
+		header::CONTENT_TYPE,
+		"application/openmetrics-text; version=1.0.0; charset=utf-8"
+			.parse()
+			.expect("valid header value"),
+	);
+	(StatusCode::OK, headers, body)
+}
+
+async fn authorize(
+	State(state): State<Arc<AppState>>,
+	headers: HeaderMap,
+	Json(req): Json<AuthorizeRequest>,
+) -> StatusCode {
+	let Some(shared_secret) = &state.shared_secret else {
+		return StatusCode::NOT_FOUND;
+	};
+
+	let secret = headers
+		.get(header::AUTHORIZATION)
+		.and_then(|value| value.to_str().ok())
+		.and_then(|value| value.strip_prefix("Bearer "));
+	let is_valid =
+		secret.is_some_and(|secret| constant_time_eq(secret.as_bytes(), shared_secret.as_bytes()));
+	if !is_valid {
+		warn!(
+			"Denied authorization of '{}' with an invalid secret",
+			req.identity
+		);
+		return StatusCode::UNAUTHORIZED;
+	}
+
+	match identity_to_peer_id(&req.identity) {
+		Ok(peer_id) => {
+			state.limits.authorize(peer_id);
+			info!("Authorized '{}' as peer '{peer_id}'", req.identity);
+			StatusCode::NO_CONTENT
+		}
+		Err(err) => {
+			warn!("Failed to authorize: {err}");
+			StatusCode::BAD_REQUEST
+		}
+	}
+}
+
+/// Compare secrets without leaking how much of them matched through timing.
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+	a.len() == b.len() && a.iter().zip(b).fold(0, |acc, (a, b)| acc | (a ^ b)) == 0
+}
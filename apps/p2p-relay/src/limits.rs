@@ -0,0 +1,218 @@
+//! Authentication and per-identity quotas, enforced through the rate limiters of `libp2p::relay`.
+
+use std::{
+	collections::{HashMap, HashSet},
+	sync::{Arc, Mutex, PoisonError},
+	time::{Duration, Instant},
+};
+
+use base64::{engine::general_purpose, Engine};
+use libp2p::{identity::ed25519, relay::RateLimiter, Multiaddr, PeerId};
+use tracing::debug;
+
+use crate::config::{AuthConfig, QuotaConfig};
+
+/// How long a node which presented the shared secret is allowed to use the relay for, before it must present it again.
+pub const AUTHORIZATION_TTL: Duration = Duration::from_secs(24 * 60 * 60);
+const QUOTA_WINDOW: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Convert the identity of a Spacedrive node (as shown in the app) into its libp2p `PeerId`.
+pub fn identity_to_peer_id(identity: &str) -> Result<PeerId, String> {
+	let bytes = general_purpose::STANDARD_NO_PAD
+		.decode(identity.trim().trim_end_matches('='))
+		.map_err(|err| format!("invalid identity '{identity}': {err}"))?;
+	let public_key = ed25519::PublicKey::try_from_bytes(&bytes)
+		.map_err(|err| format!("invalid identity '{identity}': {err}"))?;
+	Ok(PeerId::from_public_key(&public_key.into()))
+}
+
+#[derive(Debug)]
+struct Usage {
+	open_circuits: usize,
+	window_started_at: Instant,
+	circuits_in_window: u64,
+}
+
+#[derive(Debug, Default)]
+struct State {
+	/// Nodes which presented the shared secret and when that expires
+	authorized: HashMap<PeerId, Instant>,
+	usage: HashMap<PeerId, Usage>,
+}
+
+#[derive(Debug)]
+pub struct Limits {
+	open: bool,
+	allowed: HashSet<PeerId>,
+	quotas: QuotaConfig,
+	state: Mutex<State>,
+}
+
+impl Limits {
+	pub fn new(auth: &AuthConfig, quotas: QuotaConfig) -> Result<Arc<Self>, String> {
+		Ok(Arc::new(Self {
+			open: auth.is_open(),
+			allowed: auth
+				.allowed_identities
+				.iter()
+				.map(|identity| identity_to_peer_id(identity))
+				.collect::<Result<_, _>>()?,
+			quotas,
+			state: Default::default(),
+		}))
+	}
+
+	/// Allow a node to use the relay for [`AUTHORIZATION_TTL`].
+	pub fn authorize(&self, peer_id: PeerId) {
+		let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+		let now = Instant::now();
+		state.authorized.retain(|_, expires_at| *expires_at > now);
+		state.authorized.insert(peer_id, now + AUTHORIZATION_TTL);
+	}
+
+	pub fn is_authorized(&self, peer_id: &PeerId, now: Instant) -> bool {
+		self.open
+			|| self.allowed.contains(peer_id)
+			|| self
+				.state
+				.lock()
+				.unwrap_or_else(PoisonError::into_inner)
+				.authorized
+				.get(peer_id)
+				.is_some_and(|expires_at| *expires_at > now)
+	}
+
+	fn has_circuit_quota(&self, peer_id: &PeerId, now: Instant) -> bool {
+		let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+		let Some(usage) = state.usage.get_mut(peer_id) else {
+			return true;
+		};
+
+		if now.duration_since(usage.window_started_at) >= QUOTA_WINDOW {
+			usage.window_started_at = now;
+			usage.circuits_in_window = 0;
+		}
+
+		usage.open_circuits < self.quotas.max_circuits_per_identity
+			&& !matches!(
+				self.quotas.max_circuits_per_day,
+				Some(max_circuits) if usage.circuits_in_window >= max_circuits
+			)
+	}
+
+	pub fn circuit_opened(&self, peer_id: PeerId, now: Instant) {
+		let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+		let usage = state.usage.entry(peer_id).or_insert_with(|| Usage {
+			open_circuits: 0,
+			window_started_at: now,
+			circuits_in_window: 0,
+		});
+		usage.open_circuits += 1;
+		usage.circuits_in_window += 1;
+	}
+
+	pub fn circuit_closed(&self, peer_id: &PeerId) {
+		let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+		if let Some(usage) = state.usage.get_mut(peer_id) {
+			usage.open_circuits = usage.open_circuits.saturating_sub(1);
+
+			// Nothing left to track once the node's usage is outside of the window
+			if usage.open_circuits == 0 && usage.window_started_at.elapsed() >= QUOTA_WINDOW {
+				state.usage.remove(peer_id);
+			}
+		}
+	}
+}
+
+/// Only authorized nodes can reserve a slot on the relay.
+pub struct ReservationLimiter(pub Arc<Limits>);
+
+impl RateLimiter for ReservationLimiter {
+	fn try_next(&mut self, peer: PeerId, addr: &Multiaddr, now: Instant) -> bool {
+		let allowed = self.0.is_authorized(&peer, now);
+		if !allowed {
+			debug!("Denied reservation from unauthorized peer '{peer}' at '{addr}'");
+		}
+		allowed
+	}
+}
+
+/// Only authorized nodes within their quota can open a circuit through the relay.
+pub struct CircuitLimiter(pub Arc<Limits>);
+
+impl RateLimiter for CircuitLimiter {
+	fn try_next(&mut self, peer: PeerId, addr: &Multiaddr, now: Instant) -> bool {
+		if !self.0.is_authorized(&peer, now) {
+			debug!("Denied circuit from unauthorized peer '{peer}' at '{addr}'");
+			return false;
+		}
+
+		let allowed = self.0.has_circuit_quota(&peer, now);
+		if !allowed {
+			debug!("Denied circuit from peer '{peer}' at '{addr}' as it's over its quota");
+		}
+		allowed
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn limits(quotas: QuotaConfig) -> Arc<Limits> {
+		Limits::new(&AuthConfig::default(), quotas).expect("default auth config is valid")
+	}
+
+	#[test]
+	fn denies_circuits_over_the_daily_quota() {
+		let limits = limits(QuotaConfig {
+			max_circuits_per_day: Some(2),
+			..Default::default()
+		});
+		let peer_id = PeerId::random();
+		let now = Instant::now();
+
+		for _ in 0..2 {
+			assert!(limits.has_circuit_quota(&peer_id, now));
+			limits.circuit_opened(peer_id, now);
+			limits.circuit_closed(&peer_id);
+		}
+
+		assert!(!limits.has_circuit_quota(&peer_id, now));
+		// Other nodes have their own quota
+		assert!(limits.has_circuit_quota(&PeerId::random(), now));
+	}
+
+	#[test]
+	fn daily_quota_resets_after_the_window() {
+		let limits = limits(QuotaConfig {
+			max_circuits_per_day: Some(1),
+			..Default::default()
+		});
+		let peer_id = PeerId::random();
+		let now = Instant::now();
+
+		limits.circuit_opened(peer_id, now);
+		limits.circuit_closed(&peer_id);
+
+		assert!(!limits.has_circuit_quota(&peer_id, now + QUOTA_WINDOW / 2));
+		assert!(limits.has_circuit_quota(&peer_id, now + QUOTA_WINDOW));
+	}
+
+	#[test]
+	fn denies_circuits_over_the_concurrent_limit() {
+		let limits = limits(QuotaConfig {
+			max_circuits_per_identity: 2,
+			..Default::default()
+		});
+		let peer_id = PeerId::random();
+		let now = Instant::now();
+
+		limits.circuit_opened(peer_id, now);
+		limits.circuit_opened(peer_id, now);
+		assert!(!limits.has_circuit_quota(&peer_id, now));
+
+		limits.circuit_closed(&peer_id);
+		assert!(limits.has_circuit_quota(&peer_id, now));
+	}
+}
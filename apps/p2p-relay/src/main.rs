@@ -2,28 +2,33 @@ use std::{
 	io::{stdin, stdout, Write},
 	net::{Ipv4Addr, Ipv6Addr, SocketAddr},
 	path::PathBuf,
+	time::Instant,
 };
 
 use libp2p::{
 	autonat,
 	futures::StreamExt,
+	metrics::Registry,
 	relay,
 	swarm::{NetworkBehaviour, SwarmEvent},
 };
 use reqwest::header::{self, HeaderMap, HeaderValue};
 use serde::{Deserialize, Serialize};
-use tracing::{error, info, warn};
+use tracing::{debug, error, info, warn};
 use uuid::Uuid;
 
-use crate::utils::socketaddr_to_quic_multiaddr;
+use crate::{
+	limits::{CircuitLimiter, Limits, ReservationLimiter},
+	metrics::Metrics,
+	utils::socketaddr_to_quic_multiaddr,
+};
 
 mod config;
+mod http;
+mod limits;
+mod metrics;
 mod utils;
 
-// TODO: Authentication with the Spacedrive Cloud
-// TODO: Rate-limit data usage by Spacedrive account.
-// TODO: Expose libp2p metrics like - https://github.com/mxinden/rust-libp2p-server/blob/master/src/behaviour.rs
-
 #[derive(NetworkBehaviour)]
 pub struct Behaviour {
 	relay: relay::Behaviour,
@@ -73,6 +78,8 @@ async fn main() {
 		panic!("Unable to find config at path '{config_path:?}'. Please create it!"); // TODO: Error handling
 	}
 	let config = config::Config::load(&config_path).unwrap(); // TODO: Error handling
+	let limits = Limits::new(&config.auth, config.quotas.clone())
+		.unwrap_or_else(|err| panic!("Invalid auth config: {err}"));
 
 	info!("Starting...");
 
@@ -178,11 +185,26 @@ async fn main() {
 
 	let peer_id = config.keypair.public().to_peer_id();
 
+	let mut relay_config = relay::Config {
+		max_circuits_per_peer: config.quotas.max_circuits_per_identity,
+		max_circuit_bytes: config.quotas.max_circuit_bytes,
+		max_circuit_duration: config.quotas.max_circuit_duration(),
+		..Default::default()
+	};
+	relay_config
+		.reservation_rate_limiters
+		.push(Box::new(ReservationLimiter(limits.clone())));
+	relay_config
+		.circuit_src_rate_limiters
+		.push(Box::new(CircuitLimiter(limits.clone())));
+
+	let mut registry = Registry::default();
 	let mut swarm = libp2p::SwarmBuilder::with_existing_identity(config.keypair.clone())
 		.with_tokio()
 		.with_quic()
+		.with_bandwidth_metrics(&mut registry)
 		.with_behaviour(|key| Behaviour {
-			relay: relay::Behaviour::new(key.public().to_peer_id(), Default::default()), // TODO: Proper config
+			relay: relay::Behaviour::new(key.public().to_peer_id(), relay_config),
 			autonat: autonat::Behaviour::new(key.public().to_peer_id(), Default::default()), // TODO: Proper config
 		})
 		.unwrap() // TODO: Error handling
@@ -201,13 +223,44 @@ async fn main() {
 		))))
 		.unwrap(); // TODO: Error handling
 
+	let mut metrics = Metrics::new(&mut registry);
+	match config.http_addr {
+		Some(addr) => {
+			tokio::spawn(http::serve(
+				addr,
+				registry,
+				limits.clone(),
+				config.auth.shared_secret.clone(),
+			));
+		}
+		None if config.auth.shared_secret.is_some() => {
+			warn!("A shared secret is configured but no 'http_addr' to authorize with it on!");
+		}
+		None => {}
+	}
+
 	info!("Started Relay as PeerId '{peer_id}'");
 
 	loop {
-		match swarm.next().await.expect("Infinite Stream.") {
-			// SwarmEvent::Behaviour(event) => {
-			// 	println!("{event:?}")
-			// }
+		let event = swarm.next().await.expect("Infinite Stream.");
+		metrics.record_swarm_event(&event);
+
+		match event {
+			SwarmEvent::Behaviour(BehaviourEvent::Relay(event)) => {
+				metrics.record_relay_event(&event);
+
+				match event {
+					relay::Event::CircuitReqAccepted { src_peer_id, .. } => {
+						limits.circuit_opened(src_peer_id, Instant::now())
+					}
+					relay::Event::CircuitClosed { src_peer_id, .. } => {
+						limits.circuit_closed(&src_peer_id)
+					}
+					_ => {}
+				}
+
+				debug!("{event:?}");
+			}
 			SwarmEvent::NewListenAddr { address, .. } => {
 				info!("Listening on {address:?}");
 			}
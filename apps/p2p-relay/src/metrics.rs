@@ -0,0 +1,124 @@
+//! Prometheus metrics of the relay, on top of the ones provided by `libp2p-metrics`.
+
+use std::collections::HashSet;
+
+use libp2p::{
+	metrics::{self, Recorder},
+	relay,
+	swarm::SwarmEvent,
+	PeerId,
+};
+use prometheus_client::{
+	encoding::{EncodeLabelSet, EncodeLabelValue},
+	metrics::{counter::Counter, family::Family, gauge::Gauge},
+	registry::Registry,
+};
+
+#[derive(Debug, Clone, Hash, PartialEq, Eq, EncodeLabelValue)]
+enum Request {
+	Reservation,
+	Circuit,
+}
+
+#[derive(Debug, Clone, Hash, PartialEq, Eq, EncodeLabelSet)]
+struct DeniedLabels {
+	request: Request,
+}
+
+pub struct Metrics {
+	libp2p: metrics::Metrics,
+	/// `libp2p::relay` doesn't emit an event when a reservation ends because the peer disconnected
+	reserved: HashSet<PeerId>,
+	active_reservations: Gauge,
+	active_circuits: Gauge,
+	denied_requests: Family<DeniedLabels, Counter>,
+}
+
+impl Metrics {
+	pub fn new(registry: &mut Registry) -> Self {
+		let libp2p = metrics::Metrics::new(registry);
+
+		let registry = registry.sub_registry_with_prefix("relay");
+		let active_reservations = Gauge::default();
+		registry.register(
+			"active_reservations",
+			"Number of peers with a reservation on the relay",
+			active_reservations.clone(),
+		);
+		let active_circuits = Gauge::default();
+		registry.register(
+			"active_circuits",
+			"Number of circuits currently open through the relay",
+			active_circuits.clone(),
+		);
+		let denied_requests = Family::default();
+		registry.register(
+			"denied_requests",
+			"Number of reservation and circuit requests which were denied",
+			denied_requests.clone(),
+		);
+
+		Self {
+			libp2p,
+			reserved: HashSet::new(),
+			active_reservations,
+			active_circuits,
+			denied_requests,
+		}
+	}
+
+	pub fn record_swarm_event<T>(&mut self, event: &SwarmEvent<T>) {
+		self.libp2p.record(event);
+
+		if let SwarmEvent::ConnectionClosed {
+			peer_id,
+			num_established: 0,
+			..
+		} = event
+		{
+			self.reservation_ended(peer_id);
+		}
+	}
+
+	pub fn record_relay_event(&mut self, event: &relay::Event) {
+		self.libp2p.record(event);
+
+		match event {
+			relay::Event::ReservationReqAccepted { src_peer_id, .. } => {
+				if self.reserved.insert(*src_peer_id) {
+					self.active_reservations.inc();
+				}
+			}
+			relay::Event::ReservationTimedOut { src_peer_id } => {
+				self.reservation_ended(src_peer_id);
+			}
+			relay::Event::ReservationReqDenied { .. } => {
+				self.denied_requests
+					.get_or_create(&DeniedLabels {
+						request: Request::Reservation,
+					})
+					.inc();
+			}
+			relay::Event::CircuitReqDenied { .. } => {
+				self.denied_requests
+					.get_or_create(&DeniedLabels {
+						request: Request::Circuit,
+					})
+					.inc();
+			}
+			relay::Event::CircuitReqAccepted { .. } => {
+				self.active_circuits.inc();
+			}
+			relay::Event::CircuitClosed { .. } => {
+				self.active_circuits.dec();
+			}
+			_ => {}
+		}
+	}
+
+	fn reservation_ended(&mut self, peer_id: &PeerId) {
+		if self.reserved.remove(peer_id) {
+			self.active_reservations.dec();
+		}
+	}
+}
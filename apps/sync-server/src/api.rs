@@ -0,0 +1,266 @@
+//! The `/api/v1` endpoints `sd-cloud-api` talks to
+
+use sd_cloud_api::{
+	library::{
+		create::CreateResult,
+		message_collections::{self, get::InstanceTimestamp, request_add::RequestAdd},
+	},
+	Instance, Library, MessageCollection,
+};
+use sd_p2p::RemoteIdentity;
+
+use std::collections::HashMap;
+
+use axum::{
+	extract::{Path, State},
+	Json,
+};
+use serde::Deserialize;
+use uuid::Uuid;
+
+use crate::{
+	store::{parse_timestamp, NewInstance},
+	AppState, Error, User,
+};
+
+/// Most collections a node gets at once, it keeps asking until it gets none
+const MESSAGE_COLLECTIONS_PER_REQUEST: usize = 100;
+
+pub async fn me(user: User) -> Json<User> {
+	Json(user)
+}
+
+pub async fn list_libraries(
+	State(state): State<AppState>,
+	user: User,
+) -> Result<Json<Vec<Library>>, Error> {
+	state.store.libraries(&user.id).await.map(Json)
+}
+
+pub async fn get_library(
+	State(state): State<AppState>,
+	user: User,
+	Path(library_id): Path<Uuid>,
+) -> Result<Json<Option<Library>>, Error> {
+	Ok(Json(
+		state
+			.store
+			.library(library_id)
+			.await?
+			.filter(|library| library.owner_id == user.id),
+	))
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateLibraryArgs {
+	name: String,
+	instance_uuid: Uuid,
+	instance_identity: RemoteIdentity,
+	node_id: Uuid,
+	node_remote_identity: RemoteIdentity,
+	metadata: HashMap<String, String>,
+}
+
+pub async fn create_library(
+	State(state): State<AppState>,
+	user: User,
+	Path(library_id): Path<Uuid>,
+	Json(args): Json<CreateLibraryArgs>,
+) -> Result<Json<CreateResult>, Error> {
+	state
+		.store
+		.create_library(
+			library_id,
+			&args.name,
+			&user.id,
+			NewInstance {
+				uuid: args.instance_uuid,
+				identity: args.instance_identity,
+				node_id: args.node_id,
+				node_remote_identity: args.node_remote_identity,
+				metadata: args.metadata,
+			},
+		)
+		.await?;
+
+	Ok(Json(CreateResult {
+		id: library_id.to_string(),
+	}))
+}
+
+#[derive(Deserialize)]
+pub struct UpdateLibraryArgs {
+	name: Option<String>,
+}
+
+pub async fn update_library(
+	State(state): State<AppState>,
+	user: User,
+	Path(library_id): Path<Uuid>,
+	Json(args): Json<UpdateLibraryArgs>,
+) -> Result<(), Error> {
+	ensure_owner(&state, &user, library_id).await?;
+
+	if let Some(name) = args.name {
+		state.store.rename_library(library_id, &name).await?;
+	}
+
+	Ok(())
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateInstanceArgs {
+	node_id: Option<Uuid>,
+	node_remote_identity: Option<RemoteIdentity>,
+	metadata: Option<HashMap<String, String>>,
+}
+
+pub async fn update_instance(
+	State(state): State<AppState>,
+	user: User,
+	Path((library_id, instance_uuid)): Path<(Uuid, Uuid)>,
+	Json(args): Json<UpdateInstanceArgs>,
+) -> Result<(), Error> {
+	ensure_owner(&state, &user, library_id).await?;
+
+	state
+		.store
+		.update_instance(
+			library_id,
+			instance_uuid,
+			args.node_id,
+			args.node_remote_identity,
+			args.metadata,
+		)
+		.await
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JoinLibraryArgs {
+	instance_identity: RemoteIdentity,
+	node_id: Uuid,
+	node_remote_identity: RemoteIdentity,
+	metadata: HashMap<String, String>,
+}
+
+pub async fn join_library(
+	State(state): State<AppState>,
+	user: User,
+	Path((library_id, instance_uuid)): Path<(Uuid, Uuid)>,
+	Json(args): Json<JoinLibraryArgs>,
+) -> Result<Json<Vec<Instance>>, Error> {
+	ensure_owner(&state, &user, library_id).await?;
+
+	state
+		.store
+		.upsert_instance(
+			library_id,
+			NewInstance {
+				uuid: instance_uuid,
+				identity: args.instance_identity,
+				node_id: args.node_id,
+				node_remote_identity: args.node_remote_identity,
+				metadata: args.metadata,
+			},
+		)
+		.await?;
+
+	state.store.instances(library_id).await.map(Json)
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GetMessageCollectionsArgs {
+	instance_uuid: Uuid,
+	timestamps: Vec<InstanceTimestamp>,
+}
+
+pub async fn get_message_collections(
+	State(state): State<AppState>,
+	user: User,
+	Path(library_id): Path<Uuid>,
+	Json(args): Json<GetMessageCollectionsArgs>,
+) -> Result<Json<Vec<MessageCollection>>, Error> {
+	ensure_owner(&state, &user, library_id).await?;
+
+	let from_times = args
+		.timestamps
+		.iter()
+		.map(|timestamp| {
+			parse_timestamp(&timestamp.from_time).map(|time| (timestamp.instance_uuid, time))
+		})
+		.collect::<Result<HashMap<_, _>, _>>()?;
+
+	state
+		.store
+		.message_collections(
+			library_id,
+			args.instance_uuid,
+			&from_times,
+			MESSAGE_COLLECTIONS_PER_REQUEST,
+		)
+		.await
+		.map(Json)
+}
+
+#[derive(Deserialize)]
+pub struct RequestAddArgs {
+	instances: Vec<RequestAddInstance>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RequestAddInstance {
+	instance_uuid: Uuid,
+}
+
+pub async fn request_add(
+	State(state): State<AppState>,
+	user: User,
+	Path(library_id): Path<Uuid>,
+	Json(args): Json<RequestAddArgs>,
+) -> Result<Json<Vec<RequestAdd>>, Error> {
+	ensure_owner(&state, &user, library_id).await?;
+
+	state
+		.store
+		.lock_instances(
+			library_id,
+			args.instances
+				.into_iter()
+				.map(|instance| instance.instance_uuid),
+		)
+		.await
+		.map(Json)
+}
+
+#[derive(Deserialize)]
+pub struct DoAddArgs {
+	instances: Vec<message_collections::do_add::Input>,
+}
+
+pub async fn do_add(
+	State(state): State<AppState>,
+	user: User,
+	Path(library_id): Path<Uuid>,
+	Json(args): Json<DoAddArgs>,
+) -> Result<(), Error> {
+	ensure_owner(&state, &user, library_id).await?;
+
+	state
+		.store
+		.add_message_collections(library_id, args.instances)
+		.await
+}
+
+/// Libraries of other users are reported as missing, we don't tell them apart
+async fn ensure_owner(state: &AppState, user: &User, library_id: Uuid) -> Result<(), Error> {
+	if state.store.library_owner(library_id).await?.as_ref() == Some(&user.id) {
+		Ok(())
+	} else {
+		Err(Error::LibraryNotFound(library_id))
+	}
+}
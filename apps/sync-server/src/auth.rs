@@ -0,0 +1,59 @@
+use axum::{
+	async_trait,
+	extract::FromRequestParts,
+	http::{header::AUTHORIZATION, request::Parts},
+};
+use serde::Serialize;
+
+use crate::{AppState, Error};
+
+/// An account allowed to use this server, nodes authenticate as it with its token
+#[derive(Debug, Clone, Serialize)]
+pub struct User {
+	pub id: String,
+	pub email: String,
+	#[serde(skip)]
+	token: String,
+}
+
+impl User {
+	/// We have no other way of identifying users, so their email doubles as their id
+	pub fn new(email: impl Into<String>, token: impl Into<String>) -> Self {
+		let email = email.into();
+
+		Self {
+			id: email.clone(),
+			email,
+			token: token.into(),
+		}
+	}
+
+	pub(crate) fn has_token(&self, token: &str) -> bool {
+		self.token == token
+	}
+
+	pub(crate) fn token(&self) -> &str {
+		&self.token
+	}
+}
+
+#[async_trait]
+impl FromRequestParts<AppState> for User {
+	type Rejection = Error;
+
+	async fn from_request_parts(parts: &mut Parts, state: &AppState) -> Result<Self, Error> {
+		// Nodes send `{token_type} {access_token}`, and we only ever hand out bearer tokens
+		let token = parts
+			.headers
+			.get(AUTHORIZATION)
+			.and_then(|header| header.to_str().ok())
+			.and_then(|header| header.split_once(' '))
+			.map(|(_, token)| token)
+			.ok_or(Error::Unauthorized)?;
+
+		state
+			.user_by_token(token)
+			.cloned()
+			.ok_or(Error::Unauthorized)
+	}
+}
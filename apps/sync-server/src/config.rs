@@ -0,0 +1,57 @@
+use std::env;
+
+use tracing::warn;
+
+use crate::User;
+
+#[derive(Debug, Clone)]
+pub struct Config {
+	// Port to listen on.
+	pub port: u16,
+	// SQLite (`sqlite://sync.db?mode=rwc`) or Postgres (`postgres://...`) connection string.
+	pub database_url: String,
+	// URL the server is reachable at, shown to users when logging in a node.
+	pub public_url: String,
+	// Accounts allowed to use this server.
+	pub users: Vec<User>,
+}
+
+impl Config {
+	/// Reads the config from the `PORT`, `DATABASE_URL`, `PUBLIC_URL` and `SD_SYNC_USERS`
+	/// environment variables, the latter being a list like `email:token,email2:token2`
+	pub fn from_env() -> Self {
+		let port = env::var("PORT")
+			.ok()
+			.and_then(|port| port.parse().ok())
+			.unwrap_or(8080);
+
+		Self {
+			port,
+			database_url: env::var("DATABASE_URL")
+				.unwrap_or_else(|_| "sqlite://sync.db?mode=rwc".to_string()),
+			public_url: env::var("PUBLIC_URL")
+				.unwrap_or_else(|_| format!("http://localhost:{port}")),
+			users: parse_users(&env::var("SD_SYNC_USERS").unwrap_or_default()),
+		}
+	}
+}
+
+fn parse_users(input: &str) -> Vec<User> {
+	input
+		.split(',')
+		.enumerate()
+		.filter(|(_, s)| !s.is_empty())
+		.filter_map(|(i, s)| {
+			let result = s
+				.split_once(':')
+				.filter(|(email, token)| !email.is_empty() && !token.is_empty())
+				.map(|(email, token)| User::new(email, token));
+
+			if result.is_none() {
+				warn!("Found invalid user {i}. Skipping...");
+			}
+
+			result
+		})
+		.collect()
+}
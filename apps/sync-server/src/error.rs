@@ -0,0 +1,47 @@
+use axum::{
+	http::StatusCode,
+	response::{IntoResponse, Response},
+};
+use tracing::error;
+use uuid::Uuid;
+
+#[derive(thiserror::Error, Debug)]
+pub enum Error {
+	#[error("database error: {0}")]
+	Database(#[from] sqlx::Error),
+	#[error("malformed {0} in the database")]
+	MalformedData(&'static str),
+	#[error("authentication required")]
+	Unauthorized,
+	#[error("library <id='{0}'> not found")]
+	LibraryNotFound(Uuid),
+	#[error("library <id='{0}'> already exists")]
+	LibraryAlreadyExists(Uuid),
+	#[error("instance <uuid='{0}'> not found")]
+	InstanceNotFound(Uuid),
+	#[error("instance <uuid='{0}'> belongs to another library")]
+	InstanceInAnotherLibrary(Uuid),
+	#[error("lock on instance <uuid='{0}'> expired or is held by another node")]
+	InvalidKey(Uuid),
+	#[error("invalid timestamp: {0}")]
+	InvalidTimestamp(String),
+}
+
+impl IntoResponse for Error {
+	fn into_response(self) -> Response {
+		let status = match &self {
+			Self::Database(_) | Self::MalformedData(_) => {
+				error!(?self, "Internal error;");
+				StatusCode::INTERNAL_SERVER_ERROR
+			}
+			Self::Unauthorized => StatusCode::UNAUTHORIZED,
+			Self::LibraryNotFound(_) | Self::InstanceNotFound(_) => StatusCode::NOT_FOUND,
+			Self::LibraryAlreadyExists(_)
+			| Self::InstanceInAnotherLibrary(_)
+			| Self::InvalidKey(_) => StatusCode::CONFLICT,
+			Self::InvalidTimestamp(_) => StatusCode::BAD_REQUEST,
+		};
+
+		(status, self.to_string()).into_response()
+	}
+}
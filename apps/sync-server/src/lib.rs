@@ -0,0 +1,86 @@
+//! A self-hostable server implementing the parts of the Spacedrive Cloud API that `sd-cloud-api`
+//! uses for cloud sync, so libraries can be synced across sites without their operations ever
+//! leaving infrastructure we control. Nodes use it by setting `SD_API_URL` to its address.
+
+use std::{collections::HashMap, sync::Arc};
+
+use axum::{
+	routing::{get, patch, post},
+	Router,
+};
+use tokio::sync::Mutex;
+
+mod api;
+mod auth;
+mod config;
+mod error;
+mod login;
+mod store;
+
+pub use auth::User;
+pub use config::Config;
+pub use error::Error;
+pub use store::Store;
+
+#[derive(Clone)]
+pub struct AppState {
+	store: Store,
+	users: Arc<[User]>,
+	public_url: Arc<str>,
+	device_logins: Arc<Mutex<HashMap<String, login::DeviceLogin>>>,
+}
+
+impl AppState {
+	pub fn new(store: Store, users: Vec<User>, public_url: impl Into<String>) -> Self {
+		Self {
+			store,
+			users: users.into(),
+			public_url: public_url.into().trim_end_matches('/').into(),
+			device_logins: Default::default(),
+		}
+	}
+
+	fn user_by_token(&self, token: &str) -> Option<&User> {
+		self.users.iter().find(|user| user.has_token(token))
+	}
+}
+
+pub fn router(state: AppState) -> Router {
+	Router::new()
+		.route("/health", get(|| async { "OK" }))
+		.route("/login/device/code", post(login::device_code))
+		.route(
+			"/login/device",
+			get(login::device_page).post(login::approve_device),
+		)
+		.route("/login/oauth/access_token", post(login::access_token))
+		.route("/api/v1/user/me", get(api::me))
+		.route("/api/v1/libraries", get(api::list_libraries))
+		.route(
+			"/api/v1/libraries/:library_id",
+			get(api::get_library)
+				.post(api::create_library)
+				.patch(api::update_library),
+		)
+		.route(
+			"/api/v1/libraries/:library_id/:instance_uuid",
+			patch(api::update_instance),
+		)
+		.route(
+			"/api/v1/libraries/:library_id/instances/:instance_uuid",
+			post(api::join_library),
+		)
+		.route(
+			"/api/v1/libraries/:library_id/messageCollections/get",
+			post(api::get_message_collections),
+		)
+		.route(
+			"/api/v1/libraries/:library_id/messageCollections/requestAdd",
+			post(api::request_add),
+		)
+		.route(
+			"/api/v1/libraries/:library_id/messageCollections/doAdd",
+			post(api::do_add),
+		)
+		.with_state(state)
+}
@@ -0,0 +1,170 @@
+//! The OAuth device authorization flow nodes use to log in (`/login/device/code` and
+//! `/login/oauth/access_token`), with a bare-bones page where users approve a node by entering
+//! their token.
+
+use sd_cloud_api::auth::{OAuthToken, DEVICE_CODE_URN};
+
+use std::time::{Duration, Instant};
+
+use axum::{
+	extract::{Query, State},
+	http::StatusCode,
+	response::{Html, IntoResponse, Response},
+	Form, Json,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use uuid::Uuid;
+
+use crate::AppState;
+
+/// How long users have to approve a node after it started logging in
+const DEVICE_CODE_LIFETIME: Duration = Duration::from_secs(10 * 60);
+
+pub struct DeviceLogin {
+	user_code: String,
+	// Email of the user who approved the node, if they did
+	approved_by: Option<String>,
+	expires_at: Instant,
+}
+
+#[derive(Serialize)]
+pub struct DeviceCodeResponse {
+	device_code: String,
+	user_code: String,
+	verification_url: String,
+	verification_uri_complete: String,
+}
+
+/// Nodes also send their `client_id`, which we don't need as we're the only client there is
+pub async fn device_code(State(state): State<AppState>) -> Json<DeviceCodeResponse> {
+	let device_code = Uuid::new_v4().to_string();
+	let user_code = Uuid::new_v4().simple().to_string()[..8].to_uppercase();
+
+	{
+		let mut device_logins = state.device_logins.lock().await;
+		device_logins.retain(|_, login| login.expires_at > Instant::now());
+		device_logins.insert(
+			device_code.clone(),
+			DeviceLogin {
+				user_code: user_code.clone(),
+				approved_by: None,
+				expires_at: Instant::now() + DEVICE_CODE_LIFETIME,
+			},
+		);
+	}
+
+	let verification_url = format!("{}/login/device", state.public_url);
+
+	Json(DeviceCodeResponse {
+		device_code,
+		verification_uri_complete: format!("{verification_url}?user_code={user_code}"),
+		verification_url,
+		user_code,
+	})
+}
+
+#[derive(Deserialize)]
+pub struct DevicePageQuery {
+	user_code: Option<String>,
+}
+
+pub async fn device_page(Query(query): Query<DevicePageQuery>) -> Html<String> {
+	// User codes are alphanumeric, dropping anything else keeps the page safe to render
+	let user_code = query
+		.user_code
+		.unwrap_or_default()
+		.chars()
+		.filter(char::is_ascii_alphanumeric)
+		.collect::<String>();
+
+	Html(format!(
+		r#"<!DOCTYPE html>
+<html>
+	<head><title>Spacedrive Sync Server</title></head>
+	<body>
+		<h1>Log in a Spacedrive node</h1>
+		<form method="post">
+			<p><label>Code <input name="user_code" value="{user_code}" required></label></p>
+			<p><label>Token <input name="token" type="password" required></label></p>
+			<p><button type="submit">Approve</button></p>
+		</form>
+	</body>
+</html>"#
+	))
+}
+
+#[derive(Deserialize)]
+pub struct ApproveDeviceForm {
+	user_code: String,
+	token: String,
+}
+
+pub async fn approve_device(
+	State(state): State<AppState>,
+	Form(form): Form<ApproveDeviceForm>,
+) -> Response {
+	let Some(user) = state.user_by_token(&form.token) else {
+		return (StatusCode::UNAUTHORIZED, Html("Invalid token")).into_response();
+	};
+
+	let user_code = form.user_code.trim().to_uppercase();
+
+	let mut device_logins = state.device_logins.lock().await;
+	let Some(login) = device_logins
+		.values_mut()
+		.find(|login| login.user_code == user_code && login.expires_at > Instant::now())
+	else {
+		return (StatusCode::NOT_FOUND, Html("Invalid or expired code")).into_response();
+	};
+
+	login.approved_by = Some(user.email.clone());
+
+	Html("Your node is now logged in, you can close this page").into_response()
+}
+
+#[derive(Deserialize)]
+pub struct AccessTokenRequest {
+	grant_type: String,
+	device_code: String,
+}
+
+pub async fn access_token(
+	State(state): State<AppState>,
+	Form(request): Form<AccessTokenRequest>,
+) -> Response {
+	let oauth_error = |error: &str| (StatusCode::BAD_REQUEST, Json(json!({ "error": error })));
+
+	if request.grant_type != DEVICE_CODE_URN {
+		return oauth_error("unsupported_grant_type").into_response();
+	}
+
+	let mut device_logins = state.device_logins.lock().await;
+	let Some(login) = device_logins.get(&request.device_code) else {
+		return oauth_error("invalid_grant").into_response();
+	};
+
+	if login.expires_at <= Instant::now() {
+		device_logins.remove(&request.device_code);
+		return oauth_error("expired_token").into_response();
+	}
+
+	let Some(user) = login
+		.approved_by
+		.as_deref()
+		.and_then(|email| state.users.iter().find(|user| user.email == email))
+	else {
+		return oauth_error("authorization_pending").into_response();
+	};
+
+	device_logins.remove(&request.device_code);
+
+	// Tokens are configured by the administrator and never expire, so there is nothing to refresh
+	Json(OAuthToken {
+		access_token: user.token().to_string(),
+		refresh_token: String::new(),
+		token_type: "Bearer".to_string(),
+		expires_in: i32::MAX,
+	})
+	.into_response()
+}
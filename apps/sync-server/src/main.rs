@@ -0,0 +1,43 @@
+use std::net::SocketAddr;
+
+use sd_sync_server::{router, AppState, Config, Store};
+use tracing::{info, warn};
+use tracing_subscriber::EnvFilter;
+
+#[tokio::main]
+async fn main() {
+	tracing_subscriber::fmt()
+		.with_env_filter(EnvFilter::from_default_env())
+		.init();
+
+	let config = Config::from_env();
+
+	if config.users.is_empty() {
+		warn!(
+			"The 'SD_SYNC_USERS' environment variable is not set, nobody will be able to log in!"
+		);
+		warn!(
+			"Provide your users in the following format 'SD_SYNC_USERS=email:token,email2:token2'"
+		);
+	}
+
+	let store = match Store::connect(&config.database_url).await {
+		Ok(store) => store,
+		Err(e) => panic!("Failed to open database: {e}"),
+	};
+
+	let app = router(AppState::new(store, config.users, config.public_url));
+
+	let mut addr = "[::]:8080".parse::<SocketAddr>().unwrap(); // This listens on IPv6 and IPv4
+	addr.set_port(config.port);
+	info!("Listening on http://localhost:{}", config.port);
+	axum::Server::bind(&addr)
+		.serve(app.into_make_service())
+		.with_graceful_shutdown(async {
+			tokio::signal::ctrl_c()
+				.await
+				.expect("Failed to listen for Ctrl+C");
+		})
+		.await
+		.expect("Error with HTTP server!");
+}
@@ -0,0 +1,451 @@
+use sd_cloud_api::{
+	library::message_collections::{do_add, request_add::RequestAdd},
+	Instance, Library, MessageCollection,
+};
+use sd_p2p::RemoteIdentity;
+
+use std::{
+	collections::HashMap,
+	str::FromStr,
+	time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use sqlx::{
+	any::{install_default_drivers, AnyPoolOptions, AnyRow},
+	AnyPool, Row,
+};
+use uuid::Uuid;
+
+use crate::Error;
+
+/// How long a node has to upload its operations after `requestAdd` handed it a key
+const LOCK_DURATION: Duration = Duration::from_secs(30);
+
+/// Plain SQL understood by both SQLite and Postgres, timestamps are `NTP64` values stored the same
+/// way core stores them, as `i64`
+const SCHEMA: &[&str] = &[
+	"CREATE TABLE IF NOT EXISTS library (
+		id TEXT PRIMARY KEY,
+		name TEXT NOT NULL,
+		owner_id TEXT NOT NULL
+	)",
+	"CREATE INDEX IF NOT EXISTS library_owner_id_idx ON library (owner_id)",
+	"CREATE TABLE IF NOT EXISTS instance (
+		uuid TEXT PRIMARY KEY,
+		library_id TEXT NOT NULL REFERENCES library (id) ON DELETE CASCADE,
+		identity TEXT NOT NULL,
+		node_id TEXT NOT NULL,
+		node_remote_identity TEXT NOT NULL,
+		metadata TEXT NOT NULL
+	)",
+	"CREATE INDEX IF NOT EXISTS instance_library_id_idx ON instance (library_id)",
+	"CREATE TABLE IF NOT EXISTS message_collection (
+		instance_uuid TEXT NOT NULL REFERENCES instance (uuid) ON DELETE CASCADE,
+		start_time BIGINT NOT NULL,
+		end_time BIGINT NOT NULL,
+		contents TEXT NOT NULL,
+		ops_count BIGINT NOT NULL,
+		PRIMARY KEY (instance_uuid, start_time)
+	)",
+	"CREATE INDEX IF NOT EXISTS message_collection_end_time_idx
+		ON message_collection (instance_uuid, end_time)",
+	"CREATE TABLE IF NOT EXISTS instance_lock (
+		instance_uuid TEXT PRIMARY KEY REFERENCES instance (uuid) ON DELETE CASCADE,
+		lock_key TEXT NOT NULL,
+		expires_at BIGINT NOT NULL
+	)",
+];
+
+pub struct NewInstance {
+	pub uuid: Uuid,
+	pub identity: RemoteIdentity,
+	pub node_id: Uuid,
+	pub node_remote_identity: RemoteIdentity,
+	pub metadata: HashMap<String, String>,
+}
+
+#[derive(Clone)]
+pub struct Store {
+	pool: AnyPool,
+}
+
+impl Store {
+	pub async fn connect(database_url: &str) -> Result<Self, Error> {
+		install_default_drivers();
+
+		let pool = AnyPoolOptions::new().connect(database_url).await?;
+
+		for statement in SCHEMA {
+			sqlx::query(statement).execute(&pool).await?;
+		}
+
+		Ok(Self { pool })
+	}
+
+	/// The library with all of its instances, its owner being the only one allowed to access it
+	pub async fn library(&self, id: Uuid) -> Result<Option<Library>, Error> {
+		let Some(row) = sqlx::query("SELECT id, name, owner_id FROM library WHERE id = $1")
+			.bind(id.to_string())
+			.fetch_optional(&self.pool)
+			.await?
+		else {
+			return Ok(None);
+		};
+
+		Ok(Some(Library {
+			id: row.try_get("id")?,
+			uuid: id,
+			name: row.try_get("name")?,
+			instances: self.instances(id).await?,
+			owner_id: row.try_get("owner_id")?,
+		}))
+	}
+
+	pub async fn library_owner(&self, id: Uuid) -> Result<Option<String>, Error> {
+		sqlx::query("SELECT owner_id FROM library WHERE id = $1")
+			.bind(id.to_string())
+			.fetch_optional(&self.pool)
+			.await?
+			.map(|row| row.try_get("owner_id"))
+			.transpose()
+			.map_err(Into::into)
+	}
+
+	pub async fn libraries(&self, owner_id: &str) -> Result<Vec<Library>, Error> {
+		let rows = sqlx::query("SELECT id FROM library WHERE owner_id = $1 ORDER BY name")
+			.bind(owner_id)
+			.fetch_all(&self.pool)
+			.await?;
+
+		let mut libraries = Vec::with_capacity(rows.len());
+		for row in rows {
+			if let Some(library) = self.library(parse(&row, "id")?).await? {
+				libraries.push(library);
+			}
+		}
+
+		Ok(libraries)
+	}
+
+	pub async fn create_library(
+		&self,
+		id: Uuid,
+		name: &str,
+		owner_id: &str,
+		instance: NewInstance,
+	) -> Result<(), Error> {
+		let mut tx = self.pool.begin().await?;
+
+		let created = sqlx::query(
+			"INSERT INTO library (id, name, owner_id) VALUES ($1, $2, $3)
+			ON CONFLICT (id) DO NOTHING",
+		)
+		.bind(id.to_string())
+		.bind(name)
+		.bind(owner_id)
+		.execute(&mut *tx)
+		.await?
+		.rows_affected()
+			== 1;
+
+		if !created {
+			return Err(Error::LibraryAlreadyExists(id));
+		}
+
+		insert_instance(&mut tx, id, &instance).await?;
+
+		tx.commit().await.map_err(Into::into)
+	}
+
+	pub async fn rename_library(&self, id: Uuid, name: &str) -> Result<(), Error> {
+		sqlx::query("UPDATE library SET name = $1 WHERE id = $2")
+			.bind(name)
+			.bind(id.to_string())
+			.execute(&self.pool)
+			.await?;
+
+		Ok(())
+	}
+
+	pub async fn instances(&self, library_id: Uuid) -> Result<Vec<Instance>, Error> {
+		sqlx::query(
+			"SELECT uuid, identity, node_id, node_remote_identity, metadata
+			FROM instance WHERE library_id = $1 ORDER BY uuid",
+		)
+		.bind(library_id.to_string())
+		.fetch_all(&self.pool)
+		.await?
+		.iter()
+		.map(|row| {
+			Ok(Instance {
+				id: row.try_get("uuid")?,
+				uuid: parse(row, "uuid")?,
+				identity: parse(row, "identity")?,
+				node_id: parse(row, "node_id")?,
+				node_remote_identity: row.try_get("node_remote_identity")?,
+				metadata: serde_json::from_str(&row.try_get::<String, _>("metadata")?)
+					.map_err(|_| Error::MalformedData("metadata"))?,
+			})
+		})
+		.collect()
+	}
+
+	/// Adds the instance to the library, or updates it if it has already joined before
+	pub async fn upsert_instance(
+		&self,
+		library_id: Uuid,
+		instance: NewInstance,
+	) -> Result<(), Error> {
+		let mut tx = self.pool.begin().await?;
+		insert_instance(&mut tx, library_id, &instance).await?;
+		tx.commit().await.map_err(Into::into)
+	}
+
+	pub async fn update_instance(
+		&self,
+		library_id: Uuid,
+		uuid: Uuid,
+		node_id: Option<Uuid>,
+		node_remote_identity: Option<RemoteIdentity>,
+		metadata: Option<HashMap<String, String>>,
+	) -> Result<(), Error> {
+		let mut tx = self.pool.begin().await?;
+
+		let exists = sqlx::query("SELECT uuid FROM instance WHERE uuid = $1 AND library_id = $2")
+			.bind(uuid.to_string())
+			.bind(library_id.to_string())
+			.fetch_optional(&mut *tx)
+			.await?
+			.is_some();
+
+		if !exists {
+			return Err(Error::InstanceNotFound(uuid));
+		}
+
+		// One statement per column, as the `Any` driver binds `NULL`s as integers, which Postgres
+		// won't coalesce with text columns
+		for (column, value) in [
+			("node_id", node_id.map(|node_id| node_id.to_string())),
+			(
+				"node_remote_identity",
+				node_remote_identity.map(|identity| identity.to_string()),
+			),
+			(
+				"metadata",
+				metadata.map(|metadata| serialize_metadata(&metadata)),
+			),
+		] {
+			let Some(value) = value else {
+				continue;
+			};
+
+			let statement = format!("UPDATE instance SET {column} = $1 WHERE uuid = $2");
+			sqlx::query(&statement)
+				.bind(value)
+				.bind(uuid.to_string())
+				.execute(&mut *tx)
+				.await?;
+		}
+
+		tx.commit().await.map_err(Into::into)
+	}
+
+	/// Collections of every instance of the library but `this_instance_uuid`, which end after the
+	/// timestamp we were given for their instance, or all of them if we weren't given one
+	pub async fn message_collections(
+		&self,
+		library_id: Uuid,
+		this_instance_uuid: Uuid,
+		from_times: &HashMap<Uuid, i64>,
+		limit: usize,
+	) -> Result<Vec<MessageCollection>, Error> {
+		let mut collections = vec![];
+
+		for instance in self.instances(library_id).await? {
+			if instance.uuid == this_instance_uuid || collections.len() >= limit {
+				continue;
+			}
+
+			let rows = sqlx::query(
+				"SELECT start_time, end_time, contents FROM message_collection
+				WHERE instance_uuid = $1 AND end_time > $2
+				ORDER BY start_time LIMIT $3",
+			)
+			.bind(instance.uuid.to_string())
+			.bind(from_times.get(&instance.uuid).copied().unwrap_or_default())
+			.bind((limit - collections.len()) as i64)
+			.fetch_all(&self.pool)
+			.await?;
+
+			for row in rows {
+				collections.push(MessageCollection {
+					instance_uuid: instance.uuid,
+					start_time: (row.try_get::<i64, _>("start_time")? as u64).to_string(),
+					end_time: (row.try_get::<i64, _>("end_time")? as u64).to_string(),
+					contents: row.try_get("contents")?,
+				});
+			}
+		}
+
+		Ok(collections)
+	}
+
+	/// Hands out a key for each instance of the library that isn't already locked by another
+	/// node, alongside the end of the last collection we have for it
+	pub async fn lock_instances(
+		&self,
+		library_id: Uuid,
+		instances: impl IntoIterator<Item = Uuid>,
+	) -> Result<Vec<RequestAdd>, Error> {
+		let now = now();
+		let mut locks = vec![];
+
+		for instance_uuid in instances {
+			let key = Uuid::new_v4().to_string();
+
+			let locked = sqlx::query(
+				"INSERT INTO instance_lock (instance_uuid, lock_key, expires_at)
+				SELECT uuid, $1, $2 FROM instance WHERE uuid = $3 AND library_id = $4
+				ON CONFLICT (instance_uuid) DO UPDATE
+				SET lock_key = excluded.lock_key, expires_at = excluded.expires_at
+				WHERE instance_lock.expires_at < $5",
+			)
+			.bind(&key)
+			.bind(now + LOCK_DURATION.as_secs() as i64)
+			.bind(instance_uuid.to_string())
+			.bind(library_id.to_string())
+			.bind(now)
+			.execute(&self.pool)
+			.await?
+			.rows_affected() == 1;
+
+			if !locked {
+				continue;
+			}
+
+			// `MAX` would be simpler, but the `Any` driver can't decode the `NULL` it returns when
+			// there are no collections yet
+			let from_time = sqlx::query(
+				"SELECT end_time FROM message_collection WHERE instance_uuid = $1
+				ORDER BY end_time DESC LIMIT 1",
+			)
+			.bind(instance_uuid.to_string())
+			.fetch_optional(&self.pool)
+			.await?
+			.map(|row| row.try_get::<i64, _>("end_time"))
+			.transpose()?;
+
+			locks.push(RequestAdd {
+				instance_uuid,
+				from_time: from_time.map(|time| (time as u64).to_string()),
+				key,
+			});
+		}
+
+		Ok(locks)
+	}
+
+	/// Stores the collections, releasing the locks they were uploaded with. Nothing is stored if
+	/// any of the keys is no longer valid.
+	pub async fn add_message_collections(
+		&self,
+		library_id: Uuid,
+		collections: Vec<do_add::Input>,
+	) -> Result<(), Error> {
+		let now = now();
+		let mut tx = self.pool.begin().await?;
+
+		for collection in collections {
+			let unlocked = sqlx::query(
+				"DELETE FROM instance_lock
+				WHERE instance_uuid = $1 AND lock_key = $2 AND expires_at >= $3
+				AND instance_uuid IN (SELECT uuid FROM instance WHERE library_id = $4)",
+			)
+			.bind(collection.uuid.to_string())
+			.bind(&collection.key)
+			.bind(now)
+			.bind(library_id.to_string())
+			.execute(&mut *tx)
+			.await?
+			.rows_affected() == 1;
+
+			if !unlocked {
+				return Err(Error::InvalidKey(collection.uuid));
+			}
+
+			sqlx::query(
+				"INSERT INTO message_collection
+				(instance_uuid, start_time, end_time, contents, ops_count)
+				VALUES ($1, $2, $3, $4, $5)",
+			)
+			.bind(collection.uuid.to_string())
+			.bind(parse_timestamp(&collection.start_time)?)
+			.bind(parse_timestamp(&collection.end_time)?)
+			.bind(collection.contents)
+			.bind(collection.ops_count as i64)
+			.execute(&mut *tx)
+			.await?;
+		}
+
+		tx.commit().await.map_err(Into::into)
+	}
+}
+
+async fn insert_instance(
+	tx: &mut sqlx::Transaction<'_, sqlx::Any>,
+	library_id: Uuid,
+	instance: &NewInstance,
+) -> Result<(), Error> {
+	let inserted = sqlx::query(
+		"INSERT INTO instance
+		(uuid, library_id, identity, node_id, node_remote_identity, metadata)
+		VALUES ($1, $2, $3, $4, $5, $6)
+		ON CONFLICT (uuid) DO UPDATE SET
+			identity = excluded.identity,
+			node_id = excluded.node_id,
+			node_remote_identity = excluded.node_remote_identity,
+			metadata = excluded.metadata
+		WHERE instance.library_id = excluded.library_id",
+	)
+	.bind(instance.uuid.to_string())
+	.bind(library_id.to_string())
+	.bind(instance.identity.to_string())
+	.bind(instance.node_id.to_string())
+	.bind(instance.node_remote_identity.to_string())
+	.bind(serialize_metadata(&instance.metadata))
+	.execute(&mut **tx)
+	.await?
+	.rows_affected()
+		== 1;
+
+	if inserted {
+		Ok(())
+	} else {
+		Err(Error::InstanceInAnotherLibrary(instance.uuid))
+	}
+}
+
+fn parse<T: FromStr>(row: &AnyRow, column: &'static str) -> Result<T, Error> {
+	row.try_get::<String, _>(column)?
+		.parse()
+		.map_err(|_| Error::MalformedData(column))
+}
+
+/// `NTP64` values are sent as strings as they don't fit in a JavaScript number
+pub fn parse_timestamp(timestamp: &str) -> Result<i64, Error> {
+	timestamp
+		.parse::<u64>()
+		.map(|timestamp| timestamp as i64)
+		.map_err(|_| Error::InvalidTimestamp(timestamp.to_string()))
+}
+
+fn serialize_metadata(metadata: &HashMap<String, String>) -> String {
+	serde_json::to_string(metadata).expect("string maps always serialize")
+}
+
+fn now() -> i64 {
+	SystemTime::now()
+		.duration_since(UNIX_EPOCH)
+		.expect("system clock is before the unix epoch")
+		.as_secs() as i64
+}
@@ -0,0 +1,312 @@
+use sd_cloud_api::{
+	auth::{OAuthToken, DEVICE_CODE_URN},
+	library::{
+		self,
+		message_collections::{self, do_add, get::InstanceTimestamp},
+	},
+	RequestConfig,
+};
+use sd_p2p::Identity;
+use sd_sync_server::{router, AppState, Store, User};
+
+use std::{collections::HashMap, net::TcpListener};
+
+use serde_json::Value;
+use tempfile::TempDir;
+use uuid::Uuid;
+
+struct TestServer {
+	url: String,
+	_data_dir: TempDir,
+}
+
+impl TestServer {
+	async fn new() -> Self {
+		let data_dir = tempfile::tempdir().unwrap();
+		let store = Store::connect(&format!(
+			"sqlite://{}?mode=rwc",
+			data_dir.path().join("sync.db").display()
+		))
+		.await
+		.unwrap();
+
+		let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+		let url = format!("http://{}", listener.local_addr().unwrap());
+
+		let app = router(AppState::new(
+			store,
+			vec![
+				User::new("alice@example.com", "alice-token"),
+				User::new("bob@example.com", "bob-token"),
+			],
+			&url,
+		));
+
+		tokio::spawn(
+			axum::Server::from_tcp(listener)
+				.unwrap()
+				.serve(app.into_make_service()),
+		);
+
+		Self {
+			url,
+			_data_dir: data_dir,
+		}
+	}
+
+	fn config(&self, token: &str) -> RequestConfig {
+		RequestConfig {
+			client: reqwest::Client::new(),
+			api_url: self.url.clone(),
+			auth_token: Some(OAuthToken {
+				access_token: token.to_string(),
+				refresh_token: String::new(),
+				token_type: "Bearer".to_string(),
+				expires_in: i32::MAX,
+			}),
+		}
+	}
+}
+
+async fn create_library(server: &TestServer, library_id: Uuid, instance_uuid: Uuid) {
+	library::create(
+		server.config("alice-token"),
+		library_id,
+		"Photos",
+		instance_uuid,
+		Identity::new().to_remote_identity(),
+		Uuid::new_v4(),
+		Identity::new().to_remote_identity(),
+		&HashMap::new(),
+	)
+	.await
+	.unwrap();
+}
+
+#[tokio::test]
+async fn libraries_are_only_visible_to_their_owner() {
+	let server = TestServer::new().await;
+	let (library_id, first_instance, second_instance) =
+		(Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+
+	create_library(&server, library_id, first_instance).await;
+
+	let instances = library::join(
+		server.config("alice-token"),
+		library_id,
+		second_instance,
+		Identity::new().to_remote_identity(),
+		Uuid::new_v4(),
+		Identity::new().to_remote_identity(),
+		HashMap::from([("name".to_string(), "Laptop".to_string())]),
+	)
+	.await
+	.unwrap();
+	assert_eq!(instances.len(), 2);
+
+	let library = library::get(server.config("alice-token"), library_id)
+		.await
+		.unwrap()
+		.unwrap();
+	assert_eq!(library.name, "Photos");
+	assert_eq!(library.owner_id, "alice@example.com");
+	assert!(library
+		.instances
+		.iter()
+		.any(|instance| instance.uuid == second_instance
+			&& instance.metadata.get("name").map(String::as_str) == Some("Laptop")));
+
+	assert_eq!(
+		library::list(server.config("alice-token"))
+			.await
+			.unwrap()
+			.len(),
+		1
+	);
+	assert!(library::get(server.config("bob-token"), library_id)
+		.await
+		.unwrap()
+		.is_none());
+	assert!(library::list(server.config("bob-token"))
+		.await
+		.unwrap()
+		.is_empty());
+	assert!(library::get(server.config("mallory-token"), library_id)
+		.await
+		.is_err());
+}
+
+#[tokio::test]
+async fn message_collections_round_trip() {
+	let server = TestServer::new().await;
+	let (library_id, first_instance, second_instance) =
+		(Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+
+	create_library(&server, library_id, first_instance).await;
+	library::join(
+		server.config("alice-token"),
+		library_id,
+		second_instance,
+		Identity::new().to_remote_identity(),
+		Uuid::new_v4(),
+		Identity::new().to_remote_identity(),
+		HashMap::new(),
+	)
+	.await
+	.unwrap();
+
+	// Unknown instances don't get a lock
+	let locks = message_collections::request_add(
+		server.config("alice-token"),
+		library_id,
+		vec![first_instance, Uuid::new_v4()],
+	)
+	.await
+	.unwrap();
+	assert_eq!(locks.len(), 1);
+	assert_eq!(locks[0].instance_uuid, first_instance);
+	assert_eq!(locks[0].from_time, None);
+
+	// Nobody else can upload operations of the instance while it's locked
+	assert!(message_collections::request_add(
+		server.config("alice-token"),
+		library_id,
+		vec![first_instance],
+	)
+	.await
+	.unwrap()
+	.is_empty());
+
+	assert!(message_collections::do_add(
+		server.config("alice-token"),
+		library_id,
+		vec![do_add::Input {
+			uuid: first_instance,
+			key: "not-the-key".to_string(),
+			start_time: "1".to_string(),
+			end_time: "2".to_string(),
+			contents: "bogus".to_string(),
+			ops_count: 1,
+		}],
+	)
+	.await
+	.is_err());
+
+	message_collections::do_add(
+		server.config("alice-token"),
+		library_id,
+		vec![do_add::Input {
+			uuid: first_instance,
+			key: locks[0].key.clone(),
+			start_time: "100".to_string(),
+			end_time: "200".to_string(),
+			contents: "operations".to_string(),
+			ops_count: 2,
+		}],
+	)
+	.await
+	.unwrap();
+
+	let locks = message_collections::request_add(
+		server.config("alice-token"),
+		library_id,
+		vec![first_instance],
+	)
+	.await
+	.unwrap();
+	assert_eq!(locks[0].from_time.as_deref(), Some("200"));
+
+	// Instances don't get their own collections back
+	assert!(message_collections::get(
+		server.config("alice-token"),
+		library_id,
+		first_instance,
+		vec![],
+	)
+	.await
+	.unwrap()
+	.is_empty());
+
+	let collections = message_collections::get(
+		server.config("alice-token"),
+		library_id,
+		second_instance,
+		vec![],
+	)
+	.await
+	.unwrap();
+	assert_eq!(collections.len(), 1);
+	assert_eq!(collections[0].instance_uuid, first_instance);
+	assert_eq!(collections[0].start_time, "100");
+	assert_eq!(collections[0].end_time, "200");
+	assert_eq!(collections[0].contents, "operations");
+
+	assert!(message_collections::get(
+		server.config("alice-token"),
+		library_id,
+		second_instance,
+		vec![InstanceTimestamp {
+			instance_uuid: first_instance,
+			from_time: "200".to_string(),
+		}],
+	)
+	.await
+	.unwrap()
+	.is_empty());
+}
+
+#[tokio::test]
+async fn nodes_log_in_with_the_device_flow() {
+	let server = TestServer::new().await;
+	let client = reqwest::Client::new();
+
+	let codes = client
+		.post(format!("{}/login/device/code", server.url))
+		.form(&[("client_id", "node")])
+		.send()
+		.await
+		.unwrap()
+		.json::<Value>()
+		.await
+		.unwrap();
+	let device_code = codes["device_code"].as_str().unwrap();
+	let user_code = codes["user_code"].as_str().unwrap();
+
+	let request_token = || {
+		client
+			.post(format!("{}/login/oauth/access_token", server.url))
+			.form(&[
+				("grant_type", DEVICE_CODE_URN),
+				("device_code", device_code),
+				("client_id", "node"),
+			])
+			.send()
+	};
+	let approve = |token: &str| {
+		client
+			.post(format!("{}/login/device", server.url))
+			.form(&[("user_code", user_code), ("token", token)])
+			.send()
+	};
+
+	let pending = request_token().await.unwrap();
+	assert_eq!(pending.status(), 400);
+	assert_eq!(
+		pending.json::<Value>().await.unwrap()["error"],
+		"authorization_pending"
+	);
+
+	assert_eq!(approve("mallory-token").await.unwrap().status(), 401);
+	assert!(approve("alice-token").await.unwrap().status().is_success());
+
+	let token = request_token()
+		.await
+		.unwrap()
+		.json::<OAuthToken>()
+		.await
+		.unwrap();
+	assert_eq!(token.to_header(), "Bearer alice-token");
+
+	// Device codes can only be redeemed once
+	assert_eq!(request_token().await.unwrap().status(), 400);
+}
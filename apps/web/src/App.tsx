@@ -42,12 +42,22 @@ const spacedriveURL = (() => {
 
 const platform: Platform = {
 	platform: 'web',
-	getThumbnailUrlByThumbKey: (keyParts) =>
-		`${spacedriveURL}/thumbnail/${keyParts.map((i) => encodeURIComponent(i)).join('/')}.webp`,
+	getThumbnailUrlByThumbKey: (keyParts, size) =>
+		`${spacedriveURL}/thumbnail/${keyParts.map((i) => encodeURIComponent(i)).join('/')}.webp${
+			size ? `?size=${size}` : ''
+		}`,
 	getFileUrl: (libraryId, locationLocalId, filePathId) =>
 		`${spacedriveURL}/file/${encodeURIComponent(libraryId)}/${encodeURIComponent(
 			locationLocalId
 		)}/${encodeURIComponent(filePathId)}`,
+	getTranscodeUrl: (libraryId, locationLocalId, filePathId) =>
+		`${spacedriveURL}/transcode/${encodeURIComponent(libraryId)}/${encodeURIComponent(
+			locationLocalId
+		)}/${encodeURIComponent(filePathId)}`,
+	getMediaTrackUrl: (libraryId, locationLocalId, filePathId, track) =>
+		`${spacedriveURL}/tracks/${encodeURIComponent(libraryId)}/${encodeURIComponent(
+			locationLocalId
+		)}/${encodeURIComponent(filePathId)}/${encodeURIComponent(track)}`,
 	getFileUrlByPath: (path) => `${spacedriveURL}/local-file-by-path/${encodeURIComponent(path)}`,
 	getRemoteRspcEndpoint: (remote_identity) => ({
 		url: `${spacedriveURL
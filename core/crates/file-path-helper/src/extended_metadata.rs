@@ -0,0 +1,351 @@
+//! Unix permissions, ownership and the extended attributes we index for each file path.
+
+use std::{collections::BTreeMap, fs::Metadata, io, path::Path};
+
+use base64::{engine::general_purpose, Engine};
+
+/// Extended attributes stored with the file path, these are the ones other tools keep tags and comments in.
+pub const INDEXED_XATTRS: &[&str] = &[
+	"user.xdg.tags",
+	"user.xdg.comment",
+	"com.apple.metadata:_kMDItemUserTags",
+];
+
+/// Values bigger than this are left out, so a misbehaving tool can't bloat the database.
+const MAX_XATTR_VALUE_SIZE: usize = 16 * 1024;
+
+/// Prefix of the values in `xattrs` which aren't valid UTF-8 and are stored base64 encoded instead.
+pub const XATTR_BASE64_PREFIX: &str = "base64:";
+
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct ExtendedMetadata {
+	pub permissions: Option<String>,
+	pub owner: Option<String>,
+	pub owner_group: Option<String>,
+	pub xattrs: Option<String>,
+}
+
+impl ExtendedMetadata {
+	/// Permissions and ownership come from `metadata`, which is the metadata of the link itself
+	/// for symbolic links indexed as aliases, same for the extended attributes read from `path`.
+	#[cfg(target_family = "unix")]
+	#[must_use]
+	pub fn from_path(path: impl AsRef<Path>, metadata: &Metadata) -> Self {
+		use std::os::unix::fs::MetadataExt;
+
+		Self {
+			permissions: Some(permissions_string(metadata.mode())),
+			owner: Some(unix::user_name(metadata.uid())),
+			owner_group: Some(unix::group_name(metadata.gid())),
+			xattrs: unix::read_xattrs(path.as_ref(), metadata.is_symlink()),
+		}
+	}
+
+	#[cfg(target_family = "windows")]
+	#[must_use]
+	pub fn from_path(path: impl AsRef<Path>, metadata: &Metadata) -> Self {
+		let _ = (path, metadata); // Windows has ACLs instead, which we don't index
+		Self::default()
+	}
+}
+
+/// Read the extended attributes we index from `path`, serialized for the `xattrs` column.
+#[must_use]
+pub fn read_xattrs(path: impl AsRef<Path>) -> Option<String> {
+	#[cfg(target_family = "unix")]
+	{
+		let path = path.as_ref();
+		unix::read_xattrs(path, path.is_symlink())
+	}
+
+	#[cfg(target_family = "windows")]
+	{
+		let _ = path;
+		None
+	}
+}
+
+/// Set the extended attribute `name` of `path` to `value`, or remove it if `value` is `None`.
+pub fn write_xattr(path: impl AsRef<Path>, name: &str, value: Option<&[u8]>) -> io::Result<()> {
+	#[cfg(target_family = "unix")]
+	{
+		let path = path.as_ref();
+		match value {
+			Some(value) => xattr::set(path, name, value),
+			// Removing an attribute that isn't there is an error
+			None if xattr::get(path, name)?.is_some() => xattr::remove(path, name),
+			None => Ok(()),
+		}
+	}
+
+	#[cfg(target_family = "windows")]
+	{
+		let _ = (path, name, value);
+		Err(io::Error::new(
+			io::ErrorKind::Unsupported,
+			"extended attributes aren't supported on Windows",
+		))
+	}
+}
+
+/// Format the permission bits of a unix `mode` like `ls -l` does, like "rwxr-xr-x" or "rwsr-xr-t".
+#[must_use]
+pub fn permissions_string(mode: u32) -> String {
+	const SET_UID: u32 = 0o4000;
+	const SET_GID: u32 = 0o2000;
+	const STICKY: u32 = 0o1000;
+
+	let special_bits = [SET_UID, SET_GID, STICKY];
+	let special_chars = [('s', 'S'), ('s', 'S'), ('t', 'T')];
+
+	let mut permissions = String::with_capacity(9);
+	for (class, (special_bit, (special_exec, special_no_exec))) in
+		special_bits.into_iter().zip(special_chars).enumerate()
+	{
+		let shift = 6 - class * 3;
+		let bits = (mode >> shift) & 0o7;
+
+		permissions.push(if bits & 0o4 == 0 { '-' } else { 'r' });
+		permissions.push(if bits & 0o2 == 0 { '-' } else { 'w' });
+		permissions.push(match (bits & 0o1 != 0, mode & special_bit != 0) {
+			(true, true) => special_exec,
+			(false, true) => special_no_exec,
+			(true, false) => 'x',
+			(false, false) => '-',
+		});
+	}
+
+	permissions
+}
+
+/// Serialize extended attributes for the `xattrs` column, values which aren't valid UTF-8 are base64 encoded.
+#[must_use]
+pub fn encode_xattrs(xattrs: impl IntoIterator<Item = (String, Vec<u8>)>) -> Option<String> {
+	let xattrs = xattrs
+		.into_iter()
+		.map(|(name, value)| {
+			let value = String::from_utf8(value).unwrap_or_else(|e| {
+				format!(
+					"{XATTR_BASE64_PREFIX}{}",
+					general_purpose::STANDARD.encode(e.as_bytes())
+				)
+			});
+			(name, value)
+		})
+		.collect::<BTreeMap<_, _>>();
+
+	if xattrs.is_empty() {
+		None
+	} else {
+		serde_json::to_string(&xattrs).ok()
+	}
+}
+
+/// Parse the `xattrs` column back into the raw value of each extended attribute.
+#[must_use]
+pub fn decode_xattrs(xattrs: &str) -> BTreeMap<String, Vec<u8>> {
+	serde_json::from_str::<BTreeMap<String, String>>(xattrs)
+		.unwrap_or_default()
+		.into_iter()
+		.filter_map(|(name, value)| decode_xattr_value(&value).map(|value| (name, value)))
+		.collect()
+}
+
+/// Decode a single value of the `xattrs` column, `None` if it claims to be base64 but isn't.
+#[must_use]
+pub fn decode_xattr_value(value: &str) -> Option<Vec<u8>> {
+	value.strip_prefix(XATTR_BASE64_PREFIX).map_or_else(
+		|| Some(value.as_bytes().to_vec()),
+		|encoded| general_purpose::STANDARD.decode(encoded).ok(),
+	)
+}
+
+#[cfg(target_family = "unix")]
+mod unix {
+	use super::{encode_xattrs, INDEXED_XATTRS, MAX_XATTR_VALUE_SIZE};
+
+	use std::{
+		collections::HashMap,
+		ffi::CStr,
+		mem,
+		path::Path,
+		ptr,
+		sync::{Mutex, OnceLock, PoisonError},
+	};
+
+	use libc::{gid_t, uid_t, ERANGE};
+	use tracing::trace;
+
+	/// Resolving names hits `/etc/passwd` or even the network (LDAP, NIS), and a location usually has few owners
+	static USER_NAMES: OnceLock<Mutex<HashMap<uid_t, String>>> = OnceLock::new();
+	static GROUP_NAMES: OnceLock<Mutex<HashMap<gid_t, String>>> = OnceLock::new();
+
+	pub fn user_name(uid: uid_t) -> String {
+		cached_name(&USER_NAMES, uid, lookup_user_name)
+	}
+
+	pub fn group_name(gid: gid_t) -> String {
+		cached_name(&GROUP_NAMES, gid, lookup_group_name)
+	}
+
+	fn cached_name(
+		cache: &OnceLock<Mutex<HashMap<u32, String>>>,
+		id: u32,
+		lookup: fn(u32) -> Option<String>,
+	) -> String {
+		cache
+			.get_or_init(Mutex::default)
+			.lock()
+			.unwrap_or_else(PoisonError::into_inner)
+			.entry(id)
+			.or_insert_with(|| lookup(id).unwrap_or_else(|| id.to_string()))
+			.clone()
+	}
+
+	fn lookup_user_name(uid: uid_t) -> Option<String> {
+		use libc::{getpwuid_r, passwd};
+
+		let mut buf = vec![0; 1024];
+		// SAFETY: `passwd` is a plain C struct of integers and pointers, for which all zeroes is a
+		// valid value, and it's only read after `getpwuid_r` filled it in
+		let mut passwd = unsafe { mem::zeroed::<passwd>() };
+		let mut result = ptr::null_mut::<passwd>();
+
+		loop {
+			// SAFETY: every pointer is to memory we own and outlives the call, and `buf.len()` is
+			// the actual length of `buf`, so `getpwuid_r` never writes past it
+			let r =
+				unsafe { getpwuid_r(uid, &mut passwd, buf.as_mut_ptr(), buf.len(), &mut result) };
+
+			if r != ERANGE {
+				break;
+			}
+
+			let new_size = buf.len().checked_mul(2)?;
+			buf.resize(new_size, 0);
+		}
+
+		if result.is_null() || passwd.pw_name.is_null() {
+			return None;
+		}
+
+		// SAFETY: on success `pw_name` is a non null pointer, as checked above, to a nul
+		// terminated string that `getpwuid_r` wrote into `buf`, which is still alive
+		Some(
+			unsafe { CStr::from_ptr(passwd.pw_name) }
+				.to_string_lossy()
+				.into_owned(),
+		)
+	}
+
+	fn lookup_group_name(gid: gid_t) -> Option<String> {
+		use libc::{getgrgid_r, group};
+
+		let mut buf = vec![0; 1024];
+		// SAFETY: `group` is a plain C struct of integers and pointers, for which all zeroes is a
+		// valid value, and it's only read after `getgrgid_r` filled it in
+		let mut group = unsafe { mem::zeroed::<group>() };
+		let mut result = ptr::null_mut::<group>();
+
+		loop {
+			// SAFETY: every pointer is to memory we own and outlives the call, and `buf.len()` is
+			// the actual length of `buf`, so `getgrgid_r` never writes past it
+			let r =
+				unsafe { getgrgid_r(gid, &mut group, buf.as_mut_ptr(), buf.len(), &mut result) };
+
+			if r != ERANGE {
+				break;
+			}
+
+			let new_size = buf.len().checked_mul(2)?;
+			buf.resize(new_size, 0);
+		}
+
+		if result.is_null() || group.gr_name.is_null() {
+			return None;
+		}
+
+		// SAFETY: on success `gr_name` is a non null pointer, as checked above, to a nul
+		// terminated string that `getgrgid_r` wrote into `buf`, which is still alive
+		Some(
+			unsafe { CStr::from_ptr(group.gr_name) }
+				.to_string_lossy()
+				.into_owned(),
+		)
+	}
+
+	pub fn read_xattrs(path: &Path, is_symlink: bool) -> Option<String> {
+		encode_xattrs(INDEXED_XATTRS.iter().filter_map(|&name| {
+			// Attributes of a link indexed as an alias are its own, not the ones of what it points to
+			let value = if is_symlink {
+				xattr::get(path, name)
+			} else {
+				xattr::get_deref(path, name)
+			};
+
+			match value {
+				Ok(Some(value)) if value.len() <= MAX_XATTR_VALUE_SIZE => {
+					Some((name.to_string(), value))
+				}
+				Ok(Some(value)) => {
+					trace!(
+						"Skipping extended attribute '{name}' of {} with {} bytes",
+						path.display(),
+						value.len()
+					);
+					None
+				}
+				Ok(None) => None,
+				// Many filesystems don't support extended attributes at all, that isn't worth failing over
+				Err(e) => {
+					trace!(
+						"Failed to read extended attribute '{name}' of {}: {e:#?}",
+						path.display()
+					);
+					None
+				}
+			}
+		}))
+	}
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn formats_permissions_like_ls() {
+		assert_eq!(permissions_string(0o755), "rwxr-xr-x");
+		assert_eq!(permissions_string(0o100_644), "rw-r--r--");
+		assert_eq!(permissions_string(0o777), "rwxrwxrwx");
+		assert_eq!(permissions_string(0o4755), "rwsr-xr-x");
+		assert_eq!(permissions_string(0o2644), "rw-r-Sr--");
+		assert_eq!(permissions_string(0o1777), "rwxrwxrwt");
+		assert_eq!(permissions_string(0o1776), "rwxrwxrwT");
+	}
+
+	#[test]
+	fn xattrs_round_trip() {
+		let encoded = encode_xattrs([
+			("user.xdg.tags".to_string(), b"red,work".to_vec()),
+			(
+				"com.apple.metadata:_kMDItemUserTags".to_string(),
+				vec![0xff, 0x00, 0x01],
+			),
+		])
+		.unwrap();
+
+		assert!(encoded.contains(r#""user.xdg.tags":"red,work""#));
+		assert!(encoded.contains(XATTR_BASE64_PREFIX));
+
+		let decoded = decode_xattrs(&encoded);
+		assert_eq!(decoded["user.xdg.tags"], b"red,work");
+		assert_eq!(
+			decoded["com.apple.metadata:_kMDItemUserTags"],
+			[0xff, 0x00, 0x01]
+		);
+
+		assert_eq!(encode_xattrs([]), None);
+	}
+}
@@ -1,5 +1,6 @@
 use sd_core_prisma_helpers::{
-	file_path_for_file_identifier, file_path_for_media_processor, file_path_for_object_validator,
+	file_path_for_dedup, file_path_for_desktop_tags, file_path_for_file_identifier,
+	file_path_for_media_processor, file_path_for_object_validator, file_path_for_trash,
 	file_path_to_full_path, file_path_to_handle_custom_uri, file_path_to_handle_p2p_serve_file,
 	file_path_to_isolate, file_path_to_isolate_with_id, file_path_to_isolate_with_pub_id,
 	file_path_walker, file_path_with_object,
@@ -502,6 +503,8 @@ mod macros {
 
 impl_from_db!(
 	file_path,
+	file_path_for_dedup,
+	file_path_for_trash,
 	file_path_to_isolate,
 	file_path_to_isolate_with_pub_id,
 	file_path_walker,
@@ -512,6 +515,7 @@ impl_from_db!(
 impl_from_db_without_location_id!(
 	file_path_for_file_identifier,
 	file_path_to_full_path,
+	file_path_for_desktop_tags,
 	file_path_for_media_processor,
 	file_path_for_object_validator,
 	file_path_to_handle_custom_uri,
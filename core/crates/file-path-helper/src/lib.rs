@@ -43,20 +43,38 @@ use thiserror::Error;
 use tokio::{fs, io};
 use tracing::error;
 
+pub mod extended_metadata;
 pub mod isolated_file_path_data;
 
+pub use extended_metadata::ExtendedMetadata;
 pub use isolated_file_path_data::{
 	join_location_relative_path, push_location_relative_path, IsolatedFilePathData,
 	IsolatedFilePathDataParts,
 };
 
-#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+/// Where each location keeps its trashed files, which must never be indexed as part of it
+pub const TRASH_DIRECTORY: &str = ".spacedrive-trash";
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct FilePathMetadata {
 	pub inode: u64,
 	pub size_in_bytes: u64,
 	pub created_at: DateTime<Utc>,
 	pub modified_at: DateTime<Utc>,
 	pub hidden: bool,
+	/// Where the path points to, if it's a symbolic link indexed as an alias
+	#[serde(default)]
+	pub symlink_target: Option<String>,
+	/// Unix mode bits, as shown by `ls -l`
+	#[serde(default)]
+	pub permissions: Option<String>,
+	#[serde(default)]
+	pub owner: Option<String>,
+	#[serde(default)]
+	pub owner_group: Option<String>,
+	/// JSON object with the indexed extended attributes, see [`extended_metadata::INDEXED_XATTRS`]
+	#[serde(default)]
+	pub xattrs: Option<String>,
 }
 
 pub fn path_is_hidden(path: impl AsRef<Path>, metadata: &Metadata) -> bool {
@@ -125,12 +143,79 @@ impl FilePathMetadata {
 			}
 		};
 
+		// Only links that weren't followed get here with the metadata of the link itself
+		let symlink_target = if metadata.is_symlink() {
+			let target = std::fs::read_link(path).map_err(|e| FileIOError::from((path, e)))?;
+			Some(target.to_string_lossy().into_owned())
+		} else {
+			None
+		};
+
+		let ExtendedMetadata {
+			permissions,
+			owner,
+			owner_group,
+			xattrs,
+		} = ExtendedMetadata::from_path(path, metadata);
+
 		Ok(Self {
 			inode,
 			hidden: path_is_hidden(path, metadata),
 			size_in_bytes: metadata.len(),
 			created_at: metadata.created_or_now().into(),
 			modified_at: metadata.modified_or_now().into(),
+			symlink_target,
+			permissions,
+			owner,
+			owner_group,
+			xattrs,
+		})
+	}
+
+	/// Metadata of a symbolic link indexed as what it points to, everything comes from `target_metadata`
+	/// but the inode, which is the link's own so it doesn't collide with the target being indexed too.
+	pub fn from_followed_symlink(
+		path: impl AsRef<Path>,
+		link_metadata: &Metadata,
+		target_metadata: &Metadata,
+	) -> Result<Self, FilePathError> {
+		let path = path.as_ref();
+
+		let inode = {
+			#[cfg(target_family = "unix")]
+			{
+				get_inode(link_metadata)
+			}
+
+			#[cfg(target_family = "windows")]
+			{
+				use std::{fs::OpenOptions, os::windows::fs::OpenOptionsExt};
+
+				use winapi_util::{file::information, Handle};
+
+				const FILE_FLAG_BACKUP_SEMANTICS: u32 = 0x0200_0000;
+				const FILE_FLAG_OPEN_REPARSE_POINT: u32 = 0x0020_0000;
+
+				let _ = link_metadata;
+
+				// Opening the reparse point itself, as `Handle::from_path_any` would follow it
+				let info = tokio::task::block_in_place(|| {
+					OpenOptions::new()
+						.read(true)
+						.custom_flags(FILE_FLAG_BACKUP_SEMANTICS | FILE_FLAG_OPEN_REPARSE_POINT)
+						.open(path)
+						.map(Handle::from_file)
+						.and_then(|ref handle| information(handle))
+						.map_err(|e| FileIOError::from((path, e)))
+				})?;
+
+				info.file_index()
+			}
+		};
+
+		Ok(Self {
+			inode,
+			..Self::from_path(path, target_metadata)?
 		})
 	}
 }
@@ -0,0 +1,144 @@
+//! An append-only record of everything an erase did, one JSON object per line, kept in the
+//! node's data directory so it can be audited after the fact.
+
+use sd_prisma::prisma::location;
+use sd_utils::error::FileIOError;
+
+use std::{
+	io,
+	path::{Path, PathBuf},
+};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tokio::{fs, io::AsyncWriteExt};
+use uuid::Uuid;
+
+use super::IneffectiveOverwrite;
+
+pub const ERASE_LOGS_DIRECTORY: &str = "erase_logs";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EntryKind {
+	File,
+	Symlink,
+	Directory,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum LogEntry {
+	Started {
+		at: DateTime<Utc>,
+		location_id: location::id::Type,
+		location_path: PathBuf,
+		passes: usize,
+		targets: Vec<PathBuf>,
+		disk_type: Option<String>,
+		filesystem: Option<String>,
+		ineffective_overwrite: Option<IneffectiveOverwrite>,
+	},
+	Erased {
+		at: DateTime<Utc>,
+		path: PathBuf,
+		kind: EntryKind,
+		size: u64,
+		passes: usize,
+		verified: bool,
+	},
+	Failed {
+		at: DateTime<Utc>,
+		path: PathBuf,
+		kind: Option<EntryKind>,
+		reason: String,
+	},
+	Finished {
+		at: DateTime<Utc>,
+		erased: u64,
+		erased_bytes: u64,
+		verified: u64,
+		failed: u64,
+	},
+}
+
+impl LogEntry {
+	#[must_use]
+	pub fn erased(
+		path: impl Into<PathBuf>,
+		kind: EntryKind,
+		size: u64,
+		passes: usize,
+		verified: bool,
+	) -> Self {
+		Self::Erased {
+			at: Utc::now(),
+			path: path.into(),
+			kind,
+			size,
+			passes,
+			verified,
+		}
+	}
+
+	#[must_use]
+	pub fn failed(
+		path: impl Into<PathBuf>,
+		kind: Option<EntryKind>,
+		reason: &impl ToString,
+	) -> Self {
+		Self::Failed {
+			at: Utc::now(),
+			path: path.into(),
+			kind,
+			reason: reason.to_string(),
+		}
+	}
+}
+
+/// Creates a new erase log in the data directory starting with `started`, returning its path
+pub async fn create(data_directory: &Path, started: &LogEntry) -> Result<PathBuf, FileIOError> {
+	let logs_directory = data_directory.join(ERASE_LOGS_DIRECTORY);
+
+	fs::create_dir_all(&logs_directory)
+		.await
+		.map_err(|e| FileIOError::from((&logs_directory, e)))?;
+
+	let log_path = logs_directory.join(format!(
+		"{}-{}.jsonl",
+		Utc::now().format("%Y%m%dT%H%M%SZ"),
+		Uuid::new_v4()
+	));
+
+	append(&log_path, [started]).await?;
+
+	Ok(log_path)
+}
+
+/// Appends entries to an erase log, syncing them to disk so the log survives a crash mid-erase
+pub async fn append(
+	log_path: &Path,
+	entries: impl IntoIterator<Item = &LogEntry> + Send,
+) -> Result<(), FileIOError> {
+	let mut lines = vec![];
+	for entry in entries {
+		serde_json::to_writer(&mut lines, entry)
+			.map_err(|e| FileIOError::from((log_path, io::Error::other(e))))?;
+		lines.push(b'\n');
+	}
+
+	let mut log = fs::OpenOptions::new()
+		.create(true)
+		.append(true)
+		.open(log_path)
+		.await
+		.map_err(|e| FileIOError::from((log_path, e)))?;
+
+	log.write_all(&lines)
+		.await
+		.map_err(|e| FileIOError::from((log_path, e)))?;
+
+	log.sync_all()
+		.await
+		.map_err(|e| FileIOError::from((log_path, e)))
+}
@@ -0,0 +1,527 @@
+use crate::{
+	file_eraser,
+	job_system::{
+		job::{Job, JobReturn, JobTaskDispatcher, ReturnStatus},
+		report::ReportOutputMetadata,
+		utils::cancel_pending_tasks,
+		SerializableJob, SerializedTasks,
+	},
+	Error, JobName, NonCriticalError, OuterContext, ProgressUpdate,
+};
+
+use sd_core_file_path_helper::IsolatedFilePathData;
+use sd_core_prisma_helpers::file_path_to_isolate;
+
+use sd_prisma::prisma::{file_path, location};
+use sd_task_system::{
+	AnyTaskOutput, IntoTask, SerializableTask, Task, TaskDispatcher, TaskHandle, TaskId,
+	TaskOutput, TaskStatus,
+};
+use sd_utils::db::maybe_missing;
+
+use std::{
+	collections::HashMap,
+	hash::{Hash, Hasher},
+	mem,
+	path::PathBuf,
+	time::Duration,
+};
+
+use chrono::Utc;
+use futures::{stream::FuturesUnordered, StreamExt};
+use futures_concurrency::future::TryJoin;
+use itertools::Itertools;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use tracing::{error, warn};
+
+use super::{
+	erase_log::{self, EntryKind, LogEntry},
+	remove_directories,
+	tasks::{erase_files, EraseFilesTask},
+	walk, IneffectiveOverwrite, CHUNK_SIZE,
+};
+
+/// Securely erases files and directories of a location, overwriting each file with random data
+/// for a number of passes and keeping an erase log of everything it did
+#[derive(Debug)]
+pub struct FileEraser {
+	location_id: location::id::Type,
+	file_path_ids: Vec<file_path::id::Type>,
+	passes: usize,
+
+	directories_to_remove: Vec<PathBuf>,
+
+	metadata: Metadata,
+
+	errors: Vec<NonCriticalError>,
+
+	pending_tasks_on_resume: Vec<TaskHandle<Error>>,
+	tasks_for_shutdown: Vec<Box<dyn Task<Error>>>,
+}
+
+impl Hash for FileEraser {
+	fn hash<H: Hasher>(&self, state: &mut H) {
+		self.location_id.hash(state);
+		self.file_path_ids.hash(state);
+	}
+}
+
+impl Job for FileEraser {
+	const NAME: JobName = JobName::FileEraser;
+
+	async fn resume_tasks(
+		&mut self,
+		dispatcher: &JobTaskDispatcher,
+		_: &impl OuterContext,
+		SerializedTasks(serialized_tasks): SerializedTasks,
+	) -> Result<(), Error> {
+		self.pending_tasks_on_resume = dispatcher
+			.dispatch_many_boxed(
+				rmp_serde::from_slice::<Vec<Vec<u8>>>(&serialized_tasks)
+					.map_err(file_eraser::Error::from)?
+					.into_iter()
+					.map(|task_bytes| async move {
+						EraseFilesTask::deserialize(&task_bytes, ())
+							.await
+							.map(IntoTask::into_task)
+					})
+					.collect::<Vec<_>>()
+					.try_join()
+					.await
+					.map_err(file_eraser::Error::from)?,
+			)
+			.await;
+
+		Ok(())
+	}
+
+	async fn run<Ctx: OuterContext>(
+		mut self,
+		dispatcher: JobTaskDispatcher,
+		ctx: Ctx,
+	) -> Result<ReturnStatus, Error> {
+		let mut pending_running_tasks = FuturesUnordered::new();
+
+		self.init_or_resume(&mut pending_running_tasks, &ctx, &dispatcher)
+			.await?;
+
+		while let Some(task) = pending_running_tasks.next().await {
+			match task {
+				Ok(TaskStatus::Done((task_id, TaskOutput::Out(out)))) => {
+					if let Err(e) = self.process_task_output(task_id, out, &ctx).await {
+						cancel_pending_tasks(&pending_running_tasks).await;
+
+						return Err(e.into());
+					}
+				}
+
+				Ok(TaskStatus::Done((task_id, TaskOutput::Empty))) => {
+					warn!("Task <id='{task_id}'> returned an empty output");
+				}
+
+				Ok(TaskStatus::Shutdown(task)) => {
+					self.tasks_for_shutdown.push(task);
+				}
+
+				Ok(TaskStatus::Error(e)) => {
+					cancel_pending_tasks(&pending_running_tasks).await;
+
+					return Err(e);
+				}
+
+				Ok(TaskStatus::Canceled | TaskStatus::ForcedAbortion) => {
+					cancel_pending_tasks(&pending_running_tasks).await;
+
+					return Ok(ReturnStatus::Canceled);
+				}
+
+				Err(e) => {
+					cancel_pending_tasks(&pending_running_tasks).await;
+
+					return Err(e.into());
+				}
+			}
+		}
+
+		if !self.tasks_for_shutdown.is_empty() {
+			return Ok(ReturnStatus::Shutdown(
+				SerializableJob::<Ctx>::serialize(self).await,
+			));
+		}
+
+		// From this point onward, we are done with the job and it can't be interrupted anymore
+		ctx.progress_msg("Removing erased directories");
+
+		self.remove_erased_directories().await?;
+
+		let Self {
+			metadata, errors, ..
+		} = self;
+
+		if let Some(log_path) = &metadata.log_path {
+			erase_log::append(
+				log_path,
+				[&LogEntry::Finished {
+					at: Utc::now(),
+					erased: metadata.erased_files,
+					erased_bytes: metadata.erased_bytes,
+					verified: metadata.verified_files,
+					failed: metadata.failed,
+				}],
+			)
+			.await
+			.map_err(file_eraser::Error::EraseLog)?;
+		}
+
+		ctx.invalidate_query("search.paths");
+
+		Ok(ReturnStatus::Completed(
+			JobReturn::builder()
+				.with_metadata(metadata)
+				.with_non_critical_errors(errors)
+				.build(),
+		))
+	}
+}
+
+impl FileEraser {
+	#[must_use]
+	pub fn new(
+		location_id: location::id::Type,
+		file_path_ids: Vec<file_path::id::Type>,
+		passes: usize,
+	) -> Self {
+		Self {
+			location_id,
+			file_path_ids,
+			passes,
+			directories_to_remove: Vec::new(),
+			metadata: Metadata::default(),
+			errors: Vec::new(),
+			pending_tasks_on_resume: Vec::new(),
+			tasks_for_shutdown: Vec::new(),
+		}
+	}
+
+	async fn init_or_resume(
+		&mut self,
+		pending_running_tasks: &mut FuturesUnordered<TaskHandle<Error>>,
+		ctx: &impl OuterContext,
+		dispatcher: &JobTaskDispatcher,
+	) -> Result<(), file_eraser::Error> {
+		// if we already have an erase log, then this job is being resumed
+		if self.metadata.log_path.is_some() {
+			pending_running_tasks.extend(mem::take(&mut self.pending_tasks_on_resume));
+
+			return Ok(());
+		}
+
+		let db = ctx.db();
+
+		let location = db
+			.location()
+			.find_unique(location::id::equals(self.location_id))
+			.exec()
+			.await?
+			.ok_or(file_eraser::Error::LocationNotFound(self.location_id))?;
+
+		let location_path = maybe_missing(location.path, "location.path").map(PathBuf::from)?;
+
+		let targets = db
+			.file_path()
+			.find_many(vec![
+				file_path::location_id::equals(Some(self.location_id)),
+				file_path::id::in_vec(self.file_path_ids.clone()),
+			])
+			.select(file_path_to_isolate::select())
+			.exec()
+			.await?
+			.into_iter()
+			.map(|file_path| {
+				IsolatedFilePathData::try_from(file_path)
+					.map(|iso_file_path| location_path.join(iso_file_path))
+			})
+			.collect::<Result<Vec<_>, _>>()?;
+
+		let (volume, ineffective_overwrite) =
+			IneffectiveOverwrite::for_location(db, self.location_id).await?;
+
+		if let Some(ineffective_overwrite) = &ineffective_overwrite {
+			warn!(
+				"Erasing files of location <id='{}'> where {ineffective_overwrite}",
+				self.location_id
+			);
+
+			self.errors.push(
+				file_eraser::NonCriticalError::IneffectiveOverwrite(ineffective_overwrite.clone())
+					.into(),
+			);
+		}
+
+		let log_path = erase_log::create(
+			ctx.get_data_directory(),
+			&LogEntry::Started {
+				at: Utc::now(),
+				location_id: self.location_id,
+				location_path,
+				passes: self.passes,
+				targets: targets.clone(),
+				disk_type: volume.as_ref().and_then(|volume| volume.disk_type.clone()),
+				filesystem: volume.and_then(|volume| volume.filesystem),
+				ineffective_overwrite,
+			},
+		)
+		.await
+		.map_err(file_eraser::Error::EraseLog)?;
+
+		self.metadata.log_path = Some(log_path.clone());
+
+		ctx.progress_msg("Looking for files to erase");
+
+		let (walked, walk_errors) = walk(targets).await;
+
+		if !walk_errors.is_empty() {
+			erase_log::append(
+				&log_path,
+				&walk_errors
+					.iter()
+					.map(|e| LogEntry::failed(e.path.to_path_buf(), None, e))
+					.collect::<Vec<_>>(),
+			)
+			.await
+			.map_err(file_eraser::Error::EraseLog)?;
+
+			self.metadata.failed += walk_errors.len() as u64;
+			self.errors.extend(walk_errors.into_iter().map(|e| {
+				error!("Failed to find files to erase: {e:#?}");
+				file_eraser::NonCriticalError::FailedToWalk(e.to_string()).into()
+			}));
+		}
+
+		self.directories_to_remove = walked.directories;
+
+		let files_count = walked.files.len() as u64;
+
+		let erase_tasks = walked
+			.files
+			.into_iter()
+			.chunks(CHUNK_SIZE)
+			.into_iter()
+			.map(|chunk| EraseFilesTask::new(chunk.collect(), self.passes))
+			.collect::<Vec<_>>();
+
+		pending_running_tasks.extend(dispatcher.dispatch_many(erase_tasks).await);
+
+		self.metadata.total_tasks = pending_running_tasks.len() as u64;
+
+		ctx.progress(vec![
+			ProgressUpdate::TaskCount(self.metadata.total_tasks),
+			ProgressUpdate::Message(format!("{files_count} files to be erased")),
+		]);
+
+		Ok(())
+	}
+
+	/// Process output of tasks, according to the downcasted output type
+	///
+	/// # Panics
+	/// Will panic if another task type is added in the job, but this function wasn't updated to handle it
+	///
+	async fn process_task_output(
+		&mut self,
+		task_id: TaskId,
+		any_task_output: Box<dyn AnyTaskOutput>,
+		ctx: &impl OuterContext,
+	) -> Result<(), file_eraser::Error> {
+		if any_task_output.is::<erase_files::Output>() {
+			let erase_files::Output {
+				entries,
+				erase_time,
+				errors,
+			} = *any_task_output
+				.downcast::<erase_files::Output>()
+				.expect("just checked");
+
+			if let Some(log_path) = &self.metadata.log_path {
+				erase_log::append(log_path, &entries)
+					.await
+					.map_err(file_eraser::Error::EraseLog)?;
+			}
+
+			self.metadata.count(&entries);
+			self.metadata.erase_time += erase_time;
+			self.metadata.completed_tasks += 1;
+			self.errors.extend(errors);
+
+			ctx.progress(vec![
+				ProgressUpdate::CompletedTaskCount(self.metadata.completed_tasks),
+				ProgressUpdate::Message(format!("Erased {} files", self.metadata.erased_files)),
+			]);
+		} else {
+			unreachable!("Unexpected task output type: <id='{task_id}'>");
+		}
+
+		Ok(())
+	}
+
+	async fn remove_erased_directories(&mut self) -> Result<(), file_eraser::Error> {
+		let entries = remove_directories(mem::take(&mut self.directories_to_remove))
+			.await
+			.into_iter()
+			.map(|(path, res)| match res {
+				Ok(()) => LogEntry::erased(path, EntryKind::Directory, 0, 0, false),
+				Err(e) => {
+					error!("Failed to remove erased directory: {e:#?}");
+					self.errors.push(
+						file_eraser::NonCriticalError::FailedToRemoveDirectory(e.to_string())
+							.into(),
+					);
+					LogEntry::failed(path, Some(EntryKind::Directory), &e)
+				}
+			})
+			.collect::<Vec<_>>();
+
+		if let Some(log_path) = &self.metadata.log_path {
+			erase_log::append(log_path, &entries)
+				.await
+				.map_err(file_eraser::Error::EraseLog)?;
+		}
+
+		self.metadata.count(&entries);
+
+		Ok(())
+	}
+}
+
+#[derive(Serialize, Deserialize)]
+struct SaveState {
+	location_id: location::id::Type,
+	file_path_ids: Vec<file_path::id::Type>,
+	passes: usize,
+
+	directories_to_remove: Vec<PathBuf>,
+
+	metadata: Metadata,
+
+	errors: Vec<NonCriticalError>,
+
+	tasks_for_shutdown_bytes: Option<SerializedTasks>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct Metadata {
+	log_path: Option<PathBuf>,
+	erase_time: Duration,
+	erased_files: u64,
+	erased_directories: u64,
+	erased_bytes: u64,
+	verified_files: u64,
+	failed: u64,
+	total_tasks: u64,
+	completed_tasks: u64,
+}
+
+impl Metadata {
+	fn count(&mut self, entries: &[LogEntry]) {
+		for entry in entries {
+			match entry {
+				LogEntry::Erased {
+					kind: EntryKind::Directory,
+					..
+				} => self.erased_directories += 1,
+				LogEntry::Erased { size, verified, .. } => {
+					self.erased_files += 1;
+					self.erased_bytes += size;
+					if *verified {
+						self.verified_files += 1;
+					}
+				}
+				LogEntry::Failed { .. } => self.failed += 1,
+				LogEntry::Started { .. } | LogEntry::Finished { .. } => {}
+			}
+		}
+	}
+}
+
+impl From<Metadata> for ReportOutputMetadata {
+	fn from(value: Metadata) -> Self {
+		Self::Metrics(HashMap::from([
+			("erase_log_path".into(), json!(value.log_path)),
+			("erase_time".into(), json!(value.erase_time)),
+			("erased_files".into(), json!(value.erased_files)),
+			("erased_directories".into(), json!(value.erased_directories)),
+			("erased_bytes".into(), json!(value.erased_bytes)),
+			("verified_files".into(), json!(value.verified_files)),
+			("failed".into(), json!(value.failed)),
+			("total_tasks".into(), json!(value.total_tasks)),
+		]))
+	}
+}
+
+impl<Ctx: OuterContext> SerializableJob<Ctx> for FileEraser {
+	async fn serialize(self) -> Result<Option<Vec<u8>>, rmp_serde::encode::Error> {
+		let Self {
+			location_id,
+			file_path_ids,
+			passes,
+			directories_to_remove,
+			metadata,
+			errors,
+			tasks_for_shutdown,
+			..
+		} = self;
+
+		rmp_serde::to_vec_named(&SaveState {
+			location_id,
+			file_path_ids,
+			passes,
+			directories_to_remove,
+			metadata,
+			errors,
+			tasks_for_shutdown_bytes: Some(SerializedTasks(rmp_serde::to_vec_named(
+				&tasks_for_shutdown
+					.into_iter()
+					.map(|task| async move {
+						task.downcast::<EraseFilesTask>()
+							.expect("no other task types on this job")
+							.serialize()
+							.await
+					})
+					.collect::<Vec<_>>()
+					.try_join()
+					.await?,
+			)?)),
+		})
+		.map(Some)
+	}
+
+	async fn deserialize(
+		serialized_job: &[u8],
+		_: &Ctx,
+	) -> Result<Option<(Self, Option<SerializedTasks>)>, rmp_serde::decode::Error> {
+		let SaveState {
+			location_id,
+			file_path_ids,
+			passes,
+			directories_to_remove,
+			metadata,
+			errors,
+			tasks_for_shutdown_bytes,
+		} = rmp_serde::from_slice::<SaveState>(serialized_job)?;
+
+		Ok(Some((
+			Self {
+				location_id,
+				file_path_ids,
+				passes,
+				directories_to_remove,
+				metadata,
+				errors,
+				pending_tasks_on_resume: Vec::new(),
+				tasks_for_shutdown: Vec::new(),
+			},
+			tasks_for_shutdown_bytes,
+		)))
+	}
+}
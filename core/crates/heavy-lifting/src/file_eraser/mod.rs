@@ -0,0 +1,534 @@
+use sd_prisma::prisma::{location, volume, PrismaClient};
+use sd_utils::{db::MissingFieldError, error::FileIOError};
+
+use std::{
+	cmp::{self, Reverse},
+	fmt, io,
+	path::{Path, PathBuf},
+};
+
+use prisma_client_rust::QueryError;
+use rand::{rngs::StdRng, RngCore, SeedableRng};
+use rspc::ErrorCode;
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use tokio::{
+	fs::{self, File, OpenOptions},
+	io::{AsyncReadExt, AsyncSeekExt, AsyncWriteExt},
+};
+use tracing::{debug, trace};
+use uuid::Uuid;
+
+pub mod erase_log;
+pub mod job;
+mod tasks;
+
+pub use erase_log::{EntryKind, LogEntry};
+pub use job::FileEraser;
+
+// we break these tasks into chunks of 100 to improve performance
+const CHUNK_SIZE: usize = 100;
+
+/// Size of the blocks files are overwritten and read back in
+const BLOCK_LEN: usize = 1024 * 1024;
+
+/// How many random names we try for an entry before giving up on hiding its original name
+const RENAME_ATTEMPTS: usize = 8;
+
+/// Filesystems that write changes to new blocks instead of in place, so overwriting a file
+/// leaves its previous contents on disk
+const COPY_ON_WRITE_FILESYSTEMS: [&str; 7] =
+	["btrfs", "zfs", "apfs", "refs", "bcachefs", "f2fs", "nilfs2"];
+
+#[derive(thiserror::Error, Debug)]
+pub enum Error {
+	#[error("location not found <id='{0}'>")]
+	LocationNotFound(location::id::Type),
+	#[error("missing field on database: {0}")]
+	MissingField(#[from] MissingFieldError),
+	#[error("failed to deserialized stored tasks for job resume: {0}")]
+	DeserializeTasks(#[from] rmp_serde::decode::Error),
+	#[error("database error: {0}")]
+	Database(#[from] QueryError),
+	#[error("failed to write the erase log: {0}")]
+	EraseLog(FileIOError),
+
+	#[error(transparent)]
+	FilePathError(#[from] sd_core_file_path_helper::FilePathError),
+}
+
+impl From<Error> for rspc::Error {
+	fn from(err: Error) -> Self {
+		match err {
+			Error::LocationNotFound(_) => {
+				Self::with_cause(ErrorCode::NotFound, err.to_string(), err)
+			}
+
+			_ => Self::with_cause(ErrorCode::InternalServerError, err.to_string(), err),
+		}
+	}
+}
+
+#[derive(thiserror::Error, Debug, Serialize, Deserialize, Type)]
+pub enum NonCriticalError {
+	#[error("overwriting can't guarantee an erasure here: {0}")]
+	IneffectiveOverwrite(IneffectiveOverwrite),
+	#[error("failed to erase file: {0}")]
+	FailedToEraseFile(String),
+	#[error("overwritten file didn't read back as written, it was left in place: {0}")]
+	VerificationFailed(String),
+	#[error("failed to find files to erase: {0}")]
+	FailedToWalk(String),
+	#[error("failed to remove directory: {0}")]
+	FailedToRemoveDirectory(String),
+}
+
+/// Why overwriting a file doesn't destroy its previous contents on a given volume
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Type)]
+pub enum IneffectiveOverwrite {
+	/// Wear-levelling on solid-state drives writes to new cells, keeping the old ones around
+	SolidState,
+	/// The filesystem (named here) writes changes to new blocks instead of in place
+	CopyOnWrite(String),
+}
+
+impl IneffectiveOverwrite {
+	/// Checks the `disk_type` and `filesystem` stored for a volume
+	#[must_use]
+	pub fn from_volume(disk_type: Option<&str>, filesystem: Option<&str>) -> Option<Self> {
+		if disk_type == Some("SSD") {
+			return Some(Self::SolidState);
+		}
+
+		filesystem
+			.filter(|filesystem| {
+				COPY_ON_WRITE_FILESYSTEMS
+					.iter()
+					.any(|cow| cow.eq_ignore_ascii_case(filesystem))
+			})
+			.map(|filesystem| Self::CopyOnWrite(filesystem.to_string()))
+	}
+
+	/// Looks up the volume a location lives in, returning it along with why overwriting files
+	/// there won't destroy their contents, if that's the case
+	pub async fn for_location(
+		db: &PrismaClient,
+		location_id: location::id::Type,
+	) -> Result<(Option<volume::Data>, Option<Self>), QueryError> {
+		let volume = db
+			.volume()
+			.find_first(vec![volume::locations::some(vec![location::id::equals(
+				location_id,
+			)])])
+			.exec()
+			.await?;
+
+		let ineffective_overwrite = volume.as_ref().and_then(|volume| {
+			Self::from_volume(volume.disk_type.as_deref(), volume.filesystem.as_deref())
+		});
+
+		Ok((volume, ineffective_overwrite))
+	}
+}
+
+impl fmt::Display for IneffectiveOverwrite {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			Self::SolidState => write!(
+				f,
+				"the location is on a solid-state drive, whose wear-levelling keeps old copies of overwritten data"
+			),
+			Self::CopyOnWrite(filesystem) => write!(
+				f,
+				"the location's {filesystem} filesystem writes changes to new blocks, keeping the old contents of overwritten files"
+			),
+		}
+	}
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum EraseError {
+	#[error(transparent)]
+	FileIO(#[from] FileIOError),
+	#[error("overwritten contents of <path='{}'> didn't match what was written", .0.display())]
+	VerificationFailed(PathBuf),
+}
+
+impl From<EraseError> for NonCriticalError {
+	fn from(err: EraseError) -> Self {
+		match err {
+			EraseError::FileIO(e) => Self::FailedToEraseFile(e.to_string()),
+			EraseError::VerificationFailed(path) => {
+				Self::VerificationFailed(path.display().to_string())
+			}
+		}
+	}
+}
+
+/// A file or symbolic link to be erased
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EraseTarget {
+	pub path: PathBuf,
+	pub kind: EntryKind,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct ErasedFile {
+	pub size: u64,
+	pub verified: bool,
+}
+
+/// Everything to erase under the chosen paths, with directories kept apart so they're only
+/// removed once emptied
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Walked {
+	pub files: Vec<EraseTarget>,
+	pub directories: Vec<PathBuf>,
+}
+
+/// Walks the chosen paths, going into directories but never following symbolic links
+pub async fn walk(mut to_walk: Vec<PathBuf>) -> (Walked, Vec<FileIOError>) {
+	let mut walked = Walked::default();
+	let mut errors = vec![];
+
+	while let Some(path) = to_walk.pop() {
+		let metadata = match fs::symlink_metadata(&path).await {
+			Ok(metadata) => metadata,
+			Err(e) => {
+				errors.push(FileIOError::from((&path, e)));
+				continue;
+			}
+		};
+
+		if metadata.is_dir() {
+			let mut read_dir = match fs::read_dir(&path).await {
+				Ok(read_dir) => read_dir,
+				Err(e) => {
+					errors.push(FileIOError::from((&path, e)));
+					continue;
+				}
+			};
+
+			loop {
+				match read_dir.next_entry().await {
+					Ok(Some(entry)) => to_walk.push(entry.path()),
+					Ok(None) => break,
+					Err(e) => {
+						errors.push(FileIOError::from((&path, e)));
+						break;
+					}
+				}
+			}
+
+			walked.directories.push(path);
+		} else {
+			walked.files.push(EraseTarget {
+				path,
+				kind: if metadata.is_symlink() {
+					EntryKind::Symlink
+				} else {
+					EntryKind::File
+				},
+			});
+		}
+	}
+
+	(walked, errors)
+}
+
+/// Overwrites a file with random data for the given number of passes, reading the last pass back
+/// to make sure it reached the disk, then truncates it, hides its name and removes it.
+///
+/// If the read back doesn't match, the file is left in place.
+pub async fn erase_file(
+	path: impl AsRef<Path> + Send,
+	passes: usize,
+) -> Result<ErasedFile, EraseError> {
+	let path = path.as_ref();
+
+	let mut file = OpenOptions::new()
+		.read(true)
+		.write(true)
+		.open(path)
+		.await
+		.map_err(|e| FileIOError::from((path, e)))?;
+
+	let size = file
+		.metadata()
+		.await
+		.map_err(|e| FileIOError::from((path, e)))?
+		.len();
+
+	let verified = if passes > 0 {
+		trace!("Overwriting file: {} with {passes} passes", path.display());
+
+		let last_pass = overwrite(&mut file, size, passes)
+			.await
+			.map_err(|e| FileIOError::from((path, e, "Failed to overwrite file")))?;
+
+		file.sync_all()
+			.await
+			.map_err(|e| FileIOError::from((path, e)))?;
+
+		drop_cached_pages(&file);
+
+		if read_back(&mut file, size)
+			.await
+			.map_err(|e| FileIOError::from((path, e, "Failed to read back overwritten file")))?
+			!= last_pass
+		{
+			return Err(EraseError::VerificationFailed(path.to_path_buf()));
+		}
+
+		true
+	} else {
+		false
+	};
+
+	file.set_len(0)
+		.await
+		.map_err(|e| FileIOError::from((path, e)))?;
+	file.sync_all()
+		.await
+		.map_err(|e| FileIOError::from((path, e)))?;
+
+	drop(file);
+
+	let obscured_path = obscure_name(path).await?;
+	fs::remove_file(&obscured_path)
+		.await
+		.map_err(|e| FileIOError::from((obscured_path, e)))?;
+
+	Ok(ErasedFile { size, verified })
+}
+
+/// Overwrites the first `size` bytes of a file with random data `passes` times,
+/// returning the hash of what the last pass wrote
+async fn overwrite(file: &mut File, size: u64, passes: usize) -> io::Result<blake3::Hash> {
+	let mut rng = StdRng::from_entropy();
+	let mut buf = vec![0; BLOCK_LEN];
+	let mut last_pass = blake3::Hasher::new();
+
+	for _ in 0..passes {
+		last_pass.reset();
+		file.rewind().await?;
+
+		let mut remaining = size;
+		while remaining > 0 {
+			let block = &mut buf[..usize::try_from(remaining)
+				.map_or(BLOCK_LEN, |remaining| cmp::min(remaining, BLOCK_LEN))];
+
+			rng.fill_bytes(block);
+			file.write_all(block).await?;
+			last_pass.update(block);
+
+			remaining -= block.len() as u64;
+		}
+
+		file.flush().await?;
+	}
+
+	file.rewind().await?;
+
+	Ok(last_pass.finalize())
+}
+
+/// Hashes the first `size` bytes of a file, to compare them with what was last written
+async fn read_back(file: &mut File, size: u64) -> io::Result<blake3::Hash> {
+	let mut buf = vec![0; BLOCK_LEN];
+	let mut read_back = blake3::Hasher::new();
+
+	file.rewind().await?;
+
+	let mut remaining = size;
+	while remaining > 0 {
+		let block = &mut buf[..usize::try_from(remaining)
+			.map_or(BLOCK_LEN, |remaining| cmp::min(remaining, BLOCK_LEN))];
+
+		file.read_exact(block).await?;
+		read_back.update(&*block);
+
+		remaining -= block.len() as u64;
+	}
+
+	file.rewind().await?;
+
+	Ok(read_back.finalize())
+}
+
+/// Removes a symbolic link without touching its target, hiding its name first
+pub async fn erase_symlink(path: impl AsRef<Path> + Send) -> Result<(), FileIOError> {
+	let obscured_path = obscure_name(path.as_ref()).await?;
+
+	fs::remove_file(&obscured_path)
+		.await
+		.map_err(|e| FileIOError::from((obscured_path, e)))
+}
+
+/// Removes already emptied directories, deepest first and hiding their names before removal
+pub async fn remove_directories(
+	mut directories: Vec<PathBuf>,
+) -> Vec<(PathBuf, Result<(), FileIOError>)> {
+	directories.sort_by_key(|path| Reverse(path.components().count()));
+
+	let mut removed = Vec::with_capacity(directories.len());
+
+	for path in directories {
+		let res = match obscure_name(&path).await {
+			Ok(obscured_path) => fs::remove_dir(&obscured_path)
+				.await
+				.map_err(|e| FileIOError::from((obscured_path, e))),
+			Err(e) => Err(e),
+		};
+
+		removed.push((path, res));
+	}
+
+	removed
+}
+
+/// Renames an entry to a random name of the same length, so its original name doesn't linger
+/// in the directory's metadata once it's removed
+async fn obscure_name(path: &Path) -> Result<PathBuf, FileIOError> {
+	let (Some(parent), Some(name)) = (path.parent(), path.file_name()) else {
+		return Ok(path.to_path_buf());
+	};
+
+	for _ in 0..RENAME_ATTEMPTS {
+		let obscured_path = parent.join(random_name(name.len()));
+
+		if fs::symlink_metadata(&obscured_path).await.is_ok() {
+			continue;
+		}
+
+		fs::rename(path, &obscured_path)
+			.await
+			.map_err(|e| FileIOError::from((path, e)))?;
+
+		return Ok(obscured_path);
+	}
+
+	debug!(
+		"Couldn't find a free random name to hide <path='{}'>, removing it as is",
+		path.display()
+	);
+
+	Ok(path.to_path_buf())
+}
+
+fn random_name(len: usize) -> String {
+	let mut name = String::with_capacity(len + 32);
+
+	while name.len() < len {
+		name.push_str(&Uuid::new_v4().simple().to_string());
+	}
+
+	name.truncate(len);
+
+	name
+}
+
+/// Asks the OS to forget the cached pages of a file, so reading it back checks what reached the disk
+#[allow(unsafe_code)]
+fn drop_cached_pages(file: &File) {
+	#[cfg(any(target_os = "linux", target_os = "android"))]
+	{
+		use std::os::fd::AsRawFd;
+
+		// SAFETY: the file descriptor stays open for as long as `file` is borrowed
+		let res = unsafe { libc::posix_fadvise(file.as_raw_fd(), 0, 0, libc::POSIX_FADV_DONTNEED) };
+		if res != 0 {
+			debug!("Failed to drop cached pages before verifying an overwrite: {res}");
+		}
+	}
+
+	#[cfg(any(target_os = "macos", target_os = "ios"))]
+	{
+		use std::os::fd::AsRawFd;
+
+		// SAFETY: the file descriptor stays open for as long as `file` is borrowed
+		if unsafe { libc::fcntl(file.as_raw_fd(), libc::F_NOCACHE, 1) } == -1 {
+			debug!(
+				"Failed to bypass the cache before verifying an overwrite: {}",
+				io::Error::last_os_error()
+			);
+		}
+	}
+
+	#[cfg(not(any(
+		target_os = "linux",
+		target_os = "android",
+		target_os = "macos",
+		target_os = "ios"
+	)))]
+	let _ = file;
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	use tempfile::tempdir;
+
+	#[test]
+	fn detects_ineffective_overwrites() {
+		assert_eq!(
+			IneffectiveOverwrite::from_volume(Some("SSD"), Some("ext4")),
+			Some(IneffectiveOverwrite::SolidState)
+		);
+		assert_eq!(
+			IneffectiveOverwrite::from_volume(Some("HDD"), Some("Btrfs")),
+			Some(IneffectiveOverwrite::CopyOnWrite("Btrfs".to_string()))
+		);
+		assert_eq!(
+			IneffectiveOverwrite::from_volume(Some("HDD"), Some("ext4")),
+			None
+		);
+		assert_eq!(IneffectiveOverwrite::from_volume(None, None), None);
+	}
+
+	#[tokio::test]
+	async fn erases_files_and_directories() {
+		let root = tempdir().expect("failed to create temp dir");
+		let dir = root.path().join("secrets");
+		fs::create_dir_all(dir.join("nested"))
+			.await
+			.expect("failed to create dirs");
+		fs::write(dir.join("a.txt"), vec![7u8; 4096 + 3])
+			.await
+			.expect("failed to write file");
+		fs::write(dir.join("nested").join("b.txt"), b"")
+			.await
+			.expect("failed to write file");
+
+		let (walked, errors) = walk(vec![dir.clone()]).await;
+		assert!(errors.is_empty());
+		assert_eq!(walked.files.len(), 2);
+		assert_eq!(walked.directories.len(), 2);
+
+		for target in &walked.files {
+			let erased = erase_file(&target.path, 2)
+				.await
+				.expect("failed to erase file");
+			assert!(erased.verified);
+		}
+
+		for (path, res) in remove_directories(walked.directories).await {
+			assert!(res.is_ok(), "failed to remove {}", path.display());
+		}
+
+		let mut remaining = fs::read_dir(root.path()).await.expect("failed to read dir");
+		assert!(remaining
+			.next_entry()
+			.await
+			.expect("failed to read entry")
+			.is_none());
+	}
+
+	#[test]
+	fn random_names_keep_length() {
+		for len in [1, 5, 32, 100] {
+			assert_eq!(random_name(len).len(), len);
+		}
+	}
+}
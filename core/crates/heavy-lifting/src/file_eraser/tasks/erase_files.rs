@@ -0,0 +1,125 @@
+use crate::{
+	file_eraser::{self, erase_file, erase_symlink, EntryKind, EraseTarget, LogEntry},
+	Error, NonCriticalError,
+};
+
+use sd_task_system::{
+	check_interruption, ExecStatus, Interrupter, IntoAnyTaskOutput, SerializableTask, Task, TaskId,
+};
+
+use std::{mem, time::Duration};
+
+use serde::{Deserialize, Serialize};
+use tokio::time::Instant;
+use tracing::error;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct EraseFilesTask {
+	id: TaskId,
+	targets: Vec<EraseTarget>,
+	passes: usize,
+	entries: Vec<LogEntry>,
+	erase_time: Duration,
+	errors: Vec<NonCriticalError>,
+}
+
+#[derive(Debug)]
+pub struct Output {
+	pub entries: Vec<LogEntry>,
+	pub erase_time: Duration,
+	pub errors: Vec<NonCriticalError>,
+}
+
+impl EraseFilesTask {
+	#[must_use]
+	pub fn new(targets: Vec<EraseTarget>, passes: usize) -> Self {
+		Self {
+			id: TaskId::new_v4(),
+			entries: Vec::with_capacity(targets.len()),
+			targets,
+			passes,
+			erase_time: Duration::ZERO,
+			errors: Vec::new(),
+		}
+	}
+}
+
+#[async_trait::async_trait]
+impl Task<Error> for EraseFilesTask {
+	fn id(&self) -> TaskId {
+		self.id
+	}
+
+	async fn run(&mut self, interrupter: &Interrupter) -> Result<ExecStatus, Error> {
+		let Self {
+			targets,
+			passes,
+			entries,
+			erase_time,
+			errors,
+			..
+		} = self;
+
+		let start_time = Instant::now();
+
+		while let Some(EraseTarget { path, kind }) = targets.pop() {
+			match kind {
+				EntryKind::Symlink => match erase_symlink(&path).await {
+					Ok(()) => entries.push(LogEntry::erased(path, kind, 0, 0, false)),
+					Err(e) => {
+						error!("Failed to erase symbolic link: {e:#?}");
+						entries.push(LogEntry::failed(path, Some(kind), &e));
+						errors.push(
+							file_eraser::NonCriticalError::FailedToEraseFile(e.to_string()).into(),
+						);
+					}
+				},
+
+				EntryKind::File | EntryKind::Directory => match erase_file(&path, *passes).await {
+					Ok(erased) => entries.push(LogEntry::erased(
+						path,
+						EntryKind::File,
+						erased.size,
+						*passes,
+						erased.verified,
+					)),
+					Err(e) => {
+						error!("Failed to erase file: {e:#?}");
+						entries.push(LogEntry::failed(path, Some(EntryKind::File), &e));
+						errors.push(file_eraser::NonCriticalError::from(e).into());
+					}
+				},
+			}
+
+			check_interruption!(interrupter, start_time, erase_time);
+		}
+
+		Ok(ExecStatus::Done(
+			Output {
+				entries: mem::take(entries),
+				erase_time: *erase_time + start_time.elapsed(),
+				errors: mem::take(errors),
+			}
+			.into_output(),
+		))
+	}
+}
+
+impl SerializableTask<Error> for EraseFilesTask {
+	type SerializeError = rmp_serde::encode::Error;
+
+	type DeserializeError = rmp_serde::decode::Error;
+
+	type DeserializeCtx = ();
+
+	async fn serialize(self) -> Result<Vec<u8>, Self::SerializeError> {
+		rmp_serde::to_vec_named(&self)
+	}
+
+	async fn deserialize(
+		data: &[u8],
+		(): Self::DeserializeCtx,
+	) -> Result<Self, Self::DeserializeError> {
+		rmp_serde::from_slice(data)
+	}
+}
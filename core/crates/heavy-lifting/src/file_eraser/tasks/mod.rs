@@ -0,0 +1,3 @@
+pub mod erase_files;
+
+pub use erase_files::EraseFilesTask;
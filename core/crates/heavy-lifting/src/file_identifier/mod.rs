@@ -118,6 +118,27 @@ impl FileMetadata {
 			fs_metadata,
 		})
 	}
+
+	/// Fetch the metadata of a symbolic link indexed as an alias, without reading through it,
+	/// so it never gets a cas id.
+	pub async fn new_alias(
+		location_path: impl AsRef<Path> + Send,
+		iso_file_path: &IsolatedFilePathData<'_>,
+	) -> Result<Self, FileIOError> {
+		let path = location_path.as_ref().join(iso_file_path);
+
+		let fs_metadata = fs::symlink_metadata(&path)
+			.await
+			.map_err(|e| FileIOError::from((&path, e)))?;
+
+		trace!("Analyzed alias: <path='{}'>", path.display());
+
+		Ok(Self {
+			cas_id: None,
+			kind: ObjectKind::Alias,
+			fs_metadata,
+		})
+	}
 }
 
 fn orphan_path_filters_shallow(
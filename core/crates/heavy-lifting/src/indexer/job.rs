@@ -13,7 +13,7 @@ use crate::{
 };
 
 use sd_core_file_path_helper::IsolatedFilePathData;
-use sd_core_indexer_rules::{IndexerRule, IndexerRuler};
+use sd_core_indexer_rules::{symlink::SymlinkPolicy, IndexerRule, IndexerRuler};
 use sd_core_prisma_helpers::location_with_indexer_rules;
 
 use sd_prisma::prisma::location;
@@ -45,7 +45,7 @@ use super::{
 	tasks::{
 		saver::{SaveTask, SaveTaskOutput},
 		updater::{UpdateTask, UpdateTaskOutput},
-		walker::{WalkDirTask, WalkTaskOutput, WalkedEntry},
+		walker::{SymlinkHandling, WalkDirTask, WalkTaskOutput, WalkedEntry},
 	},
 	update_directory_sizes, update_location_size, IsoFilePathFactory, WalkerDBProxy, BATCH_SIZE,
 };
@@ -563,6 +563,10 @@ impl Indexer {
 						walker_root_path.as_ref(),
 						Arc::clone(&walker_root_path),
 						self.indexer_ruler.clone(),
+						SymlinkHandling {
+							policy: SymlinkPolicy::from(self.location.symlink_policy),
+							location_path: Arc::clone(&self.iso_file_path_factory.location_path),
+						},
 						self.iso_file_path_factory.clone(),
 						WalkerDBProxy {
 							location_id: self.location.id,
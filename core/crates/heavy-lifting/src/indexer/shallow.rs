@@ -2,7 +2,7 @@ use crate::{
 	indexer, utils::sub_path::get_full_path_from_sub_path, Error, NonCriticalError, OuterContext,
 };
 
-use sd_core_indexer_rules::{IndexerRule, IndexerRuler};
+use sd_core_indexer_rules::{symlink::SymlinkPolicy, IndexerRule, IndexerRuler};
 use sd_core_prisma_helpers::location_with_indexer_rules;
 use sd_core_sync::Manager as SyncManager;
 
@@ -25,7 +25,7 @@ use super::{
 	tasks::{
 		saver::{SaveTask, SaveTaskOutput},
 		updater::{UpdateTask, UpdateTaskOutput},
-		walker::{ToWalkEntry, WalkDirTask, WalkTaskOutput, WalkedEntry},
+		walker::{SymlinkHandling, ToWalkEntry, WalkDirTask, WalkTaskOutput, WalkedEntry},
 	},
 	update_directory_sizes, update_location_size, IsoFilePathFactory, WalkerDBProxy, BATCH_SIZE,
 };
@@ -137,6 +137,10 @@ async fn walk(
 				.collect::<Result<Vec<_>, _>>()
 				.map(IndexerRuler::new)
 				.map_err(indexer::Error::from)?,
+			SymlinkHandling {
+				policy: SymlinkPolicy::from(location.symlink_policy),
+				location_path: Arc::clone(&location_path),
+			},
 			IsoFilePathFactory {
 				location_id: location.id,
 				location_path,
@@ -149,7 +149,8 @@ impl Task<Error> for SaveTask {
 	async fn run(&mut self, _: &Interrupter) -> Result<ExecStatus, Error> {
 		use file_path::{
 			create_unchecked, date_created, date_indexed, date_modified, extension, hidden, inode,
-			is_dir, location, location_id, materialized_path, name, size_in_bytes_bytes,
+			is_dir, location, location_id, materialized_path, name, owner, owner_group,
+			permissions, size_in_bytes_bytes, symlink_target, xattrs,
 		};
 
 		let start_time = Instant::now();
@@ -199,6 +200,29 @@ impl Task<Error> for SaveTask {
 					sync_db_entry!(entry.metadata.modified_at.into(), date_modified),
 					sync_db_entry!(Utc::now().into(), date_indexed),
 					sync_db_entry!(entry.metadata.hidden, hidden),
+					(
+						(
+							symlink_target::NAME,
+							msgpack!(entry.metadata.symlink_target),
+						),
+						symlink_target::set(entry.metadata.symlink_target),
+					),
+					(
+						(permissions::NAME, msgpack!(entry.metadata.permissions)),
+						permissions::set(entry.metadata.permissions),
+					),
+					(
+						(owner::NAME, msgpack!(entry.metadata.owner)),
+						owner::set(entry.metadata.owner),
+					),
+					(
+						(owner_group::NAME, msgpack!(entry.metadata.owner_group)),
+						owner_group::set(entry.metadata.owner_group),
+					),
+					(
+						(xattrs::NAME, msgpack!(entry.metadata.xattrs)),
+						xattrs::set(entry.metadata.xattrs),
+					),
 				]
 				.into_iter()
 				.unzip();
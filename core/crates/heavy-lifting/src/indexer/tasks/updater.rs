@@ -138,8 +138,8 @@ impl Task<Error> for UpdateTask {
 
 	async fn run(&mut self, interrupter: &Interrupter) -> Result<ExecStatus, Error> {
 		use file_path::{
-			cas_id, date_created, date_modified, hidden, inode, is_dir, object, object_id,
-			size_in_bytes_bytes,
+			cas_id, date_created, date_modified, hidden, inode, is_dir, object, object_id, owner,
+			owner_group, permissions, size_in_bytes_bytes, symlink_target, xattrs,
 		};
 
 		let start_time = Instant::now();
@@ -185,6 +185,29 @@ impl Task<Error> for UpdateTask {
 							sync_db_entry!(v, date_modified)
 						},
 						sync_db_entry!(entry.metadata.hidden, hidden),
+						(
+							(
+								symlink_target::NAME,
+								msgpack!(entry.metadata.symlink_target),
+							),
+							symlink_target::set(entry.metadata.symlink_target),
+						),
+						(
+							(permissions::NAME, msgpack!(entry.metadata.permissions)),
+							permissions::set(entry.metadata.permissions),
+						),
+						(
+							(owner::NAME, msgpack!(entry.metadata.owner)),
+							owner::set(entry.metadata.owner),
+						),
+						(
+							(owner_group::NAME, msgpack!(entry.metadata.owner_group)),
+							owner_group::set(entry.metadata.owner_group),
+						),
+						(
+							(xattrs::NAME, msgpack!(entry.metadata.xattrs)),
+							xattrs::set(entry.metadata.xattrs),
+						),
 					],
 					[
 						// As this file was updated while Spacedrive was offline, we mark the object_id and cas_id as null
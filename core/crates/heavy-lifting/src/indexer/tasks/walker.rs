@@ -1,8 +1,11 @@
 use crate::{indexer, Error, NonCriticalError};
 
-use sd_core_file_path_helper::{FilePathError, FilePathMetadata, IsolatedFilePathData};
+use sd_core_file_path_helper::{
+	FilePathError, FilePathMetadata, IsolatedFilePathData, TRASH_DIRECTORY,
+};
 use sd_core_indexer_rules::{
 	seed::{GitIgnoreRules, GITIGNORE},
+	symlink::{resolve_symlink, SymlinkPolicy, SymlinkResolution},
 	IndexerRuler, MetadataForIndexerRules, RuleKind,
 };
 use sd_core_prisma_helpers::{file_path_pub_and_cas_ids, file_path_walker};
@@ -121,10 +124,20 @@ pub trait WalkerDBProxy: Clone + Send + Sync + fmt::Debug + 'static {
 	       + Send;
 }
 
+/// How the walker handles the symbolic links it finds, links are only followed if they point within `location_path`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SymlinkHandling {
+	pub policy: SymlinkPolicy,
+	pub location_path: Arc<PathBuf>,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ToWalkEntry {
 	path: PathBuf,
 	parent_dir_accepted_by_its_children: Option<bool>,
+	/// Canonical targets of the directory symlinks followed to get to this path
+	#[serde(default)]
+	followed_symlinks: Vec<PathBuf>,
 }
 
 impl<P: AsRef<Path>> From<P> for ToWalkEntry {
@@ -132,6 +145,7 @@ impl<P: AsRef<Path>> From<P> for ToWalkEntry {
 		Self {
 			path: path.as_ref().into(),
 			parent_dir_accepted_by_its_children: None,
+			followed_symlinks: Vec::new(),
 		}
 	}
 }
@@ -152,34 +166,81 @@ pub struct WalkTaskOutput {
 #[derive(Debug, Serialize, Deserialize)]
 struct InnerMetadata {
 	pub is_dir: bool,
-	pub is_symlink: bool,
 	pub inode: u64,
 	pub size_in_bytes: u64,
 	pub hidden: bool,
 	pub created_at: DateTime<Utc>,
 	pub modified_at: DateTime<Utc>,
+	#[serde(default)]
+	pub symlink_target: Option<String>,
+	#[serde(default)]
+	pub permissions: Option<String>,
+	#[serde(default)]
+	pub owner: Option<String>,
+	#[serde(default)]
+	pub owner_group: Option<String>,
+	#[serde(default)]
+	pub xattrs: Option<String>,
+	/// Canonical target of the symlink followed to get this metadata, if any
+	#[serde(default)]
+	pub followed_symlink: Option<PathBuf>,
 }
 
 impl InnerMetadata {
 	fn new(path: impl AsRef<Path>, metadata: &Metadata) -> Result<Self, indexer::NonCriticalError> {
-		let FilePathMetadata {
+		FilePathMetadata::from_path(path, metadata)
+			.map(|file_path_metadata| Self::with(metadata.is_dir(), file_path_metadata, None))
+			.map_err(|e| indexer::NonCriticalError::FilePathMetadata(e.to_string()))
+	}
+
+	/// The link keeps its own inode, otherwise it would collide with its target on the database
+	fn followed(
+		path: impl AsRef<Path>,
+		link_metadata: &Metadata,
+		target_metadata: &Metadata,
+		canonical_target: PathBuf,
+	) -> Result<Self, indexer::NonCriticalError> {
+		FilePathMetadata::from_followed_symlink(path, link_metadata, target_metadata)
+			.map(|file_path_metadata| {
+				Self::with(
+					target_metadata.is_dir(),
+					file_path_metadata,
+					Some(canonical_target),
+				)
+			})
+			.map_err(|e| indexer::NonCriticalError::FilePathMetadata(e.to_string()))
+	}
+
+	fn with(
+		is_dir: bool,
+		FilePathMetadata {
 			inode,
 			size_in_bytes,
 			created_at,
 			modified_at,
 			hidden,
-		} = FilePathMetadata::from_path(path, metadata)
-			.map_err(|e| indexer::NonCriticalError::FilePathMetadata(e.to_string()))?;
-
-		Ok(Self {
-			is_dir: metadata.is_dir(),
-			is_symlink: metadata.is_symlink(),
+			symlink_target,
+			permissions,
+			owner,
+			owner_group,
+			xattrs,
+		}: FilePathMetadata,
+		followed_symlink: Option<PathBuf>,
+	) -> Self {
+		Self {
+			is_dir,
 			inode,
 			size_in_bytes,
 			hidden,
 			created_at,
 			modified_at,
-		})
+			symlink_target,
+			permissions,
+			owner,
+			owner_group,
+			xattrs,
+			followed_symlink,
+		}
 	}
 }
 
@@ -197,6 +258,11 @@ impl From<InnerMetadata> for FilePathMetadata {
 			hidden: metadata.hidden,
 			created_at: metadata.created_at,
 			modified_at: metadata.modified_at,
+			symlink_target: metadata.symlink_target,
+			permissions: metadata.permissions,
+			owner: metadata.owner,
+			owner_group: metadata.owner_group,
+			xattrs: metadata.xattrs,
 		}
 	}
 }
@@ -237,6 +303,8 @@ struct WalkDirSaveState {
 	entry: ToWalkEntry,
 	root: Arc<PathBuf>,
 	entry_iso_file_path: IsolatedFilePathData<'static>,
+	#[serde(default)]
+	symlink_handling: SymlinkHandling,
 	stage: WalkerStageSaveState,
 	errors: Vec<NonCriticalError>,
 	scan_time: Duration,
@@ -364,6 +432,7 @@ where
 	root: Arc<PathBuf>,
 	entry_iso_file_path: IsolatedFilePathData<'static>,
 	indexer_ruler: IndexerRuler,
+	symlink_handling: SymlinkHandling,
 	iso_file_path_factory: IsoPathFactory,
 	db_proxy: DBProxy,
 	stage: WalkerStage,
@@ -383,6 +452,7 @@ where
 		entry: impl Into<ToWalkEntry> + Send,
 		root: Arc<PathBuf>,
 		indexer_ruler: IndexerRuler,
+		symlink_handling: SymlinkHandling,
 		iso_file_path_factory: IsoPathFactory,
 		db_proxy: DBProxy,
 		dispatcher: Dispatcher,
@@ -392,6 +462,7 @@ where
 			id: TaskId::new_v4(),
 			root,
 			indexer_ruler,
+			symlink_handling,
 			entry_iso_file_path: iso_file_path_factory.build(&entry.path, true)?,
 			iso_file_path_factory,
 			db_proxy,
@@ -414,6 +485,7 @@ where
 		entry: impl Into<ToWalkEntry> + Send,
 		root: Arc<PathBuf>,
 		indexer_ruler: IndexerRuler,
+		symlink_handling: SymlinkHandling,
 		iso_file_path_factory: IsoPathFactory,
 		db_proxy: DBProxy,
 	) -> Result<Self, indexer::Error> {
@@ -422,6 +494,7 @@ where
 			id: TaskId::new_v4(),
 			root,
 			indexer_ruler,
+			symlink_handling,
 			entry_iso_file_path: iso_file_path_factory.build(&entry.path, true)?,
 			iso_file_path_factory,
 			db_proxy,
@@ -452,6 +525,7 @@ where
 			entry,
 			root,
 			entry_iso_file_path,
+			symlink_handling,
 			stage,
 			errors,
 			scan_time,
@@ -463,6 +537,7 @@ where
 			entry,
 			root,
 			entry_iso_file_path,
+			symlink_handling,
 			stage: stage.into(),
 			errors,
 			scan_time,
@@ -480,6 +555,7 @@ where
 			     entry,
 			     root,
 			     entry_iso_file_path,
+			     symlink_handling,
 			     stage,
 			     errors,
 			     scan_time,
@@ -490,6 +566,7 @@ where
 				root,
 				entry_iso_file_path,
 				indexer_ruler,
+				symlink_handling,
 				iso_file_path_factory,
 				db_proxy,
 				stage: stage.into(),
@@ -523,13 +600,16 @@ where
 	async fn run(&mut self, interrupter: &Interrupter) -> Result<ExecStatus, Error> {
 		let Self {
 			root,
-			entry: ToWalkEntry {
-				path,
-				parent_dir_accepted_by_its_children,
-			},
+			entry:
+				ToWalkEntry {
+					path,
+					parent_dir_accepted_by_its_children,
+					followed_symlinks,
+				},
 			entry_iso_file_path,
 			iso_file_path_factory,
 			indexer_ruler,
+			symlink_handling,
 			db_proxy,
 			stage,
 			maybe_dispatcher,
@@ -570,6 +650,8 @@ where
 				} => {
 					while let Some(res) = read_dir_stream.next().await {
 						match res {
+							// The trash of the location is never indexed, whatever its rules are
+							Ok(dir_entry) if dir_entry.file_name() == TRASH_DIRECTORY => {}
 							Ok(dir_entry) => {
 								found_paths.push(dir_entry.path());
 							}
@@ -594,7 +676,13 @@ where
 
 				WalkerStage::CollectingMetadata { found_paths } => {
 					*stage = WalkerStage::CheckingIndexerRules {
-						paths_and_metadatas: collect_metadata(found_paths, errors).await,
+						paths_and_metadatas: collect_metadata(
+							found_paths,
+							symlink_handling,
+							followed_symlinks,
+							errors,
+						)
+						.await,
 					};
 
 					check_interruption!(interrupter, start_time, scan_time);
@@ -623,6 +711,7 @@ where
 						root,
 						iso_file_path_factory,
 						*parent_dir_accepted_by_its_children,
+						followed_symlinks,
 						paths_metadatas_and_acceptance,
 						&mut maybe_to_keep_walking,
 						errors,
@@ -675,6 +764,7 @@ where
 					let handles = keep_walking(
 						root,
 						indexer_ruler,
+						symlink_handling,
 						iso_file_path_factory,
 						db_proxy,
 						maybe_to_keep_walking,
@@ -755,6 +845,11 @@ async fn segregate_creates_and_updates(
 								// instead of using != operator
 								|| DateTime::<FixedOffset>::from(metadata.modified_at) - *date_modified
 									> ChronoDuration::milliseconds(1) || file_path.hidden.is_none() || metadata.hidden != file_path.hidden.unwrap_or_default()
+								// Permissions, ownership and extended attributes don't touch the modification date
+								|| metadata.permissions != file_path.permissions
+								|| metadata.owner != file_path.owner
+								|| metadata.owner_group != file_path.owner_group
+								|| metadata.xattrs != file_path.xattrs
 							)
 							// We ignore the size of directories because it is not reliable, we need to
 							// calculate it ourselves later
@@ -796,6 +891,7 @@ async fn segregate_creates_and_updates(
 async fn keep_walking(
 	root: &Arc<PathBuf>,
 	indexer_ruler: &IndexerRuler,
+	symlink_handling: &SymlinkHandling,
 	iso_file_path_factory: &impl IsoFilePathFactory,
 	db_proxy: &impl WalkerDBProxy,
 	maybe_to_keep_walking: &mut Option<Vec<ToWalkEntry>>,
@@ -812,6 +908,7 @@ async fn keep_walking(
 							entry,
 							Arc::clone(root),
 							indexer_ruler.clone(),
+							symlink_handling.clone(),
 							iso_file_path_factory.clone(),
 							db_proxy.clone(),
 							dispatcher.clone(),
@@ -828,28 +925,53 @@ async fn keep_walking(
 
 async fn collect_metadata(
 	found_paths: &mut Vec<PathBuf>,
+	symlink_handling: &SymlinkHandling,
+	followed_symlinks: &[PathBuf],
 	errors: &mut Vec<NonCriticalError>,
 ) -> HashMap<PathBuf, InnerMetadata> {
 	found_paths
 		.drain(..)
 		.map(|current_path| async move {
-			fs::metadata(&current_path)
-				.await
-				.map_err(|e| {
-					indexer::NonCriticalError::Metadata(
-						FileIOError::from((&current_path, e)).to_string(),
-					)
-				})
-				.and_then(|metadata| {
-					InnerMetadata::new(&current_path, &metadata)
-						.map(|metadata| (current_path, metadata))
-				})
+			let metadata = fs::symlink_metadata(&current_path).await.map_err(|e| {
+				indexer::NonCriticalError::Metadata(
+					FileIOError::from((&current_path, e)).to_string(),
+				)
+			})?;
+
+			if !metadata.is_symlink() {
+				return InnerMetadata::new(&current_path, &metadata)
+					.map(|metadata| Some((current_path, metadata)));
+			}
+
+			match resolve_symlink(
+				&current_path,
+				symlink_handling.policy,
+				symlink_handling.location_path.as_ref(),
+				followed_symlinks,
+			)
+			.await
+			.map_err(|e| indexer::NonCriticalError::Metadata(e.to_string()))?
+			{
+				SymlinkResolution::Skip => Ok(None),
+				SymlinkResolution::Alias => InnerMetadata::new(&current_path, &metadata)
+					.map(|metadata| Some((current_path, metadata))),
+				SymlinkResolution::Follow {
+					metadata: target_metadata,
+					canonical_target,
+				} => InnerMetadata::followed(
+					&current_path,
+					&metadata,
+					&target_metadata,
+					canonical_target,
+				)
+				.map(|metadata| Some((current_path, metadata))),
+			}
 		})
 		.collect::<Vec<_>>()
 		.join()
 		.await
 		.into_iter()
-		.filter_map(|res| res.map_err(|e| errors.push(e.into())).ok())
+		.filter_map(|res| res.map_err(|e| errors.push(e.into())).ok().flatten())
 		.collect()
 }
 
@@ -860,8 +982,6 @@ async fn apply_indexer_rules(
 ) -> HashMap<PathBuf, (InnerMetadata, HashMap<RuleKind, Vec<bool>>)> {
 	paths_and_metadatas
 		.drain()
-		// TODO: Hard ignoring symlinks for now, but this should be configurable
-		.filter(|(_, metadata)| !metadata.is_symlink)
 		.map(|(current_path, metadata)| async {
 			indexer_ruler
 				.apply_all(&current_path, &metadata)
@@ -883,6 +1003,7 @@ async fn process_rules_results(
 	root: &Arc<PathBuf>,
 	iso_file_path_factory: &impl IsoFilePathFactory,
 	parent_dir_accepted_by_its_children: Option<bool>,
+	followed_symlinks: &[PathBuf],
 	paths_metadatas_and_acceptance: &mut HashMap<
 		PathBuf,
 		(InnerMetadata, HashMap<RuleKind, Vec<bool>>),
@@ -919,6 +1040,11 @@ async fn process_rules_results(
 					&current_path,
 					&acceptance_per_rule_kind,
 					&mut accept_by_children_dir,
+					followed_symlinks
+						.iter()
+						.chain(&metadata.followed_symlink)
+						.cloned()
+						.collect(),
 					maybe_to_keep_walking,
 				) {
 				trace!(
@@ -989,6 +1115,7 @@ fn process_and_maybe_reject_by_directory_rules(
 	current_path: &Path,
 	acceptance_per_rule_kind: &HashMap<RuleKind, Vec<bool>>,
 	accept_by_children_dir: &mut Option<bool>,
+	followed_symlinks: Vec<PathBuf>,
 	maybe_to_keep_walking: &mut Option<Vec<ToWalkEntry>>,
 ) -> bool {
 	// If it is a directory, first we check if we must reject it and its children entirely
@@ -1019,6 +1146,7 @@ fn process_and_maybe_reject_by_directory_rules(
 		to_keep_walking.push(ToWalkEntry {
 			path: current_path.to_path_buf(),
 			parent_dir_accepted_by_its_children: *accept_by_children_dir,
+			followed_symlinks,
 		});
 	}
 
@@ -1312,6 +1440,22 @@ mod tests {
 		indexer_ruler: IndexerRuler,
 		expected: HashSet<WalkedEntry>,
 	) {
+		let actual_set = walk(root_path, indexer_ruler, SymlinkHandling::default()).await;
+
+		assert_eq!(
+			actual_set,
+			expected,
+			"Expected \\ Actual: {:#?};\n Actual \\ Expected: {:#?}",
+			expected.difference(&actual_set),
+			actual_set.difference(&expected)
+		);
+	}
+
+	async fn walk(
+		root_path: &Path,
+		indexer_ruler: IndexerRuler,
+		symlink_handling: SymlinkHandling,
+	) -> HashSet<WalkedEntry> {
 		let system = TaskSystem::new();
 
 		let handle = system
@@ -1320,6 +1464,7 @@ mod tests {
 					root_path.to_path_buf(),
 					Arc::new(root_path.to_path_buf()),
 					indexer_ruler,
+					symlink_handling,
 					DummyIsoPathFactory {
 						root_path: Arc::new(root_path.to_path_buf()),
 					},
@@ -1372,13 +1517,7 @@ mod tests {
 			actual_set.extend(ancestors);
 		}
 
-		assert_eq!(
-			actual_set,
-			expected,
-			"Expected \\ Actual: {:#?};\n Actual \\ Expected: {:#?}",
-			expected.difference(&actual_set),
-			actual_set.difference(&expected)
-		);
+		actual_set
 	}
 
 	#[tokio::test]
@@ -1393,6 +1532,11 @@ mod tests {
 			created_at: Utc::now(),
 			modified_at: Utc::now(),
 			hidden: false,
+			symlink_target: None,
+			permissions: None,
+			owner: None,
+			owner_group: None,
+			xattrs: None,
 		};
 
 		let f = |path, is_dir| IsolatedFilePathData::new(0, root_path, path, is_dir).unwrap();
@@ -1401,29 +1545,29 @@ mod tests {
 
 		#[rustfmt::skip]
 		let expected = [
-			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("rust_project"), true), metadata },
-			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("rust_project/.git"), true), metadata },
-			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("rust_project/.gitignore"), false), metadata },
-			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("rust_project/Cargo.toml"), false), metadata },
-			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("rust_project/partial"), true), metadata },
-			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("rust_project/partial/readme"), false), metadata },
-			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("rust_project/src"), true), metadata },
-			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("rust_project/src/main.rs"), false), metadata },
-			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("inner"), true), metadata },
-			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("inner/node_project"), true), metadata },
-			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("inner/node_project/.git"), true), metadata },
-			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("inner/node_project/.gitignore"), false), metadata },
-			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("inner/node_project/package.json"), false), metadata },
-			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("inner/node_project/src"), true), metadata },
-			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("inner/node_project/src/App.tsx"), false), metadata },
-			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("inner/node_project/node_modules"), true), metadata },
-			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("inner/node_project/node_modules/react"), true), metadata },
-			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("inner/node_project/node_modules/react/package.json"), false), metadata },
-			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("photos"), true), metadata },
-			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("photos/photo1.png"), false), metadata },
-			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("photos/photo2.jpg"), false), metadata },
-			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("photos/photo3.jpeg"), false), metadata },
-			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("photos/text.txt"), false), metadata },
+			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("rust_project"), true), metadata: metadata.clone() },
+			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("rust_project/.git"), true), metadata: metadata.clone() },
+			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("rust_project/.gitignore"), false), metadata: metadata.clone() },
+			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("rust_project/Cargo.toml"), false), metadata: metadata.clone() },
+			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("rust_project/partial"), true), metadata: metadata.clone() },
+			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("rust_project/partial/readme"), false), metadata: metadata.clone() },
+			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("rust_project/src"), true), metadata: metadata.clone() },
+			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("rust_project/src/main.rs"), false), metadata: metadata.clone() },
+			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("inner"), true), metadata: metadata.clone() },
+			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("inner/node_project"), true), metadata: metadata.clone() },
+			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("inner/node_project/.git"), true), metadata: metadata.clone() },
+			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("inner/node_project/.gitignore"), false), metadata: metadata.clone() },
+			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("inner/node_project/package.json"), false), metadata: metadata.clone() },
+			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("inner/node_project/src"), true), metadata: metadata.clone() },
+			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("inner/node_project/src/App.tsx"), false), metadata: metadata.clone() },
+			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("inner/node_project/node_modules"), true), metadata: metadata.clone() },
+			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("inner/node_project/node_modules/react"), true), metadata: metadata.clone() },
+			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("inner/node_project/node_modules/react/package.json"), false), metadata: metadata.clone() },
+			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("photos"), true), metadata: metadata.clone() },
+			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("photos/photo1.png"), false), metadata: metadata.clone() },
+			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("photos/photo2.jpg"), false), metadata: metadata.clone() },
+			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("photos/photo3.jpeg"), false), metadata: metadata.clone() },
+			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("photos/text.txt"), false), metadata: metadata.clone() },
 		]
 		.into_iter()
 		.collect::<HashSet<_>>();
@@ -1443,6 +1587,11 @@ mod tests {
 			created_at: Utc::now(),
 			modified_at: Utc::now(),
 			hidden: false,
+			symlink_target: None,
+			permissions: None,
+			owner: None,
+			owner_group: None,
+			xattrs: None,
 		};
 
 		let f = |path, is_dir| IsolatedFilePathData::new(0, root_path, path, is_dir).unwrap();
@@ -1451,10 +1600,10 @@ mod tests {
 
 		#[rustfmt::skip]
 		let expected = [
-			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("photos"), true), metadata },
-			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("photos/photo1.png"), false), metadata },
-			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("photos/photo2.jpg"), false), metadata },
-			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("photos/photo3.jpeg"), false), metadata },
+			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("photos"), true), metadata: metadata.clone() },
+			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("photos/photo1.png"), false), metadata: metadata.clone() },
+			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("photos/photo2.jpg"), false), metadata: metadata.clone() },
+			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("photos/photo3.jpeg"), false), metadata: metadata.clone() },
 		]
 		.into_iter()
 		.collect::<HashSet<_>>();
@@ -1489,6 +1638,11 @@ mod tests {
 			created_at: Utc::now(),
 			modified_at: Utc::now(),
 			hidden: false,
+			symlink_target: None,
+			permissions: None,
+			owner: None,
+			owner_group: None,
+			xattrs: None,
 		};
 
 		let f = |path, is_dir| IsolatedFilePathData::new(0, root_path, path, is_dir).unwrap();
@@ -1497,24 +1651,24 @@ mod tests {
 
 		#[rustfmt::skip]
 		let expected = [
-			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("rust_project"), true), metadata },
-			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("rust_project/.git"), true), metadata },
-			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("rust_project/.gitignore"), false), metadata },
-			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("rust_project/Cargo.toml"), false), metadata },
-			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("rust_project/src"), true), metadata },
-			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("rust_project/src/main.rs"), false), metadata },
-			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("rust_project/partial"), true), metadata },
-			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("rust_project/partial/readme"), false), metadata },
-			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("inner"), true), metadata },
-			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("inner/node_project"), true), metadata },
-			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("inner/node_project/.git"), true), metadata },
-			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("inner/node_project/package.json"), false), metadata },
-			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("inner/node_project/.gitignore"), false), metadata },
-			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("inner/node_project/src"), true), metadata },
-			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("inner/node_project/src/App.tsx"), false), metadata },
-			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("inner/node_project/node_modules"), true), metadata },
-			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("inner/node_project/node_modules/react"), true), metadata },
-			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("inner/node_project/node_modules/react/package.json"), false), metadata },
+			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("rust_project"), true), metadata: metadata.clone() },
+			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("rust_project/.git"), true), metadata: metadata.clone() },
+			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("rust_project/.gitignore"), false), metadata: metadata.clone() },
+			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("rust_project/Cargo.toml"), false), metadata: metadata.clone() },
+			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("rust_project/src"), true), metadata: metadata.clone() },
+			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("rust_project/src/main.rs"), false), metadata: metadata.clone() },
+			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("rust_project/partial"), true), metadata: metadata.clone() },
+			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("rust_project/partial/readme"), false), metadata: metadata.clone() },
+			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("inner"), true), metadata: metadata.clone() },
+			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("inner/node_project"), true), metadata: metadata.clone() },
+			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("inner/node_project/.git"), true), metadata: metadata.clone() },
+			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("inner/node_project/package.json"), false), metadata: metadata.clone() },
+			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("inner/node_project/.gitignore"), false), metadata: metadata.clone() },
+			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("inner/node_project/src"), true), metadata: metadata.clone() },
+			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("inner/node_project/src/App.tsx"), false), metadata: metadata.clone() },
+			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("inner/node_project/node_modules"), true), metadata: metadata.clone() },
+			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("inner/node_project/node_modules/react"), true), metadata: metadata.clone() },
+			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("inner/node_project/node_modules/react/package.json"), false), metadata: metadata.clone() },
 		]
 		.into_iter()
 		.collect::<HashSet<_>>();
@@ -1545,6 +1699,11 @@ mod tests {
 			created_at: Utc::now(),
 			modified_at: Utc::now(),
 			hidden: false,
+			symlink_target: None,
+			permissions: None,
+			owner: None,
+			owner_group: None,
+			xattrs: None,
 		};
 
 		let f = |path, is_dir| IsolatedFilePathData::new(0, root_path, path, is_dir).unwrap();
@@ -1553,21 +1712,21 @@ mod tests {
 
 		#[rustfmt::skip]
 		let expected = [
-			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("rust_project"), true), metadata },
-			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("rust_project/.git"), true), metadata },
-			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("rust_project/.gitignore"), false), metadata },
-			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("rust_project/Cargo.toml"), false), metadata },
-			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("rust_project/partial"), true), metadata },
-			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("rust_project/partial/readme"), false), metadata },
-			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("rust_project/src"), true), metadata },
-			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("rust_project/src/main.rs"), false), metadata },
-			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("inner"), true), metadata },
-			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("inner/node_project"), true), metadata },
-			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("inner/node_project/.git"), true), metadata },
-			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("inner/node_project/.gitignore"), false), metadata },
-			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("inner/node_project/package.json"), false), metadata },
-			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("inner/node_project/src"), true), metadata },
-			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("inner/node_project/src/App.tsx"), false), metadata },
+			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("rust_project"), true), metadata: metadata.clone() },
+			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("rust_project/.git"), true), metadata: metadata.clone() },
+			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("rust_project/.gitignore"), false), metadata: metadata.clone() },
+			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("rust_project/Cargo.toml"), false), metadata: metadata.clone() },
+			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("rust_project/partial"), true), metadata: metadata.clone() },
+			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("rust_project/partial/readme"), false), metadata: metadata.clone() },
+			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("rust_project/src"), true), metadata: metadata.clone() },
+			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("rust_project/src/main.rs"), false), metadata: metadata.clone() },
+			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("inner"), true), metadata: metadata.clone() },
+			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("inner/node_project"), true), metadata: metadata.clone() },
+			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("inner/node_project/.git"), true), metadata: metadata.clone() },
+			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("inner/node_project/.gitignore"), false), metadata: metadata.clone() },
+			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("inner/node_project/package.json"), false), metadata: metadata.clone() },
+			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("inner/node_project/src"), true), metadata: metadata.clone() },
+			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("inner/node_project/src/App.tsx"), false), metadata: metadata.clone() },
 		]
 		.into_iter()
 		.collect::<HashSet<_>>();
@@ -1609,4 +1768,72 @@ mod tests {
 		)
 		.await;
 	}
+
+	#[tokio::test]
+	#[traced_test]
+	async fn trash_is_never_walked() {
+		let root = tempdir().unwrap();
+		let root_path = root.path();
+
+		fs::create_dir_all(root_path.join(TRASH_DIRECTORY).join("files"))
+			.await
+			.unwrap();
+		fs::write(root_path.join(TRASH_DIRECTORY).join("files/old.txt"), b"")
+			.await
+			.unwrap();
+		fs::write(root_path.join("new.txt"), b"").await.unwrap();
+
+		let walked = walk(
+			root_path,
+			IndexerRuler::default(),
+			SymlinkHandling::default(),
+		)
+		.await;
+
+		assert_eq!(
+			walked
+				.iter()
+				.map(|entry| entry.iso_file_path.to_parts().name)
+				.collect::<Vec<_>>(),
+			["new"]
+		);
+	}
+
+	#[cfg(unix)]
+	#[tokio::test]
+	#[traced_test]
+	async fn followed_symlinks_keep_their_own_inode() {
+		let root = tempdir().unwrap();
+		let root_path = root.path();
+
+		fs::write(root_path.join("photo.png"), b"not really a photo")
+			.await
+			.unwrap();
+		fs::symlink(root_path.join("photo.png"), root_path.join("link.png"))
+			.await
+			.unwrap();
+
+		let walked = walk(
+			root_path,
+			IndexerRuler::default(),
+			SymlinkHandling {
+				policy: SymlinkPolicy::Follow,
+				location_path: Arc::new(root_path.to_path_buf()),
+			},
+		)
+		.await;
+
+		let entry = |name| {
+			walked
+				.iter()
+				.find(|entry| entry.iso_file_path.to_parts().name == name)
+				.unwrap()
+		};
+		let (photo, link) = (entry("photo"), entry("link"));
+
+		// Both are saved in the same location, so they can't share an inode
+		assert_ne!(photo.metadata.inode, link.metadata.inode);
+		assert_eq!(photo.metadata.size_in_bytes, link.metadata.size_in_bytes);
+		assert!(link.metadata.symlink_target.is_none());
+	}
 }
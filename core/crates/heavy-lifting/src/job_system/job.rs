@@ -48,6 +48,7 @@ pub enum JobName {
 	Indexer,
 	FileIdentifier,
 	MediaProcessor,
+	FileEraser,
 	// TODO: Add more job names as needed
 }
 
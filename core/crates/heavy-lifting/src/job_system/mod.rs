@@ -4,7 +4,13 @@ use sd_prisma::prisma::location;
 use sd_task_system::BaseTaskDispatcher;
 use sd_utils::error::FileIOError;
 
-use std::{cell::RefCell, collections::hash_map::HashMap, path::Path, sync::Arc};
+use std::{
+	cell::RefCell,
+	collections::hash_map::HashMap,
+	io,
+	path::{Path, PathBuf},
+	sync::Arc,
+};
 
 use async_channel as chan;
 use futures::Stream;
@@ -42,14 +48,15 @@ pub struct JobSystem<Ctx: OuterContext> {
 	msgs_tx: chan::Sender<RunnerMessage<Ctx>>,
 	job_outputs_rx: chan::Receiver<(JobId, Result<JobOutput, JobSystemError>)>,
 	runner_handle: RefCell<Option<JoinHandle<()>>>,
+	store_jobs_file: Arc<PathBuf>,
 }
 
 impl<Ctx: OuterContext> JobSystem<Ctx> {
-	pub async fn new(
+	/// Starts the job system, jobs left pending on the last shutdown are only resumed by [`JobSystem::init`]
+	pub fn new(
 		base_dispatcher: BaseTaskDispatcher<Error>,
 		data_directory: impl AsRef<Path> + Send,
-		previously_existing_contexts: &HashMap<Uuid, Ctx>,
-	) -> Result<Self, JobSystemError> {
+	) -> Self {
 		let (job_outputs_tx, job_outputs_rx) = chan::unbounded();
 		let (job_return_status_tx, job_return_status_rx) = chan::bounded(16);
 		let (msgs_tx, msgs_rx) = chan::bounded(8);
@@ -97,18 +104,25 @@ impl<Ctx: OuterContext> JobSystem<Ctx> {
 			}
 		})));
 
-		load_stored_job_entries(
-			store_jobs_file.as_ref(),
-			previously_existing_contexts,
-			&msgs_tx,
-		)
-		.await?;
-
-		Ok(Self {
+		Self {
 			msgs_tx,
 			job_outputs_rx,
 			runner_handle,
-		})
+			store_jobs_file,
+		}
+	}
+
+	/// Resumes the jobs left pending on the last shutdown, once the contexts they ran in are available again
+	pub async fn init(
+		&self,
+		previously_existing_contexts: &HashMap<Uuid, Ctx>,
+	) -> Result<(), JobSystemError> {
+		load_stored_job_entries(
+			self.store_jobs_file.as_ref(),
+			previously_existing_contexts,
+			&self.msgs_tx,
+		)
+		.await
 	}
 
 	/// Checks if *any* of the desired jobs is running for the desired location
@@ -165,7 +179,7 @@ impl<Ctx: OuterContext> JobSystem<Ctx> {
 	/// # Panics
 	/// Panics only happen if internal channels are unexpectedly closed
 	pub async fn dispatch<J: Job + SerializableJob<Ctx>>(
-		&mut self,
+		&self,
 		job: impl IntoJob<J, Ctx> + Send,
 		location_id: location::id::Type,
 		ctx: Ctx,
@@ -239,15 +253,21 @@ async fn load_stored_job_entries<Ctx: OuterContext>(
 ) -> Result<(), JobSystemError> {
 	let store_jobs_file = store_jobs_file.as_ref();
 
-	let stores_jobs_by_db = rmp_serde::from_slice::<HashMap<Uuid, Vec<StoredJobEntry>>>(
-		&fs::read(store_jobs_file).await.map_err(|e| {
-			JobSystemError::StoredJobs(FileIOError::from((
+	let stored_jobs_bytes = match fs::read(store_jobs_file).await {
+		Ok(bytes) => bytes,
+		// No jobs were left pending on the last shutdown
+		Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(()),
+		Err(e) => {
+			return Err(JobSystemError::StoredJobs(FileIOError::from((
 				store_jobs_file,
 				e,
 				"Failed to load jobs from disk",
-			)))
-		})?,
-	)?;
+			))))
+		}
+	};
+
+	let stores_jobs_by_db =
+		rmp_serde::from_slice::<HashMap<Uuid, Vec<StoredJobEntry>>>(&stored_jobs_bytes)?;
 
 	stores_jobs_by_db
 		.into_iter()
@@ -1,4 +1,4 @@
-use crate::{file_identifier, indexer, media_processor};
+use crate::{file_eraser, file_identifier, indexer, media_processor};
 
 use sd_prisma::prisma::{job, location};
 use sd_utils::uuid_to_bytes;
@@ -214,6 +214,7 @@ async fn load_job<Ctx: OuterContext>(
 			indexer::job::Indexer,
 			file_identifier::job::FileIdentifier,
 			media_processor::job::MediaProcessor,
+			file_eraser::job::FileEraser,
 			// TODO: Add more jobs here
 		]
 	)
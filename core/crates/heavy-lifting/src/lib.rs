@@ -35,6 +35,7 @@ use serde::{Deserialize, Serialize};
 use specta::Type;
 use thiserror::Error;
 
+pub mod file_eraser;
 pub mod file_identifier;
 pub mod indexer;
 pub mod job_system;
@@ -56,6 +57,8 @@ pub enum Error {
 	FileIdentifier(#[from] file_identifier::Error),
 	#[error(transparent)]
 	MediaProcessor(#[from] media_processor::Error),
+	#[error(transparent)]
+	FileEraser(#[from] file_eraser::Error),
 
 	#[error(transparent)]
 	TaskSystem(#[from] TaskSystemError),
@@ -67,6 +70,7 @@ impl From<Error> for rspc::Error {
 			Error::Indexer(e) => e.into(),
 			Error::FileIdentifier(e) => e.into(),
 			Error::MediaProcessor(e) => e.into(),
+			Error::FileEraser(e) => e.into(),
 			Error::TaskSystem(e) => {
 				Self::with_cause(rspc::ErrorCode::InternalServerError, e.to_string(), e)
 			}
@@ -83,6 +87,8 @@ pub enum NonCriticalError {
 	FileIdentifier(#[from] file_identifier::NonCriticalError),
 	#[error(transparent)]
 	MediaProcessor(#[from] media_processor::NonCriticalError),
+	#[error(transparent)]
+	FileEraser(#[from] file_eraser::NonCriticalError),
 }
 
 #[repr(i32)]
@@ -222,14 +222,19 @@ async fn create_ffmpeg_chapters(
 						time_base_den,
 						time_base_num,
 						ffmpeg_data_id,
-						_params: vec![ffmpeg_media_chapter::metadata::set(
-							serde_json::to_vec(&metadata)
-								.map_err(|err| {
-									error!("Error reading FFmpegMediaChapter metadata: {err:#?}");
-									err
-								})
-								.ok(),
-						)],
+						_params: vec![
+							ffmpeg_media_chapter::title::set(metadata.title.clone()),
+							ffmpeg_media_chapter::metadata::set(
+								serde_json::to_vec(&metadata)
+									.map_err(|err| {
+										error!(
+											"Error reading FFmpegMediaChapter metadata: {err:#?}"
+										);
+										err
+									})
+									.ok(),
+							),
+						],
 					},
 				)
 				.collect(),
@@ -1,10 +1,14 @@
 use once_cell::sync::Lazy;
 use sd_file_ext::extensions::{
-	DocumentExtension, Extension, ImageExtension, ALL_DOCUMENT_EXTENSIONS, ALL_IMAGE_EXTENSIONS,
+	BookExtension, DocumentExtension, Extension, FontExtension, ImageExtension, MeshExtension,
+	ALL_BOOK_EXTENSIONS, ALL_DOCUMENT_EXTENSIONS, ALL_FONT_EXTENSIONS, ALL_IMAGE_EXTENSIONS,
+	ALL_MESH_EXTENSIONS,
 };
 
 #[cfg(feature = "ffmpeg")]
-use sd_file_ext::extensions::{VideoExtension, ALL_VIDEO_EXTENSIONS};
+use sd_file_ext::extensions::{
+	AudioExtension, VideoExtension, ALL_AUDIO_EXTENSIONS, ALL_VIDEO_EXTENSIONS,
+};
 
 use std::time::Duration;
 
@@ -38,6 +42,16 @@ pub static THUMBNAILABLE_VIDEO_EXTENSIONS: Lazy<Vec<Extension>> = Lazy::new(|| {
 		.collect()
 });
 
+#[cfg(feature = "ffmpeg")]
+pub static THUMBNAILABLE_AUDIO_EXTENSIONS: Lazy<Vec<Extension>> = Lazy::new(|| {
+	ALL_AUDIO_EXTENSIONS
+		.iter()
+		.copied()
+		.filter(|&ext| can_generate_thumbnail_for_audio(ext))
+		.map(Extension::Audio)
+		.collect()
+});
+
 pub static THUMBNAILABLE_EXTENSIONS: Lazy<Vec<Extension>> = Lazy::new(|| {
 	ALL_IMAGE_EXTENSIONS
 		.iter()
@@ -51,6 +65,27 @@ pub static THUMBNAILABLE_EXTENSIONS: Lazy<Vec<Extension>> = Lazy::new(|| {
 				.filter(|&ext| can_generate_thumbnail_for_document(ext))
 				.map(Extension::Document),
 		)
+		.chain(
+			ALL_FONT_EXTENSIONS
+				.iter()
+				.copied()
+				.filter(|&ext| can_generate_thumbnail_for_font(ext))
+				.map(Extension::Font),
+		)
+		.chain(
+			ALL_MESH_EXTENSIONS
+				.iter()
+				.copied()
+				.filter(|&ext| can_generate_thumbnail_for_mesh(ext))
+				.map(Extension::Mesh),
+		)
+		.chain(
+			ALL_BOOK_EXTENSIONS
+				.iter()
+				.copied()
+				.filter(|&ext| can_generate_thumbnail_for_book(ext))
+				.map(Extension::Book),
+		)
 		.collect()
 });
 
@@ -60,6 +95,7 @@ pub static ALL_THUMBNAILABLE_EXTENSIONS: Lazy<Vec<Extension>> = Lazy::new(|| {
 		.iter()
 		.cloned()
 		.chain(THUMBNAILABLE_VIDEO_EXTENSIONS.iter().cloned())
+		.chain(THUMBNAILABLE_AUDIO_EXTENSIONS.iter().cloned())
 		.collect();
 
 	#[cfg(not(feature = "ffmpeg"))]
@@ -117,14 +153,34 @@ pub const fn can_generate_thumbnail_for_video(video_extension: VideoExtension) -
 	!matches!(video_extension, Mpg | Swf | M2v | Hevc | M2ts | Mts | Ts)
 }
 
+/// Audio files only have a thumbnail if they have embedded cover art, which we only find out
+/// when trying to extract it, so we only try for formats that FFmpeg can read cover art from
+#[cfg(feature = "ffmpeg")]
+pub const fn can_generate_thumbnail_for_audio(audio_extension: AudioExtension) -> bool {
+	use AudioExtension::{Aif, Aiff, Flac, M4a, Mp3, Oga, Ogg, Opus, Tta, Wav, Wma, Wv};
+
+	matches!(
+		audio_extension,
+		Mp3 | M4a | Flac | Ogg | Oga | Opus | Wma | Aiff | Aif | Wav | Wv | Tta
+	)
+}
+
 pub const fn can_generate_thumbnail_for_image(image_extension: ImageExtension) -> bool {
 	use ImageExtension::{
-		Avif, Bmp, Gif, Heic, Heics, Heif, Heifs, Ico, Jpeg, Jpg, Png, Svg, Webp,
+		Arw, Avif, Bmp, Cr2, Cr3, Dng, Gif, Heic, Heics, Heif, Heifs, Ico, Jpeg, Jpg, Nef, Png,
+		Raf, Rw2, Svg, Webp,
 	};
 
 	matches!(
 		image_extension,
-		Jpg | Jpeg | Png | Webp | Gif | Svg | Heic | Heics | Heif | Heifs | Avif | Bmp | Ico
+		Jpg | Jpeg
+			| Png | Webp | Gif
+			| Svg | Heic | Heics
+			| Heif | Heifs
+			| Avif | Bmp | Ico
+			| Dng | Cr2 | Cr3
+			| Nef | Arw | Raf
+			| Rw2
 	)
 }
 
@@ -133,3 +189,21 @@ pub const fn can_generate_thumbnail_for_document(document_extension: DocumentExt
 
 	matches!(document_extension, Pdf)
 }
+
+pub const fn can_generate_thumbnail_for_font(font_extension: FontExtension) -> bool {
+	use FontExtension::{Otf, Ttf};
+
+	matches!(font_extension, Ttf | Otf)
+}
+
+pub const fn can_generate_thumbnail_for_mesh(mesh_extension: MeshExtension) -> bool {
+	use MeshExtension::{Glb, Gltf, Obj, Stl};
+
+	matches!(mesh_extension, Obj | Stl | Gltf | Glb)
+}
+
+pub const fn can_generate_thumbnail_for_book(book_extension: BookExtension) -> bool {
+	use BookExtension::Epub;
+
+	matches!(book_extension, Epub)
+}
@@ -12,8 +12,10 @@ use crate::{
 	media_processor::{
 		self,
 		helpers::thumbnailer::{
-			can_generate_thumbnail_for_document, can_generate_thumbnail_for_image, get_shard_hex,
-			EPHEMERAL_DIR, TARGET_PX, TARGET_QUALITY, THUMBNAIL_GENERATION_TIMEOUT, WEBP_EXTENSION,
+			can_generate_thumbnail_for_book, can_generate_thumbnail_for_document,
+			can_generate_thumbnail_for_font, can_generate_thumbnail_for_image,
+			can_generate_thumbnail_for_mesh, get_shard_hex, EPHEMERAL_DIR, TARGET_PX,
+			TARGET_QUALITY, THUMBNAIL_GENERATION_TIMEOUT, WEBP_EXTENSION,
 		},
 		ThumbKey, ThumbnailKind,
 	},
@@ -23,7 +25,9 @@ use crate::{
 use sd_core_file_path_helper::IsolatedFilePathData;
 use sd_core_prisma_helpers::file_path_for_media_processor;
 
-use sd_file_ext::extensions::{DocumentExtension, ImageExtension};
+use sd_file_ext::extensions::{
+	BookExtension, DocumentExtension, FontExtension, ImageExtension, MeshExtension,
+};
 use sd_images::{format_image, scale_dimensions, ConvertibleExtension};
 use sd_media_metadata::exif::Orientation;
 use sd_prisma::prisma::{file_path, location};
@@ -232,6 +236,8 @@ pub enum NonCriticalError {
 	FailedToExtractIsolatedFilePathData(file_path::id::Type, String),
 	#[error("failed to generate video file thumbnail <path='{}'>: {1}", .0.display())]
 	VideoThumbnailGenerationFailed(PathBuf, String),
+	#[error("failed to extract audio file cover art <path='{}'>: {1}", .0.display())]
+	AudioCoverArtExtractionFailed(PathBuf, String),
 	#[error("failed to format image <path='{}'>: {1}", .0.display())]
 	FormatImage(PathBuf, String),
 	#[error("failed to encode webp image <path='{}'>: {1}", .0.display())]
@@ -559,12 +565,32 @@ async fn generate_thumbnail(
 				return (start.elapsed(), Err(e));
 			}
 		}
+	} else if let Ok(extension) = FontExtension::from_str(extension) {
+		if can_generate_thumbnail_for_font(extension) {
+			if let Err(e) = generate_image_thumbnail(&path, &output_path).await {
+				return (start.elapsed(), Err(e));
+			}
+		}
+	} else if let Ok(extension) = MeshExtension::from_str(extension) {
+		if can_generate_thumbnail_for_mesh(extension) {
+			if let Err(e) = generate_image_thumbnail(&path, &output_path).await {
+				return (start.elapsed(), Err(e));
+			}
+		}
+	} else if let Ok(extension) = BookExtension::from_str(extension) {
+		if can_generate_thumbnail_for_book(extension) {
+			if let Err(e) = generate_image_thumbnail(&path, &output_path).await {
+				return (start.elapsed(), Err(e));
+			}
+		}
 	}
 
 	#[cfg(feature = "ffmpeg")]
 	{
-		use crate::media_processor::helpers::thumbnailer::can_generate_thumbnail_for_video;
-		use sd_file_ext::extensions::VideoExtension;
+		use crate::media_processor::helpers::thumbnailer::{
+			can_generate_thumbnail_for_audio, can_generate_thumbnail_for_video,
+		};
+		use sd_file_ext::extensions::{AudioExtension, VideoExtension};
 
 		if let Ok(extension) = VideoExtension::from_str(extension) {
 			if can_generate_thumbnail_for_video(extension) {
@@ -572,6 +598,20 @@ async fn generate_thumbnail(
 					return (start.elapsed(), Err(e));
 				}
 			}
+		} else if let Ok(extension) = AudioExtension::from_str(extension) {
+			if can_generate_thumbnail_for_audio(extension) {
+				match generate_cover_art_thumbnail(&path, &output_path).await {
+					Ok(true) => {}
+					Ok(false) => {
+						trace!("No cover art found in {}", path.display());
+						return (
+							start.elapsed(),
+							Ok((ThumbKey::new(cas_id, kind), GenerationStatus::Skipped)),
+						);
+					}
+					Err(e) => return (start.elapsed(), Err(e)),
+				}
+			}
 		}
 	}
 
@@ -614,9 +654,9 @@ async fn generate_image_thumbnail(
 			// this corrects the rotation/flip of the image based on the *available* exif data
 			// not all images have exif data, so we don't error. we also don't rotate HEIF as that's against the spec
 			if let Some(orientation) = Orientation::from_path(&file_path) {
+				// Fonts, meshes and ebooks aren't convertible, and don't need to be rotated either
 				if ConvertibleExtension::try_from(file_path.as_ref())
-					.expect("we already checked if the image was convertible")
-					.should_rotate()
+					.is_ok_and(ConvertibleExtension::should_rotate)
 				{
 					img = orientation.correct_thumbnail(img);
 				}
@@ -675,3 +715,25 @@ async fn generate_video_thumbnail(
 		NonCriticalError::VideoThumbnailGenerationFailed(file_path.to_path_buf(), e.to_string())
 	})
 }
+
+/// Returns `false` if the audio file has no embedded cover art
+#[cfg(feature = "ffmpeg")]
+async fn generate_cover_art_thumbnail(
+	file_path: impl AsRef<Path> + Send,
+	output_path: impl AsRef<Path> + Send,
+) -> Result<bool, NonCriticalError> {
+	use sd_ffmpeg::{to_cover_art_thumbnail, ThumbnailSize};
+
+	let file_path = file_path.as_ref();
+
+	to_cover_art_thumbnail(
+		file_path,
+		output_path,
+		ThumbnailSize::Scale(1024),
+		TARGET_QUALITY,
+	)
+	.await
+	.map_err(|e| {
+		NonCriticalError::AudioCoverArtExtractionFailed(file_path.to_path_buf(), e.to_string())
+	})
+}
@@ -57,6 +57,7 @@ use uuid::Uuid;
 
 pub mod seed;
 mod serde_impl;
+pub mod symlink;
 
 #[derive(Error, Debug)]
 pub enum IndexerRuleError {
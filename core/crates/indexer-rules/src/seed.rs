@@ -214,6 +214,8 @@ pub static NO_SYSTEM_FILES: Lazy<SystemIndexerRule> = Lazy::new(|| {
 			[
 				vec![
 					"**/.spacedrive",
+					// Files trashed from the location
+					"**/.spacedrive-trash",
 				],
 				// Globset, even on Windows, requires the use of / as a separator
 				// https://github.com/github/gitignore/blob/main/Global/Windows.gitignore
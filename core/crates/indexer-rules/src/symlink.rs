@@ -0,0 +1,263 @@
+use sd_utils::error::FileIOError;
+
+use std::{
+	fs::Metadata,
+	io,
+	path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use tokio::fs;
+use tracing::{debug, trace, warn};
+
+/// What the indexer and the watcher do with the symbolic links found in a location.
+#[repr(i32)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, Type)]
+pub enum SymlinkPolicy {
+	/// Symbolic links are skipped
+	#[default]
+	Ignore = 0,
+	/// The link itself is indexed as an alias, recording the path it points to
+	IndexAsAlias = 1,
+	/// The link is indexed as what it points to, as long as it stays within the location
+	Follow = 2,
+}
+
+impl From<Option<i32>> for SymlinkPolicy {
+	fn from(value: Option<i32>) -> Self {
+		match value {
+			None | Some(0) => Self::Ignore,
+			Some(1) => Self::IndexAsAlias,
+			Some(2) => Self::Follow,
+			Some(value) => {
+				warn!("Invalid symlink policy on database: {value}, ignoring symlinks");
+				Self::Ignore
+			}
+		}
+	}
+}
+
+/// What to do with a symbolic link, according to a [`SymlinkPolicy`].
+#[derive(Debug)]
+pub enum SymlinkResolution {
+	Skip,
+	/// Index the link itself, with its own metadata
+	Alias,
+	/// Index the link as its target, with the target's metadata
+	Follow {
+		metadata: Metadata,
+		canonical_target: PathBuf,
+	},
+}
+
+/// Resolve a symbolic link found at `path` in the location at `location_path`.
+///
+/// `followed_symlinks` are the canonical targets of the directory links already followed to reach `path`,
+/// so a chain of links pointing at each other is only walked once.
+pub async fn resolve_symlink(
+	path: impl AsRef<Path> + Send,
+	policy: SymlinkPolicy,
+	location_path: impl AsRef<Path> + Send,
+	followed_symlinks: &[PathBuf],
+) -> Result<SymlinkResolution, FileIOError> {
+	let path = path.as_ref();
+
+	match policy {
+		SymlinkPolicy::Ignore => Ok(SymlinkResolution::Skip),
+		SymlinkPolicy::IndexAsAlias => Ok(SymlinkResolution::Alias),
+		SymlinkPolicy::Follow => {
+			let canonical_target = match fs::canonicalize(path).await {
+				Ok(canonical_target) => canonical_target,
+				Err(e) if e.kind() == io::ErrorKind::NotFound => {
+					trace!("Skipping broken symlink: {}", path.display());
+					return Ok(SymlinkResolution::Skip);
+				}
+				Err(e) => return Err(FileIOError::from((path, e))),
+			};
+
+			let location_path = location_path.as_ref();
+			let canonical_location_path = fs::canonicalize(location_path)
+				.await
+				.map_err(|e| FileIOError::from((location_path, e)))?;
+
+			if !canonical_target.starts_with(&canonical_location_path) {
+				debug!(
+					"Skipping symlink pointing outside of the location: {} -> {}",
+					path.display(),
+					canonical_target.display()
+				);
+				return Ok(SymlinkResolution::Skip);
+			}
+
+			let metadata = fs::metadata(&canonical_target)
+				.await
+				.map_err(|e| FileIOError::from((&canonical_target, e)))?;
+
+			if metadata.is_dir() {
+				let canonical_parent = match path.parent() {
+					Some(parent) => fs::canonicalize(parent)
+						.await
+						.map_err(|e| FileIOError::from((parent, e)))?,
+					None => canonical_location_path,
+				};
+
+				// Following a link to one of the directories we're in would walk it forever
+				if followed_symlinks
+					.iter()
+					.chain([&canonical_parent])
+					.any(|walking| walking.starts_with(&canonical_target))
+				{
+					debug!(
+						"Skipping symlink cycle: {} -> {}",
+						path.display(),
+						canonical_target.display()
+					);
+					return Ok(SymlinkResolution::Skip);
+				}
+			}
+
+			Ok(SymlinkResolution::Follow {
+				metadata,
+				canonical_target,
+			})
+		}
+	}
+}
+
+#[cfg(all(test, unix))]
+#[allow(clippy::unwrap_used, clippy::panic)]
+mod tests {
+	use super::*;
+
+	use std::os::unix::fs::symlink;
+
+	use tempfile::tempdir;
+
+	#[tokio::test]
+	async fn follow_stays_within_location() {
+		let outside = tempdir().unwrap();
+		let location = tempdir().unwrap();
+		let location_path = location.path();
+
+		std::fs::create_dir(location_path.join("photos")).unwrap();
+		std::fs::write(location_path.join("photos/cat.jpg"), b"meow").unwrap();
+		symlink(
+			location_path.join("photos/cat.jpg"),
+			location_path.join("cat.jpg"),
+		)
+		.unwrap();
+		symlink(outside.path(), location_path.join("outside")).unwrap();
+		symlink(location_path.join("missing"), location_path.join("broken")).unwrap();
+
+		assert!(matches!(
+			resolve_symlink(
+				location_path.join("cat.jpg"),
+				SymlinkPolicy::Follow,
+				location_path,
+				&[]
+			)
+			.await
+			.unwrap(),
+			SymlinkResolution::Follow { metadata, .. } if metadata.is_file()
+		));
+		assert!(matches!(
+			resolve_symlink(
+				location_path.join("outside"),
+				SymlinkPolicy::Follow,
+				location_path,
+				&[]
+			)
+			.await
+			.unwrap(),
+			SymlinkResolution::Skip
+		));
+		assert!(matches!(
+			resolve_symlink(
+				location_path.join("broken"),
+				SymlinkPolicy::Follow,
+				location_path,
+				&[]
+			)
+			.await
+			.unwrap(),
+			SymlinkResolution::Skip
+		));
+		assert!(matches!(
+			resolve_symlink(
+				location_path.join("outside"),
+				SymlinkPolicy::IndexAsAlias,
+				location_path,
+				&[]
+			)
+			.await
+			.unwrap(),
+			SymlinkResolution::Alias
+		));
+	}
+
+	#[tokio::test]
+	async fn follow_detects_cycles() {
+		let location = tempdir().unwrap();
+		let location_path = location.path();
+
+		std::fs::create_dir_all(location_path.join("a/b")).unwrap();
+		std::fs::create_dir(location_path.join("c")).unwrap();
+		symlink(location_path.join("a"), location_path.join("a/b/up")).unwrap();
+		symlink(location_path.join("c"), location_path.join("a/to_c")).unwrap();
+		symlink(location_path.join("a"), location_path.join("c/to_a")).unwrap();
+
+		// A link to an ancestor
+		assert!(matches!(
+			resolve_symlink(
+				location_path.join("a/b/up"),
+				SymlinkPolicy::Follow,
+				location_path,
+				&[]
+			)
+			.await
+			.unwrap(),
+			SymlinkResolution::Skip
+		));
+
+		// Two links pointing at each other, `a/to_c/to_a` is reached after following `a/to_c`
+		let SymlinkResolution::Follow {
+			canonical_target, ..
+		} = resolve_symlink(
+			location_path.join("a/to_c"),
+			SymlinkPolicy::Follow,
+			location_path,
+			&[],
+		)
+		.await
+		.unwrap()
+		else {
+			panic!("expected to follow a/to_c");
+		};
+		let SymlinkResolution::Follow {
+			canonical_target: second_target,
+			..
+		} = resolve_symlink(
+			location_path.join("a/to_c/to_a"),
+			SymlinkPolicy::Follow,
+			location_path,
+			std::slice::from_ref(&canonical_target),
+		)
+		.await
+		.unwrap()
+		else {
+			panic!("expected to follow a/to_c/to_a");
+		};
+		assert!(matches!(
+			resolve_symlink(
+				location_path.join("a/to_c/to_a/to_c"),
+				SymlinkPolicy::Follow,
+				location_path,
+				&[canonical_target, second_target],
+			)
+			.await
+			.unwrap(),
+			SymlinkResolution::Skip
+		));
+	}
+}
@@ -28,7 +28,7 @@
 #![forbid(deprecated_in_future)]
 #![allow(clippy::missing_errors_doc, clippy::module_name_repetitions)]
 
-use sd_prisma::prisma::{file_path, job, label, location, object};
+use sd_prisma::prisma::{file_path, job, label, location, object, trash_item};
 
 // File Path selectables!
 file_path::select!(file_path_pub_id { pub_id });
@@ -46,6 +46,8 @@ file_path::select!(file_path_for_file_identifier {
 	name
 	extension
 	object_id
+	symlink_target
+	xattrs
 });
 file_path::select!(file_path_for_object_validator {
 	pub_id
@@ -55,6 +57,29 @@ file_path::select!(file_path_for_object_validator {
 	extension
 	integrity_checksum
 });
+file_path::select!(file_path_for_dedup {
+	id
+	pub_id
+	location_id
+	materialized_path
+	is_dir
+	name
+	extension
+	object_id
+	integrity_checksum
+});
+file_path::select!(file_path_for_trash {
+	id
+	pub_id
+	location_id
+	materialized_path
+	is_dir
+	name
+	extension
+	cas_id
+	object_id
+	size_in_bytes_bytes
+});
 file_path::select!(file_path_for_media_processor {
 	id
 	materialized_path
@@ -99,6 +124,10 @@ file_path::select!(file_path_walker {
 	inode
 	size_in_bytes_bytes
 	hidden
+	permissions
+	owner
+	owner_group
+	xattrs
 });
 file_path::select!(file_path_to_handle_custom_uri {
 	pub_id
@@ -137,6 +166,25 @@ file_path::select!(file_path_to_full_path {
 		path
 	}
 });
+file_path::select!(file_path_for_desktop_tags {
+	pub_id
+	materialized_path
+	is_dir
+	name
+	extension
+	xattrs
+	location: select {
+		id
+		path
+	}
+	object: select {
+		tags: select {
+			tag: select {
+				name
+			}
+		}
+	}
+});
 
 // File Path includes!
 file_path::include!(file_path_with_object { object });
@@ -192,6 +240,8 @@ object::include!(object_with_file_paths {
 });
 object::include!(object_with_media_data {
 	exif_data
+	audio_data
+	document_data
 	ffmpeg_data: include {
 		chapters
 		programs: include {
@@ -244,9 +294,15 @@ impl From<location_with_indexer_rules::Data> for location::Data {
 			hidden: data.hidden,
 			date_created: data.date_created,
 			scan_state: data.scan_state,
+			symlink_policy: data.symlink_policy,
+			desktop_tags_policy: data.desktop_tags_policy,
+			volume_id: data.volume_id,
 			file_paths: None,
 			indexer_rules: None,
 			instance: None,
+			volume: None,
+			storage_statistics: None,
+			trash_items: None,
 		}
 	}
 }
@@ -268,13 +324,32 @@ impl From<&location_with_indexer_rules::Data> for location::Data {
 			hidden: data.hidden,
 			date_created: data.date_created,
 			scan_state: data.scan_state,
+			symlink_policy: data.symlink_policy,
+			desktop_tags_policy: data.desktop_tags_policy,
+			volume_id: data.volume_id,
 			file_paths: None,
 			indexer_rules: None,
 			instance: None,
+			volume: None,
+			storage_statistics: None,
+			trash_items: None,
 		}
 	}
 }
 
+// Trash item includes!
+trash_item::include!(trash_item_with_location {
+	location: select { id name path }
+});
+trash_item::include!(trash_item_with_objects {
+	location: select { path instance_id }
+	objects: select {
+		relative_path
+		cas_id
+		object: select { id pub_id }
+	}
+});
+
 // Label includes!
 label::include!((take: i64) => label_with_objects {
 	label_objects(vec![]).take(take): select {
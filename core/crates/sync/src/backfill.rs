@@ -113,6 +113,14 @@ pub async fn backfill_operations(db: &PrismaClient, sync: &crate::Manager, insta
 													sync_preview_media
 												),
 												option_sync_entry!(l.hidden, hidden),
+												option_sync_entry!(
+													l.symlink_policy,
+													symlink_policy
+												),
+												option_sync_entry!(
+													l.desktop_tags_policy,
+													desktop_tags_policy
+												),
 												option_sync_entry!(l.date_created, date_created),
 												option_sync_entry!(
 													l.instance.map(|i| {
@@ -287,6 +295,14 @@ pub async fn backfill_operations(db: &PrismaClient, sync: &crate::Manager, insta
 													size_in_bytes_bytes
 												),
 												option_sync_entry!(fp.inode, inode),
+												option_sync_entry!(
+													fp.symlink_target,
+													symlink_target
+												),
+												option_sync_entry!(fp.permissions, permissions),
+												option_sync_entry!(fp.owner, owner),
+												option_sync_entry!(fp.owner_group, owner_group),
+												option_sync_entry!(fp.xattrs, xattrs),
 												option_sync_entry!(fp.date_created, date_created),
 												option_sync_entry!(fp.date_modified, date_modified),
 												option_sync_entry!(fp.date_indexed, date_indexed),
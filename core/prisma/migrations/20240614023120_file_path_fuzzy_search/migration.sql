@@ -0,0 +1,29 @@
+-- CreateVirtualTable
+-- Trigram index over file path names, used by `search.fuzzyPaths` to fetch typo tolerant candidates.
+-- The `rowid` of each entry is the `id` of the indexed `file_path` row.
+CREATE VIRTUAL TABLE "file_path_search" USING fts5("name", tokenize = 'trigram');
+
+-- Backfill
+INSERT INTO "file_path_search" ("rowid", "name")
+SELECT "id", COALESCE("name", '') || ' ' || COALESCE("extension", '') FROM "file_path";
+
+-- CreateTrigger
+CREATE TRIGGER "file_path_search_after_insert" AFTER INSERT ON "file_path"
+BEGIN
+    INSERT INTO "file_path_search" ("rowid", "name")
+    VALUES (new."id", COALESCE(new."name", '') || ' ' || COALESCE(new."extension", ''));
+END;
+
+-- CreateTrigger
+CREATE TRIGGER "file_path_search_after_delete" AFTER DELETE ON "file_path"
+BEGIN
+    DELETE FROM "file_path_search" WHERE "rowid" = old."id";
+END;
+
+-- CreateTrigger
+CREATE TRIGGER "file_path_search_after_update" AFTER UPDATE OF "name", "extension" ON "file_path"
+BEGIN
+    DELETE FROM "file_path_search" WHERE "rowid" = old."id";
+    INSERT INTO "file_path_search" ("rowid", "name")
+    VALUES (new."id", COALESCE(new."name", '') || ' ' || COALESCE(new."extension", ''));
+END;
@@ -0,0 +1,16 @@
+-- CreateTable
+CREATE TABLE "storage_statistics" (
+    "id" INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT,
+    "date_captured" DATETIME NOT NULL,
+    "kind" INTEGER NOT NULL,
+    "file_count" INTEGER NOT NULL,
+    "total_bytes" BLOB NOT NULL,
+    "location_id" INTEGER NOT NULL,
+    CONSTRAINT "storage_statistics_location_id_fkey" FOREIGN KEY ("location_id") REFERENCES "location" ("id") ON DELETE CASCADE ON UPDATE CASCADE
+);
+
+-- CreateIndex
+CREATE INDEX "storage_statistics_date_captured_idx" ON "storage_statistics"("date_captured");
+
+-- CreateIndex
+CREATE UNIQUE INDEX "storage_statistics_location_id_date_captured_kind_key" ON "storage_statistics"("location_id", "date_captured", "kind");
@@ -0,0 +1,49 @@
+/*
+  Warnings:
+
+  - A unique constraint covering the columns `[identifier]` on the table `volume` will be added. If there are existing duplicate values, this will fail.
+  - Added the required column `identifier` to the `volume` table without a default value. This is not possible if the table is not empty.
+
+*/
+-- RedefineTables
+PRAGMA foreign_keys=OFF;
+DROP TABLE "volume";
+CREATE TABLE "volume" (
+    "id" INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT,
+    "identifier" TEXT NOT NULL,
+    "name" TEXT NOT NULL,
+    "mount_point" TEXT NOT NULL,
+    "total_bytes_capacity" TEXT NOT NULL DEFAULT '0',
+    "total_bytes_available" TEXT NOT NULL DEFAULT '0',
+    "disk_type" TEXT,
+    "filesystem" TEXT,
+    "is_system" BOOLEAN NOT NULL DEFAULT false,
+    "is_mounted" BOOLEAN NOT NULL DEFAULT true,
+    "date_modified" DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+CREATE UNIQUE INDEX "volume_identifier_key" ON "volume"("identifier");
+CREATE TABLE "new_location" (
+    "id" INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT,
+    "pub_id" BLOB NOT NULL,
+    "name" TEXT,
+    "path" TEXT,
+    "total_capacity" INTEGER,
+    "available_capacity" INTEGER,
+    "size_in_bytes" BLOB,
+    "is_archived" BOOLEAN,
+    "generate_preview_media" BOOLEAN,
+    "sync_preview_media" BOOLEAN,
+    "hidden" BOOLEAN,
+    "date_created" DATETIME,
+    "scan_state" INTEGER NOT NULL DEFAULT 0,
+    "instance_id" INTEGER,
+    "volume_id" INTEGER,
+    CONSTRAINT "location_instance_id_fkey" FOREIGN KEY ("instance_id") REFERENCES "instance" ("id") ON DELETE SET NULL ON UPDATE CASCADE,
+    CONSTRAINT "location_volume_id_fkey" FOREIGN KEY ("volume_id") REFERENCES "volume" ("id") ON DELETE SET NULL ON UPDATE CASCADE
+);
+INSERT INTO "new_location" ("available_capacity", "date_created", "generate_preview_media", "hidden", "id", "instance_id", "is_archived", "name", "path", "pub_id", "scan_state", "size_in_bytes", "sync_preview_media", "total_capacity") SELECT "available_capacity", "date_created", "generate_preview_media", "hidden", "id", "instance_id", "is_archived", "name", "path", "pub_id", "scan_state", "size_in_bytes", "sync_preview_media", "total_capacity" FROM "location";
+DROP TABLE "location";
+ALTER TABLE "new_location" RENAME TO "location";
+CREATE UNIQUE INDEX "location_pub_id_key" ON "location"("pub_id");
+PRAGMA foreign_key_check;
+PRAGMA foreign_keys=ON;
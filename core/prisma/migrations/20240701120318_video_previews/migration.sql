@@ -0,0 +1,3 @@
+-- AlterTable
+ALTER TABLE "object" ADD COLUMN "has_thumbstrip" BOOLEAN;
+ALTER TABLE "object" ADD COLUMN "has_video_preview" BOOLEAN;
@@ -0,0 +1,15 @@
+-- CreateTable
+CREATE TABLE "media_subtitle" (
+    "id" INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT,
+    "stream_id" INTEGER,
+    "sidecar" TEXT,
+    "language" TEXT,
+    "title" TEXT,
+    "cues" BLOB NOT NULL,
+    "text" TEXT NOT NULL,
+    "object_id" INTEGER NOT NULL,
+    CONSTRAINT "media_subtitle_object_id_fkey" FOREIGN KEY ("object_id") REFERENCES "object" ("id") ON DELETE CASCADE ON UPDATE CASCADE
+);
+
+-- CreateIndex
+CREATE INDEX "media_subtitle_object_id_idx" ON "media_subtitle"("object_id");
@@ -0,0 +1,26 @@
+-- CreateTable
+CREATE TABLE "audio_data" (
+    "id" INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT,
+    "title" TEXT,
+    "artist" TEXT,
+    "album" TEXT,
+    "album_artist" TEXT,
+    "genre" TEXT,
+    "composer" TEXT,
+    "year" INTEGER,
+    "track_number" INTEGER,
+    "track_total" INTEGER,
+    "disc_number" INTEGER,
+    "disc_total" INTEGER,
+    "object_id" INTEGER NOT NULL,
+    CONSTRAINT "audio_data_object_id_fkey" FOREIGN KEY ("object_id") REFERENCES "object" ("id") ON DELETE CASCADE ON UPDATE CASCADE
+);
+
+-- CreateIndex
+CREATE UNIQUE INDEX "audio_data_object_id_key" ON "audio_data"("object_id");
+
+-- CreateIndex
+CREATE INDEX "audio_data_artist_idx" ON "audio_data"("artist");
+
+-- CreateIndex
+CREATE INDEX "audio_data_album_idx" ON "audio_data"("album");
@@ -0,0 +1,20 @@
+-- CreateTable
+CREATE TABLE "document_data" (
+    "id" INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT,
+    "title" TEXT,
+    "author" TEXT,
+    "subject" TEXT,
+    "page_count" INTEGER,
+    "date_created" DATETIME,
+    "object_id" INTEGER NOT NULL,
+    CONSTRAINT "document_data_object_id_fkey" FOREIGN KEY ("object_id") REFERENCES "object" ("id") ON DELETE CASCADE ON UPDATE CASCADE
+);
+
+-- CreateIndex
+CREATE UNIQUE INDEX "document_data_object_id_key" ON "document_data"("object_id");
+
+-- CreateIndex
+CREATE INDEX "document_data_author_idx" ON "document_data"("author");
+
+-- CreateIndex
+CREATE INDEX "document_data_page_count_idx" ON "document_data"("page_count");
@@ -0,0 +1,5 @@
+-- AlterTable
+ALTER TABLE "file_path" ADD COLUMN "permissions" TEXT;
+ALTER TABLE "file_path" ADD COLUMN "owner" TEXT;
+ALTER TABLE "file_path" ADD COLUMN "owner_group" TEXT;
+ALTER TABLE "file_path" ADD COLUMN "xattrs" TEXT;
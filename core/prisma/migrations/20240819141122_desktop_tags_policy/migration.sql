@@ -0,0 +1,2 @@
+-- AlterTable
+ALTER TABLE "location" ADD COLUMN "desktop_tags_policy" INTEGER;
@@ -0,0 +1,37 @@
+-- CreateTable
+CREATE TABLE "trash_item" (
+    "id" INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT,
+    "pub_id" BLOB NOT NULL,
+    "materialized_path" TEXT NOT NULL,
+    "name" TEXT NOT NULL,
+    "extension" TEXT NOT NULL,
+    "is_dir" BOOLEAN NOT NULL,
+    "size_in_bytes_bytes" BLOB,
+    "date_trashed" DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    "location_id" INTEGER NOT NULL,
+    CONSTRAINT "trash_item_location_id_fkey" FOREIGN KEY ("location_id") REFERENCES "location" ("id") ON DELETE CASCADE ON UPDATE CASCADE
+);
+
+-- CreateTable
+CREATE TABLE "trash_item_object" (
+    "trash_item_id" INTEGER NOT NULL,
+    "relative_path" TEXT NOT NULL,
+    "cas_id" TEXT,
+    "object_id" INTEGER NOT NULL,
+
+    PRIMARY KEY ("trash_item_id", "relative_path"),
+    CONSTRAINT "trash_item_object_trash_item_id_fkey" FOREIGN KEY ("trash_item_id") REFERENCES "trash_item" ("id") ON DELETE CASCADE ON UPDATE CASCADE,
+    CONSTRAINT "trash_item_object_object_id_fkey" FOREIGN KEY ("object_id") REFERENCES "object" ("id") ON DELETE CASCADE ON UPDATE CASCADE
+);
+
+-- CreateIndex
+CREATE UNIQUE INDEX "trash_item_pub_id_key" ON "trash_item"("pub_id");
+
+-- CreateIndex
+CREATE INDEX "trash_item_location_id_idx" ON "trash_item"("location_id");
+
+-- CreateIndex
+CREATE INDEX "trash_item_date_trashed_idx" ON "trash_item"("date_trashed");
+
+-- CreateIndex
+CREATE INDEX "trash_item_object_object_id_idx" ON "trash_item_object"("object_id");
@@ -4,9 +4,14 @@ use crate::{
 		utils::library,
 	},
 	invalidate_query,
-	library::Library,
+	library::{
+		trash::{find_indexed_file_path, move_to_trash},
+		Library,
+	},
 	object::{
-		fs::{error::FileSystemJobsError, find_available_filename_for_duplicate},
+		fs::{
+			error::FileSystemJobsError, find_available_filename_for_duplicate, reflink::copy_file,
+		},
 		media::exif_metadata_extractor::{can_extract_exif_data_for_image, extract_exif_data},
 	},
 };
@@ -30,8 +35,6 @@ use specta::Type;
 use tokio::{fs, io};
 use tokio_stream::{wrappers::ReadDirStream, StreamExt};
 use tracing::{error, warn};
-#[cfg(not(any(target_os = "ios", target_os = "android")))]
-use trash;
 
 use super::{
 	files::{create_directory, FromPattern},
@@ -42,6 +45,30 @@ const UNTITLED_FOLDER_STR: &str = "Untitled Folder";
 const UNTITLED_FILE_STR: &str = "Untitled";
 const UNTITLED_TEXT_FILE_STR: &str = "Untitled.txt";
 
+#[cfg(not(any(target_os = "ios", target_os = "android")))]
+fn move_to_os_trash(path: PathBuf) -> Result<(), rspc::Error> {
+	trash::delete(&path).map_err(|e| {
+		FileIOError::from((
+			path,
+			match e {
+				#[cfg(all(unix, not(target_os = "macos")))]
+				trash::Error::FileSystem { path: _, source: e } => e,
+				_ => io::Error::other(e),
+			},
+			"Failed to delete file",
+		))
+		.into()
+	})
+}
+
+#[cfg(any(target_os = "ios", target_os = "android"))]
+fn move_to_os_trash(_path: PathBuf) -> Result<(), rspc::Error> {
+	Err(rspc::Error::new(
+		ErrorCode::MethodNotSupported,
+		"Moving files outside of a location to trash is not supported on this platform".to_string(),
+	))
+}
+
 #[derive(Type, Deserialize)]
 #[serde(rename_all = "camelCase")]
 enum EphemeralFileCreateContextTypes {
@@ -180,40 +207,36 @@ pub(crate) fn mount() -> AlphaRouter<Ctx> {
 		})
 		.procedure("moveToTrash", {
 			R.with2(library())
-				.mutation(|(_, library), paths: Vec<PathBuf>| async move {
-					if cfg!(target_os = "ios") || cfg!(target_os = "android") {
-						return Err(rspc::Error::new(
-							ErrorCode::MethodNotSupported,
-							"Moving to trash is not supported on this platform".to_string(),
-						));
-					}
-
+				.mutation(|(node, library), paths: Vec<PathBuf>| async move {
 					paths
 						.into_iter()
-						.map(|path| async move {
-							match fs::metadata(&path).await {
-								Ok(_) => {
-									#[cfg(not(any(target_os = "ios", target_os = "android")))]
-									trash::delete(&path).map_err(|e| {
-										FileIOError::from((
-											path,
-											match e {
-												#[cfg(all(unix, not(target_os = "macos")))]
-												trash::Error::FileSystem { path: _, source: e } => e,
-												_ => io::Error::other(e),
-											},
-											"Failed to delete file",
-										))
-									})?;
-
-									Ok(())
+						.map(|path| {
+							let (node, library) = (&node, &library);
+							async move {
+								// Files indexed in a location go to the library trash, so they can be restored
+								if let Some((location_id, file_path_id)) =
+									find_indexed_file_path(library, &path).await?
+								{
+									return move_to_trash(
+										node,
+										library,
+										location_id,
+										&[file_path_id],
+									)
+									.await
+									.map_err(Into::into);
+								}
+
+								match fs::metadata(&path).await {
+									Ok(_) => move_to_os_trash(path),
+									Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(()),
+									Err(e) => Err(FileIOError::from((
+										path,
+										e,
+										"Failed to get file metadata for deletion",
+									))
+									.into()),
 								}
-								Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(()),
-								Err(e) => Err(FileIOError::from((
-									path,
-									e,
-									"Failed to get file metadata for deletion",
-								))),
 							}
 						})
 						.collect::<Vec<_>>()
@@ -530,7 +553,7 @@ impl EphemeralFileSystemOps {
 					}
 				}
 
-				fs::copy(&source, target).await.map_err(|e| {
+				copy_file(&source, target).await.map_err(|e| {
 					FileSystemJobsError::FileIO(FileIOError::from((
 						source,
 						e,
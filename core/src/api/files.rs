@@ -1,20 +1,25 @@
 use crate::{
 	api::utils::library,
+	context::NodeContext,
 	invalidate_query,
-	library::Library,
+	library::{trash::move_to_trash, Library},
 	location::{get_location_path_from_location_id, LocationError},
 	object::{
 		fs::{
 			error::FileSystemJobsError, find_available_filename_for_duplicate,
 			old_copy::OldFileCopierJobInit, old_cut::OldFileCutterJobInit,
-			old_delete::OldFileDeleterJobInit, old_erase::OldFileEraserJobInit,
+			old_dedup::OldFileDeduplicatorJobInit, old_delete::OldFileDeleterJobInit,
+		},
+		media::{
+			audio_data_from_prisma_data, document_data_from_prisma_data,
+			exif_media_data_from_prisma_data, ffmpeg_data_from_prisma_data,
 		},
-		media::{exif_media_data_from_prisma_data, ffmpeg_data_from_prisma_data},
 	},
 	old_job::Job,
 };
 
 use sd_core_file_path_helper::{FilePathError, IsolatedFilePathData};
+use sd_core_heavy_lifting::file_eraser::FileEraser;
 use sd_core_prisma_helpers::{
 	file_path_to_isolate, file_path_to_isolate_with_id, object_with_file_paths,
 	object_with_media_data,
@@ -22,9 +27,9 @@ use sd_core_prisma_helpers::{
 
 use sd_file_ext::kind::ObjectKind;
 use sd_images::ConvertibleExtension;
-use sd_media_metadata::{ExifMetadata, FFmpegMetadata};
+use sd_media_metadata::{AudioMetadata, DocumentMetadata, ExifMetadata, FFmpegMetadata};
 use sd_prisma::{
-	prisma::{file_path, location, object},
+	prisma::{file_path, location, media_subtitle, object},
 	prisma_sync,
 };
 use sd_sync::OperationFactory;
@@ -41,11 +46,10 @@ use futures::future::join_all;
 use regex::Regex;
 use rspc::{alpha::AlphaRouter, ErrorCode};
 use serde::{Deserialize, Serialize};
+use serde_with::{serde_as, DisplayFromStr};
 use specta::Type;
 use tokio::{fs, io, task::spawn_blocking};
 use tracing::{error, warn};
-#[cfg(not(any(target_os = "ios", target_os = "android")))]
-use trash;
 
 use super::{Ctx, R};
 
@@ -64,6 +68,12 @@ enum FileCreateContextTypes {
 pub(crate) enum MediaData {
 	Exif(ExifMetadata),
 	FFmpeg(FFmpegMetadata),
+	/// Audio files with tags, along with their technical metadata if we have it
+	Audio {
+		tags: AudioMetadata,
+		ffmpeg: Option<FFmpegMetadata>,
+	},
+	Document(DocumentMetadata),
 }
 
 pub(crate) fn mount() -> AlphaRouter<Ctx> {
@@ -129,12 +139,24 @@ pub(crate) fn mount() -> AlphaRouter<Ctx> {
 								Some(v) if v == ObjectKind::Image as i32 => MediaData::Exif(
 									exif_media_data_from_prisma_data(obj.exif_data?),
 								),
+								Some(v) if v == ObjectKind::Audio as i32 => match obj.audio_data {
+									Some(audio_data) => MediaData::Audio {
+										tags: audio_data_from_prisma_data(audio_data),
+										ffmpeg: obj.ffmpeg_data.map(ffmpeg_data_from_prisma_data),
+									},
+									None => MediaData::FFmpeg(ffmpeg_data_from_prisma_data(
+										obj.ffmpeg_data?,
+									)),
+								},
+								Some(v) if v == ObjectKind::Video as i32 => MediaData::FFmpeg(
+									ffmpeg_data_from_prisma_data(obj.ffmpeg_data?),
+								),
 								Some(v)
-									if v == ObjectKind::Audio as i32
-										|| v == ObjectKind::Video as i32 =>
+									if v == ObjectKind::Document as i32
+										|| v == ObjectKind::Book as i32 =>
 								{
-									MediaData::FFmpeg(ffmpeg_data_from_prisma_data(
-										obj.ffmpeg_data?,
+									MediaData::Document(document_data_from_prisma_data(
+										obj.document_data?,
 									))
 								}
 								_ => return None, // No media data
@@ -145,6 +167,37 @@ pub(crate) fn mount() -> AlphaRouter<Ctx> {
 						})
 				})
 		})
+		.procedure("getSubtitles", {
+			#[derive(Type, Serialize)]
+			#[serde(rename_all = "camelCase")]
+			pub struct SubtitleTrackInfo {
+				pub id: media_subtitle::id::Type,
+				pub stream_id: Option<i32>,
+				pub sidecar: Option<String>,
+				pub language: Option<String>,
+				pub title: Option<String>,
+			}
+
+			R.with2(library())
+				.query(|(_, library), object_id: object::id::Type| async move {
+					Ok(library
+						.db
+						.media_subtitle()
+						.find_many(vec![media_subtitle::object_id::equals(object_id)])
+						.select(media_subtitle::select!({ id stream_id sidecar language title }))
+						.exec()
+						.await?
+						.into_iter()
+						.map(|subtitle| SubtitleTrackInfo {
+							id: subtitle.id,
+							stream_id: subtitle.stream_id,
+							sidecar: subtitle.sidecar,
+							language: subtitle.language,
+							title: subtitle.title,
+						})
+						.collect::<Vec<_>>())
+				})
+		})
 		.procedure("getPath", {
 			R.with2(library())
 				.query(|(_, library), id: i32| async move {
@@ -505,66 +558,9 @@ pub(crate) fn mount() -> AlphaRouter<Ctx> {
 		.procedure("moveToTrash", {
 			R.with2(library())
 				.mutation(|(node, library), args: OldFileDeleterJobInit| async move {
-					if cfg!(target_os = "ios") || cfg!(target_os = "android") {
-						return Err(rspc::Error::new(
-							ErrorCode::MethodNotSupported,
-							"Moving to trash is not supported on this platform".to_string(),
-						));
-					}
-
-					match args.file_path_ids.len() {
-						0 => Ok(()),
-						1 => {
-							let (maybe_location, maybe_file_path) = library
-								.db
-								._batch((
-									library
-										.db
-										.location()
-										.find_unique(location::id::equals(args.location_id))
-										.select(location::select!({ path })),
-									library
-										.db
-										.file_path()
-										.find_unique(file_path::id::equals(args.file_path_ids[0]))
-										.select(file_path_to_isolate::select()),
-								))
-								.await?;
-
-							let location_path = maybe_location
-								.ok_or(LocationError::IdNotFound(args.location_id))?
-								.path
-								.ok_or(LocationError::MissingPath(args.location_id))?;
-
-							let file_path = maybe_file_path.ok_or(LocationError::FilePath(
-								FilePathError::IdNotFound(args.file_path_ids[0]),
-							))?;
-
-							let full_path = Path::new(&location_path).join(
-								IsolatedFilePathData::try_from(&file_path)
-									.map_err(LocationError::MissingField)?,
-							);
-
-							#[cfg(not(any(target_os = "ios", target_os = "android")))]
-							trash::delete(&full_path).map_err(|e| {
-								FileIOError::from((
-									full_path,
-									match e {
-										#[cfg(all(unix, not(target_os = "macos")))]
-										trash::Error::FileSystem { path: _, source: e } => e,
-										_ => io::Error::other(e),
-									},
-									"Failed to delete file",
-								))
-							})?;
-
-							Ok(())
-						}
-						_ => Job::new(args)
-							.spawn(&node, &library)
-							.await
-							.map_err(Into::into),
-					}
+					move_to_trash(&node, &library, args.location_id, &args.file_path_ids)
+						.await
+						.map_err(Into::into)
 				})
 		})
 		.procedure("convertImage", {
@@ -704,11 +700,26 @@ pub(crate) fn mount() -> AlphaRouter<Ctx> {
 			R.query(|_, _: ()| async move { Ok(sd_images::all_compatible_extensions()) })
 		})
 		.procedure("eraseFiles", {
+			#[serde_as]
+			#[derive(Type, Deserialize)]
+			pub struct FileEraserJobInit {
+				pub location_id: location::id::Type,
+				pub file_path_ids: Vec<file_path::id::Type>,
+				#[specta(type = String)]
+				#[serde_as(as = "DisplayFromStr")]
+				pub passes: usize,
+			}
+
 			R.with2(library())
-				.mutation(|(node, library), args: OldFileEraserJobInit| async move {
-					Job::new(args)
-						.spawn(&node, &library)
+				.mutation(|(node, library), args: FileEraserJobInit| async move {
+					node.job_system
+						.dispatch(
+							FileEraser::new(args.location_id, args.file_path_ids, args.passes),
+							args.location_id,
+							NodeContext::new(&node, &library),
+						)
 						.await
+						.map(|_| ())
 						.map_err(Into::into)
 				})
 		})
@@ -730,6 +741,16 @@ pub(crate) fn mount() -> AlphaRouter<Ctx> {
 						.map_err(Into::into)
 				})
 		})
+		.procedure("deduplicateFiles", {
+			R.with2(library()).mutation(
+				|(node, library), args: OldFileDeduplicatorJobInit| async move {
+					Job::new(args)
+						.spawn(&node, &library)
+						.await
+						.map_err(Into::into)
+				},
+			)
+		})
 		.procedure("renameFile", {
 			#[derive(Type, Deserialize)]
 			pub struct RenameOne {
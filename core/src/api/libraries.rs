@@ -1,16 +1,22 @@
 use crate::{
 	invalidate_query,
-	library::{update_library_statistics, Library, LibraryConfig, LibraryName},
-	location::{scan_location, LocationCreateArgs, ScanState},
+	library::{size_from_bytes, update_library_statistics, Library, LibraryConfig, LibraryName},
+	location::{scan_location, LocationCreateArgs, LocationError, ScanState},
+	object::media::old_thumbnail::get_indexed_thumb_key,
 	util::MaybeUndefined,
 	Node,
 };
 
 use futures::StreamExt;
-use prisma_client_rust::raw;
+use prisma_client_rust::{raw, PrismaValue, QueryError, Raw};
+use sd_core_prisma_helpers::file_path_for_frontend;
 use sd_file_ext::kind::ObjectKind;
 use sd_p2p::RemoteIdentity;
-use sd_prisma::prisma::{indexer_rule, object, statistics};
+use sd_prisma::prisma::{
+	file_path, indexer_rule, location, object, statistics, storage_statistics, PrismaClient,
+	SortOrder,
+};
+use sd_utils::chain_optional_iter;
 use tokio_stream::wrappers::IntervalStream;
 use tracing::{info, warn};
 
@@ -23,6 +29,7 @@ use std::{
 };
 
 use async_channel as chan;
+use chrono::{DateTime, Utc};
 use directories::UserDirs;
 use futures_concurrency::{future::Join, stream::Merge};
 use once_cell::sync::Lazy;
@@ -38,7 +45,10 @@ use tokio::{
 use tracing::{debug, error};
 use uuid::Uuid;
 
-use super::{utils::library, Ctx, R};
+use super::{locations::ExplorerItem, utils::library, Ctx, R};
+
+const DEFAULT_STORAGE_TREE_DEPTH: u8 = 3;
+const DEFAULT_STORAGE_TREE_MAX_CHILDREN: u8 = 20;
 
 const ONE_MINUTE: Duration = Duration::from_secs(60);
 const TWO_MINUTES: Duration = Duration::from_secs(60 * 2);
@@ -133,6 +143,14 @@ pub(crate) fn mount() -> AlphaRouter<Ctx> {
 				statistics: Vec<KindStatistic>,
 			}
 			R.with2(library()).query(|(_, library), _: ()| async move {
+				let total_bytes_by_kind = latest_storage_statistics(&library.db)
+					.await?
+					.into_iter()
+					.fold(HashMap::<i32, u64>::new(), |mut acc, stat| {
+						*acc.entry(stat.kind).or_default() += size_from_bytes(&stat.total_bytes);
+						acc
+					});
+
 				let mut statistics: Vec<KindStatistic> = vec![];
 				for kind in ObjectKind::iter() {
 					let count = library
@@ -146,13 +164,249 @@ pub(crate) fn mount() -> AlphaRouter<Ctx> {
 						kind: kind as i32,
 						name: kind.to_string(),
 						count: count as i32,
-						total_bytes: "0".to_string(),
+						total_bytes: total_bytes_by_kind
+							.get(&(kind as i32))
+							.copied()
+							.unwrap_or(0)
+							.to_string(),
 					});
 				}
 
 				Ok(KindStatistics { statistics })
 			})
 		})
+		.procedure("storageHistory", {
+			#[derive(Deserialize, Type)]
+			pub struct StorageHistoryArgs {
+				#[specta(optional)]
+				location_id: Option<location::id::Type>,
+				#[specta(optional)]
+				since: Option<DateTime<Utc>>,
+			}
+
+			#[derive(Serialize, Type)]
+			pub struct StorageSnapshot {
+				date_captured: DateTime<Utc>,
+				location_id: location::id::Type,
+				kind: i32,
+				file_count: i32,
+				total_bytes: String,
+			}
+
+			R.with2(library()).query(
+				|(_, library), StorageHistoryArgs { location_id, since }| async move {
+					Ok(library
+						.db
+						.storage_statistics()
+						.find_many(chain_optional_iter(
+							[],
+							[
+								location_id.map(storage_statistics::location_id::equals),
+								since.map(|since| {
+									storage_statistics::date_captured::gte(since.into())
+								}),
+							],
+						))
+						.order_by(storage_statistics::date_captured::order(SortOrder::Asc))
+						.exec()
+						.await?
+						.into_iter()
+						.map(|stat| StorageSnapshot {
+							date_captured: stat.date_captured.into(),
+							location_id: stat.location_id,
+							kind: stat.kind,
+							file_count: stat.file_count,
+							total_bytes: size_from_bytes(&stat.total_bytes).to_string(),
+						})
+						.collect::<Vec<_>>())
+				},
+			)
+		})
+		.procedure("storageTree", {
+			#[derive(Deserialize, Type)]
+			pub struct StorageTreeArgs {
+				location_id: location::id::Type,
+				/// Materialized path of the directory to use as the tree root, defaults to the location root
+				#[specta(optional)]
+				path: Option<String>,
+				#[specta(optional)]
+				depth: Option<u8>,
+				#[specta(optional)]
+				max_children: Option<u8>,
+			}
+
+			R.with2(library()).query(
+				|(_, library),
+				 StorageTreeArgs {
+				     location_id,
+				     path,
+				     depth,
+				     max_children,
+				 }| async move {
+					let path = path.unwrap_or_else(|| "/".to_string());
+
+					let (name, total_bytes) = if path == "/" {
+						let location = library
+							.db
+							.location()
+							.find_unique(location::id::equals(location_id))
+							.exec()
+							.await?
+							.ok_or(LocationError::IdNotFound(location_id))?;
+
+						(
+							location.name.unwrap_or_default(),
+							location.size_in_bytes.as_deref().map(size_from_bytes),
+						)
+					} else {
+						let (parent, name) = path
+							.trim_end_matches('/')
+							.rsplit_once('/')
+							.map(|(parent, name)| (format!("{parent}/"), name.to_string()))
+							.ok_or_else(|| {
+								rspc::Error::new(
+									ErrorCode::BadRequest,
+									"Path must be a directory materialized path".to_string(),
+								)
+							})?;
+
+						let directory = library
+							.db
+							.file_path()
+							.find_first(vec![
+								file_path::location_id::equals(Some(location_id)),
+								file_path::materialized_path::equals(Some(parent)),
+								file_path::name::equals(Some(name.clone())),
+								file_path::is_dir::equals(Some(true)),
+							])
+							.select(file_path::select!({ size_in_bytes_bytes }))
+							.exec()
+							.await?
+							.ok_or_else(|| {
+								rspc::Error::new(
+									ErrorCode::NotFound,
+									"Directory not found".to_string(),
+								)
+							})?;
+
+						(
+							name,
+							directory
+								.size_in_bytes_bytes
+								.as_deref()
+								.map(size_from_bytes),
+						)
+					};
+
+					let depth = depth.unwrap_or(DEFAULT_STORAGE_TREE_DEPTH);
+
+					let directories = library
+						.db
+						.file_path()
+						.find_many(vec![
+							file_path::location_id::equals(Some(location_id)),
+							file_path::materialized_path::starts_with(path.clone()),
+							file_path::is_dir::equals(Some(true)),
+						])
+						.select(file_path::select!({ materialized_path name size_in_bytes_bytes }))
+						.exec()
+						.await?
+						.into_iter()
+						.filter_map(|directory| {
+							Some((
+								directory.materialized_path?,
+								directory.name?,
+								directory
+									.size_in_bytes_bytes
+									.as_deref()
+									.map(size_from_bytes)
+									.unwrap_or(0),
+							))
+						});
+
+					let mut children_by_parent =
+						StorageTreeNode::group_by_parent(&path, depth, directories);
+
+					Ok(StorageTreeNode::build(
+						name,
+						path,
+						total_bytes.unwrap_or(0),
+						depth,
+						max_children.unwrap_or(DEFAULT_STORAGE_TREE_MAX_CHILDREN) as usize,
+						&mut children_by_parent,
+					)
+					.1)
+				},
+			)
+		})
+		.procedure("largestPaths", {
+			#[derive(Deserialize, Type)]
+			pub struct LargestPathsArgs {
+				#[specta(optional)]
+				location_id: Option<location::id::Type>,
+				is_dir: bool,
+				take: u8,
+			}
+
+			R.with2(library()).query(
+				|(node, library),
+				 LargestPathsArgs {
+				     location_id,
+				     is_dir,
+				     take,
+				 }| async move {
+					#[derive(Deserialize)]
+					struct LargestPath {
+						id: file_path::id::Type,
+					}
+
+					let (query, params) = largest_paths_query(location_id, is_dir, take);
+					let ids = library
+						.db
+						._query_raw::<LargestPath>(Raw::new(&query, params))
+						.exec()
+						.await?
+						.into_iter()
+						.map(|file_path| file_path.id)
+						.collect::<Vec<_>>();
+
+					let mut file_paths = library
+						.db
+						.file_path()
+						.find_many(vec![file_path::id::in_vec(ids.clone())])
+						.include(file_path_for_frontend::include())
+						.exec()
+						.await?;
+
+					file_paths
+						.sort_by_key(|file_path| ids.iter().position(|id| *id == file_path.id));
+
+					let mut items = Vec::with_capacity(file_paths.len());
+
+					for file_path in file_paths {
+						let has_created_thumbnail = if let Some(cas_id) = &file_path.cas_id {
+							library
+								.thumbnail_exists(&node, cas_id)
+								.await
+								.map_err(LocationError::from)?
+						} else {
+							false
+						};
+
+						items.push(ExplorerItem::Path {
+							thumbnail: file_path
+								.cas_id
+								.as_ref()
+								.map(|cas_id| get_indexed_thumb_key(cas_id, library.id)),
+							has_created_thumbnail,
+							item: Box::new(file_path),
+						});
+					}
+
+					Ok(items)
+				},
+			)
+		})
 		.procedure("create", {
 			#[derive(Deserialize, Type, Default)]
 			pub struct DefaultLocations {
@@ -396,6 +650,151 @@ pub(crate) fn mount() -> AlphaRouter<Ctx> {
 		)
 }
 
+/// A directory in the treemap dataset returned by `library.storageTree`
+#[derive(Serialize, Type, Debug)]
+pub struct StorageTreeNode {
+	name: String,
+	/// Materialized path for this directory's children, can be used as the `path` of a new tree
+	path: String,
+	total_bytes: String,
+	/// Space taken by the files directly inside this directory and by
+	/// any subdirectory left out due to the `depth` or `max_children` limits
+	other_bytes: String,
+	children: Vec<StorageTreeNode>,
+}
+
+impl StorageTreeNode {
+	/// Groups the directories below `path`, given as `(materialized_path, name, total_bytes)`,
+	/// by the materialized path of their parent
+	fn group_by_parent(
+		path: &str,
+		depth: u8,
+		directories: impl IntoIterator<Item = (String, String, u64)>,
+	) -> HashMap<String, Vec<(String, u64)>> {
+		let mut children_by_parent = HashMap::<_, Vec<_>>::new();
+
+		for (materialized_path, name, total_bytes) in directories {
+			// `LIKE` is case insensitive and has its own wildcards, so not everything it matched is below `path`
+			let Some(relative_path) = materialized_path.strip_prefix(path) else {
+				continue;
+			};
+
+			// Directories deeper than requested won't ever show up in the tree
+			if relative_path.matches('/').count() >= depth as usize {
+				continue;
+			}
+
+			children_by_parent
+				.entry(materialized_path)
+				.or_default()
+				.push((name, total_bytes));
+		}
+
+		children_by_parent
+	}
+
+	fn build(
+		name: String,
+		path: String,
+		total_bytes: u64,
+		depth: u8,
+		max_children: usize,
+		children_by_parent: &mut HashMap<String, Vec<(String, u64)>>,
+	) -> (u64, Self) {
+		let mut children = if depth > 0 {
+			children_by_parent
+				.remove(&path)
+				.unwrap_or_default()
+				.into_iter()
+				.map(|(child_name, child_total_bytes)| {
+					let child_path = format!("{path}{child_name}/");
+					Self::build(
+						child_name,
+						child_path,
+						child_total_bytes,
+						depth - 1,
+						max_children,
+						children_by_parent,
+					)
+				})
+				.collect::<Vec<_>>()
+		} else {
+			vec![]
+		};
+
+		children.sort_unstable_by(|(bytes1, _), (bytes2, _)| bytes2.cmp(bytes1));
+		children.truncate(max_children);
+
+		let children_bytes = children.iter().map(|(bytes, _)| bytes).sum::<u64>();
+
+		(
+			total_bytes,
+			Self {
+				name,
+				path,
+				total_bytes: total_bytes.to_string(),
+				other_bytes: total_bytes.saturating_sub(children_bytes).to_string(),
+				children: children.into_iter().map(|(_, child)| child).collect(),
+			},
+		)
+	}
+}
+
+/// Builds the query fetching the ids of the largest file paths, with `{}` placeholders for its parameters.
+///
+/// Sizes are stored as big endian bytes, which SQLite can only compare byte by byte, so they're compared
+/// as hex digits without their leading zeros instead: first by how many digits they have and then by value.
+fn largest_paths_query(
+	location_id: Option<location::id::Type>,
+	is_dir: bool,
+	take: u8,
+) -> (String, Vec<PrismaValue>) {
+	let mut params = vec![PrismaValue::Boolean(is_dir)];
+
+	let location_condition = if let Some(location_id) = location_id {
+		params.push(PrismaValue::Int(i64::from(location_id)));
+		" AND location_id = {}"
+	} else {
+		""
+	};
+
+	params.push(PrismaValue::Int(i64::from(take)));
+
+	(
+		format!(
+			"SELECT id FROM file_path
+			WHERE is_dir = {{}}{location_condition}
+			ORDER BY
+				length(ltrim(hex(size_in_bytes_bytes), '0')) DESC,
+				ltrim(hex(size_in_bytes_bytes), '0') DESC
+			LIMIT {{}}"
+		),
+		params,
+	)
+}
+
+/// Fetches the most recent storage statistics captured for each location
+async fn latest_storage_statistics(
+	db: &PrismaClient,
+) -> Result<Vec<storage_statistics::Data>, QueryError> {
+	let Some(latest) = db
+		.storage_statistics()
+		.find_first(vec![])
+		.order_by(storage_statistics::date_captured::order(SortOrder::Desc))
+		.exec()
+		.await?
+	else {
+		return Ok(vec![]);
+	};
+
+	db.storage_statistics()
+		.find_many(vec![storage_statistics::date_captured::equals(
+			latest.date_captured,
+		)])
+		.exec()
+		.await
+}
+
 async fn update_statistics_loop(
 	node: Arc<Node>,
 	library: Arc<Library>,
@@ -436,3 +835,107 @@ async fn update_statistics_loop(
 		}
 	}
 }
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn storage_tree_only_has_directories_below_its_path() {
+		let directories = [
+			("/photos_2024/", "trips", 60),
+			("/photos_2024/", "pets", 30),
+			("/photos_2024/trips/", "japan", 50),
+			// Too deep to show up
+			("/photos_2024/trips/japan/", "tokyo", 40),
+			// Matched by `LIKE`, but not below the path
+			("/photosX2024/", "other", 1000),
+			("/Photos_2024/", "other", 1000),
+			("/", "photos_2024", 100),
+		]
+		.map(|(materialized_path, name, total_bytes)| {
+			(materialized_path.to_string(), name.to_string(), total_bytes)
+		});
+
+		let mut children_by_parent =
+			StorageTreeNode::group_by_parent("/photos_2024/", 2, directories);
+		let (_, tree) = StorageTreeNode::build(
+			"photos_2024".to_string(),
+			"/photos_2024/".to_string(),
+			100,
+			2,
+			1,
+			&mut children_by_parent,
+		);
+
+		assert!(children_by_parent.is_empty());
+		assert_eq!(tree.total_bytes, "100");
+		// `pets` is left out by `max_children`, so it's counted here
+		assert_eq!(tree.other_bytes, "40");
+		assert_eq!(tree.children.len(), 1);
+
+		let trips = &tree.children[0];
+		assert_eq!(trips.name, "trips");
+		assert_eq!(trips.path, "/photos_2024/trips/");
+		assert_eq!(trips.other_bytes, "10");
+		assert_eq!(trips.children[0].name, "japan");
+		assert!(trips.children[0].children.is_empty());
+		assert_eq!(trips.children[0].other_bytes, "50");
+	}
+
+	#[test]
+	fn largest_paths_are_ordered_by_size() {
+		let conn = rusqlite::Connection::open_in_memory().unwrap();
+		conn.execute_batch(
+			"CREATE TABLE file_path (
+				id INTEGER PRIMARY KEY,
+				is_dir BOOLEAN,
+				location_id INTEGER,
+				size_in_bytes_bytes BLOB
+			);",
+		)
+		.unwrap();
+
+		let file_paths: [(bool, i32, Option<Vec<u8>>); 7] = [
+			(false, 1, Some(255_u64.to_be_bytes().to_vec())),
+			(false, 1, Some(1_u64.to_be_bytes().to_vec())),
+			(false, 1, None),
+			(false, 1, Some(70_000_u64.to_be_bytes().to_vec())),
+			// Bigger than everything else byte by byte, but not by size
+			(false, 1, Some(vec![0xff, 0xff])),
+			(false, 2, Some(u64::MAX.to_be_bytes().to_vec())),
+			(true, 1, Some(u64::MAX.to_be_bytes().to_vec())),
+		];
+
+		for (is_dir, location_id, size) in file_paths {
+			conn.execute(
+				"INSERT INTO file_path (is_dir, location_id, size_in_bytes_bytes) VALUES (?, ?, ?)",
+				rusqlite::params![is_dir, location_id, size],
+			)
+			.unwrap();
+		}
+
+		let largest_paths = |location_id, take| {
+			let (query, params) = largest_paths_query(location_id, false, take);
+			let params = params
+				.into_iter()
+				.map(|param| match param {
+					PrismaValue::Int(i) => rusqlite::types::Value::Integer(i),
+					PrismaValue::Boolean(b) => rusqlite::types::Value::Integer(i64::from(b)),
+					_ => unreachable!(),
+				})
+				.collect::<Vec<_>>();
+
+			conn.prepare(&query.replace("{}", "?"))
+				.unwrap()
+				.query_map(rusqlite::params_from_iter(params), |row| row.get(0))
+				.unwrap()
+				.collect::<Result<Vec<i64>, _>>()
+				.unwrap()
+		};
+
+		assert_eq!(largest_paths(Some(1), 10), vec![4, 5, 1, 2, 3]);
+		assert_eq!(largest_paths(None, 2), vec![6, 4]);
+	}
+}
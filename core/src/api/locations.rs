@@ -5,13 +5,16 @@ use crate::{
 		non_indexed::NonIndexedPathItem, relink_location, scan_location, scan_location_sub_path,
 		LocationCreateArgs, LocationError, LocationUpdateArgs, ScanState,
 	},
-	object::old_file_identifier::old_file_identifier_job::OldFileIdentifierJobInit,
+	object::{
+		old_file_identifier::old_file_identifier_job::OldFileIdentifierJobInit,
+		tag::desktop::DesktopTagsPolicy,
+	},
 	old_job::StatefulJob,
 	p2p::PeerMetadata,
 	util::AbortOnDrop,
 };
 
-use sd_core_indexer_rules::IndexerRuleCreateArgs;
+use sd_core_indexer_rules::{symlink::SymlinkPolicy, IndexerRuleCreateArgs};
 use sd_core_prisma_helpers::{
 	file_path_for_frontend, label_with_objects, location_with_indexer_rules, object_with_file_paths,
 };
@@ -224,6 +227,8 @@ pub(crate) fn mount() -> AlphaRouter<Ctx> {
 				pub sync_preview_media: Option<bool>,
 				pub hidden: Option<bool>,
 				pub date_created: Option<DateTime<FixedOffset>>,
+				pub symlink_policy: SymlinkPolicy,
+				pub desktop_tags_policy: DesktopTagsPolicy,
 				pub instance_id: Option<i32>,
 				pub indexer_rules: Vec<indexer_rule::Data>,
 			}
@@ -243,6 +248,8 @@ pub(crate) fn mount() -> AlphaRouter<Ctx> {
 						sync_preview_media: value.sync_preview_media,
 						hidden: value.hidden,
 						date_created: value.date_created,
+						symlink_policy: SymlinkPolicy::from(value.symlink_policy),
+						desktop_tags_policy: DesktopTagsPolicy::from(value.desktop_tags_policy),
 						instance_id: value.instance_id,
 						indexer_rules: value
 							.indexer_rules
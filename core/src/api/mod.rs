@@ -38,6 +38,7 @@ mod preferences;
 pub(crate) mod search;
 mod sync;
 mod tags;
+mod trash;
 pub mod utils;
 pub mod volumes;
 mod web_api;
@@ -209,6 +210,7 @@ pub(crate) fn mount() -> Arc<Router> {
 		.merge("preferences.", preferences::mount())
 		.merge("notifications.", notifications::mount())
 		.merge("backups.", backups::mount())
+		.merge("trash.", trash::mount())
 		.merge("invalidation.", utils::mount_invalidate())
 		.sd_patch_types_dangerously(|type_map| {
 			let def =
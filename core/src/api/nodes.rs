@@ -1,4 +1,4 @@
-use std::collections::HashSet;
+use std::{collections::HashSet, path::PathBuf};
 
 use crate::{
 	invalidate_query,
@@ -28,6 +28,8 @@ pub(crate) fn mount() -> AlphaRouter<Ctx> {
 				pub p2p_discovery: Option<P2PDiscoveryState>,
 				pub p2p_remote_access: Option<bool>,
 				pub p2p_manual_peers: Option<HashSet<String>>,
+				/// An empty path stops using a rendezvous directory
+				pub p2p_rendezvous_dir: Option<PathBuf>,
 				pub image_labeler_version: Option<String>,
 			}
 			R.mutation(|node, args: ChangeNodeNameArgs| async move {
@@ -70,6 +72,10 @@ pub(crate) fn mount() -> AlphaRouter<Ctx> {
 						if let Some(manual_peers) = args.p2p_manual_peers {
 							config.p2p.manual_peers = manual_peers;
 						};
+						if let Some(rendezvous_dir) = args.p2p_rendezvous_dir {
+							config.p2p.rendezvous_dir =
+								(!rendezvous_dir.as_os_str().is_empty()).then_some(rendezvous_dir);
+						};
 
 						#[cfg(feature = "ai")]
 						if let Some(version) = args.image_labeler_version {
@@ -184,11 +190,13 @@ pub(crate) fn mount() -> AlphaRouter<Ctx> {
 			#[derive(Deserialize, Type)]
 			pub struct UpdateThumbnailerPreferences {
 				pub background_processing_percentage: u8, // 0-100
+				pub generate_video_previews: Option<bool>,
 			}
 			R.mutation(
 				|node,
 				 UpdateThumbnailerPreferences {
 				     background_processing_percentage,
+				     generate_video_previews,
 				 }: UpdateThumbnailerPreferences| async move {
 					node.config
 						.update_preferences(|preferences| {
@@ -197,6 +205,12 @@ pub(crate) fn mount() -> AlphaRouter<Ctx> {
 								.set_background_processing_percentage(
 									background_processing_percentage,
 								);
+
+							if let Some(generate_video_previews) = generate_video_previews {
+								preferences
+									.thumbnailer
+									.set_generate_video_previews(generate_video_previews);
+							}
 						})
 						.await
 						.map_err(|e| {
@@ -1,4 +1,7 @@
-use crate::p2p::{operations, ConnectionMethod, DiscoveryMethod, Header, P2PEvent, PeerMetadata};
+use crate::{
+	invalidate_query,
+	p2p::{operations, ConnectionMethod, DiscoveryMethod, Header, P2PEvent, PeerMetadata},
+};
 
 use sd_p2p::{PeerConnectionCandidate, RemoteIdentity};
 
@@ -13,6 +16,8 @@ use super::{Ctx, R};
 
 pub(crate) fn mount() -> AlphaRouter<Ctx> {
 	R.router()
+		.merge("pairing.", pairing::mount())
+		.merge("remoteAccess.", remote_access::mount())
 		.procedure("events", {
 			R.subscription(|node, _: ()| async move {
 				let mut rx = node.p2p.events.subscribe();
@@ -149,3 +154,122 @@ pub(crate) fn mount() -> AlphaRouter<Ctx> {
 			})
 		})
 }
+
+mod pairing {
+	use crate::{
+		api::{libraries::LibraryConfigWrapped, utils::library},
+		p2p::operations::pairing,
+	};
+
+	use super::*;
+
+	pub fn mount() -> AlphaRouter<Ctx> {
+		R.router()
+			.procedure("start", {
+				R.with2(library())
+					.mutation(|(node, library), _: ()| async move {
+						Ok(node.p2p.start_pairing(library.id))
+					})
+			})
+			.procedure("cancel", {
+				R.with2(library())
+					.mutation(|(node, library), _: ()| async move {
+						node.p2p.cancel_pairing(library.id);
+
+						Ok(())
+					})
+			})
+			.procedure("join", {
+				#[derive(Deserialize, Type)]
+				pub struct JoinPairingArgs {
+					/// The node to pair with, not needed when `code` is the payload of a pairing QR code
+					identity: Option<RemoteIdentity>,
+					code: String,
+				}
+
+				R.mutation(|node, args: JoinPairingArgs| async move {
+					let (identity, code) = match pairing::parse_payload(&args.code) {
+						Some(payload) => payload,
+						None => (
+							args.identity.ok_or_else(|| {
+								rspc::Error::new(
+									ErrorCode::BadRequest,
+									"the node to pair with is required".into(),
+								)
+							})?,
+							args.code,
+						),
+					};
+
+					let library = pairing::pair(&node, identity, code).await?;
+
+					Ok(LibraryConfigWrapped::from_library(&library).await)
+				})
+			})
+	}
+}
+
+mod remote_access {
+	use crate::p2p::RemoteAccessGrant;
+
+	use tracing::error;
+
+	use super::*;
+
+	pub fn mount() -> AlphaRouter<Ctx> {
+		R.router()
+			.procedure("list", {
+				R.query(|node, _: ()| async move { Ok(node.config.get().await.p2p.remote_access) })
+			})
+			.procedure("grant", {
+				R.mutation(|node, grant: RemoteAccessGrant| async move {
+					node.config
+						.write(|config| {
+							// A node only ever holds a single grant, granting it again changes its scope
+							config
+								.p2p
+								.remote_access
+								.retain(|existing| existing.identity != grant.identity);
+							config.p2p.remote_access.push(grant);
+						})
+						.await
+						.map_err(|err| {
+							error!("Failed to write config: {}", err);
+							rspc::Error::new(
+								ErrorCode::InternalServerError,
+								"error updating config".into(),
+							)
+						})?;
+
+					invalidate_query!(node; node, "p2p.remoteAccess.list");
+					invalidate_query!(node; node, "nodeState");
+
+					Ok(())
+				})
+			})
+			.procedure("revoke", {
+				R.mutation(|node, identity: RemoteIdentity| async move {
+					node.config
+						.write(|config| {
+							config
+								.p2p
+								.remote_access
+								.retain(|grant| grant.identity != identity);
+						})
+						.await
+						.map_err(|err| {
+							error!("Failed to write config: {}", err);
+							rspc::Error::new(
+								ErrorCode::InternalServerError,
+								"error updating config".into(),
+							)
+						})?;
+
+					invalidate_query!(node; node, "p2p.remoteAccess.list");
+					invalidate_query!(node; node, "nodeState");
+
+					Ok(())
+				})
+			})
+	}
+}
@@ -0,0 +1,38 @@
+use sd_prisma::prisma::{self, audio_data};
+
+use serde::{Deserialize, Serialize};
+use specta::Type;
+
+use super::utils::*;
+
+#[derive(Serialize, Deserialize, Type, Debug, Clone)]
+#[serde(rename_all = "camelCase", tag = "field", content = "value")]
+pub enum AudioDataOrder {
+	Artist(SortOrder),
+	Album(SortOrder),
+	Year(SortOrder),
+	TrackNumber(SortOrder),
+}
+
+impl AudioDataOrder {
+	pub fn get_sort_order(&self) -> prisma::SortOrder {
+		(*match self {
+			Self::Artist(v) => v,
+			Self::Album(v) => v,
+			Self::Year(v) => v,
+			Self::TrackNumber(v) => v,
+		})
+		.into()
+	}
+
+	pub fn into_param(self) -> audio_data::OrderByWithRelationParam {
+		let dir = self.get_sort_order();
+		use audio_data::*;
+		match self {
+			Self::Artist(_) => artist::order(dir),
+			Self::Album(_) => album::order(dir),
+			Self::Year(_) => year::order(dir),
+			Self::TrackNumber(_) => track_number::order(dir),
+		}
+	}
+}
@@ -5,7 +5,10 @@ use sd_core_file_path_helper::{check_file_path_exists, IsolatedFilePathData};
 use sd_prisma::prisma::{self, file_path};
 
 use chrono::{DateTime, FixedOffset, Utc};
-use prisma_client_rust::{OrderByQuery, PaginatedQuery, WhereQuery};
+use prisma_client_rust::{
+	operator::{not, or},
+	OrderByQuery, PaginatedQuery, WhereQuery,
+};
 use rspc::ErrorCode;
 use serde::{Deserialize, Serialize};
 use specta::Type;
@@ -70,6 +73,12 @@ pub enum FilePathFilterArgs {
 	ModifiedAt(Range<DateTime<Utc>>),
 	IndexedAt(Range<DateTime<Utc>>),
 	Hidden(bool),
+	/// Files anyone on the system can write to, according to their unix permissions
+	WorldWritable(bool),
+	Owner(InOrNotIn<String>),
+	OwnerGroup(InOrNotIn<String>),
+	/// Files with the named extended attribute, among the ones we index
+	ExtendedAttribute(String),
 }
 
 impl FilePathFilterArgs {
@@ -152,6 +161,39 @@ impl FilePathFilterArgs {
 			Self::Hidden(v) => {
 				vec![hidden::equals(Some(v))]
 			}
+			Self::WorldWritable(v) => {
+				// The write bit for others is the second to last character, like "rw-rw-rw-" or "rwxrwxrwt"
+				let world_writable = or(["w-", "wx", "wt", "wT"]
+					.into_iter()
+					.map(|suffix| permissions::ends_with(suffix.into()))
+					.collect());
+
+				if v {
+					vec![world_writable]
+				} else {
+					vec![permissions::not(None), not(vec![world_writable])]
+				}
+			}
+			Self::Owner(v) => v
+				.into_param(owner::in_vec, owner::not_in_vec)
+				.map(|v| vec![v])
+				.unwrap_or_default(),
+			Self::OwnerGroup(v) => v
+				.into_param(owner_group::in_vec, owner_group::not_in_vec)
+				.map(|v| vec![v])
+				.unwrap_or_default(),
+			Self::ExtendedAttribute(name) => {
+				// Names are the keys of the JSON object stored in `xattrs`
+				let key = serde_json::to_string(&name).map_err(|e| {
+					rspc::Error::with_cause(
+						ErrorCode::BadRequest,
+						"Invalid extended attribute name".into(),
+						e,
+					)
+				})?;
+
+				vec![xattrs::contains(format!("{key}:"))]
+			}
 		})
 	}
 }
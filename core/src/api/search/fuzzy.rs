@@ -0,0 +1,566 @@
+//! Typo tolerant file name matching, used by the `search.fuzzyPaths` procedure.
+//!
+//! Candidates are fetched from the `file_path_search` FTS5 trigram index, which is kept up to date
+//! by triggers on the `file_path` table, and then re-ranked here by how well their names match the
+//! query and how recently their objects were accessed.
+
+use sd_core_file_path_helper::IsolatedFilePathData;
+
+use sd_prisma::prisma::{file_path, PrismaClient};
+
+use chrono::{DateTime, FixedOffset, Utc};
+use itertools::Itertools;
+use prisma_client_rust::{PrismaValue, QueryError, Raw};
+use serde::Deserialize;
+
+use super::{FilePathFilterArgs, InOrNotIn, SearchFilterArgs};
+
+/// Maximum amount of rows fetched from the trigram index to be re-ranked
+const MAX_CANDIDATES: i64 = 2_000;
+/// Anything scoring below this is considered noise and discarded
+const MIN_SCORE: f64 = 0.45;
+/// Maximum boost given to an object that was just accessed
+const RECENCY_WEIGHT: f64 = 0.25;
+/// Every this many days without being accessed, the recency boost is halved
+const RECENCY_HALF_LIFE_DAYS: f64 = 14.0;
+
+/// Splits a file name or a search query into lowercase words, treating any non alphanumeric
+/// character and camelCase boundaries as separators.
+///
+/// So `my_report-final.pdf`, `my report final pdf` and `MyReportFinal.pdf` all produce the same words.
+pub fn tokenize(text: &str) -> Vec<String> {
+	let mut tokens = vec![];
+	let mut current = String::new();
+	let mut prev_is_lowercase = false;
+
+	for c in text.chars() {
+		if !c.is_alphanumeric() {
+			if !current.is_empty() {
+				tokens.push(std::mem::take(&mut current));
+			}
+			prev_is_lowercase = false;
+			continue;
+		}
+
+		if c.is_uppercase() && prev_is_lowercase {
+			tokens.push(std::mem::take(&mut current));
+		}
+
+		prev_is_lowercase = c.is_lowercase();
+		current.extend(c.to_lowercase());
+	}
+
+	if !current.is_empty() {
+		tokens.push(current);
+	}
+
+	tokens
+}
+
+/// Builds a FTS5 `MATCH` expression that matches any name sharing at least one trigram with the query,
+/// returns `None` if every word in the query is too short to have a trigram.
+fn match_expression(query_tokens: &[String]) -> Option<String> {
+	let expression = query_tokens
+		.iter()
+		.flat_map(|token| {
+			token
+				.chars()
+				.collect::<Vec<_>>()
+				.windows(3)
+				.map(|trigram| trigram.iter().collect::<String>())
+				.collect::<Vec<_>>()
+		})
+		.unique()
+		// Tokens only have alphanumeric chars, so quoting them is enough to escape them
+		.map(|trigram| format!("\"{trigram}\""))
+		.join(" OR ");
+
+	(!expression.is_empty()).then_some(expression)
+}
+
+/// Conditions on the `file_path` table checked along with the name match, before candidates are capped,
+/// so names matching elsewhere can't push the ones in a scoped search out of the candidates.
+///
+/// Only filters on the file path's own columns are turned into conditions, every filter is still
+/// applied again when the candidates are fetched.
+#[derive(Debug, Default)]
+pub struct CandidateFilters {
+	conditions: Vec<String>,
+	params: Vec<PrismaValue>,
+}
+
+impl CandidateFilters {
+	pub fn new(filters: &[SearchFilterArgs]) -> Self {
+		let mut candidate_filters = Self::default();
+
+		for filter in filters {
+			let SearchFilterArgs::FilePath(filter) = filter else {
+				continue;
+			};
+
+			match filter {
+				FilePathFilterArgs::Locations(location_ids) => {
+					candidate_filters.push_in_or_not_in("fp.location_id", location_ids, |id| {
+						PrismaValue::Int(i64::from(*id))
+					})
+				}
+				FilePathFilterArgs::Path {
+					location_id,
+					path,
+					include_descendants,
+				} => {
+					let materialized_path = if !path.is_empty() && path != "/" {
+						IsolatedFilePathData::from_relative_str(*location_id, path)
+							.materialized_path_for_children()
+					} else {
+						Some("/".into())
+					};
+
+					match materialized_path {
+						Some(materialized_path) if *include_descendants => candidate_filters.push(
+							"fp.materialized_path LIKE {} ESCAPE '\\'",
+							[PrismaValue::String(format!(
+								"{}%",
+								escape_like(&materialized_path)
+							))],
+						),
+						Some(materialized_path) => candidate_filters.push(
+							"fp.materialized_path = {}",
+							[PrismaValue::String(materialized_path)],
+						),
+						None => {}
+					}
+				}
+				FilePathFilterArgs::Extension(extensions) => {
+					candidate_filters.push_in_or_not_in("fp.extension", extensions, |extension| {
+						PrismaValue::String(extension.clone())
+					})
+				}
+				FilePathFilterArgs::Hidden(hidden) => {
+					candidate_filters.push("fp.hidden = {}", [PrismaValue::Boolean(*hidden)]);
+				}
+				_ => {}
+			}
+		}
+
+		candidate_filters
+	}
+
+	fn push(&mut self, condition: &str, params: impl IntoIterator<Item = PrismaValue>) {
+		self.conditions.push(condition.to_string());
+		self.params.extend(params);
+	}
+
+	fn push_in_or_not_in<T>(
+		&mut self,
+		column: &str,
+		values: &InOrNotIn<T>,
+		to_param: impl Fn(&T) -> PrismaValue,
+	) {
+		let (operator, values) = match values {
+			InOrNotIn::In(values) => ("IN", values),
+			InOrNotIn::NotIn(values) => ("NOT IN", values),
+		};
+
+		// Just like the filter itself, an empty list doesn't filter anything
+		if !values.is_empty() {
+			self.push(
+				&format!(
+					"{column} {operator} ({})",
+					vec!["{}"; values.len()].join(", ")
+				),
+				values.iter().map(to_param),
+			);
+		}
+	}
+
+	fn sql(&self) -> String {
+		self.conditions
+			.iter()
+			.map(|condition| format!(" AND {condition}"))
+			.collect()
+	}
+}
+
+fn escape_like(text: &str) -> String {
+	text.replace('\\', "\\\\")
+		.replace('%', "\\%")
+		.replace('_', "\\_")
+}
+
+/// Builds the query fetching the ids of the file paths that are worth ranking against the query,
+/// with `{}` placeholders for its parameters
+fn candidates_query(
+	query_tokens: &[String],
+	filters: &CandidateFilters,
+) -> Option<(String, Vec<PrismaValue>)> {
+	let (query, mut params) = if let Some(expression) = match_expression(query_tokens) {
+		// Ordering by bm25 puts the names sharing the most trigrams with the query first
+		(
+			format!(
+				"SELECT fp.id AS id FROM file_path_search
+				INNER JOIN file_path fp ON fp.id = file_path_search.rowid
+				WHERE file_path_search MATCH {{}}{}
+				ORDER BY bm25(file_path_search)
+				LIMIT {{}}",
+				filters.sql()
+			),
+			vec![PrismaValue::String(expression)],
+		)
+	} else {
+		// Queries this short can't use the trigram index, so we just look for names starting with it
+		let first_token = query_tokens.first()?;
+
+		(
+			format!(
+				"SELECT fp.id AS id FROM file_path fp
+				WHERE fp.name LIKE {{}} ESCAPE '\\'{}
+				LIMIT {{}}",
+				filters.sql()
+			),
+			vec![PrismaValue::String(format!(
+				"{}%",
+				escape_like(first_token)
+			))],
+		)
+	};
+
+	params.extend(filters.params.iter().cloned());
+	params.push(PrismaValue::Int(MAX_CANDIDATES));
+
+	Some((query, params))
+}
+
+/// Fetches the ids of the file paths that are worth ranking against the query.
+pub async fn candidates(
+	db: &PrismaClient,
+	query_tokens: &[String],
+	filters: &CandidateFilters,
+) -> Result<Vec<file_path::id::Type>, QueryError> {
+	#[derive(Deserialize)]
+	struct Candidate {
+		id: file_path::id::Type,
+	}
+
+	let Some((query, params)) = candidates_query(query_tokens, filters) else {
+		return Ok(vec![]);
+	};
+
+	db._query_raw::<Candidate>(Raw::new(&query, params))
+		.exec()
+		.await
+		.map(|candidates| {
+			candidates
+				.into_iter()
+				.map(|candidate| candidate.id)
+				.collect()
+		})
+}
+
+/// Scores a file path against the query, returning `None` if it isn't a relevant match.
+///
+/// Higher is better, a perfect name match scores `1.0` plus the recency boost.
+pub fn score(
+	query_tokens: &[String],
+	name: Option<&str>,
+	extension: Option<&str>,
+	date_accessed: Option<DateTime<FixedOffset>>,
+	now: DateTime<Utc>,
+) -> Option<f64> {
+	let mut candidate_tokens = tokenize(name.unwrap_or_default());
+	candidate_tokens.extend(tokenize(extension.unwrap_or_default()));
+
+	name_score(query_tokens, &candidate_tokens)
+		.map(|score| score + recency_boost(date_accessed, now))
+}
+
+fn name_score(query_tokens: &[String], candidate_tokens: &[String]) -> Option<f64> {
+	if query_tokens.is_empty() || candidate_tokens.is_empty() {
+		return None;
+	}
+
+	// Catches words glued together, like `myreport` against `my_report`
+	let compact = candidate_tokens.concat();
+
+	let mut total = 0.0;
+	let mut last_position = 0;
+	let mut in_order = true;
+
+	for query_token in query_tokens {
+		let (position, mut best) = candidate_tokens
+			.iter()
+			.map(|candidate_token| token_similarity(query_token, candidate_token))
+			.enumerate()
+			.max_by(|(_, a), (_, b)| a.total_cmp(b))?;
+
+		if best < 0.75 && compact.contains(query_token.as_str()) {
+			best = 0.75;
+		}
+
+		in_order &= position >= last_position;
+		last_position = position;
+		total += best;
+	}
+
+	let mut score = total / query_tokens.len() as f64;
+
+	if in_order && query_tokens.len() > 1 {
+		score += 0.05;
+	}
+
+	// Between two equally good matches, prefer the one with fewer extra words
+	score -= (0.02 * candidate_tokens.len().saturating_sub(query_tokens.len()) as f64).min(0.1);
+
+	(score >= MIN_SCORE).then_some(score)
+}
+
+fn token_similarity(query: &str, candidate: &str) -> f64 {
+	if candidate == query {
+		return 1.0;
+	}
+
+	if candidate.starts_with(query) {
+		return 0.9;
+	}
+
+	if candidate.contains(query) {
+		return 0.75;
+	}
+
+	let query = query.chars().collect::<Vec<_>>();
+	let candidate = candidate.chars().collect::<Vec<_>>();
+
+	let max_typos = match query.len() {
+		0..=2 => return 0.0,
+		3..=5 => 1,
+		_ => 2,
+	};
+
+	// Also comparing against the candidate's prefix, so words still being typed can match
+	let distance = if candidate.len() > query.len() {
+		edit_distance(&query, &candidate).min(edit_distance(&query, &candidate[..query.len()]))
+	} else {
+		edit_distance(&query, &candidate)
+	};
+
+	if distance <= max_typos {
+		0.7 - 0.15 * distance as f64
+	} else {
+		0.0
+	}
+}
+
+/// Optimal string alignment distance, a Levenshtein distance that also counts
+/// swapping two adjacent characters as a single typo.
+fn edit_distance(a: &[char], b: &[char]) -> usize {
+	let mut before_previous = vec![0; b.len() + 1];
+	let mut previous = (0..=b.len()).collect::<Vec<_>>();
+	let mut current = vec![0; b.len() + 1];
+
+	for i in 1..=a.len() {
+		current[0] = i;
+
+		for j in 1..=b.len() {
+			let cost = usize::from(a[i - 1] != b[j - 1]);
+
+			current[j] = (previous[j] + 1)
+				.min(current[j - 1] + 1)
+				.min(previous[j - 1] + cost);
+
+			if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+				current[j] = current[j].min(before_previous[j - 2] + 1);
+			}
+		}
+
+		std::mem::swap(&mut before_previous, &mut previous);
+		std::mem::swap(&mut previous, &mut current);
+	}
+
+	previous[b.len()]
+}
+
+fn recency_boost(date_accessed: Option<DateTime<FixedOffset>>, now: DateTime<Utc>) -> f64 {
+	date_accessed.map_or(0.0, |date_accessed| {
+		let age_in_days = (now - date_accessed.with_timezone(&Utc))
+			.num_seconds()
+			.max(0) as f64
+			/ 86_400.0;
+
+		RECENCY_WEIGHT * 0.5_f64.powf(age_in_days / RECENCY_HALF_LIFE_DAYS)
+	})
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	use chrono::Duration;
+
+	fn score_name(query: &str, name: &str) -> Option<f64> {
+		score(&tokenize(query), Some(name), None, None, Utc::now())
+	}
+
+	/// Runs the candidates query against a database with a `file_path` row for each of `rows`,
+	/// given as `(location_id, materialized_path, name)`
+	fn candidate_ids(
+		rows: &[(i32, &str, &str)],
+		query: &str,
+		filters: &[SearchFilterArgs],
+	) -> Vec<i64> {
+		let conn = rusqlite::Connection::open_in_memory().unwrap();
+		conn.execute_batch(
+			"CREATE TABLE file_path (
+				id INTEGER PRIMARY KEY,
+				location_id INTEGER,
+				materialized_path TEXT,
+				name TEXT,
+				extension TEXT,
+				hidden BOOLEAN
+			);
+			CREATE VIRTUAL TABLE file_path_search USING fts5(name, tokenize = 'trigram');
+			CREATE TRIGGER file_path_search_after_insert AFTER INSERT ON file_path
+			BEGIN
+				INSERT INTO file_path_search (rowid, name) VALUES (new.id, new.name);
+			END;",
+		)
+		.unwrap();
+
+		for (location_id, materialized_path, name) in rows {
+			conn.execute(
+				"INSERT INTO file_path (location_id, materialized_path, name) VALUES (?, ?, ?)",
+				rusqlite::params![location_id, materialized_path, name],
+			)
+			.unwrap();
+		}
+
+		let (query, params) =
+			candidates_query(&tokenize(query), &CandidateFilters::new(filters)).unwrap();
+		let params = params
+			.into_iter()
+			.map(|param| match param {
+				PrismaValue::Int(i) => rusqlite::types::Value::Integer(i),
+				PrismaValue::Boolean(b) => rusqlite::types::Value::Integer(i64::from(b)),
+				PrismaValue::String(s) => rusqlite::types::Value::Text(s),
+				_ => unreachable!(),
+			})
+			.collect::<Vec<_>>();
+
+		let mut statement = conn.prepare(&query.replace("{}", "?")).unwrap();
+		let ids = statement
+			.query_map(rusqlite::params_from_iter(params), |row| row.get(0))
+			.unwrap()
+			.collect::<Result<_, _>>()
+			.unwrap();
+
+		ids
+	}
+
+	#[test]
+	fn filters_apply_before_candidates_are_capped() {
+		let in_scope = IsolatedFilePathData::from_relative_str(1, "/docs_2024/")
+			.materialized_path_for_children()
+			.unwrap();
+		let out_of_scope = in_scope.replace('_', "X");
+
+		// Way more matches than we keep as candidates outside of the scope, which also match better
+		let mut rows = vec![(2, "/", "report"); MAX_CANDIDATES as usize + 500];
+		rows.extend([
+			(1, out_of_scope.as_str(), "old report"),
+			(1, in_scope.as_str(), "annual report"),
+			(1, in_scope.as_str(), "reports"),
+		]);
+		let scoped_ids = vec![rows.len() as i64 - 1, rows.len() as i64];
+
+		let unscoped = candidate_ids(&rows, "report", &[]);
+		assert_eq!(unscoped.len(), MAX_CANDIDATES as usize);
+		assert!(!unscoped.iter().any(|id| scoped_ids.contains(id)));
+
+		let mut scoped = candidate_ids(
+			&rows,
+			"report",
+			&[
+				SearchFilterArgs::FilePath(FilePathFilterArgs::Locations(InOrNotIn::In(vec![1]))),
+				SearchFilterArgs::FilePath(FilePathFilterArgs::Path {
+					location_id: 1,
+					path: "/docs_2024/".to_string(),
+					include_descendants: true,
+				}),
+			],
+		);
+		scoped.sort_unstable();
+		assert_eq!(scoped, scoped_ids);
+
+		// Too short for the trigram index
+		let scoped = candidate_ids(
+			&rows,
+			"re",
+			&[SearchFilterArgs::FilePath(FilePathFilterArgs::Locations(
+				InOrNotIn::NotIn(vec![2]),
+			))],
+		);
+		assert_eq!(scoped, vec![rows.len() as i64]);
+	}
+
+	#[test]
+	fn tokenize_ignores_separators_and_case() {
+		let expected = vec!["my", "report", "final"];
+
+		assert_eq!(tokenize("my_report-final"), expected);
+		assert_eq!(tokenize("my report final"), expected);
+		assert_eq!(tokenize("MyReportFinal"), expected);
+		assert_eq!(tokenize("  MY.report__final "), expected);
+	}
+
+	#[test]
+	fn edit_distance_counts_transpositions_once() {
+		let chars = |s: &str| s.chars().collect::<Vec<_>>();
+
+		assert_eq!(edit_distance(&chars("report"), &chars("report")), 0);
+		assert_eq!(edit_distance(&chars("reprot"), &chars("report")), 1);
+		assert_eq!(edit_distance(&chars("rport"), &chars("report")), 1);
+		assert_eq!(edit_distance(&chars("kitten"), &chars("sitting")), 3);
+	}
+
+	#[test]
+	fn tolerates_separators_and_typos() {
+		assert!(score_name("my report final", "my_report-final").is_some());
+		assert!(score_name("myreport", "my_report-final").is_some());
+		assert!(score_name("reprot fnial", "my_report-final").is_some());
+		assert!(score_name("invoice", "my_report-final").is_none());
+	}
+
+	#[test]
+	fn ranks_better_matches_first() {
+		let exact = score_name("report", "report").unwrap();
+		let prefix = score_name("report", "reports").unwrap();
+		let typo = score_name("reprot", "report").unwrap();
+
+		assert!(exact > prefix);
+		assert!(prefix > typo);
+	}
+
+	#[test]
+	fn recently_accessed_files_rank_higher() {
+		let now = Utc::now();
+		let tokens = tokenize("report");
+
+		let recent = score(
+			&tokens,
+			Some("report"),
+			None,
+			Some((now - Duration::hours(1)).into()),
+			now,
+		)
+		.unwrap();
+		let old = score(
+			&tokens,
+			Some("report"),
+			None,
+			Some((now - Duration::days(365)).into()),
+			now,
+		)
+		.unwrap();
+
+		assert!(recent > old);
+	}
+}
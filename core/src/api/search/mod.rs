@@ -10,19 +10,23 @@ use prisma_client_rust::Operator;
 use sd_core_prisma_helpers::{file_path_for_frontend, object_with_file_paths};
 use sd_prisma::prisma::{self, PrismaClient};
 
-use std::path::PathBuf;
+use std::{cmp::Ordering, collections::HashMap, path::PathBuf};
 
 use async_stream::stream;
+use chrono::Utc;
 use futures::StreamExt;
 use itertools::Either;
 use rspc::{alpha::AlphaRouter, ErrorCode};
 use serde::{Deserialize, Serialize};
 use specta::Type;
 
+pub mod audio_data;
 pub mod exif_data;
 pub mod file_path;
+mod fuzzy;
 pub mod object;
 pub mod saved;
+mod similar;
 mod utils;
 
 pub use self::{file_path::*, object::*, utils::*};
@@ -244,6 +248,111 @@ pub fn mount() -> AlphaRouter<Ctx> {
 				},
 			)
 		})
+		.procedure("fuzzyPaths", {
+			#[derive(Deserialize, Type, Debug)]
+			#[serde(rename_all = "camelCase")]
+			struct FuzzyPathSearchArgs {
+				query: String,
+				#[specta(optional)]
+				take: Option<u8>,
+				#[serde(default)]
+				filters: Vec<SearchFilterArgs>,
+			}
+
+			R.with2(library()).query(
+				|(node, library),
+				 FuzzyPathSearchArgs {
+				     query,
+				     take,
+				     filters,
+				 }| async move {
+					let Library { db, .. } = library.as_ref();
+
+					let query_tokens = fuzzy::tokenize(&query);
+
+					let candidate_ids = fuzzy::candidates(
+						db,
+						&query_tokens,
+						&fuzzy::CandidateFilters::new(&filters),
+					)
+					.await?;
+					if candidate_ids.is_empty() {
+						return Ok(SearchData {
+							items: vec![],
+							cursor: None,
+						});
+					}
+
+					let params = {
+						let (mut fp, obj) = merge_filters(filters, db).await?;
+
+						if !obj.is_empty() {
+							fp.push(prisma::file_path::object::is(obj));
+						}
+
+						fp.push(prisma::file_path::id::in_vec(candidate_ids));
+
+						fp
+					};
+
+					let now = Utc::now();
+
+					let mut ranked_file_paths = db
+						.file_path()
+						.find_many(andify(params))
+						.include(file_path_for_frontend::include())
+						.exec()
+						.await?
+						.into_iter()
+						.filter_map(|file_path| {
+							fuzzy::score(
+								&query_tokens,
+								file_path.name.as_deref(),
+								file_path.extension.as_deref(),
+								file_path
+									.object
+									.as_ref()
+									.and_then(|object| object.date_accessed),
+								now,
+							)
+							.map(|score| (score, file_path))
+						})
+						.collect::<Vec<_>>();
+
+					ranked_file_paths.sort_unstable_by(|(score1, _), (score2, _)| {
+						score2.partial_cmp(score1).unwrap_or(Ordering::Equal)
+					});
+					ranked_file_paths.truncate(take.unwrap_or(MAX_TAKE).min(MAX_TAKE) as usize);
+
+					let mut items = Vec::with_capacity(ranked_file_paths.len());
+
+					for (_, file_path) in ranked_file_paths {
+						let has_created_thumbnail = if let Some(cas_id) = &file_path.cas_id {
+							library
+								.thumbnail_exists(&node, cas_id)
+								.await
+								.map_err(LocationError::from)?
+						} else {
+							false
+						};
+
+						items.push(ExplorerItem::Path {
+							thumbnail: file_path
+								.cas_id
+								.as_ref()
+								.map(|i| get_indexed_thumb_key(i, library.id)),
+							has_created_thumbnail,
+							item: Box::new(file_path),
+						})
+					}
+
+					Ok(SearchData {
+						items,
+						cursor: None,
+					})
+				},
+			)
+		})
 		.procedure("pathsCount", {
 			#[derive(Deserialize, Type, Debug)]
 			#[serde(rename_all = "camelCase")]
@@ -387,6 +496,111 @@ pub fn mount() -> AlphaRouter<Ctx> {
 						.await? as u32)
 				})
 		})
+		.procedure("similar", {
+			#[derive(Deserialize, Type, Debug)]
+			#[serde(rename_all = "camelCase")]
+			struct SimilarSearchArgs {
+				/// Objects that look like this one, if not set every group of similar objects
+				/// in the library is returned instead
+				#[specta(optional)]
+				object_id: Option<prisma::object::id::Type>,
+				#[specta(optional)]
+				max_distance: Option<u32>,
+				#[specta(optional)]
+				take: Option<u8>,
+			}
+
+			#[derive(Serialize, Type, Debug)]
+			struct SimilarItem {
+				/// Hamming distance between the perceptual hashes of this object and the first
+				/// object in its group
+				distance: u32,
+				item: ExplorerItem,
+			}
+
+			R.with2(library()).query(
+				|(node, library),
+				 SimilarSearchArgs {
+				     object_id,
+				     max_distance,
+				     take,
+				 }| async move {
+					let Library { db, .. } = library.as_ref();
+
+					let max_distance = max_distance
+						.unwrap_or(similar::DEFAULT_MAX_DISTANCE)
+						.min(similar::MAX_DISTANCE);
+
+					let hashes = similar::fetch_hashes(db).await?;
+
+					let mut groups = if let Some(object_id) = object_id {
+						let group = similar::similar_to(&hashes, object_id, max_distance);
+						if group.len() < 2 {
+							// Not hashed yet, or nothing looks like it
+							return Ok(vec![]);
+						}
+
+						vec![group]
+					} else {
+						similar::cluster(&hashes, max_distance)
+					};
+
+					groups.truncate(take.unwrap_or(MAX_TAKE).min(MAX_TAKE) as usize);
+
+					let mut objects = db
+						.object()
+						.find_many(vec![prisma::object::id::in_vec(
+							groups.iter().flatten().map(|&(_, id)| id).collect(),
+						)])
+						.include(object_with_file_paths::include())
+						.exec()
+						.await?
+						.into_iter()
+						.map(|object| (object.id, object))
+						.collect::<HashMap<_, _>>();
+
+					let mut result = Vec::with_capacity(groups.len());
+
+					for group in groups {
+						let mut items = Vec::with_capacity(group.len());
+
+						for (distance, id) in group {
+							let Some(object) = objects.remove(&id) else {
+								continue;
+							};
+
+							let cas_id = object.file_paths.iter().find_map(|fp| fp.cas_id.clone());
+
+							let has_created_thumbnail = if let Some(cas_id) = &cas_id {
+								library.thumbnail_exists(&node, cas_id).await.map_err(|e| {
+									rspc::Error::with_cause(
+										ErrorCode::InternalServerError,
+										"Failed to check that thumbnail exists".to_string(),
+										e,
+									)
+								})?
+							} else {
+								false
+							};
+
+							items.push(SimilarItem {
+								distance,
+								item: ExplorerItem::Object {
+									thumbnail: cas_id
+										.map(|cas_id| get_indexed_thumb_key(&cas_id, library.id)),
+									item: object,
+									has_created_thumbnail,
+								},
+							});
+						}
+
+						result.push(items);
+					}
+
+					Ok(result)
+				},
+			)
+		})
 		.merge("saved.", saved::mount())
 }
 
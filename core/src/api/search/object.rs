@@ -1,6 +1,6 @@
 // use crate::library::Category;
 
-use sd_prisma::prisma::{self, label_on_object, object, tag_on_object};
+use sd_prisma::prisma::{self, label_on_object, media_subtitle, object, tag_on_object};
 
 use chrono::{DateTime, FixedOffset};
 use prisma_client_rust::{not, or, OrderByQuery, PaginatedQuery, WhereQuery};
@@ -8,6 +8,7 @@ use serde::{Deserialize, Serialize};
 use specta::Type;
 
 use super::{
+	audio_data::*,
 	exif_data::*,
 	utils::{self, *},
 };
@@ -62,6 +63,7 @@ pub enum ObjectOrder {
 	DateAccessed(SortOrder),
 	Kind(SortOrder),
 	MediaData(Box<ExifDataOrder>),
+	AudioData(Box<AudioDataOrder>),
 }
 
 impl ObjectOrder {
@@ -70,6 +72,7 @@ impl ObjectOrder {
 			Self::DateAccessed(v) => v,
 			Self::Kind(v) => v,
 			Self::MediaData(v) => return v.get_sort_order(),
+			Self::AudioData(v) => return v.get_sort_order(),
 		})
 		.into()
 	}
@@ -82,6 +85,7 @@ impl ObjectOrder {
 			Self::DateAccessed(_) => date_accessed::order(dir),
 			Self::Kind(_) => kind::order(dir),
 			Self::MediaData(v) => exif_data::order(vec![v.into_param()]),
+			Self::AudioData(v) => audio_data::order(vec![v.into_param()]),
 		}
 	}
 }
@@ -115,6 +119,15 @@ pub enum ObjectFilterArgs {
 	Tags(InOrNotIn<i32>),
 	Labels(InOrNotIn<i32>),
 	DateAccessed(Range<chrono::DateTime<FixedOffset>>),
+	/// A line of dialogue in any of the subtitle tracks of a video
+	Subtitles(TextMatch),
+	Artist(TextMatch),
+	Album(TextMatch),
+	Year(InOrNotIn<i32>),
+	TrackNumber(InOrNotIn<i32>),
+	/// Author of a document or ebook
+	Author(TextMatch),
+	PageCount(Range<i32>),
 }
 
 impl ObjectFilterArgs {
@@ -151,6 +164,60 @@ impl ObjectFilterArgs {
 					},
 				]
 			}
+			Self::Subtitles(v) => v
+				.into_param(
+					media_subtitle::text::contains,
+					media_subtitle::text::starts_with,
+					media_subtitle::text::ends_with,
+					media_subtitle::text::equals,
+				)
+				.map(|v| vec![subtitles::some(vec![v])])
+				.unwrap_or_default(),
+			Self::Artist(v) => v
+				.into_param(
+					prisma::audio_data::artist::contains,
+					prisma::audio_data::artist::starts_with,
+					prisma::audio_data::artist::ends_with,
+					|s| prisma::audio_data::artist::equals(Some(s)),
+				)
+				.map(|v| vec![audio_data::is(vec![v])])
+				.unwrap_or_default(),
+			Self::Album(v) => v
+				.into_param(
+					prisma::audio_data::album::contains,
+					prisma::audio_data::album::starts_with,
+					prisma::audio_data::album::ends_with,
+					|s| prisma::audio_data::album::equals(Some(s)),
+				)
+				.map(|v| vec![audio_data::is(vec![v])])
+				.unwrap_or_default(),
+			Self::Year(v) => v
+				.into_param(
+					prisma::audio_data::year::in_vec,
+					prisma::audio_data::year::not_in_vec,
+				)
+				.map(|v| vec![audio_data::is(vec![v])])
+				.unwrap_or_default(),
+			Self::TrackNumber(v) => v
+				.into_param(
+					prisma::audio_data::track_number::in_vec,
+					prisma::audio_data::track_number::not_in_vec,
+				)
+				.map(|v| vec![audio_data::is(vec![v])])
+				.unwrap_or_default(),
+			Self::Author(v) => v
+				.into_param(
+					prisma::document_data::author::contains,
+					prisma::document_data::author::starts_with,
+					prisma::document_data::author::ends_with,
+					|s| prisma::document_data::author::equals(Some(s)),
+				)
+				.map(|v| vec![document_data::is(vec![v])])
+				.unwrap_or_default(),
+			Self::PageCount(v) => vec![document_data::is(vec![match v {
+				Range::From(v) => prisma::document_data::page_count::gte(v),
+				Range::To(v) => prisma::document_data::page_count::lte(v),
+			}])],
 		}
 	}
 }
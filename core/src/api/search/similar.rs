@@ -0,0 +1,226 @@
+//! Visually similar objects, used by the `search.similar` procedure.
+//!
+//! Objects are compared by the Hamming distance between their perceptual hashes, which are
+//! indexed in a BK-tree so we don't have to compare every pair of objects in the library.
+
+use crate::object::media::perceptual_hash::{from_bytes, hamming_distance};
+
+use sd_prisma::prisma::{object, PrismaClient};
+
+use std::collections::{HashMap, HashSet};
+
+use prisma_client_rust::QueryError;
+
+/// Default maximum Hamming distance for two objects to be considered similar, out of 64 bits
+pub const DEFAULT_MAX_DISTANCE: u32 = 10;
+/// Past this distance pretty much anything is "similar", so we don't allow it
+pub const MAX_DISTANCE: u32 = 20;
+
+struct Node<T> {
+	hash: u64,
+	ids: Vec<T>,
+	/// Children by their distance to this node
+	children: HashMap<u32, usize>,
+}
+
+/// A BK-tree over the Hamming distance, objects with the same hash share a node
+pub struct BkTree<T> {
+	nodes: Vec<Node<T>>,
+}
+
+impl<T> Default for BkTree<T> {
+	fn default() -> Self {
+		Self { nodes: vec![] }
+	}
+}
+
+impl<T: Copy> BkTree<T> {
+	pub fn insert(&mut self, hash: u64, id: T) {
+		if self.nodes.is_empty() {
+			self.nodes.push(Node {
+				hash,
+				ids: vec![id],
+				children: HashMap::new(),
+			});
+			return;
+		}
+
+		let mut current = 0;
+		loop {
+			let distance = hamming_distance(self.nodes[current].hash, hash);
+			if distance == 0 {
+				self.nodes[current].ids.push(id);
+				return;
+			}
+
+			if let Some(&child) = self.nodes[current].children.get(&distance) {
+				current = child;
+			} else {
+				let new_node = self.nodes.len();
+				self.nodes.push(Node {
+					hash,
+					ids: vec![id],
+					children: HashMap::new(),
+				});
+				self.nodes[current].children.insert(distance, new_node);
+				return;
+			}
+		}
+	}
+
+	/// Every id within `max_distance` of `hash`, along with their distance
+	pub fn find(&self, hash: u64, max_distance: u32) -> Vec<(u32, T)> {
+		let mut found = vec![];
+
+		if self.nodes.is_empty() {
+			return found;
+		}
+
+		let mut to_visit = vec![0];
+		while let Some(current) = to_visit.pop() {
+			let node = &self.nodes[current];
+			let distance = hamming_distance(node.hash, hash);
+
+			if distance <= max_distance {
+				found.extend(node.ids.iter().map(|&id| (distance, id)));
+			}
+
+			// By the triangle inequality, only children in this range can be close enough
+			to_visit.extend(
+				node.children
+					.iter()
+					.filter(|(&child_distance, _)| {
+						child_distance.abs_diff(distance) <= max_distance
+					})
+					.map(|(_, &child)| child),
+			);
+		}
+
+		found
+	}
+}
+
+/// Groups similar objects around a leader, with each member's distance to it, so a group never
+/// drifts through a chain of slightly different images. Only groups with more than one object
+/// are returned, biggest first.
+pub fn cluster<T: Copy + Eq + std::hash::Hash + Ord>(
+	hashes: &[(T, u64)],
+	max_distance: u32,
+) -> Vec<Vec<(u32, T)>> {
+	let mut tree = BkTree::default();
+	for &(id, hash) in hashes {
+		tree.insert(hash, id);
+	}
+
+	let mut grouped = HashSet::with_capacity(hashes.len());
+	let mut groups = vec![];
+
+	for &(leader, hash) in hashes {
+		if grouped.contains(&leader) {
+			continue;
+		}
+
+		let mut group = tree
+			.find(hash, max_distance)
+			.into_iter()
+			.filter(|(_, id)| *id == leader || !grouped.contains(id))
+			.collect::<Vec<_>>();
+
+		if group.len() < 2 {
+			continue;
+		}
+
+		// Leader first, then the closest ones
+		group.sort_unstable_by_key(|&(distance, id)| (id != leader, distance, id));
+		grouped.extend(group.iter().map(|&(_, id)| id));
+		groups.push(group);
+	}
+
+	groups.sort_by_key(|group| std::cmp::Reverse(group.len()));
+
+	groups
+}
+
+/// Objects within `max_distance` of `leader`, including itself as the first one, then the
+/// closest ones. It's just a single lookup, so there's no point in building a tree for it.
+pub fn similar_to<T: Copy + Eq + Ord>(
+	hashes: &[(T, u64)],
+	leader: T,
+	max_distance: u32,
+) -> Vec<(u32, T)> {
+	let Some(&(_, leader_hash)) = hashes.iter().find(|(id, _)| *id == leader) else {
+		return vec![];
+	};
+
+	let mut group = hashes
+		.iter()
+		.map(|&(id, hash)| (hamming_distance(leader_hash, hash), id))
+		.filter(|&(distance, _)| distance <= max_distance)
+		.collect::<Vec<_>>();
+
+	group.sort_unstable_by_key(|&(distance, id)| (id != leader, distance, id));
+
+	group
+}
+
+/// Every object in the library that has a perceptual hash
+pub async fn fetch_hashes(db: &PrismaClient) -> Result<Vec<(object::id::Type, u64)>, QueryError> {
+	Ok(db
+		.object()
+		.find_many(vec![object::perceptual_hash::not(None)])
+		.select(object::select!({ id perceptual_hash }))
+		.exec()
+		.await?
+		.into_iter()
+		.filter_map(|object| {
+			object
+				.perceptual_hash
+				.as_deref()
+				.and_then(from_bytes)
+				.map(|hash| (object.id, hash))
+		})
+		.collect())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn finds_within_distance() {
+		let mut tree = BkTree::default();
+		tree.insert(0b0000, 1);
+		tree.insert(0b0001, 2);
+		tree.insert(0b0011, 3);
+		tree.insert(0b1111, 4);
+		tree.insert(0b0000, 5);
+
+		let mut found = tree.find(0, 1);
+		found.sort_unstable();
+		assert_eq!(found, vec![(0, 1), (0, 5), (1, 2)]);
+
+		assert_eq!(tree.find(u64::MAX, 3), vec![]);
+		assert_eq!(tree.find(0b1111, 0), vec![(0, 4)]);
+	}
+
+	#[test]
+	fn clusters_around_leaders() {
+		let hashes = [
+			(1, 0b0000_0000),
+			(2, 0b0000_0001),
+			(3, u64::MAX),
+			(4, 0b0000_0011),
+			(5, u64::MAX - 1),
+			(6, 0b1111_0000),
+		];
+
+		assert_eq!(
+			cluster(&hashes, 2),
+			vec![vec![(0, 1), (1, 2), (2, 4)], vec![(0, 3), (1, 5)]]
+		);
+		assert_eq!(cluster(&hashes, 0), Vec::<Vec<(u32, i32)>>::new());
+
+		assert_eq!(similar_to(&hashes, 4, 1), vec![(0, 4), (1, 2)]);
+		assert_eq!(similar_to(&hashes, 7, 64), vec![]);
+	}
+}
@@ -1,7 +1,11 @@
-use crate::{invalidate_query, library::Library, object::tag::TagCreateArgs};
+use crate::{
+	invalidate_query,
+	library::Library,
+	object::tag::{desktop::write_back_desktop_tags, TagCreateArgs},
+};
 
 use sd_prisma::{
-	prisma::{file_path, object, tag, tag_on_object},
+	prisma::{file_path, object, tag, tag_on_object, PrismaClient},
 	prisma_sync,
 };
 use sd_sync::{option_sync_db_entry, OperationFactory};
@@ -181,20 +185,20 @@ pub(crate) fn mount() -> AlphaRouter<Ctx> {
 						};
 					}
 
+					let mut object_ids = objects
+						.iter()
+						.map(|o| o.id)
+						.chain(
+							file_paths
+								.iter()
+								.filter_map(|fp| fp.object.as_ref().map(|o| o.id)),
+						)
+						.collect::<Vec<_>>();
+
 					if args.unassign {
 						let query = db.tag_on_object().delete_many(vec![
 							tag_on_object::tag_id::equals(args.tag_id),
-							tag_on_object::object_id::in_vec(
-								objects
-									.iter()
-									.map(|o| o.id)
-									.chain(
-										file_paths
-											.iter()
-											.filter_map(|fp| fp.object.as_ref().map(|o| o.id)),
-									)
-									.collect(),
-							),
+							tag_on_object::object_id::in_vec(object_ids.clone()),
 						]);
 
 						sync.write_ops(
@@ -249,6 +253,7 @@ pub(crate) fn mount() -> AlphaRouter<Ctx> {
 							.unzip();
 
 						let (new_objects, _) = sync.write_ops(db, (sync_params, db_params)).await?;
+						object_ids.extend(new_objects.iter().map(|o| o.id));
 
 						let (sync_ops, db_creates) = objects
 							.into_iter()
@@ -286,6 +291,8 @@ pub(crate) fn mount() -> AlphaRouter<Ctx> {
 						.await?;
 					}
 
+					write_back_desktop_tags(&library, object_ids).await?;
+
 					invalidate_query!(library, "tags.getForObject");
 					invalidate_query!(library, "tags.getWithObjects");
 					invalidate_query!(library, "search.objects");
@@ -324,6 +331,8 @@ pub(crate) fn mount() -> AlphaRouter<Ctx> {
 						.exec()
 						.await?;
 
+					let renamed = args.name.is_some();
+
 					let (sync_params, db_params): (Vec<_>, Vec<_>) = [
 						option_sync_db_entry!(args.name, tag::name),
 						option_sync_db_entry!(args.color, tag::color),
@@ -352,6 +361,11 @@ pub(crate) fn mount() -> AlphaRouter<Ctx> {
 					)
 					.await?;
 
+					if renamed {
+						write_back_desktop_tags(&library, tagged_object_ids(db, args.id).await?)
+							.await?;
+					}
+
 					invalidate_query!(library, "tags.list");
 
 					Ok(())
@@ -361,6 +375,8 @@ pub(crate) fn mount() -> AlphaRouter<Ctx> {
 			"delete",
 			R.with2(library())
 				.mutation(|(_, library), tag_id: i32| async move {
+					let object_ids = tagged_object_ids(&library.db, tag_id).await?;
+
 					library
 						.db
 						.tag_on_object()
@@ -375,9 +391,26 @@ pub(crate) fn mount() -> AlphaRouter<Ctx> {
 						.exec()
 						.await?;
 
+					write_back_desktop_tags(&library, object_ids).await?;
+
 					invalidate_query!(library, "tags.list");
 
 					Ok(())
 				}),
 		)
 }
+
+async fn tagged_object_ids(
+	db: &PrismaClient,
+	tag_id: tag::id::Type,
+) -> Result<Vec<object::id::Type>, prisma_client_rust::QueryError> {
+	Ok(db
+		.tag_on_object()
+		.find_many(vec![tag_on_object::tag_id::equals(tag_id)])
+		.select(tag_on_object::select!({ object_id }))
+		.exec()
+		.await?
+		.into_iter()
+		.map(|tag_on_object| tag_on_object.object_id)
+		.collect())
+}
@@ -0,0 +1,69 @@
+use crate::{
+	invalidate_query,
+	library::trash::{delete_from_trash, empty, restore, update_trash_config, TrashConfig},
+};
+
+use sd_core_prisma_helpers::trash_item_with_location;
+
+use sd_prisma::prisma::{location, trash_item, SortOrder};
+
+use rspc::alpha::AlphaRouter;
+
+use super::{utils::library, Ctx, R};
+
+pub(crate) fn mount() -> AlphaRouter<Ctx> {
+	R.router()
+		.procedure("list", {
+			R.with2(library()).query(
+				|(_, library), location_id: Option<location::id::Type>| async move {
+					Ok(library
+						.db
+						.trash_item()
+						.find_many(
+							location_id
+								.map(trash_item::location_id::equals)
+								.into_iter()
+								.collect(),
+						)
+						.include(trash_item_with_location::include())
+						.order_by(trash_item::date_trashed::order(SortOrder::Desc))
+						.exec()
+						.await?)
+				},
+			)
+		})
+		.procedure("restore", {
+			R.with2(library()).mutation(
+				|(node, library), ids: Vec<trash_item::id::Type>| async move {
+					restore(&node, &library, &ids).await.map_err(Into::into)
+				},
+			)
+		})
+		.procedure("delete", {
+			R.with2(library())
+				.mutation(|(_, library), ids: Vec<trash_item::id::Type>| async move {
+					delete_from_trash(&library, &ids).await.map_err(Into::into)
+				})
+		})
+		.procedure("empty", {
+			R.with2(library()).mutation(
+				|(_, library), location_id: Option<location::id::Type>| async move {
+					empty(&library, location_id).await.map_err(Into::into)
+				},
+			)
+		})
+		.procedure("getConfig", {
+			R.with2(library())
+				.query(|(_, library), _: ()| async move { Ok(library.config().await.trash) })
+		})
+		.procedure("setConfig", {
+			R.with2(library())
+				.mutation(|(node, library), config: TrashConfig| async move {
+					update_trash_config(&node, &library, config).await?;
+
+					invalidate_query!(library, "trash.getConfig");
+
+					Ok(())
+				})
+		})
+}
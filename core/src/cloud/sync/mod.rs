@@ -104,3 +104,262 @@ macro_rules! err_break {
 	};
 }
 pub(crate) use err_break;
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	use crate::library::Libraries;
+
+	use sd_cloud_api::{auth::OAuthToken, RequestConfig, RequestConfigProvider};
+	use sd_core_sync::NTP64;
+	use sd_p2p::Identity;
+	use sd_prisma::{
+		prisma::{tag, PrismaClient},
+		prisma_sync,
+	};
+	use sd_sync_server::{router, AppState, Store, User};
+	use sd_utils::uuid_to_bytes;
+
+	use std::{collections::HashMap, future::Future, net::TcpListener, path::Path, time::Duration};
+
+	use chrono::Utc;
+	use tokio::time::{sleep, timeout};
+
+	const TOKEN: &str = "token";
+
+	/// A sync server on a random port, with a single user whose token every instance uses
+	struct SyncServer {
+		url: String,
+	}
+
+	impl SyncServer {
+		async fn spawn(data_dir: &Path) -> Arc<Self> {
+			let store = Store::connect(&format!(
+				"sqlite://{}?mode=rwc",
+				data_dir.join("sync-server.db").display()
+			))
+			.await
+			.unwrap();
+
+			let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+			let url = format!("http://{}", listener.local_addr().unwrap());
+
+			let app = router(AppState::new(
+				store,
+				vec![User::new("user@example.com", TOKEN)],
+				&url,
+			));
+
+			tokio::spawn(
+				axum::Server::from_tcp(listener)
+					.unwrap()
+					.serve(app.into_make_service()),
+			);
+
+			Arc::new(Self { url })
+		}
+	}
+
+	impl RequestConfigProvider for SyncServer {
+		async fn get_request_config(self: &Arc<Self>) -> RequestConfig {
+			RequestConfig {
+				client: reqwest::Client::new(),
+				api_url: self.url.clone(),
+				auth_token: Some(OAuthToken {
+					access_token: TOKEN.to_string(),
+					refresh_token: String::new(),
+					token_type: "Bearer".to_string(),
+					expires_in: i32::MAX,
+				}),
+			}
+		}
+	}
+
+	struct Instance {
+		id: Uuid,
+		db: Arc<PrismaClient>,
+		sync: Arc<sd_core_sync::Manager>,
+	}
+
+	impl Instance {
+		async fn new(data_dir: &Path) -> Self {
+			let id = Uuid::new_v4();
+
+			let db = Arc::new(
+				PrismaClient::_builder()
+					.with_url(format!(
+						"file:{}",
+						data_dir.join(format!("{id}.db")).display()
+					))
+					.build()
+					.await
+					.unwrap(),
+			);
+			db._db_push().await.unwrap();
+
+			db.instance()
+				.create(
+					uuid_to_bytes(id),
+					vec![],
+					vec![],
+					Utc::now().into(),
+					Utc::now().into(),
+					vec![],
+				)
+				.exec()
+				.await
+				.unwrap();
+
+			let sync = sd_core_sync::Manager::new(
+				&db,
+				id,
+				&Arc::new(AtomicBool::new(true)),
+				HashMap::from([(id, NTP64(0))]),
+				&Default::default(),
+			)
+			.await;
+
+			Self {
+				id,
+				db,
+				sync: Arc::new(sync.manager),
+			}
+		}
+	}
+
+	async fn wait_for<F: Future<Output = bool>>(condition: impl Fn() -> F) {
+		timeout(Duration::from_secs(30), async {
+			while !condition().await {
+				sleep(Duration::from_millis(100)).await;
+			}
+		})
+		.await
+		.expect("timed out");
+	}
+
+	#[tokio::test]
+	async fn operations_are_synced_through_the_sync_server() {
+		let data_dir = tempfile::tempdir().unwrap();
+		let server = SyncServer::spawn(data_dir.path()).await;
+		let library_id = Uuid::new_v4();
+
+		let sender = Instance::new(data_dir.path()).await;
+		let receiver = Instance::new(data_dir.path()).await;
+
+		sd_cloud_api::library::create(
+			server.get_request_config().await,
+			library_id,
+			"Library",
+			sender.id,
+			Identity::new().to_remote_identity(),
+			Uuid::new_v4(),
+			Identity::new().to_remote_identity(),
+			&HashMap::new(),
+		)
+		.await
+		.unwrap();
+
+		sd_cloud_api::library::join(
+			server.get_request_config().await,
+			library_id,
+			receiver.id,
+			Identity::new().to_remote_identity(),
+			Uuid::new_v4(),
+			Identity::new().to_remote_identity(),
+			HashMap::new(),
+		)
+		.await
+		.unwrap();
+
+		let pub_id = Uuid::new_v4().as_bytes().to_vec();
+		let (sync_params, db_params): (Vec<_>, Vec<_>) =
+			[sync_db_entry!("Synced".to_string(), tag::name)]
+				.into_iter()
+				.unzip();
+
+		sender
+			.sync
+			.write_ops(
+				&sender.db,
+				(
+					sender.sync.shared_create(
+						prisma_sync::tag::SyncId {
+							pub_id: pub_id.clone(),
+						},
+						sync_params,
+					),
+					sender.db.tag().create(pub_id.clone(), db_params),
+				),
+			)
+			.await
+			.unwrap();
+
+		tokio::spawn(send::run_actor(
+			library_id,
+			sender.sync.clone(),
+			server.clone(),
+			Default::default(),
+			Default::default(),
+		));
+
+		// The receiver only polls every minute, so we only start it once the operations are up
+		wait_for(|| {
+			let (server, receiver_id) = (server.clone(), receiver.id);
+			async move {
+				!sd_cloud_api::library::message_collections::get(
+					server.get_request_config().await,
+					library_id,
+					receiver_id,
+					vec![],
+				)
+				.await
+				.unwrap()
+				.is_empty()
+			}
+		})
+		.await;
+
+		// The ingester must be waiting for notifications before the receiver sends any
+		let ingest_notify = Arc::new(Notify::new());
+		let ingest_active = Arc::new(AtomicBool::new(true));
+		tokio::spawn(ingest::run_actor(
+			receiver.sync.clone(),
+			ingest_notify.clone(),
+			ingest_active.clone(),
+			Default::default(),
+		));
+		wait_for(|| {
+			let ingest_active = ingest_active.clone();
+			async move { !ingest_active.load(atomic::Ordering::Relaxed) }
+		})
+		.await;
+
+		tokio::spawn(receive::run_actor(
+			Libraries::new(data_dir.path().join("libraries"))
+				.await
+				.unwrap(),
+			receiver.db.clone(),
+			library_id,
+			receiver.id,
+			receiver.sync.clone(),
+			ingest_notify,
+			server.clone(),
+			Default::default(),
+			Default::default(),
+		));
+
+		wait_for(|| {
+			let (db, pub_id) = (receiver.db.clone(), pub_id.clone());
+			async move {
+				db.tag()
+					.find_unique(tag::pub_id::equals(pub_id))
+					.exec()
+					.await
+					.unwrap()
+					.is_some_and(|tag| tag.name.as_deref() == Some("Synced"))
+			}
+		})
+		.await;
+	}
+}
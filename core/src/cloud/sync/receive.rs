@@ -1,4 +1,4 @@
-use crate::{library::Libraries, Node};
+use crate::library::Libraries;
 
 use sd_cloud_api::RequestConfigProvider;
 use sd_p2p::RemoteIdentity;
@@ -35,7 +35,7 @@ pub async fn run_actor(
 	instance_uuid: Uuid,
 	sync: Arc<sd_core_sync::Manager>,
 	ingest_notify: Arc<Notify>,
-	node: Arc<Node>,
+	cloud_api_config_provider: Arc<impl RequestConfigProvider>,
 	active: Arc<AtomicBool>,
 	active_notify: Arc<Notify>,
 ) {
@@ -110,7 +110,7 @@ pub async fn run_actor(
 
 			let collections = err_break!(
 				sd_cloud_api::library::message_collections::get(
-					node.get_request_config().await,
+					cloud_api_config_provider.get_request_config().await,
 					library_id,
 					instance_uuid,
 					instance_timestamps,
@@ -132,7 +132,7 @@ pub async fn run_actor(
 						None => {
 							let Some(fetched_library) = err_break!(
 								sd_cloud_api::library::get(
-									node.get_request_config().await,
+									cloud_api_config_provider.get_request_config().await,
 									library_id
 								)
 								.await
@@ -170,7 +170,7 @@ pub async fn run_actor(
 							instance.node_id,
 							RemoteIdentity::from_str(&instance.node_remote_identity)
 								.expect("malformed remote identity in the DB"),
-							node.p2p.peer_metadata(),
+							instance.metadata.clone(),
 						)
 						.await
 					);
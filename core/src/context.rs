@@ -0,0 +1,95 @@
+use crate::{
+	api::{utils::InvalidateOperationEvent, CoreEvent},
+	library::Library,
+	sync, Node,
+};
+
+use sd_core_heavy_lifting::{OuterContext, ProgressUpdate, UpdateEvent};
+use sd_prisma::prisma::PrismaClient;
+
+use std::{path::Path, sync::Arc};
+
+use serde_json::Value;
+use tracing::trace;
+use uuid::Uuid;
+
+/// What the jobs of the [`sd_core_heavy_lifting`] job system get to run in, a library of this node
+#[derive(Clone)]
+pub struct NodeContext {
+	pub node: Arc<Node>,
+	pub library: Arc<Library>,
+}
+
+impl NodeContext {
+	pub fn new(node: &Arc<Node>, library: &Arc<Library>) -> Self {
+		Self {
+			node: Arc::clone(node),
+			library: Arc::clone(library),
+		}
+	}
+}
+
+impl OuterContext for NodeContext {
+	fn id(&self) -> Uuid {
+		self.library.id
+	}
+
+	fn db(&self) -> &Arc<PrismaClient> {
+		&self.library.db
+	}
+
+	fn sync(&self) -> &Arc<sync::Manager> {
+		&self.library.sync
+	}
+
+	fn invalidate_query(&self, query: &'static str) {
+		invalidate(&self.library, query);
+	}
+
+	fn query_invalidator(&self) -> impl Fn(&'static str) + Send + Sync {
+		let library = Arc::clone(&self.library);
+		move |query| invalidate(&library, query)
+	}
+
+	fn progress(&self, updates: Vec<ProgressUpdate>) {
+		for update in updates {
+			match update {
+				ProgressUpdate::TaskCount(count) => trace!("Job task count: {count}"),
+				ProgressUpdate::CompletedTaskCount(count) => {
+					trace!("Job completed task count: {count}");
+				}
+				ProgressUpdate::Message(message) => trace!("Job progress: {message}"),
+				ProgressUpdate::Phase(phase) => trace!("Job phase: {phase}"),
+			}
+		}
+
+		// Reports are kept up to date on the database by the job system
+		invalidate(&self.library, "jobs.reports");
+	}
+
+	fn report_update(&self, update: UpdateEvent) {
+		self.node.emit(match update {
+			UpdateEvent::NewThumbnailEvent { thumb_key } => CoreEvent::NewThumbnail {
+				thumb_key: vec![
+					thumb_key.base_directory_str,
+					thumb_key.shard_hex,
+					thumb_key.cas_id,
+				],
+			},
+			UpdateEvent::NewIdentifiedObjects { file_path_ids } => {
+				CoreEvent::NewIdentifiedObjects { file_path_ids }
+			}
+		});
+	}
+
+	fn get_data_directory(&self) -> &Path {
+		&self.node.data_dir
+	}
+}
+
+fn invalidate(library: &Library, query: &'static str) {
+	// The keys come from the job system, so they can't be checked with `invalidate_query!`
+	library.emit(CoreEvent::InvalidateOperation(
+		InvalidateOperationEvent::dangerously_create(query, Value::Null, None),
+	));
+}
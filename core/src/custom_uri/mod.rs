@@ -1,7 +1,10 @@
 use crate::{
 	api::{utils::InvalidateOperationEvent, CoreEvent},
 	library::Library,
-	object::media::old_thumbnail::WEBP_EXTENSION,
+	object::media::old_thumbnail::{
+		tier::{generate_tier, ThumbnailTier},
+		THUMBSTRIP_INDEX_EXTENSION, WEBP_EXTENSION,
+	},
 	p2p::operations::{self, request_file},
 	util::InfallibleResponse,
 	Node,
@@ -42,9 +45,11 @@ use axum::{
 use http_body::combinators::UnsyncBoxBody;
 use hyper::{header, upgrade::OnUpgrade};
 use mini_moka::sync::Cache;
+use serde::Deserialize;
 use tokio::{
 	fs::{self, File},
 	io::{self, copy_bidirectional, AsyncReadExt, AsyncSeekExt, SeekFrom},
+	sync::Semaphore,
 };
 use tracing::{error, warn};
 use uuid::Uuid;
@@ -54,6 +59,9 @@ use self::{serve_file::serve_file, utils::*};
 mod async_read_body;
 mod mpsc_to_async_write;
 mod serve_file;
+mod tracks;
+#[cfg(feature = "ffmpeg")]
+mod transcode;
 mod utils;
 
 type CacheKey = (Uuid, file_path::id::Type);
@@ -67,6 +75,7 @@ struct CacheValue {
 }
 
 const MAX_TEXT_READ_LENGTH: usize = 10 * 1024; // 10KB
+const MAX_CONCURRENT_THUMBNAIL_TIERS: usize = 4;
 
 #[derive(Debug, Clone)]
 pub enum ServeFrom {
@@ -88,6 +97,15 @@ pub struct LocalState {
 	// The main advantage of this LRU Cache is for video files. Video files are fetch in multiple chunks and the cache prevents a DB lookup on every chunk reducing the request time from 15-25ms to 1-10ms.
 	// TODO: We should listen to events when deleting or moving a location and evict the cache accordingly.
 	file_metadata_cache: Arc<Cache<CacheKey, CacheValue>>,
+
+	// Limits how many thumbnail tiers are generated at the same time, as scrolling through a big
+	// grid can request a lot of them at once.
+	thumbnail_tiers_semaphore: Arc<Semaphore>,
+
+	// Transcoding is way heavier than generating thumbnails, and a single player only needs one
+	// segment at a time anyway
+	#[cfg(feature = "ffmpeg")]
+	transcode_semaphore: Arc<Semaphore>,
 }
 
 type ExtractedPath = extract::Path<(String, String, String)>;
@@ -219,23 +237,90 @@ async fn get_or_init_lru_entry(
 	}
 }
 
+#[derive(Deserialize)]
+struct ThumbnailQuery {
+	/// Size in pixels the thumbnail is going to be displayed at
+	size: Option<u32>,
+}
+
+/// Returns the path of the requested tier, generating it if needed, or the base thumbnail if
+/// the tier can't be generated
+async fn serve_thumbnail_tier(
+	state: &LocalState,
+	base_thumbnail_path: PathBuf,
+	tier: ThumbnailTier,
+) -> PathBuf {
+	let tier_path = tier.path(&base_thumbnail_path);
+	if fs::metadata(&tier_path).await.is_ok() {
+		return tier_path;
+	}
+
+	let Ok(_permit) = state.thumbnail_tiers_semaphore.acquire().await else {
+		return base_thumbnail_path;
+	};
+
+	// Another request may have generated it while we were waiting for a permit
+	if fs::metadata(&tier_path).await.is_ok() {
+		return tier_path;
+	}
+
+	generate_tier(&state.node, &base_thumbnail_path, tier)
+		.await
+		.unwrap_or_else(|e| {
+			warn!(
+				"Failed to generate thumbnail tier {tier:?} for '{}': {e:#?}",
+				base_thumbnail_path.display()
+			);
+			base_thumbnail_path
+		})
+}
+
 pub fn base_router() -> Router<LocalState> {
-	Router::new()
+	let router = Router::new()
 		.route(
 			"/thumbnail/*path",
 			get(
 				|State(state): State<LocalState>,
 				 extract::Path(path): extract::Path<String>,
+				 extract::Query(ThumbnailQuery { size }): extract::Query<ThumbnailQuery>,
 				 request: Request<Body>| async move {
 					let thumbnail_path = state.node.config.data_directory().join("thumbnails");
-					let path = thumbnail_path.join(path);
+					let mut path = thumbnail_path.join(path);
 
 					// Prevent directory traversal attacks (Eg. requesting `../../../etc/passwd`)
-					// For now we only support `webp` thumbnails.
-					(path.starts_with(&thumbnail_path)
-						&& path.extension() == Some(WEBP_EXTENSION.as_ref()))
-					.then_some(())
-					.ok_or_else(|| not_found(()))?;
+					// For now we only support `webp` thumbnails, and the `json` timing index of
+					// video thumbstrips.
+					let content_type = path
+						.starts_with(&thumbnail_path)
+						.then(|| path.extension())
+						.flatten()
+						.and_then(|extension| {
+							if extension == WEBP_EXTENSION {
+								Some("image/webp")
+							} else if extension == THUMBSTRIP_INDEX_EXTENSION {
+								Some("application/json")
+							} else {
+								None
+							}
+						})
+						.ok_or_else(|| not_found(()))?;
+
+					// Size tiers other than the base one are only available for regular
+					// thumbnails, as in `<cas_id>.webp`, and are generated when first requested
+					if let Some(tier) = size
+						.map(ThumbnailTier::for_size)
+						.filter(|tier| *tier != ThumbnailTier::BASE)
+					{
+						let is_base_thumbnail = path
+							.file_stem()
+							.and_then(OsStr::to_str)
+							.is_some_and(|stem| !stem.contains('.'))
+							&& content_type == "image/webp";
+
+						if is_base_thumbnail {
+							path = serve_thumbnail_tier(&state, path, tier).await;
+						}
+					}
 
 					let file = File::open(&path).await.map_err(|err| {
 						InfallibleResponse::builder()
@@ -252,7 +337,7 @@ pub fn base_router() -> Router<LocalState> {
 						metadata,
 						request.into_parts().0,
 						InfallibleResponse::builder()
-							.header("Content-Type", HeaderValue::from_static("image/webp")),
+							.header("Content-Type", HeaderValue::from_static(content_type)),
 					)
 					.await
 				},
@@ -377,7 +462,185 @@ pub fn base_router() -> Router<LocalState> {
 					serve_file(file, Ok(metadata), request.into_parts().0, resp).await
 				},
 			),
+		);
+
+	// Media the webview can't play is transcoded on demand into HLS segments
+	#[cfg(feature = "ffmpeg")]
+	let router = router
+		.route(
+			"/transcode/:lib_id/:loc_id/:path_id",
+			get(
+				|State(state): State<LocalState>,
+				 extract::Path((lib_id, loc_id, path_id)): ExtractedPath,
+				 request: Request<Body>| async move {
+					let (
+						CacheValue {
+							name, serve_from, ..
+						},
+						..,
+					) = get_or_init_lru_entry(
+						&state,
+						extract::Path((lib_id, loc_id, path_id.clone())),
+					)
+					.await?;
+
+					match serve_from {
+						ServeFrom::Local => {
+							// Segments are relative to the playlist, which doesn't end with a slash
+							let playlist = sd_ffmpeg::to_hls_playlist(
+								&name,
+								transcode::SEGMENT_DURATION,
+								&format!("{path_id}/"),
+							)
+							.await
+							.map_err(internal_server_error)?;
+
+							Ok(InfallibleResponse::builder()
+								.header(
+									"Content-Type",
+									HeaderValue::from_static("application/vnd.apple.mpegurl"),
+								)
+								.body(body::boxed(Full::from(playlist))))
+						}
+						// The node that has the file transcodes it, we just relay its response
+						ServeFrom::Remote { node_identity, .. } => Ok(request_to_remote_node(
+							state.node.p2p.p2p.clone(),
+							node_identity,
+							request,
+						)
+						.await),
+					}
+				},
+			),
 		)
+		.route(
+			"/transcode/:lib_id/:loc_id/:path_id/:segment",
+			get(
+				|State(state): State<LocalState>,
+				 extract::Path((lib_id, loc_id, path_id, segment)): extract::Path<(
+					String,
+					String,
+					String,
+					String,
+				)>,
+				 request: Request<Body>| async move {
+					let index = segment
+						.strip_suffix(sd_ffmpeg::HLS_SEGMENT_EXTENSION)
+						.and_then(|index| index.strip_suffix('.'))
+						.and_then(|index| index.parse::<u32>().ok())
+						.ok_or_else(|| not_found(()))?;
+
+					let (
+						CacheValue {
+							name,
+							file_path_pub_id,
+							serve_from,
+							..
+						},
+						..,
+					) = get_or_init_lru_entry(&state, extract::Path((lib_id, loc_id, path_id)))
+						.await?;
+
+					match serve_from {
+						ServeFrom::Local => {
+							let metadata =
+								fs::metadata(&name).await.map_err(internal_server_error)?;
+							(!metadata.is_dir())
+								.then_some(())
+								.ok_or_else(|| not_found(()))?;
+
+							let segment_path = transcode::get_segment(
+								&state.node,
+								&state.transcode_semaphore,
+								&name,
+								&transcode::segments_directory(
+									&state.node,
+									file_path_pub_id,
+									&metadata,
+								),
+								index,
+							)
+							.await?;
+
+							let file = File::open(&segment_path)
+								.await
+								.map_err(internal_server_error)?;
+							let metadata = file.metadata().await;
+							serve_file(
+								file,
+								metadata,
+								request.into_parts().0,
+								InfallibleResponse::builder()
+									.header("Content-Type", HeaderValue::from_static("video/mp2t")),
+							)
+							.await
+						}
+						ServeFrom::Remote { node_identity, .. } => Ok(request_to_remote_node(
+							state.node.p2p.p2p.clone(),
+							node_identity,
+							request,
+						)
+						.await),
+					}
+				},
+			),
+		);
+
+	// Chapters and subtitles of a video as WebVTT text tracks
+	router.route(
+		"/tracks/:lib_id/:loc_id/:path_id/:track",
+		get(
+			|State(state): State<LocalState>,
+			 extract::Path((lib_id, loc_id, path_id, track)): extract::Path<(
+				String,
+				String,
+				String,
+				String,
+			)>,
+			 request: Request<Body>| async move {
+				let file_path_id = path_id
+					.parse::<file_path::id::Type>()
+					.map_err(bad_request)?;
+
+				let (
+					CacheValue {
+						name, serve_from, ..
+					},
+					library,
+				) = get_or_init_lru_entry(&state, extract::Path((lib_id, loc_id, path_id))).await?;
+
+				match serve_from {
+					ServeFrom::Local => {
+						let vtt = if track == tracks::CHAPTERS_TRACK {
+							tracks::chapters(&library, file_path_id).await?
+						} else {
+							let subtitle_id = track
+								.strip_suffix(tracks::TRACK_EXTENSION)
+								.and_then(|id| id.strip_suffix('.'))
+								.and_then(|id| id.parse().ok())
+								.ok_or_else(|| not_found(()))?;
+
+							tracks::subtitle(&library, file_path_id, subtitle_id, &name).await?
+						};
+
+						Ok(InfallibleResponse::builder()
+							.header(
+								"Content-Type",
+								HeaderValue::from_static("text/vtt; charset=utf-8"),
+							)
+							.body(body::boxed(Full::from(vtt))))
+					}
+					// Sidecar files are read by the node that has them
+					ServeFrom::Remote { node_identity, .. } => Ok(request_to_remote_node(
+						state.node.p2p.p2p.clone(),
+						node_identity,
+						request,
+					)
+					.await),
+				}
+			},
+		),
+	)
 }
 
 pub fn with_state(node: Arc<Node>) -> LocalState {
@@ -409,6 +672,9 @@ pub fn with_state(node: Arc<Node>) -> LocalState {
 	LocalState {
 		node,
 		file_metadata_cache,
+		thumbnail_tiers_semaphore: Arc::new(Semaphore::new(MAX_CONCURRENT_THUMBNAIL_TIERS)),
+		#[cfg(feature = "ffmpeg")]
+		transcode_semaphore: Arc::new(Semaphore::new(transcode::MAX_CONCURRENT_TRANSCODES)),
 	}
 }
 
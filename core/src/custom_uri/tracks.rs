@@ -0,0 +1,133 @@
+//! WebVTT text tracks of a video, its chapters and its subtitles, served by the `/tracks` route.
+
+use crate::{library::Library, object::media::ffmpeg_chapter_from_prisma_data};
+
+use sd_media_metadata::ffmpeg::subtitle::{
+	chapters_to_webvtt, cues_to_webvtt, SubtitleCue, SubtitleTrack,
+};
+use sd_prisma::prisma::{
+	ffmpeg_data, ffmpeg_media_chapter, file_path, media_subtitle, object, SortOrder,
+};
+
+use std::path::Path;
+
+use axum::{body::BoxBody, http::Response};
+use tracing::warn;
+
+use super::utils::{internal_server_error, not_found};
+
+pub const CHAPTERS_TRACK: &str = "chapters.vtt";
+pub const TRACK_EXTENSION: &str = "vtt";
+
+pub async fn chapters(
+	library: &Library,
+	file_path_id: file_path::id::Type,
+) -> Result<String, Response<BoxBody>> {
+	let chapters = library
+		.db
+		.ffmpeg_media_chapter()
+		.find_many(vec![ffmpeg_media_chapter::ffmpeg_data::is(vec![
+			ffmpeg_data::object::is(vec![object::file_paths::some(vec![file_path::id::equals(
+				file_path_id,
+			)])]),
+		])])
+		.order_by(ffmpeg_media_chapter::chapter_id::order(SortOrder::Asc))
+		.exec()
+		.await
+		.map_err(internal_server_error)?;
+
+	if chapters.is_empty() {
+		return Err(not_found(()));
+	}
+
+	Ok(chapters_to_webvtt(
+		&chapters
+			.into_iter()
+			.map(ffmpeg_chapter_from_prisma_data)
+			.collect::<Vec<_>>(),
+	))
+}
+
+pub async fn subtitle(
+	library: &Library,
+	file_path_id: file_path::id::Type,
+	subtitle_id: media_subtitle::id::Type,
+	video_path: &Path,
+) -> Result<String, Response<BoxBody>> {
+	let subtitle = library
+		.db
+		.media_subtitle()
+		.find_first(vec![
+			media_subtitle::id::equals(subtitle_id),
+			media_subtitle::object::is(vec![object::file_paths::some(vec![
+				file_path::id::equals(file_path_id),
+			])]),
+		])
+		.exec()
+		.await
+		.map_err(internal_server_error)?
+		.ok_or_else(|| not_found(()))?;
+
+	let cues = serde_json::from_slice::<Vec<SubtitleCue>>(&subtitle.cues)
+		.map_err(internal_server_error)?;
+
+	let cues = match (subtitle.sidecar, video_path.parent()) {
+		(Some(sidecar), Some(parent)) => {
+			refresh_sidecar(library, subtitle.id, &parent.join(sidecar), cues).await
+		}
+		_ => cues,
+	};
+
+	Ok(cues_to_webvtt(&cues))
+}
+
+/// Sidecar files are edited all the time, so we read them again instead of serving the cues we
+/// extracted when the video was indexed, updating them to keep the search results in sync
+async fn refresh_sidecar(
+	library: &Library,
+	subtitle_id: media_subtitle::id::Type,
+	sidecar_path: &Path,
+	stored_cues: Vec<SubtitleCue>,
+) -> Vec<SubtitleCue> {
+	let track = match SubtitleTrack::from_path(sidecar_path).await {
+		Ok(tracks) => tracks.into_iter().next(),
+		Err(e) => {
+			warn!(
+				"Failed to read subtitle sidecar file '{}', serving the indexed one: {e:#?}",
+				sidecar_path.display()
+			);
+			return stored_cues;
+		}
+	};
+
+	let Some(track) = track else {
+		return stored_cues;
+	};
+
+	if track.cues == stored_cues {
+		return stored_cues;
+	}
+
+	match serde_json::to_vec(&track.cues) {
+		Ok(cues) => {
+			if let Err(e) = library
+				.db
+				.media_subtitle()
+				.update(
+					media_subtitle::id::equals(subtitle_id),
+					vec![
+						media_subtitle::cues::set(cues),
+						media_subtitle::text::set(track.text()),
+					],
+				)
+				.exec()
+				.await
+			{
+				warn!("Failed to update the cues of subtitle {subtitle_id}: {e:#?}");
+			}
+		}
+		Err(e) => warn!("Failed to serialize the cues of subtitle {subtitle_id}: {e:#?}"),
+	}
+
+	track.cues
+}
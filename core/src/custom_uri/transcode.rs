@@ -0,0 +1,173 @@
+//! HLS segments of media the webview can't play, transcoded on demand by the `/transcode` route.
+//!
+//! Segments are cached on disk, so seeking back and forth through a video doesn't transcode the
+//! same segments over and over, and the least recently used ones are removed once the cache
+//! grows past [`MAX_CACHE_SIZE`].
+
+use crate::Node;
+
+use sd_ffmpeg::HLS_SEGMENT_EXTENSION;
+
+use std::{
+	fs::{self as std_fs, Metadata},
+	io,
+	path::{Path, PathBuf},
+	time::{SystemTime, UNIX_EPOCH},
+};
+
+use axum::{body::BoxBody, http::Response};
+use tokio::{fs, sync::Semaphore, task::spawn_blocking};
+use tracing::{error, warn};
+use uuid::Uuid;
+
+use super::utils::internal_server_error;
+
+pub const SEGMENT_DURATION: f64 = 6.0;
+/// Anything taller is scaled down, it's a preview after all and it keeps transcoding real time
+pub const MAX_HEIGHT: u32 = 1080;
+pub const MAX_CONCURRENT_TRANSCODES: usize = 2;
+pub const MAX_CACHE_SIZE: u64 = 2 * 1024 * 1024 * 1024; // 2GB
+const CACHE_DIRECTORY: &str = "transcode_cache";
+
+/// Directory holding the segments of a file, which changes whenever the file is modified so we
+/// never serve segments of an older version of it
+pub fn segments_directory(node: &Node, file_path_pub_id: Uuid, metadata: &Metadata) -> PathBuf {
+	let modified_at = metadata
+		.modified()
+		.ok()
+		.and_then(|modified_at| modified_at.duration_since(UNIX_EPOCH).ok())
+		.map_or(0, |modified_at| modified_at.as_secs());
+
+	node.config
+		.data_directory()
+		.join(CACHE_DIRECTORY)
+		.join(format!(
+			"{file_path_pub_id}-{modified_at}-{}",
+			metadata.len()
+		))
+}
+
+/// Returns the path of a segment, transcoding it first if it isn't cached yet
+pub async fn get_segment(
+	node: &Node,
+	semaphore: &Semaphore,
+	source_path: &Path,
+	segments_directory: &Path,
+	index: u32,
+) -> Result<PathBuf, Response<BoxBody>> {
+	let segment_path = segments_directory.join(format!("{index}.{HLS_SEGMENT_EXTENSION}"));
+	if fs::metadata(&segment_path).await.is_ok() {
+		mark_as_used(segment_path.clone());
+		return Ok(segment_path);
+	}
+
+	let _permit = semaphore.acquire().await.map_err(internal_server_error)?;
+
+	// Another request may have transcoded it while we were waiting for a permit
+	if fs::metadata(&segment_path).await.is_ok() {
+		return Ok(segment_path);
+	}
+
+	fs::create_dir_all(segments_directory)
+		.await
+		.map_err(internal_server_error)?;
+
+	// We transcode to a temporary file first, so a partially written segment is never served,
+	// named uniquely so concurrent requests for the same segment never write over each other
+	let partial_path = segment_path.with_extension(format!(
+		"{}.{HLS_SEGMENT_EXTENSION}.part",
+		Uuid::new_v4().simple()
+	));
+	if let Err(e) = sd_ffmpeg::to_hls_segment(
+		source_path,
+		&partial_path,
+		index,
+		SEGMENT_DURATION,
+		MAX_HEIGHT,
+	)
+	.await
+	{
+		fs::remove_file(&partial_path).await.ok();
+		error!(
+			"Failed to transcode segment {index} of '{}': {e:#?}",
+			source_path.display()
+		);
+		return Err(internal_server_error(()));
+	}
+
+	fs::rename(&partial_path, &segment_path)
+		.await
+		.map_err(internal_server_error)?;
+
+	tokio::spawn(evict_least_recently_used(
+		node.config.data_directory().join(CACHE_DIRECTORY),
+	));
+
+	Ok(segment_path)
+}
+
+/// Bumps the modification time of a segment, which is what we sort by when evicting them
+fn mark_as_used(segment_path: PathBuf) {
+	spawn_blocking(move || {
+		if let Err(e) = std_fs::File::options()
+			.append(true)
+			.open(&segment_path)
+			.and_then(|file| file.set_modified(SystemTime::now()))
+		{
+			warn!(
+				"Failed to update modification time of transcoded segment '{}': {e:#?}",
+				segment_path.display()
+			);
+		}
+	});
+}
+
+async fn evict_least_recently_used(cache_directory: PathBuf) {
+	let res = spawn_blocking(move || -> io::Result<()> {
+		let mut segments = vec![];
+		let mut cache_size = 0;
+
+		for segments_directory in std_fs::read_dir(&cache_directory)? {
+			for segment in std_fs::read_dir(segments_directory?.path())? {
+				let segment = segment?;
+				// Segments still being transcoded are left alone
+				if segment.path().extension() != Some(HLS_SEGMENT_EXTENSION.as_ref()) {
+					continue;
+				}
+
+				let metadata = segment.metadata()?;
+				cache_size += metadata.len();
+				segments.push((metadata.modified()?, metadata.len(), segment.path()));
+			}
+		}
+
+		if cache_size <= MAX_CACHE_SIZE {
+			return Ok(());
+		}
+
+		segments.sort_unstable_by_key(|(modified_at, ..)| *modified_at);
+
+		for (_, size, segment_path) in segments {
+			if cache_size <= MAX_CACHE_SIZE {
+				break;
+			}
+
+			std_fs::remove_file(&segment_path)?;
+			cache_size -= size;
+
+			// Only succeeds once the directory is empty
+			if let Some(segments_directory) = segment_path.parent() {
+				std_fs::remove_dir(segments_directory).ok();
+			}
+		}
+
+		Ok(())
+	})
+	.await;
+
+	match res {
+		Ok(Ok(())) => {}
+		Ok(Err(e)) => warn!("Failed to evict transcoded segments: {e:#?}"),
+		Err(e) => error!("Transcoded segments eviction task panicked: {e:#?}"),
+	}
+}
@@ -3,12 +3,15 @@
 
 use crate::{
 	api::{CoreEvent, Router},
+	context::NodeContext,
 	location::LocationManagerError,
 	object::media::old_thumbnail::old_actor::OldThumbnailer,
 };
 
 #[cfg(feature = "ai")]
 use sd_ai::old_image_labeler::{DownloadModelError, OldImageLabeler, YoloV8};
+use sd_core_heavy_lifting::JobSystem;
+use sd_task_system::TaskSystem;
 use sd_utils::error::FileIOError;
 
 use api::notifications::{Notification, NotificationData, NotificationId};
@@ -34,6 +37,7 @@ use tracing_subscriber::{filter::FromEnvError, prelude::*, EnvFilter};
 
 pub mod api;
 mod cloud;
+pub(crate) mod context;
 pub mod custom_uri;
 mod env;
 pub mod library;
@@ -61,6 +65,8 @@ pub struct Node {
 	pub config: Arc<config::Manager>,
 	pub libraries: Arc<library::Libraries>,
 	pub old_jobs: Arc<old_job::OldJobs>,
+	pub task_system: TaskSystem<sd_core_heavy_lifting::Error>,
+	pub job_system: JobSystem<NodeContext>,
 	pub locations: location::Locations,
 	pub p2p: Arc<p2p::P2PManager>,
 	pub event_bus: (broadcast::Sender<CoreEvent>, broadcast::Receiver<CoreEvent>),
@@ -115,6 +121,8 @@ impl Node {
 
 		let (locations, locations_actor) = location::Locations::new();
 		let (old_jobs, jobs_actor) = old_job::OldJobs::new();
+		let task_system = TaskSystem::new();
+		let job_system = JobSystem::new(task_system.get_dispatcher(), data_dir);
 		let libraries = library::Libraries::new(data_dir.join("libraries")).await?;
 
 		let (p2p, start_p2p) = p2p::P2PManager::new(config.clone(), libraries.clone())
@@ -123,6 +131,8 @@ impl Node {
 		let node = Arc::new(Node {
 			data_dir: data_dir.to_path_buf(),
 			old_jobs,
+			task_system,
+			job_system,
 			locations,
 			notifications: notifications::Notifications::new(),
 			p2p,
@@ -168,6 +178,21 @@ impl Node {
 		locations_actor.start(node.clone());
 		node.libraries.init(&node).await?;
 		jobs_actor.start(node.clone());
+		if let Err(e) = node
+			.job_system
+			.init(
+				&node
+					.libraries
+					.get_all()
+					.await
+					.into_iter()
+					.map(|library| (library.id, NodeContext::new(&node, &library)))
+					.collect(),
+			)
+			.await
+		{
+			error!("Failed to resume the jobs left pending on the last shutdown: {e:#?}");
+		}
 		start_p2p(
 			node.clone(),
 			axum::Router::new()
@@ -261,6 +286,8 @@ impl Node {
 		info!("Spacedrive shutting down...");
 		self.thumbnailer.shutdown().await;
 		self.old_jobs.shutdown().await;
+		self.job_system.shutdown().await;
+		self.task_system.shutdown().await;
 		self.p2p.shutdown().await;
 		#[cfg(feature = "ai")]
 		if let Some(image_labeller) = &self.old_image_labeller {
@@ -0,0 +1,322 @@
+//! Encryption of backup archives, on top of [`sd_crypto`].
+//!
+//! Every library with encrypted backups has a random backup key, which is never stored as is:
+//! only a [`Keyslot`] is, which holds the salts to hash the backup passphrase with and the
+//! backup key encrypted with a key derived from that hash. Each encrypted archive starts with
+//! the keyslot of the key it was encrypted with, so it can be restored anywhere with just the
+//! passphrase.
+//!
+//! Archives are encrypted with XChaCha20-Poly1305 in blocks, following the STREAM construction,
+//! so they're never fully loaded in memory and can't be truncated or reordered unnoticed.
+
+use sd_crypto::{
+	crypto::{Decryptor, Encryptor},
+	encoding::{decode, encode, file::keyslot},
+	hashing::Hasher,
+	primitives::{AAD_LEN, XCHACHA20_POLY1305_NONCE_LEN},
+	types::{
+		Aad, Algorithm, DerivationContext, HashingAlgorithm, Key, Nonce, Params, Salt, SecretKey,
+	},
+	Protected,
+};
+use sd_utils::error::FileIOError;
+
+use std::{
+	io::{self, Read, Write},
+	path::Path,
+};
+
+use super::BackupError;
+
+const MAGIC_BYTES: &[u8; 6] = b"sdbkpe";
+
+const ALGORITHM: Algorithm = Algorithm::XChaCha20Poly1305;
+const HASHING_ALGORITHM: HashingAlgorithm = HashingAlgorithm::Argon2id(Params::Standard);
+
+const KEYSLOT_CONTEXT: DerivationContext =
+	DerivationContext::new("spacedrive 2024-06-24 14:02:51 backup keyslot context");
+
+/// Encoded keyslots are around 150 bytes, anything much bigger isn't a keyslot
+const MAX_KEYSLOT_LEN: usize = 1024;
+
+/// The backup key wrapped by a key derived from the backup passphrase, encoded by [`sd_crypto`]
+#[derive(Clone, PartialEq, Eq)]
+pub struct Keyslot(Vec<u8>);
+
+/// A backup key along with the keyslot it can be unlocked from.
+///
+/// It's only kept in memory, so encrypted backups need the passphrase once each time the node
+/// starts before they can be made.
+#[derive(Clone)]
+pub struct BackupKey {
+	key: Key,
+	keyslot: Keyslot,
+}
+
+/// How we get the backup key of an encrypted archive
+pub enum DecryptionKey {
+	Passphrase(String),
+	Unlocked(BackupKey),
+}
+
+impl Keyslot {
+	pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+		(bytes.len() <= MAX_KEYSLOT_LEN && decode::<keyslot::Keyslot>(bytes).is_ok())
+			.then(|| Self(bytes.to_vec()))
+	}
+
+	pub fn as_bytes(&self) -> &[u8] {
+		&self.0
+	}
+
+	/// Decrypts the backup key, failing with [`BackupError::Decrypt`] on a wrong passphrase
+	pub fn unlock(self, passphrase: &str) -> Result<BackupKey, BackupError> {
+		let keyslot = decode::<keyslot::Keyslot>(&self.0)?;
+
+		let hashed_passphrase = Hasher::hash_password(
+			keyslot.hashing_algorithm,
+			&Protected::new(passphrase.as_bytes().to_vec()),
+			keyslot.hash_salt,
+			&SecretKey::Null,
+		)?;
+
+		let key = keyslot
+			.decrypt(ALGORITHM, &hashed_passphrase, Aad::Null, KEYSLOT_CONTEXT)
+			.map_err(|e| match e {
+				sd_crypto::Error::Decrypt => BackupError::Decrypt,
+				e => e.into(),
+			})?;
+
+		Ok(BackupKey { key, keyslot: self })
+	}
+}
+
+impl BackupKey {
+	/// Generates a new backup key, protected by `passphrase`
+	pub fn generate(passphrase: &str) -> Result<Self, BackupError> {
+		let key = Key::generate();
+		let hash_salt = Salt::generate();
+
+		let hashed_passphrase = Hasher::hash_password(
+			HASHING_ALGORITHM,
+			&Protected::new(passphrase.as_bytes().to_vec()),
+			hash_salt,
+			&SecretKey::Null,
+		)?;
+
+		let keyslot = keyslot::Keyslot::new(
+			ALGORITHM,
+			HASHING_ALGORITHM,
+			hash_salt,
+			&hashed_passphrase,
+			&key,
+			Aad::Null,
+			KEYSLOT_CONTEXT,
+		)?;
+
+		Ok(Self {
+			key,
+			keyslot: Keyslot(encode(&keyslot)?),
+		})
+	}
+
+	pub fn keyslot(&self) -> &Keyslot {
+		&self.keyslot
+	}
+}
+
+/// Every block of an archive is authenticated along with its whole header
+fn header_aad(header: &[u8]) -> Aad {
+	let mut aad = [0; AAD_LEN];
+	aad.copy_from_slice(Hasher::blake3(header).expose());
+
+	Aad::Standard(aad)
+}
+
+/// Encrypts a whole archive with `key`, `path` only gives context to IO errors
+pub fn encrypt_archive(
+	reader: impl Read,
+	mut writer: impl Write,
+	key: &BackupKey,
+	path: &Path,
+) -> Result<(), BackupError> {
+	let nonce = Nonce::generate(ALGORITHM);
+	let keyslot = key.keyslot.as_bytes();
+
+	let mut header = Vec::with_capacity(MAGIC_BYTES.len() + 4 + keyslot.len() + nonce.len());
+	header.extend_from_slice(MAGIC_BYTES);
+	header.extend_from_slice(&(keyslot.len() as u32).to_le_bytes());
+	header.extend_from_slice(keyslot);
+	header.extend_from_slice(nonce.inner());
+
+	writer
+		.write_all(&header)
+		.map_err(|e| FileIOError::from((path, e, "Failed to encrypt backup")))?;
+
+	Encryptor::new(&key.key, &nonce, ALGORITHM)?
+		.encrypt_streams(reader, writer, header_aad(&header))
+		.map_err(Into::into)
+}
+
+/// Reads the keyslot at the start of an encrypted archive, without decrypting anything
+pub fn archive_keyslot(mut reader: impl Read) -> io::Result<Option<Keyslot>> {
+	let mut magic = [0; MAGIC_BYTES.len()];
+	reader.read_exact(&mut magic)?;
+
+	if &magic != MAGIC_BYTES {
+		return Ok(None);
+	}
+
+	let mut len = [0; 4];
+	reader.read_exact(&mut len)?;
+	let len = u32::from_le_bytes(len) as usize;
+
+	if len > MAX_KEYSLOT_LEN {
+		return Ok(None);
+	}
+
+	let mut keyslot = vec![0; len];
+	reader.read_exact(&mut keyslot)?;
+
+	Ok(Keyslot::from_bytes(&keyslot))
+}
+
+/// Decrypts an archive encrypted by [`encrypt_archive`], `path` only gives context to IO errors
+pub fn decrypt_archive(
+	mut reader: impl Read,
+	writer: impl Write,
+	key: &DecryptionKey,
+	path: &Path,
+) -> Result<(), BackupError> {
+	let map_io_err = |e: io::Error| FileIOError::from((path, e, "Failed to decrypt backup"));
+
+	let mut header = vec![0; MAGIC_BYTES.len() + 4];
+	reader.read_exact(&mut header).map_err(map_io_err)?;
+
+	let (magic, len) = header.split_at(MAGIC_BYTES.len());
+
+	if magic != MAGIC_BYTES {
+		return Err(BackupError::MalformedHeader);
+	}
+
+	let keyslot_len = u32::from_le_bytes([len[0], len[1], len[2], len[3]]) as usize;
+
+	if keyslot_len > MAX_KEYSLOT_LEN {
+		return Err(BackupError::MalformedHeader);
+	}
+
+	header.resize(header.len() + keyslot_len + XCHACHA20_POLY1305_NONCE_LEN, 0);
+	reader
+		.read_exact(&mut header[MAGIC_BYTES.len() + 4..])
+		.map_err(map_io_err)?;
+
+	let (keyslot, nonce) = header[MAGIC_BYTES.len() + 4..].split_at(keyslot_len);
+	let keyslot = Keyslot::from_bytes(keyslot).ok_or(BackupError::MalformedHeader)?;
+
+	let mut nonce_bytes = [0; XCHACHA20_POLY1305_NONCE_LEN];
+	nonce_bytes.copy_from_slice(nonce);
+
+	let key = match key {
+		DecryptionKey::Passphrase(passphrase) => keyslot.unlock(passphrase)?.key,
+		// Archives made before the passphrase was changed have another backup key
+		DecryptionKey::Unlocked(key) if key.keyslot == keyslot => key.key.clone(),
+		DecryptionKey::Unlocked(_) => return Err(BackupError::Decrypt),
+	};
+
+	Decryptor::new(&key, &Nonce::XChaCha20Poly1305(nonce_bytes), ALGORITHM)?
+		.decrypt_streams(reader, writer, header_aad(&header))
+		.map_err(|e| match e {
+			sd_crypto::Error::Decrypt => BackupError::Decrypt,
+			e => e.into(),
+		})
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+	use sd_crypto::primitives::{AEAD_TAG_LEN, BLOCK_LEN};
+
+	use super::*;
+
+	fn encrypted(plaintext: &[u8], key: &BackupKey) -> Vec<u8> {
+		let mut ciphertext = vec![];
+		encrypt_archive(plaintext, &mut ciphertext, key, Path::new("")).unwrap();
+		ciphertext
+	}
+
+	fn decrypted(ciphertext: &[u8], key: &DecryptionKey) -> Result<Vec<u8>, BackupError> {
+		let mut plaintext = vec![];
+		decrypt_archive(ciphertext, &mut plaintext, key, Path::new("")).map(|()| plaintext)
+	}
+
+	#[test]
+	fn archive_roundtrip() {
+		let key = BackupKey::generate("passphrase").unwrap();
+
+		// Exactly one block long, so the last block is an empty one
+		for len in [0, 1000, BLOCK_LEN, BLOCK_LEN * 2 + 7] {
+			let plaintext = (0..len).map(|i| i as u8).collect::<Vec<_>>();
+			let ciphertext = encrypted(&plaintext, &key);
+
+			assert_eq!(
+				archive_keyslot(ciphertext.as_slice()).unwrap().as_ref(),
+				Some(key.keyslot())
+			);
+			assert_eq!(
+				decrypted(&ciphertext, &DecryptionKey::Unlocked(key.clone())).unwrap(),
+				plaintext
+			);
+			assert_eq!(
+				decrypted(
+					&ciphertext,
+					&DecryptionKey::Passphrase("passphrase".to_string())
+				)
+				.unwrap(),
+				plaintext
+			);
+		}
+	}
+
+	#[test]
+	fn keyslot_only_unlocks_with_its_passphrase() {
+		let key = BackupKey::generate("passphrase").unwrap();
+		let keyslot = Keyslot::from_bytes(key.keyslot().as_bytes()).unwrap();
+
+		assert!(!keyslot
+			.as_bytes()
+			.windows(key.key.expose().len())
+			.any(|w| w == key.key.expose()));
+		assert!(keyslot.clone().unlock("passphrase").unwrap().key == key.key);
+		assert!(matches!(
+			keyslot.unlock("wrong passphrase"),
+			Err(BackupError::Decrypt)
+		));
+	}
+
+	#[test]
+	fn tampered_archives_are_rejected() {
+		let key = BackupKey::generate("passphrase").unwrap();
+		let unlocked = DecryptionKey::Unlocked(key.clone());
+		let ciphertext = encrypted(&vec![7; BLOCK_LEN + 10], &key);
+
+		let mut flipped = ciphertext.clone();
+		*flipped.last_mut().unwrap() ^= 1;
+		assert!(matches!(
+			decrypted(&flipped, &unlocked),
+			Err(BackupError::Decrypt)
+		));
+
+		// Dropping the last block would otherwise leave a valid looking archive
+		let truncated = &ciphertext[..ciphertext.len() - (10 + AEAD_TAG_LEN)];
+		assert!(matches!(
+			decrypted(truncated, &unlocked),
+			Err(BackupError::Decrypt)
+		));
+
+		let other_key = BackupKey::generate("passphrase").unwrap();
+		assert!(matches!(
+			decrypted(&ciphertext, &DecryptionKey::Unlocked(other_key)),
+			Err(BackupError::Decrypt)
+		));
+	}
+}
@@ -0,0 +1,419 @@
+use crate::{
+	api::notifications::{NotificationData, NotificationKind},
+	invalidate_query,
+	old_job::{
+		CurrentStep, JobError, JobInitOutput, JobReportUpdate, JobResult, JobStepOutput,
+		StatefulJob, WorkerContext,
+	},
+};
+
+use sd_prisma::prisma::location;
+use sd_utils::error::FileIOError;
+
+use std::{
+	io::{self, BufReader, BufWriter, Write},
+	path::PathBuf,
+	time::{SystemTime, UNIX_EPOCH},
+};
+
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use specta::Type;
+use tokio::{fs, task::spawn_blocking};
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use super::{
+	backups_directory, backups_to_delete,
+	encryption::{archive_keyslot, encrypt_archive},
+	library_config_path, list_backups, open_backup, read_manifest, snapshot_database,
+	unlocked_backup_key, write_archive, Backup, BackupError, DecryptionKey, Header, Manifest,
+	BACKUP_EXTENSION,
+};
+
+#[derive(Serialize, Deserialize, Hash, Type, Debug)]
+pub struct BackupJobInit {
+	/// If the backup was started by the schedule instead of the user
+	pub scheduled: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct BackupJobData {
+	id: Uuid,
+	directory: PathBuf,
+	encrypted: bool,
+	/// Full backup this one is incremental to, if any
+	base: Option<(Uuid, PathBuf)>,
+}
+
+impl BackupJobData {
+	fn snapshot_path(&self) -> PathBuf {
+		self.directory.join(format!("{}.db.part", self.id))
+	}
+
+	fn backup_path(&self) -> PathBuf {
+		self.directory
+			.join(format!("{}.{BACKUP_EXTENSION}", self.id))
+	}
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub enum BackupJobStep {
+	Snapshot,
+	Archive,
+	ApplyRetention,
+}
+
+#[async_trait::async_trait]
+impl StatefulJob for BackupJobInit {
+	type Data = BackupJobData;
+	type Step = BackupJobStep;
+	type RunMetadata = ();
+
+	const NAME: &'static str = "backup";
+
+	fn target_location(&self) -> location::id::Type {
+		// Backups aren't about any location, and location ids start at 1
+		0
+	}
+
+	async fn init(
+		&self,
+		ctx: &WorkerContext,
+		data: &mut Option<Self::Data>,
+	) -> Result<JobInitOutput<Self::RunMetadata, Self::Step>, JobError> {
+		let config = ctx.library.config().await.backup;
+		let directory = backups_directory(&ctx.node, &config);
+
+		let base = async {
+			fs::create_dir_all(&directory).await.map_err(|e| {
+				FileIOError::from((&directory, e, "Failed to create backups directory"))
+			})?;
+
+			let base = pick_base_backup(
+				ctx.library.id,
+				list_backups(&directory).await?,
+				config.incremental_backups,
+				config.encrypted,
+			);
+
+			if !config.encrypted {
+				return Ok(base);
+			}
+
+			let key = unlocked_backup_key(&ctx.node, &ctx.library).await?;
+
+			// Changing the passphrase makes a new backup key, and incremental backups
+			// must be encrypted with the same key as the full backup they're made on top of
+			let Some((base_id, base_path)) = base else {
+				return Ok(None);
+			};
+
+			let (_, base_file) = open_backup(&base_path).await?;
+			let base_keyslot = spawn_blocking(move || archive_keyslot(base_file))
+				.await?
+				.map_err(|e| FileIOError::from((&base_path, e, "Failed to read backup keyslot")))?;
+
+			Ok((base_keyslot.as_ref() == Some(key.keyslot())).then_some((base_id, base_path)))
+		}
+		.await;
+
+		let base = match base {
+			Ok(base) => base,
+			Err(e) => return Err(notify_failure(ctx, e).await),
+		};
+
+		*data = Some(BackupJobData {
+			id: Uuid::new_v4(),
+			directory,
+			encrypted: config.encrypted,
+			base,
+		});
+
+		ctx.progress(vec![JobReportUpdate::TaskCount(3)]);
+
+		Ok(vec![
+			BackupJobStep::Snapshot,
+			BackupJobStep::Archive,
+			BackupJobStep::ApplyRetention,
+		]
+		.into())
+	}
+
+	async fn execute_step(
+		&self,
+		ctx: &WorkerContext,
+		CurrentStep { step, step_number }: CurrentStep<'_, Self::Step>,
+		data: &Self::Data,
+		_: &Self::RunMetadata,
+	) -> Result<JobStepOutput<Self::Step, Self::RunMetadata>, JobError> {
+		let res = match step {
+			BackupJobStep::Snapshot => {
+				ctx.progress(vec![JobReportUpdate::Message(
+					"Taking a snapshot of the library database".to_string(),
+				)]);
+				snapshot(ctx, data).await
+			}
+			BackupJobStep::Archive => {
+				ctx.progress(vec![JobReportUpdate::Message(
+					if data.base.is_some() {
+						"Writing incremental backup"
+					} else {
+						"Writing full backup"
+					}
+					.to_string(),
+				)]);
+				archive(ctx, data).await
+			}
+			BackupJobStep::ApplyRetention => {
+				ctx.progress(vec![JobReportUpdate::Message(
+					"Removing old backups".to_string(),
+				)]);
+				apply_retention(ctx, data).await
+			}
+		};
+
+		if let Err(e) = res {
+			return Err(notify_failure(ctx, e).await);
+		}
+
+		ctx.progress(vec![JobReportUpdate::CompletedTaskCount(step_number + 1)]);
+
+		Ok(().into())
+	}
+
+	async fn finalize(
+		&self,
+		ctx: &WorkerContext,
+		data: &Option<Self::Data>,
+		_: &Self::RunMetadata,
+	) -> JobResult {
+		let init = self;
+		invalidate_query!(ctx.library, "backups.getAll");
+
+		if let Some(data) = data {
+			info!(
+				"Backup '{}' for library '{}' created at '{}'!",
+				data.id,
+				ctx.library.id,
+				data.backup_path().display()
+			);
+		}
+
+		Ok(Some(json!({
+			"init": init,
+			"id": data.as_ref().map(|data| data.id),
+			"incremental": data.as_ref().map(|data| data.base.is_some()),
+		})))
+	}
+}
+
+/// Incremental backups are made on top of the latest full backup of the library, as long as
+/// it has less than `incremental_backups` incremental backups and the same encryption setting.
+fn pick_base_backup(
+	library_id: Uuid,
+	backups: Vec<Backup>,
+	incremental_backups: u32,
+	encrypted: bool,
+) -> Option<(Uuid, PathBuf)> {
+	if incremental_backups == 0 {
+		return None;
+	}
+
+	let backups = backups
+		.into_iter()
+		.filter(|backup| backup.header.library_id == library_id)
+		.collect::<Vec<_>>();
+
+	let latest_full = backups
+		.iter()
+		.filter(|backup| backup.header.base_id.is_none())
+		.max_by_key(|backup| backup.header.timestamp)?;
+
+	let incrementals = backups
+		.iter()
+		.filter(|backup| backup.header.base_id == Some(latest_full.header.id))
+		.count();
+
+	(incrementals < incremental_backups as usize && latest_full.header.encrypted == encrypted)
+		.then(|| (latest_full.header.id, latest_full.path.clone()))
+}
+
+async fn snapshot(ctx: &WorkerContext, data: &BackupJobData) -> Result<(), BackupError> {
+	let snapshot_path = data.snapshot_path();
+	let db_path = ctx
+		.node
+		.libraries
+		.libraries_dir
+		.join(format!("{}.db", ctx.library.id));
+
+	// Interrupted jobs may leave a partial snapshot behind
+	if let Err(e) = fs::remove_file(&snapshot_path).await {
+		if e.kind() != io::ErrorKind::NotFound {
+			return Err(FileIOError::from((&snapshot_path, e)).into());
+		}
+	}
+
+	spawn_blocking(move || snapshot_database(&db_path, &snapshot_path)).await?
+}
+
+async fn archive(ctx: &WorkerContext, data: &BackupJobData) -> Result<(), BackupError> {
+	let library = &ctx.library;
+	let snapshot_path = data.snapshot_path();
+	let backup_path = data.backup_path();
+	let part_path = backup_path.with_extension(format!("{BACKUP_EXTENSION}.part"));
+
+	let key = if data.encrypted {
+		Some(unlocked_backup_key(&ctx.node, library).await?)
+	} else {
+		None
+	};
+
+	let base = if let Some((base_id, base_path)) = &data.base {
+		let (base_header, base_file) = open_backup(base_path).await?;
+		Some((*base_id, base_header, base_file))
+	} else {
+		None
+	};
+
+	// Header. We do this so the file is self-sufficient.
+	let mut header_bytes = vec![];
+	Header {
+		id: data.id,
+		timestamp: SystemTime::now()
+			.duration_since(UNIX_EPOCH)
+			.expect("Time went backwards")
+			.as_millis(),
+		library_id: library.id,
+		library_name: library.config().await.name.to_string(),
+		encrypted: data.encrypted,
+		base_id: base.as_ref().map(|(base_id, _, _)| *base_id),
+	}
+	.write(&mut header_bytes)
+	.await
+	.map_err(|e| FileIOError::from((&part_path, e, "Failed to create backup header")))?;
+
+	let config_path = library_config_path(&ctx.node, library.id);
+	let directory = data.directory.clone();
+
+	spawn_blocking({
+		let part_path = part_path.clone();
+		let snapshot_path = snapshot_path.clone();
+
+		move || -> Result<(), BackupError> {
+			let manifest = Manifest::from_snapshot(&snapshot_path)
+				.map_err(|e| FileIOError::from((&snapshot_path, e, "Failed to read snapshot")))?;
+
+			let changed_pages = base
+				.map(|(_, base_header, base_file)| {
+					let key = key.clone().map(DecryptionKey::Unlocked);
+
+					read_manifest(base_file, &base_header, key.as_ref(), &directory)
+						.map(|base_manifest| manifest.changed_pages(&base_manifest))
+				})
+				.transpose()?;
+
+			let map_io_err =
+				|e: io::Error| FileIOError::from((&part_path, e, "Failed to write backup archive"));
+
+			let mut file = BufWriter::new(std::fs::File::create(&part_path).map_err(map_io_err)?);
+			file.write_all(&header_bytes).map_err(map_io_err)?;
+
+			if let Some(key) = &key {
+				// Encrypting works on whole streams, so the archive goes to a temporary file first
+				let mut archive = tempfile::tempfile_in(&directory).map_err(map_io_err)?;
+
+				write_archive(
+					&mut archive,
+					&config_path,
+					&snapshot_path,
+					&manifest,
+					changed_pages.as_deref(),
+				)
+				.map_err(map_io_err)?;
+
+				io::Seek::rewind(&mut archive).map_err(map_io_err)?;
+
+				encrypt_archive(BufReader::new(archive), &mut file, key, &part_path)?;
+			} else {
+				write_archive(
+					&mut file,
+					&config_path,
+					&snapshot_path,
+					&manifest,
+					changed_pages.as_deref(),
+				)
+				.map_err(map_io_err)?;
+			}
+
+			file.into_inner()
+				.map_err(|e| map_io_err(e.into_error()))?
+				.sync_all()
+				.map_err(map_io_err)?;
+
+			Ok(())
+		}
+	})
+	.await??;
+
+	fs::rename(&part_path, &backup_path)
+		.await
+		.map_err(|e| FileIOError::from((&backup_path, e, "Failed to finish backup file")))?;
+
+	if let Err(e) = fs::remove_file(&snapshot_path).await {
+		warn!(
+			"Failed to remove database snapshot at '{}': {e:#?}",
+			snapshot_path.display()
+		);
+	}
+
+	Ok(())
+}
+
+async fn apply_retention(ctx: &WorkerContext, data: &BackupJobData) -> Result<(), BackupError> {
+	let config = ctx.library.config().await.backup;
+
+	let backups = list_backups(&data.directory)
+		.await?
+		.into_iter()
+		.filter(|backup| backup.header.library_id == ctx.library.id)
+		.collect::<Vec<_>>();
+
+	let to_delete = backups_to_delete(
+		&backups
+			.iter()
+			.map(|backup| &backup.header)
+			.collect::<Vec<_>>(),
+		config.keep_daily,
+		config.keep_weekly,
+	);
+
+	for backup in backups
+		.iter()
+		.filter(|backup| to_delete.contains(&backup.header.id))
+	{
+		fs::remove_file(&backup.path)
+			.await
+			.map_err(|e| FileIOError::from((&backup.path, e, "Failed to remove old backup")))?;
+	}
+
+	Ok(())
+}
+
+async fn notify_failure(ctx: &WorkerContext, e: BackupError) -> JobError {
+	ctx.node
+		.emit_notification(
+			NotificationData {
+				title: "Backup failed".to_string(),
+				content: format!(
+					"Backup of library \"{}\" failed: {e}",
+					ctx.library.config().await.name
+				),
+				kind: NotificationKind::Error,
+			},
+			None,
+		)
+		.await;
+
+	e.into()
+}
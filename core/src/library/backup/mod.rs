@@ -0,0 +1,1256 @@
+//! Library backups.
+//!
+//! A backup is a single `.bkp` file made of a [`Header`] followed by a tar.gz archive, which is
+//! encrypted when the library has backup encryption enabled (see [`encryption`]).
+//!
+//! Full backups hold a snapshot of the whole library database. Incremental backups only hold the
+//! database pages that changed since the latest full backup, so that one is needed to restore
+//! them and retention never deletes it while they are kept.
+
+use crate::{
+	api::notifications::{NotificationData, NotificationKind},
+	library::{LibraryConfig, LibraryManagerError},
+	old_job::{JobBuilder, JobManagerError},
+	Node,
+};
+
+use sd_p2p::Identity;
+use sd_prisma::prisma::{instance, location};
+use sd_utils::{
+	db,
+	error::{FileIOError, NonUtf8PathError},
+};
+
+use std::{
+	cmp,
+	collections::HashSet,
+	io::{self, BufReader, Read, Seek, SeekFrom, Write},
+	ops::Range,
+	path::{Path, PathBuf},
+	sync::{Arc, Weak},
+	thread,
+	time::Duration,
+};
+
+use chrono::{DateTime, Datelike, Utc};
+use flate2::{bufread::GzDecoder, write::GzEncoder, Compression};
+use futures_concurrency::future::Join;
+use rspc::ErrorCode;
+use rusqlite::{backup::StepResult, Connection, OpenFlags};
+use serde::{Deserialize, Serialize, Serializer};
+use specta::Type;
+use tar::Archive;
+use tempfile::tempdir;
+use thiserror::Error;
+use tokio::{
+	fs::{self, File},
+	io::{AsyncRead, AsyncReadExt, AsyncSeekExt, AsyncWrite, AsyncWriteExt},
+	task::{spawn_blocking, JoinError},
+	time::{interval, MissedTickBehavior},
+};
+use tracing::{error, warn};
+use uuid::Uuid;
+
+use super::Library;
+
+mod encryption;
+mod job;
+
+pub use encryption::BackupKey;
+pub use job::BackupJobInit;
+
+use encryption::{decrypt_archive, DecryptionKey, Keyslot};
+
+pub const BACKUP_EXTENSION: &str = "bkp";
+
+/// How often we check if a scheduled backup is due
+const SCHEDULE_CHECK_INTERVAL: Duration = Duration::from_secs(10 * 60);
+
+const MANIFEST_ENTRY: &str = "manifest.json";
+const CONFIG_ENTRY: &str = "library.sdlibrary";
+const DB_ENTRY: &str = "library.db";
+const PAGES_DIR: &str = "pages";
+
+const ENCRYPTED_FLAG: u8 = 0b1;
+
+/// Backup settings of a library, stored in its config file.
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct BackupConfig {
+	/// Directory backups are written to, defaults to the `backups` directory in the node data directory.
+	pub destination: Option<PathBuf>,
+	/// Hours between scheduled backups, they are disabled while this is `None`.
+	pub interval_hours: Option<u32>,
+	/// How many incremental backups are made after each full backup.
+	pub incremental_backups: u32,
+	/// The latest backup of each of the last `keep_daily` days is kept.
+	pub keep_daily: u32,
+	/// The latest backup of each of the last `keep_weekly` weeks is kept.
+	/// Backups are never deleted while both `keep_daily` and `keep_weekly` are zero.
+	pub keep_weekly: u32,
+	/// Encrypts backups with the passphrase set through `backups.setConfig`, which has to be
+	/// entered again after the node restarts before encrypted backups can be made.
+	pub encrypted: bool,
+}
+
+impl Default for BackupConfig {
+	fn default() -> Self {
+		Self {
+			destination: None,
+			interval_hours: None,
+			incremental_backups: 0,
+			keep_daily: 0,
+			keep_weekly: 0,
+			encrypted: false,
+		}
+	}
+}
+
+#[derive(Error, Debug)]
+pub enum BackupError {
+	#[error("library manager error: {0}")]
+	LibraryManager(#[from] LibraryManagerError),
+	#[error("database error: {0}")]
+	Database(#[from] prisma_client_rust::QueryError),
+	#[error("malformed header")]
+	MalformedHeader,
+	#[error("Library already exists, please remove it and try again!")]
+	LibraryAlreadyExists,
+	#[error("this backup is encrypted, a passphrase is required to restore it")]
+	PassphraseRequired,
+	#[error("wrong passphrase or corrupted backup")]
+	Decrypt,
+	#[error("backup encryption is enabled, but no passphrase was set")]
+	MissingPassphrase,
+	#[error("backups are encrypted, the backup passphrase must be entered since the node started")]
+	Locked,
+	#[error("the full backup <id='{0}'> this incremental backup was made on top of wasn't found")]
+	MissingBaseBackup(Uuid),
+	#[error("backup archive is missing '{0}'")]
+	MissingArchiveEntry(&'static str),
+	#[error("the library config of this backup has no current instance")]
+	MissingInstance,
+	#[error("invalid backup manifest: {0}")]
+	Manifest(#[from] serde_json::Error),
+	#[error("backup encryption error: {0}")]
+	Crypto(#[from] sd_crypto::Error),
+	#[error("failed to take database snapshot: {0}")]
+	Snapshot(#[from] rusqlite::Error),
+	#[error("failed to join blocking task: {0}")]
+	JoinTask(#[from] JoinError),
+
+	#[error(transparent)]
+	FileIO(#[from] FileIOError),
+}
+
+impl From<BackupError> for rspc::Error {
+	fn from(e: BackupError) -> Self {
+		let code = match e {
+			BackupError::LibraryAlreadyExists
+			| BackupError::PassphraseRequired
+			| BackupError::Decrypt
+			| BackupError::MissingPassphrase
+			| BackupError::Locked
+			| BackupError::MissingBaseBackup(_) => ErrorCode::BadRequest,
+			_ => ErrorCode::InternalServerError,
+		};
+
+		Self::with_cause(code, e.to_string(), e)
+	}
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Type)]
+pub struct Header {
+	// Backup unique id
+	pub id: Uuid,
+	// Time since epoch the backup was created at
+	#[specta(type = String)]
+	#[serde(serialize_with = "as_string")]
+	pub timestamp: u128,
+	// Library id
+	pub library_id: Uuid,
+	// Library display name
+	pub library_name: String,
+	// If the archive is encrypted
+	pub encrypted: bool,
+	// Id of the full backup this incremental backup was made on top of
+	pub base_id: Option<Uuid>,
+}
+
+fn as_string<T: ToString, S>(x: &T, s: S) -> Result<S::Ok, S::Error>
+where
+	S: Serializer,
+{
+	s.serialize_str(&x.to_string())
+}
+
+impl Header {
+	async fn write(&self, file: &mut (impl AsyncWrite + Unpin)) -> Result<(), io::Error> {
+		// For future versioning we can bump `2` to `3` and match on it in the decoder.
+		file.write_all(b"sdbkp2").await?;
+		file.write_all(&self.id.to_bytes_le()).await?;
+		file.write_all(&self.timestamp.to_le_bytes()).await?;
+		file.write_all(&self.library_id.to_bytes_le()).await?;
+		file.write_all(&[if self.encrypted { ENCRYPTED_FLAG } else { 0 }])
+			.await?;
+		file.write_all(&self.base_id.unwrap_or_default().to_bytes_le())
+			.await?;
+		{
+			let bytes = &self.library_name.as_bytes()
+				[..cmp::min(u32::MAX as usize, self.library_name.len())];
+			file.write_all(&(bytes.len() as u32).to_le_bytes()).await?;
+			file.write_all(bytes).await?;
+		}
+
+		Ok(())
+	}
+
+	async fn read(
+		file: &mut (impl AsyncRead + Unpin),
+		path: impl AsRef<Path>,
+	) -> Result<Self, BackupError> {
+		let path = path.as_ref();
+
+		let mut magic = [0u8; 6];
+		file.read_exact(&mut magic)
+			.await
+			.map_err(|e| FileIOError::from((path, e)))?;
+
+		// Version 1 backups are never encrypted nor incremental, so they don't have those fields
+		let is_v1 = match &magic {
+			b"sdbkp1" => true,
+			b"sdbkp2" => false,
+			_ => return Err(BackupError::MalformedHeader),
+		};
+
+		let len = if is_v1 {
+			16 + 16 + 16 + 4
+		} else {
+			16 + 16 + 16 + 1 + 16 + 4
+		};
+
+		let mut buf = vec![0u8; len];
+		file.read_exact(&mut buf)
+			.await
+			.map_err(|e| FileIOError::from((path, e)))?;
+
+		let (encrypted, base_id, name_len) = if is_v1 {
+			(false, None, &buf[48..52])
+		} else {
+			let base_id = Uuid::from_bytes_le(
+				buf[49..65]
+					.try_into()
+					.map_err(|_| BackupError::MalformedHeader)?,
+			);
+
+			(
+				buf[48] & ENCRYPTED_FLAG != 0,
+				(!base_id.is_nil()).then_some(base_id),
+				&buf[65..69],
+			)
+		};
+
+		Ok(Self {
+			id: Uuid::from_bytes_le(
+				buf[0..16]
+					.try_into()
+					.map_err(|_| BackupError::MalformedHeader)?,
+			),
+			timestamp: u128::from_le_bytes(
+				buf[16..32]
+					.try_into()
+					.map_err(|_| BackupError::MalformedHeader)?,
+			),
+			library_id: Uuid::from_bytes_le(
+				buf[32..48]
+					.try_into()
+					.map_err(|_| BackupError::MalformedHeader)?,
+			),
+
+			library_name: {
+				let len = u32::from_le_bytes(
+					name_len
+						.try_into()
+						.map_err(|_| BackupError::MalformedHeader)?,
+				);
+
+				let mut name = vec![0; len as usize];
+				file.read_exact(&mut name)
+					.await
+					.map_err(|e| FileIOError::from((path, e)))?;
+
+				String::from_utf8(name).map_err(|_| BackupError::MalformedHeader)?
+			},
+			encrypted,
+			base_id,
+		})
+	}
+
+	fn date(&self) -> DateTime<Utc> {
+		DateTime::from_timestamp_millis(self.timestamp as i64).unwrap_or_default()
+	}
+}
+
+#[derive(Serialize, Type)]
+pub struct Backup {
+	#[serde(flatten)]
+	pub header: Header,
+	pub path: PathBuf,
+}
+
+/// Describes the database snapshot a backup was made from, so the next incremental
+/// backups know which pages changed.
+#[derive(Debug, Serialize, Deserialize)]
+struct Manifest {
+	page_size: usize,
+	db_size: u64,
+	// First 8 bytes of the blake3 hash of each page
+	pages: Vec<u64>,
+}
+
+impl Manifest {
+	fn from_snapshot(snapshot_path: &Path) -> io::Result<Self> {
+		let mut file = std::fs::File::open(snapshot_path)?;
+
+		// The page size is a big-endian u16 at offset 16 of the database header, with 1 meaning 65536
+		let mut header = [0; 18];
+		file.read_exact(&mut header)?;
+		file.rewind()?;
+
+		let page_size = match u16::from_be_bytes([header[16], header[17]]) {
+			1 => 65536,
+			page_size if page_size.is_power_of_two() && page_size >= 512 => page_size as usize,
+			_ => {
+				return Err(io::Error::new(
+					io::ErrorKind::InvalidData,
+					"snapshot isn't a SQLite database",
+				))
+			}
+		};
+
+		let mut buf = vec![0; page_size];
+
+		let mut db_size = 0;
+		let mut pages = vec![];
+
+		loop {
+			let read = read_chunk(&mut file, &mut buf)?;
+			if read == 0 {
+				break;
+			}
+
+			db_size += read as u64;
+
+			let hash = blake3::hash(&buf[..read]);
+			let mut prefix = [0; 8];
+			prefix.copy_from_slice(&hash.as_bytes()[..8]);
+			pages.push(u64::from_le_bytes(prefix));
+
+			if read < page_size {
+				break;
+			}
+		}
+
+		Ok(Self {
+			page_size,
+			db_size,
+			pages,
+		})
+	}
+
+	/// Runs of consecutive pages that aren't the same in `base`
+	fn changed_pages(&self, base: &Self) -> Vec<Range<usize>> {
+		let mut changed: Vec<Range<usize>> = vec![];
+
+		if self.page_size != base.page_size {
+			if !self.pages.is_empty() {
+				changed.push(0..self.pages.len());
+			}
+
+			return changed;
+		}
+
+		for (index, hash) in self.pages.iter().enumerate() {
+			if base.pages.get(index) == Some(hash) {
+				continue;
+			}
+
+			match changed.last_mut() {
+				Some(run) if run.end == index => run.end += 1,
+				_ => changed.push(index..index + 1),
+			}
+		}
+
+		changed
+	}
+}
+
+/// Fills `buf` as much as possible, only returning less bytes at the end of the reader
+fn read_chunk(reader: &mut impl Read, buf: &mut [u8]) -> io::Result<usize> {
+	let mut total = 0;
+
+	while total < buf.len() {
+		match reader.read(&mut buf[total..]) {
+			Ok(0) => break,
+			Ok(read) => total += read,
+			Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+			Err(e) => return Err(e),
+		}
+	}
+
+	Ok(total)
+}
+
+/// Copies the database at `db_path` with SQLite's online backup, which gives us a consistent
+/// snapshot without having to stop writes to the database.
+///
+/// Unlike `VACUUM INTO`, which rebuilds the whole database, this copies every page where it is
+/// in the database file, so a small change to the library only changes a few pages of the next
+/// snapshot and the incremental backup made from it stays small.
+fn snapshot_database(db_path: &Path, snapshot_path: &Path) -> Result<(), BackupError> {
+	let db = Connection::open_with_flags(
+		db_path,
+		OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_NO_MUTEX,
+	)?;
+	let mut snapshot = Connection::open(snapshot_path)?;
+
+	let backup = rusqlite::backup::Backup::new(&db, &mut snapshot)?;
+
+	// Copying everything in a single step keeps the snapshot consistent,
+	// we only have to retry while something else holds a lock on the database
+	loop {
+		match backup.step(-1)? {
+			StepResult::Done => return Ok(()),
+			// Everything is copied in one step, so this is the database being busy or locked
+			_ => thread::sleep(Duration::from_millis(100)),
+		}
+	}
+}
+
+/// Where the [`Keyslot`] of a library's backup key is kept
+fn keyslot_path(node: &Node, library_id: Uuid) -> PathBuf {
+	node.libraries
+		.libraries_dir
+		.join(format!("{library_id}.sdbackupkey"))
+}
+
+pub fn backups_directory(node: &Node, config: &BackupConfig) -> PathBuf {
+	config
+		.destination
+		.clone()
+		.unwrap_or_else(|| node.data_dir.join("backups"))
+}
+
+/// Sets the passphrase used to encrypt the backups of a library from now on.
+///
+/// Backups made before keep their own key, so they're still restored with the previous passphrase.
+pub async fn set_backup_passphrase(
+	node: &Node,
+	library: &Library,
+	passphrase: String,
+) -> Result<(), BackupError> {
+	let key = spawn_blocking(move || BackupKey::generate(&passphrase)).await??;
+
+	let keyslot_path = keyslot_path(node, library.id);
+
+	fs::write(&keyslot_path, key.keyslot().as_bytes())
+		.await
+		.map_err(|e| FileIOError::from((&keyslot_path, e, "Failed to save backup keyslot")))?;
+
+	*library.backup_key.write().await = Some(key);
+
+	Ok(())
+}
+
+/// Unlocks the backup key of a library with its passphrase, until the node stops.
+pub async fn unlock_backups(
+	node: &Node,
+	library: &Library,
+	passphrase: String,
+) -> Result<(), BackupError> {
+	let keyslot = load_keyslot(node, library.id).await?;
+
+	let key = spawn_blocking(move || keyslot.unlock(&passphrase)).await??;
+
+	*library.backup_key.write().await = Some(key);
+
+	Ok(())
+}
+
+async fn load_keyslot(node: &Node, library_id: Uuid) -> Result<Keyslot, BackupError> {
+	let keyslot_path = keyslot_path(node, library_id);
+
+	match fs::read(&keyslot_path).await {
+		Ok(bytes) => Keyslot::from_bytes(&bytes).ok_or(BackupError::MissingPassphrase),
+		Err(e) if e.kind() == io::ErrorKind::NotFound => Err(BackupError::MissingPassphrase),
+		Err(e) => {
+			Err(FileIOError::from((&keyslot_path, e, "Failed to read backup keyslot")).into())
+		}
+	}
+}
+
+/// The unlocked backup key of a library, if it still matches the library's keyslot
+async fn unlocked_backup_key(node: &Node, library: &Library) -> Result<BackupKey, BackupError> {
+	let keyslot = load_keyslot(node, library.id).await?;
+
+	library
+		.backup_key
+		.read()
+		.await
+		.clone()
+		.filter(|key| *key.keyslot() == keyslot)
+		.ok_or(BackupError::Locked)
+}
+
+/// Reads every backup in `directory`, ignoring files that aren't backups.
+pub async fn list_backups(directory: impl AsRef<Path>) -> Result<Vec<Backup>, BackupError> {
+	let directory = directory.as_ref();
+
+	let mut read_dir = match fs::read_dir(directory).await {
+		Ok(read_dir) => read_dir,
+		Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(vec![]),
+		Err(e) => {
+			return Err(
+				FileIOError::from((directory, e, "Failed to read backups directory")).into(),
+			)
+		}
+	};
+
+	let mut backups = vec![];
+
+	while let Some(entry) = read_dir
+		.next_entry()
+		.await
+		.map_err(|e| FileIOError::from((directory, e, "Failed to read next entry to backup")))?
+	{
+		let entry_path = entry.path();
+
+		if entry_path.extension().and_then(|ext| ext.to_str()) != Some(BACKUP_EXTENSION) {
+			continue;
+		}
+
+		backups.push(async move {
+			let mut file = File::open(&entry_path)
+				.await
+				.map_err(|e| FileIOError::from((&entry_path, e, "Failed to open backup entry")))?;
+
+			Header::read(&mut file, &entry_path)
+				.await
+				.map(|header| Backup {
+					header,
+					path: entry_path,
+				})
+		});
+	}
+
+	Ok(backups
+		.join()
+		.await
+		.into_iter()
+		.filter_map(|res| {
+			res.map_err(|e| warn!("Skipping unreadable backup: {e:#?}"))
+				.ok()
+		})
+		.collect())
+}
+
+/// Opens a backup, returning its header and the file positioned right at its archive
+async fn open_backup(path: &Path) -> Result<(Header, std::fs::File), BackupError> {
+	let mut file = File::open(path)
+		.await
+		.map_err(|e| FileIOError::from((path, e, "Failed to open backup file")))?;
+
+	let header = Header::read(&mut file, path).await?;
+
+	let archive_start = file
+		.stream_position()
+		.await
+		.map_err(|e| FileIOError::from((path, e)))?;
+
+	let mut file = file.into_std().await;
+	file.seek(SeekFrom::Start(archive_start))
+		.map_err(|e| FileIOError::from((path, e)))?;
+
+	Ok((header, file))
+}
+
+/// Writes the tar.gz archive of a backup. Incremental backups only get the `changed_pages`
+/// of the snapshot, one entry per run of pages, while full ones get the whole snapshot.
+fn write_archive(
+	writer: impl Write,
+	config_path: &Path,
+	snapshot_path: &Path,
+	manifest: &Manifest,
+	changed_pages: Option<&[Range<usize>]>,
+) -> io::Result<()> {
+	fn append_bytes(
+		tar: &mut tar::Builder<impl Write>,
+		name: &str,
+		bytes: &[u8],
+	) -> io::Result<()> {
+		let mut header = tar::Header::new_gnu();
+		header.set_size(bytes.len() as u64);
+		header.set_mode(0o644);
+		header.set_cksum();
+
+		tar.append_data(&mut header, name, bytes)
+	}
+
+	let mut tar = tar::Builder::new(GzEncoder::new(writer, Compression::default()));
+
+	// The manifest goes first, so reading it doesn't require decompressing everything else
+	append_bytes(&mut tar, MANIFEST_ENTRY, &serde_json::to_vec(manifest)?)?;
+	tar.append_path_with_name(config_path, CONFIG_ENTRY)?;
+
+	if let Some(changed_pages) = changed_pages {
+		let mut snapshot = std::fs::File::open(snapshot_path)?;
+		let mut buf = vec![];
+
+		for run in changed_pages {
+			buf.resize(run.len() * manifest.page_size, 0);
+
+			snapshot.seek(SeekFrom::Start((run.start * manifest.page_size) as u64))?;
+			let read = read_chunk(&mut snapshot, &mut buf)?;
+
+			append_bytes(
+				&mut tar,
+				&format!("{PAGES_DIR}/{}", run.start),
+				&buf[..read],
+			)?;
+		}
+	} else {
+		tar.append_path_with_name(snapshot_path, DB_ENTRY)?;
+	}
+
+	tar.into_inner()?.finish()?.flush()
+}
+
+/// Opens the tar.gz archive of a backup, decrypting it into a temporary file first if needed.
+fn open_archive(
+	file: std::fs::File,
+	header: &Header,
+	key: Option<&DecryptionKey>,
+	temp_dir: &Path,
+) -> Result<Archive<GzDecoder<BufReader<std::fs::File>>>, BackupError> {
+	let file = if header.encrypted {
+		let key = key.ok_or(BackupError::PassphraseRequired)?;
+
+		let mut decrypted = tempfile::tempfile_in(temp_dir).map_err(|e| {
+			FileIOError::from((temp_dir, e, "Failed to create file to decrypt backup"))
+		})?;
+
+		decrypt_archive(BufReader::new(file), &mut decrypted, key, temp_dir)?;
+
+		decrypted
+			.rewind()
+			.map_err(|e| FileIOError::from((temp_dir, e)))?;
+
+		decrypted
+	} else {
+		file
+	};
+
+	Ok(Archive::new(GzDecoder::new(BufReader::new(file))))
+}
+
+/// Reads the manifest of a backup, which is the first entry of its archive.
+fn read_manifest(
+	file: std::fs::File,
+	header: &Header,
+	key: Option<&DecryptionKey>,
+	temp_dir: &Path,
+) -> Result<Manifest, BackupError> {
+	let mut archive = open_archive(file, header, key, temp_dir)?;
+
+	let entry = archive
+		.entries()
+		.and_then(|mut entries| entries.next().transpose())
+		.map_err(|e| FileIOError::from((temp_dir, e, "Failed to read backup archive")))?
+		.filter(|entry| {
+			entry
+				.path()
+				.map_or(false, |path| path == Path::new(MANIFEST_ENTRY))
+		})
+		.ok_or(BackupError::MissingArchiveEntry(MANIFEST_ENTRY))?;
+
+	serde_json::from_reader(entry).map_err(Into::into)
+}
+
+/// Picks which backups of a library must be deleted to honor the retention policy.
+///
+/// The latest backup is always kept, as are the full backups that kept incremental backups need.
+fn backups_to_delete(backups: &[&Header], keep_daily: u32, keep_weekly: u32) -> Vec<Uuid> {
+	if keep_daily == 0 && keep_weekly == 0 {
+		return vec![];
+	}
+
+	let mut newest_first = backups.to_vec();
+	newest_first.sort_by(|a, b| b.timestamp.cmp(&a.timestamp));
+
+	let mut keep = HashSet::new();
+
+	if let Some(latest) = newest_first.first() {
+		keep.insert(latest.id);
+	}
+
+	let mut kept_days = HashSet::new();
+	let mut kept_weeks = HashSet::new();
+
+	for header in &newest_first {
+		let date = header.date();
+
+		if kept_days.len() < keep_daily as usize && kept_days.insert(date.date_naive()) {
+			keep.insert(header.id);
+		}
+
+		let week = date.iso_week();
+		if kept_weeks.len() < keep_weekly as usize && kept_weeks.insert((week.year(), week.week()))
+		{
+			keep.insert(header.id);
+		}
+	}
+
+	let bases = newest_first
+		.iter()
+		.filter(|header| keep.contains(&header.id))
+		.filter_map(|header| header.base_id)
+		.collect::<Vec<_>>();
+	keep.extend(bases);
+
+	newest_first
+		.into_iter()
+		.filter(|header| !keep.contains(&header.id))
+		.map(|header| header.id)
+		.collect()
+}
+
+/// Keeps making backups of the library according to its schedule,
+/// stopping once the library is unloaded.
+pub async fn scheduled_backups_loop(node: Arc<Node>, library: Weak<Library>) {
+	let mut tick = interval(SCHEDULE_CHECK_INTERVAL);
+	tick.set_missed_tick_behavior(MissedTickBehavior::Skip);
+
+	// So a locked backup key is only brought up once, instead of on every check until it's unlocked
+	let mut notified_locked = false;
+
+	loop {
+		tick.tick().await;
+
+		let Some(library) = library.upgrade() else {
+			break;
+		};
+
+		if let Err(e) = spawn_backup_if_due(&node, &library, &mut notified_locked).await {
+			error!("Failed to run scheduled backup: {e:#?}");
+		}
+	}
+}
+
+async fn spawn_backup_if_due(
+	node: &Arc<Node>,
+	library: &Arc<Library>,
+	notified_locked: &mut bool,
+) -> Result<(), BackupError> {
+	let config = library.config().await.backup;
+
+	let Some(interval_hours) = config.interval_hours else {
+		return Ok(());
+	};
+
+	let latest_backup = list_backups(backups_directory(node, &config))
+		.await?
+		.into_iter()
+		.filter(|backup| backup.header.library_id == library.id)
+		.map(|backup| backup.header.timestamp)
+		.max();
+
+	let now = Utc::now().timestamp_millis() as u128;
+
+	if latest_backup.map_or(true, |latest_backup| {
+		now.saturating_sub(latest_backup) >= u128::from(interval_hours) * 60 * 60 * 1000
+	}) {
+		// The job would only fail, so we wait for the passphrase to be entered instead
+		if config.encrypted {
+			match unlocked_backup_key(node, library).await {
+				Ok(_) => *notified_locked = false,
+				Err(BackupError::Locked) => {
+					if !*notified_locked {
+						*notified_locked = true;
+						node.emit_notification(
+							NotificationData {
+								title: "Backups paused".to_string(),
+								content: format!(
+									"Scheduled backups of library \"{}\" are encrypted, \
+									unlock them with the backup passphrase to resume backups",
+									library.config().await.name
+								),
+								kind: NotificationKind::Info,
+							},
+							None,
+						)
+						.await;
+					}
+
+					return Ok(());
+				}
+				Err(e) => return Err(e),
+			}
+		}
+
+		match JobBuilder::new(BackupJobInit { scheduled: true })
+			.build()
+			.spawn(node, library)
+			.await
+		{
+			Ok(()) | Err(JobManagerError::AlreadyRunningJob { .. }) => {}
+			Err(e) => error!("Failed to spawn scheduled backup job: {e:#?}"),
+		}
+	}
+
+	Ok(())
+}
+
+/// Restores a backup, either as the library it was made from, which must not exist,
+/// or as a new library with its own id, so it can live alongside the original one.
+pub async fn restore_backup(
+	node: &Arc<Node>,
+	path: impl AsRef<Path>,
+	passphrase: Option<String>,
+	as_new_library: bool,
+) -> Result<Header, BackupError> {
+	let path = path.as_ref();
+
+	let (header, file) = open_backup(path).await?;
+
+	let library_id = if as_new_library {
+		Uuid::new_v4()
+	} else {
+		// TODO: Actually handle restoring into a library that exists. For now it's easier to error out.
+		let None = node.libraries.get_library(&header.library_id).await else {
+			return Err(BackupError::LibraryAlreadyExists);
+		};
+
+		header.library_id
+	};
+
+	let base = if let Some(base_id) = header.base_id {
+		let base = list_backups(path.parent().unwrap_or(path))
+			.await?
+			.into_iter()
+			.find(|backup| backup.header.id == base_id)
+			.ok_or(BackupError::MissingBaseBackup(base_id))?;
+
+		Some(open_backup(&base.path).await?)
+	} else {
+		None
+	};
+
+	let key = passphrase.map(DecryptionKey::Passphrase);
+
+	let temp_dir = tempdir().map_err(|e| {
+		FileIOError::from((
+			"/tmp",
+			e,
+			"Failed to get a temporary directory to restore backup",
+		))
+	})?;
+
+	let temp_dir_path = temp_dir.path().to_path_buf();
+
+	let header = spawn_blocking(move || -> Result<Header, BackupError> {
+		let map_io_err =
+			|e: io::Error| FileIOError::from((&temp_dir_path, e, "Failed to unpack backup"));
+
+		match base {
+			None => open_archive(file, &header, key.as_ref(), &temp_dir_path)?
+				.unpack(&temp_dir_path)
+				.map_err(map_io_err)?,
+
+			Some((base_header, base_file)) => {
+				// First we restore the database of the full backup...
+				let mut base_archive =
+					open_archive(base_file, &base_header, key.as_ref(), &temp_dir_path)?;
+				let mut db_entry = base_archive
+					.entries()
+					.map_err(map_io_err)?
+					.filter_map(Result::ok)
+					.find(|entry| {
+						entry
+							.path()
+							.map_or(false, |path| path == Path::new(DB_ENTRY))
+					})
+					.ok_or(BackupError::MissingArchiveEntry(DB_ENTRY))?;
+				db_entry
+					.unpack(temp_dir_path.join(DB_ENTRY))
+					.map_err(map_io_err)?;
+
+				// ...then apply the pages that changed on top of it
+				let mut db = std::fs::OpenOptions::new()
+					.write(true)
+					.open(temp_dir_path.join(DB_ENTRY))
+					.map_err(map_io_err)?;
+
+				let mut archive = open_archive(file, &header, key.as_ref(), &temp_dir_path)?;
+				let mut manifest = None;
+
+				for entry in archive.entries().map_err(map_io_err)? {
+					let mut entry = entry.map_err(map_io_err)?;
+					let entry_path = entry.path().map_err(map_io_err)?.into_owned();
+
+					if entry_path == Path::new(MANIFEST_ENTRY) {
+						manifest = Some(serde_json::from_reader::<_, Manifest>(&mut entry)?);
+					} else if entry_path == Path::new(CONFIG_ENTRY) {
+						entry
+							.unpack(temp_dir_path.join(CONFIG_ENTRY))
+							.map_err(map_io_err)?;
+					} else if let Some(index) = entry_path
+						.strip_prefix(PAGES_DIR)
+						.ok()
+						.and_then(|index| index.to_str())
+						.and_then(|index| index.parse::<u64>().ok())
+					{
+						let manifest = manifest
+							.as_ref()
+							.ok_or(BackupError::MissingArchiveEntry(MANIFEST_ENTRY))?;
+
+						db.seek(SeekFrom::Start(index * manifest.page_size as u64))
+							.map_err(map_io_err)?;
+						io::copy(&mut entry, &mut db).map_err(map_io_err)?;
+					}
+				}
+
+				let manifest = manifest.ok_or(BackupError::MissingArchiveEntry(MANIFEST_ENTRY))?;
+				db.set_len(manifest.db_size).map_err(map_io_err)?;
+			}
+		}
+
+		Ok(header)
+	})
+	.await??;
+
+	let temp_dir_path = temp_dir.path();
+
+	let library_config_path = temp_dir_path.join(CONFIG_ENTRY);
+	let db_path = temp_dir_path.join(DB_ENTRY);
+
+	if as_new_library {
+		detach_restored_library(node, &library_config_path, &db_path).await?;
+	}
+
+	let library_config_restored_path = node
+		.libraries
+		.libraries_dir
+		.join(format!("{library_id}.sdlibrary"));
+
+	fs::copy(library_config_path, &library_config_restored_path)
+		.await
+		.map_err(|e| {
+			FileIOError::from((
+				&library_config_restored_path,
+				e,
+				"Failed to restore library config file from backup",
+			))
+		})?;
+
+	let db_restored_path = node
+		.libraries
+		.libraries_dir
+		.join(format!("{library_id}.db"));
+
+	fs::copy(db_path, &db_restored_path).await.map_err(|e| {
+		FileIOError::from((
+			&db_restored_path,
+			e,
+			"Failed to restore library database file from backup",
+		))
+	})?;
+
+	node.libraries
+		.load(
+			library_id,
+			db_restored_path,
+			library_config_restored_path,
+			None,
+			true,
+			node,
+		)
+		.await?;
+
+	Ok(header)
+}
+
+/// A library restored as a new one gets its own instance, a different name and is unlinked
+/// from the cloud, as it would otherwise be mistaken for the original library.
+async fn detach_restored_library(
+	node: &Node,
+	config_path: &Path,
+	db_path: &Path,
+) -> Result<(), BackupError> {
+	let mut config = serde_json::from_slice::<serde_json::Map<String, serde_json::Value>>(
+		&fs::read(config_path)
+			.await
+			.map_err(|e| FileIOError::from((config_path, e, "Failed to read restored config")))?,
+	)?;
+
+	let original_instance_id = config
+		.get("instance_id")
+		.and_then(serde_json::Value::as_i64)
+		.and_then(|id| i32::try_from(id).ok())
+		.ok_or(BackupError::MissingInstance)?;
+
+	let db_url = format!(
+		"file:{}?socket_timeout=15&connection_limit=1",
+		db_path
+			.to_str()
+			.ok_or_else(|| LibraryManagerError::from(NonUtf8PathError(db_path.into())))?
+	);
+	let db = db::load_and_migrate(&db_url)
+		.await
+		.map_err(LibraryManagerError::from)?;
+
+	let node_config = node.config.get().await;
+	let identity = Identity::new();
+	let now = Utc::now().fixed_offset();
+
+	let instance = db
+		.instance()
+		.create(
+			Uuid::new_v4().as_bytes().to_vec(),
+			identity.to_remote_identity().get_bytes().to_vec(),
+			node_config.id.as_bytes().to_vec(),
+			now,
+			now,
+			vec![
+				instance::identity::set(Some(identity.to_bytes())),
+				instance::metadata::set(Some(
+					serde_json::to_vec(&node.p2p.peer_metadata()).expect("invalid node metadata"),
+				)),
+			],
+		)
+		.exec()
+		.await?;
+
+	// The locations of the original instance are on this node, so they're the new instance's now,
+	// and no other instance keeps its private identity, so the copy can't pass for the original
+	db._batch((
+		db.location().update_many(
+			vec![location::instance_id::equals(Some(original_instance_id))],
+			vec![location::instance_id::set(Some(instance.id))],
+		),
+		db.instance().update_many(
+			vec![instance::id::not(instance.id)],
+			vec![instance::identity::set(None)],
+		),
+	))
+	.await?;
+
+	if let Some(serde_json::Value::String(name)) = config.get_mut("name") {
+		name.push_str(" (restored)");
+	}
+	config.remove("cloud_id");
+	config.insert("instance_id".to_string(), instance.id.into());
+
+	fs::write(config_path, serde_json::to_vec(&config)?)
+		.await
+		.map_err(|e| FileIOError::from((config_path, e, "Failed to write restored config")).into())
+}
+
+/// Path of the config file of a library
+fn library_config_path(node: &Node, library_id: Uuid) -> PathBuf {
+	node.libraries
+		.libraries_dir
+		.join(format!("{library_id}.sdlibrary"))
+}
+
+/// Updates the backup settings of a library
+pub async fn update_backup_config(
+	node: &Node,
+	library: &Library,
+	backup_config: BackupConfig,
+) -> Result<(), BackupError> {
+	library
+		.update_config(
+			|config: &mut LibraryConfig| config.backup = backup_config,
+			library_config_path(node, library.id),
+		)
+		.await
+		.map_err(Into::into)
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+	use super::*;
+
+	fn header_at(timestamp: DateTime<Utc>, base_id: Option<Uuid>) -> Header {
+		Header {
+			id: Uuid::new_v4(),
+			timestamp: timestamp.timestamp_millis() as u128,
+			library_id: Uuid::nil(),
+			library_name: "Test Library".to_string(),
+			encrypted: false,
+			base_id,
+		}
+	}
+
+	fn noon() -> DateTime<Utc> {
+		DateTime::from_timestamp(1_719_576_000, 0).unwrap_or_default()
+	}
+
+	#[tokio::test]
+	async fn test_backup_header() {
+		let original = Header {
+			id: Uuid::new_v4(),
+			timestamp: 1234567890,
+			library_id: Uuid::new_v4(),
+			library_name: "Test Library".to_string(),
+			encrypted: true,
+			base_id: Some(Uuid::new_v4()),
+		};
+
+		let mut buf = Vec::new();
+		original.write(&mut buf).await.unwrap();
+
+		let decoded = Header::read(&mut buf.as_slice(), "").await.unwrap();
+		assert_eq!(original, decoded);
+	}
+
+	#[tokio::test]
+	async fn test_v1_backup_header() {
+		let id = Uuid::new_v4();
+		let library_id = Uuid::new_v4();
+
+		let mut buf = b"sdbkp1".to_vec();
+		buf.extend_from_slice(&id.to_bytes_le());
+		buf.extend_from_slice(&1234567890u128.to_le_bytes());
+		buf.extend_from_slice(&library_id.to_bytes_le());
+		buf.extend_from_slice(&4u32.to_le_bytes());
+		buf.extend_from_slice(b"Test");
+
+		let decoded = Header::read(&mut buf.as_slice(), "").await.unwrap();
+		assert_eq!(
+			decoded,
+			Header {
+				id,
+				timestamp: 1234567890,
+				library_id,
+				library_name: "Test".to_string(),
+				encrypted: false,
+				base_id: None,
+			}
+		);
+	}
+
+	#[test]
+	fn changed_pages_are_grouped_in_runs() {
+		let manifest = |pages: &[u64]| Manifest {
+			page_size: 4096,
+			db_size: 0,
+			pages: pages.to_vec(),
+		};
+
+		let base = manifest(&[1, 2, 3, 4, 5]);
+		let current = manifest(&[1, 9, 9, 4, 5, 6, 7]);
+
+		assert_eq!(current.changed_pages(&base), vec![1..3, 5..7]);
+	}
+
+	#[test]
+	fn small_change_makes_small_incremental_backup() {
+		let dir = tempdir().unwrap();
+		let db_path = dir.path().join("library.db");
+		let config_path = dir.path().join("library.sdlibrary");
+		let base_path = dir.path().join("base.db");
+		let snapshot_path = dir.path().join("snapshot.db");
+
+		std::fs::write(&config_path, b"{}").unwrap();
+
+		let db = Connection::open(&db_path).unwrap();
+		db.execute_batch(
+			"PRAGMA journal_mode = WAL;
+			CREATE TABLE file_path (id INTEGER PRIMARY KEY, name TEXT NOT NULL);
+			CREATE INDEX file_path_name ON file_path (name);
+			WITH RECURSIVE seq(n) AS (SELECT 1 UNION ALL SELECT n + 1 FROM seq WHERE n < 20000)
+			INSERT INTO file_path (name) SELECT hex(randomblob(32)) FROM seq;",
+		)
+		.unwrap();
+
+		snapshot_database(&db_path, &base_path).unwrap();
+		let base = Manifest::from_snapshot(&base_path).unwrap();
+
+		// Changes in the middle of the table, which would shift everything after them in a `VACUUM`
+		db.execute_batch(
+			"UPDATE file_path SET name = 'renamed file with a much longer name' WHERE id = 10000;
+			DELETE FROM file_path WHERE id = 10001;",
+		)
+		.unwrap();
+
+		snapshot_database(&db_path, &snapshot_path).unwrap();
+		let current = Manifest::from_snapshot(&snapshot_path).unwrap();
+		let changed_pages = current.changed_pages(&base);
+
+		let changed_count = changed_pages.iter().map(Range::len).sum::<usize>();
+		assert!(changed_count > 0);
+		assert!(changed_count <= 8, "{changed_count} pages changed");
+		assert!(current.pages.len() > 100);
+
+		let mut full = vec![];
+		write_archive(&mut full, &config_path, &snapshot_path, &current, None).unwrap();
+
+		let mut incremental = vec![];
+		write_archive(
+			&mut incremental,
+			&config_path,
+			&snapshot_path,
+			&current,
+			Some(&changed_pages),
+		)
+		.unwrap();
+
+		assert!(
+			incremental.len() * 20 < full.len(),
+			"incremental backup is {} bytes, full backup is {} bytes",
+			incremental.len(),
+			full.len()
+		);
+	}
+
+	#[test]
+	fn retention_keeps_latest_backup_of_each_day_and_week() {
+		let now = noon();
+
+		let latest = header_at(now, None);
+		let same_day = header_at(now - chrono::Duration::minutes(1), None);
+		let yesterday = header_at(now - chrono::Duration::days(1), None);
+		let last_month = header_at(now - chrono::Duration::days(30), None);
+
+		let to_delete = backups_to_delete(&[&same_day, &last_month, &latest, &yesterday], 2, 0);
+
+		assert!(to_delete.contains(&same_day.id));
+		assert!(to_delete.contains(&last_month.id));
+		assert!(!to_delete.contains(&latest.id));
+		assert!(!to_delete.contains(&yesterday.id));
+
+		let to_delete = backups_to_delete(&[&same_day, &last_month, &latest, &yesterday], 0, 10);
+		assert!(!to_delete.contains(&last_month.id));
+	}
+
+	#[test]
+	fn retention_keeps_base_of_kept_incremental_backups() {
+		let now = noon();
+
+		let full = header_at(now - chrono::Duration::days(10), None);
+		let incremental = header_at(now, Some(full.id));
+
+		assert!(backups_to_delete(&[&full, &incremental], 1, 0).is_empty());
+	}
+
+	#[test]
+	fn retention_disabled_keeps_everything() {
+		let now = noon();
+
+		let old = header_at(now - chrono::Duration::days(100), None);
+		let latest = header_at(now, None);
+
+		assert!(backups_to_delete(&[&old, &latest], 0, 0).is_empty());
+	}
+}
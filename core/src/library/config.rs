@@ -24,7 +24,7 @@ use tokio::fs;
 use tracing::error;
 use uuid::Uuid;
 
-use super::name::LibraryName;
+use super::{backup::BackupConfig, name::LibraryName, trash::TrashConfig};
 
 /// LibraryConfig holds the configuration for a specific library. This is stored as a '{uuid}.sdlibrary' file.
 #[derive(Debug, Clone, Serialize, Deserialize, Type)]
@@ -43,6 +43,12 @@ pub struct LibraryConfig {
 	// true = sync is enabled as either the library is new or it has been manually toggled on
 	#[serde(default)]
 	pub generate_sync_operations: Arc<AtomicBool>,
+	/// How and where the library is backed up.
+	#[serde(default)]
+	pub backup: BackupConfig,
+	/// How long trashed files are kept.
+	#[serde(default)]
+	pub trash: TrashConfig,
 	version: LibraryConfigVersion,
 }
 
@@ -98,6 +104,8 @@ impl LibraryConfig {
 			cloud_id: None,
 			// will always be `true` eventually
 			generate_sync_operations: Arc::new(AtomicBool::new(generate_sync_operations)),
+			backup: BackupConfig::default(),
+			trash: TrashConfig::default(),
 		};
 
 		this.save(path).await.map(|()| this)
@@ -20,7 +20,7 @@ use tokio::{fs, io, sync::broadcast, sync::RwLock};
 use tracing::warn;
 use uuid::Uuid;
 
-use super::{LibraryConfig, LibraryManagerError};
+use super::{backup::BackupKey, LibraryConfig, LibraryManagerError};
 
 // TODO: Finish this
 // pub enum LibraryNew {
@@ -42,6 +42,8 @@ pub struct Library {
 	pub cloud: cloud::State,
 	/// key manager that provides encryption keys to functions that require them
 	// pub key_manager: Arc<KeyManager>,
+	/// backup key, once unlocked with the backup passphrase
+	pub backup_key: RwLock<Option<BackupKey>>,
 	/// p2p identity
 	pub identity: Arc<Identity>,
 	// pub orphan_remover: OrphanRemoverActor,
@@ -92,6 +94,7 @@ impl Library {
 			cloud,
 			db: db.clone(),
 			// key_manager,
+			backup_key: RwLock::default(),
 			identity,
 			// orphan_remover: OrphanRemoverActor::spawn(db),
 			instance_uuid,
@@ -5,6 +5,7 @@ use crate::{
 	object::tag,
 	p2p, sync,
 	util::{mpscrr, MaybeUndefined},
+	volume::{get_volumes_cached, save_volumes},
 	Node,
 };
 
@@ -39,7 +40,10 @@ use tokio::{
 use tracing::{debug, error, info, warn};
 use uuid::Uuid;
 
-use super::{Library, LibraryConfig, LibraryName};
+use super::{
+	backup::scheduled_backups_loop, storage_statistics_loop, trash::trash_purge_loop, Library,
+	LibraryConfig, LibraryName,
+};
 
 mod error;
 
@@ -601,6 +605,22 @@ impl Libraries {
 			error!("Failed to resume jobs for library. {:#?}", e);
 		}
 
+		tokio::spawn(storage_statistics_loop(Arc::downgrade(&library)));
+		tokio::spawn(scheduled_backups_loop(
+			Arc::clone(node),
+			Arc::downgrade(&library),
+		));
+		tokio::spawn(trash_purge_loop(Arc::downgrade(&library)));
+
+		tokio::spawn({
+			let library = Arc::clone(&library);
+			async move {
+				if let Err(e) = save_volumes(&library, &get_volumes_cached().await).await {
+					error!("Failed to save volumes for library. {:#?}", e);
+				}
+			}
+		});
+
 		tokio::spawn({
 			let this = self.clone();
 			let node = node.clone();
@@ -1,9 +1,11 @@
+pub mod backup;
 mod config;
 #[allow(clippy::module_inception)]
 mod library;
 mod manager;
 mod name;
 mod statistics;
+pub mod trash;
 
 pub use config::*;
 pub use library::*;
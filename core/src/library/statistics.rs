@@ -1,11 +1,24 @@
 use crate::{api::utils::get_size, library::Library, volume::get_volumes, Node};
 
-use sd_prisma::prisma::statistics;
+use sd_file_ext::kind::ObjectKind;
+use sd_prisma::prisma::{
+	file_path, location, statistics, storage_statistics, PrismaClient, SortOrder,
+};
+use sd_utils::chain_optional_iter;
 
-use chrono::Utc;
+use std::{collections::HashMap, sync::Weak, time::Duration};
+
+use chrono::{DateTime, FixedOffset, NaiveTime, Utc};
+use prisma_client_rust::QueryError;
+use tokio::time::interval;
 
 use super::LibraryManagerError;
-use tracing::{error, info};
+use tracing::{debug, error, info};
+
+/// How many file paths are loaded at once while capturing storage statistics
+const STORAGE_STATISTICS_BATCH_SIZE: i64 = 10_000;
+/// How often we check if any location is missing today's storage statistics
+const STORAGE_STATISTICS_CHECK_INTERVAL: Duration = Duration::from_secs(60 * 60);
 
 pub async fn update_library_statistics(
 	node: &Node,
@@ -83,3 +96,189 @@ pub async fn update_library_statistics(
 
 	Ok(stats)
 }
+
+/// Decodes the big endian `u64` we store in `Bytes` columns, like `size_in_bytes_bytes`
+pub(crate) fn size_from_bytes(bytes: &[u8]) -> u64 {
+	<[u8; 8]>::try_from(bytes)
+		.map(u64::from_be_bytes)
+		.unwrap_or_else(|_| {
+			error!(
+				"Found a size in bytes with an invalid length: {}",
+				bytes.len()
+			);
+			0
+		})
+}
+
+fn today() -> DateTime<FixedOffset> {
+	Utc::now()
+		.date_naive()
+		.and_time(NaiveTime::MIN)
+		.and_utc()
+		.into()
+}
+
+/// Keeps a daily snapshot of the storage statistics of every location in the library,
+/// stopping once the library is unloaded.
+pub async fn storage_statistics_loop(library: Weak<Library>) {
+	let mut tick = interval(STORAGE_STATISTICS_CHECK_INTERVAL);
+
+	loop {
+		tick.tick().await;
+
+		let Some(library) = library.upgrade() else {
+			break;
+		};
+
+		if let Err(e) = capture_missing_storage_statistics(&library).await {
+			error!("Failed to capture storage statistics: {e:#?}");
+		}
+	}
+}
+
+/// Captures today's storage statistics for every location that doesn't have them yet.
+pub async fn capture_missing_storage_statistics(
+	library: &Library,
+) -> Result<(), LibraryManagerError> {
+	let date_captured = today();
+
+	let locations_ids = library
+		.db
+		.location()
+		.find_many(vec![location::storage_statistics::none(vec![
+			storage_statistics::date_captured::equals(date_captured),
+		])])
+		.select(location::select!({ id }))
+		.exec()
+		.await?;
+
+	for location in locations_ids {
+		capture_location_storage_statistics(&library.db, location.id, date_captured).await?;
+	}
+
+	Ok(())
+}
+
+/// Adds a file to the count and size of its kind, files without an object are of an unknown kind
+fn count_file(
+	statistics_by_kind: &mut HashMap<i32, (i32, u64)>,
+	kind: Option<i32>,
+	size_in_bytes_bytes: Option<&[u8]>,
+) {
+	let (file_count, total_bytes) = statistics_by_kind
+		.entry(kind.unwrap_or(ObjectKind::Unknown as i32))
+		.or_default();
+
+	*file_count += 1;
+	*total_bytes += size_in_bytes_bytes.map(size_from_bytes).unwrap_or(0);
+}
+
+/// Aggregates the count and size of every file in a location by kind, replacing any snapshot
+/// already captured for the same day.
+pub async fn capture_location_storage_statistics(
+	db: &PrismaClient,
+	location_id: location::id::Type,
+	date_captured: DateTime<FixedOffset>,
+) -> Result<(), QueryError> {
+	let mut statistics_by_kind = HashMap::<i32, (i32, u64)>::new();
+	let mut cursor = None;
+
+	loop {
+		let file_paths = db
+			.file_path()
+			.find_many(chain_optional_iter(
+				[
+					file_path::location_id::equals(Some(location_id)),
+					file_path::is_dir::equals(Some(false)),
+				],
+				[cursor.map(file_path::id::gt)],
+			))
+			.order_by(file_path::id::order(SortOrder::Asc))
+			.take(STORAGE_STATISTICS_BATCH_SIZE)
+			.select(file_path::select!({ id size_in_bytes_bytes object: select { kind } }))
+			.exec()
+			.await?;
+
+		let fetched_count = file_paths.len() as i64;
+		cursor = file_paths.last().map(|file_path| file_path.id);
+
+		for file_path in file_paths {
+			count_file(
+				&mut statistics_by_kind,
+				file_path.object.and_then(|object| object.kind),
+				file_path.size_in_bytes_bytes.as_deref(),
+			);
+		}
+
+		if fetched_count < STORAGE_STATISTICS_BATCH_SIZE {
+			break;
+		}
+	}
+
+	db._batch((
+		db.storage_statistics().delete_many(vec![
+			storage_statistics::location_id::equals(location_id),
+			storage_statistics::date_captured::equals(date_captured),
+		]),
+		db.storage_statistics().create_many(
+			statistics_by_kind
+				.into_iter()
+				.map(|(kind, (file_count, total_bytes))| {
+					storage_statistics::create_unchecked(
+						date_captured,
+						kind,
+						file_count,
+						total_bytes.to_be_bytes().to_vec(),
+						location_id,
+						vec![],
+					)
+				})
+				.collect(),
+		),
+	))
+	.await?;
+
+	debug!("Captured storage statistics for location <id='{location_id}'>");
+
+	Ok(())
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+	use super::*;
+
+	#[tokio::test]
+	async fn storage_statistics_loop_stops_once_the_library_is_unloaded() {
+		tokio::time::timeout(Duration::from_secs(5), storage_statistics_loop(Weak::new()))
+			.await
+			.unwrap();
+	}
+
+	#[test]
+	fn files_are_counted_by_kind() {
+		let mut statistics_by_kind = HashMap::new();
+		let image = ObjectKind::Image as i32;
+
+		count_file(
+			&mut statistics_by_kind,
+			Some(image),
+			Some(&10_u64.to_be_bytes()),
+		);
+		count_file(
+			&mut statistics_by_kind,
+			Some(image),
+			Some(&(1_u64 << 40).to_be_bytes()),
+		);
+		count_file(&mut statistics_by_kind, None, Some(&5_u64.to_be_bytes()));
+		count_file(&mut statistics_by_kind, None, None);
+
+		assert_eq!(statistics_by_kind[&image], (2, (1 << 40) + 10));
+		assert_eq!(statistics_by_kind[&(ObjectKind::Unknown as i32)], (2, 5));
+	}
+
+	#[test]
+	fn snapshots_are_captured_at_midnight() {
+		assert_eq!(today().time(), NaiveTime::MIN);
+	}
+}
@@ -0,0 +1,924 @@
+//! Library trash.
+//!
+//! Trashed file paths are moved to the `.spacedrive-trash` directory at the root of their location,
+//! laid out like a FreeDesktop trash directory: the file itself goes to `files/` and a `.trashinfo`
+//! file with its original path and deletion date goes to `info/`. The file paths are removed from the
+//! library but their objects are kept, so tags and notes are back once a file is restored.
+
+use crate::{
+	invalidate_query,
+	library::{Library, LibraryConfig, LibraryManagerError},
+	location::LocationError,
+	object::fs::{error::FileSystemJobsError, find_available_filename_for_duplicate},
+	Node,
+};
+
+use sd_core_file_path_helper::{FilePathError, FilePathMetadata, IsolatedFilePathData};
+use sd_core_prisma_helpers::{file_path_for_trash, trash_item_with_objects};
+
+use sd_prisma::{
+	prisma::{
+		file_path, label_on_object, location, object, tag_on_object, trash_item, trash_item_object,
+	},
+	prisma_sync,
+};
+use sd_sync::OperationFactory;
+use sd_utils::{
+	db::{maybe_missing, MissingFieldError},
+	error::FileIOError,
+	from_bytes_to_uuid, msgpack, uuid_to_bytes,
+};
+
+use std::{
+	collections::HashMap,
+	io,
+	path::{Path, PathBuf},
+	sync::{Arc, Weak},
+	time::Duration,
+};
+
+use chrono::{Local, Utc};
+use prisma_client_rust::QueryError;
+use rspc::ErrorCode;
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use thiserror::Error;
+use tokio::{
+	fs,
+	time::{interval, MissedTickBehavior},
+};
+use tracing::{error, trace, warn};
+use uuid::Uuid;
+
+pub use sd_core_file_path_helper::TRASH_DIRECTORY;
+
+const TRASH_FILES_DIRECTORY: &str = "files";
+const TRASH_INFO_DIRECTORY: &str = "info";
+const TRASH_INFO_EXTENSION: &str = "trashinfo";
+
+/// How often we look for trashed files older than the retention period
+const PURGE_CHECK_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// Trash settings of a library, stored in its config file.
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct TrashConfig {
+	/// Days trashed files are kept before being deleted for good, they are kept forever while this is `None`.
+	pub retention_days: Option<u32>,
+}
+
+impl Default for TrashConfig {
+	fn default() -> Self {
+		Self {
+			retention_days: Some(30),
+		}
+	}
+}
+
+#[derive(Error, Debug)]
+pub enum TrashError {
+	#[error("trash item not found: <id='{0}'>")]
+	NotFound(trash_item::id::Type),
+	#[error("location is on another device: <id='{0}'>")]
+	RemoteLocation(location::id::Type),
+	#[error("library manager error: {0}")]
+	LibraryManager(#[from] LibraryManagerError),
+	#[error("database error: {0}")]
+	Database(#[from] QueryError),
+	#[error("missing field on database: {0}")]
+	MissingField(#[from] MissingFieldError),
+
+	#[error(transparent)]
+	Location(#[from] LocationError),
+	#[error(transparent)]
+	FilePath(#[from] FilePathError),
+	#[error(transparent)]
+	FileSystemJobs(#[from] FileSystemJobsError),
+	#[error(transparent)]
+	FileIO(#[from] FileIOError),
+}
+
+impl From<TrashError> for rspc::Error {
+	fn from(e: TrashError) -> Self {
+		match e {
+			TrashError::Location(e) => e.into(),
+			TrashError::NotFound(_) => Self::with_cause(ErrorCode::NotFound, e.to_string(), e),
+			TrashError::RemoteLocation(_) => {
+				Self::with_cause(ErrorCode::BadRequest, e.to_string(), e)
+			}
+			_ => Self::with_cause(ErrorCode::InternalServerError, e.to_string(), e),
+		}
+	}
+}
+
+/// Path of the trash directory of the location at `location_path`
+pub fn trash_directory(location_path: impl AsRef<Path>) -> PathBuf {
+	location_path.as_ref().join(TRASH_DIRECTORY)
+}
+
+fn trashed_file_path(trash_directory: &Path, pub_id: Uuid) -> PathBuf {
+	trash_directory
+		.join(TRASH_FILES_DIRECTORY)
+		.join(pub_id.to_string())
+}
+
+fn trash_info_path(trash_directory: &Path, pub_id: Uuid) -> PathBuf {
+	trash_directory
+		.join(TRASH_INFO_DIRECTORY)
+		.join(format!("{pub_id}.{TRASH_INFO_EXTENSION}"))
+}
+
+/// Path of a location on this device, files on other devices can't be trashed or restored from here
+async fn local_location_path(
+	library: &Library,
+	location_id: location::id::Type,
+) -> Result<PathBuf, TrashError> {
+	let location = library
+		.db
+		.location()
+		.find_unique(location::id::equals(location_id))
+		.select(location::select!({ path instance_id }))
+		.exec()
+		.await?
+		.ok_or(LocationError::IdNotFound(location_id))?;
+
+	if location.instance_id != Some(library.config().await.instance_id) {
+		return Err(TrashError::RemoteLocation(location_id));
+	}
+
+	location
+		.path
+		.map(PathBuf::from)
+		.ok_or_else(|| LocationError::MissingPath(location_id).into())
+}
+
+/// Moves file paths of a location to its trash, along with everything inside the directories among them.
+pub async fn move_to_trash(
+	node: &Node,
+	library: &Arc<Library>,
+	location_id: location::id::Type,
+	file_path_ids: &[file_path::id::Type],
+) -> Result<(), TrashError> {
+	let location_path = local_location_path(library, location_id).await?;
+	let trash_directory = trash_directory(&location_path);
+
+	for directory in [TRASH_FILES_DIRECTORY, TRASH_INFO_DIRECTORY] {
+		let path = trash_directory.join(directory);
+		fs::create_dir_all(&path)
+			.await
+			.map_err(|e| FileIOError::from((path, e, "Failed to create trash directory")))?;
+	}
+
+	let file_paths = library
+		.db
+		.file_path()
+		.find_many(vec![
+			file_path::id::in_vec(file_path_ids.to_vec()),
+			file_path::location_id::equals(Some(location_id)),
+		])
+		.select(file_path_for_trash::select())
+		.exec()
+		.await?;
+
+	for file_path in file_paths {
+		trash_file_path(node, library, &location_path, &trash_directory, file_path).await?;
+	}
+
+	invalidate_query!(library, "search.paths");
+	invalidate_query!(library, "search.objects");
+	invalidate_query!(library, "trash.list");
+
+	Ok(())
+}
+
+async fn trash_file_path(
+	node: &Node,
+	library: &Arc<Library>,
+	location_path: &Path,
+	trash_directory: &Path,
+	file_path: file_path_for_trash::Data,
+) -> Result<(), TrashError> {
+	let Library { db, sync, .. } = &**library;
+
+	let iso_file_path = IsolatedFilePathData::try_from(&file_path)?;
+	let full_path = location_path.join(&iso_file_path);
+
+	let descendants = match iso_file_path.materialized_path_for_children() {
+		Some(children_materialized_path) if file_path.is_dir.unwrap_or(false) => db
+			.file_path()
+			.find_many(vec![
+				file_path::location_id::equals(file_path.location_id),
+				file_path::materialized_path::starts_with(children_materialized_path.clone()),
+			])
+			.select(file_path_for_trash::select())
+			.exec()
+			.await?
+			.into_iter()
+			.map(|descendant| {
+				let relative_path =
+					IsolatedFilePathData::try_from(&descendant).map(|iso_descendant| {
+						let materialized_path = descendant
+							.materialized_path
+							.as_deref()
+							.and_then(|path| path.strip_prefix(&children_materialized_path))
+							.unwrap_or_default();
+
+						format!("{materialized_path}{}", iso_descendant.full_name())
+					})?;
+
+				Ok((relative_path, descendant))
+			})
+			.collect::<Result<Vec<_>, MissingFieldError>>()?,
+		_ => vec![],
+	};
+
+	let pub_id = Uuid::new_v4();
+	let trashed_path = trashed_file_path(trash_directory, pub_id);
+	let info_path = trash_info_path(trash_directory, pub_id);
+
+	let relative_path: &Path = iso_file_path.as_ref();
+	fs::write(&info_path, trash_info(relative_path))
+		.await
+		.map_err(|e| FileIOError::from((&info_path, e, "Failed to write trash info")))?;
+
+	let _guard = node
+		.locations
+		.temporary_ignore_events_for_path(
+			iso_file_path.location_id(),
+			Arc::clone(library),
+			&full_path,
+		)
+		.await
+		.map_err(|e| warn!("Failed to ignore watcher events for trashed file: {e:#?}"))
+		.ok();
+
+	if let Err(e) = fs::rename(&full_path, &trashed_path).await {
+		remove_if_exists(&info_path).await?;
+		return Err(FileIOError::from((full_path, e, "Failed to move file to trash")).into());
+	}
+
+	let objects = [(String::new(), &file_path)]
+		.into_iter()
+		.chain(
+			descendants
+				.iter()
+				.map(|(relative_path, descendant)| (relative_path.clone(), descendant)),
+		)
+		.filter_map(|(relative_path, file_path)| {
+			file_path
+				.object_id
+				.map(|object_id| (relative_path, file_path.cas_id.clone(), object_id))
+		})
+		.collect::<Vec<_>>();
+
+	let (sync_ops, ids): (Vec<_>, Vec<_>) = [&file_path]
+		.into_iter()
+		.chain(descendants.iter().map(|(_, descendant)| descendant))
+		.map(|file_path| {
+			(
+				sync.shared_delete(prisma_sync::file_path::SyncId {
+					pub_id: file_path.pub_id.clone(),
+				}),
+				file_path.id,
+			)
+		})
+		.unzip();
+
+	let res = async {
+		let trash_item = db
+			.trash_item()
+			.create(
+				uuid_to_bytes(pub_id),
+				maybe_missing(
+					file_path.materialized_path.clone(),
+					"file_path.materialized_path",
+				)?,
+				maybe_missing(file_path.name.clone(), "file_path.name")?,
+				maybe_missing(file_path.extension.clone(), "file_path.extension")?,
+				file_path.is_dir.unwrap_or(false),
+				location::id::equals(iso_file_path.location_id()),
+				vec![trash_item::size_in_bytes_bytes::set(
+					file_path.size_in_bytes_bytes.clone(),
+				)],
+			)
+			.exec()
+			.await?;
+
+		db.trash_item_object()
+			.create_many(
+				objects
+					.into_iter()
+					.map(|(relative_path, cas_id, object_id)| {
+						trash_item_object::create_unchecked(
+							trash_item.id,
+							relative_path,
+							object_id,
+							vec![trash_item_object::cas_id::set(cas_id)],
+						)
+					})
+					.collect(),
+			)
+			.exec()
+			.await?;
+
+		sync.write_ops(
+			db,
+			(
+				sync_ops,
+				db.file_path().delete_many(vec![file_path::id::in_vec(ids)]),
+			),
+		)
+		.await?;
+
+		Ok::<_, TrashError>(())
+	}
+	.await;
+
+	if let Err(e) = res {
+		// Putting the file back, so it isn't in the trash without the library knowing about it
+		if let Err(e) = fs::rename(&trashed_path, &full_path).await {
+			error!(
+				"Failed to move {} back from the trash: {e:#?}",
+				full_path.display()
+			);
+		} else {
+			remove_if_exists(&info_path).await?;
+		}
+
+		db.trash_item()
+			.delete_many(vec![trash_item::pub_id::equals(uuid_to_bytes(pub_id))])
+			.exec()
+			.await?;
+
+		return Err(e);
+	}
+
+	trace!(
+		"Moved {} to the trash as {}",
+		full_path.display(),
+		trashed_path.display()
+	);
+
+	Ok(())
+}
+
+/// Moves trashed files back to where they were, or next to it if something else took their place,
+/// and links them to the objects they had.
+pub async fn restore(
+	node: &Node,
+	library: &Arc<Library>,
+	trash_item_ids: &[trash_item::id::Type],
+) -> Result<(), TrashError> {
+	let instance_id = library.config().await.instance_id;
+
+	for &trash_item_id in trash_item_ids {
+		let trash_item = library
+			.db
+			.trash_item()
+			.find_unique(trash_item::id::equals(trash_item_id))
+			.include(trash_item_with_objects::include())
+			.exec()
+			.await?
+			.ok_or(TrashError::NotFound(trash_item_id))?;
+
+		if trash_item.location.instance_id != Some(instance_id) {
+			return Err(TrashError::RemoteLocation(trash_item.location_id));
+		}
+
+		restore_trash_item(node, library, trash_item).await?;
+	}
+
+	invalidate_query!(library, "search.paths");
+	invalidate_query!(library, "search.objects");
+	invalidate_query!(library, "trash.list");
+
+	Ok(())
+}
+
+async fn restore_trash_item(
+	node: &Node,
+	library: &Arc<Library>,
+	trash_item: trash_item_with_objects::Data,
+) -> Result<(), TrashError> {
+	let location_id = trash_item.location_id;
+	let location_path = trash_item
+		.location
+		.path
+		.map(PathBuf::from)
+		.ok_or(LocationError::MissingPath(location_id))?;
+	let trash_directory = trash_directory(&location_path);
+	let pub_id = from_bytes_to_uuid(&trash_item.pub_id);
+	let trashed_path = trashed_file_path(&trash_directory, pub_id);
+
+	let mut target_path = location_path.join(IsolatedFilePathData::from_db_data(
+		location_id,
+		trash_item.is_dir,
+		trash_item.materialized_path.into(),
+		trash_item.name.into(),
+		trash_item.extension.into(),
+	));
+
+	match fs::symlink_metadata(&target_path).await {
+		Ok(_) => target_path = find_available_filename_for_duplicate(&target_path).await?,
+		Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+		Err(e) => return Err(FileIOError::from((target_path, e)).into()),
+	}
+
+	if let Some(parent) = target_path.parent() {
+		index_missing_directories(library, location_id, &location_path, parent).await?;
+	}
+
+	let _guard = node
+		.locations
+		.temporary_ignore_events_for_path(location_id, Arc::clone(library), &target_path)
+		.await
+		.map_err(|e| warn!("Failed to ignore watcher events for restored file: {e:#?}"))
+		.ok();
+
+	fs::rename(&trashed_path, &target_path)
+		.await
+		.map_err(|e| FileIOError::from((&trashed_path, e, "Failed to restore file from trash")))?;
+
+	let objects = trash_item
+		.objects
+		.into_iter()
+		.map(|trashed_object| {
+			(
+				trashed_object.relative_path,
+				(trashed_object.cas_id, trashed_object.object.pub_id),
+			)
+		})
+		.collect::<HashMap<_, _>>();
+
+	index_restored_files(library, location_id, &location_path, &target_path, objects).await?;
+
+	remove_if_exists(trash_info_path(&trash_directory, pub_id)).await?;
+
+	library
+		.db
+		.trash_item()
+		.delete(trash_item::id::equals(trash_item.id))
+		.exec()
+		.await?;
+
+	trace!("Restored {} from the trash", target_path.display());
+
+	Ok(())
+}
+
+/// Creates the directories between the location root and `directory` which aren't there anymore,
+/// and the file paths of those which aren't indexed.
+async fn index_missing_directories(
+	library: &Library,
+	location_id: location::id::Type,
+	location_path: &Path,
+	directory: &Path,
+) -> Result<(), TrashError> {
+	fs::create_dir_all(directory)
+		.await
+		.map_err(|e| FileIOError::from((directory, e, "Failed to create parent directory")))?;
+
+	let mut missing = vec![];
+
+	for ancestor in directory.ancestors() {
+		if ancestor == location_path || !ancestor.starts_with(location_path) {
+			break;
+		}
+
+		let iso_file_path = IsolatedFilePathData::new(location_id, location_path, ancestor, true)?;
+
+		if library
+			.db
+			.file_path()
+			.find_unique((&iso_file_path).into())
+			.select(file_path::select!({ id }))
+			.exec()
+			.await?
+			.is_some()
+		{
+			break;
+		}
+
+		missing.push((ancestor, iso_file_path));
+	}
+
+	for (path, iso_file_path) in missing.into_iter().rev() {
+		let metadata = fs::metadata(path)
+			.await
+			.map_err(|e| FileIOError::from((path, e)))?;
+
+		crate::location::create_file_path(
+			library,
+			iso_file_path.to_parts(),
+			None,
+			FilePathMetadata::from_path(path, &metadata)?,
+		)
+		.await?;
+	}
+
+	Ok(())
+}
+
+/// Indexes the restored file, or everything in the restored directory, linking each file path
+/// to the object it had before being trashed. Symbolic links are left for the next rescan.
+async fn index_restored_files(
+	library: &Library,
+	location_id: location::id::Type,
+	location_path: &Path,
+	restored_path: &Path,
+	mut objects: HashMap<String, (Option<String>, object::pub_id::Type)>,
+) -> Result<(), TrashError> {
+	let Library { db, sync, .. } = library;
+
+	let mut to_index = vec![restored_path.to_path_buf()];
+
+	while let Some(path) = to_index.pop() {
+		let metadata = fs::symlink_metadata(&path)
+			.await
+			.map_err(|e| FileIOError::from((&path, e)))?;
+
+		if metadata.is_symlink() {
+			continue;
+		}
+
+		if metadata.is_dir() {
+			let mut read_dir = fs::read_dir(&path)
+				.await
+				.map_err(|e| FileIOError::from((&path, e)))?;
+
+			while let Some(entry) = read_dir
+				.next_entry()
+				.await
+				.map_err(|e| FileIOError::from((&path, e)))?
+			{
+				to_index.push(entry.path());
+			}
+		}
+
+		let relative_path = path
+			.strip_prefix(restored_path)
+			.unwrap_or(&path)
+			.components()
+			.map(|component| component.as_os_str().to_string_lossy())
+			.collect::<Vec<_>>()
+			.join("/");
+
+		let (cas_id, object_pub_id) = objects
+			.remove(&relative_path)
+			.map_or((None, None), |(cas_id, pub_id)| (cas_id, Some(pub_id)));
+
+		let created_path = crate::location::create_file_path(
+			library,
+			IsolatedFilePathData::new(location_id, location_path, &path, metadata.is_dir())?
+				.to_parts(),
+			cas_id,
+			FilePathMetadata::from_path(&path, &metadata)?,
+		)
+		.await?;
+
+		if let Some(object_pub_id) = object_pub_id {
+			sync.write_op(
+				db,
+				sync.shared_update(
+					prisma_sync::file_path::SyncId {
+						pub_id: created_path.pub_id,
+					},
+					file_path::object::NAME,
+					msgpack!(prisma_sync::object::SyncId {
+						pub_id: object_pub_id.clone(),
+					}),
+				),
+				db.file_path().update(
+					file_path::id::equals(created_path.id),
+					vec![file_path::object::connect(object::pub_id::equals(
+						object_pub_id,
+					))],
+				),
+			)
+			.await?;
+		}
+	}
+
+	Ok(())
+}
+
+/// Deletes trashed files for good, along with the objects no other file path is linked to.
+pub async fn delete_from_trash(
+	library: &Library,
+	trash_item_ids: &[trash_item::id::Type],
+) -> Result<(), TrashError> {
+	let instance_id = library.config().await.instance_id;
+	let mut objects_ids = vec![];
+
+	for &trash_item_id in trash_item_ids {
+		let trash_item = library
+			.db
+			.trash_item()
+			.find_unique(trash_item::id::equals(trash_item_id))
+			.include(trash_item_with_objects::include())
+			.exec()
+			.await?
+			.ok_or(TrashError::NotFound(trash_item_id))?;
+
+		if trash_item.location.instance_id != Some(instance_id) {
+			return Err(TrashError::RemoteLocation(trash_item.location_id));
+		}
+
+		let location_path = trash_item
+			.location
+			.path
+			.ok_or(LocationError::MissingPath(trash_item.location_id))?;
+		let trash_directory = trash_directory(location_path);
+		let pub_id = from_bytes_to_uuid(&trash_item.pub_id);
+
+		let trashed_path = trashed_file_path(&trash_directory, pub_id);
+		match if trash_item.is_dir {
+			fs::remove_dir_all(&trashed_path).await
+		} else {
+			fs::remove_file(&trashed_path).await
+		} {
+			Ok(()) => {}
+			Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+			Err(e) => {
+				return Err(
+					FileIOError::from((trashed_path, e, "Failed to delete trashed file")).into(),
+				)
+			}
+		}
+
+		remove_if_exists(trash_info_path(&trash_directory, pub_id)).await?;
+
+		objects_ids.extend(
+			trash_item
+				.objects
+				.into_iter()
+				.map(|trashed_object| trashed_object.object.id),
+		);
+
+		library
+			.db
+			.trash_item()
+			.delete(trash_item::id::equals(trash_item.id))
+			.exec()
+			.await?;
+	}
+
+	remove_orphan_objects(library, objects_ids).await?;
+
+	invalidate_query!(library, "search.objects");
+	invalidate_query!(library, "trash.list");
+
+	Ok(())
+}
+
+/// Removes the objects among `objects_ids` which aren't linked to any file path, trashed or not
+async fn remove_orphan_objects(
+	library: &Library,
+	objects_ids: Vec<object::id::Type>,
+) -> Result<(), TrashError> {
+	let Library { db, sync, .. } = library;
+
+	if objects_ids.is_empty() {
+		return Ok(());
+	}
+
+	let orphans = db
+		.object()
+		.find_many(vec![
+			object::id::in_vec(objects_ids),
+			object::file_paths::none(vec![]),
+			object::trash_items::none(vec![]),
+		])
+		.select(object::select!({ id pub_id }))
+		.exec()
+		.await?;
+
+	if orphans.is_empty() {
+		return Ok(());
+	}
+
+	let (sync_ops, ids): (Vec<_>, Vec<_>) = orphans
+		.into_iter()
+		.map(|object| {
+			(
+				sync.shared_delete(prisma_sync::object::SyncId {
+					pub_id: object.pub_id,
+				}),
+				object.id,
+			)
+		})
+		.unzip();
+
+	db._batch((
+		db.tag_on_object()
+			.delete_many(vec![tag_on_object::object_id::in_vec(ids.clone())]),
+		db.label_on_object()
+			.delete_many(vec![label_on_object::object_id::in_vec(ids.clone())]),
+	))
+	.await?;
+
+	sync.write_ops(
+		db,
+		(
+			sync_ops,
+			db.object().delete_many(vec![object::id::in_vec(ids)]),
+		),
+	)
+	.await?;
+
+	Ok(())
+}
+
+/// Deletes everything in the trash of a location, or in the trash of every location on this device.
+pub async fn empty(
+	library: &Library,
+	location_id: Option<location::id::Type>,
+) -> Result<(), TrashError> {
+	let ids = local_trash_items(library, location_id.map(trash_item::location_id::equals)).await?;
+
+	delete_from_trash(library, &ids).await
+}
+
+/// Deletes the trashed files older than the retention period of the library
+pub async fn purge_expired(library: &Library) -> Result<(), TrashError> {
+	let Some(retention_days) = library.config().await.trash.retention_days else {
+		return Ok(());
+	};
+
+	let cutoff = Utc::now() - chrono::Duration::days(i64::from(retention_days));
+
+	let ids = local_trash_items(library, Some(trash_item::date_trashed::lt(cutoff.into()))).await?;
+
+	if !ids.is_empty() {
+		trace!("Purging {} expired items from the trash", ids.len());
+		delete_from_trash(library, &ids).await?;
+	}
+
+	Ok(())
+}
+
+async fn local_trash_items(
+	library: &Library,
+	filter: Option<trash_item::WhereParam>,
+) -> Result<Vec<trash_item::id::Type>, TrashError> {
+	let instance_id = library.config().await.instance_id;
+
+	Ok(library
+		.db
+		.trash_item()
+		.find_many(sd_utils::chain_optional_iter(
+			[trash_item::location::is(vec![
+				location::instance_id::equals(Some(instance_id)),
+			])],
+			[filter],
+		))
+		.select(trash_item::select!({ id }))
+		.exec()
+		.await?
+		.into_iter()
+		.map(|trash_item| trash_item.id)
+		.collect())
+}
+
+/// Keeps deleting trashed files once they are older than the retention period,
+/// stopping once the library is unloaded.
+pub async fn trash_purge_loop(library: Weak<Library>) {
+	let mut tick = interval(PURGE_CHECK_INTERVAL);
+	tick.set_missed_tick_behavior(MissedTickBehavior::Skip);
+
+	loop {
+		tick.tick().await;
+
+		let Some(library) = library.upgrade() else {
+			break;
+		};
+
+		if let Err(e) = purge_expired(&library).await {
+			error!("Failed to purge expired trash items: {e:#?}");
+		}
+	}
+}
+
+/// Updates the trash settings of a library
+pub async fn update_trash_config(
+	node: &Node,
+	library: &Library,
+	trash_config: TrashConfig,
+) -> Result<(), TrashError> {
+	library
+		.update_config(
+			|config: &mut LibraryConfig| config.trash = trash_config,
+			node.libraries
+				.libraries_dir
+				.join(format!("{}.sdlibrary", library.id)),
+		)
+		.await
+		.map_err(Into::into)
+}
+
+/// Finds the file path of `path` if it's indexed in a location on this device,
+/// so files browsed outside of a location still go to the library trash when they're in one.
+pub async fn find_indexed_file_path(
+	library: &Library,
+	path: impl AsRef<Path>,
+) -> Result<Option<(location::id::Type, file_path::id::Type)>, TrashError> {
+	let path = path.as_ref();
+	let instance_id = library.config().await.instance_id;
+
+	let Some((location_id, location_path)) = library
+		.db
+		.location()
+		.find_many(vec![location::instance_id::equals(Some(instance_id))])
+		.select(location::select!({ id path }))
+		.exec()
+		.await?
+		.into_iter()
+		.filter_map(|location| location.path.map(|path| (location.id, PathBuf::from(path))))
+		.filter(|(_, location_path)| path.starts_with(location_path) && path != location_path)
+		// Nested locations aren't allowed, but the deepest one is the right one if there are any
+		.max_by_key(|(_, location_path)| location_path.components().count())
+	else {
+		return Ok(None);
+	};
+
+	let metadata = match fs::symlink_metadata(path).await {
+		Ok(metadata) => metadata,
+		Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+		Err(e) => return Err(FileIOError::from((path, e)).into()),
+	};
+
+	let iso_file_path =
+		IsolatedFilePathData::new(location_id, &location_path, path, metadata.is_dir())?;
+
+	Ok(library
+		.db
+		.file_path()
+		.find_unique((&iso_file_path).into())
+		.select(file_path::select!({ id }))
+		.exec()
+		.await?
+		.map(|file_path| (location_id, file_path.id)))
+}
+
+async fn remove_if_exists(path: impl AsRef<Path>) -> Result<(), FileIOError> {
+	let path = path.as_ref();
+
+	match fs::remove_file(path).await {
+		Ok(()) => Ok(()),
+		Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(()),
+		Err(e) => Err(FileIOError::from((path, e))),
+	}
+}
+
+/// Contents of the `.trashinfo` file of a trashed file, following the FreeDesktop trash specification.
+/// The path is relative to the location, like it is for trash directories at the top of a volume.
+fn trash_info(relative_path: &Path) -> String {
+	format!(
+		"[Trash Info]\nPath={}\nDeletionDate={}\n",
+		escape_trash_info_path(&relative_path.to_string_lossy()),
+		Local::now().format("%Y-%m-%dT%H:%M:%S")
+	)
+}
+
+/// Percent encodes everything but unreserved characters and path separators, as the specification requires
+fn escape_trash_info_path(path: &str) -> String {
+	let mut escaped = String::with_capacity(path.len());
+
+	for byte in path.bytes() {
+		match byte {
+			b'a'..=b'z' | b'A'..=b'Z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' | b'/' => {
+				escaped.push(char::from(byte));
+			}
+			#[cfg(target_family = "windows")]
+			b'\\' => escaped.push('/'),
+			_ => escaped.push_str(&format!("%{byte:02X}")),
+		}
+	}
+
+	escaped
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn escapes_trash_info_path() {
+		assert_eq!(escape_trash_info_path("photos/cat.jpg"), "photos/cat.jpg");
+		assert_eq!(
+			escape_trash_info_path("my photos/café #1.jpg"),
+			"my%20photos/caf%C3%A9%20%231.jpg"
+		);
+	}
+
+	#[test]
+	fn trash_info_has_path_and_date() {
+		let info = trash_info(Path::new("docs/report 2024.pdf"));
+		let mut lines = info.lines();
+
+		assert_eq!(lines.next(), Some("[Trash Info]"));
+		assert_eq!(lines.next(), Some("Path=docs/report%202024.pdf"));
+		assert!(lines
+			.next()
+			.is_some_and(|line| line.starts_with("DeletionDate=") && line.len() == 32));
+	}
+}
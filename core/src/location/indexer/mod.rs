@@ -135,6 +135,29 @@ async fn execute_indexer_save_step(
 					sync_db_entry!(v, date_indexed)
 				},
 				sync_db_entry!(entry.metadata.hidden, hidden),
+				(
+					(
+						symlink_target::NAME,
+						msgpack!(entry.metadata.symlink_target),
+					),
+					symlink_target::set(entry.metadata.symlink_target.clone()),
+				),
+				(
+					(permissions::NAME, msgpack!(entry.metadata.permissions)),
+					permissions::set(entry.metadata.permissions.clone()),
+				),
+				(
+					(owner::NAME, msgpack!(entry.metadata.owner)),
+					owner::set(entry.metadata.owner.clone()),
+				),
+				(
+					(owner_group::NAME, msgpack!(entry.metadata.owner_group)),
+					owner_group::set(entry.metadata.owner_group.clone()),
+				),
+				(
+					(xattrs::NAME, msgpack!(entry.metadata.xattrs)),
+					xattrs::set(entry.metadata.xattrs.clone()),
+				),
 			]
 			.into_iter()
 			.unzip();
@@ -210,6 +233,29 @@ async fn execute_indexer_update_step(
 					sync_db_entry!(v, date_modified)
 				}),
 				Some(sync_db_entry!(entry.metadata.hidden, hidden)),
+				Some((
+					(
+						symlink_target::NAME,
+						msgpack!(entry.metadata.symlink_target),
+					),
+					symlink_target::set(entry.metadata.symlink_target.clone()),
+				)),
+				Some((
+					(permissions::NAME, msgpack!(entry.metadata.permissions)),
+					permissions::set(entry.metadata.permissions.clone()),
+				)),
+				Some((
+					(owner::NAME, msgpack!(entry.metadata.owner)),
+					owner::set(entry.metadata.owner.clone()),
+				)),
+				Some((
+					(owner_group::NAME, msgpack!(entry.metadata.owner_group)),
+					owner_group::set(entry.metadata.owner_group.clone()),
+				)),
+				Some((
+					(xattrs::NAME, msgpack!(entry.metadata.xattrs)),
+					xattrs::set(entry.metadata.xattrs.clone()),
+				)),
 			]
 			.into_iter()
 			.flatten()
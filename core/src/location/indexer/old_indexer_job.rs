@@ -13,7 +13,7 @@ use sd_core_file_path_helper::{
 	ensure_file_path_exists, ensure_sub_path_is_directory, ensure_sub_path_is_in_location,
 	IsolatedFilePathData,
 };
-use sd_core_indexer_rules::IndexerRule;
+use sd_core_indexer_rules::{symlink::SymlinkPolicy, IndexerRule};
 
 use sd_prisma::{
 	prisma::{file_path, location},
@@ -211,6 +211,7 @@ impl StatefulJob for OldIndexerJobInit {
 			&location_path,
 			&to_walk_path,
 			&indexer_rules,
+			SymlinkPolicy::from(init.location.symlink_policy),
 			update_notifier_fn(ctx),
 			file_paths_db_fetcher_fn!(&db),
 			to_remove_db_fetcher_fn!(location_id, &db),
@@ -400,6 +401,7 @@ impl StatefulJob for OldIndexerJobInit {
 					location_path,
 					to_walk_entry,
 					&data.indexer_rules,
+					SymlinkPolicy::from(init.location.symlink_policy),
 					update_notifier_fn(ctx),
 					file_paths_db_fetcher_fn!(&db),
 					to_remove_db_fetcher_fn!(location_id, &db),
@@ -15,7 +15,7 @@ use sd_core_file_path_helper::{
 	check_file_path_exists, ensure_sub_path_is_directory, ensure_sub_path_is_in_location,
 	IsolatedFilePathData,
 };
-use sd_core_indexer_rules::IndexerRule;
+use sd_core_indexer_rules::{symlink::SymlinkPolicy, IndexerRule};
 
 use sd_utils::db::maybe_missing;
 
@@ -82,6 +82,7 @@ pub async fn old_shallow(
 			location_path,
 			&to_walk_path,
 			&indexer_rules,
+			SymlinkPolicy::from(location.symlink_policy),
 			file_paths_db_fetcher_fn!(&db),
 			to_remove_db_fetcher_fn!(location_id, &db),
 			iso_file_path_factory(location_id, location_path),
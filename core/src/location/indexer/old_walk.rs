@@ -1,6 +1,7 @@
-use sd_core_file_path_helper::{FilePathMetadata, IsolatedFilePathData};
+use sd_core_file_path_helper::{FilePathMetadata, IsolatedFilePathData, TRASH_DIRECTORY};
 use sd_core_indexer_rules::{
 	seed::{GitIgnoreRules, GITIGNORE},
+	symlink::{resolve_symlink, SymlinkPolicy, SymlinkResolution},
 	IndexerRule, RuleKind,
 };
 use sd_core_prisma_helpers::{file_path_pub_and_cas_ids, file_path_walker};
@@ -43,6 +44,9 @@ pub struct ToWalkEntry {
 	path: PathBuf,
 	parent_dir_accepted_by_its_children: Option<bool>,
 	maybe_parent: Option<PathBuf>,
+	/// Canonical targets of the directory symlinks followed to get to this path
+	#[serde(default)]
+	followed_symlinks: Vec<PathBuf>,
 }
 
 #[derive(Debug)]
@@ -122,6 +126,7 @@ pub(super) async fn walk<FilePathDBFetcherFut, ToRemoveDbFetcherFut>(
 	library_root: impl AsRef<Path>,
 	current_dir: impl AsRef<Path>,
 	indexer_rules: &[IndexerRule],
+	symlink_policy: SymlinkPolicy,
 	mut update_notifier: impl FnMut(&Path, usize),
 	file_paths_db_fetcher: impl Fn(Vec<file_path::WhereParam>) -> FilePathDBFetcherFut,
 	to_remove_db_fetcher: impl Fn(
@@ -150,6 +155,7 @@ where
 		path: current_dir.to_path_buf(),
 		parent_dir_accepted_by_its_children: None,
 		maybe_parent: None,
+		followed_symlinks: vec![],
 	});
 	let mut indexed_paths = HashSet::with_capacity(WALKER_PATHS_BUFFER_INITIAL_CAPACITY);
 	let mut errors = vec![];
@@ -165,6 +171,7 @@ where
 			current_dir,
 			&entry,
 			indexer_rules,
+			symlink_policy,
 			&to_remove_db_fetcher,
 			&iso_file_path_factory,
 			WorkingTable {
@@ -208,6 +215,7 @@ pub(super) async fn keep_walking<FilePathDBFetcherFut, ToRemoveDbFetcherFut>(
 	location_path: impl AsRef<Path>,
 	to_walk_entry: &ToWalkEntry,
 	indexer_rules: &[IndexerRule],
+	symlink_policy: SymlinkPolicy,
 	mut update_notifier: impl FnMut(&Path, usize),
 	file_paths_db_fetcher: impl Fn(Vec<file_path::WhereParam>) -> FilePathDBFetcherFut,
 	to_remove_db_fetcher: impl Fn(
@@ -238,6 +246,7 @@ where
 		to_walk_entry.path.clone(),
 		to_walk_entry,
 		indexer_rules,
+		symlink_policy,
 		&to_remove_db_fetcher,
 		&iso_file_path_factory,
 		WorkingTable {
@@ -276,6 +285,7 @@ pub(super) async fn walk_single_dir<FilePathDBFetcherFut, ToRemoveDbFetcherFut>(
 	location_path: impl AsRef<Path>,
 	current_dir: impl AsRef<Path>,
 	indexer_rules: &[IndexerRule],
+	symlink_policy: SymlinkPolicy,
 	file_paths_db_fetcher: impl Fn(Vec<file_path::WhereParam>) -> FilePathDBFetcherFut,
 	to_remove_db_fetcher: impl Fn(
 		IsolatedFilePathData<'static>,
@@ -323,8 +333,10 @@ where
 			path: current_directory.to_path_buf(),
 			parent_dir_accepted_by_its_children: None,
 			maybe_parent: None,
+			followed_symlinks: vec![],
 		},
 		indexer_rules,
+		symlink_policy,
 		&to_remove_db_fetcher,
 		&iso_file_path_factory,
 		WorkingTable {
@@ -392,6 +404,11 @@ where
 								// instead of using != operator
 								|| DateTime::<FixedOffset>::from(metadata.modified_at) - *date_modified
 									> Duration::milliseconds(1) || file_path.hidden.is_none() || metadata.hidden != file_path.hidden.unwrap_or_default()
+								// Permissions, ownership and extended attributes don't touch the modification date
+								|| metadata.permissions != file_path.permissions
+								|| metadata.owner != file_path.owner
+								|| metadata.owner_group != file_path.owner_group
+								|| metadata.xattrs != file_path.xattrs
 							)
 							// We ignore the size of directories because it is not reliable, we need to
 							// calculate it ourselves later
@@ -445,9 +462,11 @@ async fn inner_walk_single_dir<ToRemoveDbFetcherFut>(
 	ToWalkEntry {
 		path,
 		parent_dir_accepted_by_its_children,
+		followed_symlinks,
 		..
 	}: &ToWalkEntry,
 	indexer_rules: &[IndexerRule],
+	symlink_policy: SymlinkPolicy,
 	to_remove_db_fetcher: impl Fn(
 		IsolatedFilePathData<'static>,
 		Vec<file_path::WhereParam>,
@@ -502,6 +521,11 @@ where
 			}
 		};
 
+		// The trash of the location is never indexed, whatever its rules are
+		if entry.file_name() == TRASH_DIRECTORY {
+			continue 'entries;
+		}
+
 		// Accept by children has three states,
 		// None if we don't now yet or if this check doesn't apply
 		// Some(true) if this check applies and it passes
@@ -542,7 +566,7 @@ where
 			}
 		}
 
-		let Ok(metadata) = entry
+		let Ok(mut metadata) = entry
 			.metadata()
 			.await
 			.map_err(|e| errors.push(FileIOError::from((&current_path, e)).into()))
@@ -550,9 +574,40 @@ where
 			continue 'entries;
 		};
 
-		// TODO: Hard ignoring symlinks for now, but this should be configurable
+		let mut children_followed_symlinks = None;
+		let mut followed_link_metadata = None;
+
 		if metadata.is_symlink() {
-			continue 'entries;
+			match resolve_symlink(
+				&current_path,
+				symlink_policy,
+				library_root.as_ref(),
+				followed_symlinks,
+			)
+			.await
+			{
+				Ok(SymlinkResolution::Skip) => continue 'entries,
+				// Aliases keep the metadata of the link itself, so they're never walked into
+				Ok(SymlinkResolution::Alias) => {}
+				Ok(SymlinkResolution::Follow {
+					metadata: target_metadata,
+					canonical_target,
+				}) => {
+					followed_link_metadata =
+						Some(std::mem::replace(&mut metadata, target_metadata));
+					children_followed_symlinks = Some(
+						followed_symlinks
+							.iter()
+							.cloned()
+							.chain([canonical_target])
+							.collect(),
+					);
+				}
+				Err(e) => {
+					errors.push(e.into());
+					continue 'entries;
+				}
+			}
 		}
 
 		let is_dir = metadata.is_dir();
@@ -603,6 +658,8 @@ where
 					path: current_path.clone(),
 					parent_dir_accepted_by_its_children: accept_by_children_dir,
 					maybe_parent: Some(path.clone()),
+					followed_symlinks: children_followed_symlinks
+						.unwrap_or_else(|| followed_symlinks.clone()),
 				});
 			}
 		}
@@ -626,7 +683,19 @@ where
 				continue 'entries;
 			};
 
-			let Ok(metadata) = FilePathMetadata::from_path(&current_path, &metadata)
+			// Followed links keep their own inode, otherwise they would collide with their targets
+			let Ok(metadata) = followed_link_metadata
+				.as_ref()
+				.map_or_else(
+					|| FilePathMetadata::from_path(&current_path, &metadata),
+					|link_metadata| {
+						FilePathMetadata::from_followed_symlink(
+							&current_path,
+							link_metadata,
+							&metadata,
+						)
+					},
+				)
 				.map_err(|e| errors.push(e.into()))
 			else {
 				continue;
@@ -820,6 +889,11 @@ mod tests {
 			created_at: Utc::now(),
 			modified_at: Utc::now(),
 			hidden: false,
+			symlink_target: None,
+			permissions: None,
+			owner: None,
+			owner_group: None,
+			xattrs: None,
 		};
 
 		let f = |path, is_dir| IsolatedFilePathData::new(0, root_path, path, is_dir).unwrap();
@@ -828,28 +902,28 @@ mod tests {
 
 		#[rustfmt::skip]
 		let expected = [
-			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("rust_project"), true), metadata },
-			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("rust_project/.git"), true), metadata },
-			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("rust_project/Cargo.toml"), false), metadata },
-			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("rust_project/src"), true), metadata },
-			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("rust_project/src/main.rs"), false), metadata },
-			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("rust_project/target"), true), metadata },
-			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("rust_project/target/debug"), true), metadata },
-			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("rust_project/target/debug/main"), false), metadata },
-			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("inner"), true), metadata },
-			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("inner/node_project"), true), metadata },
-			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("inner/node_project/.git"), true), metadata },
-			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("inner/node_project/package.json"), false), metadata },
-			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("inner/node_project/src"), true), metadata },
-			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("inner/node_project/src/App.tsx"), false), metadata },
-			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("inner/node_project/node_modules"), true), metadata },
-			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("inner/node_project/node_modules/react"), true), metadata },
-			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("inner/node_project/node_modules/react/package.json"), false), metadata },
-			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("photos"), true), metadata },
-			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("photos/photo1.png"), false), metadata },
-			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("photos/photo2.jpg"), false), metadata },
-			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("photos/photo3.jpeg"), false), metadata },
-			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("photos/text.txt"), false), metadata },
+			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("rust_project"), true), metadata: metadata.clone() },
+			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("rust_project/.git"), true), metadata: metadata.clone() },
+			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("rust_project/Cargo.toml"), false), metadata: metadata.clone() },
+			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("rust_project/src"), true), metadata: metadata.clone() },
+			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("rust_project/src/main.rs"), false), metadata: metadata.clone() },
+			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("rust_project/target"), true), metadata: metadata.clone() },
+			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("rust_project/target/debug"), true), metadata: metadata.clone() },
+			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("rust_project/target/debug/main"), false), metadata: metadata.clone() },
+			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("inner"), true), metadata: metadata.clone() },
+			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("inner/node_project"), true), metadata: metadata.clone() },
+			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("inner/node_project/.git"), true), metadata: metadata.clone() },
+			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("inner/node_project/package.json"), false), metadata: metadata.clone() },
+			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("inner/node_project/src"), true), metadata: metadata.clone() },
+			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("inner/node_project/src/App.tsx"), false), metadata: metadata.clone() },
+			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("inner/node_project/node_modules"), true), metadata: metadata.clone() },
+			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("inner/node_project/node_modules/react"), true), metadata: metadata.clone() },
+			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("inner/node_project/node_modules/react/package.json"), false), metadata: metadata.clone() },
+			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("photos"), true), metadata: metadata.clone() },
+			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("photos/photo1.png"), false), metadata: metadata.clone() },
+			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("photos/photo2.jpg"), false), metadata: metadata.clone() },
+			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("photos/photo3.jpeg"), false), metadata: metadata.clone() },
+			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("photos/text.txt"), false), metadata: metadata.clone() },
 		]
 		.into_iter()
 		.collect::<HashSet<_>>();
@@ -858,6 +932,7 @@ mod tests {
 			root_path.to_path_buf(),
 			root_path.to_path_buf(),
 			&mut [],
+			SymlinkPolicy::Ignore,
 			|_, _| {},
 			|_| async { Ok(vec![]) },
 			|_, _| async { Ok(vec![]) },
@@ -892,6 +967,11 @@ mod tests {
 			created_at: Utc::now(),
 			modified_at: Utc::now(),
 			hidden: false,
+			symlink_target: None,
+			permissions: None,
+			owner: None,
+			owner_group: None,
+			xattrs: None,
 		};
 
 		let f = |path, is_dir| IsolatedFilePathData::new(0, root_path, path, is_dir).unwrap();
@@ -900,10 +980,10 @@ mod tests {
 
 		#[rustfmt::skip]
 		let expected = [
-			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("photos"), true), metadata },
-			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("photos/photo1.png"), false), metadata },
-			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("photos/photo2.jpg"), false), metadata },
-			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("photos/photo3.jpeg"), false), metadata },
+			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("photos"), true), metadata: metadata.clone() },
+			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("photos/photo1.png"), false), metadata: metadata.clone() },
+			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("photos/photo2.jpg"), false), metadata: metadata.clone() },
+			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("photos/photo3.jpeg"), false), metadata: metadata.clone() },
 		]
 		.into_iter()
 		.collect::<HashSet<_>>();
@@ -924,6 +1004,7 @@ mod tests {
 			root_path.to_path_buf(),
 			root_path.to_path_buf(),
 			&mut only_photos_rule,
+			SymlinkPolicy::Ignore,
 			|_, _| {},
 			|_| async { Ok(vec![]) },
 			|_, _| async { Ok(vec![]) },
@@ -958,6 +1039,11 @@ mod tests {
 			created_at: Utc::now(),
 			modified_at: Utc::now(),
 			hidden: false,
+			symlink_target: None,
+			permissions: None,
+			owner: None,
+			owner_group: None,
+			xattrs: None,
 		};
 
 		let f = |path, is_dir| IsolatedFilePathData::new(0, root_path, path, is_dir).unwrap();
@@ -966,23 +1052,23 @@ mod tests {
 
 		#[rustfmt::skip]
 		let expected = [
-			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("rust_project"), true), metadata },
-			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("rust_project/.git"), true), metadata },
-			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("rust_project/Cargo.toml"), false), metadata },
-			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("rust_project/src"), true), metadata },
-			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("rust_project/src/main.rs"), false), metadata },
-			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("rust_project/target/"), true), metadata },
-			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("rust_project/target/debug"), true), metadata },
-			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("rust_project/target/debug/main"), false), metadata },
-			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("inner"), true), metadata },
-			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("inner/node_project"), true), metadata },
-			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("inner/node_project/.git"), true), metadata },
-			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("inner/node_project/package.json"), false), metadata },
-			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("inner/node_project/src"), true), metadata },
-			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("inner/node_project/src/App.tsx"), false), metadata },
-			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("inner/node_project/node_modules"), true), metadata },
-			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("inner/node_project/node_modules/react"), true), metadata },
-			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("inner/node_project/node_modules/react/package.json"), false), metadata },
+			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("rust_project"), true), metadata: metadata.clone() },
+			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("rust_project/.git"), true), metadata: metadata.clone() },
+			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("rust_project/Cargo.toml"), false), metadata: metadata.clone() },
+			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("rust_project/src"), true), metadata: metadata.clone() },
+			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("rust_project/src/main.rs"), false), metadata: metadata.clone() },
+			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("rust_project/target/"), true), metadata: metadata.clone() },
+			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("rust_project/target/debug"), true), metadata: metadata.clone() },
+			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("rust_project/target/debug/main"), false), metadata: metadata.clone() },
+			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("inner"), true), metadata: metadata.clone() },
+			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("inner/node_project"), true), metadata: metadata.clone() },
+			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("inner/node_project/.git"), true), metadata: metadata.clone() },
+			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("inner/node_project/package.json"), false), metadata: metadata.clone() },
+			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("inner/node_project/src"), true), metadata: metadata.clone() },
+			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("inner/node_project/src/App.tsx"), false), metadata: metadata.clone() },
+			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("inner/node_project/node_modules"), true), metadata: metadata.clone() },
+			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("inner/node_project/node_modules/react"), true), metadata: metadata.clone() },
+			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("inner/node_project/node_modules/react/package.json"), false), metadata: metadata.clone() },
 		]
 		.into_iter()
 		.collect::<HashSet<_>>();
@@ -999,6 +1085,7 @@ mod tests {
 			root_path.to_path_buf(),
 			root_path.to_path_buf(),
 			&mut git_repos,
+			SymlinkPolicy::Ignore,
 			|_, _| {},
 			|_| async { Ok(vec![]) },
 			|_, _| async { Ok(vec![]) },
@@ -1035,6 +1122,11 @@ mod tests {
 			created_at: Utc::now(),
 			modified_at: Utc::now(),
 			hidden: false,
+			symlink_target: None,
+			permissions: None,
+			owner: None,
+			owner_group: None,
+			xattrs: None,
 		};
 
 		let f = |path, is_dir| IsolatedFilePathData::new(0, root_path, path, is_dir).unwrap();
@@ -1043,17 +1135,17 @@ mod tests {
 
 		#[rustfmt::skip]
 		let expected = [
-			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("rust_project"), true), metadata },
-			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("rust_project/.git"), true), metadata },
-			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("rust_project/Cargo.toml"), false), metadata },
-			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("rust_project/src"), true), metadata },
-			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("rust_project/src/main.rs"), false), metadata },
-			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("inner"), true), metadata },
-			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("inner/node_project"), true), metadata },
-			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("inner/node_project/.git"), true), metadata },
-			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("inner/node_project/package.json"), false), metadata },
-			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("inner/node_project/src"), true), metadata },
-			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("inner/node_project/src/App.tsx"), false), metadata },
+			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("rust_project"), true), metadata: metadata.clone() },
+			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("rust_project/.git"), true), metadata: metadata.clone() },
+			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("rust_project/Cargo.toml"), false), metadata: metadata.clone() },
+			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("rust_project/src"), true), metadata: metadata.clone() },
+			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("rust_project/src/main.rs"), false), metadata: metadata.clone() },
+			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("inner"), true), metadata: metadata.clone() },
+			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("inner/node_project"), true), metadata: metadata.clone() },
+			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("inner/node_project/.git"), true), metadata: metadata.clone() },
+			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("inner/node_project/package.json"), false), metadata: metadata.clone() },
+			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("inner/node_project/src"), true), metadata: metadata.clone() },
+			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("inner/node_project/src/App.tsx"), false), metadata: metadata.clone() },
 		]
 		.into_iter()
 		.collect::<HashSet<_>>();
@@ -1094,6 +1186,7 @@ mod tests {
 			root_path.to_path_buf(),
 			root_path.to_path_buf(),
 			&mut git_repos_no_deps_no_build_dirs,
+			SymlinkPolicy::Ignore,
 			|_, _| {},
 			|_| async { Ok(vec![]) },
 			|_, _| async { Ok(vec![]) },
@@ -1,20 +1,23 @@
 use crate::{
 	library::{Library, LibraryId},
+	location::{relink_moved_location, scan_location, ScanState},
+	volume::get_volumes_cached,
 	Node,
 };
 
-use sd_prisma::prisma::location;
+use sd_core_prisma_helpers::location_with_indexer_rules;
+use sd_prisma::prisma::{location, volume};
 use sd_utils::db::maybe_missing;
 
 use std::{
 	collections::{HashMap, HashSet},
-	path::{Path, PathBuf},
+	path::{Component, Path, PathBuf},
 	sync::Arc,
 	time::Duration,
 };
 
 use tokio::{fs, io::ErrorKind, sync::oneshot, time::sleep};
-use tracing::{error, warn};
+use tracing::{debug, error, warn};
 use uuid::Uuid;
 
 use super::{watcher::LocationWatcher, LocationManagerError};
@@ -32,8 +35,13 @@ pub(super) async fn check_online(
 
 	let location_path = maybe_missing(&location.path, "location.path").map(Path::new)?;
 
-	// TODO(N): This isn't gonna work with removable media and this will likely permanently break if the DB is restored from a backup.
+	// TODO(N): This will likely permanently break if the DB is restored from a backup.
 	if location.instance_id == Some(library.config().await.instance_id) {
+		if !is_volume_mounted(location, library).await? {
+			node.locations.remove_online(&pub_id).await;
+			return Ok(false);
+		}
+
 		match fs::metadata(&location_path).await {
 			Ok(_) => {
 				node.locations.add_online(pub_id).await;
@@ -55,6 +63,34 @@ pub(super) async fn check_online(
 	}
 }
 
+/// When a removable volume is unplugged, its mount point directory may still exist or even be
+/// reused by another volume, so for locations linked to a volume we also check that the same
+/// volume is still mounted.
+async fn is_volume_mounted(
+	location: &location::Data,
+	library: &Library,
+) -> Result<bool, LocationManagerError> {
+	let Some(volume_id) = location.volume_id else {
+		return Ok(true);
+	};
+
+	let Some(volume) = library
+		.db
+		.volume()
+		.find_unique(volume::id::equals(volume_id))
+		.select(volume::select!({ identifier }))
+		.exec()
+		.await?
+	else {
+		return Ok(true);
+	};
+
+	Ok(get_volumes_cached()
+		.await
+		.iter()
+		.any(|mounted| mounted.stable_id() == volume.identifier))
+}
+
 pub(super) async fn location_check_sleep(
 	location_id: location::id::Type,
 	library: Arc<Library>,
@@ -63,6 +99,113 @@ pub(super) async fn location_check_sleep(
 	(location_id, library)
 }
 
+pub(super) async fn rescan_reconnected_location(
+	location_id: location::id::Type,
+	node: Arc<Node>,
+	library: Arc<Library>,
+) {
+	let location = match library
+		.db
+		.location()
+		.find_unique(location::id::equals(location_id))
+		.include(location_with_indexer_rules::include())
+		.exec()
+		.await
+	{
+		Ok(Some(location)) => location,
+		Ok(None) => return,
+		Err(e) => {
+			error!("Failed to fetch reconnected location <id='{location_id}'>: {e:#?}");
+			return;
+		}
+	};
+
+	let scan_state = match ScanState::try_from(location.scan_state) {
+		Ok(scan_state) => scan_state,
+		Err(e) => {
+			error!("Invalid scan state for reconnected location <id='{location_id}'>: {e:#?}");
+			return;
+		}
+	};
+
+	// Anything anywhere in the location may have changed while it was offline, so a location that
+	// was fully scanned is scanned again in full, while one that went offline mid scan resumes it
+	debug!("Location <id='{location_id}'> is back online, scanning it with state: {scan_state:?}");
+
+	if let Err(e) = scan_location(&node, &library, location, scan_state).await {
+		error!("Failed to scan reconnected location <id='{location_id}'>: {e:#?}");
+	}
+}
+
+/// When the volume of an offline location is mounted somewhere else, which happens a lot with
+/// removable drives on Linux, looks for the location under the new mount point and relinks it.
+pub(super) async fn relink_from_remounted_volume(
+	location: location::Data,
+	node: Arc<Node>,
+	library: Arc<Library>,
+) {
+	let (Some(volume_id), Some(location_path)) = (location.volume_id, location.path) else {
+		return;
+	};
+
+	let volume = match library
+		.db
+		.volume()
+		.find_unique(volume::id::equals(volume_id))
+		.select(volume::select!({ identifier }))
+		.exec()
+		.await
+	{
+		Ok(Some(volume)) => volume,
+		Ok(None) => return,
+		Err(e) => {
+			error!(
+				"Failed to fetch volume of location <id='{}'>: {e:#?}",
+				location.id
+			);
+			return;
+		}
+	};
+
+	let volumes = get_volumes_cached().await;
+	let Some(mounted_volume) = volumes
+		.iter()
+		.find(|mounted| mounted.stable_id() == volume.identifier)
+	else {
+		return;
+	};
+
+	let location_path = PathBuf::from(location_path);
+
+	// The volume is still mounted where the location was, so the location itself is gone
+	if mounted_volume.mount_point_for(&location_path).is_some() {
+		return;
+	}
+
+	// We don't know where the volume used to be mounted, so we try every suffix of the location
+	// path under the new mount points, like `/media/USB/photos` becoming `/run/media/user/USB/photos`
+	let components = location_path
+		.components()
+		.filter(|component| matches!(component, Component::Normal(_)))
+		.collect::<Vec<_>>();
+
+	for mount_point in &mounted_volume.mount_points {
+		for start in 0..=components.len() {
+			let candidate = mount_point.join(components[start..].iter().collect::<PathBuf>());
+
+			match relink_moved_location(&node, &library, &candidate).await {
+				Ok(Some(_)) => return,
+				Ok(None) => {}
+				Err(e) => debug!(
+					"Failed to relink location <id='{}'> at {}: {e:#?}",
+					location.id,
+					candidate.display()
+				),
+			}
+		}
+	}
+}
+
 pub(super) fn watch_location(
 	location: location::Data,
 	library_id: LibraryId,
@@ -357,7 +357,8 @@ impl Locations {
 		use helpers::{
 			check_online, drop_location, get_location, handle_ignore_path_request,
 			handle_reinit_watcher_request, handle_remove_location_request,
-			handle_stop_watcher_request, location_check_sleep, unwatch_location, watch_location,
+			handle_stop_watcher_request, location_check_sleep, relink_from_remounted_volume,
+			rescan_reconnected_location, unwatch_location, watch_location,
 		};
 		use watcher::LocationWatcher;
 
@@ -505,6 +506,16 @@ impl Locations {
 							if is_online
 								&& !forced_unwatch.contains(&key)
 							{
+								if locations_unwatched.contains_key(&key) {
+									// The location was offline, probably because its volume was unplugged,
+									// so we rescan it to pick up whatever changed while we weren't watching
+									tokio::spawn(rescan_reconnected_location(
+										location_id,
+										node.clone(),
+										library.clone(),
+									));
+								}
+
 								watch_location(
 									location,
 									library.id,
@@ -512,6 +523,14 @@ impl Locations {
 									&mut locations_unwatched,
 								);
 							} else {
+								if !is_online && location.volume_id.is_some() {
+									tokio::spawn(relink_from_remounted_volume(
+										location.clone(),
+										node.clone(),
+										library.clone(),
+									));
+								}
+
 								unwatch_location(
 									location,
 									library.id,
@@ -1,10 +1,11 @@
 use crate::{
 	invalidate_query,
-	library::Library,
+	library::{trash::TRASH_DIRECTORY, Library},
 	location::{
 		create_file_path, delete_directory, find_location,
 		indexer::reverse_update_directories_sizes, location_with_indexer_rules,
-		manager::LocationManagerError, scan_location_sub_path, update_location_size,
+		manager::LocationManagerError, relink_moved_location, scan_location_sub_path,
+		update_location_size,
 	},
 	object::{
 		media::{
@@ -25,9 +26,10 @@ use crate::{
 use sd_core_file_path_helper::{
 	check_file_path_exists, filter_existing_file_path_params,
 	isolated_file_path_data::extract_normalized_materialized_path_str,
-	loose_find_existing_file_path_params, path_is_hidden, FilePathError, FilePathMetadata,
-	IsolatedFilePathData, MetadataExt,
+	loose_find_existing_file_path_params, path_is_hidden, ExtendedMetadata, FilePathError,
+	FilePathMetadata, IsolatedFilePathData, MetadataExt,
 };
+use sd_core_indexer_rules::symlink::{resolve_symlink, SymlinkPolicy, SymlinkResolution};
 use sd_core_prisma_helpers::file_path_with_object;
 
 use sd_file_ext::{
@@ -74,11 +76,13 @@ use uuid::Uuid;
 use super::{INode, HUNDRED_MILLIS};
 
 pub(super) fn check_event(event: &Event, ignore_paths: &HashSet<PathBuf>) -> bool {
-	// if path includes .DS_Store, .spacedrive file creation or is in the `ignore_paths` set, we ignore
+	// if path includes .DS_Store, .spacedrive file creation, is in the trash or is in the `ignore_paths` set, we ignore
 	!event.paths.iter().any(|p| {
 		p.file_name()
 			.and_then(OsStr::to_str)
 			.map_or(false, |name| name == ".DS_Store" || name == ".spacedrive")
+			|| p.components()
+				.any(|component| component.as_os_str() == TRASH_DIRECTORY)
 			|| ignore_paths.contains(p)
 	})
 }
@@ -100,6 +104,22 @@ pub(super) async fn create_dir(
 
 	let location_path = maybe_missing(&location.path, "location.path")?;
 
+	let Some(metadata) = resolve_watched_symlink(
+		path,
+		metadata,
+		location_path,
+		SymlinkPolicy::from(location.symlink_policy),
+	)
+	.await?
+	else {
+		return Ok(());
+	};
+
+	// A link to a directory indexed as an alias is just a file for us
+	if !metadata.is_dir() {
+		return inner_create_file(location_id, location_path, path, &metadata, node, library).await;
+	}
+
 	trace!(
 		"Location: <root_path ='{}'> creating directory: {}",
 		location_path,
@@ -129,10 +149,25 @@ pub(super) async fn create_dir(
 		library,
 		iso_file_path.to_parts(),
 		None,
-		FilePathMetadata::from_path(path, metadata)?,
+		watched_file_path_metadata(path, &metadata).await?,
 	)
 	.await?;
 
+	// A directory moved in here may be one of our locations that was moved from somewhere else
+	tokio::spawn({
+		let node = Arc::clone(node);
+		let library = Arc::clone(library);
+		let path = path.to_path_buf();
+		async move {
+			if let Err(e) = relink_moved_location(&node, &library, &path).await {
+				error!(
+					"Failed to relink location found at {}: {e:#?}",
+					path.display()
+				);
+			}
+		}
+	});
+
 	// scan the new directory
 	scan_location_sub_path(node, library, location, &children_materialized_path).await?;
 
@@ -176,6 +211,34 @@ async fn inner_create_file(
 	let path = path.as_ref();
 	let location_path = location_path.as_ref();
 
+	let metadata = if fs::symlink_metadata(path)
+		.await
+		.map_err(|e| FileIOError::from((path, e)))?
+		.is_symlink()
+	{
+		let symlink_policy = find_location(library, location_id)
+			.select(location::select!({ symlink_policy }))
+			.exec()
+			.await?
+			.ok_or(LocationManagerError::MissingLocation(location_id))?
+			.symlink_policy;
+
+		let Some(metadata) = resolve_watched_symlink(
+			path,
+			metadata,
+			location_path,
+			SymlinkPolicy::from(symlink_policy),
+		)
+		.await?
+		else {
+			return Ok(());
+		};
+
+		metadata
+	} else {
+		metadata.clone()
+	};
+
 	trace!(
 		"Location: <root_path ='{}'> creating file: {}",
 		location_path.display(),
@@ -186,7 +249,8 @@ async fn inner_create_file(
 	let iso_file_path_parts = iso_file_path.to_parts();
 	let extension = iso_file_path_parts.extension.to_string();
 
-	let metadata = FilePathMetadata::from_path(path, metadata)?;
+	let metadata = watched_file_path_metadata(path, &metadata).await?;
+	let is_alias = metadata.symlink_target.is_some();
 
 	// First we check if already exist a file with this same inode number
 	// if it does, we just update it
@@ -244,7 +308,11 @@ async fn inner_create_file(
 		cas_id,
 		kind,
 		fs_metadata,
-	} = FileMetadata::new(&location_path, &iso_file_path).await?;
+	} = if is_alias {
+		FileMetadata::new_alias(&location_path, &iso_file_path).await?
+	} else {
+		FileMetadata::new(&location_path, &iso_file_path).await?
+	};
 
 	debug!("Creating path: {}", iso_file_path);
 
@@ -322,28 +390,31 @@ async fn inner_create_file(
 	if !extension.is_empty()
 		&& matches!(
 			kind,
-			ObjectKind::Image | ObjectKind::Video | ObjectKind::Audio
+			ObjectKind::Image
+				| ObjectKind::Video
+				| ObjectKind::Audio
+				| ObjectKind::Font
+				| ObjectKind::Mesh
+				| ObjectKind::Book
 		) {
 		// Running in a detached task as thumbnail generation can take a while and we don't want to block the watcher
-		if matches!(kind, ObjectKind::Image | ObjectKind::Video) {
-			if let Some(cas_id) = cas_id {
-				spawn({
-					let extension = extension.clone();
-					let path = path.to_path_buf();
-					let node = node.clone();
-					let library_id = *library_id;
-
-					async move {
-						if let Err(e) = node
-							.thumbnailer
-							.generate_single_indexed_thumbnail(&extension, cas_id, path, library_id)
-							.await
-						{
-							error!("Failed to generate thumbnail in the watcher: {e:#?}");
-						}
+		if let Some(cas_id) = cas_id {
+			spawn({
+				let extension = extension.clone();
+				let path = path.to_path_buf();
+				let node = node.clone();
+				let library_id = *library_id;
+
+				async move {
+					if let Err(e) = node
+						.thumbnailer
+						.generate_single_indexed_thumbnail(&extension, cas_id, path, library_id)
+						.await
+					{
+						error!("Failed to generate thumbnail in the watcher: {e:#?}");
 					}
-				});
-			}
+				}
+			});
 		}
 
 		match kind {
@@ -496,14 +567,23 @@ async fn inner_update_file(
 		cas_id,
 		fs_metadata,
 		kind,
-	} = FileMetadata::new(&location_path, &iso_file_path).await?;
+	} = if file_path.symlink_target.is_some() {
+		FileMetadata::new_alias(&location_path, &iso_file_path).await?
+	} else {
+		FileMetadata::new(&location_path, &iso_file_path).await?
+	};
 
 	let inode = if let Some(inode) = maybe_new_inode {
 		inode
 	} else {
 		#[cfg(target_family = "unix")]
 		{
-			get_inode(&fs_metadata)
+			// Followed links are read through, but they keep their own inode
+			get_inode(
+				&fs::symlink_metadata(full_path)
+					.await
+					.map_err(|e| FileIOError::from((full_path, e)))?,
+			)
 		}
 
 		#[cfg(target_family = "windows")]
@@ -820,6 +900,68 @@ async fn inner_update_file(
 		invalidate_query!(library, "search.paths");
 	}
 
+	let extended = ExtendedMetadata::from_path(full_path, &fs_metadata);
+	let (sync_params, db_params): (Vec<_>, Vec<_>) = {
+		use file_path::*;
+
+		[
+			(file_path.permissions != extended.permissions).then(|| {
+				(
+					(permissions::NAME, msgpack!(extended.permissions)),
+					permissions::set(extended.permissions.clone()),
+				)
+			}),
+			(file_path.owner != extended.owner).then(|| {
+				(
+					(owner::NAME, msgpack!(extended.owner)),
+					owner::set(extended.owner.clone()),
+				)
+			}),
+			(file_path.owner_group != extended.owner_group).then(|| {
+				(
+					(owner_group::NAME, msgpack!(extended.owner_group)),
+					owner_group::set(extended.owner_group.clone()),
+				)
+			}),
+			(file_path.xattrs != extended.xattrs).then(|| {
+				(
+					(xattrs::NAME, msgpack!(extended.xattrs)),
+					xattrs::set(extended.xattrs.clone()),
+				)
+			}),
+		]
+		.into_iter()
+		.flatten()
+		.unzip()
+	};
+
+	if !sync_params.is_empty() {
+		sync.write_ops(
+			db,
+			(
+				sync_params
+					.into_iter()
+					.map(|(field, value)| {
+						sync.shared_update(
+							prisma_sync::file_path::SyncId {
+								pub_id: file_path.pub_id.clone(),
+							},
+							field,
+							value,
+						)
+					})
+					.collect(),
+				db.file_path().update(
+					file_path::pub_id::equals(file_path.pub_id.clone()),
+					db_params,
+				),
+			),
+		)
+		.await?;
+
+		invalidate_query!(library, "search.paths");
+	}
+
 	Ok(())
 }
 
@@ -1045,6 +1187,8 @@ pub(super) async fn remove_by_file_path(
 							object::id::equals(object_id),
 							// https://www.prisma.io/docs/reference/api-reference/prisma-client-reference#none
 							object::file_paths::none(vec![]),
+							// Objects of trashed files are kept until the trash is emptied
+							object::trash_items::none(vec![]),
 						])
 						.exec()
 						.await?;
@@ -1095,6 +1239,50 @@ pub(super) async fn extract_inode_from_path(
 		)
 }
 
+/// Applies the symlink policy of the location to a path the watcher is about to index, giving back the
+/// metadata to index it with or `None` if it must be skipped. `metadata` is used as is for anything but symlinks.
+async fn resolve_watched_symlink(
+	path: &Path,
+	metadata: &Metadata,
+	location_path: impl AsRef<Path> + Send,
+	symlink_policy: SymlinkPolicy,
+) -> Result<Option<Metadata>, LocationManagerError> {
+	let link_metadata = fs::symlink_metadata(path)
+		.await
+		.map_err(|e| FileIOError::from((path, e)))?;
+
+	if !link_metadata.is_symlink() {
+		return Ok(Some(metadata.clone()));
+	}
+
+	// The watcher doesn't know which links were followed to get here,
+	// but links to any of the directories we're in are still caught
+	Ok(
+		match resolve_symlink(path, symlink_policy, location_path, &[]).await? {
+			SymlinkResolution::Skip => None,
+			SymlinkResolution::Alias => Some(link_metadata),
+			SymlinkResolution::Follow { metadata, .. } => Some(metadata),
+		},
+	)
+}
+
+/// The [`FilePathMetadata`] of a path indexed with the `metadata` given by [`resolve_watched_symlink`],
+/// followed links keep their own inode so they aren't taken for their targets.
+async fn watched_file_path_metadata(
+	path: &Path,
+	metadata: &Metadata,
+) -> Result<FilePathMetadata, LocationManagerError> {
+	let link_metadata = fs::symlink_metadata(path)
+		.await
+		.map_err(|e| FileIOError::from((path, e)))?;
+
+	Ok(if link_metadata.is_symlink() && !metadata.is_symlink() {
+		FilePathMetadata::from_followed_symlink(path, &link_metadata, metadata)?
+	} else {
+		FilePathMetadata::from_path(path, metadata)?
+	})
+}
+
 pub(super) async fn extract_location_path(
 	location_id: location::id::Type,
 	library: &Library,
@@ -17,7 +17,7 @@ use uuid::Uuid;
 
 use super::LocationPubId;
 
-static SPACEDRIVE_LOCATION_METADATA_FILE: &str = ".spacedrive";
+pub static SPACEDRIVE_LOCATION_METADATA_FILE: &str = ".spacedrive";
 
 #[derive(Serialize, Deserialize, Default, Debug)]
 struct LocationMetadata {
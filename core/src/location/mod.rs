@@ -1,17 +1,21 @@
 use crate::{
+	api::notifications::{NotificationData, NotificationKind},
 	invalidate_query,
 	library::Library,
 	object::{
 		media::{old_media_processor, OldMediaProcessorJobInit},
 		old_file_identifier::{self, old_file_identifier_job::OldFileIdentifierJobInit},
+		tag::desktop::{import_location_desktop_tags, DesktopTagsPolicy},
 	},
 	old_job::{JobBuilder, JobError, JobManagerError},
+	volume::{get_volumes_cached, save_volumes},
 	Node,
 };
 
 use sd_core_file_path_helper::{
 	filter_existing_file_path_params, IsolatedFilePathData, IsolatedFilePathDataParts,
 };
+use sd_core_indexer_rules::symlink::SymlinkPolicy;
 use sd_core_prisma_helpers::location_with_indexer_rules;
 
 use sd_prisma::{
@@ -28,12 +32,13 @@ use sd_utils::{
 use std::{
 	collections::HashSet,
 	path::{Component, Path, PathBuf},
-	sync::Arc,
+	sync::{Arc, Mutex, PoisonError},
 };
 
 use chrono::Utc;
 use futures::future::TryFutureExt;
 use normpath::PathExt;
+use once_cell::sync::Lazy;
 use prisma_client_rust::{operator::and, or, QueryError};
 use serde::{Deserialize, Serialize};
 use serde_json::json;
@@ -299,6 +304,8 @@ pub struct LocationUpdateArgs {
 	hidden: Option<bool>,
 	indexer_rules_ids: Vec<i32>,
 	path: Option<String>,
+	symlink_policy: Option<SymlinkPolicy>,
+	desktop_tags_policy: Option<DesktopTagsPolicy>,
 }
 
 impl LocationUpdateArgs {
@@ -346,6 +353,20 @@ impl LocationUpdateArgs {
 					location::path::set(Some(v)),
 				)
 			}),
+			self.symlink_policy.map(|v| {
+				let v = v as i32;
+				(
+					(location::symlink_policy::NAME, msgpack!(v)),
+					location::symlink_policy::set(Some(v)),
+				)
+			}),
+			self.desktop_tags_policy.map(|v| {
+				let v = v as i32;
+				(
+					(location::desktop_tags_policy::NAME, msgpack!(v)),
+					location::desktop_tags_policy::set(Some(v)),
+				)
+			}),
 		]
 		.into_iter()
 		.flatten()
@@ -390,6 +411,16 @@ impl LocationUpdateArgs {
 				node.locations.remove(self.id, library.clone()).await?;
 				node.locations.add(self.id, library.clone()).await?;
 			}
+
+			// Files identified before the location opted in won't go through the identifier again
+			if DesktopTagsPolicy::from(location.desktop_tags_policy) == DesktopTagsPolicy::Ignore
+				&& self
+					.desktop_tags_policy
+					.is_some_and(|policy| policy != DesktopTagsPolicy::Ignore)
+			{
+				import_location_desktop_tags(library, self.id).await?;
+				invalidate_query!(library, "tags.list");
+			}
 		}
 
 		let current_rules_ids = location
@@ -648,6 +679,165 @@ pub async fn relink_location(
 	Ok(location_id.id)
 }
 
+/// Relinks offered by [`offer_moved_location_relink`], so each one is only brought up once
+static OFFERED_RELINKS: Lazy<Mutex<HashSet<(Uuid, location::id::Type, PathBuf)>>> =
+	Lazy::new(Default::default);
+
+/// A location of this library whose metadata file was found away from its path
+struct MovedLocation {
+	id: location::id::Type,
+	name: Option<String>,
+	previous_path: Option<String>,
+}
+
+/// Checks if the directory at `path` holds the metadata file of a location from this library that
+/// was moved there, like when a drive gets mounted somewhere else, and its previous path is gone.
+async fn find_moved_location(
+	library: &Library,
+	path: &Path,
+) -> Result<Option<MovedLocation>, LocationError> {
+	let Some(metadata) = SpacedriveLocationMetadataFile::try_load(path).await? else {
+		return Ok(None);
+	};
+
+	if !metadata.has_library(library.id) {
+		return Ok(None);
+	}
+
+	let location_pub_id = metadata.location_pub_id(library.id)?;
+
+	let Some(location) = library
+		.db
+		.location()
+		.find_unique(location::pub_id::equals(
+			location_pub_id.as_bytes().to_vec(),
+		))
+		.select(location::select!({ id name path instance_id }))
+		.exec()
+		.await?
+	else {
+		return Ok(None);
+	};
+
+	if location.instance_id != Some(library.config().await.instance_id) {
+		return Ok(None);
+	}
+
+	if let Some(previous_path) = &location.path {
+		if Path::new(previous_path) == path {
+			return Ok(None);
+		}
+
+		// If the location is still where we left it, we found a copy of it instead
+		if matches!(
+			SpacedriveLocationMetadataFile::try_load(previous_path).await,
+			Ok(Some(previous_metadata))
+				if previous_metadata.location_pub_id(library.id).ok() == Some(location_pub_id)
+		) {
+			debug!(
+				"Found a copy of location <id='{}'> at {}, ignoring it",
+				location.id,
+				path.display()
+			);
+			return Ok(None);
+		}
+	}
+
+	Ok(Some(MovedLocation {
+		id: location.id,
+		name: location.name,
+		previous_path: location.path,
+	}))
+}
+
+/// Relinks a location of this library found at `path`, if it was moved there (see
+/// [`find_moved_location`]). As file paths are stored relative to their location, no reindexing
+/// is needed.
+///
+/// Returns the id of the relinked location, if any.
+pub async fn relink_moved_location(
+	node: &Arc<Node>,
+	library: &Arc<Library>,
+	path: impl AsRef<Path>,
+) -> Result<Option<location::id::Type>, LocationError> {
+	let path = path.as_ref();
+
+	let Some(location) = find_moved_location(library, path).await? else {
+		return Ok(None);
+	};
+
+	let location_id = relink_location(library, path).await?;
+
+	info!(
+		"Location <id='{location_id}'> was moved from {:?} to {}, relinked it",
+		location.previous_path,
+		path.display()
+	);
+
+	// The watcher is still looking at the previous path, so we restart it
+	node.locations
+		.remove(location_id, Arc::clone(library))
+		.await?;
+	node.locations.add(location_id, Arc::clone(library)).await?;
+
+	invalidate_query!(library, "locations.list");
+
+	node.emit_notification(
+		NotificationData {
+			title: "Location relinked".to_string(),
+			content: format!(
+				"\"{}\" was found at {} and relinked",
+				location.name.unwrap_or_default(),
+				path.display()
+			),
+			kind: NotificationKind::Info,
+		},
+		None,
+	)
+	.await;
+
+	Ok(Some(location_id))
+}
+
+/// Like [`relink_moved_location`], but only offers the user to relink the location, as just
+/// browsing a directory can't tell a moved location from a copy of it, like a backup drive
+/// plugged in while the original one isn't.
+pub async fn offer_moved_location_relink(
+	node: &Node,
+	library: &Library,
+	path: impl AsRef<Path>,
+) -> Result<(), LocationError> {
+	let path = path.as_ref();
+
+	let Some(location) = find_moved_location(library, path).await? else {
+		return Ok(());
+	};
+
+	if !OFFERED_RELINKS
+		.lock()
+		.unwrap_or_else(PoisonError::into_inner)
+		.insert((library.id, location.id, path.to_path_buf()))
+	{
+		return Ok(());
+	}
+
+	node.emit_notification(
+		NotificationData {
+			title: "Location found".to_string(),
+			content: format!(
+				"\"{}\" seems to have been moved to {}, add it as a location to relink it",
+				location.name.unwrap_or_default(),
+				path.display()
+			),
+			kind: NotificationKind::Info,
+		},
+		None,
+	)
+	.await;
+
+	Ok(())
+}
+
 #[derive(Debug)]
 pub struct CreatedLocationResult {
 	pub name: String,
@@ -779,6 +969,11 @@ async fn create_location(
 		link_location_and_indexer_rules(library, location.id, indexer_rules_ids).await?;
 	}
 
+	// Also links the new location to the volume it lives in
+	if let Err(e) = save_volumes(library, &get_volumes_cached().await).await {
+		error!("Failed to link new location to its volume: {e:#?}");
+	}
+
 	// Updating our location variable to include information about the indexer rules
 	let location = find_location(library, location.id)
 		.include(location_with_indexer_rules::include())
@@ -1129,6 +1324,26 @@ pub async fn create_file_path(
 				(hidden::NAME, msgpack!(metadata.hidden)),
 				hidden::set(Some(metadata.hidden)),
 			),
+			(
+				(symlink_target::NAME, msgpack!(metadata.symlink_target)),
+				symlink_target::set(metadata.symlink_target),
+			),
+			(
+				(permissions::NAME, msgpack!(metadata.permissions)),
+				permissions::set(metadata.permissions),
+			),
+			(
+				(owner::NAME, msgpack!(metadata.owner)),
+				owner::set(metadata.owner),
+			),
+			(
+				(owner_group::NAME, msgpack!(metadata.owner_group)),
+				owner_group::set(metadata.owner_group),
+			),
+			(
+				(xattrs::NAME, msgpack!(metadata.xattrs)),
+				xattrs::set(metadata.xattrs),
+			),
 		]
 		.into_iter()
 		.unzip()
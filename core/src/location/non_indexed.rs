@@ -37,7 +37,9 @@ use tokio::{io, sync::mpsc, task::JoinError};
 use tokio_stream::wrappers::ReceiverStream;
 use tracing::{error, span, warn, Level};
 
-use super::normalize_path;
+use super::{
+	metadata::SPACEDRIVE_LOCATION_METADATA_FILE, normalize_path, offer_moved_location_relink,
+};
 
 #[derive(Debug, Error)]
 pub enum NonIndexedLocationError {
@@ -110,6 +112,27 @@ pub async fn walk(
 > {
 	let mut entries = get_all_entries(path.clone()).await?;
 
+	// Browsing a directory with a metadata file from this library means one of our locations might
+	// have been moved here, so we offer to relink it
+	if entries
+		.iter()
+		.any(|entry| entry.name == SPACEDRIVE_LOCATION_METADATA_FILE)
+	{
+		tokio::spawn({
+			let node = Arc::clone(&node);
+			let library = Arc::clone(&library);
+			let path = path.clone();
+			async move {
+				if let Err(e) = offer_moved_location_relink(&node, &library, &path).await {
+					error!(
+						"Failed to check location found at {}: {e:#?}",
+						path.display()
+					);
+				}
+			}
+		});
+	}
+
 	{
 		let span = span!(Level::INFO, "sort_fn");
 		let _enter = span.enter();
@@ -129,7 +152,7 @@ pub async fn walk(
 		);
 
 		let mut thumbnails_to_generate = vec![];
-		// Generating thumbnails for PDFs is kinda slow, so we're leaving them for last in the batch
+		// Generating thumbnails for PDFs, meshes and ebooks is kinda slow, so we're leaving them for last in the batch
 		let mut document_thumbnails_to_generate = vec![];
 		let mut directories = vec![];
 
@@ -189,13 +212,21 @@ pub async fn walk(
 					{
 						matches!(
 							kind,
-							ObjectKind::Image | ObjectKind::Video | ObjectKind::Document
+							ObjectKind::Image
+								| ObjectKind::Video | ObjectKind::Audio
+								| ObjectKind::Document | ObjectKind::Font
+								| ObjectKind::Mesh | ObjectKind::Book
 						)
 					}
 
 					#[cfg(not(feature = "ffmpeg"))]
 					{
-						matches!(kind, ObjectKind::Image | ObjectKind::Document)
+						matches!(
+							kind,
+							ObjectKind::Image
+								| ObjectKind::Document | ObjectKind::Font
+								| ObjectKind::Mesh | ObjectKind::Book
+						)
 					}
 				};
 
@@ -208,7 +239,10 @@ pub async fn walk(
 									NonIndexedLocationError::from((path, e)).into(),
 								)))
 							}) {
-						if kind == ObjectKind::Document {
+						if matches!(
+							kind,
+							ObjectKind::Document | ObjectKind::Mesh | ObjectKind::Book
+						) {
 							document_thumbnails_to_generate.push(GenerateThumbnailArgs::new(
 								extension.clone(),
 								cas_id.clone(),
@@ -1,6 +1,7 @@
 use crate::{
 	api::{notifications::Notification, BackendFeature},
 	object::media::old_thumbnail::preferences::ThumbnailerPreferences,
+	p2p::RemoteAccessGrant,
 	util::version_manager::{Kind, ManagedVersion, VersionManager, VersionManagerError},
 };
 
@@ -84,6 +85,9 @@ pub struct NodeConfigP2P {
 	pub disable_relay: bool,
 	#[serde(default, skip_serializing_if = "skip_if_false")]
 	pub enable_remote_access: bool,
+	/// The remote nodes allowed to use this node's rspc router and custom URIs, and what they are allowed to do.
+	#[serde(default, skip_serializing_if = "Vec::is_empty")]
+	pub remote_access: Vec<RemoteAccessGrant>,
 	/// A list of peer addresses to try and manually connect to, instead of relying on discovery.
 	///
 	/// All of these are valid values:
@@ -94,6 +98,10 @@ pub struct NodeConfigP2P {
 	/// which is why we use `String` not `SocketAddr`
 	#[serde(default)]
 	pub manual_peers: HashSet<String>,
+	/// A directory shared with other nodes (like a network share) where nodes publish signed records of their addresses
+	/// to find each other, for networks where mDNS doesn't work.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub rendezvous_dir: Option<PathBuf>,
 }
 
 impl Default for NodeConfigP2P {
@@ -105,11 +113,22 @@ impl Default for NodeConfigP2P {
 			disable_ipv6: true,
 			disable_relay: true,
 			enable_remote_access: false,
+			remote_access: Default::default(),
 			manual_peers: Default::default(),
+			rendezvous_dir: None,
 		}
 	}
 }
 
+/// The shared secret of a self-hosted relay, which the node presents to the relay's `POST /authorize` endpoint
+/// each time it pulls the relay configuration, well within how long the relay keeps the node authorized for.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RelayAuthorization {
+	/// Base URL the relay's HTTP endpoints are reachable at, must be `https` so the secret isn't sent in the clear
+	pub url: String,
+	pub secret: String,
+}
+
 /// NodeConfig is the configuration for a node. This is shared between all libraries and is stored in a JSON file on disk.
 #[derive(Debug, Clone, Serialize, Deserialize)] // If you are adding `specta::Type` on this your probably about to leak the P2P private key
 pub struct NodeConfig {
@@ -127,6 +146,9 @@ pub struct NodeConfig {
 	/// P2P config
 	#[serde(default)]
 	pub p2p: NodeConfigP2P,
+	/// Self-hosted relays which require a shared secret, kept out of [`NodeConfigP2P`] as it's sent to the frontend
+	#[serde(default, skip_serializing_if = "Vec::is_empty")]
+	pub relay_authorizations: Vec<RelayAuthorization>,
 	/// Feature flags enabled on the node
 	#[serde(default)]
 	pub features: Vec<BackendFeature>,
@@ -209,6 +231,7 @@ impl ManagedVersion<NodeConfigVersion> for NodeConfig {
 			name,
 			identity: Identity::default(),
 			p2p: NodeConfigP2P::default(),
+			relay_authorizations: vec![],
 			version: Self::LATEST_VERSION,
 			features: vec![],
 			notifications: vec![],
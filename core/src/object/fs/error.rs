@@ -2,7 +2,7 @@ use crate::location::LocationError;
 
 use sd_core_file_path_helper::FilePathError;
 
-use sd_prisma::prisma::file_path;
+use sd_prisma::prisma::{file_path, location};
 use sd_utils::{
 	db::MissingFieldError,
 	error::{FileIOError, NonUtf8PathError},
@@ -42,6 +42,8 @@ pub enum FileSystemJobsError {
 	NonUTF8Path(#[from] NonUtf8PathError),
 	#[error("failed to find an available name to avoid duplication: <path='{}'>", .0.display())]
 	FailedToFindAvailableName(Box<Path>),
+	#[error("location is on another device: <id='{0}'>")]
+	RemoteLocation(location::id::Type),
 }
 
 impl From<FileSystemJobsError> for rspc::Error {
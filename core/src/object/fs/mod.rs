@@ -19,10 +19,11 @@ use regex::Regex;
 use serde::{Deserialize, Serialize};
 
 pub mod old_delete;
-pub mod old_erase;
 
 pub mod old_copy;
 pub mod old_cut;
+pub mod old_dedup;
+pub mod reflink;
 
 // pub mod decrypt;
 // pub mod encrypt;
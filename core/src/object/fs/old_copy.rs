@@ -24,7 +24,7 @@ use tracing::{trace, warn};
 use super::{
 	construct_target_filename, error::FileSystemJobsError, fetch_source_and_target_location_paths,
 	find_available_filename_for_duplicate, get_file_data_from_isolated_file_path,
-	get_many_files_datas, FileData,
+	get_many_files_datas, reflink::copy_file, FileData,
 };
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -187,7 +187,7 @@ impl StatefulJob for OldFileCopierJobInit {
 					// Already exist a file with this name, so we need to find an available name
 					match find_available_filename_for_duplicate(target_full_path).await {
 						Ok(new_path) => {
-							fs::copy(&source_file_data.full_path, &new_path)
+							copy_file(&source_file_data.full_path, &new_path)
 								.await
 								// Using the ? here because we don't want to increase the completed task
 								// count in case of file system errors
@@ -213,7 +213,7 @@ impl StatefulJob for OldFileCopierJobInit {
 						target_full_path.display()
 					);
 
-					fs::copy(&source_file_data.full_path, &target_full_path)
+					copy_file(&source_file_data.full_path, &target_full_path)
 						.await
 						// Using the ? here because we don't want to increase the completed task
 						// count in case of file system errors
@@ -0,0 +1,544 @@
+use crate::{
+	library::Library,
+	location::LocationError,
+	object::validation::hash::file_checksum,
+	old_job::{
+		CurrentStep, JobError, JobInitOutput, JobResult, JobRunErrors, JobRunMetadata,
+		JobStepOutput, StatefulJob, WorkerContext,
+	},
+};
+
+use sd_core_file_path_helper::{get_inode_from_path, IsolatedFilePathData};
+use sd_core_prisma_helpers::file_path_for_dedup;
+
+use sd_prisma::{
+	prisma::{file_path, location, SortOrder},
+	prisma_sync,
+};
+use sd_sync::OperationFactory;
+use sd_utils::{db::inode_to_db, error::FileIOError, msgpack};
+
+use std::{
+	collections::HashMap,
+	ffi::OsString,
+	fs::Metadata,
+	hash::Hash,
+	path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use specta::Type;
+use tokio::{fs, io};
+use tracing::{info, trace, warn};
+
+use super::{error::FileSystemJobsError, reflink::reflink};
+
+/// How a duplicated file is replaced.
+#[derive(Serialize, Deserialize, Hash, Type, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DedupMethod {
+	/// The copies share their data blocks but stay independent files, needs Btrfs or XFS
+	Reflink,
+	/// The copies become the same file, so changing one of them changes all of them
+	Hardlink,
+}
+
+#[derive(Serialize, Deserialize, Hash, Type, Debug)]
+pub struct OldFileDeduplicatorJobInit {
+	pub location_id: location::id::Type,
+	pub method: DedupMethod,
+	/// Only report what would be deduplicated, without touching any file
+	pub dry_run: bool,
+}
+
+/// Paths of the same object, the first one is kept and the others are replaced by links to it.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct DuplicatedFiles {
+	files: Vec<DuplicatedFile>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct DuplicatedFile {
+	pub_id: file_path::pub_id::Type,
+	location_id: location::id::Type,
+	full_path: PathBuf,
+	integrity_checksum: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Default, Debug)]
+pub struct OldFileDeduplicatorJobRunMetadata {
+	/// Files replaced by a link, or which would be on a dry run
+	deduplicated: Vec<DeduplicatedFile>,
+	/// Files with an identical copy which were left alone
+	skipped: Vec<SkippedFile>,
+	reclaimed_bytes: u64,
+}
+
+impl JobRunMetadata for OldFileDeduplicatorJobRunMetadata {
+	fn update(&mut self, new_data: Self) {
+		self.deduplicated.extend(new_data.deduplicated);
+		self.skipped.extend(new_data.skipped);
+		self.reclaimed_bytes += new_data.reclaimed_bytes;
+	}
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct DeduplicatedFile {
+	path: PathBuf,
+	linked_to: PathBuf,
+	size: u64,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct SkippedFile {
+	path: PathBuf,
+	reason: SkipReason,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+pub enum SkipReason {
+	/// The full checksum doesn't match, the sampled one the object was identified by does
+	ContentsDiffer,
+	/// Links can't cross filesystems
+	OtherFilesystem,
+	/// Both paths are hard links to the same file already
+	AlreadyLinked,
+	/// The index identifies files by inode within a location, so two paths of a location can't share one
+	HardlinkWithinLocation,
+	/// A hard link would change the permissions or the owner seen through this path
+	DifferentOwnership,
+	/// The file changed after we verified it
+	Modified,
+	/// The filesystem can't share data blocks between files
+	ReflinkUnsupported,
+}
+
+/// A file whose contents we just checksummed.
+struct VerifiedFile<'file> {
+	file: &'file DuplicatedFile,
+	metadata: Metadata,
+	checksum: String,
+}
+
+#[async_trait::async_trait]
+impl StatefulJob for OldFileDeduplicatorJobInit {
+	type Data = ();
+	type Step = DuplicatedFiles;
+	type RunMetadata = OldFileDeduplicatorJobRunMetadata;
+
+	const NAME: &'static str = "file_deduplicator";
+
+	fn target_location(&self) -> location::id::Type {
+		self.location_id
+	}
+
+	async fn init(
+		&self,
+		ctx: &WorkerContext,
+		data: &mut Option<Self::Data>,
+	) -> Result<JobInitOutput<Self::RunMetadata, Self::Step>, JobError> {
+		let init = self;
+		let Library { db, .. } = &*ctx.library;
+
+		let location = db
+			.location()
+			.find_unique(location::id::equals(init.location_id))
+			.exec()
+			.await?
+			.ok_or(LocationError::IdNotFound(init.location_id))?;
+
+		let instance_id = ctx.library.config().await.instance_id;
+		if location.instance_id != Some(instance_id) {
+			return Err(FileSystemJobsError::RemoteLocation(init.location_id).into());
+		}
+
+		// Links can't cross filesystems, so copies are only looked for in the locations on the same volume
+		let location_paths = db
+			.location()
+			.find_many(vec![
+				location::instance_id::equals(Some(instance_id)),
+				location.volume_id.map_or_else(
+					|| location::id::equals(location.id),
+					|volume_id| location::volume_id::equals(Some(volume_id)),
+				),
+			])
+			.select(location::select!({ id path }))
+			.exec()
+			.await?
+			.into_iter()
+			.filter_map(|location| location.path.map(|path| (location.id, PathBuf::from(path))))
+			.collect::<HashMap<_, _>>();
+
+		let mut files_by_object = HashMap::<_, Vec<_>>::new();
+		for file_path in db
+			.file_path()
+			.find_many(vec![
+				file_path::location_id::in_vec(location_paths.keys().copied().collect()),
+				file_path::is_dir::equals(Some(false)),
+				file_path::symlink_target::equals(None),
+				file_path::object_id::not(None),
+			])
+			.order_by(file_path::id::order(SortOrder::Asc))
+			.select(file_path_for_dedup::select())
+			.exec()
+			.await?
+		{
+			let (Some(object_id), Some(location_id)) = (file_path.object_id, file_path.location_id)
+			else {
+				continue;
+			};
+			let Some(location_path) = location_paths.get(&location_id) else {
+				continue;
+			};
+
+			files_by_object
+				.entry(object_id)
+				.or_default()
+				.push(DuplicatedFile {
+					full_path: location_path.join(
+						IsolatedFilePathData::try_from(&file_path)
+							.map_err(FileSystemJobsError::from)?,
+					),
+					location_id,
+					pub_id: file_path.pub_id,
+					integrity_checksum: file_path.integrity_checksum,
+				});
+		}
+
+		let steps = files_by_object
+			.into_values()
+			.filter(|files| {
+				files.len() > 1 && files.iter().any(|file| file.location_id == location.id)
+			})
+			.map(|files| DuplicatedFiles { files })
+			.collect::<Vec<_>>();
+
+		trace!(
+			"Found {} objects with copies in location {}",
+			steps.len(),
+			location.id
+		);
+
+		// Must fill in the data, otherwise the job will not run
+		*data = Some(());
+
+		Ok(steps.into())
+	}
+
+	async fn execute_step(
+		&self,
+		ctx: &WorkerContext,
+		CurrentStep { step, .. }: CurrentStep<'_, Self::Step>,
+		_: &Self::Data,
+		_: &Self::RunMetadata,
+	) -> Result<JobStepOutput<Self::Step, Self::RunMetadata>, JobError> {
+		let init = self;
+		let Library { db, sync, .. } = ctx.library.as_ref();
+
+		let mut new_metadata = Self::RunMetadata::default();
+		let mut errors = vec![];
+
+		// Checksums are always computed again, as the index may be outdated and we're about to replace files
+		let mut verified_files = Vec::with_capacity(step.files.len());
+		for file in &step.files {
+			match verify_file(file).await {
+				Ok(Some(verified)) => {
+					if file.integrity_checksum.as_ref() != Some(&verified.checksum) {
+						sync.write_op(
+							db,
+							sync.shared_update(
+								prisma_sync::file_path::SyncId {
+									pub_id: file.pub_id.clone(),
+								},
+								file_path::integrity_checksum::NAME,
+								msgpack!(&verified.checksum),
+							),
+							db.file_path().update(
+								file_path::pub_id::equals(file.pub_id.clone()),
+								vec![file_path::integrity_checksum::set(Some(
+									verified.checksum.clone(),
+								))],
+							),
+						)
+						.await?;
+					}
+
+					verified_files.push(verified);
+				}
+				Ok(None) => {}
+				Err(e) => errors.push(e.to_string()),
+			}
+		}
+
+		let Some((kept, duplicates)) = verified_files.split_first() else {
+			return Ok((new_metadata, JobRunErrors(errors)).into());
+		};
+
+		for duplicate in duplicates {
+			let path = &duplicate.file.full_path;
+
+			let mut maybe_skip_reason = skip_reason(init.method, kept, duplicate);
+			if maybe_skip_reason.is_none() && !init.dry_run {
+				match replace_with_link(ctx, init.method, kept, duplicate).await {
+					Ok(None) => {
+						// The path now points to another inode, which the index must know about
+						match get_inode_from_path(path).await {
+							Ok(inode) => {
+								sync.write_op(
+									db,
+									sync.shared_update(
+										prisma_sync::file_path::SyncId {
+											pub_id: duplicate.file.pub_id.clone(),
+										},
+										file_path::inode::NAME,
+										msgpack!(inode_to_db(inode)),
+									),
+									db.file_path().update(
+										file_path::pub_id::equals(duplicate.file.pub_id.clone()),
+										vec![file_path::inode::set(Some(inode_to_db(inode)))],
+									),
+								)
+								.await?;
+							}
+							Err(e) => errors.push(e.to_string()),
+						}
+					}
+					Ok(Some(reason)) => maybe_skip_reason = Some(reason),
+					Err(e) => {
+						errors.push(e.to_string());
+						continue;
+					}
+				}
+			}
+
+			if let Some(reason) = maybe_skip_reason {
+				trace!("Skipping {}: {reason:?}", path.display());
+				new_metadata.skipped.push(SkippedFile {
+					path: path.clone(),
+					reason,
+				});
+			} else {
+				new_metadata.reclaimed_bytes += duplicate.metadata.len();
+				new_metadata.deduplicated.push(DeduplicatedFile {
+					path: path.clone(),
+					linked_to: kept.file.full_path.clone(),
+					size: duplicate.metadata.len(),
+				});
+			}
+		}
+
+		Ok((new_metadata, JobRunErrors(errors)).into())
+	}
+
+	async fn finalize(
+		&self,
+		_: &WorkerContext,
+		_data: &Option<Self::Data>,
+		run_metadata: &Self::RunMetadata,
+	) -> JobResult {
+		let init = self;
+
+		info!(
+			"{} {} files with {} bytes on location {}, skipped {}",
+			if init.dry_run {
+				"Could deduplicate"
+			} else {
+				"Deduplicated"
+			},
+			run_metadata.deduplicated.len(),
+			run_metadata.reclaimed_bytes,
+			init.location_id,
+			run_metadata.skipped.len(),
+		);
+
+		Ok(Some(json!({ "init": init, "run_metadata": run_metadata })))
+	}
+}
+
+async fn verify_file(file: &DuplicatedFile) -> Result<Option<VerifiedFile<'_>>, FileIOError> {
+	let metadata = match fs::symlink_metadata(&file.full_path).await {
+		Ok(metadata) if metadata.is_file() => metadata,
+		Ok(_) => return Ok(None),
+		Err(e) if e.kind() == io::ErrorKind::NotFound => {
+			warn!(
+				"File not found in the file system, skipping: {}",
+				file.full_path.display()
+			);
+			return Ok(None);
+		}
+		Err(e) => return Err(FileIOError::from((&file.full_path, e))),
+	};
+
+	let checksum = file_checksum(&file.full_path)
+		.await
+		.map_err(|e| FileIOError::from((&file.full_path, e)))?;
+
+	Ok(Some(VerifiedFile {
+		file,
+		metadata,
+		checksum,
+	}))
+}
+
+fn skip_reason(
+	method: DedupMethod,
+	kept: &VerifiedFile<'_>,
+	duplicate: &VerifiedFile<'_>,
+) -> Option<SkipReason> {
+	if kept.checksum != duplicate.checksum {
+		Some(SkipReason::ContentsDiffer)
+	} else if !same_filesystem(&kept.metadata, &duplicate.metadata) {
+		Some(SkipReason::OtherFilesystem)
+	} else if same_file(&kept.metadata, &duplicate.metadata) {
+		Some(SkipReason::AlreadyLinked)
+	} else if method == DedupMethod::Hardlink && kept.file.location_id == duplicate.file.location_id
+	{
+		Some(SkipReason::HardlinkWithinLocation)
+	} else if method == DedupMethod::Hardlink
+		&& !same_ownership(&kept.metadata, &duplicate.metadata)
+	{
+		Some(SkipReason::DifferentOwnership)
+	} else {
+		None
+	}
+}
+
+/// Link `duplicate` to `kept` through a temporary file renamed over it, so the path never goes missing
+/// even if we're interrupted, returning why it was left alone if it couldn't be done.
+async fn replace_with_link(
+	ctx: &WorkerContext,
+	method: DedupMethod,
+	kept: &VerifiedFile<'_>,
+	duplicate: &VerifiedFile<'_>,
+) -> Result<Option<SkipReason>, FileIOError> {
+	let path = &duplicate.file.full_path;
+	let temp_path = temp_path_for(path);
+
+	let _guard = ctx
+		.node
+		.locations
+		.temporary_ignore_events_for_path(
+			duplicate.file.location_id,
+			ctx.library.clone(),
+			&temp_path,
+		)
+		.await
+		.map_err(|e| warn!("Failed to ignore watcher events while deduplicating: {e:#?}"))
+		.ok();
+
+	match fs::remove_file(&temp_path).await {
+		Ok(()) => trace!("Removed leftover {}", temp_path.display()),
+		Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+		Err(e) => return Err(FileIOError::from((temp_path, e))),
+	}
+
+	match method {
+		DedupMethod::Reflink => match reflink(&kept.file.full_path, &temp_path).await {
+			Ok(()) => {
+				// The clone has the permissions and owner of the kept file, the path keeps its own
+				if let Err(e) = copy_ownership(&temp_path, &duplicate.metadata).await {
+					remove_temp_file(&temp_path).await;
+					return if e.kind() == io::ErrorKind::PermissionDenied {
+						Ok(Some(SkipReason::DifferentOwnership))
+					} else {
+						Err(FileIOError::from((temp_path, e)))
+					};
+				}
+			}
+			Err(e) if e.kind() == io::ErrorKind::Unsupported => {
+				return Ok(Some(SkipReason::ReflinkUnsupported))
+			}
+			Err(e) => return Err(FileIOError::from((temp_path, e))),
+		},
+		DedupMethod::Hardlink => fs::hard_link(&kept.file.full_path, &temp_path)
+			.await
+			.map_err(|e| FileIOError::from((&temp_path, e)))?,
+	}
+
+	let unchanged = fs::symlink_metadata(path).await.is_ok_and(|metadata| {
+		metadata.len() == duplicate.metadata.len()
+			&& metadata.modified().ok() == duplicate.metadata.modified().ok()
+	});
+	if !unchanged {
+		remove_temp_file(&temp_path).await;
+		return Ok(Some(SkipReason::Modified));
+	}
+
+	if let Err(e) = fs::rename(&temp_path, path).await {
+		remove_temp_file(&temp_path).await;
+		return Err(FileIOError::from((path, e)));
+	}
+
+	Ok(None)
+}
+
+fn temp_path_for(path: &Path) -> PathBuf {
+	let mut temp_name = OsString::from(".");
+	temp_name.push(path.file_name().unwrap_or_default());
+	temp_name.push(".sd-dedup");
+
+	path.with_file_name(temp_name)
+}
+
+async fn remove_temp_file(temp_path: &Path) {
+	if let Err(e) = fs::remove_file(temp_path).await {
+		warn!(
+			"Failed to remove temporary file {}: {e:#?}",
+			temp_path.display()
+		);
+	}
+}
+
+#[cfg(target_family = "unix")]
+async fn copy_ownership(path: &Path, metadata: &Metadata) -> Result<(), io::Error> {
+	use std::os::unix::fs::{chown, MetadataExt};
+
+	let current = fs::symlink_metadata(path).await?;
+	if current.uid() != metadata.uid() || current.gid() != metadata.gid() {
+		chown(path, Some(metadata.uid()), Some(metadata.gid()))?;
+	}
+
+	fs::set_permissions(path, metadata.permissions()).await
+}
+
+#[cfg(target_family = "windows")]
+async fn copy_ownership(path: &Path, metadata: &Metadata) -> Result<(), io::Error> {
+	fs::set_permissions(path, metadata.permissions()).await
+}
+
+#[cfg(target_family = "unix")]
+fn same_filesystem(a: &Metadata, b: &Metadata) -> bool {
+	use std::os::unix::fs::MetadataExt;
+
+	a.dev() == b.dev()
+}
+
+#[cfg(target_family = "windows")]
+fn same_filesystem(_: &Metadata, _: &Metadata) -> bool {
+	// Creating the link fails if they aren't, as we only look within a volume
+	true
+}
+
+#[cfg(target_family = "unix")]
+fn same_file(a: &Metadata, b: &Metadata) -> bool {
+	use std::os::unix::fs::MetadataExt;
+
+	a.dev() == b.dev() && a.ino() == b.ino()
+}
+
+#[cfg(target_family = "windows")]
+fn same_file(_: &Metadata, _: &Metadata) -> bool {
+	false
+}
+
+#[cfg(target_family = "unix")]
+fn same_ownership(a: &Metadata, b: &Metadata) -> bool {
+	use std::os::unix::fs::MetadataExt;
+
+	a.uid() == b.uid() && a.gid() == b.gid() && a.mode() == b.mode()
+}
+
+#[cfg(target_family = "windows")]
+fn same_ownership(a: &Metadata, b: &Metadata) -> bool {
+	a.permissions() == b.permissions()
+}
@@ -0,0 +1,132 @@
+//! Copies which share the data blocks of the source file, on filesystems supporting it (Btrfs, XFS).
+
+use std::{io, path::Path};
+
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use tokio::{fs, task::spawn_blocking};
+use tracing::trace;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Type)]
+pub enum CopyMethod {
+	/// The copy shares its data blocks with the source until either of them is modified
+	Reflink,
+	/// The bytes were copied, which the standard library does with `copy_file_range` on Linux
+	/// and `clonefile` on macOS, so this may still be a clone done by the filesystem
+	Copy,
+}
+
+/// Copy `source` to a new file at `target`, cloning it if possible and copying its bytes otherwise.
+pub async fn copy_file(
+	source: impl AsRef<Path>,
+	target: impl AsRef<Path>,
+) -> Result<CopyMethod, io::Error> {
+	let (source, target) = (source.as_ref(), target.as_ref());
+
+	match reflink(source, target).await {
+		Ok(()) => return Ok(CopyMethod::Reflink),
+		Err(e) if e.kind() == io::ErrorKind::Unsupported => {
+			trace!(
+				"Can't clone {} to {}, copying instead: {e}",
+				source.display(),
+				target.display()
+			);
+		}
+		Err(e) => return Err(e),
+	}
+
+	fs::copy(source, target).await.map(|_| CopyMethod::Copy)
+}
+
+/// Create `target` as a clone of `source`, failing with [`io::ErrorKind::Unsupported`] if the
+/// filesystem (or the platform) can't do it, like when both files are on different filesystems.
+pub async fn reflink(source: impl AsRef<Path>, target: impl AsRef<Path>) -> Result<(), io::Error> {
+	let source = source.as_ref().to_path_buf();
+	let target = target.as_ref().to_path_buf();
+
+	spawn_blocking(move || reflink_blocking(&source, &target))
+		.await
+		.map_err(io::Error::other)?
+}
+
+#[cfg(target_os = "linux")]
+fn reflink_blocking(source: &Path, target: &Path) -> Result<(), io::Error> {
+	use std::{
+		fs::{File, OpenOptions},
+		os::unix::io::AsRawFd,
+	};
+
+	// `_IOW(0x94, 9, int)` from `linux/fs.h`
+	const FICLONE: libc::c_ulong = 0x4004_9409;
+
+	let source_file = File::open(source)?;
+	let target_file = OpenOptions::new()
+		.write(true)
+		.create_new(true)
+		.open(target)?;
+
+	// SAFETY: both file descriptors are valid for as long as the files are alive
+	if unsafe {
+		libc::ioctl(
+			target_file.as_raw_fd(),
+			FICLONE as _,
+			source_file.as_raw_fd(),
+		)
+	} == -1
+	{
+		let e = io::Error::last_os_error();
+		drop(target_file);
+		remove_partial_target(target);
+
+		return Err(match e.raw_os_error() {
+			Some(libc::EOPNOTSUPP | libc::EXDEV | libc::EINVAL | libc::ENOTTY | libc::ENOSYS) => {
+				io::Error::new(io::ErrorKind::Unsupported, e)
+			}
+			_ => e,
+		});
+	}
+
+	// Same as `fs::copy`, the clone gets the permissions of the source
+	target_file.set_permissions(source_file.metadata()?.permissions())
+}
+
+#[cfg(not(target_os = "linux"))]
+fn reflink_blocking(_source: &Path, _target: &Path) -> Result<(), io::Error> {
+	Err(io::Error::new(
+		io::ErrorKind::Unsupported,
+		"cloning files is only supported on Linux",
+	))
+}
+
+#[cfg(target_os = "linux")]
+fn remove_partial_target(target: &Path) {
+	if let Err(e) = std::fs::remove_file(target) {
+		trace!(
+			"Failed to remove {} after a failed clone: {e}",
+			target.display()
+		);
+	}
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+	use super::*;
+
+	use tempfile::tempdir;
+
+	#[tokio::test]
+	async fn copies_whether_or_not_cloning_is_supported() {
+		let dir = tempdir().unwrap();
+		let source = dir.path().join("source.txt");
+		let target = dir.path().join("target.txt");
+		std::fs::write(&source, b"the same bytes").unwrap();
+
+		copy_file(&source, &target).await.unwrap();
+		assert_eq!(std::fs::read(&target).unwrap(), b"the same bytes");
+
+		// A clone never replaces an existing file
+		assert!(reflink(&source, &target).await.is_err());
+		assert_eq!(std::fs::read(&target).unwrap(), b"the same bytes");
+	}
+}
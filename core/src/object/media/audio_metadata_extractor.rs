@@ -0,0 +1,166 @@
+use crate::old_job::JobRunErrors;
+
+use sd_core_file_path_helper::IsolatedFilePathData;
+use sd_core_prisma_helpers::file_path_for_media_processor;
+
+use sd_file_ext::extensions::{AudioExtension, Extension, ALL_AUDIO_EXTENSIONS};
+use sd_media_metadata::AudioMetadata;
+use sd_prisma::prisma::{audio_data, location, PrismaClient};
+
+use std::{collections::HashSet, path::Path};
+
+use futures_concurrency::future::Join;
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tracing::error;
+
+use super::audio_data_to_query;
+
+#[derive(Error, Debug)]
+pub enum AudioDataError {
+	// Internal errors
+	#[error("database error: {0}")]
+	Database(#[from] prisma_client_rust::QueryError),
+	#[error(transparent)]
+	MediaData(#[from] sd_media_metadata::Error),
+}
+
+#[derive(Serialize, Deserialize, Default, Debug)]
+pub struct OldAudioDataExtractorMetadata {
+	pub extracted: u32,
+	pub skipped: u32,
+}
+
+pub(super) static FILTERED_AUDIO_EXTENSIONS: Lazy<Vec<Extension>> = Lazy::new(|| {
+	ALL_AUDIO_EXTENSIONS
+		.iter()
+		.copied()
+		.filter(can_extract_audio_data)
+		.map(Extension::Audio)
+		.collect()
+});
+
+/// Formats whose tags we can read, being ID3v1/v2, Vorbis comments, APE tags, MP4 atoms or
+/// RIFF INFO chunks
+pub const fn can_extract_audio_data(audio_extension: &AudioExtension) -> bool {
+	use AudioExtension::*;
+	matches!(
+		audio_extension,
+		Mp3 | M4a | Wav | Aiff | Aif | Flac | Ogg | Oga | Opus | Aac | Wv | Tta
+	)
+}
+
+pub async fn extract_audio_data(
+	path: impl AsRef<Path> + Send,
+) -> Result<Option<AudioMetadata>, AudioDataError> {
+	AudioMetadata::from_path(path).await.map_err(Into::into)
+}
+
+pub async fn process(
+	files_paths: &[file_path_for_media_processor::Data],
+	location_id: location::id::Type,
+	location_path: impl AsRef<Path>,
+	db: &PrismaClient,
+	ctx_update_fn: &impl Fn(usize),
+) -> Result<(OldAudioDataExtractorMetadata, JobRunErrors), AudioDataError> {
+	let mut run_metadata = OldAudioDataExtractorMetadata::default();
+	if files_paths.is_empty() {
+		return Ok((run_metadata, JobRunErrors::default()));
+	}
+
+	let location_path = location_path.as_ref();
+
+	let objects_already_with_audio_data = db
+		.audio_data()
+		.find_many(vec![audio_data::object_id::in_vec(
+			files_paths
+				.iter()
+				.filter_map(|file_path| file_path.object_id)
+				.collect(),
+		)])
+		.select(audio_data::select!({ object_id }))
+		.exec()
+		.await?;
+
+	if files_paths.len() == objects_already_with_audio_data.len() {
+		// All files already have media data, skipping
+		run_metadata.skipped = files_paths.len() as u32;
+		return Ok((run_metadata, JobRunErrors::default()));
+	}
+
+	let objects_already_with_audio_data = objects_already_with_audio_data
+		.into_iter()
+		.map(|audio_data| audio_data.object_id)
+		.collect::<HashSet<_>>();
+
+	run_metadata.skipped = objects_already_with_audio_data.len() as u32;
+
+	let (audio_datas, errors) = {
+		let maybe_audio_data = files_paths
+			.iter()
+			.enumerate()
+			.filter_map(|(idx, file_path)| {
+				file_path.object_id.and_then(|object_id| {
+					(!objects_already_with_audio_data.contains(&object_id))
+						.then_some((idx, file_path, object_id))
+				})
+			})
+			.filter_map(|(idx, file_path, object_id)| {
+				IsolatedFilePathData::try_from((location_id, file_path))
+					.map_err(|e| error!("{e:#?}"))
+					.ok()
+					.map(|iso_file_path| (idx, location_path.join(iso_file_path), object_id))
+			})
+			.map(|(idx, path, object_id)| async move {
+				let res = extract_audio_data(&path).await;
+				ctx_update_fn(idx + 1);
+				(res, path, object_id)
+			})
+			.collect::<Vec<_>>()
+			.join()
+			.await;
+
+		let total_audio_data = maybe_audio_data.len();
+
+		maybe_audio_data.into_iter().fold(
+			// In the good case, all audio data were extracted
+			(Vec::with_capacity(total_audio_data), Vec::new()),
+			|(mut audio_datas, mut errors), (maybe_audio_data, path, object_id)| {
+				match maybe_audio_data {
+					Ok(Some(audio_data)) => audio_datas.push((audio_data, object_id)),
+					Ok(None) => {
+						// No tags on path, skipping
+						run_metadata.skipped += 1;
+					}
+					Err(e) => errors.push((e, path)),
+				}
+				(audio_datas, errors)
+			},
+		)
+	};
+
+	let created = db
+		.audio_data()
+		.create_many(
+			audio_datas
+				.into_iter()
+				.map(|(audio_data, object_id)| audio_data_to_query(audio_data, object_id))
+				.collect(),
+		)
+		.skip_duplicates()
+		.exec()
+		.await?;
+
+	run_metadata.extracted = created as u32;
+	run_metadata.skipped += errors.len() as u32;
+
+	Ok((
+		run_metadata,
+		errors
+			.into_iter()
+			.map(|(e, path)| format!("Couldn't process file: \"{}\"; Error: {e}", path.display()))
+			.collect::<Vec<_>>()
+			.into(),
+	))
+}
@@ -0,0 +1,170 @@
+use crate::old_job::JobRunErrors;
+
+use sd_core_file_path_helper::IsolatedFilePathData;
+use sd_core_prisma_helpers::file_path_for_media_processor;
+
+use sd_file_ext::extensions::{
+	BookExtension, DocumentExtension, Extension, ALL_BOOK_EXTENSIONS, ALL_DOCUMENT_EXTENSIONS,
+};
+use sd_media_metadata::DocumentMetadata;
+use sd_prisma::prisma::{document_data, location, PrismaClient};
+
+use std::{collections::HashSet, path::Path};
+
+use futures_concurrency::future::Join;
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tracing::error;
+
+use super::document_data_to_query;
+
+#[derive(Error, Debug)]
+pub enum DocumentDataError {
+	// Internal errors
+	#[error("database error: {0}")]
+	Database(#[from] prisma_client_rust::QueryError),
+	#[error(transparent)]
+	MediaData(#[from] sd_media_metadata::Error),
+}
+
+#[derive(Serialize, Deserialize, Default, Debug)]
+pub struct OldDocumentDataExtractorMetadata {
+	pub extracted: u32,
+	pub skipped: u32,
+}
+
+pub(super) static FILTERED_DOCUMENT_EXTENSIONS: Lazy<Vec<Extension>> = Lazy::new(|| {
+	ALL_DOCUMENT_EXTENSIONS
+		.iter()
+		.copied()
+		.map(Extension::Document)
+		.chain(ALL_BOOK_EXTENSIONS.iter().copied().map(Extension::Book))
+		.filter(can_extract_document_data)
+		.collect()
+});
+
+/// PDFs, OOXML and ODF documents and EPUB ebooks. Legacy binary Office formats and the other
+/// ebook formats keep their metadata in ways we don't parse.
+pub const fn can_extract_document_data(extension: &Extension) -> bool {
+	use DocumentExtension::*;
+	matches!(
+		extension,
+		Extension::Document(Pdf | Docx | Xlsx | Pptx | Odt | Ods | Odp)
+			| Extension::Book(BookExtension::Epub)
+	)
+}
+
+pub async fn extract_document_data(
+	path: impl AsRef<Path> + Send,
+) -> Result<Option<DocumentMetadata>, DocumentDataError> {
+	DocumentMetadata::from_path(path).await.map_err(Into::into)
+}
+
+pub async fn process(
+	files_paths: &[file_path_for_media_processor::Data],
+	location_id: location::id::Type,
+	location_path: impl AsRef<Path>,
+	db: &PrismaClient,
+	ctx_update_fn: &impl Fn(usize),
+) -> Result<(OldDocumentDataExtractorMetadata, JobRunErrors), DocumentDataError> {
+	let mut run_metadata = OldDocumentDataExtractorMetadata::default();
+	if files_paths.is_empty() {
+		return Ok((run_metadata, JobRunErrors::default()));
+	}
+
+	let location_path = location_path.as_ref();
+
+	let objects_already_with_document_data = db
+		.document_data()
+		.find_many(vec![document_data::object_id::in_vec(
+			files_paths
+				.iter()
+				.filter_map(|file_path| file_path.object_id)
+				.collect(),
+		)])
+		.select(document_data::select!({ object_id }))
+		.exec()
+		.await?;
+
+	if files_paths.len() == objects_already_with_document_data.len() {
+		// All files already have media data, skipping
+		run_metadata.skipped = files_paths.len() as u32;
+		return Ok((run_metadata, JobRunErrors::default()));
+	}
+
+	let objects_already_with_document_data = objects_already_with_document_data
+		.into_iter()
+		.map(|document_data| document_data.object_id)
+		.collect::<HashSet<_>>();
+
+	run_metadata.skipped = objects_already_with_document_data.len() as u32;
+
+	let (document_datas, errors) = {
+		let maybe_document_data = files_paths
+			.iter()
+			.enumerate()
+			.filter_map(|(idx, file_path)| {
+				file_path.object_id.and_then(|object_id| {
+					(!objects_already_with_document_data.contains(&object_id))
+						.then_some((idx, file_path, object_id))
+				})
+			})
+			.filter_map(|(idx, file_path, object_id)| {
+				IsolatedFilePathData::try_from((location_id, file_path))
+					.map_err(|e| error!("{e:#?}"))
+					.ok()
+					.map(|iso_file_path| (idx, location_path.join(iso_file_path), object_id))
+			})
+			.map(|(idx, path, object_id)| async move {
+				let res = extract_document_data(&path).await;
+				ctx_update_fn(idx + 1);
+				(res, path, object_id)
+			})
+			.collect::<Vec<_>>()
+			.join()
+			.await;
+
+		let total_document_data = maybe_document_data.len();
+
+		maybe_document_data.into_iter().fold(
+			// In the good case, all document data were extracted
+			(Vec::with_capacity(total_document_data), Vec::new()),
+			|(mut document_datas, mut errors), (maybe_document_data, path, object_id)| {
+				match maybe_document_data {
+					Ok(Some(document_data)) => document_datas.push((document_data, object_id)),
+					Ok(None) => {
+						// No metadata on path, skipping
+						run_metadata.skipped += 1;
+					}
+					Err(e) => errors.push((e, path)),
+				}
+				(document_datas, errors)
+			},
+		)
+	};
+
+	let created = db
+		.document_data()
+		.create_many(
+			document_datas
+				.into_iter()
+				.map(|(document_data, object_id)| document_data_to_query(document_data, object_id))
+				.collect(),
+		)
+		.skip_duplicates()
+		.exec()
+		.await?;
+
+	run_metadata.extracted = created as u32;
+	run_metadata.skipped += errors.len() as u32;
+
+	Ok((
+		run_metadata,
+		errors
+			.into_iter()
+			.map(|(e, path)| format!("Couldn't process file: \"{}\"; Error: {e}", path.display()))
+			.collect::<Vec<_>>()
+			.into(),
+	))
+}
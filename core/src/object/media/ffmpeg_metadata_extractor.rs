@@ -1,5 +1,7 @@
 use crate::old_job::JobRunErrors;
 
+use super::subtitle_extractor;
+
 use prisma_client_rust::QueryError;
 use sd_core_file_path_helper::IsolatedFilePathData;
 use sd_core_prisma_helpers::file_path_for_media_processor;
@@ -29,13 +31,14 @@ use sd_utils::db::ffmpeg_data_field_to_db;
 use std::{
 	collections::{HashMap, HashSet},
 	path::Path,
+	str::FromStr,
 };
 
 use futures_concurrency::future::{Join, TryJoin};
 use once_cell::sync::Lazy;
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
-use tracing::error;
+use tracing::{error, warn};
 
 #[derive(Error, Debug)]
 pub enum FFmpegDataError {
@@ -102,6 +105,12 @@ pub const fn can_extract_ffmpeg_data_for_video(video_extension: &VideoExtension)
 	)
 }
 
+fn is_video(path: &Path) -> bool {
+	path.extension()
+		.and_then(|extension| extension.to_str())
+		.is_some_and(|extension| VideoExtension::from_str(extension).is_ok())
+}
+
 pub async fn extract_ffmpeg_data(
 	path: impl AsRef<Path> + Send,
 ) -> Result<FFmpegMetadata, FFmpegDataError> {
@@ -148,6 +157,7 @@ pub async fn process(
 	run_metadata.skipped = objects_already_with_ffmpeg_data.len() as u32;
 
 	let mut errors = vec![];
+	let mut paths = HashMap::new();
 
 	let ffmpeg_datas = files_paths
 		.iter()
@@ -173,14 +183,55 @@ pub async fn process(
 		.join()
 		.await
 		.into_iter()
-		.filter_map(|(res, path, object_id)| {
-			res.map(|ffmpeg_data| (ffmpeg_data, object_id))
-				.map_err(|e| errors.push((e, path)))
-				.ok()
+		.filter_map(|(res, path, object_id)| match res {
+			Ok(ffmpeg_data) => {
+				let is_video = is_video(&path);
+				paths.insert(object_id, path);
+				Some((ffmpeg_data, object_id, is_video))
+			}
+			Err(e) => {
+				errors.push((e, path));
+				None
+			}
 		})
 		.collect::<Vec<_>>();
 
-	let created = save_ffmpeg_data(ffmpeg_datas, db).await?;
+	let subtitled_objects = ffmpeg_datas
+		.iter()
+		.filter(|(_, _, is_video)| *is_video)
+		.map(|(_, object_id, _)| *object_id)
+		.collect::<Vec<_>>();
+
+	let created = save_ffmpeg_data(
+		ffmpeg_datas
+			.into_iter()
+			.map(|(ffmpeg_data, object_id, _)| (ffmpeg_data, object_id)),
+		db,
+	)
+	.await?;
+
+	// Subtitles are a nice to have, so failing to extract them doesn't fail the file
+	subtitled_objects
+		.into_iter()
+		.filter_map(|object_id| paths.remove(&object_id).map(|path| (object_id, path)))
+		.map(|(object_id, path)| async move {
+			match subtitle_extractor::extract_subtitles(&path).await {
+				Ok(tracks) if tracks.is_empty() => {}
+				Ok(tracks) => {
+					if let Err(e) = subtitle_extractor::save_subtitles(object_id, tracks, db).await
+					{
+						error!("Failed to save subtitles of '{}': {e:#?}", path.display());
+					}
+				}
+				Err(e) => warn!(
+					"Failed to extract subtitles of '{}': {e:#?}",
+					path.display()
+				),
+			}
+		})
+		.collect::<Vec<_>>()
+		.join()
+		.await;
 
 	run_metadata.extracted = created as u32;
 	run_metadata.skipped += errors.len() as u32;
@@ -310,14 +361,19 @@ async fn create_ffmpeg_chapters(
 						time_base_den,
 						time_base_num,
 						ffmpeg_data_id,
-						_params: vec![ffmpeg_media_chapter::metadata::set(
-							serde_json::to_vec(&metadata)
-								.map_err(|err| {
-									error!("Error reading FFmpegMediaChapter metadata: {err:#?}");
-									err
-								})
-								.ok(),
-						)],
+						_params: vec![
+							ffmpeg_media_chapter::title::set(metadata.title.clone()),
+							ffmpeg_media_chapter::metadata::set(
+								serde_json::to_vec(&metadata)
+									.map_err(|err| {
+										error!(
+											"Error reading FFmpegMediaChapter metadata: {err:#?}"
+										);
+										err
+									})
+									.ok(),
+							),
+						],
 					},
 				)
 				.collect(),
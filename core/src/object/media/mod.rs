@@ -8,16 +8,21 @@ use sd_media_metadata::{
 		stream::Stream,
 		video_props::VideoProps,
 	},
-	ExifMetadata, FFmpegMetadata,
+	AudioMetadata, DocumentMetadata, ExifMetadata, FFmpegMetadata,
 };
 use sd_prisma::prisma::{
-	exif_data::*, ffmpeg_media_audio_props, ffmpeg_media_chapter, ffmpeg_media_video_props,
+	audio_data, document_data, exif_data::*, ffmpeg_media_audio_props, ffmpeg_media_chapter,
+	ffmpeg_media_video_props,
 };
 
+pub mod audio_metadata_extractor;
+pub mod document_metadata_extractor;
 pub mod exif_metadata_extractor;
 pub mod ffmpeg_metadata_extractor;
 pub mod old_media_processor;
 pub mod old_thumbnail;
+pub mod perceptual_hash;
+pub mod subtitle_extractor;
 
 pub use old_media_processor::OldMediaProcessorJobInit;
 use sd_utils::db::ffmpeg_data_field_from_db;
@@ -74,6 +79,108 @@ pub fn exif_media_data_from_prisma_data(data: sd_prisma::prisma::exif_data::Data
 	}
 }
 
+pub fn audio_data_to_query(
+	audio: AudioMetadata,
+	object_id: audio_data::object_id::Type,
+) -> audio_data::CreateUnchecked {
+	// Tags are u32, but nothing sane is going to overflow an i32
+	let to_db = |value: Option<u32>| value.and_then(|value| i32::try_from(value).ok());
+
+	audio_data::CreateUnchecked {
+		object_id,
+		_params: vec![
+			audio_data::title::set(audio.title),
+			audio_data::artist::set(audio.artist),
+			audio_data::album::set(audio.album),
+			audio_data::album_artist::set(audio.album_artist),
+			audio_data::genre::set(audio.genre),
+			audio_data::composer::set(audio.composer),
+			audio_data::year::set(to_db(audio.year)),
+			audio_data::track_number::set(to_db(audio.track_number)),
+			audio_data::track_total::set(to_db(audio.track_total)),
+			audio_data::disc_number::set(to_db(audio.disc_number)),
+			audio_data::disc_total::set(to_db(audio.disc_total)),
+		],
+	}
+}
+
+pub fn audio_data_from_prisma_data(data: audio_data::Data) -> AudioMetadata {
+	let from_db = |value: Option<i32>| value.and_then(|value| u32::try_from(value).ok());
+
+	AudioMetadata {
+		title: data.title,
+		artist: data.artist,
+		album: data.album,
+		album_artist: data.album_artist,
+		genre: data.genre,
+		composer: data.composer,
+		year: from_db(data.year),
+		track_number: from_db(data.track_number),
+		track_total: from_db(data.track_total),
+		disc_number: from_db(data.disc_number),
+		disc_total: from_db(data.disc_total),
+	}
+}
+
+pub fn document_data_to_query(
+	document: DocumentMetadata,
+	object_id: document_data::object_id::Type,
+) -> document_data::CreateUnchecked {
+	document_data::CreateUnchecked {
+		object_id,
+		_params: vec![
+			document_data::title::set(document.title),
+			document_data::author::set(document.author),
+			document_data::subject::set(document.subject),
+			document_data::page_count::set(
+				document
+					.page_count
+					.and_then(|page_count| i32::try_from(page_count).ok()),
+			),
+			document_data::date_created::set(document.date_created),
+		],
+	}
+}
+
+pub fn document_data_from_prisma_data(data: document_data::Data) -> DocumentMetadata {
+	DocumentMetadata {
+		title: data.title,
+		author: data.author,
+		subject: data.subject,
+		page_count: data
+			.page_count
+			.and_then(|page_count| u32::try_from(page_count).ok()),
+		date_created: data.date_created,
+	}
+}
+
+pub fn ffmpeg_chapter_from_prisma_data(
+	ffmpeg_media_chapter::Data {
+		chapter_id,
+		start,
+		end,
+		time_base_den,
+		time_base_num,
+		metadata,
+		..
+	}: ffmpeg_media_chapter::Data,
+) -> Chapter {
+	Chapter {
+		id: chapter_id,
+		start: {
+			let start = ffmpeg_data_field_from_db(&start);
+			((start >> 32) as i32, start as u32)
+		},
+		end: {
+			let end = ffmpeg_data_field_from_db(&end);
+			((end >> 32) as i32, end as u32)
+		},
+		time_base_den,
+		time_base_num,
+		metadata: from_slice_option_to_option(metadata).unwrap_or_default(),
+	}
+}
+
 pub fn ffmpeg_data_from_prisma_data(
 	object_with_media_data::ffmpeg_data::Data {
 		formats,
@@ -102,30 +209,7 @@ pub fn ffmpeg_data_from_prisma_data(
 		},
 		chapters: chapters
 			.into_iter()
-			.map(
-				|ffmpeg_media_chapter::Data {
-				     chapter_id,
-				     start,
-				     end,
-				     time_base_den,
-				     time_base_num,
-				     metadata,
-				     ..
-				 }| Chapter {
-					id: chapter_id,
-					start: {
-						let start = ffmpeg_data_field_from_db(&start);
-						((start >> 32) as i32, start as u32)
-					},
-					end: {
-						let end = ffmpeg_data_field_from_db(&end);
-						((end >> 32) as i32, end as u32)
-					},
-					time_base_den,
-					time_base_num,
-					metadata: from_slice_option_to_option(metadata).unwrap_or_default(),
-				},
-			)
+			.map(ffmpeg_chapter_from_prisma_data)
 			.collect(),
 		programs: programs
 			.into_iter()
@@ -2,17 +2,17 @@ use crate::{
 	invalidate_query,
 	library::Library,
 	location::ScanState,
-	object::media::ffmpeg_metadata_extractor,
+	object::media::{
+		audio_metadata_extractor, document_metadata_extractor, ffmpeg_metadata_extractor,
+		perceptual_hash,
+	},
 	old_job::{
-		CurrentStep, JobError, JobInitOutput, JobReportUpdate, JobResult, JobStepOutput,
-		StatefulJob, WorkerContext,
+		CurrentStep, JobError, JobInitOutput, JobReportUpdate, JobResult, JobRunErrors,
+		JobStepOutput, StatefulJob, WorkerContext,
 	},
 	Node,
 };
 
-#[cfg(feature = "ai")]
-use crate::old_job::JobRunErrors;
-
 use sd_core_file_path_helper::{
 	ensure_file_path_exists, ensure_sub_path_is_directory, ensure_sub_path_is_in_location,
 	IsolatedFilePathData,
@@ -20,7 +20,7 @@ use sd_core_file_path_helper::{
 use sd_core_prisma_helpers::file_path_for_media_processor;
 
 use sd_file_ext::extensions::Extension;
-use sd_prisma::prisma::{location, PrismaClient};
+use sd_prisma::prisma::{location, object, PrismaClient};
 use sd_utils::db::maybe_missing;
 
 #[cfg(feature = "ai")]
@@ -42,14 +42,14 @@ use itertools::Itertools;
 use prisma_client_rust::{raw, PrismaValue};
 use serde::{Deserialize, Serialize};
 use serde_json::json;
-use tokio::time::sleep;
+use tokio::{fs, task::spawn_blocking, time::sleep};
 use tracing::{debug, error, info, trace, warn};
 
 use super::{
 	exif_metadata_extractor,
 	old_thumbnail::{self, GenerateThumbnailArgs},
-	process_audio_and_video, process_images, BatchToProcess, MediaProcessorError,
-	OldMediaProcessorMetadata,
+	process_audio_and_video, process_audio_tags, process_documents, process_images, BatchToProcess,
+	MediaProcessorError, OldMediaProcessorMetadata,
 };
 
 const BATCH_SIZE: usize = 10;
@@ -88,7 +88,20 @@ pub struct OldMediaProcessorJobData {
 pub enum OldMediaProcessorJobStep {
 	ExtractImageMediaData(Vec<file_path_for_media_processor::Data>),
 	ExtractAudioAndVideoMediaData(Vec<file_path_for_media_processor::Data>),
+	ExtractAudioTags(Vec<file_path_for_media_processor::Data>),
+	ExtractDocumentMetadata(Vec<file_path_for_media_processor::Data>),
 	WaitThumbnails(usize),
+	#[cfg(feature = "ffmpeg")]
+	GenerateVideoPreviews {
+		file_paths: Vec<file_path_for_media_processor::Data>,
+		completed: usize,
+		total: usize,
+	},
+	ComputePerceptualHashes {
+		file_paths: Vec<file_path_for_media_processor::Data>,
+		completed: usize,
+		total: usize,
+	},
 	#[cfg(feature = "ai")]
 	WaitLabels(usize),
 }
@@ -183,6 +196,36 @@ impl StatefulJob for OldMediaProcessorJobInit {
 			get_files_for_image_media_data_extraction(db, &iso_file_path).await?;
 		let file_paths_to_extract_ffmpeg_data =
 			get_files_for_audio_and_video_media_data_extraction(db, &iso_file_path).await?;
+		let file_paths_to_extract_audio_data =
+			get_files_for_audio_tags_extraction(db, &iso_file_path).await?;
+		let file_paths_to_extract_document_data =
+			get_files_for_document_metadata_extraction(db, &iso_file_path).await?;
+
+		#[cfg(feature = "ffmpeg")]
+		let file_paths_for_video_previews = if ctx
+			.node
+			.config
+			.get()
+			.await
+			.preferences
+			.thumbnailer
+			.generate_video_previews()
+		{
+			get_files_for_video_previews(db, &iso_file_path, self.regenerate_thumbnails).await?
+		} else {
+			vec![]
+		};
+
+		#[cfg(feature = "ffmpeg")]
+		let total_files_for_video_previews = file_paths_for_video_previews.len();
+
+		// Perceptual hashes are computed from thumbnails, so we always take them after the
+		// thumbnailer is done, even if no new thumbnails were dispatched in this run
+		let file_paths_for_perceptual_hashing =
+			get_files_for_perceptual_hashing(db, &iso_file_path, self.regenerate_thumbnails)
+				.await?;
+
+		let total_files_for_perceptual_hashing = file_paths_for_perceptual_hashing.len();
 
 		#[cfg(feature = "ai")]
 		let file_paths_for_labeling =
@@ -208,8 +251,42 @@ impl StatefulJob for OldMediaProcessorJobInit {
 				(uuid::Uuid::new_v4(), None)
 			};
 
-		let total_files =
-			file_paths_to_extract_exif_data.len() + file_paths_to_extract_ffmpeg_data.len();
+		let total_files = file_paths_to_extract_exif_data.len()
+			+ file_paths_to_extract_ffmpeg_data.len()
+			+ file_paths_to_extract_audio_data.len()
+			+ file_paths_to_extract_document_data.len();
+
+		#[cfg(feature = "ffmpeg")]
+		let video_previews_steps = file_paths_for_video_previews
+			.into_iter()
+			.chunks(BATCH_SIZE)
+			.into_iter()
+			.enumerate()
+			.map(
+				|(chunk_idx, chunk)| OldMediaProcessorJobStep::GenerateVideoPreviews {
+					file_paths: chunk.collect(),
+					completed: chunk_idx * BATCH_SIZE,
+					total: total_files_for_video_previews,
+				},
+			)
+			.collect::<Vec<_>>();
+
+		#[cfg(not(feature = "ffmpeg"))]
+		let video_previews_steps = vec![];
+
+		let perceptual_hashes_steps = file_paths_for_perceptual_hashing
+			.into_iter()
+			.chunks(BATCH_SIZE)
+			.into_iter()
+			.enumerate()
+			.map(
+				|(chunk_idx, chunk)| OldMediaProcessorJobStep::ComputePerceptualHashes {
+					file_paths: chunk.collect(),
+					completed: chunk_idx * BATCH_SIZE,
+					total: total_files_for_perceptual_hashing,
+				},
+			)
+			.collect::<Vec<_>>();
 
 		let chunked_files = file_paths_to_extract_exif_data
 			.into_iter()
@@ -225,6 +302,22 @@ impl StatefulJob for OldMediaProcessorJobInit {
 					.map(|chunk| chunk.collect::<Vec<_>>())
 					.map(OldMediaProcessorJobStep::ExtractAudioAndVideoMediaData),
 			)
+			.chain(
+				file_paths_to_extract_audio_data
+					.into_iter()
+					.chunks(BATCH_SIZE)
+					.into_iter()
+					.map(|chunk| chunk.collect::<Vec<_>>())
+					.map(OldMediaProcessorJobStep::ExtractAudioTags),
+			)
+			.chain(
+				file_paths_to_extract_document_data
+					.into_iter()
+					.chunks(BATCH_SIZE)
+					.into_iter()
+					.map(|chunk| chunk.collect::<Vec<_>>())
+					.map(OldMediaProcessorJobStep::ExtractDocumentMetadata),
+			)
 			.chain(
 				[(thumbs_to_process_count > 0).then_some(
 					OldMediaProcessorJobStep::WaitThumbnails(thumbs_to_process_count as usize),
@@ -232,6 +325,8 @@ impl StatefulJob for OldMediaProcessorJobInit {
 				.into_iter()
 				.flatten(),
 			)
+			.chain(video_previews_steps)
+			.chain(perceptual_hashes_steps)
 			.chain(
 				[
 					#[cfg(feature = "ai")]
@@ -319,6 +414,36 @@ impl StatefulJob for OldMediaProcessorJobInit {
 				.map_err(Into::into)
 			}
 
+			OldMediaProcessorJobStep::ExtractAudioTags(file_paths) => process_audio_tags(
+				file_paths,
+				self.location.id,
+				&data.location_path,
+				&ctx.library.db,
+				&|completed_count| {
+					ctx.progress(vec![JobReportUpdate::CompletedTaskCount(
+						step_number * BATCH_SIZE + completed_count,
+					)]);
+				},
+			)
+			.await
+			.map(Into::into)
+			.map_err(Into::into),
+
+			OldMediaProcessorJobStep::ExtractDocumentMetadata(file_paths) => process_documents(
+				file_paths,
+				self.location.id,
+				&data.location_path,
+				&ctx.library.db,
+				&|completed_count| {
+					ctx.progress(vec![JobReportUpdate::CompletedTaskCount(
+						step_number * BATCH_SIZE + completed_count,
+					)]);
+				},
+			)
+			.await
+			.map(Into::into)
+			.map_err(Into::into),
+
 			OldMediaProcessorJobStep::WaitThumbnails(total_thumbs) => {
 				ctx.progress(vec![
 					JobReportUpdate::TaskCount(*total_thumbs),
@@ -364,6 +489,59 @@ impl StatefulJob for OldMediaProcessorJobInit {
 				Ok(None.into())
 			}
 
+			#[cfg(feature = "ffmpeg")]
+			OldMediaProcessorJobStep::GenerateVideoPreviews {
+				file_paths,
+				completed,
+				total,
+			} => {
+				ctx.progress(vec![
+					JobReportUpdate::TaskCount(*total),
+					JobReportUpdate::CompletedTaskCount(*completed),
+					JobReportUpdate::Phase("video_previews".to_string()),
+					JobReportUpdate::Message(format!("Generating previews for {total} videos")),
+				]);
+
+				Ok(generate_video_previews(
+					ctx,
+					self.location.id,
+					&data.location_path,
+					file_paths,
+					&|completed_count| {
+						ctx.progress(vec![JobReportUpdate::CompletedTaskCount(
+							completed + completed_count,
+						)]);
+					},
+				)
+				.await
+				.into())
+			}
+
+			OldMediaProcessorJobStep::ComputePerceptualHashes {
+				file_paths,
+				completed,
+				total,
+			} => {
+				ctx.progress(vec![
+					JobReportUpdate::TaskCount(*total),
+					JobReportUpdate::CompletedTaskCount(*completed),
+					JobReportUpdate::Phase("perceptual_hashes".to_string()),
+					JobReportUpdate::Message(format!(
+						"Computing perceptual hashes for {total} files"
+					)),
+				]);
+
+				Ok(
+					compute_perceptual_hashes(ctx, file_paths, &|completed_count| {
+						ctx.progress(vec![JobReportUpdate::CompletedTaskCount(
+							completed + completed_count,
+						)]);
+					})
+					.await
+					.into(),
+				)
+			}
+
 			#[cfg(feature = "ai")]
 			OldMediaProcessorJobStep::WaitLabels(total_labels) => {
 				let Some(image_labeller) = ctx.node.old_image_labeller.as_ref() else {
@@ -449,7 +627,12 @@ impl StatefulJob for OldMediaProcessorJobInit {
 				.display()
 		);
 
-		if run_metadata.exif_data.extracted > 0 || run_metadata.ffmpeg_data.extracted > 0 {
+		if run_metadata.exif_data.extracted > 0
+			|| run_metadata.ffmpeg_data.extracted > 0
+			|| run_metadata.audio_data.extracted > 0
+			|| run_metadata.document_data.extracted > 0
+			|| run_metadata.video_previews_generated > 0
+		{
 			invalidate_query!(ctx.library, "search.paths");
 		}
 
@@ -570,6 +753,32 @@ async fn get_files_for_audio_and_video_media_data_extraction(
 	.map_err(Into::into)
 }
 
+async fn get_files_for_audio_tags_extraction(
+	db: &PrismaClient,
+	parent_iso_file_path: &IsolatedFilePathData<'_>,
+) -> Result<Vec<file_path_for_media_processor::Data>, MediaProcessorError> {
+	get_all_children_files_by_extensions(
+		db,
+		parent_iso_file_path,
+		&audio_metadata_extractor::FILTERED_AUDIO_EXTENSIONS,
+	)
+	.await
+	.map_err(Into::into)
+}
+
+async fn get_files_for_document_metadata_extraction(
+	db: &PrismaClient,
+	parent_iso_file_path: &IsolatedFilePathData<'_>,
+) -> Result<Vec<file_path_for_media_processor::Data>, MediaProcessorError> {
+	get_all_children_files_by_extensions(
+		db,
+		parent_iso_file_path,
+		&document_metadata_extractor::FILTERED_DOCUMENT_EXTENSIONS,
+	)
+	.await
+	.map_err(Into::into)
+}
+
 #[cfg(feature = "ai")]
 async fn get_files_for_labeling(
 	db: &PrismaClient,
@@ -615,6 +824,270 @@ async fn get_files_for_labeling(
 	.map_err(Into::into)
 }
 
+#[cfg(feature = "ffmpeg")]
+async fn get_files_for_video_previews(
+	db: &PrismaClient,
+	parent_iso_file_path: &IsolatedFilePathData<'_>,
+	regenerate_previews: bool,
+) -> Result<Vec<file_path_for_media_processor::Data>, MediaProcessorError> {
+	// FIXME: Had to use format! macro because PCR doesn't support IN with Vec for SQLite
+	// We have no data coming from the user, so this is sql injection safe
+	db._query_raw(raw!(
+		&format!(
+			"SELECT id, materialized_path, is_dir, name, extension, cas_id, object_id
+			FROM file_path f
+			WHERE
+				location_id={{}}
+				AND cas_id IS NOT NULL
+				AND object_id IS NOT NULL
+				AND LOWER(extension) IN ({})
+				AND materialized_path LIKE {{}}
+				{}
+			ORDER BY materialized_path ASC",
+			// Ordering by materialized_path so we can prioritize processing the first files
+			// in the above part of the directories tree
+			&old_thumbnail::THUMBNAILABLE_VIDEO_EXTENSIONS
+				.iter()
+				.map(|ext| format!("LOWER('{ext}')"))
+				.collect::<Vec<_>>()
+				.join(","),
+			if !regenerate_previews {
+				"AND NOT EXISTS (
+					SELECT 1 FROM object
+					WHERE id = f.object_id AND has_thumbstrip = 1 AND has_video_preview = 1
+				)"
+			} else {
+				""
+			}
+		),
+		PrismaValue::Int(parent_iso_file_path.location_id()),
+		PrismaValue::String(format!(
+			"{}%",
+			parent_iso_file_path
+				.materialized_path_for_children()
+				.expect("sub path iso_file_path must be a directory")
+		))
+	))
+	.exec()
+	.await
+	.map_err(Into::into)
+}
+
+/// Generates the scrubbing thumbstrip and the animated preview for each video.
+///
+/// Failing to generate preview media for a video isn't fatal for the job, so errors are
+/// collected and reported at the end.
+#[cfg(feature = "ffmpeg")]
+async fn generate_video_previews(
+	ctx: &WorkerContext,
+	location_id: location::id::Type,
+	location_path: &Path,
+	file_paths: &[file_path_for_media_processor::Data],
+	ctx_update_fn: &impl Fn(usize),
+) -> (OldMediaProcessorMetadata, JobRunErrors) {
+	use old_thumbnail::video_preview::{generate_thumbstrip, generate_video_preview};
+
+	let Library { id, db, .. } = ctx.library.as_ref();
+
+	let mut generated = 0;
+	let mut errors = vec![];
+
+	for (idx, file_path) in file_paths.iter().enumerate() {
+		let file_path_id = file_path.id;
+
+		let (Some(cas_id), Some(object_id)) = (&file_path.cas_id, file_path.object_id) else {
+			ctx_update_fn(idx + 1);
+			continue;
+		};
+
+		let video_path = match IsolatedFilePathData::try_from((location_id, file_path)) {
+			Ok(iso_file_path) => location_path.join(&iso_file_path),
+			Err(e) => {
+				errors.push(format!(
+					"Failed to extract isolated file path data from file path <id='{file_path_id}'>: {e}"
+				));
+				ctx_update_fn(idx + 1);
+				continue;
+			}
+		};
+
+		let res = async {
+			generate_thumbstrip(&ctx.node, cas_id, *id, &video_path).await?;
+			generate_video_preview(&ctx.node, cas_id, *id, &video_path).await?;
+
+			// These flags are about files in this node's thumbnails directory, so they're
+			// not synced, as other nodes have to generate their own preview media
+			db.object()
+				.update(
+					object::id::equals(object_id),
+					vec![
+						object::has_thumbstrip::set(Some(true)),
+						object::has_video_preview::set(Some(true)),
+					],
+				)
+				.exec()
+				.await?;
+
+			Ok::<_, MediaProcessorError>(())
+		}
+		.await;
+
+		match res {
+			Ok(()) => generated += 1,
+			Err(e) => {
+				error!(
+					"Failed to generate video previews for <file_path_id='{file_path_id}'>: {e:#?}"
+				);
+				errors.push(format!(
+					"Failed to generate video previews for <path='{}'>: {e}",
+					video_path.display()
+				));
+			}
+		}
+
+		ctx_update_fn(idx + 1);
+	}
+
+	(
+		OldMediaProcessorMetadata {
+			video_previews_generated: generated,
+			..Default::default()
+		},
+		JobRunErrors(errors),
+	)
+}
+
+async fn get_files_for_perceptual_hashing(
+	db: &PrismaClient,
+	parent_iso_file_path: &IsolatedFilePathData<'_>,
+	regenerate_hashes: bool,
+) -> Result<Vec<file_path_for_media_processor::Data>, MediaProcessorError> {
+	// FIXME: Had to use format! macro because PCR doesn't support IN with Vec for SQLite
+	// We have no data coming from the user, so this is sql injection safe
+	db._query_raw(raw!(
+		&format!(
+			"SELECT id, materialized_path, is_dir, name, extension, cas_id, object_id
+			FROM file_path f
+			WHERE
+				location_id={{}}
+				AND cas_id IS NOT NULL
+				AND object_id IS NOT NULL
+				AND LOWER(extension) IN ({})
+				AND materialized_path LIKE {{}}
+				{}
+			ORDER BY materialized_path ASC",
+			&perceptual_hash::HASHABLE_EXTENSIONS
+				.iter()
+				.map(|ext| format!("LOWER('{ext}')"))
+				.collect::<Vec<_>>()
+				.join(","),
+			if !regenerate_hashes {
+				"AND NOT EXISTS (
+					SELECT 1 FROM object
+					WHERE id = f.object_id AND perceptual_hash IS NOT NULL
+				)"
+			} else {
+				""
+			}
+		),
+		PrismaValue::Int(parent_iso_file_path.location_id()),
+		PrismaValue::String(format!(
+			"{}%",
+			parent_iso_file_path
+				.materialized_path_for_children()
+				.expect("sub path iso_file_path must be a directory")
+		))
+	))
+	.exec()
+	.await
+	.map_err(Into::into)
+}
+
+/// Computes the perceptual hash of each file from its thumbnail.
+///
+/// Files without a thumbnail yet are skipped, they will be hashed in a later run.
+async fn compute_perceptual_hashes(
+	ctx: &WorkerContext,
+	file_paths: &[file_path_for_media_processor::Data],
+	ctx_update_fn: &impl Fn(usize),
+) -> (OldMediaProcessorMetadata, JobRunErrors) {
+	let Library { id, db, .. } = ctx.library.as_ref();
+
+	let mut computed = 0;
+	let mut errors = vec![];
+
+	for (idx, file_path) in file_paths.iter().enumerate() {
+		let (Some(cas_id), Some(object_id)) = (&file_path.cas_id, file_path.object_id) else {
+			ctx_update_fn(idx + 1);
+			continue;
+		};
+
+		let thumbnail_path = old_thumbnail::get_indexed_thumbnail_path(&ctx.node, cas_id, *id);
+
+		let bytes = match fs::read(&thumbnail_path).await {
+			Ok(bytes) => bytes,
+			Err(e) => {
+				trace!(
+					"No thumbnail to compute perceptual hash <file_path_id='{}'>: {e:#?}",
+					file_path.id
+				);
+				ctx_update_fn(idx + 1);
+				continue;
+			}
+		};
+
+		let hash = match spawn_blocking(move || perceptual_hash::hash_thumbnail(&bytes)).await {
+			Ok(Some(hash)) => hash,
+			Ok(None) => {
+				errors.push(format!(
+					"Failed to decode thumbnail to compute perceptual hash <path='{}'>",
+					thumbnail_path.display()
+				));
+				ctx_update_fn(idx + 1);
+				continue;
+			}
+			Err(e) => {
+				errors.push(format!(
+					"Perceptual hashing task panicked <path='{}'>: {e}",
+					thumbnail_path.display()
+				));
+				ctx_update_fn(idx + 1);
+				continue;
+			}
+		};
+
+		// Not synced, as every node computes it from its own thumbnails
+		if let Err(e) = db
+			.object()
+			.update(
+				object::id::equals(object_id),
+				vec![object::perceptual_hash::set(Some(
+					perceptual_hash::to_bytes(hash),
+				))],
+			)
+			.exec()
+			.await
+		{
+			errors.push(format!(
+				"Failed to save perceptual hash <file_path_id='{}'>: {e}",
+				file_path.id
+			));
+		} else {
+			computed += 1;
+		}
+
+		ctx_update_fn(idx + 1);
+	}
+
+	(
+		OldMediaProcessorMetadata {
+			perceptual_hashes_computed: computed,
+			..Default::default()
+		},
+		JobRunErrors(errors),
+	)
+}
+
 async fn get_all_children_files_by_extensions(
 	db: &PrismaClient,
 	parent_iso_file_path: &IsolatedFilePathData<'_>,
@@ -12,6 +12,8 @@ use thiserror::Error;
 use tracing::error;
 
 use super::{
+	audio_metadata_extractor::{self, AudioDataError, OldAudioDataExtractorMetadata},
+	document_metadata_extractor::{self, DocumentDataError, OldDocumentDataExtractorMetadata},
 	exif_metadata_extractor::{self, ExifDataError, OldExifDataExtractorMetadata},
 	ffmpeg_metadata_extractor::{self, FFmpegDataError, OldFFmpegDataExtractorMetadata},
 	old_thumbnail::{self, BatchToProcess, ThumbnailerError},
@@ -39,14 +41,22 @@ pub enum MediaProcessorError {
 	ExifMediaDataExtractor(#[from] ExifDataError),
 	#[error(transparent)]
 	FFmpegDataExtractor(#[from] FFmpegDataError),
+	#[error(transparent)]
+	AudioDataExtractor(#[from] AudioDataError),
+	#[error(transparent)]
+	DocumentDataExtractor(#[from] DocumentDataError),
 }
 
 #[derive(Debug, Serialize, Deserialize, Default)]
 pub struct OldMediaProcessorMetadata {
 	exif_data: OldExifDataExtractorMetadata,
 	ffmpeg_data: OldFFmpegDataExtractorMetadata,
+	audio_data: OldAudioDataExtractorMetadata,
+	document_data: OldDocumentDataExtractorMetadata,
 	thumbs_processed: u32,
 	labels_extracted: u32,
+	video_previews_generated: u32,
+	perceptual_hashes_computed: u32,
 }
 
 impl From<OldExifDataExtractorMetadata> for OldMediaProcessorMetadata {
@@ -54,8 +64,12 @@ impl From<OldExifDataExtractorMetadata> for OldMediaProcessorMetadata {
 		Self {
 			exif_data,
 			ffmpeg_data: Default::default(),
+			audio_data: Default::default(),
+			document_data: Default::default(),
 			thumbs_processed: 0,
 			labels_extracted: 0,
+			video_previews_generated: 0,
+			perceptual_hashes_computed: 0,
 		}
 	}
 }
@@ -65,8 +79,42 @@ impl From<OldFFmpegDataExtractorMetadata> for OldMediaProcessorMetadata {
 		Self {
 			exif_data: Default::default(),
 			ffmpeg_data,
+			audio_data: Default::default(),
+			document_data: Default::default(),
+			thumbs_processed: 0,
+			labels_extracted: 0,
+			video_previews_generated: 0,
+			perceptual_hashes_computed: 0,
+		}
+	}
+}
+
+impl From<OldAudioDataExtractorMetadata> for OldMediaProcessorMetadata {
+	fn from(audio_data: OldAudioDataExtractorMetadata) -> Self {
+		Self {
+			exif_data: Default::default(),
+			ffmpeg_data: Default::default(),
+			audio_data,
+			document_data: Default::default(),
+			thumbs_processed: 0,
+			labels_extracted: 0,
+			video_previews_generated: 0,
+			perceptual_hashes_computed: 0,
+		}
+	}
+}
+
+impl From<OldDocumentDataExtractorMetadata> for OldMediaProcessorMetadata {
+	fn from(document_data: OldDocumentDataExtractorMetadata) -> Self {
+		Self {
+			exif_data: Default::default(),
+			ffmpeg_data: Default::default(),
+			audio_data: Default::default(),
+			document_data,
 			thumbs_processed: 0,
 			labels_extracted: 0,
+			video_previews_generated: 0,
+			perceptual_hashes_computed: 0,
 		}
 	}
 }
@@ -77,8 +125,14 @@ impl JobRunMetadata for OldMediaProcessorMetadata {
 		self.exif_data.skipped += new_data.exif_data.skipped;
 		self.ffmpeg_data.extracted += new_data.ffmpeg_data.extracted;
 		self.ffmpeg_data.skipped += new_data.ffmpeg_data.skipped;
+		self.audio_data.extracted += new_data.audio_data.extracted;
+		self.audio_data.skipped += new_data.audio_data.skipped;
+		self.document_data.extracted += new_data.document_data.extracted;
+		self.document_data.skipped += new_data.document_data.skipped;
 		self.thumbs_processed += new_data.thumbs_processed;
 		self.labels_extracted += new_data.labels_extracted;
+		self.video_previews_generated += new_data.video_previews_generated;
+		self.perceptual_hashes_computed += new_data.perceptual_hashes_computed;
 	}
 }
 
@@ -107,3 +161,29 @@ pub async fn process_audio_and_video(
 		.map(|(ffmpeg_extraction_metadata, errors)| (ffmpeg_extraction_metadata.into(), errors))
 		.map_err(Into::into)
 }
+
+pub async fn process_audio_tags(
+	files_paths: &[file_path_for_media_processor::Data],
+	location_id: location::id::Type,
+	location_path: impl AsRef<Path> + Send,
+	db: &PrismaClient,
+	ctx_update_fn: &impl Fn(usize),
+) -> Result<(OldMediaProcessorMetadata, JobRunErrors), MediaProcessorError> {
+	audio_metadata_extractor::process(files_paths, location_id, location_path, db, ctx_update_fn)
+		.await
+		.map(|(audio_extraction_metadata, errors)| (audio_extraction_metadata.into(), errors))
+		.map_err(Into::into)
+}
+
+pub async fn process_documents(
+	files_paths: &[file_path_for_media_processor::Data],
+	location_id: location::id::Type,
+	location_path: impl AsRef<Path> + Send,
+	db: &PrismaClient,
+	ctx_update_fn: &impl Fn(usize),
+) -> Result<(OldMediaProcessorMetadata, JobRunErrors), MediaProcessorError> {
+	document_metadata_extractor::process(files_paths, location_id, location_path, db, ctx_update_fn)
+		.await
+		.map(|(document_extraction_metadata, errors)| (document_extraction_metadata.into(), errors))
+		.map_err(Into::into)
+}
@@ -31,7 +31,8 @@ use tracing::{debug, error};
 use futures::StreamExt;
 
 use super::{
-	exif_metadata_extractor, ffmpeg_metadata_extractor,
+	audio_metadata_extractor, document_metadata_extractor, exif_metadata_extractor,
+	ffmpeg_metadata_extractor,
 	old_thumbnail::{self, BatchToProcess, GenerateThumbnailArgs},
 	MediaProcessorError, OldMediaProcessorMetadata,
 };
@@ -95,6 +96,10 @@ pub async fn old_shallow(
 		get_files_for_exif_media_data_extraction(db, &iso_file_path).await?;
 	let file_paths_to_extract_ffmpeg_data =
 		get_files_for_ffmpeg_media_data_extraction(db, &iso_file_path).await?;
+	let file_paths_to_extract_audio_data =
+		get_files_for_audio_tags_extraction(db, &iso_file_path).await?;
+	let file_paths_to_extract_document_data =
+		get_files_for_document_metadata_extraction(db, &iso_file_path).await?;
 
 	#[cfg(feature = "ai")]
 	let file_paths_for_labelling =
@@ -103,8 +108,10 @@ pub async fn old_shallow(
 	#[cfg(feature = "ai")]
 	let has_labels = !file_paths_for_labelling.is_empty();
 
-	let total_files =
-		file_paths_to_extract_exif_data.len() + file_paths_to_extract_ffmpeg_data.len();
+	let total_files = file_paths_to_extract_exif_data.len()
+		+ file_paths_to_extract_ffmpeg_data.len()
+		+ file_paths_to_extract_audio_data.len()
+		+ file_paths_to_extract_document_data.len();
 
 	let chunked_files_to_extract_exif_data = file_paths_to_extract_exif_data
 		.into_iter()
@@ -120,9 +127,26 @@ pub async fn old_shallow(
 		.map(Iterator::collect)
 		.collect::<Vec<Vec<_>>>();
 
+	let chunked_files_to_extract_audio_data = file_paths_to_extract_audio_data
+		.into_iter()
+		.chunks(BATCH_SIZE)
+		.into_iter()
+		.map(Iterator::collect)
+		.collect::<Vec<Vec<_>>>();
+
+	let chunked_files_to_extract_document_data = file_paths_to_extract_document_data
+		.into_iter()
+		.chunks(BATCH_SIZE)
+		.into_iter()
+		.map(Iterator::collect)
+		.collect::<Vec<Vec<_>>>();
+
 	debug!(
 		"Preparing to process {total_files} files in {} chunks",
-		chunked_files_to_extract_exif_data.len() + chunked_files_to_extract_ffmpeg_data.len()
+		chunked_files_to_extract_exif_data.len()
+			+ chunked_files_to_extract_ffmpeg_data.len()
+			+ chunked_files_to_extract_audio_data.len()
+			+ chunked_files_to_extract_document_data.len()
 	);
 
 	#[cfg(feature = "ai")]
@@ -167,9 +191,39 @@ pub async fn old_shallow(
 		}
 	}
 
+	for files in chunked_files_to_extract_audio_data {
+		let (more_run_metadata, errors) =
+			audio_metadata_extractor::process(&files, location.id, &location_path, db, &|_| {})
+				.await
+				.map_err(MediaProcessorError::from)?;
+
+		run_metadata.update(more_run_metadata.into());
+
+		if !errors.is_empty() {
+			error!("Errors processing chunk of audio tags shallow extraction:\n{errors}");
+		}
+	}
+
+	for files in chunked_files_to_extract_document_data {
+		let (more_run_metadata, errors) =
+			document_metadata_extractor::process(&files, location.id, &location_path, db, &|_| {})
+				.await
+				.map_err(MediaProcessorError::from)?;
+
+		run_metadata.update(more_run_metadata.into());
+
+		if !errors.is_empty() {
+			error!("Errors processing chunk of document metadata shallow extraction:\n{errors}");
+		}
+	}
+
 	debug!("Media shallow processor run metadata: {run_metadata:?}");
 
-	if run_metadata.exif_data.extracted > 0 || run_metadata.ffmpeg_data.extracted > 0 {
+	if run_metadata.exif_data.extracted > 0
+		|| run_metadata.ffmpeg_data.extracted > 0
+		|| run_metadata.audio_data.extracted > 0
+		|| run_metadata.document_data.extracted > 0
+	{
 		invalidate_query!(library, "search.paths");
 		invalidate_query!(library, "search.objects");
 	}
@@ -233,6 +287,32 @@ async fn get_files_for_ffmpeg_media_data_extraction(
 	.map_err(Into::into)
 }
 
+async fn get_files_for_audio_tags_extraction(
+	db: &PrismaClient,
+	parent_iso_file_path: &IsolatedFilePathData<'_>,
+) -> Result<Vec<file_path_for_media_processor::Data>, MediaProcessorError> {
+	get_files_by_extensions(
+		db,
+		parent_iso_file_path,
+		&audio_metadata_extractor::FILTERED_AUDIO_EXTENSIONS,
+	)
+	.await
+	.map_err(Into::into)
+}
+
+async fn get_files_for_document_metadata_extraction(
+	db: &PrismaClient,
+	parent_iso_file_path: &IsolatedFilePathData<'_>,
+) -> Result<Vec<file_path_for_media_processor::Data>, MediaProcessorError> {
+	get_files_by_extensions(
+		db,
+		parent_iso_file_path,
+		&document_metadata_extractor::FILTERED_DOCUMENT_EXTENSIONS,
+	)
+	.await
+	.map_err(Into::into)
+}
+
 #[cfg(feature = "ai")]
 async fn get_files_for_labeling(
 	db: &PrismaClient,
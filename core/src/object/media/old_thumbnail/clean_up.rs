@@ -3,13 +3,44 @@ use crate::library::LibraryId;
 use sd_prisma::prisma::{file_path, PrismaClient};
 use sd_utils::error::FileIOError;
 
-use std::{collections::HashSet, ffi::OsString, path::PathBuf, sync::Arc};
+use std::{
+	collections::HashSet,
+	ffi::OsString,
+	path::PathBuf,
+	sync::Arc,
+	time::{Duration, SystemTime},
+};
 
 use futures_concurrency::future::Join;
 use tokio::{fs, spawn};
 use tracing::{debug, error};
 
-use super::{ThumbnailerError, EPHEMERAL_DIR, WEBP_EXTENSION};
+use super::{
+	tier::PART_SUFFIX, ThumbnailerError, EPHEMERAL_DIR, THUMBSTRIP_INDEX_EXTENSION, WEBP_EXTENSION,
+};
+
+/// How long a partial thumbnail tier can stay around before we consider it abandoned,
+/// generating a tier never takes anywhere near as long
+const ABANDONED_PART_FILE_AGE: Duration = Duration::from_secs(60 * 60);
+
+/// Partial thumbnail tiers are left behind when the node stops while generating them
+async fn is_abandoned_part_file(thumb_entry: &fs::DirEntry) -> bool {
+	if !thumb_entry
+		.file_name()
+		.to_str()
+		.is_some_and(|file_name| file_name.ends_with(PART_SUFFIX))
+	{
+		return false;
+	}
+
+	thumb_entry
+		.metadata()
+		.await
+		.and_then(|metadata| metadata.modified())
+		.ok()
+		.and_then(|modified| SystemTime::now().duration_since(modified).ok())
+		.is_some_and(|age| age > ABANDONED_PART_FILE_AGE)
+}
 
 pub(super) async fn process_ephemeral_clean_up(
 	thumbnails_directory: Arc<PathBuf>,
@@ -46,9 +77,20 @@ pub(super) async fn process_ephemeral_clean_up(
 					.map_err(|e| FileIOError::from((&shard_path, e)))?
 				{
 					let thumb_path = thumb_entry.path();
-					if thumb_path.extension() == Some(WEBP_EXTENSION.as_ref())
-						&& !existing_ephemeral_thumbs.contains(&thumb_entry.file_name())
-					{
+
+					// Size tiers are stored as `<cas_id>.<size>.webp`, and they are stale
+					// whenever their `<cas_id>.webp` base thumbnail is
+					let is_stale = thumb_entry
+						.file_name()
+						.to_str()
+						.and_then(|file_name| file_name.split_once('.'))
+						.is_some_and(|(cas_id, extension)| {
+							extension.ends_with(WEBP_EXTENSION)
+								&& !existing_ephemeral_thumbs
+									.contains(&OsString::from(format!("{cas_id}.{WEBP_EXTENSION}")))
+						});
+
+					if is_stale || is_abandoned_part_file(&thumb_entry).await {
 						to_remove.push(async move {
 							debug!(
 								"Removing stale ephemeral thumbnail: {}",
@@ -99,12 +141,7 @@ pub(super) async fn process_indexed_clean_up(
 					.exec()
 					.await?
 					.into_iter()
-					.map(|file_path| {
-						OsString::from(format!(
-							"{}.webp",
-							file_path.cas_id.expect("we filtered right")
-						))
-					})
+					.map(|file_path| file_path.cas_id.expect("we filtered right"))
 					.collect::<HashSet<_>>();
 
 				let mut read_library_thumbs_dir = fs::read_dir(&library_thumbs_dir)
@@ -135,9 +172,22 @@ pub(super) async fn process_indexed_clean_up(
 							.map_err(|e| FileIOError::from((&shard_path, e)))?
 						{
 							let thumb_path = thumb_entry.path();
-							if thumb_path.extension() == Some(WEBP_EXTENSION.as_ref())
-								&& !existing_thumbs.contains(&thumb_entry.file_name())
-							{
+
+							// Besides `<cas_id>.webp`, we have size tiers stored as
+							// `<cas_id>.<size>.webp` and video files also have their preview media
+							// stored as `<cas_id>.thumbstrip.webp`, `<cas_id>.thumbstrip.json` and
+							// `<cas_id>.preview.webp`, so we only look at what's before the first dot
+							let is_stale = thumb_entry
+								.file_name()
+								.to_str()
+								.and_then(|file_name| file_name.split_once('.'))
+								.is_some_and(|(cas_id, extension)| {
+									(extension.ends_with(WEBP_EXTENSION)
+										|| extension.ends_with(THUMBSTRIP_INDEX_EXTENSION))
+										&& !existing_thumbs.contains(cas_id)
+								});
+
+							if is_stale || is_abandoned_part_file(&thumb_entry).await {
 								to_remove.push(async move {
 									debug!(
 										"Removing stale indexed thumbnail: {}",
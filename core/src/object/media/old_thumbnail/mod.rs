@@ -1,12 +1,16 @@
 use crate::{library::LibraryId, util::version_manager::VersionManagerError, Node};
 
 use sd_file_ext::extensions::{
-	DocumentExtension, Extension, ImageExtension, ALL_DOCUMENT_EXTENSIONS, ALL_IMAGE_EXTENSIONS,
+	BookExtension, DocumentExtension, Extension, FontExtension, ImageExtension, MeshExtension,
+	ALL_BOOK_EXTENSIONS, ALL_DOCUMENT_EXTENSIONS, ALL_FONT_EXTENSIONS, ALL_IMAGE_EXTENSIONS,
+	ALL_MESH_EXTENSIONS,
 };
 use sd_utils::error::FileIOError;
 
 #[cfg(feature = "ffmpeg")]
-use sd_file_ext::extensions::{VideoExtension, ALL_VIDEO_EXTENSIONS};
+use sd_file_ext::extensions::{
+	AudioExtension, VideoExtension, ALL_AUDIO_EXTENSIONS, ALL_VIDEO_EXTENSIONS,
+};
 
 use std::{
 	path::{Path, PathBuf},
@@ -26,6 +30,9 @@ pub mod preferences;
 mod process;
 mod shard;
 mod state;
+pub mod tier;
+#[cfg(feature = "ffmpeg")]
+pub mod video_preview;
 mod worker;
 
 pub use process::{BatchToProcess, GenerateThumbnailArgs};
@@ -38,6 +45,8 @@ const THUMBNAIL_CACHE_DIR_NAME: &str = "thumbnails";
 const SAVE_STATE_FILE: &str = "thumbs_to_process.bin";
 const VERSION_FILE: &str = "version.txt";
 pub const WEBP_EXTENSION: &str = "webp";
+/// Extension of the timing index stored alongside video thumbstrips
+pub const THUMBSTRIP_INDEX_EXTENSION: &str = "json";
 const EPHEMERAL_DIR: &str = "ephemeral";
 
 /// This is the target pixel count for all thumbnails to be resized to, and it is eventually downscaled
@@ -116,6 +125,16 @@ pub(super) static THUMBNAILABLE_VIDEO_EXTENSIONS: Lazy<Vec<Extension>> = Lazy::n
 		.collect()
 });
 
+#[cfg(feature = "ffmpeg")]
+pub(super) static THUMBNAILABLE_AUDIO_EXTENSIONS: Lazy<Vec<Extension>> = Lazy::new(|| {
+	ALL_AUDIO_EXTENSIONS
+		.iter()
+		.cloned()
+		.filter(can_generate_thumbnail_for_audio)
+		.map(Extension::Audio)
+		.collect()
+});
+
 pub(super) static THUMBNAILABLE_EXTENSIONS: Lazy<Vec<Extension>> = Lazy::new(|| {
 	ALL_IMAGE_EXTENSIONS
 		.iter()
@@ -129,6 +148,27 @@ pub(super) static THUMBNAILABLE_EXTENSIONS: Lazy<Vec<Extension>> = Lazy::new(||
 				.filter(can_generate_thumbnail_for_document)
 				.map(Extension::Document),
 		)
+		.chain(
+			ALL_FONT_EXTENSIONS
+				.iter()
+				.cloned()
+				.filter(can_generate_thumbnail_for_font)
+				.map(Extension::Font),
+		)
+		.chain(
+			ALL_MESH_EXTENSIONS
+				.iter()
+				.cloned()
+				.filter(can_generate_thumbnail_for_mesh)
+				.map(Extension::Mesh),
+		)
+		.chain(
+			ALL_BOOK_EXTENSIONS
+				.iter()
+				.cloned()
+				.filter(can_generate_thumbnail_for_book)
+				.map(Extension::Book),
+		)
 		.collect()
 });
 
@@ -138,6 +178,7 @@ pub(super) static ALL_THUMBNAILABLE_EXTENSIONS: Lazy<Vec<Extension>> = Lazy::new
 		.iter()
 		.cloned()
 		.chain(THUMBNAILABLE_VIDEO_EXTENSIONS.iter().cloned())
+		.chain(THUMBNAILABLE_AUDIO_EXTENSIONS.iter().cloned())
 		.collect();
 
 	#[cfg(not(feature = "ffmpeg"))]
@@ -189,12 +230,31 @@ pub const fn can_generate_thumbnail_for_video(video_extension: &VideoExtension)
 	!matches!(video_extension, Mpg | Swf | M2v | Hevc | M2ts | Mts | Ts)
 }
 
+/// Audio files only have a thumbnail if they have embedded cover art, which we only find out
+/// when trying to extract it, so we only try for formats that FFmpeg can read cover art from
+#[cfg(feature = "ffmpeg")]
+pub const fn can_generate_thumbnail_for_audio(audio_extension: &AudioExtension) -> bool {
+	use AudioExtension::*;
+
+	matches!(
+		audio_extension,
+		Mp3 | M4a | Flac | Ogg | Oga | Opus | Wma | Aiff | Aif | Wav | Wv | Tta
+	)
+}
+
 pub const fn can_generate_thumbnail_for_image(image_extension: &ImageExtension) -> bool {
 	use ImageExtension::*;
 
 	matches!(
 		image_extension,
-		Jpg | Jpeg | Png | Webp | Gif | Svg | Heic | Heics | Heif | Heifs | Avif | Bmp | Ico
+		Jpg | Jpeg
+			| Png | Webp | Gif
+			| Svg | Heic | Heics
+			| Heif | Heifs
+			| Avif | Bmp | Ico
+			| Dng | Cr2 | Cr3
+			| Nef | Arw | Raf
+			| Rw2
 	)
 }
 
@@ -203,3 +263,21 @@ pub const fn can_generate_thumbnail_for_document(document_extension: &DocumentEx
 
 	matches!(document_extension, Pdf)
 }
+
+pub const fn can_generate_thumbnail_for_font(font_extension: &FontExtension) -> bool {
+	use FontExtension::*;
+
+	matches!(font_extension, Ttf | Otf)
+}
+
+pub const fn can_generate_thumbnail_for_mesh(mesh_extension: &MeshExtension) -> bool {
+	use MeshExtension::*;
+
+	matches!(mesh_extension, Obj | Stl | Gltf | Glb)
+}
+
+pub const fn can_generate_thumbnail_for_book(book_extension: &BookExtension) -> bool {
+	use BookExtension::*;
+
+	matches!(book_extension, Epub)
+}
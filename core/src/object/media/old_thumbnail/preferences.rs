@@ -4,12 +4,16 @@ use specta::Type;
 #[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq, Type)]
 pub struct ThumbnailerPreferences {
 	background_processing_percentage: u8, // 0-100
+	// thumbstrips and animated previews for videos, which are a lot more expensive than thumbnails
+	#[serde(default)]
+	generate_video_previews: bool,
 }
 
 impl Default for ThumbnailerPreferences {
 	fn default() -> Self {
 		Self {
 			background_processing_percentage: 50, // 50% of CPU cores available
+			generate_video_previews: false,
 		}
 	}
 }
@@ -31,4 +35,14 @@ impl ThumbnailerPreferences {
 
 		self
 	}
+
+	pub fn generate_video_previews(&self) -> bool {
+		self.generate_video_previews
+	}
+
+	pub fn set_generate_video_previews(&mut self, generate_video_previews: bool) -> &mut Self {
+		self.generate_video_previews = generate_video_previews;
+
+		self
+	}
 }
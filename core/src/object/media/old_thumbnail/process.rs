@@ -1,6 +1,8 @@
 use crate::api::CoreEvent;
 
-use sd_file_ext::extensions::{DocumentExtension, ImageExtension};
+use sd_file_ext::extensions::{
+	BookExtension, DocumentExtension, FontExtension, ImageExtension, MeshExtension,
+};
 use sd_images::{format_image, scale_dimensions, ConvertibleExtension};
 use sd_media_metadata::exif::Orientation;
 use sd_prisma::prisma::location;
@@ -30,11 +32,16 @@ use tracing::{debug, error, trace, warn};
 use webp::Encoder;
 
 use super::{
-	can_generate_thumbnail_for_document, can_generate_thumbnail_for_image, get_thumb_key,
-	preferences::ThumbnailerPreferences, shard::get_shard_hex, ThumbnailKind, ThumbnailerError,
+	can_generate_thumbnail_for_book, can_generate_thumbnail_for_document,
+	can_generate_thumbnail_for_font, can_generate_thumbnail_for_image,
+	can_generate_thumbnail_for_mesh, get_thumb_key, preferences::ThumbnailerPreferences,
+	shard::get_shard_hex, tier::remove_derived_tiers, ThumbnailKind, ThumbnailerError,
 	EPHEMERAL_DIR, TARGET_PX, TARGET_QUALITY, THIRTY_SECS, WEBP_EXTENSION,
 };
 
+#[cfg(feature = "ffmpeg")]
+use super::tier::ThumbnailTier;
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct GenerateThumbnailArgs {
 	pub extension: String,
@@ -354,25 +361,53 @@ pub(super) async fn generate_thumbnail(
 
 	if let Ok(extension) = ImageExtension::from_str(extension) {
 		if can_generate_thumbnail_for_image(&extension) {
-			generate_image_thumbnail(&path, &output_path).await?;
+			generate_image_thumbnail(&path, &output_path, TARGET_PX).await?;
 		}
 	} else if let Ok(extension) = DocumentExtension::from_str(extension) {
 		if can_generate_thumbnail_for_document(&extension) {
-			generate_image_thumbnail(&path, &output_path).await?;
+			generate_image_thumbnail(&path, &output_path, TARGET_PX).await?;
+		}
+	} else if let Ok(extension) = FontExtension::from_str(extension) {
+		if can_generate_thumbnail_for_font(&extension) {
+			generate_image_thumbnail(&path, &output_path, TARGET_PX).await?;
+		}
+	} else if let Ok(extension) = MeshExtension::from_str(extension) {
+		if can_generate_thumbnail_for_mesh(&extension) {
+			generate_image_thumbnail(&path, &output_path, TARGET_PX).await?;
+		}
+	} else if let Ok(extension) = BookExtension::from_str(extension) {
+		if can_generate_thumbnail_for_book(&extension) {
+			generate_image_thumbnail(&path, &output_path, TARGET_PX).await?;
 		}
 	}
 
 	#[cfg(feature = "ffmpeg")]
 	{
-		use crate::object::media::old_thumbnail::can_generate_thumbnail_for_video;
-		use sd_file_ext::extensions::VideoExtension;
+		use crate::object::media::old_thumbnail::{
+			can_generate_thumbnail_for_audio, can_generate_thumbnail_for_video,
+		};
+		use sd_file_ext::extensions::{AudioExtension, VideoExtension};
 
 		if let Ok(extension) = VideoExtension::from_str(extension) {
 			if can_generate_thumbnail_for_video(&extension) {
-				generate_video_thumbnail(&path, &output_path).await?;
+				generate_video_thumbnail(&path, &output_path, ThumbnailTier::BASE).await?;
+			}
+		} else if let Ok(extension) = AudioExtension::from_str(extension) {
+			if can_generate_thumbnail_for_audio(&extension)
+				&& !generate_cover_art_thumbnail(&path, &output_path, ThumbnailTier::BASE).await?
+			{
+				// Nothing was generated, so there is no new thumbnail to notify about
+				trace!("No cover art found in {}", path.display());
+				return Ok(cas_id);
 			}
 		}
 	}
+
+	if should_regenerate {
+		// Other tiers are derived from the base thumbnail, so they are stale now
+		remove_derived_tiers(&output_path).await;
+	}
+
 	// This if is REALLY needed, due to the sheer performance of the thumbnailer,
 	// I restricted to only send events notifying for thumbnails in the current
 	// opened directory, sending events for the entire location turns into a
@@ -396,9 +431,10 @@ pub(super) async fn generate_thumbnail(
 	Ok(cas_id)
 }
 
-async fn generate_image_thumbnail(
+pub(super) async fn generate_image_thumbnail(
 	file_path: impl AsRef<Path>,
 	output_path: impl AsRef<Path>,
+	target_px: f32,
 ) -> Result<(), ThumbnailerError> {
 	let file_path = file_path.as_ref().to_path_buf();
 
@@ -409,7 +445,7 @@ async fn generate_image_thumbnail(
 		})?;
 
 		let (w, h) = img.dimensions();
-		let (w_scaled, h_scaled) = scale_dimensions(w as f32, h as f32, TARGET_PX);
+		let (w_scaled, h_scaled) = scale_dimensions(w as f32, h as f32, target_px);
 
 		// Optionally, resize the existing photo and convert back into DynamicImage
 		if w != w_scaled && h != h_scaled {
@@ -424,9 +460,9 @@ async fn generate_image_thumbnail(
 		// this corrects the rotation/flip of the image based on the *available* exif data
 		// not all images have exif data, so we don't error. we also don't rotate HEIF as that's against the spec
 		if let Some(orientation) = Orientation::from_path(&file_path) {
+			// Fonts, meshes and ebooks aren't convertible, and don't need to be rotated either
 			if ConvertibleExtension::try_from(file_path.as_ref())
-				.expect("we already checked if the image was convertible")
-				.should_rotate()
+				.is_ok_and(|extension| extension.should_rotate())
 			{
 				img = orientation.correct_thumbnail(img);
 			}
@@ -466,16 +502,36 @@ async fn generate_image_thumbnail(
 }
 
 #[cfg(feature = "ffmpeg")]
-async fn generate_video_thumbnail(
+pub(super) async fn generate_video_thumbnail(
 	file_path: impl AsRef<Path> + Send,
 	output_path: impl AsRef<Path> + Send,
+	tier: ThumbnailTier,
 ) -> Result<(), ThumbnailerError> {
 	use sd_ffmpeg::{to_thumbnail, ThumbnailSize};
 
 	to_thumbnail(
 		file_path,
 		output_path,
-		ThumbnailSize::Scale(1024),
+		ThumbnailSize::Scale(tier.max_dimension()),
+		TARGET_QUALITY,
+	)
+	.await
+	.map_err(Into::into)
+}
+
+/// Returns `false` if the audio file has no embedded cover art
+#[cfg(feature = "ffmpeg")]
+pub(super) async fn generate_cover_art_thumbnail(
+	file_path: impl AsRef<Path> + Send,
+	output_path: impl AsRef<Path> + Send,
+	tier: ThumbnailTier,
+) -> Result<bool, ThumbnailerError> {
+	use sd_ffmpeg::{to_cover_art_thumbnail, ThumbnailSize};
+
+	to_cover_art_thumbnail(
+		file_path,
+		output_path,
+		ThumbnailSize::Scale(tier.max_dimension()),
 		TARGET_QUALITY,
 	)
 	.await
@@ -0,0 +1,376 @@
+use crate::Node;
+
+use sd_core_file_path_helper::IsolatedFilePathData;
+use sd_core_prisma_helpers::file_path_to_full_path;
+use sd_file_ext::extensions::{
+	BookExtension, DocumentExtension, FontExtension, ImageExtension, MeshExtension,
+};
+use sd_images::scale_dimensions;
+use sd_prisma::prisma::file_path;
+use sd_utils::error::FileIOError;
+
+use std::{
+	collections::HashMap,
+	ops::Deref,
+	path::{Path, PathBuf},
+	str::FromStr,
+	sync::{Arc, Mutex, PoisonError},
+};
+
+use image::{imageops, DynamicImage, GenericImageView};
+use once_cell::sync::Lazy;
+use tokio::{fs, io, sync::Mutex as AsyncMutex, task::spawn_blocking};
+use tracing::{debug, warn};
+use uuid::Uuid;
+use webp::{Decoder, Encoder};
+
+use super::{
+	can_generate_thumbnail_for_book, can_generate_thumbnail_for_document,
+	can_generate_thumbnail_for_font, can_generate_thumbnail_for_image,
+	can_generate_thumbnail_for_mesh, process::generate_image_thumbnail, ThumbnailerError,
+	TARGET_QUALITY, WEBP_EXTENSION,
+};
+
+/// Suffix of the temporary files tiers are written to before being moved in place
+pub(super) const PART_SUFFIX: &str = ".part.webp";
+
+/// Tiers being generated, by their path, so concurrent requests for the same tier wait for it
+/// instead of generating it again
+static GENERATING_TIERS: Lazy<Mutex<HashMap<PathBuf, Arc<AsyncMutex<()>>>>> =
+	Lazy::new(Mutex::default);
+
+/// Sizes thumbnails are available in.
+///
+/// Only the [`ThumbnailTier::BASE`] tier is generated by the thumbnailer, as `<cas_id>.webp`.
+/// The others are stored alongside it as `<cas_id>.<size>.webp` and generated on demand: smaller
+/// ones are downscaled from the base thumbnail, while bigger ones need the original file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ThumbnailTier {
+	Small,
+	Medium,
+	Large,
+	ExtraLarge,
+}
+
+impl ThumbnailTier {
+	pub const ALL: [Self; 4] = [Self::Small, Self::Medium, Self::Large, Self::ExtraLarge];
+
+	pub const BASE: Self = Self::Large;
+
+	pub const fn max_dimension(self) -> u32 {
+		match self {
+			Self::Small => 128,
+			Self::Medium => 512,
+			Self::Large => 1024,
+			Self::ExtraLarge => 2048,
+		}
+	}
+
+	/// The smallest tier that is at least `size` pixels, so it never has to be upscaled
+	pub fn for_size(size: u32) -> Self {
+		Self::ALL
+			.into_iter()
+			.find(|tier| tier.max_dimension() >= size)
+			.unwrap_or(Self::ExtraLarge)
+	}
+
+	fn target_px(self) -> f32 {
+		(self.max_dimension() * self.max_dimension()) as f32
+	}
+
+	/// Path of this tier, given the path of the base thumbnail
+	pub fn path(self, base_thumbnail_path: &Path) -> PathBuf {
+		if self == Self::BASE {
+			base_thumbnail_path.to_path_buf()
+		} else {
+			base_thumbnail_path.with_extension(format!("{}.{WEBP_EXTENSION}", self.max_dimension()))
+		}
+	}
+}
+
+/// Removes every tier derived from the base thumbnail, as they have to follow it when it's regenerated
+pub(super) async fn remove_derived_tiers(base_thumbnail_path: &Path) {
+	for tier in ThumbnailTier::ALL
+		.into_iter()
+		.filter(|tier| *tier != ThumbnailTier::BASE)
+	{
+		let tier_path = tier.path(base_thumbnail_path);
+		if let Err(e) = fs::remove_file(&tier_path).await {
+			if e.kind() != io::ErrorKind::NotFound {
+				warn!(
+					"Failed to remove stale thumbnail tier at '{}': {e:#?}",
+					tier_path.display()
+				);
+			}
+		}
+	}
+}
+
+/// Generates the requested tier of a thumbnail, returning the path to be served.
+///
+/// If the tier can't be generated, like a bigger tier for a file that isn't available on this
+/// node, we fall back to the base thumbnail.
+pub async fn generate_tier(
+	node: &Node,
+	base_thumbnail_path: &Path,
+	tier: ThumbnailTier,
+) -> Result<PathBuf, ThumbnailerError> {
+	let tier_path = tier.path(base_thumbnail_path);
+
+	let generating = GENERATING_TIERS
+		.lock()
+		.unwrap_or_else(PoisonError::into_inner)
+		.entry(tier_path.clone())
+		.or_default()
+		.clone();
+
+	let res = {
+		let _generating_guard = generating.lock().await;
+
+		// Another request may have generated it while we were waiting for it
+		if fs::metadata(&tier_path).await.is_ok() {
+			Ok(tier_path.clone())
+		} else {
+			generate_tier_to(node, base_thumbnail_path, tier, &tier_path).await
+		}
+	};
+
+	let mut generating_tiers = GENERATING_TIERS
+		.lock()
+		.unwrap_or_else(PoisonError::into_inner);
+
+	// Nobody else is waiting for this tier when only we and the map have it
+	if Arc::strong_count(&generating) == 2 {
+		generating_tiers.remove(&tier_path);
+	}
+
+	res
+}
+
+async fn generate_tier_to(
+	node: &Node,
+	base_thumbnail_path: &Path,
+	tier: ThumbnailTier,
+	tier_path: &Path,
+) -> Result<PathBuf, ThumbnailerError> {
+	// Writing to a temporary file first, so concurrent requests never serve a partial thumbnail,
+	// named uniquely so a request can never write over the one of another
+	let part_path = base_thumbnail_path.with_extension(format!(
+		"{}.{}{PART_SUFFIX}",
+		tier.max_dimension(),
+		Uuid::new_v4().simple()
+	));
+
+	let generated = if tier < ThumbnailTier::BASE {
+		downscale_base_thumbnail(base_thumbnail_path, &part_path, tier)
+			.await
+			.map(|()| true)
+	} else if let Some((source_path, extension)) = find_source(node, base_thumbnail_path).await {
+		generate_from_source(&source_path, &extension, &part_path, tier).await
+	} else {
+		Ok(false)
+	};
+
+	let generated = match generated {
+		Ok(generated) => generated,
+		Err(e) => {
+			remove_part_file(&part_path).await;
+			return Err(e);
+		}
+	};
+
+	if !generated {
+		remove_part_file(&part_path).await;
+		debug!(
+			"No source available for thumbnail tier {tier:?} of '{}', falling back to base tier",
+			base_thumbnail_path.display()
+		);
+		return Ok(base_thumbnail_path.to_path_buf());
+	}
+
+	if let Err(e) = fs::rename(&part_path, tier_path).await {
+		remove_part_file(&part_path).await;
+		return Err(FileIOError::from((tier_path, e)).into());
+	}
+
+	Ok(tier_path.to_path_buf())
+}
+
+/// Removes what a failed generation left behind, anything missed here is removed by the clean up
+async fn remove_part_file(part_path: &Path) {
+	if let Err(e) = fs::remove_file(part_path).await {
+		if e.kind() != io::ErrorKind::NotFound {
+			warn!(
+				"Failed to remove partial thumbnail tier at '{}': {e:#?}",
+				part_path.display()
+			);
+		}
+	}
+}
+
+async fn downscale_base_thumbnail(
+	base_thumbnail_path: &Path,
+	output_path: &Path,
+	tier: ThumbnailTier,
+) -> Result<(), ThumbnailerError> {
+	let bytes = fs::read(base_thumbnail_path)
+		.await
+		.map_err(|e| FileIOError::from((base_thumbnail_path, e)))?;
+
+	let webp = spawn_blocking({
+		let base_thumbnail_path = base_thumbnail_path.to_path_buf();
+		move || -> Result<_, ThumbnailerError> {
+			let img = Decoder::new(&bytes)
+				.decode()
+				.ok_or_else(|| ThumbnailerError::WebPEncoding {
+					path: base_thumbnail_path.clone().into_boxed_path(),
+					reason: "failed to decode base thumbnail".to_string(),
+				})?
+				.to_image();
+
+			let (w, h) = img.dimensions();
+			let (w_scaled, h_scaled) = scale_dimensions(w as f32, h as f32, tier.target_px());
+
+			let img = DynamicImage::ImageRgba8(imageops::resize(
+				&img,
+				w_scaled,
+				h_scaled,
+				imageops::FilterType::Triangle,
+			));
+
+			let encoder =
+				Encoder::from_image(&img).map_err(|reason| ThumbnailerError::WebPEncoding {
+					path: base_thumbnail_path.into_boxed_path(),
+					reason: reason.to_string(),
+				})?;
+
+			// Type WebPMemory is !Send, so we copy it to a Vec<u8> before leaving the blocking task
+			Ok(encoder.encode(TARGET_QUALITY).deref().to_owned())
+		}
+	})
+	.await??;
+
+	fs::write(output_path, &webp)
+		.await
+		.map_err(|e| FileIOError::from((output_path, e)).into())
+}
+
+/// Returns `false` if we can't generate thumbnails for this kind of file
+async fn generate_from_source(
+	source_path: &Path,
+	extension: &str,
+	output_path: &Path,
+	tier: ThumbnailTier,
+) -> Result<bool, ThumbnailerError> {
+	if ImageExtension::from_str(extension).is_ok_and(|ext| can_generate_thumbnail_for_image(&ext))
+		|| DocumentExtension::from_str(extension)
+			.is_ok_and(|ext| can_generate_thumbnail_for_document(&ext))
+		|| FontExtension::from_str(extension).is_ok_and(|ext| can_generate_thumbnail_for_font(&ext))
+		|| MeshExtension::from_str(extension).is_ok_and(|ext| can_generate_thumbnail_for_mesh(&ext))
+		|| BookExtension::from_str(extension).is_ok_and(|ext| can_generate_thumbnail_for_book(&ext))
+	{
+		return generate_image_thumbnail(source_path, output_path, tier.target_px())
+			.await
+			.map(|()| true);
+	}
+
+	#[cfg(feature = "ffmpeg")]
+	{
+		use super::{
+			can_generate_thumbnail_for_audio, can_generate_thumbnail_for_video,
+			process::{generate_cover_art_thumbnail, generate_video_thumbnail},
+		};
+		use sd_file_ext::extensions::{AudioExtension, VideoExtension};
+
+		if VideoExtension::from_str(extension)
+			.is_ok_and(|ext| can_generate_thumbnail_for_video(&ext))
+		{
+			return generate_video_thumbnail(source_path, output_path, tier)
+				.await
+				.map(|()| true);
+		}
+
+		if AudioExtension::from_str(extension)
+			.is_ok_and(|ext| can_generate_thumbnail_for_audio(&ext))
+		{
+			return generate_cover_art_thumbnail(source_path, output_path, tier).await;
+		}
+	}
+
+	Ok(false)
+}
+
+/// Looks for a file in this node that the thumbnail was generated from, using the library and
+/// `cas_id` from the thumbnail path, as in `<library_id>/<shard>/<cas_id>.webp`.
+///
+/// Ephemeral thumbnails have no library, so there's no way to find their source.
+async fn find_source(node: &Node, base_thumbnail_path: &Path) -> Option<(PathBuf, String)> {
+	let cas_id = base_thumbnail_path.file_stem()?.to_str()?;
+	let library_id = base_thumbnail_path
+		.parent()?
+		.parent()?
+		.file_name()?
+		.to_str()
+		.and_then(|library_id| Uuid::from_str(library_id).ok())?;
+
+	let library = node.libraries.get_library(&library_id).await?;
+
+	let file_paths = library
+		.db
+		.file_path()
+		.find_many(vec![file_path::cas_id::equals(Some(cas_id.to_string()))])
+		.select(file_path_to_full_path::select())
+		.exec()
+		.await
+		.map_err(|e| warn!("Failed to fetch file paths for cas_id '{cas_id}': {e:#?}"))
+		.ok()?;
+
+	// Any of them will do, as long as it's in a location on this node
+	for file_path in file_paths {
+		let Some(location) = &file_path.location else {
+			continue;
+		};
+		let Some(location_path) = &location.path else {
+			continue;
+		};
+		let Ok(iso_file_path) = IsolatedFilePathData::try_from((location.id, &file_path)) else {
+			continue;
+		};
+
+		let path = Path::new(location_path).join(&iso_file_path);
+		if fs::metadata(&path).await.is_ok() {
+			return Some((path, iso_file_path.extension().to_string()));
+		}
+	}
+
+	None
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn picks_smallest_tier_that_fits() {
+		assert_eq!(ThumbnailTier::for_size(0), ThumbnailTier::Small);
+		assert_eq!(ThumbnailTier::for_size(128), ThumbnailTier::Small);
+		assert_eq!(ThumbnailTier::for_size(129), ThumbnailTier::Medium);
+		assert_eq!(ThumbnailTier::for_size(1024), ThumbnailTier::Large);
+		assert_eq!(ThumbnailTier::for_size(10_000), ThumbnailTier::ExtraLarge);
+	}
+
+	#[test]
+	fn tier_paths() {
+		let base = Path::new("thumbnails/ephemeral/abc/abcdef.webp");
+
+		assert_eq!(ThumbnailTier::BASE.path(base), base);
+		assert_eq!(
+			ThumbnailTier::Small.path(base),
+			Path::new("thumbnails/ephemeral/abc/abcdef.128.webp")
+		);
+		assert_eq!(
+			ThumbnailTier::ExtraLarge.path(base),
+			Path::new("thumbnails/ephemeral/abc/abcdef.2048.webp")
+		);
+	}
+}
@@ -0,0 +1,149 @@
+use crate::{library::LibraryId, Node};
+
+use sd_ffmpeg::{to_animated_preview, to_thumbstrip, ThumbnailSize, Thumbstrip};
+use sd_utils::error::FileIOError;
+
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use tokio::fs;
+
+use super::{
+	get_indexed_thumbnail_path, ThumbnailerError, THUMBSTRIP_INDEX_EXTENSION, WEBP_EXTENSION,
+};
+
+/// How many frames we sample from the video for the scrubbing thumbstrip
+const THUMBSTRIP_FRAMES: u32 = 20;
+/// Thumbstrip frames are small, they are only shown while hovering over a video
+const THUMBSTRIP_FRAME_SIZE: ThumbnailSize = ThumbnailSize::Scale(240);
+
+/// How many frames the animated preview loops through
+const PREVIEW_FRAMES: u32 = 8;
+const PREVIEW_FRAME_SIZE: ThumbnailSize = ThumbnailSize::Scale(320);
+const PREVIEW_FRAME_DURATION_MS: u32 = 600;
+
+/// Preview media is way bigger than a single thumbnail, so we trade some quality for size
+const PREVIEW_QUALITY: f32 = 50.0;
+
+const THUMBSTRIP_SUFFIX: &str = "thumbstrip";
+const PREVIEW_SUFFIX: &str = "preview";
+
+/// Where a frame is in the video and in the thumbstrip sprite sheet
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ThumbstripFrame {
+	/// Position of the frame in the video, in seconds
+	pub timestamp: f64,
+	pub x: u32,
+	pub y: u32,
+}
+
+/// Timing index stored as `<cas_id>.thumbstrip.json` alongside the `<cas_id>.thumbstrip.webp`
+/// sprite sheet, so the frontend can pick a frame according to the cursor position
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ThumbstripIndex {
+	pub frame_width: u32,
+	pub frame_height: u32,
+	/// Duration of the video, in seconds
+	pub duration: f64,
+	pub frames: Vec<ThumbstripFrame>,
+}
+
+impl From<Thumbstrip> for ThumbstripIndex {
+	fn from(
+		Thumbstrip {
+			frame_width,
+			frame_height,
+			duration,
+			frames,
+		}: Thumbstrip,
+	) -> Self {
+		Self {
+			frame_width,
+			frame_height,
+			duration,
+			frames: frames
+				.into_iter()
+				.map(|frame| ThumbstripFrame {
+					timestamp: frame.timestamp,
+					x: frame.x,
+					y: frame.y,
+				})
+				.collect(),
+		}
+	}
+}
+
+/// Turns `<shard>/<cas_id>.webp` into `<shard>/<cas_id>.<suffix>.<extension>`
+fn with_suffix(thumbnail_path: &Path, suffix: &str, extension: &str) -> PathBuf {
+	thumbnail_path.with_extension(format!("{suffix}.{extension}"))
+}
+
+pub fn get_thumbstrip_path(node: &Node, cas_id: &str, library_id: LibraryId) -> PathBuf {
+	with_suffix(
+		&get_indexed_thumbnail_path(node, cas_id, library_id),
+		THUMBSTRIP_SUFFIX,
+		WEBP_EXTENSION,
+	)
+}
+
+pub fn get_thumbstrip_index_path(node: &Node, cas_id: &str, library_id: LibraryId) -> PathBuf {
+	with_suffix(
+		&get_indexed_thumbnail_path(node, cas_id, library_id),
+		THUMBSTRIP_SUFFIX,
+		THUMBSTRIP_INDEX_EXTENSION,
+	)
+}
+
+pub fn get_video_preview_path(node: &Node, cas_id: &str, library_id: LibraryId) -> PathBuf {
+	with_suffix(
+		&get_indexed_thumbnail_path(node, cas_id, library_id),
+		PREVIEW_SUFFIX,
+		WEBP_EXTENSION,
+	)
+}
+
+/// Generates the scrubbing thumbstrip, with its timing index, for a video file
+pub async fn generate_thumbstrip(
+	node: &Node,
+	cas_id: &str,
+	library_id: LibraryId,
+	video_path: impl AsRef<Path> + Send,
+) -> Result<(), ThumbnailerError> {
+	let thumbstrip = to_thumbstrip(
+		video_path,
+		get_thumbstrip_path(node, cas_id, library_id),
+		THUMBSTRIP_FRAMES,
+		THUMBSTRIP_FRAME_SIZE,
+		PREVIEW_QUALITY,
+	)
+	.await?;
+
+	let index_path = get_thumbstrip_index_path(node, cas_id, library_id);
+
+	fs::write(
+		&index_path,
+		serde_json::to_vec(&ThumbstripIndex::from(thumbstrip))
+			.expect("ThumbstripIndex is always serializable"),
+	)
+	.await
+	.map_err(|e| FileIOError::from((index_path, e)).into())
+}
+
+/// Generates the looping animated preview shown on hover for a video file
+pub async fn generate_video_preview(
+	node: &Node,
+	cas_id: &str,
+	library_id: LibraryId,
+	video_path: impl AsRef<Path> + Send,
+) -> Result<(), ThumbnailerError> {
+	to_animated_preview(
+		video_path,
+		get_video_preview_path(node, cas_id, library_id),
+		PREVIEW_FRAMES,
+		PREVIEW_FRAME_SIZE,
+		PREVIEW_FRAME_DURATION_MS,
+		PREVIEW_QUALITY,
+	)
+	.await
+	.map_err(Into::into)
+}
@@ -0,0 +1,113 @@
+//! Perceptual hashes used to find visually similar images and videos.
+//!
+//! We use a 64 bits difference hash (dHash) computed from the thumbnail instead of the original
+//! file, so every kind of file we can thumbnail gets hashed the same way, videos included, as their
+//! thumbnail is a keyframe. Resized, recompressed or slightly edited copies of the same image end
+//! up with hashes within a small Hamming distance of each other.
+
+use sd_file_ext::extensions::Extension;
+
+use image::{imageops::FilterType, DynamicImage};
+use once_cell::sync::Lazy;
+use webp::Decoder;
+
+use super::old_thumbnail;
+
+/// The hash is computed over a 9x8 grayscale version of the image, comparing each pixel with
+/// its right neighbor, giving us 8x8 bits
+const HASH_WIDTH: u32 = 9;
+const HASH_HEIGHT: u32 = 8;
+
+/// Documents, fonts and the like are thumbnailed too, but they all look alike after being
+/// shrunk to 9x8 pixels, so we only hash images and videos
+pub(super) static HASHABLE_EXTENSIONS: Lazy<Vec<Extension>> = Lazy::new(|| {
+	let images = old_thumbnail::THUMBNAILABLE_EXTENSIONS
+		.iter()
+		.filter(|ext| matches!(ext, Extension::Image(_)))
+		.cloned();
+
+	#[cfg(feature = "ffmpeg")]
+	return images
+		.chain(
+			old_thumbnail::THUMBNAILABLE_VIDEO_EXTENSIONS
+				.iter()
+				.cloned(),
+		)
+		.collect();
+
+	#[cfg(not(feature = "ffmpeg"))]
+	images.collect()
+});
+
+pub fn dhash(img: &DynamicImage) -> u64 {
+	let pixels = img
+		.resize_exact(HASH_WIDTH, HASH_HEIGHT, FilterType::Triangle)
+		.into_luma8();
+
+	let mut hash = 0;
+	for y in 0..HASH_HEIGHT {
+		for x in 0..HASH_WIDTH - 1 {
+			hash <<= 1;
+			if pixels.get_pixel(x, y)[0] < pixels.get_pixel(x + 1, y)[0] {
+				hash |= 1;
+			}
+		}
+	}
+
+	hash
+}
+
+/// Decodes a webp thumbnail and hashes it, returns `None` if the thumbnail can't be decoded
+pub fn hash_thumbnail(webp_bytes: &[u8]) -> Option<u64> {
+	Decoder::new(webp_bytes)
+		.decode()
+		.map(|webp| dhash(&webp.to_image()))
+}
+
+pub const fn hamming_distance(a: u64, b: u64) -> u32 {
+	(a ^ b).count_ones()
+}
+
+/// Hashes are stored as big endian bytes, as SQLite integers are signed
+pub fn to_bytes(hash: u64) -> Vec<u8> {
+	hash.to_be_bytes().to_vec()
+}
+
+pub fn from_bytes(bytes: &[u8]) -> Option<u64> {
+	bytes.try_into().ok().map(u64::from_be_bytes)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	use image::{GrayImage, Luma};
+
+	fn gradient(width: u32, height: u32, reversed: bool) -> DynamicImage {
+		DynamicImage::ImageLuma8(GrayImage::from_fn(width, height, |x, _| {
+			let value = (x * 255 / (width - 1)) as u8;
+			Luma([if reversed { 255 - value } else { value }])
+		}))
+	}
+
+	#[test]
+	fn resized_copies_hash_alike() {
+		let original = dhash(&gradient(640, 480, false));
+
+		assert_eq!(
+			hamming_distance(original, dhash(&gradient(64, 48, false))),
+			0
+		);
+		assert_eq!(original, u64::MAX);
+		assert_eq!(dhash(&gradient(640, 480, true)), 0);
+	}
+
+	#[test]
+	fn bytes_roundtrip() {
+		let hash = 0xDEAD_BEEF_0123_4567;
+
+		assert_eq!(from_bytes(&to_bytes(hash)), Some(hash));
+		assert_eq!(from_bytes(&[1, 2, 3]), None);
+		assert_eq!(hamming_distance(0b1011, 0b0110), 3);
+	}
+}
@@ -0,0 +1,148 @@
+//! Text subtitles of videos, embedded in them or from sidecar files next to them, stored so we can
+//! serve them as WebVTT tracks and search for a line of dialogue.
+
+use sd_media_metadata::ffmpeg::subtitle::SubtitleTrack;
+use sd_prisma::prisma::{media_subtitle, object, PrismaClient};
+use sd_utils::error::FileIOError;
+
+use std::path::Path;
+
+use prisma_client_rust::QueryError;
+use tokio::fs;
+use tracing::{error, warn};
+
+use super::ffmpeg_metadata_extractor::FFmpegDataError;
+
+pub const SIDECAR_EXTENSIONS: [&str; 4] = ["srt", "ass", "ssa", "vtt"];
+
+/// Whether `file_name` is a sidecar subtitle file of the video with `video_stem`, like
+/// `movie.srt` or `movie.pt-BR.srt` for `movie.mkv`, along with the language in its name
+pub fn sidecar_language(video_stem: &str, file_name: &str) -> Option<Option<String>> {
+	let (name, extension) = file_name.rsplit_once('.')?;
+
+	if !SIDECAR_EXTENSIONS
+		.iter()
+		.any(|sidecar_extension| extension.eq_ignore_ascii_case(sidecar_extension))
+	{
+		return None;
+	}
+
+	if name == video_stem {
+		return Some(None);
+	}
+
+	name.strip_prefix(video_stem)
+		.and_then(|language| language.strip_prefix('.'))
+		.filter(|language| !language.is_empty())
+		.map(|language| Some(language.to_string()))
+}
+
+/// Embedded subtitle tracks of a video along with the ones from its sidecar files, which are
+/// returned with their file name
+pub async fn extract_subtitles(
+	video_path: impl AsRef<Path> + Send,
+) -> Result<Vec<(Option<String>, SubtitleTrack)>, FFmpegDataError> {
+	let video_path = video_path.as_ref();
+
+	let mut tracks = SubtitleTrack::from_path(video_path)
+		.await?
+		.into_iter()
+		.map(|track| (None, track))
+		.collect::<Vec<_>>();
+
+	let (Some(video_stem), Some(parent)) = (
+		video_path.file_stem().and_then(|stem| stem.to_str()),
+		video_path.parent(),
+	) else {
+		return Ok(tracks);
+	};
+
+	let mut read_dir = match fs::read_dir(parent).await {
+		Ok(read_dir) => read_dir,
+		Err(e) => {
+			warn!("{}", FileIOError::from((parent, e)));
+			return Ok(tracks);
+		}
+	};
+
+	while let Ok(Some(entry)) = read_dir.next_entry().await {
+		let Some(file_name) = entry.file_name().to_str().map(str::to_string) else {
+			continue;
+		};
+
+		let Some(language) = sidecar_language(video_stem, &file_name) else {
+			continue;
+		};
+
+		match SubtitleTrack::from_path(entry.path()).await {
+			Ok(sidecar_tracks) => tracks.extend(sidecar_tracks.into_iter().map(|mut track| {
+				track.stream_id = None;
+				track.language = language.clone().or(track.language);
+				(Some(file_name.clone()), track)
+			})),
+			Err(e) => warn!(
+				"Failed to read subtitle sidecar file '{}': {e:#?}",
+				entry.path().display()
+			),
+		}
+	}
+
+	Ok(tracks)
+}
+
+pub async fn save_subtitles(
+	object_id: object::id::Type,
+	tracks: Vec<(Option<String>, SubtitleTrack)>,
+	db: &PrismaClient,
+) -> Result<(), QueryError> {
+	db._batch((
+		db.media_subtitle()
+			.delete_many(vec![media_subtitle::object_id::equals(object_id)]),
+		db.media_subtitle().create_many(
+			tracks
+				.into_iter()
+				.filter_map(|(sidecar, track)| {
+					let text = track.text();
+					serde_json::to_vec(&track.cues)
+						.map_err(|e| error!("Failed to serialize subtitle cues: {e:#?}"))
+						.ok()
+						.map(|cues| media_subtitle::CreateUnchecked {
+							cues,
+							text,
+							object_id,
+							_params: vec![
+								media_subtitle::stream_id::set(track.stream_id),
+								media_subtitle::sidecar::set(sidecar),
+								media_subtitle::language::set(track.language),
+								media_subtitle::title::set(track.title),
+							],
+						})
+				})
+				.collect(),
+		),
+	))
+	.await
+	.map(|_| ())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn matches_sidecar_files() {
+		assert_eq!(sidecar_language("movie", "movie.srt"), Some(None));
+		assert_eq!(
+			sidecar_language("movie", "movie.pt-BR.SRT"),
+			Some(Some("pt-BR".to_string()))
+		);
+		assert_eq!(
+			sidecar_language("movie.2019", "movie.2019.en.ass"),
+			Some(Some("en".to_string()))
+		);
+		assert_eq!(sidecar_language("movie", "movie.mkv"), None);
+		assert_eq!(sidecar_language("movie", "movies.srt"), None);
+		assert_eq!(sidecar_language("movie", "other.en.srt"), None);
+		assert_eq!(sidecar_language("movie", "movie..srt"), None);
+	}
+}
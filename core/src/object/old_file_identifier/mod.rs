@@ -1,4 +1,12 @@
-use crate::{library::Library, object::cas::generate_cas_id, old_job::JobError};
+use crate::{
+	invalidate_query,
+	library::Library,
+	object::{
+		cas::generate_cas_id,
+		tag::desktop::{import_desktop_tags, DesktopTagsPolicy},
+	},
+	old_job::JobError,
+};
 
 use sd_core_file_path_helper::{FilePathError, IsolatedFilePathData};
 use sd_core_prisma_helpers::{file_path_for_file_identifier, object_for_file_identifier};
@@ -90,10 +98,31 @@ impl FileMetadata {
 			fs_metadata,
 		})
 	}
+
+	/// Assembles `create_unchecked` params for a symbolic link indexed as an alias,
+	/// without reading through the link
+	pub async fn new_alias(
+		location_path: impl AsRef<Path>,
+		iso_file_path: &IsolatedFilePathData<'_>,
+	) -> Result<FileMetadata, FileIOError> {
+		let path = location_path.as_ref().join(iso_file_path);
+
+		let fs_metadata = fs::symlink_metadata(&path)
+			.await
+			.map_err(|e| FileIOError::from((&path, e)))?;
+
+		trace!("Analyzed alias: {path:?}");
+
+		Ok(FileMetadata {
+			cas_id: None,
+			kind: ObjectKind::Alias,
+			fs_metadata,
+		})
+	}
 }
 
 async fn identifier_job_step(
-	Library { db, sync, .. }: &Library,
+	library @ Library { db, sync, .. }: &Library,
 	location: &location::Data,
 	file_paths: &[file_path_for_file_identifier::Data],
 ) -> Result<(usize, usize), JobError> {
@@ -109,33 +138,35 @@ async fn identifier_job_step(
 					.ok()
 			})
 			.map(|(iso_file_path, file_path)| async move {
-				FileMetadata::new(&location_path, &iso_file_path)
-					.await
-					.map(|metadata| {
-						(
-							// SAFETY: This should never happen
-							Uuid::from_slice(&file_path.pub_id)
-								.expect("file_path.pub_id is invalid!"),
-							(metadata, file_path),
-						)
-					})
-					.map_err(|e| {
-						#[cfg(target_os = "windows")]
-						{
-							// Handle case where file is on-demand (NTFS only)
-							if e.source.raw_os_error().map_or(false, |code| code == 362) {
-								error!("Failed to extract metadata from on-demand file: {e:#?}");
-							} else {
-								error!("Failed to extract file metadata: {e:#?}")
-							}
+				if file_path.symlink_target.is_some() {
+					FileMetadata::new_alias(&location_path, &iso_file_path).await
+				} else {
+					FileMetadata::new(&location_path, &iso_file_path).await
+				}
+				.map(|metadata| {
+					(
+						// SAFETY: This should never happen
+						Uuid::from_slice(&file_path.pub_id).expect("file_path.pub_id is invalid!"),
+						(metadata, file_path),
+					)
+				})
+				.map_err(|e| {
+					#[cfg(target_os = "windows")]
+					{
+						// Handle case where file is on-demand (NTFS only)
+						if e.source.raw_os_error().map_or(false, |code| code == 362) {
+							error!("Failed to extract metadata from on-demand file: {e:#?}");
+						} else {
+							error!("Failed to extract file metadata: {e:#?}")
 						}
+					}
 
-						#[cfg(not(target_os = "windows"))]
-						{
-							error!("Failed to extract file metadata: {e:#?}");
-						}
-					})
-					.ok()
+					#[cfg(not(target_os = "windows"))]
+					{
+						error!("Failed to extract file metadata: {e:#?}");
+					}
+				})
+				.ok()
 			}),
 	)
 	.await
@@ -343,6 +374,25 @@ async fn identifier_job_step(
 		0
 	};
 
+	if DesktopTagsPolicy::from(location.desktop_tags_policy) != DesktopTagsPolicy::Ignore {
+		let imported = import_desktop_tags(
+			library,
+			file_paths
+				.iter()
+				.filter(|file_path| file_path.xattrs.is_some())
+				.map(|file_path| file_path.pub_id.clone())
+				.collect(),
+		)
+		.await?;
+
+		if imported > 0 {
+			trace!("Imported {imported} tags from extended attributes");
+			invalidate_query!(library, "tags.getForObject");
+			invalidate_query!(library, "tags.getWithObjects");
+			invalidate_query!(library, "tags.list");
+		}
+	}
+
 	Ok((total_created, updated_file_paths.len()))
 }
 
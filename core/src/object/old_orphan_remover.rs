@@ -62,7 +62,10 @@ impl OrphanRemoverActor {
 		loop {
 			let Ok(objects_ids) = db
 				.object()
-				.find_many(vec![object::file_paths::none(vec![])])
+				.find_many(vec![
+					object::file_paths::none(vec![]),
+					object::trash_items::none(vec![]),
+				])
 				.take(512)
 				.select(object::select!({ id }))
 				.exec()
@@ -0,0 +1,489 @@
+//! Tags kept by other tools in extended attributes, freedesktop's `user.xdg.tags` (used by Dolphin and Baloo)
+//! and Finder's `com.apple.metadata:_kMDItemUserTags`, which survives on copies of macOS volumes mounted elsewhere.
+
+use crate::library::Library;
+
+use sd_core_file_path_helper::{
+	extended_metadata::{decode_xattrs, read_xattrs, write_xattr},
+	IsolatedFilePathData,
+};
+use sd_core_prisma_helpers::file_path_for_desktop_tags;
+
+use sd_prisma::{
+	prisma::{file_path, location, object, tag, tag_on_object},
+	prisma_sync,
+};
+use sd_sync::OperationFactory;
+use sd_utils::{
+	db::{maybe_missing, MissingFieldError},
+	error::FileIOError,
+	msgpack,
+};
+
+use std::{
+	collections::{BTreeSet, HashMap, HashSet},
+	io,
+	path::{Path, PathBuf},
+};
+
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use tracing::{error, trace, warn};
+
+use super::TagCreateArgs;
+
+pub const XDG_TAGS_XATTR: &str = "user.xdg.tags";
+pub const FINDER_TAGS_XATTR: &str = "com.apple.metadata:_kMDItemUserTags";
+
+/// Colors of Finder's tag labels, by the index stored with each tag.
+const FINDER_COLORS: [&str; 8] = [
+	DEFAULT_COLOR,
+	"#8E8E93", // Gray
+	"#34C759", // Green
+	"#AF52DE", // Purple
+	"#007AFF", // Blue
+	"#FFCC00", // Yellow
+	"#FF3B30", // Red
+	"#FF9500", // Orange
+];
+/// Color of the tags without one, which Finder shows without a label.
+const DEFAULT_COLOR: &str = "#A1A1AA";
+
+/// What the file identifier and tag changes do with the tags other tools keep in extended attributes.
+#[repr(i32)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, Type)]
+pub enum DesktopTagsPolicy {
+	/// Tags in extended attributes are left alone
+	#[default]
+	Ignore = 0,
+	/// Tags in extended attributes are added to the identified objects
+	Import = 1,
+	/// Tags are imported, and changes to the tags of objects are written back to their files
+	ImportAndWriteBack = 2,
+}
+
+impl From<Option<i32>> for DesktopTagsPolicy {
+	fn from(value: Option<i32>) -> Self {
+		match value {
+			None | Some(0) => Self::Ignore,
+			Some(1) => Self::Import,
+			Some(2) => Self::ImportAndWriteBack,
+			Some(value) => {
+				warn!("Invalid desktop tags policy on database: {value}, ignoring desktop tags");
+				Self::Ignore
+			}
+		}
+	}
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DesktopTag {
+	pub name: String,
+	pub color: &'static str,
+}
+
+/// Extract the tags from the `xattrs` column of a file path.
+pub fn parse_desktop_tags(xattrs: &str) -> Vec<DesktopTag> {
+	let mut xattrs = decode_xattrs(xattrs);
+
+	let mut seen = HashSet::new();
+	let mut tags = xattrs
+		.remove(FINDER_TAGS_XATTR)
+		.map(|value| parse_finder_tags(&value))
+		.unwrap_or_default();
+	tags.extend(
+		xattrs
+			.remove(XDG_TAGS_XATTR)
+			.map(|value| parse_xdg_tags(&value))
+			.unwrap_or_default(),
+	);
+
+	// Tags found in both attributes keep the color from Finder
+	tags.retain(|tag| seen.insert(tag.name.clone()));
+	tags
+}
+
+/// `user.xdg.tags` is a comma separated list of tag names.
+fn parse_xdg_tags(value: &[u8]) -> Vec<DesktopTag> {
+	String::from_utf8_lossy(value)
+		.split(',')
+		.map(str::trim)
+		.filter(|name| !name.is_empty())
+		.map(|name| DesktopTag {
+			name: name.to_string(),
+			color: DEFAULT_COLOR,
+		})
+		.collect()
+}
+
+/// Finder keeps a property list with an array of strings, each a tag name optionally followed by a new line
+/// and the index of its color.
+fn parse_finder_tags(value: &[u8]) -> Vec<DesktopTag> {
+	plist::from_bytes::<Vec<String>>(value)
+		.map_err(|e| trace!("Invalid Finder tags: {e:#?}"))
+		.unwrap_or_default()
+		.into_iter()
+		.filter_map(|tag| {
+			let (name, color) = tag
+				.split_once('\n')
+				.map_or((tag.as_str(), None), |(name, color)| {
+					(name, color.trim().parse::<usize>().ok())
+				});
+			let name = name.trim();
+
+			(!name.is_empty()).then(|| DesktopTag {
+				name: name.to_string(),
+				color: color
+					.and_then(|color| FINDER_COLORS.get(color).copied())
+					.unwrap_or(DEFAULT_COLOR),
+			})
+		})
+		.collect()
+}
+
+/// The color index of a tag being written back to Finder's attribute, keeping the color it already had there.
+fn finder_color_index(name: &str, previous: &[DesktopTag]) -> usize {
+	previous
+		.iter()
+		.find(|tag| tag.name == name)
+		.and_then(|tag| FINDER_COLORS.iter().position(|color| *color == tag.color))
+		.unwrap_or_default()
+}
+
+/// Add the tags found in extended attributes to the objects of the given file paths, creating the missing tags.
+/// Tags are only ever added, removing one on disk doesn't remove it from the object.
+pub async fn import_desktop_tags(
+	library @ Library { db, sync, .. }: &Library,
+	file_path_pub_ids: Vec<file_path::pub_id::Type>,
+) -> Result<usize, prisma_client_rust::QueryError> {
+	if file_path_pub_ids.is_empty() {
+		return Ok(0);
+	}
+
+	let objects_tags = db
+		.file_path()
+		.find_many(vec![
+			file_path::pub_id::in_vec(file_path_pub_ids),
+			file_path::object_id::not(None),
+			file_path::xattrs::not(None),
+		])
+		.select(file_path::select!({ xattrs object: select { id pub_id } }))
+		.exec()
+		.await?
+		.into_iter()
+		.filter_map(|file_path| {
+			let tags = parse_desktop_tags(file_path.xattrs.as_deref()?);
+			file_path
+				.object
+				.filter(|_| !tags.is_empty())
+				.map(|object| (object, tags))
+		})
+		.collect::<Vec<_>>();
+
+	if objects_tags.is_empty() {
+		return Ok(0);
+	}
+
+	let mut tags_by_name = HashMap::new();
+	for tag in objects_tags.iter().flat_map(|(_, tags)| tags) {
+		tags_by_name
+			.entry(tag.name.clone())
+			.or_insert_with(|| tag.color);
+	}
+
+	let mut existing_tags = db
+		.tag()
+		.find_many(vec![tag::name::in_vec(
+			tags_by_name.keys().cloned().collect(),
+		)])
+		.select(tag::select!({ id pub_id name }))
+		.exec()
+		.await?
+		.into_iter()
+		.filter_map(|tag| tag.name.map(|name| (name, (tag.id, tag.pub_id))))
+		.collect::<HashMap<_, _>>();
+
+	for (name, color) in tags_by_name {
+		if existing_tags.contains_key(&name) {
+			continue;
+		}
+
+		let created = TagCreateArgs {
+			name: name.clone(),
+			color: color.to_string(),
+		}
+		.exec(library)
+		.await?;
+
+		trace!("Created tag '{name}' imported from extended attributes");
+		existing_tags.insert(name, (created.id, created.pub_id));
+	}
+
+	let (sync_ops, db_creates) = objects_tags
+		.into_iter()
+		.flat_map(|(object, tags)| {
+			tags.into_iter()
+				.filter_map(|tag| existing_tags.get(&tag.name))
+				.map(move |(tag_id, tag_pub_id)| (object.clone(), *tag_id, tag_pub_id.clone()))
+				.collect::<Vec<_>>()
+		})
+		.fold(
+			(vec![], vec![]),
+			|(mut sync_ops, mut db_creates), (object, tag_id, tag_pub_id)| {
+				db_creates.push(tag_on_object::CreateUnchecked {
+					tag_id,
+					object_id: object.id,
+					_params: vec![tag_on_object::date_created::set(Some(Utc::now().into()))],
+				});
+
+				sync_ops.extend(sync.relation_create(
+					prisma_sync::tag_on_object::SyncId {
+						tag: prisma_sync::tag::SyncId { pub_id: tag_pub_id },
+						object: prisma_sync::object::SyncId {
+							pub_id: object.pub_id,
+						},
+					},
+					[],
+				));
+
+				(sync_ops, db_creates)
+			},
+		);
+
+	#[allow(clippy::cast_sign_loss)]
+	sync.write_ops(
+		db,
+		(
+			sync_ops,
+			db.tag_on_object().create_many(db_creates).skip_duplicates(),
+		),
+	)
+	.await
+	.map(|count| count as usize)
+}
+
+/// Import the tags of every file already identified in a location, for when it starts importing them.
+pub async fn import_location_desktop_tags(
+	library: &Library,
+	location_id: location::id::Type,
+) -> Result<usize, prisma_client_rust::QueryError> {
+	const CHUNK_SIZE: usize = 1000;
+
+	let pub_ids = library
+		.db
+		.file_path()
+		.find_many(vec![
+			file_path::location_id::equals(Some(location_id)),
+			file_path::object_id::not(None),
+			file_path::xattrs::not(None),
+		])
+		.select(file_path::select!({ pub_id }))
+		.exec()
+		.await?;
+
+	let mut imported = 0;
+	for chunk in pub_ids.chunks(CHUNK_SIZE) {
+		imported += import_desktop_tags(
+			library,
+			chunk
+				.iter()
+				.map(|file_path| file_path.pub_id.clone())
+				.collect(),
+		)
+		.await?;
+	}
+
+	Ok(imported)
+}
+
+/// Write the tags of the given objects to the extended attributes of their files, in locations which opted into it.
+///
+/// `user.xdg.tags` is always written, Finder's attribute is only rewritten on files which already had it.
+/// Failing to write to a file is only logged, as the tags in the library are already up to date.
+pub async fn write_back_desktop_tags(
+	library @ Library { db, sync, .. }: &Library,
+	object_ids: Vec<object::id::Type>,
+) -> Result<(), prisma_client_rust::QueryError> {
+	if object_ids.is_empty() {
+		return Ok(());
+	}
+
+	let instance_id = library.config().await.instance_id;
+
+	let file_paths = db
+		.file_path()
+		.find_many(vec![
+			file_path::object_id::in_vec(object_ids),
+			// Links indexed as aliases can't hold extended attributes of their own on most systems
+			file_path::symlink_target::equals(None),
+			file_path::location::is(vec![
+				location::desktop_tags_policy::equals(Some(
+					DesktopTagsPolicy::ImportAndWriteBack as i32,
+				)),
+				// We can only write to the files on this device
+				location::instance_id::equals(Some(instance_id)),
+			]),
+		])
+		.select(file_path_for_desktop_tags::select())
+		.exec()
+		.await?;
+
+	for file_path in file_paths {
+		let path = match file_path_full_path(&file_path) {
+			Ok(path) => path,
+			Err(e) => {
+				error!("Failed to build the path to write tags to: {e:#?}");
+				continue;
+			}
+		};
+
+		let names = file_path
+			.object
+			.iter()
+			.flat_map(|object| &object.tags)
+			.filter_map(|tag_on_object| tag_on_object.tag.name.clone())
+			.collect::<BTreeSet<_>>();
+
+		if let Err(e) = write_tags_to_xattrs(&path, &names, file_path.xattrs.as_deref()) {
+			error!(
+				"Failed to write tags to extended attributes: {:#?}",
+				FileIOError::from((&path, e))
+			);
+			continue;
+		}
+
+		// Keep the indexed attributes in sync with what we just wrote, so the indexer doesn't see them as changed
+		let xattrs = read_xattrs(&path);
+		if xattrs != file_path.xattrs {
+			sync.write_op(
+				db,
+				sync.shared_update(
+					prisma_sync::file_path::SyncId {
+						pub_id: file_path.pub_id.clone(),
+					},
+					file_path::xattrs::NAME,
+					msgpack!(xattrs),
+				),
+				db.file_path().update(
+					file_path::pub_id::equals(file_path.pub_id),
+					vec![file_path::xattrs::set(xattrs)],
+				),
+			)
+			.await?;
+		}
+	}
+
+	Ok(())
+}
+
+fn file_path_full_path(
+	file_path: &file_path_for_desktop_tags::Data,
+) -> Result<PathBuf, MissingFieldError> {
+	let location = maybe_missing(&file_path.location, "file_path.location")?;
+	let location_path = maybe_missing(&location.path, "location.path")?;
+
+	Ok(Path::new(location_path).join(IsolatedFilePathData::try_from((location.id, file_path))?))
+}
+
+fn write_tags_to_xattrs(
+	path: &Path,
+	names: &BTreeSet<String>,
+	xattrs: Option<&str>,
+) -> io::Result<()> {
+	let xdg_tags = names
+		.iter()
+		.map(String::as_str)
+		.collect::<Vec<_>>()
+		.join(",");
+	write_xattr(
+		path,
+		XDG_TAGS_XATTR,
+		(!xdg_tags.is_empty()).then_some(xdg_tags.as_bytes()),
+	)?;
+
+	let Some(finder_tags) = xattrs
+		.map(decode_xattrs)
+		.and_then(|mut xattrs| xattrs.remove(FINDER_TAGS_XATTR))
+	else {
+		return Ok(());
+	};
+
+	let previous = parse_finder_tags(&finder_tags);
+	let finder_tags = names
+		.iter()
+		.map(|name| format!("{name}\n{}", finder_color_index(name, &previous)))
+		.collect::<Vec<_>>();
+
+	let mut value = vec![];
+	plist::to_writer_binary(&mut value, &finder_tags)
+		.map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+
+	write_xattr(
+		path,
+		FINDER_TAGS_XATTR,
+		(!finder_tags.is_empty()).then_some(value.as_slice()),
+	)
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+	use super::*;
+
+	use sd_core_file_path_helper::extended_metadata::encode_xattrs;
+
+	#[test]
+	fn parses_xdg_and_finder_tags() {
+		let mut finder_tags = vec![];
+		plist::to_writer_binary(
+			&mut finder_tags,
+			&vec![
+				"Red\n6".to_string(),
+				"Work\n0".to_string(),
+				"Plain".to_string(),
+			],
+		)
+		.unwrap();
+
+		let xattrs = encode_xattrs([
+			(XDG_TAGS_XATTR.to_string(), b"Work, Photos,,".to_vec()),
+			(FINDER_TAGS_XATTR.to_string(), finder_tags),
+		])
+		.unwrap();
+
+		assert_eq!(
+			parse_desktop_tags(&xattrs),
+			vec![
+				DesktopTag {
+					name: "Red".to_string(),
+					color: "#FF3B30",
+				},
+				DesktopTag {
+					name: "Work".to_string(),
+					color: DEFAULT_COLOR,
+				},
+				DesktopTag {
+					name: "Plain".to_string(),
+					color: DEFAULT_COLOR,
+				},
+				DesktopTag {
+					name: "Photos".to_string(),
+					color: DEFAULT_COLOR,
+				},
+			]
+		);
+	}
+
+	#[test]
+	fn keeps_finder_colors_when_writing_back() {
+		let previous = parse_finder_tags(&{
+			let mut value = vec![];
+			plist::to_writer_binary(&mut value, &vec!["Red\n6".to_string()]).unwrap();
+			value
+		});
+
+		assert_eq!(finder_color_index("Red", &previous), 6);
+		assert_eq!(finder_color_index("New", &previous), 0);
+	}
+}
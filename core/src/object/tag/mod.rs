@@ -9,6 +9,7 @@ use serde::Deserialize;
 use specta::Type;
 use uuid::Uuid;
 
+pub mod desktop;
 pub mod seed;
 
 #[derive(Type, Deserialize, Clone)]
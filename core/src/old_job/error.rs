@@ -1,4 +1,5 @@
 use crate::{
+	library::backup::BackupError,
 	location::{indexer::IndexerError, LocationError},
 	object::{
 		fs::error::FileSystemJobsError, media::old_media_processor::MediaProcessorError,
@@ -66,6 +67,8 @@ pub enum JobError {
 	Validator(#[from] ValidatorError),
 	#[error(transparent)]
 	FileSystemJobsError(#[from] FileSystemJobsError),
+	#[error(transparent)]
+	Backup(#[from] BackupError),
 	// #[error(transparent)]
 	// CryptoError(#[from] CryptoError),
 
@@ -1,10 +1,10 @@
 use crate::{
-	library::Library,
+	library::{backup::BackupJobInit, Library},
 	location::indexer::old_indexer_job::OldIndexerJobInit,
 	object::{
 		fs::{
 			old_copy::OldFileCopierJobInit, old_cut::OldFileCutterJobInit,
-			old_delete::OldFileDeleterJobInit, old_erase::OldFileEraserJobInit,
+			old_dedup::OldFileDeduplicatorJobInit, old_delete::OldFileDeleterJobInit,
 		},
 		media::old_media_processor::OldMediaProcessorJobInit,
 		old_file_identifier::old_file_identifier_job::OldFileIdentifierJobInit,
@@ -403,7 +403,8 @@ fn initialize_resumable_job(
 			OldFileCutterJobInit,
 			OldFileCopierJobInit,
 			OldFileDeleterJobInit,
-			OldFileEraserJobInit,
+			OldFileDeduplicatorJobInit,
+			BackupJobInit,
 		]
 	)
 }
@@ -14,7 +14,7 @@ use axum::routing::IntoMakeService;
 
 use sd_p2p::{
 	flume::{bounded, Receiver},
-	hooks::{Libp2pPeerId, Mdns, QuicHandle, QuicTransport, RelayServerEntry},
+	hooks::{Libp2pPeerId, Mdns, QuicHandle, QuicTransport, RelayServerEntry, Rendezvous},
 	Peer, RemoteIdentity, UnicastStream, P2P,
 };
 use sd_p2p_tunnel::Tunnel;
@@ -57,6 +57,7 @@ pub struct Listeners {
 pub struct P2PManager {
 	pub(crate) p2p: Arc<P2P>,
 	mdns: Mutex<Option<Mdns>>,
+	rendezvous: Mutex<Option<Rendezvous>>,
 	quic_transport: QuicTransport,
 	pub quic: Arc<QuicHandle>,
 	// The `libp2p::PeerId`. This is for debugging only, use `RemoteIdentity` instead.
@@ -64,6 +65,7 @@ pub struct P2PManager {
 	pub(crate) events: P2PEvents,
 	pub(super) spacedrop_pairing_reqs: Arc<Mutex<HashMap<Uuid, oneshot::Sender<Option<String>>>>>,
 	pub(super) spacedrop_cancellations: Arc<Mutex<HashMap<Uuid, Arc<AtomicBool>>>>,
+	pub(super) pairing_codes: Mutex<HashMap<Uuid, operations::pairing::PendingPairing>>,
 	pub(crate) node_config: Arc<config::Manager>,
 	pub listeners: Mutex<Listeners>,
 	relay_config: Mutex<Vec<RelayServerEntry>>,
@@ -89,11 +91,13 @@ impl P2PManager {
 			p2p: p2p.clone(),
 			lp2p_peer_id,
 			mdns: Mutex::new(None),
+			rendezvous: Mutex::new(None),
 			events: P2PEvents::spawn(p2p.clone(), quic.handle()),
 			quic: quic.handle(),
 			quic_transport: quic,
 			spacedrop_pairing_reqs: Default::default(),
 			spacedrop_cancellations: Default::default(),
+			pairing_codes: Default::default(),
 			node_config,
 			listeners: Default::default(),
 			relay_config: Default::default(),
@@ -115,6 +119,12 @@ impl P2PManager {
 			tokio::spawn(async move {
 				let client = reqwest::Client::new();
 				loop {
+					let node_config = node.config.get().await;
+					if !node_config.p2p.disabled && !node_config.p2p.disable_relay {
+						this.authorize_with_relays(&client, &node_config.relay_authorizations)
+							.await;
+					}
+
 					match client
 						.get(format!("{}/api/p2p/relays", node.env.api_url.lock().await))
 						.send()
@@ -189,6 +199,40 @@ impl P2PManager {
 		}))
 	}
 
+	/// Present our identity along with the shared secret of each self-hosted relay which requires one.
+	/// The relays pull loop runs far more often than relays expire these authorizations, so they never lapse.
+	async fn authorize_with_relays(
+		&self,
+		client: &reqwest::Client,
+		authorizations: &[config::RelayAuthorization],
+	) {
+		for authorization in authorizations {
+			let url = authorization.url.trim_end_matches('/');
+			if !url.starts_with("https://") {
+				error!("Refusing to send the shared secret of relay '{url}' over an insecure connection");
+				continue;
+			}
+
+			match client
+				.post(format!("{url}/authorize"))
+				.bearer_auth(&authorization.secret)
+				.json(&json!({ "identity": self.p2p.remote_identity().to_string() }))
+				.send()
+				.await
+			{
+				Ok(resp) if resp.status().is_success() => {
+					info!("Authorized with relay '{url}'");
+				}
+				Ok(resp) => error!(
+					"Failed to authorize with relay '{url}': {} {:?}",
+					resp.status(),
+					resp.text().await
+				),
+				Err(err) => error!("Error authorizing with relay '{url}': {err:?}"),
+			}
+		}
+	}
+
 	pub fn peer_metadata(&self) -> HashMap<String, String> {
 		self.p2p.metadata().clone()
 	}
@@ -296,6 +340,37 @@ impl P2PManager {
 			}
 		};
 
+		// Restarted when the directory changes, so our record is removed from the previous one
+		let rendezvous_dir = (!config.p2p.disabled
+			&& config.p2p.discovery != P2PDiscoveryState::Disabled)
+			.then(|| config.p2p.rendezvous_dir.clone())
+			.flatten();
+		let previous_rendezvous = {
+			let mut rendezvous = self
+				.rendezvous
+				.lock()
+				.unwrap_or_else(PoisonError::into_inner);
+			if rendezvous.as_ref().map(Rendezvous::dir) == rendezvous_dir.as_deref() {
+				None
+			} else {
+				rendezvous.take()
+			}
+		};
+		if let Some(rendezvous) = previous_rendezvous {
+			rendezvous.shutdown().await;
+			info!("Rendezvous discovery shutdown successfully.");
+		}
+		if let Some(dir) = rendezvous_dir {
+			let mut rendezvous = self
+				.rendezvous
+				.lock()
+				.unwrap_or_else(PoisonError::into_inner);
+			if rendezvous.is_none() {
+				info!("Starting rendezvous discovery in '{}'", dir.display());
+				*rendezvous = Some(Rendezvous::spawn(self.p2p.clone(), dir));
+			}
+		}
+
 		// The `should_revert` bit is weird but we need this future to stay `Send` as rspc requires.
 		// To make it send we have to drop `quic` (a `!Send` `MutexGuard`).
 		// Doing it within the above scope seems to not work (even when manually calling `drop`).
@@ -425,7 +500,7 @@ async fn start(
 				}
 				Header::RspcRemote => {
 					let remote = stream.remote_identity();
-					let Err(err) = operations::rspc::receiver(stream, &mut service, &node).await
+					let Err(err) = operations::rspc::receiver(stream, &mut service, node).await
 					else {
 						return;
 					};
@@ -445,6 +520,14 @@ async fn start(
 
 					error!("Failed to handling library file request with {remote:?} for {file_path_id}: {err:?}");
 				}
+				Header::Pairing => {
+					let remote = stream.remote_identity();
+					let Err(err) = operations::pairing::receiver(stream, &node).await else {
+						return;
+					};
+
+					error!("Failed to handle pairing request from '{remote}': {err}");
+				}
 			};
 		});
 	}
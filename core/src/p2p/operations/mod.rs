@@ -1,8 +1,10 @@
 pub mod library;
+pub mod pairing;
 pub mod ping;
 pub mod rspc;
 pub mod spacedrop;
 
 pub use library::request_file;
+pub use pairing::pair;
 pub use rspc::remote_rspc;
 pub use spacedrop::spacedrop;
@@ -0,0 +1,510 @@
+//! Pairing a node with a library of another node over the local network, without a Spacedrive account.
+//!
+//! The node holding the library generates a short-lived code which is shown to the user (and as a QR code).
+//! The joining node dials the holder and both prove they know the code, bound to both of their node identities, so
+//! neither side can be impersonated by another node on the network. Then the instances are exchanged and the holder
+//! backfills the new instance with its sync operations.
+
+use crate::{
+	cloud::sync::receive::upsert_instance,
+	library::{Library, LibraryManagerError, LibraryName},
+	p2p::{sync::alert_peer, Header, P2PManager},
+	util::MaybeUndefined,
+	Node,
+};
+
+use sd_p2p::{RemoteIdentity, UnicastStream};
+use sd_p2p_proto::{decode, encode};
+use sd_prisma::prisma::instance;
+use sd_utils::from_bytes_to_uuid;
+
+use std::{
+	collections::HashMap,
+	str::FromStr,
+	sync::{atomic::Ordering, Arc, PoisonError},
+	time::{Duration, Instant},
+};
+
+use rand::Rng;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use specta::Type;
+use thiserror::Error;
+use tokio::io::{AsyncRead, AsyncWrite, AsyncWriteExt};
+use tracing::{debug, error, info, warn};
+use uuid::Uuid;
+
+/// How long a pairing code can be used for after it was generated
+const PAIRING_CODE_TIMEOUT: Duration = Duration::from_secs(5 * 60);
+/// How many wrong codes are accepted before every pending pairing is cancelled
+const MAX_PAIRING_ATTEMPTS: u8 = 3;
+const PAIRING_CODE_LENGTH: usize = 6;
+const PAIRING_PAYLOAD_PREFIX: &str = "spacedrive-pairing:";
+
+#[derive(Debug, Error)]
+pub enum PairingError {
+	#[error("peer not found, has it been discovered?")]
+	PeerNotFound,
+	#[error("error creating stream: {0}")]
+	NewStream(#[from] sd_p2p::NewStreamError),
+	#[error("io error: {0}")]
+	Io(#[from] std::io::Error),
+	#[error("error decoding message: {0}")]
+	Decode(#[from] decode::Error),
+	#[error("malformed message: {0}")]
+	MalformedMessage(#[from] rmp_serde::decode::Error),
+	#[error("the pairing code is wrong or has expired")]
+	Rejected,
+	#[error("the remote node couldn't prove it knows the pairing code")]
+	InvalidProof,
+	#[error("the library already exists on this node")]
+	LibraryAlreadyExists,
+	#[error("invalid library name: {0}")]
+	InvalidLibraryName(String),
+	#[error("library error: {0}")]
+	Library(#[from] LibraryManagerError),
+	#[error("database error: {0}")]
+	Database(#[from] prisma_client_rust::QueryError),
+}
+
+impl From<PairingError> for rspc::Error {
+	fn from(err: PairingError) -> Self {
+		let code = match err {
+			PairingError::PeerNotFound => rspc::ErrorCode::NotFound,
+			PairingError::Rejected | PairingError::InvalidProof => rspc::ErrorCode::Forbidden,
+			PairingError::LibraryAlreadyExists => rspc::ErrorCode::Conflict,
+			_ => rspc::ErrorCode::InternalServerError,
+		};
+
+		rspc::Error::with_cause(code, err.to_string(), err)
+	}
+}
+
+/// A code waiting for a node to pair with the library it was generated for
+#[derive(Debug)]
+pub(crate) struct PendingPairing {
+	code: String,
+	expires_at: Instant,
+	failed_attempts: u8,
+}
+
+/// What the user has to give to the joining node, typed in or scanned as a QR code
+#[derive(Debug, Serialize, Type)]
+pub struct PairingCode {
+	pub code: String,
+	pub payload: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct PairingRequest {
+	proof: [u8; 32],
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+enum PairingResponse {
+	Accepted {
+		proof: [u8; 32],
+		library: PairingLibrary,
+	},
+	Rejected,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct PairingLibrary {
+	id: Uuid,
+	name: String,
+	description: Option<String>,
+	instances: Vec<PairingInstance>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct PairingInstance {
+	uuid: Uuid,
+	identity: RemoteIdentity,
+	node_id: Uuid,
+	node_remote_identity: RemoteIdentity,
+	metadata: HashMap<String, String>,
+}
+
+impl PairingInstance {
+	/// Instances created before the node's identity and metadata were required can't be paired with
+	fn from_db(instance: instance::Data) -> Option<Self> {
+		Some(Self {
+			uuid: from_bytes_to_uuid(&instance.pub_id),
+			identity: RemoteIdentity::from_bytes(&instance.remote_identity).ok()?,
+			node_id: from_bytes_to_uuid(&instance.node_id),
+			node_remote_identity: RemoteIdentity::from_bytes(
+				instance.node_remote_identity.as_ref()?,
+			)
+			.ok()?,
+			metadata: serde_json::from_slice(instance.metadata.as_ref()?).ok()?,
+		})
+	}
+
+	async fn upsert(self, library: &Library, node: &Node) -> Result<(), PairingError> {
+		upsert_instance(
+			library.id,
+			&library.db,
+			&library.sync,
+			&node.libraries,
+			self.uuid,
+			self.identity,
+			self.node_id,
+			self.node_remote_identity,
+			self.metadata,
+		)
+		.await
+		.map_err(Into::into)
+	}
+}
+
+/// Proof of knowing the pairing code, bound to both nodes so it can't be relayed by another node.
+fn proof(role: &str, code: &str, holder: RemoteIdentity, joiner: RemoteIdentity) -> blake3::Hash {
+	let mut hasher = blake3::Hasher::new_derive_key(&format!("spacedrive pairing {role}"));
+	hasher.update(code.as_bytes());
+	hasher.update(&holder.get_bytes());
+	hasher.update(&joiner.get_bytes());
+	hasher.finalize()
+}
+
+/// Parse the payload of a pairing QR code into the identity of the node holding the library and the code.
+pub fn parse_payload(payload: &str) -> Option<(RemoteIdentity, String)> {
+	let (identity, code) = payload
+		.strip_prefix(PAIRING_PAYLOAD_PREFIX)?
+		.rsplit_once(':')?;
+
+	Some((RemoteIdentity::from_str(identity).ok()?, code.to_string()))
+}
+
+async fn write_message(
+	stream: &mut (impl AsyncWrite + Unpin),
+	message: &impl Serialize,
+) -> Result<(), PairingError> {
+	let mut buf = vec![];
+	encode::buf(
+		&mut buf,
+		&rmp_serde::to_vec_named(message).expect("pairing messages are always serializable"),
+	);
+	stream.write_all(&buf).await?;
+	stream.flush().await.map_err(Into::into)
+}
+
+async fn read_message<T: DeserializeOwned>(
+	stream: &mut (impl AsyncRead + Unpin),
+) -> Result<T, PairingError> {
+	Ok(rmp_serde::from_slice(&decode::buf(stream).await?)?)
+}
+
+impl P2PManager {
+	/// Generate a code another node can use to join the library, replacing any previous one.
+	pub fn start_pairing(&self, library_id: Uuid) -> PairingCode {
+		let code = {
+			let mut rng = rand::thread_rng();
+			(0..PAIRING_CODE_LENGTH)
+				.map(|_| char::from(b'0' + rng.gen_range(0..10)))
+				.collect::<String>()
+		};
+
+		self.pairing_codes
+			.lock()
+			.unwrap_or_else(PoisonError::into_inner)
+			.insert(
+				library_id,
+				PendingPairing {
+					code: code.clone(),
+					expires_at: Instant::now() + PAIRING_CODE_TIMEOUT,
+					failed_attempts: 0,
+				},
+			);
+
+		PairingCode {
+			payload: format!(
+				"{PAIRING_PAYLOAD_PREFIX}{}:{code}",
+				self.p2p.remote_identity()
+			),
+			code,
+		}
+	}
+
+	pub fn cancel_pairing(&self, library_id: Uuid) {
+		self.pairing_codes
+			.lock()
+			.unwrap_or_else(PoisonError::into_inner)
+			.remove(&library_id);
+	}
+
+	/// Find the library the joining node proved it has the code of, this uses up the code.
+	fn redeem_pairing_code(
+		&self,
+		joiner: RemoteIdentity,
+		request_proof: blake3::Hash,
+	) -> Option<(Uuid, String)> {
+		let holder = self.p2p.remote_identity();
+		let mut pairing_codes = self
+			.pairing_codes
+			.lock()
+			.unwrap_or_else(PoisonError::into_inner);
+
+		pairing_codes.retain(|_, pending| pending.expires_at > Instant::now());
+
+		let library_id = pairing_codes.iter().find_map(|(library_id, pending)| {
+			(proof("joiner", &pending.code, holder, joiner) == request_proof).then_some(*library_id)
+		});
+
+		match library_id {
+			Some(library_id) => pairing_codes
+				.remove(&library_id)
+				.map(|pending| (library_id, pending.code)),
+			None => {
+				// We don't know which code was being guessed, so every code has to be protected
+				pairing_codes.retain(|_, pending| {
+					pending.failed_attempts += 1;
+					pending.failed_attempts < MAX_PAIRING_ATTEMPTS
+				});
+
+				None
+			}
+		}
+	}
+}
+
+/// Join a library of another node using a pairing code generated by it.
+pub async fn pair(
+	node: &Arc<Node>,
+	holder: RemoteIdentity,
+	code: String,
+) -> Result<Arc<Library>, PairingError> {
+	let peer = node
+		.p2p
+		.p2p
+		.peers()
+		.get(&holder)
+		.ok_or(PairingError::PeerNotFound)?
+		.clone();
+	let mut stream = peer.new_stream().await?;
+	let joiner = node.p2p.p2p.remote_identity();
+
+	stream.write_all(&Header::Pairing.to_bytes()).await?;
+	write_message(
+		&mut stream,
+		&PairingRequest {
+			proof: *proof("joiner", &code, holder, joiner).as_bytes(),
+		},
+	)
+	.await?;
+
+	let library = match read_message(&mut stream).await? {
+		PairingResponse::Accepted {
+			proof: holder_proof,
+			library,
+		} => {
+			if proof("holder", &code, holder, joiner) != blake3::Hash::from(holder_proof) {
+				return Err(PairingError::InvalidProof);
+			}
+
+			library
+		}
+		PairingResponse::Rejected => return Err(PairingError::Rejected),
+	};
+
+	match join_library(node, library).await {
+		Ok((library, instance)) => {
+			write_message(&mut stream, &Some(instance)).await?;
+			info!(
+				"Paired with node '{holder}' to join library '{}'",
+				library.id
+			);
+
+			Ok(library)
+		}
+		Err(err) => {
+			write_message(&mut stream, &None::<PairingInstance>).await?;
+
+			Err(err)
+		}
+	}
+}
+
+async fn join_library(
+	node: &Arc<Node>,
+	remote_library: PairingLibrary,
+) -> Result<(Arc<Library>, PairingInstance), PairingError> {
+	if node
+		.libraries
+		.get_library(&remote_library.id)
+		.await
+		.is_some()
+	{
+		return Err(PairingError::LibraryAlreadyExists);
+	}
+
+	let library = node
+		.libraries
+		.create_with_uuid(
+			remote_library.id,
+			LibraryName::new(remote_library.name)
+				.map_err(|err| PairingError::InvalidLibraryName(err.to_string()))?,
+			remote_library.description,
+			false,
+			None,
+			node,
+			true,
+		)
+		.await?;
+
+	for instance in remote_library.instances {
+		instance.upsert(&library, node).await?;
+	}
+
+	let node_config = node.config.get().await;
+	let instance = PairingInstance {
+		uuid: library.instance_uuid,
+		identity: library.identity.to_remote_identity(),
+		node_id: node_config.id,
+		node_remote_identity: node_config.identity.to_remote_identity(),
+		metadata: node.p2p.peer_metadata(),
+	};
+
+	Ok((library, instance))
+}
+
+pub(crate) async fn receiver(
+	mut stream: UnicastStream,
+	node: &Arc<Node>,
+) -> Result<(), PairingError> {
+	let joiner = stream.remote_identity();
+	let holder = node.p2p.p2p.remote_identity();
+	let PairingRequest {
+		proof: request_proof,
+	} = read_message(&mut stream).await?;
+
+	let Some((library_id, code)) = node
+		.p2p
+		.redeem_pairing_code(joiner, blake3::Hash::from(request_proof))
+	else {
+		warn!("Rejected pairing request from '{joiner}' with a wrong or expired code");
+		return write_message(&mut stream, &PairingResponse::Rejected).await;
+	};
+
+	let Some(library) = node.libraries.get_library(&library_id).await else {
+		// The library was deleted while the code was still valid
+		return write_message(&mut stream, &PairingResponse::Rejected).await;
+	};
+
+	let config = library.config().await;
+	let instances = library
+		.db
+		.instance()
+		.find_many(vec![])
+		.exec()
+		.await?
+		.into_iter()
+		.filter_map(PairingInstance::from_db)
+		.collect();
+
+	write_message(
+		&mut stream,
+		&PairingResponse::Accepted {
+			proof: *proof("holder", &code, holder, joiner).as_bytes(),
+			library: PairingLibrary {
+				id: library.id,
+				name: config.name.to_string(),
+				description: config.description.clone(),
+				instances,
+			},
+		},
+	)
+	.await?;
+
+	let Some(instance) = read_message::<Option<PairingInstance>>(&mut stream).await? else {
+		debug!("Node '{joiner}' failed to join library '{library_id}' after pairing");
+		return Ok(());
+	};
+
+	// The code only proves who we're talking to, so it can't be used to add an instance for another node
+	if instance.node_remote_identity != joiner {
+		warn!("Node '{joiner}' tried to join library '{library_id}' as another node");
+		return Err(PairingError::InvalidProof);
+	}
+
+	instance.upsert(&library, node).await?;
+	info!("Paired library '{library_id}' with node '{joiner}'");
+
+	backfill(node, library, joiner).await
+}
+
+/// Send every operation of the library to the newly paired node, generating them first if sync was never enabled.
+async fn backfill(
+	node: &Arc<Node>,
+	library: Arc<Library>,
+	joiner: RemoteIdentity,
+) -> Result<(), PairingError> {
+	let config = library.config().await;
+	if !config.generate_sync_operations.load(Ordering::Relaxed) {
+		sd_core_sync::backfill::backfill_operations(&library.db, &library.sync, config.instance_id)
+			.await;
+
+		node.libraries
+			.edit(
+				library.id,
+				None,
+				MaybeUndefined::Undefined,
+				MaybeUndefined::Undefined,
+				Some(true),
+			)
+			.await?;
+	}
+
+	let Some(peer) = node.p2p.p2p.peers().get(&joiner).cloned() else {
+		error!("Paired node '{joiner}' disconnected before its backfill");
+		return Ok(());
+	};
+
+	let sync = library.sync.clone();
+	alert_peer(library, sync, joiner, peer).await;
+
+	Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	use sd_p2p::Identity;
+
+	#[test]
+	fn proofs_are_bound_to_both_nodes() {
+		let (holder, joiner, other) = (
+			Identity::new().to_remote_identity(),
+			Identity::new().to_remote_identity(),
+			Identity::new().to_remote_identity(),
+		);
+
+		assert_eq!(
+			proof("joiner", "123456", holder, joiner),
+			proof("joiner", "123456", holder, joiner)
+		);
+		assert_ne!(
+			proof("joiner", "123456", holder, joiner),
+			proof("holder", "123456", holder, joiner)
+		);
+		assert_ne!(
+			proof("joiner", "123456", holder, joiner),
+			proof("joiner", "123456", holder, other)
+		);
+		assert_ne!(
+			proof("joiner", "123456", holder, joiner),
+			proof("joiner", "654321", holder, joiner)
+		);
+	}
+
+	#[test]
+	fn payload_round_trip() {
+		let identity = Identity::new().to_remote_identity();
+
+		assert_eq!(
+			parse_payload(&format!("{PAIRING_PAYLOAD_PREFIX}{identity}:042133")),
+			Some((identity, "042133".to_string()))
+		);
+		assert_eq!(parse_payload("042133"), None);
+	}
+}
@@ -1,12 +1,29 @@
-use std::{error::Error, sync::Arc};
+use crate::{
+	p2p::{Header, ProcedureKind, RemoteAccessScope},
+	Node,
+};
 
-use axum::{body::Body, http, Router};
-use hyper::{server::conn::Http, Response};
 use sd_p2p::{RemoteIdentity, UnicastStream, P2P};
-use tokio::io::AsyncWriteExt;
-use tracing::debug;
 
-use crate::{p2p::Header, Node};
+use std::{collections::HashMap, convert::Infallible, error::Error, sync::Arc};
+
+use axum::{
+	body::{Body, BoxBody},
+	extract::{
+		ws::{close_code, CloseFrame, Message, WebSocket, WebSocketUpgrade},
+		FromRequestParts, Query,
+	},
+	http::{self, Method, Request, StatusCode},
+	response::IntoResponse,
+	Router,
+};
+use futures::{SinkExt, StreamExt};
+use hyper::{server::conn::Http, service::service_fn, Response};
+use serde_json::Value;
+use tokio::io::AsyncWriteExt;
+use tokio_tungstenite::tungstenite;
+use tower_service::Service;
+use tracing::{debug, error};
 
 /// Transfer an rspc query to a remote node.
 pub async fn remote_rspc(
@@ -36,24 +53,236 @@ pub async fn remote_rspc(
 pub(crate) async fn receiver(
 	stream: UnicastStream,
 	service: &mut Router,
-	node: &Node,
+	node: Arc<Node>,
 ) -> Result<(), Box<dyn Error>> {
-	debug!(
-		"Received http request from peer '{}'",
-		stream.remote_identity(),
-	);
-
-	// TODO: Authentication
-	#[allow(clippy::todo)]
-	if !node.config.get().await.p2p.enable_remote_access {
-		todo!("No way buddy!");
-	}
+	let remote = stream.remote_identity();
+	debug!("Received http request from peer '{remote}'");
 
+	let service = service.clone();
 	Http::new()
 		.http1_only(true)
 		.http1_keep_alive(true)
-		.serve_connection(stream, service)
+		.serve_connection(
+			stream,
+			service_fn(move |request| authorize(request, remote, node.clone(), service.clone())),
+		)
 		.with_upgrades()
 		.await
 		.map_err(Into::into)
 }
+
+/// The scope granted to a remote node, if remote access is enabled at all.
+async fn remote_access_scope(node: &Node, identity: RemoteIdentity) -> Option<RemoteAccessScope> {
+	let config = node.config.get().await.p2p;
+
+	config.enable_remote_access.then_some(())?;
+
+	config
+		.remote_access
+		.into_iter()
+		.find(|grant| grant.identity == identity)
+		.map(|grant| grant.scope)
+}
+
+fn forbidden() -> Response<BoxBody> {
+	(StatusCode::FORBIDDEN, "Remote access denied").into_response()
+}
+
+/// Check a request of a remote node against its grant.
+/// The grant is looked up for every request so revoking it also applies to open connections.
+async fn authorize(
+	request: Request<Body>,
+	remote: RemoteIdentity,
+	node: Arc<Node>,
+	mut service: Router,
+) -> Result<Response<BoxBody>, Infallible> {
+	let Some(scope) = remote_access_scope(&node, remote).await else {
+		debug!("Denied remote access to peer '{remote}' without a grant");
+		return Ok(forbidden());
+	};
+
+	if scope == RemoteAccessScope::Full {
+		return service.call(request).await;
+	}
+
+	let path = request.uri().path().to_owned();
+
+	if path == "/rspc/ws" {
+		let (mut parts, _) = request.into_parts();
+		return Ok(
+			match WebSocketUpgrade::from_request_parts(&mut parts, &()).await {
+				Ok(upgrade) => upgrade
+					.on_upgrade(move |socket| proxy_websocket(socket, remote, node, service))
+					.into_response(),
+				Err(rejection) => rejection.into_response(),
+			},
+		);
+	}
+
+	let (allowed, request) = if let Some(procedure) = path.strip_prefix("/rspc/") {
+		match *request.method() {
+			Method::GET => {
+				let input = match Query::<HashMap<String, String>>::try_from_uri(request.uri()) {
+					Ok(Query(query)) => query
+						.get("input")
+						.map_or(Some(Value::Null), |input| serde_json::from_str(input).ok()),
+					Err(_) => None,
+				};
+
+				(
+					input.is_some_and(|input| {
+						scope.allows_procedure(ProcedureKind::Query, procedure, &input)
+					}),
+					request,
+				)
+			}
+			Method::POST => {
+				let (parts, body) = request.into_parts();
+				let Ok(body) = hyper::body::to_bytes(body).await else {
+					return Ok(StatusCode::BAD_REQUEST.into_response());
+				};
+
+				let input = if body.is_empty() {
+					Some(Value::Null)
+				} else {
+					serde_json::from_slice(&body).ok()
+				};
+
+				(
+					input.is_some_and(|input| {
+						scope.allows_procedure(ProcedureKind::Mutation, procedure, &input)
+					}),
+					Request::from_parts(parts, Body::from(body)),
+				)
+			}
+			_ => (false, request),
+		}
+	} else if let Some(uri) = path.strip_prefix("/uri") {
+		(
+			request.method() == Method::GET && scope.allows_uri(uri),
+			request,
+		)
+	} else {
+		(false, request)
+	};
+
+	if !allowed {
+		debug!("Denied remote access to '{path}' for peer '{remote}'");
+		return Ok(forbidden());
+	}
+
+	service.call(request).await
+}
+
+/// rspc multiplexes every procedure over a single websocket, so for scoped grants we sit in between the remote node
+/// and the router to check each of the requests made over it.
+async fn proxy_websocket(
+	socket: WebSocket,
+	remote: RemoteIdentity,
+	node: Arc<Node>,
+	service: Router,
+) {
+	let (client, server) = tokio::io::duplex(64 * 1024);
+	tokio::spawn(async move {
+		if let Err(err) = Http::new()
+			.http1_only(true)
+			.serve_connection(server, service)
+			.with_upgrades()
+			.await
+		{
+			error!("Error serving proxied rspc websocket: {err:?}");
+		}
+	});
+
+	let local = match tokio_tungstenite::client_async("ws://localhost/rspc/ws", client).await {
+		Ok((local, _)) => local,
+		Err(err) => {
+			error!("Failed to open proxied rspc websocket: {err:?}");
+			return;
+		}
+	};
+
+	let (mut remote_tx, mut remote_rx) = socket.split();
+	let (mut local_tx, mut local_rx) = local.split();
+
+	loop {
+		tokio::select! {
+			msg = remote_rx.next() => {
+				let msg = match msg {
+					Some(Ok(Message::Text(text))) => {
+						if !allows_websocket_message(&text, remote, &node).await {
+							debug!("Denied remote access to an rspc request over websocket for peer '{remote}'");
+							remote_tx
+								.send(Message::Close(Some(CloseFrame {
+									code: close_code::POLICY,
+									reason: "Remote access denied".into(),
+								})))
+								.await
+								.ok();
+							break;
+						}
+
+						tungstenite::Message::Text(text)
+					}
+					// Pings are answered by axum
+					Some(Ok(Message::Ping(_) | Message::Pong(_))) => continue,
+					_ => break,
+				};
+
+				if local_tx.send(msg).await.is_err() {
+					break;
+				}
+			}
+			msg = local_rx.next() => {
+				let msg = match msg {
+					Some(Ok(tungstenite::Message::Text(text))) => Message::Text(text),
+					Some(Ok(tungstenite::Message::Binary(data))) => Message::Binary(data),
+					Some(Ok(tungstenite::Message::Ping(_) | tungstenite::Message::Pong(_))) => continue,
+					_ => break,
+				};
+
+				if remote_tx.send(msg).await.is_err() {
+					break;
+				}
+			}
+		}
+	}
+
+	local_tx.close().await.ok();
+}
+
+/// Check every request of a, possibly batched, rspc websocket message.
+async fn allows_websocket_message(text: &str, remote: RemoteIdentity, node: &Node) -> bool {
+	let Some(scope) = remote_access_scope(node, remote).await else {
+		return false;
+	};
+
+	let requests = match serde_json::from_str::<Value>(text) {
+		Ok(Value::Array(requests)) => requests,
+		Ok(request) => vec![request],
+		Err(_) => return false,
+	};
+
+	requests.iter().all(|request| {
+		let method = request
+			.get("method")
+			.and_then(Value::as_str)
+			.unwrap_or_default();
+
+		// Stopping a subscription only affects the subscriptions of this connection
+		if method == "subscriptionStop" {
+			return true;
+		}
+
+		let params = request.get("params").unwrap_or(&Value::Null);
+		match (
+			ProcedureKind::from_method(method),
+			params.get("path").and_then(Value::as_str),
+		) {
+			(Some(kind), Some(path)) => {
+				scope.allows_procedure(kind, path, params.get("input").unwrap_or(&Value::Null))
+			}
+			_ => false,
+		}
+	})
+}
@@ -21,6 +21,8 @@ pub enum Header {
 		file_path_id: Uuid,
 		range: Range,
 	},
+	/// Joining a library of another node with a pairing code
+	Pairing,
 }
 
 #[derive(Debug, Error)]
@@ -77,6 +79,7 @@ impl Header {
 					d => return Err(HeaderError::LibraryDiscriminatorInvalid(d)),
 				},
 			}),
+			7 => Ok(Self::Pairing),
 			d => Err(HeaderError::DiscriminatorInvalid(d)),
 		}
 	}
@@ -100,6 +103,7 @@ impl Header {
 				buf.extend_from_slice(&range.to_bytes());
 				buf
 			}
+			Self::Pairing => vec![7],
 		}
 	}
 }
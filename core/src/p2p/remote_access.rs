@@ -0,0 +1,286 @@
+use sd_prisma::prisma::location;
+
+use sd_p2p::RemoteIdentity;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use specta::Type;
+use uuid::Uuid;
+
+/// Node-level queries every grant is allowed to call, the interface can't even start without them.
+const NODE_QUERIES: [&str; 3] = ["buildInfo", "nodeState", "library.list"];
+
+/// Custom URI routes which serve a file from a location, they all look like `/{route}/{library_id}/{location_id}/...`
+const LOCATION_URI_ROUTES: [&str; 3] = ["file", "transcode", "tracks"];
+
+/// Queries whose whole argument is a location id.
+/// Anything else taking a bare id, like `files.get`, identifies something which may live in other locations.
+const LOCATION_QUERIES: [&str; 3] = [
+	"locations.get",
+	"locations.getWithRules",
+	"locations.indexer_rules.listForLocation",
+];
+
+/// Searches only returning file paths or counts, so a location filter is enough to scope them.
+/// `search.objects` isn't here as objects come with their file paths in every location.
+const LOCATION_FILTERED_SEARCHES: [&str; 4] = [
+	"search.paths",
+	"search.pathsCount",
+	"search.fuzzyPaths",
+	"search.objectsCount",
+];
+
+/// Allows a remote node to use this node's rspc router and custom URIs over P2P
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Type)]
+pub struct RemoteAccessGrant {
+	pub identity: RemoteIdentity,
+	pub scope: RemoteAccessScope,
+}
+
+/// What a remote node holding a [`RemoteAccessGrant`] is allowed to do
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Type)]
+#[serde(tag = "type")]
+pub enum RemoteAccessScope {
+	/// Every query and subscription, but no mutations
+	ReadOnly,
+	/// Every procedure of these libraries, including mutations
+	Libraries { library_ids: Vec<Uuid> },
+	/// Browsing and fetching the files of these locations of a library
+	Locations {
+		library_id: Uuid,
+		location_ids: Vec<location::id::Type>,
+	},
+	/// The same access the local interface has
+	Full,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProcedureKind {
+	Query,
+	Mutation,
+	Subscription,
+}
+
+impl ProcedureKind {
+	pub fn from_method(method: &str) -> Option<Self> {
+		match method {
+			"query" => Some(Self::Query),
+			"mutation" => Some(Self::Mutation),
+			"subscription" => Some(Self::Subscription),
+			_ => None,
+		}
+	}
+}
+
+impl RemoteAccessScope {
+	/// Check if an rspc procedure call is allowed, `input` being the raw input of the procedure.
+	pub fn allows_procedure(&self, kind: ProcedureKind, path: &str, input: &Value) -> bool {
+		let library_id = || {
+			input
+				.get("library_id")
+				.and_then(Value::as_str)
+				.and_then(|id| Uuid::parse_str(id).ok())
+		};
+
+		match self {
+			Self::Full => true,
+			_ if kind == ProcedureKind::Query && NODE_QUERIES.contains(&path) => true,
+			Self::ReadOnly => kind != ProcedureKind::Mutation,
+			Self::Libraries { library_ids } => {
+				library_id().is_some_and(|id| library_ids.contains(&id))
+			}
+			Self::Locations {
+				library_id: granted_library_id,
+				location_ids,
+			} => {
+				let is_granted = |id: &Value| {
+					location_ids
+						.iter()
+						.any(|granted| Some(i64::from(*granted)) == id.as_i64())
+				};
+				let arg = input.get("arg").unwrap_or(&Value::Null);
+
+				let scoped = if LOCATION_FILTERED_SEARCHES.contains(&path) {
+					// Filters are all applied, so a single one keeping the search to granted locations is enough
+					arg.get("filters")
+						.and_then(Value::as_array)
+						.is_some_and(|filters| {
+							filters.iter().any(|filter| {
+								let filter = filter.get("filePath").unwrap_or(&Value::Null);
+
+								filter
+									.get("locations")
+									.and_then(|locations| locations.get("in"))
+									.and_then(Value::as_array)
+									.is_some_and(|ids| {
+										!ids.is_empty() && ids.iter().all(is_granted)
+									}) || filter
+									.get("path")
+									.and_then(|path| path.get("location_id"))
+									.is_some_and(is_granted)
+							})
+						})
+				} else {
+					LOCATION_QUERIES.contains(&path) && is_granted(arg)
+				};
+
+				kind != ProcedureKind::Mutation
+					&& library_id() == Some(*granted_library_id)
+					&& scoped
+			}
+		}
+	}
+
+	/// Check if a `GET` request to a custom URI is allowed, `path` being relative to the custom URI router.
+	pub fn allows_uri(&self, path: &str) -> bool {
+		let mut segments = path.trim_start_matches('/').split('/');
+		let route = segments.next().unwrap_or_default();
+		let library_id = segments.next().and_then(|id| Uuid::parse_str(id).ok());
+		let location_id = segments
+			.next()
+			.and_then(|id| id.parse::<location::id::Type>().ok());
+
+		match self {
+			Self::Full => true,
+			// Thumbnails are only named after the content they were generated from, so they leak nothing
+			_ if route == "thumbnail" => true,
+			// Anything else, like `local-file-by-path`, can reach outside of the libraries
+			_ if !LOCATION_URI_ROUTES.contains(&route) => false,
+			Self::ReadOnly => true,
+			Self::Libraries { library_ids } => {
+				library_id.is_some_and(|id| library_ids.contains(&id))
+			}
+			Self::Locations {
+				library_id: granted_library_id,
+				location_ids,
+			} => {
+				library_id == Some(*granted_library_id)
+					&& location_id.is_some_and(|id| location_ids.contains(&id))
+			}
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	use serde_json::json;
+
+	#[test]
+	fn read_only_denies_mutations() {
+		let scope = RemoteAccessScope::ReadOnly;
+
+		assert!(scope.allows_procedure(ProcedureKind::Query, "locations.list", &json!(null)));
+		assert!(scope.allows_procedure(ProcedureKind::Subscription, "jobs.progress", &json!(null)));
+		assert!(!scope.allows_procedure(ProcedureKind::Mutation, "nodes.edit", &json!({})));
+		assert!(!scope.allows_uri("/local-file-by-path/%2Fetc%2Fpasswd"));
+	}
+
+	#[test]
+	fn libraries_are_scoped_by_library_id() {
+		let (granted, other) = (Uuid::new_v4(), Uuid::new_v4());
+		let scope = RemoteAccessScope::Libraries {
+			library_ids: vec![granted],
+		};
+
+		assert!(scope.allows_procedure(
+			ProcedureKind::Mutation,
+			"tags.create",
+			&json!({ "library_id": granted, "arg": { "name": "Holidays" } })
+		));
+		assert!(!scope.allows_procedure(
+			ProcedureKind::Query,
+			"tags.list",
+			&json!({ "library_id": other, "arg": null })
+		));
+		assert!(!scope.allows_procedure(ProcedureKind::Mutation, "nodes.edit", &json!({})));
+		assert!(scope.allows_procedure(ProcedureKind::Query, "library.list", &json!(null)));
+
+		assert!(scope.allows_uri(&format!("/file/{granted}/1/2")));
+		assert!(!scope.allows_uri(&format!("/file/{other}/1/2")));
+	}
+
+	#[test]
+	fn locations_are_scoped_by_location_id() {
+		let library_id = Uuid::new_v4();
+		let scope = RemoteAccessScope::Locations {
+			library_id,
+			location_ids: vec![1],
+		};
+
+		assert!(scope.allows_procedure(
+			ProcedureKind::Query,
+			"locations.get",
+			&json!({ "library_id": library_id, "arg": 1 })
+		));
+		assert!(!scope.allows_procedure(
+			ProcedureKind::Query,
+			"locations.get",
+			&json!({ "library_id": library_id, "arg": 2 })
+		));
+		assert!(!scope.allows_procedure(
+			ProcedureKind::Mutation,
+			"locations.fullRescan",
+			&json!({ "library_id": library_id, "arg": { "location_id": 1, "reidentify_objects": false } })
+		));
+
+		// Ids of anything else aren't location ids, even when they happen to match one
+		for path in ["files.get", "files.getPath"] {
+			assert!(!scope.allows_procedure(
+				ProcedureKind::Query,
+				path,
+				&json!({ "library_id": library_id, "arg": 1 })
+			));
+		}
+
+		assert!(scope.allows_uri(&format!("/file/{library_id}/1/42")));
+		assert!(!scope.allows_uri(&format!("/transcode/{library_id}/2/42")));
+		assert!(scope.allows_uri("/thumbnail/ab/abcdef.webp"));
+	}
+
+	#[test]
+	fn location_searches_are_scoped_by_their_filters() {
+		let library_id = Uuid::new_v4();
+		let scope = RemoteAccessScope::Locations {
+			library_id,
+			location_ids: vec![1, 3],
+		};
+		let search = |path, filters| {
+			scope.allows_procedure(
+				ProcedureKind::Query,
+				path,
+				&json!({ "library_id": library_id, "arg": { "take": 100, "filters": filters } }),
+			)
+		};
+
+		assert!(search(
+			"search.paths",
+			json!([
+				{ "filePath": { "hidden": false } },
+				{ "filePath": { "locations": { "in": [1, 3] } } }
+			])
+		));
+		assert!(search(
+			"search.pathsCount",
+			json!([{ "filePath": { "path": { "location_id": 3, "path": "/photos/", "include_descendants": true } } }])
+		));
+		assert!(!search(
+			"search.paths",
+			json!([{ "filePath": { "locations": { "in": [1, 2] } } }])
+		));
+		assert!(!search(
+			"search.paths",
+			json!([{ "filePath": { "locations": { "notIn": [2] } } }])
+		));
+		assert!(!search(
+			"search.paths",
+			json!([{ "filePath": { "locations": { "in": [] } } }])
+		));
+		assert!(!search("search.paths", json!([])));
+		assert!(!search(
+			"search.objects",
+			json!([{ "filePath": { "locations": { "in": [1] } } }])
+		));
+	}
+}
@@ -18,12 +18,13 @@ use super::P2PManager;
 mod proto;
 pub use proto::*;
 
-pub use originator::run as originator;
+pub use originator::{alert_peer, run as originator};
 mod originator {
 	use crate::p2p::Header;
 
 	use super::*;
 	use responder::tx as rx;
+	use sd_p2p::{Peer, RemoteIdentity};
 	use sd_p2p_tunnel::Tunnel;
 
 	pub mod tx {
@@ -94,39 +95,49 @@ mod originator {
 				continue;
 			};
 
-			let sync = sync.clone();
+			tokio::spawn(alert_peer(
+				library.clone(),
+				sync.clone(),
+				remote_identity,
+				peer,
+			));
+		}
+	}
 
-			let library = library.clone();
-			tokio::spawn(async move {
-				debug!(
-					"Alerting peer {remote_identity:?} of new sync events for library {:?}",
-					library.id
-				);
+	/// Alert a single peer of new sync events, it then requests the operations it's missing.
+	pub async fn alert_peer(
+		library: Arc<Library>,
+		sync: Arc<sync::Manager>,
+		remote_identity: RemoteIdentity,
+		peer: Arc<Peer>,
+	) {
+		debug!(
+			"Alerting peer {remote_identity:?} of new sync events for library {:?}",
+			library.id
+		);
 
-				let mut stream = peer.new_stream().await.unwrap();
+		let mut stream = peer.new_stream().await.unwrap();
 
-				stream.write_all(&Header::Sync.to_bytes()).await.unwrap();
+		stream.write_all(&Header::Sync.to_bytes()).await.unwrap();
 
-				let mut tunnel = Tunnel::initiator(stream, &library.identity).await.unwrap();
+		let mut tunnel = Tunnel::initiator(stream, &library.identity).await.unwrap();
 
-				tunnel
-					.write_all(&SyncMessage::NewOperations.to_bytes())
-					.await
-					.unwrap();
-				tunnel.flush().await.unwrap();
+		tunnel
+			.write_all(&SyncMessage::NewOperations.to_bytes())
+			.await
+			.unwrap();
+		tunnel.flush().await.unwrap();
 
-				while let Ok(rx::MainRequest::GetOperations(args)) =
-					rx::MainRequest::from_stream(&mut tunnel).await
-				{
-					let ops = sync.get_ops(args).await.unwrap();
+		while let Ok(rx::MainRequest::GetOperations(args)) =
+			rx::MainRequest::from_stream(&mut tunnel).await
+		{
+			let ops = sync.get_ops(args).await.unwrap();
 
-					tunnel
-						.write_all(&tx::Operations(CompressedCRDTOperations::new(ops)).to_bytes())
-						.await
-						.unwrap();
-					tunnel.flush().await.unwrap();
-				}
-			});
+			tunnel
+				.write_all(&tx::Operations(CompressedCRDTOperations::new(ops)).to_bytes())
+				.await
+				.unwrap();
+			tunnel.flush().await.unwrap();
 		}
 	}
 }
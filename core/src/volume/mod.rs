@@ -1,27 +1,41 @@
 // Adapted from: https://github.com/kimlimjustin/xplorer/blob/f4f3590d06783d64949766cc2975205a3b689a56/src-tauri/src/drives.rs
 
+use crate::library::Library;
+
+use sd_prisma::prisma::{location, volume};
+
 use std::{
 	fmt::Display,
 	hash::{Hash, Hasher},
-	path::PathBuf,
+	path::{Path, PathBuf},
 	sync::OnceLock,
+	time::{Duration, Instant},
 };
 
+use chrono::Utc;
 use serde::{Deserialize, Serialize};
 use serde_with::{serde_as, DisplayFromStr};
 use specta::Type;
 use sysinfo::{DiskExt, System, SystemExt};
 use thiserror::Error;
 use tokio::sync::Mutex;
-use tracing::error;
+use tracing::{debug, error};
 
 pub mod watcher;
 
+/// How long the result of [`get_volumes_cached`] is reused for
+const VOLUMES_CACHE_TTL: Duration = Duration::from_secs(5);
+
 fn sys_guard() -> &'static Mutex<System> {
 	static SYS: OnceLock<Mutex<System>> = OnceLock::new();
 	SYS.get_or_init(|| Mutex::new(System::new_all()))
 }
 
+fn volumes_cache() -> &'static Mutex<Option<(Instant, Vec<Volume>)>> {
+	static CACHE: OnceLock<Mutex<Option<(Instant, Vec<Volume>)>>> = OnceLock::new();
+	CACHE.get_or_init(|| Mutex::new(None))
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone, Type, Hash, PartialEq, Eq)]
 #[allow(clippy::upper_case_acronyms)]
 pub enum DiskType {
@@ -54,6 +68,52 @@ pub struct Volume {
 	pub disk_type: DiskType,
 	pub file_system: Option<String>,
 	pub is_root_filesystem: bool,
+	/// Filesystem UUID or serial number, when the platform exposes one
+	pub identifier: Option<String>,
+}
+
+impl Volume {
+	/// An id that stays the same between mounts, falling back to the mount point and capacity
+	/// when we couldn't find out the filesystem UUID or serial number, as lots of volumes share
+	/// the same name and file system, like every `Untitled` FAT32 thumb drive
+	pub fn stable_id(&self) -> String {
+		self.identifier.clone().unwrap_or_else(|| {
+			format!(
+				"{}:{}",
+				self.mount_points
+					.first()
+					.map(|mount_point| mount_point.display().to_string())
+					.unwrap_or_default(),
+				self.total_capacity
+			)
+		})
+	}
+
+	/// Returns the mount point of this volume containing `path`, if any
+	pub fn mount_point_for(&self, path: impl AsRef<Path>) -> Option<&Path> {
+		let path = path.as_ref();
+
+		self.mount_points
+			.iter()
+			.filter(|mount_point| path.starts_with(mount_point))
+			.max_by_key(|mount_point| mount_point.as_os_str().len())
+			.map(PathBuf::as_path)
+	}
+}
+
+/// Finds the volume where `path` lives, which is the one with the longest mount point containing it
+pub fn volume_for_path<'v>(volumes: &'v [Volume], path: impl AsRef<Path>) -> Option<&'v Volume> {
+	let path = path.as_ref();
+
+	volumes
+		.iter()
+		.filter_map(|volume| {
+			volume
+				.mount_point_for(path)
+				.map(|mount_point| (mount_point.as_os_str().len(), volume))
+		})
+		.max_by_key(|(mount_point_len, _)| *mount_point_len)
+		.map(|(_, volume)| volume)
 }
 
 impl Hash for Volume {
@@ -99,11 +159,13 @@ impl From<VolumeError> for rspc::Error {
 
 #[cfg(target_os = "linux")]
 pub async fn get_volumes() -> Vec<Volume> {
-	use std::{collections::HashMap, path::Path};
+	use std::collections::HashMap;
 
 	let mut sys = sys_guard().lock().await;
 	sys.refresh_disks_list();
 
+	let filesystem_uuids = get_filesystem_uuids().await;
+
 	let mut volumes: Vec<Volume> = Vec::new();
 	let mut path_to_volume_index = HashMap::new();
 	for disk in sys.disks() {
@@ -117,9 +179,12 @@ pub async fn get_volumes() -> Vec<Volume> {
 		let is_root_filesystem = mount_point.is_absolute() && mount_point.parent().is_none();
 
 		let mut disk_path: PathBuf = PathBuf::from(disk_name);
+		let identifier;
 		if file_system.as_ref().map(|fs| fs == "ZFS").unwrap_or(false) {
 			// Use a custom path for ZFS disks to avoid conflicts with normal disks paths
 			disk_path = Path::new("zfs://").join(disk_path);
+			// ZFS datasets don't have a filesystem UUID, but their names are unique and stable
+			identifier = Some(disk_path.to_string_lossy().to_string());
 		} else {
 			// Ignore non-devices disks (overlay, fuse, tmpfs, etc.)
 			if !disk_path.starts_with("/dev") {
@@ -139,6 +204,8 @@ pub async fn get_volumes() -> Vec<Volume> {
 				Ok(real_path) => real_path,
 			};
 
+			identifier = filesystem_uuids.get(&real_path).cloned();
+
 			// Check if disk is a symlink to another disk
 			if real_path != disk_path {
 				// Disk is a symlink to another disk, assign it to the same volume
@@ -210,12 +277,35 @@ pub async fn get_volumes() -> Vec<Volume> {
 			total_capacity,
 			available_capacity,
 			is_root_filesystem,
+			identifier,
 		});
 	}
 
 	volumes
 }
 
+/// Maps each block device to its filesystem UUID, using the symlinks udev keeps in `/dev/disk/by-uuid`
+#[cfg(target_os = "linux")]
+async fn get_filesystem_uuids() -> std::collections::HashMap<PathBuf, String> {
+	let mut uuids = std::collections::HashMap::new();
+
+	let mut entries = match tokio::fs::read_dir("/dev/disk/by-uuid").await {
+		Ok(entries) => entries,
+		Err(e) => {
+			debug!("Failed to read filesystem UUIDs: {e:#?}");
+			return uuids;
+		}
+	};
+
+	while let Ok(Some(entry)) = entries.next_entry().await {
+		if let Ok(device_path) = tokio::fs::canonicalize(entry.path()).await {
+			uuids.insert(device_path, entry.file_name().to_string_lossy().to_string());
+		}
+	}
+
+	uuids
+}
+
 #[cfg(target_os = "ios")]
 pub async fn get_volumes() -> Vec<Volume> {
 	use std::os::unix::fs::MetadataExt;
@@ -263,6 +353,7 @@ pub async fn get_volumes() -> Vec<Volume> {
 			total_capacity: total_space,
 			available_capacity: free_space,
 			is_root_filesystem: true,
+			identifier: None,
 		});
 	}
 
@@ -425,6 +516,8 @@ pub async fn get_volumes() -> Vec<Volume> {
 			name = "Unknown".to_string()
 		}
 
+		let identifier = get_volume_identifier(&mount_point).await;
+
 		Some(Volume {
 			name,
 			disk_type: if disk.is_removable() {
@@ -441,6 +534,7 @@ pub async fn get_volumes() -> Vec<Volume> {
 			total_capacity,
 			available_capacity,
 			is_root_filesystem,
+			identifier,
 		})
 	}))
 	.await
@@ -449,40 +543,211 @@ pub async fn get_volumes() -> Vec<Volume> {
 	.collect::<Vec<Volume>>()
 }
 
-// pub async fn save_volume(library: &Library) -> Result<(), VolumeError> {
-// 	// enter all volumes associate with this client add to db
-// 	for volume in get_volumes() {
-// 		let params = vec![
-// 			disk_type::set(volume.disk_type.map(|t| t.to_string())),
-// 			filesystem::set(volume.file_system.clone()),
-// 			total_bytes_capacity::set(volume.total_capacity.to_string()),
-// 			total_bytes_available::set(volume.available_capacity.to_string()),
-// 		];
-
-// 		library
-// 			.db
-// 			.volume()
-// 			.upsert(
-// 				node_id_mount_point_name(
-// 					library.node_local_id,
-// 					volume.mount_point,
-// 					volume.name,
-// 				),
-// 				volume::create(
-// 					library.node_local_id,
-// 					volume.name,
-// 					volume.mount_point,
-// 					params.clone(),
-// 				),
-// 				params,
-// 			)
-// 			.exec()
-// 			.await?;
-// 	}
-// 	// cleanup: remove all unmodified volumes associate with this client
-
-// 	Ok(())
-// }
+#[cfg(target_os = "macos")]
+#[derive(Deserialize)]
+struct DiskUtilInfo {
+	#[serde(rename = "VolumeUUID")]
+	volume_uuid: Option<String>,
+}
+
+/// Gets the filesystem UUID (macOS) or serial number (Windows) of the volume mounted at `mount_point`.
+///
+/// These require spawning a process, so results are cached by mount point.
+#[cfg(not(any(target_os = "linux", target_os = "ios")))]
+async fn get_volume_identifier(mount_point: &Path) -> Option<String> {
+	use std::collections::HashMap;
+
+	static IDENTIFIERS: OnceLock<Mutex<HashMap<PathBuf, Option<String>>>> = OnceLock::new();
+
+	let mut identifiers = IDENTIFIERS
+		.get_or_init(|| Mutex::new(HashMap::new()))
+		.lock()
+		.await;
+
+	if let Some(identifier) = identifiers.get(mount_point) {
+		return identifier.clone();
+	}
+
+	#[cfg(target_os = "macos")]
+	let identifier = tokio::process::Command::new("diskutil")
+		.args(["info", "-plist"])
+		.arg(mount_point)
+		.output()
+		.await
+		.map_err(|e| error!("Failed to execute diskutil: {e:#?}"))
+		.ok()
+		.filter(|output| output.status.success())
+		.and_then(|output| {
+			plist::from_bytes::<DiskUtilInfo>(&output.stdout)
+				.map_err(|e| error!("Failed to parse diskutil output: {e:#?}"))
+				.ok()
+		})
+		.and_then(|info| info.volume_uuid);
+
+	#[cfg(windows)]
+	let identifier = {
+		let drive = mount_point.to_string_lossy();
+
+		tokio::process::Command::new("cmd")
+			.args(["/C", &format!("vol {}", drive.trim_end_matches('\\'))])
+			.output()
+			.await
+			.map_err(|e| error!("Failed to execute vol: {e:#?}"))
+			.ok()
+			.filter(|output| output.status.success())
+			.and_then(|output| String::from_utf8(output.stdout).ok())
+			.and_then(|output| {
+				// The serial number is the last word of the output, like `Volume Serial Number is 1A2B-3C4D`
+				output
+					.split_whitespace()
+					.last()
+					.filter(|serial| serial.len() == 9 && serial.contains('-'))
+					.map(str::to_string)
+			})
+	};
+
+	#[cfg(not(any(target_os = "macos", windows)))]
+	let identifier = None;
+
+	identifiers.insert(mount_point.to_path_buf(), identifier.clone());
+
+	identifier
+}
+
+/// Same as [`get_volumes`], but reuses the last result for a few seconds,
+/// as this is called every time we check if a location is online.
+pub async fn get_volumes_cached() -> Vec<Volume> {
+	let mut cache = volumes_cache().lock().await;
+
+	if let Some((fetched_at, volumes)) = cache.as_ref() {
+		if fetched_at.elapsed() < VOLUMES_CACHE_TTL {
+			return volumes.clone();
+		}
+	}
+
+	let volumes = get_volumes().await;
+	*cache = Some((Instant::now(), volumes.clone()));
+
+	volumes
+}
+
+/// Persists the currently mounted volumes in the library, marking the ones we didn't see as unmounted,
+/// and links every location of this instance to the volume it lives in.
+pub async fn save_volumes(library: &Library, volumes: &[Volume]) -> Result<(), VolumeError> {
+	let db = &library.db;
+
+	let mut mounted_volumes_ids = Vec::with_capacity(volumes.len());
+
+	for volume in volumes {
+		use volume::*;
+
+		let mount_point = volume
+			.mount_points
+			.first()
+			.map(|mount_point| mount_point.to_string_lossy().to_string())
+			.unwrap_or_default();
+
+		let params = vec![
+			name::set(volume.name.clone()),
+			mount_point::set(mount_point.clone()),
+			total_bytes_capacity::set(volume.total_capacity.to_string()),
+			total_bytes_available::set(volume.available_capacity.to_string()),
+			disk_type::set(Some(volume.disk_type.to_string())),
+			filesystem::set(volume.file_system.clone()),
+			is_system::set(volume.is_root_filesystem),
+			is_mounted::set(true),
+			date_modified::set(Utc::now().into()),
+		];
+
+		let saved = db
+			.volume()
+			.upsert(
+				identifier::equals(volume.stable_id()),
+				create(
+					volume.stable_id(),
+					volume.name.clone(),
+					mount_point,
+					params.clone(),
+				),
+				params,
+			)
+			.select(volume::select!({ id }))
+			.exec()
+			.await?;
+
+		mounted_volumes_ids.push(saved.id);
+	}
+
+	db.volume()
+		.update_many(
+			vec![
+				volume::id::not_in_vec(mounted_volumes_ids),
+				volume::is_mounted::equals(true),
+			],
+			vec![
+				volume::is_mounted::set(false),
+				volume::date_modified::set(Utc::now().into()),
+			],
+		)
+		.exec()
+		.await?;
+
+	link_locations_to_volumes(library, volumes).await
+}
+
+/// Links the locations of this instance that are currently reachable to the volume they live in
+async fn link_locations_to_volumes(
+	library: &Library,
+	volumes: &[Volume],
+) -> Result<(), VolumeError> {
+	let db = &library.db;
+
+	let locations = db
+		.location()
+		.find_many(vec![location::instance_id::equals(Some(
+			library.config().await.instance_id,
+		))])
+		.select(location::select!({ id path volume: select { identifier } }))
+		.exec()
+		.await?;
+
+	for location in locations {
+		let Some(path) = location.path else {
+			continue;
+		};
+
+		// A location in an unmounted volume would be matched with whatever volume holds its
+		// mount point directory now, so we only link locations we can actually reach
+		if tokio::fs::metadata(&path).await.is_err() {
+			continue;
+		}
+
+		let Some(volume) = volume_for_path(volumes, &path) else {
+			continue;
+		};
+
+		let stable_id = volume.stable_id();
+
+		if location.volume.map(|volume| volume.identifier).as_ref() != Some(&stable_id) {
+			debug!(
+				"Linking location <id='{}'> to volume <identifier='{stable_id}'>",
+				location.id
+			);
+
+			db.location()
+				.update(
+					location::id::equals(location.id),
+					vec![location::volume::connect(volume::identifier::equals(
+						stable_id,
+					))],
+				)
+				.exec()
+				.await?;
+		}
+	}
+
+	Ok(())
+}
 
 // #[test]
 // fn test_get_volumes() {
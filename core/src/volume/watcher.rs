@@ -10,8 +10,9 @@ pub fn spawn_volume_watcher(library: Arc<Library>) {
 		spawn,
 		time::{interval, Duration},
 	};
+	use tracing::error;
 
-	use super::get_volumes;
+	use super::{get_volumes, save_volumes};
 	spawn(async move {
 		let mut interval = interval(Duration::from_secs(1));
 		let mut existing_volumes = get_volumes().await.into_iter().collect::<HashSet<_>>();
@@ -22,6 +23,15 @@ pub fn spawn_volume_watcher(library: Arc<Library>) {
 			let current_volumes = get_volumes().await.into_iter().collect::<HashSet<_>>();
 
 			if existing_volumes != current_volumes {
+				if let Err(e) = save_volumes(
+					&library,
+					&current_volumes.iter().cloned().collect::<Vec<_>>(),
+				)
+				.await
+				{
+					error!("Failed to save volumes: {e:#?}");
+				}
+
 				existing_volumes = current_volumes;
 				invalidate_query!(&library, "volumes.list");
 			}
@@ -197,7 +197,7 @@ pub mod library {
 	pub mod create {
 		use super::*;
 
-		#[derive(Debug, Deserialize)]
+		#[derive(Debug, Serialize, Deserialize)]
 		pub struct CreateResult {
 			pub id: String,
 		}
@@ -352,7 +352,7 @@ pub mod library {
 		pub mod get {
 			use super::*;
 
-			#[derive(Serialize)]
+			#[derive(Serialize, Deserialize)]
 			#[serde(rename_all = "camelCase")]
 			pub struct InstanceTimestamp {
 				pub instance_uuid: Uuid,
@@ -395,7 +395,7 @@ pub mod library {
 		pub mod request_add {
 			use super::*;
 
-			#[derive(Deserialize, Debug)]
+			#[derive(Serialize, Deserialize, Debug)]
 			#[serde(rename_all = "camelCase")]
 			pub struct RequestAdd {
 				pub instance_uuid: Uuid,
@@ -441,7 +441,7 @@ pub mod library {
 		pub mod do_add {
 			use super::*;
 
-			#[derive(Serialize, Debug)]
+			#[derive(Serialize, Deserialize, Debug)]
 			#[serde(rename_all = "camelCase")]
 			pub struct Input {
 				pub uuid: Uuid,
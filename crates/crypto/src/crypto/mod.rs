@@ -610,6 +610,54 @@ mod tests {
 		assert_eq!(buf, output);
 	}
 
+	#[test]
+	#[cfg_attr(miri, ignore)]
+	fn xchacha20_poly1305_encrypt_and_decrypt_with_short_reads() {
+		// Like a `BufReader` handing out what's left of its buffer
+		struct ShortReader<R>(R);
+
+		impl<R: std::io::Read> std::io::Read for ShortReader<R> {
+			fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+				let len = buf.len().min(4096);
+				self.0.read(&mut buf[..len])
+			}
+		}
+
+		let buf = CryptoRng::generate_vec(BLOCK_LEN * 2 + 5);
+
+		let mut reader = ShortReader(Cursor::new(&buf));
+		let mut writer = Cursor::new(Vec::new());
+
+		let encryptor = Encryptor::new(
+			&Key::new([0x23; KEY_LEN]),
+			&XCHACHA20_POLY1305_NONCE,
+			Algorithm::XChaCha20Poly1305,
+		)
+		.unwrap();
+
+		encryptor
+			.encrypt_streams(&mut reader, &mut writer, Aad::Null)
+			.unwrap();
+
+		let mut reader = ShortReader(Cursor::new(writer.into_inner()));
+		let mut writer = Cursor::new(Vec::new());
+
+		let decryptor = Decryptor::new(
+			&Key::new([0x23; KEY_LEN]),
+			&XCHACHA20_POLY1305_NONCE,
+			Algorithm::XChaCha20Poly1305,
+		)
+		.unwrap();
+
+		decryptor
+			.decrypt_streams(&mut reader, &mut writer, Aad::Null)
+			.unwrap();
+
+		let output = writer.into_inner();
+
+		assert_eq!(buf, output);
+	}
+
 	#[test]
 	#[ignore]
 	fn xchacha20_poly1305_encrypt_and_decrypt_128mib() {
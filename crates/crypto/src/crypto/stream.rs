@@ -16,6 +16,41 @@ use chacha20poly1305::XChaCha20Poly1305;
 #[cfg(feature = "tokio")]
 use tokio::io::{AsyncReadExt, AsyncWriteExt};
 
+/// Fills `buffer` as much as possible, so that a short read always means the last block
+fn read_block<R: Read>(reader: &mut R, buffer: &mut [u8]) -> std::io::Result<usize> {
+	let mut count = 0;
+
+	while count < buffer.len() {
+		match reader.read(&mut buffer[count..]) {
+			Ok(0) => break,
+			Ok(n) => count += n,
+			Err(e) if e.kind() == std::io::ErrorKind::Interrupted => {}
+			Err(e) => return Err(e),
+		}
+	}
+
+	Ok(count)
+}
+
+#[cfg(feature = "tokio")]
+async fn read_block_async<R: AsyncReadExt + Unpin + Send>(
+	reader: &mut R,
+	buffer: &mut [u8],
+) -> std::io::Result<usize> {
+	let mut count = 0;
+
+	while count < buffer.len() {
+		match reader.read(&mut buffer[count..]).await {
+			Ok(0) => break,
+			Ok(n) => count += n,
+			Err(e) if e.kind() == std::io::ErrorKind::Interrupted => {}
+			Err(e) => return Err(e),
+		}
+	}
+
+	Ok(count)
+}
+
 macro_rules! impl_stream {
 	(
 	$name:ident, // "Decryptor", "Encryptor"
@@ -107,7 +142,7 @@ macro_rules! impl_stream {
 				let mut buffer = vec![0u8; $size].into_boxed_slice();
 
 				loop {
-					let count = reader.read(&mut buffer)?;
+					let count = read_block(&mut reader, &mut buffer)?;
 
 					let payload = Payload {
 						aad: aad.inner(),
@@ -150,7 +185,7 @@ macro_rules! impl_stream {
 				let mut buffer = vec![0u8; $size].into_boxed_slice();
 
 				loop {
-					let count = reader.read(&mut buffer).await?;
+					let count = read_block_async(&mut reader, &mut buffer).await?;
 
 					// TODO(brxken128): block on `next_fn` and `last_fn` exclusively
 
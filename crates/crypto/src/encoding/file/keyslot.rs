@@ -1,3 +1,5 @@
+use bincode::{Decode, Encode};
+
 use crate::{
 	crypto::{Decryptor, Encryptor},
 	hashing::Hasher,
@@ -6,6 +8,7 @@ use crate::{
 	Result,
 };
 
+#[derive(Clone, Encode, Decode)]
 pub struct Keyslot {
 	pub hashing_algorithm: HashingAlgorithm, // password hashing algorithm
 	pub hash_salt: Salt,                     // salt to hash the password with
@@ -42,7 +45,7 @@ impl Keyslot {
 		})
 	}
 
-	pub(super) fn decrypt(
+	pub fn decrypt(
 		&self,
 		algorithm: Algorithm,
 		key: &Key,
@@ -47,9 +47,6 @@ pub mod vault;
 ))]
 pub mod keyring;
 
-#[cfg(feature = "sys")]
-pub mod sys;
-
 pub use self::error::{Error, Result};
 pub use aead::Payload;
 pub use protected::Protected;
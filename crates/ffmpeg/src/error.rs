@@ -28,6 +28,8 @@ pub enum Error {
 	InvalidQuality(f32),
 	#[error("Received an invalid seek percentage: {0}")]
 	InvalidSeekPercentage(f32),
+	#[error("Received an invalid frame count, expected at least 1 frame")]
+	InvalidFrameCount,
 	#[error("Error while casting an integer to another integer type")]
 	IntCastError(#[from] TryFromIntError),
 	#[error("Duration for video stream is unavailable")]
@@ -46,6 +48,8 @@ pub enum Error {
 	SeekError,
 	#[error("Seek not allowed")]
 	SeekNotAllowed,
+	#[error("Failed to encode animated webp: {0}")]
+	AnimatedWebPEncoding(String),
 
 	#[error(transparent)]
 	FileIO(#[from] FileIOError),
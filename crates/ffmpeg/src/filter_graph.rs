@@ -157,6 +157,72 @@ impl<'a> FFmpegFilterGraph {
 		Ok((filter_graph, filter_source_ctx, filter_sink_ctx))
 	}
 
+	/// Builds a graph where frames go through each one of `filters`, in order
+	pub(crate) fn chain_graph(
+		source: &CStr,
+		source_args: &str,
+		sink: &CStr,
+		filters: &[(&CStr, Option<CString>)],
+	) -> Result<(Self, &'a mut AVFilterContext, &'a mut AVFilterContext), Error> {
+		let mut filter_graph = Self::new()?;
+
+		let mut filter_source = ptr::null_mut();
+		filter_graph.setup_filter(
+			&mut filter_source,
+			source,
+			c"chain_source",
+			Some(CString::new(source_args)?.as_c_str()),
+			"Failed to create filter source",
+		)?;
+
+		let mut filter_sink = ptr::null_mut();
+		filter_graph.setup_filter(
+			&mut filter_sink,
+			sink,
+			c"chain_sink",
+			None,
+			"Failed to create filter sink",
+		)?;
+
+		let mut previous_filter = filter_source;
+		for (index, (name, args)) in filters.iter().enumerate() {
+			let mut filter = ptr::null_mut();
+			filter_graph.setup_filter(
+				&mut filter,
+				name,
+				CString::new(format!("chain_{index}"))?.as_c_str(),
+				args.as_deref(),
+				"Failed to create chained filter",
+			)?;
+
+			Self::link(
+				previous_filter,
+				0,
+				filter,
+				0,
+				"Failed to link chained filter",
+			)?;
+
+			previous_filter = filter;
+		}
+
+		Self::link(
+			previous_filter,
+			0,
+			filter_sink,
+			0,
+			"Failed to link final filter",
+		)?;
+
+		filter_graph.config()?;
+
+		Ok((
+			filter_graph,
+			unsafe { filter_source.as_mut() }.ok_or(FFmpegError::NullError)?,
+			unsafe { filter_sink.as_mut() }.ok_or(FFmpegError::NullError)?,
+		))
+	}
+
 	pub(crate) fn as_mut(&mut self) -> &mut AVFilterGraph {
 		unsafe { self.0.as_mut() }.expect("initialized on struct creation")
 	}
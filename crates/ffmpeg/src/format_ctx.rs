@@ -205,7 +205,7 @@ impl FFmpegFormatContext {
 			.unwrap_or(vec![])
 	}
 
-	fn start_time(&self) -> Option<i64> {
+	pub(crate) fn start_time(&self) -> Option<i64> {
 		let start_time = self.as_ref().start_time;
 		if start_time == AV_NOPTS_VALUE {
 			return None;
@@ -27,11 +27,14 @@
 #![forbid(deprecated_in_future)]
 #![allow(clippy::missing_errors_doc, clippy::module_name_repetitions)]
 
-use crate::{format_ctx::FFmpegFormatContext, frame_decoder::FrameDecoder, utils::from_path};
+use crate::{
+	error::FFmpegError, format_ctx::FFmpegFormatContext, frame_decoder::FrameDecoder,
+	utils::from_path,
+};
 
 use std::path::Path;
 
-use ffmpeg_sys_next::{av_log_set_level, AV_LOG_FATAL};
+use ffmpeg_sys_next::{av_log_set_level, AV_LOG_FATAL, AV_TIME_BASE};
 
 mod codec_ctx;
 mod dict;
@@ -40,15 +43,20 @@ mod filter_graph;
 mod format_ctx;
 mod frame_decoder;
 pub mod model;
+mod subtitle;
 mod thumbnailer;
+mod thumbstrip;
+mod transcode;
 mod utils;
 mod video_frame;
 
 pub use error::Error;
 pub use frame_decoder::ThumbnailSize;
-pub use model::FFmpegMediaData;
+pub use model::{FFmpegMediaData, FFmpegSubtitleCue, FFmpegSubtitleTrack};
 pub use thumbnailer::ThumbnailerBuilder;
+pub use thumbstrip::{Thumbstrip, ThumbstripFrame};
 use tokio::task::spawn_blocking;
+pub use transcode::{hls_segment_count, HLS_SEGMENT_EXTENSION};
 
 /// Helper function to generate retrieve media data from from a video/audio file
 pub async fn probe(filename: impl AsRef<Path> + Send) -> Result<FFmpegMediaData, Error> {
@@ -97,6 +105,154 @@ pub async fn to_thumbnail(
 		.await
 }
 
+/// Helper function to generate a thumbnail file from the cover art embedded in an audio file.
+///
+/// Returns `false` if the file has no cover art, as audio files usually don't have any other
+/// video stream that we could take a frame from.
+pub async fn to_cover_art_thumbnail(
+	audio_file_path: impl AsRef<Path> + Send,
+	output_thumbnail_path: impl AsRef<Path> + Send,
+	size: ThumbnailSize,
+	quality: f32,
+) -> Result<bool, Error> {
+	// Reduce the amount of logs generated by FFmpeg
+	unsafe { av_log_set_level(AV_LOG_FATAL) };
+
+	match ThumbnailerBuilder::new()
+		.size(size)
+		.quality(quality)?
+		.prefer_embedded_metadata(true)
+		.build()
+		.process(audio_file_path, output_thumbnail_path)
+		.await
+	{
+		Ok(()) => Ok(true),
+		Err(Error::FFmpeg(FFmpegError::StreamNotFound)) => Ok(false),
+		Err(e) => Err(e),
+	}
+}
+
+/// Helper function to generate a thumbstrip, a sprite sheet with `frame_count` frames taken at even
+/// intervals of a video file, returning the timing index of the frames in the sprite sheet
+pub async fn to_thumbstrip(
+	video_file_path: impl AsRef<Path> + Send,
+	output_thumbstrip_path: impl AsRef<Path> + Send,
+	frame_count: u32,
+	frame_size: ThumbnailSize,
+	quality: f32,
+) -> Result<Thumbstrip, Error> {
+	// Reduce the amount of logs generated by FFmpeg
+	unsafe { av_log_set_level(AV_LOG_FATAL) };
+
+	thumbstrip::generate_thumbstrip(
+		video_file_path,
+		output_thumbstrip_path,
+		frame_count,
+		frame_size,
+		quality,
+	)
+	.await
+}
+
+/// Helper function to generate a short looping animated webp preview from a video file,
+/// made of `frame_count` frames taken at even intervals and shown for `frame_duration_ms` each
+pub async fn to_animated_preview(
+	video_file_path: impl AsRef<Path> + Send,
+	output_preview_path: impl AsRef<Path> + Send,
+	frame_count: u32,
+	frame_size: ThumbnailSize,
+	frame_duration_ms: u32,
+	quality: f32,
+) -> Result<(), Error> {
+	// Reduce the amount of logs generated by FFmpeg
+	unsafe { av_log_set_level(AV_LOG_FATAL) };
+
+	thumbstrip::generate_animated_preview(
+		video_file_path,
+		output_preview_path,
+		frame_count,
+		frame_size,
+		frame_duration_ms,
+		quality,
+	)
+	.await
+}
+
+/// Helper function to build the HLS playlist of a video/audio file, splitting it in segments of
+/// `segment_duration` seconds to be transcoded on demand with [`to_hls_segment`]
+pub async fn to_hls_playlist(
+	media_file_path: impl AsRef<Path> + Send,
+	segment_duration: f64,
+	segment_uri_prefix: &str,
+) -> Result<String, Error> {
+	// Reduce the amount of logs generated by FFmpeg
+	unsafe { av_log_set_level(AV_LOG_FATAL) };
+
+	let duration = spawn_blocking({
+		let media_file_path = media_file_path.as_ref().to_path_buf();
+		move || -> Result<_, Error> {
+			let mut fmt_ctx =
+				FFmpegFormatContext::open_file(from_path(media_file_path)?.as_c_str())?;
+			fmt_ctx.find_stream_info()?;
+
+			fmt_ctx.duration().ok_or(Error::NoVideoDuration)
+		}
+	})
+	.await??;
+
+	#[allow(clippy::cast_precision_loss)]
+	Ok(transcode::hls_playlist(
+		// SAFETY: the duration would need to be humongous for this cast to f64 to cause problems
+		duration as f64 / f64::from(AV_TIME_BASE),
+		segment_duration,
+		segment_uri_prefix,
+	))
+}
+
+/// Helper function to transcode the segment at `index` of the playlist built by
+/// [`to_hls_playlist`] into a MPEG-TS file, with H.264 video no taller than `max_height` and AAC
+/// audio, so it can be played by any browser
+pub async fn to_hls_segment(
+	media_file_path: impl AsRef<Path> + Send,
+	output_segment_path: impl AsRef<Path> + Send,
+	index: u32,
+	segment_duration: f64,
+	max_height: u32,
+) -> Result<(), Error> {
+	// Reduce the amount of logs generated by FFmpeg
+	unsafe { av_log_set_level(AV_LOG_FATAL) };
+
+	spawn_blocking({
+		let media_file_path = media_file_path.as_ref().to_path_buf();
+		let output_segment_path = output_segment_path.as_ref().to_path_buf();
+		move || {
+			transcode::transcode_segment(
+				&media_file_path,
+				&output_segment_path,
+				f64::from(index) * segment_duration,
+				segment_duration,
+				max_height,
+			)
+		}
+	})
+	.await?
+}
+
+/// Helper function to extract the text subtitle streams of a media file, or of a sidecar
+/// subtitle file, along with their cues
+pub async fn extract_subtitles(
+	media_file_path: impl AsRef<Path> + Send,
+) -> Result<Vec<FFmpegSubtitleTrack>, Error> {
+	// Reduce the amount of logs generated by FFmpeg
+	unsafe { av_log_set_level(AV_LOG_FATAL) };
+
+	spawn_blocking({
+		let media_file_path = media_file_path.as_ref().to_path_buf();
+		move || subtitle::extract_subtitles(&media_file_path)
+	})
+	.await?
+}
+
 #[cfg(test)]
 mod tests {
 	use super::*;
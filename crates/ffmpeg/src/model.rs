@@ -122,3 +122,19 @@ pub struct FFmpegSubtitleProps {
 	pub width: i32,
 	pub height: i32,
 }
+
+/// A text subtitle stream embedded in a media file
+#[derive(Debug)]
+pub struct FFmpegSubtitleTrack {
+	pub stream_id: i32,
+	pub language: Option<String>,
+	pub title: Option<String>,
+	pub cues: Vec<FFmpegSubtitleCue>,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct FFmpegSubtitleCue {
+	pub start_ms: i64,
+	pub end_ms: i64,
+	pub text: String,
+}
@@ -0,0 +1,223 @@
+use crate::{
+	codec_ctx::FFmpegCodecContext,
+	dict::FFmpegDictionary,
+	error::{Error, FFmpegError},
+	format_ctx::FFmpegFormatContext,
+	model::{FFmpegSubtitleCue, FFmpegSubtitleTrack},
+	transcode::open_decoder,
+	utils::from_path,
+	video_frame::FFmpegPacket,
+};
+
+use std::{
+	ffi::{c_int, CStr},
+	mem,
+	path::Path,
+};
+
+use ffmpeg_sys_next::{
+	av_packet_unref, av_rescale_q, avcodec_decode_subtitle2, avsubtitle_free, AVMediaType,
+	AVPacket, AVRational, AVStream, AVSubtitle, AVSubtitleType, AV_NOPTS_VALUE,
+};
+
+const MILLISECONDS: AVRational = AVRational { num: 1, den: 1000 };
+
+struct SubtitleDecoder {
+	stream_index: c_int,
+	time_base: AVRational,
+	decoder: FFmpegCodecContext,
+	track: FFmpegSubtitleTrack,
+}
+
+impl SubtitleDecoder {
+	fn new(stream: &AVStream) -> Result<Self, Error> {
+		let metadata = FFmpegDictionary::new(unsafe { stream.metadata.as_mut() });
+
+		Ok(Self {
+			stream_index: stream.index,
+			time_base: stream.time_base,
+			decoder: open_decoder(stream)?,
+			track: FFmpegSubtitleTrack {
+				stream_id: stream.id,
+				language: metadata.get(c"language"),
+				title: metadata.get(c"title"),
+				cues: vec![],
+			},
+		})
+	}
+
+	fn decode(&mut self, packet: &AVPacket, start_offset_ms: i64) {
+		let mut subtitle = unsafe { mem::zeroed::<AVSubtitle>() };
+		let mut got_subtitle = 0;
+
+		// A broken packet only costs us a single line, so we just skip it
+		if unsafe {
+			avcodec_decode_subtitle2(
+				self.decoder.as_mut(),
+				&mut subtitle,
+				&mut got_subtitle,
+				packet,
+			)
+		} < 0 || got_subtitle == 0
+		{
+			return;
+		}
+
+		let text = subtitle_text(&subtitle);
+
+		let pts_ms = if subtitle.pts == AV_NOPTS_VALUE {
+			unsafe { av_rescale_q(packet.pts, self.time_base, MILLISECONDS) }
+		} else {
+			// The decoder gives us the pts in AV_TIME_BASE units
+			subtitle.pts / 1000
+		} - start_offset_ms;
+
+		// Some formats only tell how long a line is shown through the packet duration
+		let end_display_time = if subtitle.end_display_time > 0 {
+			i64::from(subtitle.end_display_time)
+		} else {
+			unsafe { av_rescale_q(packet.duration, self.time_base, MILLISECONDS) }
+		};
+
+		let start_display_time = i64::from(subtitle.start_display_time);
+
+		unsafe { avsubtitle_free(&mut subtitle) };
+
+		if let Some(text) = text {
+			self.track.cues.push(FFmpegSubtitleCue {
+				start_ms: pts_ms + start_display_time,
+				end_ms: pts_ms + end_display_time,
+				text,
+			});
+		}
+	}
+}
+
+/// Text of every rect in a subtitle, bitmap subtitles have none and we can't OCR them
+fn subtitle_text(subtitle: &AVSubtitle) -> Option<String> {
+	let lines = (0..subtitle.num_rects as usize)
+		.filter_map(|i| unsafe { (*subtitle.rects.add(i)).as_ref() })
+		.filter_map(|rect| match rect.type_ {
+			AVSubtitleType::SUBTITLE_ASS if !rect.ass.is_null() => Some(ass_dialogue_to_text(
+				&unsafe { CStr::from_ptr(rect.ass) }.to_string_lossy(),
+			)),
+			AVSubtitleType::SUBTITLE_TEXT if !rect.text.is_null() => Some(
+				unsafe { CStr::from_ptr(rect.text) }
+					.to_string_lossy()
+					.trim()
+					.to_string(),
+			),
+			_ => None,
+		})
+		.filter(|text| !text.is_empty())
+		.collect::<Vec<_>>();
+
+	(!lines.is_empty()).then(|| lines.join("\n"))
+}
+
+/// Plain text of an ASS dialogue event as output by `FFmpeg` decoders, which is
+/// `ReadOrder,Layer,Style,Name,MarginL,MarginR,MarginV,Effect,Text`, with the override blocks
+/// (`{\i1}` and such) removed
+fn ass_dialogue_to_text(dialogue: &str) -> String {
+	let text = dialogue.splitn(9, ',').nth(8).unwrap_or(dialogue);
+
+	let mut plain = String::with_capacity(text.len());
+	let mut in_override_block = false;
+	let mut chars = text.chars().peekable();
+	while let Some(c) = chars.next() {
+		match c {
+			'{' => in_override_block = true,
+			'}' if in_override_block => in_override_block = false,
+			_ if in_override_block => {}
+			'\\' => match chars.peek() {
+				Some('N' | 'n') => {
+					chars.next();
+					plain.push('\n');
+				}
+				Some('h') => {
+					chars.next();
+					plain.push(' ');
+				}
+				_ => plain.push(c),
+			},
+			_ => plain.push(c),
+		}
+	}
+
+	plain
+		.lines()
+		.map(str::trim)
+		.filter(|line| !line.is_empty())
+		.collect::<Vec<_>>()
+		.join("\n")
+}
+
+/// Decodes every text subtitle stream of a media file. Sidecar subtitle files (`.srt`, `.ass`
+/// and the like) are media files with a single subtitle stream for `FFmpeg`, so they work too.
+pub(crate) fn extract_subtitles(filename: &Path) -> Result<Vec<FFmpegSubtitleTrack>, Error> {
+	let mut fmt_ctx = FFmpegFormatContext::open_file(from_path(filename)?.as_c_str())?;
+	fmt_ctx.find_stream_info()?;
+
+	let mut decoders = (0..fmt_ctx.as_ref().nb_streams)
+		.filter_map(|index| fmt_ctx.stream(index))
+		.filter(|stream| {
+			unsafe { stream.codecpar.as_ref() }.is_some_and(|codec_params| {
+				codec_params.codec_type == AVMediaType::AVMEDIA_TYPE_SUBTITLE
+			})
+		})
+		// Subtitle streams without a decoder available aren't worth failing the whole file
+		.filter_map(|stream| SubtitleDecoder::new(stream).ok())
+		.collect::<Vec<_>>();
+
+	if decoders.is_empty() {
+		return Ok(vec![]);
+	}
+
+	// Cues are relative to the start of the media, which is what players expect
+	let start_offset_ms = fmt_ctx
+		.start_time()
+		.map_or(0, |start_time| start_time / 1000);
+
+	let mut packet = FFmpegPacket::new()?;
+	loop {
+		match fmt_ctx.read_frame(packet.as_mut()) {
+			Ok(_) => {}
+			Err(Error::FFmpegWithReason(FFmpegError::Eof, _)) => break,
+			Err(e) => return Err(e),
+		}
+
+		let packet = packet.as_mut();
+		if let Some(decoder) = decoders
+			.iter_mut()
+			.find(|decoder| decoder.stream_index == packet.stream_index)
+		{
+			decoder.decode(packet, start_offset_ms);
+		}
+
+		unsafe { av_packet_unref(packet) };
+	}
+
+	Ok(decoders
+		.into_iter()
+		.map(|decoder| decoder.track)
+		.filter(|track| !track.cues.is_empty())
+		.collect())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn strips_ass_dialogue() {
+		assert_eq!(
+			ass_dialogue_to_text("0,0,Default,,0,0,0,,{\\i1}Hello,{\\i0} world!\\NHow are you?"),
+			"Hello, world!\nHow are you?"
+		);
+		assert_eq!(
+			ass_dialogue_to_text("3,0,Sign,,0,0,0,,  Non\\hbreaking  \\N\\N"),
+			"Non breaking"
+		);
+		assert_eq!(ass_dialogue_to_text("no commas here"), "no commas here");
+	}
+}
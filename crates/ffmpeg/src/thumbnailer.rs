@@ -1,4 +1,7 @@
-use crate::{frame_decoder::ThumbnailSize, Error, FrameDecoder};
+use crate::{
+	frame_decoder::{ThumbnailSize, VideoFrame},
+	Error, FrameDecoder,
+};
 
 use std::{io, ops::Deref, path::Path};
 
@@ -100,21 +103,7 @@ impl Thumbnailer {
 				let video_frame =
 					decoder.get_scaled_video_frame(Some(size), maintain_aspect_ratio)?;
 
-				let mut image = DynamicImage::ImageRgb8(
-					RgbImage::from_raw(video_frame.width, video_frame.height, video_frame.data)
-						.ok_or(Error::CorruptVideo(video_file_path.into_boxed_path()))?,
-				);
-
-				let image = if video_frame.rotation < -135.0 {
-					imageops::rotate180_in_place(&mut image);
-					image
-				} else if video_frame.rotation > 45.0 && video_frame.rotation < 135.0 {
-					image.rotate270()
-				} else if video_frame.rotation < -45.0 && video_frame.rotation > -135.0 {
-					image.rotate90()
-				} else {
-					image
-				};
+				let image = frame_to_image(video_frame, &video_file_path)?;
 
 				// Type WebPMemory is !Send, which makes the Future in this function !Send,
 				// this make us `deref` to have a `&[u8]` and then `to_owned` to make a Vec<u8>
@@ -130,6 +119,28 @@ impl Thumbnailer {
 	}
 }
 
+/// Turns a decoded video frame into an image, undoing the rotation the video was recorded with
+pub(crate) fn frame_to_image(
+	video_frame: VideoFrame,
+	video_file_path: &Path,
+) -> Result<DynamicImage, Error> {
+	let mut image = DynamicImage::ImageRgb8(
+		RgbImage::from_raw(video_frame.width, video_frame.height, video_frame.data)
+			.ok_or_else(|| Error::CorruptVideo(video_file_path.into()))?,
+	);
+
+	Ok(if video_frame.rotation < -135.0 {
+		imageops::rotate180_in_place(&mut image);
+		image
+	} else if video_frame.rotation > 45.0 && video_frame.rotation < 135.0 {
+		image.rotate270()
+	} else if video_frame.rotation < -45.0 && video_frame.rotation > -135.0 {
+		image.rotate90()
+	} else {
+		image
+	})
+}
+
 /// `ThumbnailerBuilder` struct holds data to build a `Thumbnailer` struct, exposing many methods
 /// to configure how a thumbnail must be generated.
 #[derive(Debug, Clone)]
@@ -0,0 +1,271 @@
+use crate::{frame_decoder::ThumbnailSize, thumbnailer::frame_to_image, Error, FrameDecoder};
+
+use std::{io, ops::Deref, path::Path};
+
+use image::{imageops, DynamicImage, GenericImageView, RgbImage};
+use sd_utils::error::FileIOError;
+use tokio::{fs, task::spawn_blocking};
+use tracing::warn;
+use webp::{AnimEncoder, AnimFrame, Encoder, WebPConfig};
+
+/// Frames per row in the thumbstrip sprite sheet
+const THUMBSTRIP_COLUMNS: u32 = 5;
+
+/// Where a frame is in the video and in the thumbstrip sprite sheet
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ThumbstripFrame {
+	/// Position of the frame in the video, in seconds
+	pub timestamp: f64,
+	/// Horizontal offset of the frame in the sprite sheet, in pixels
+	pub x: u32,
+	/// Vertical offset of the frame in the sprite sheet, in pixels
+	pub y: u32,
+}
+
+/// Timing index of a thumbstrip, so a frame can be picked according to the cursor position
+/// when scrubbing over a video
+#[derive(Debug, Clone, PartialEq)]
+pub struct Thumbstrip {
+	pub frame_width: u32,
+	pub frame_height: u32,
+	pub duration: f64,
+	pub frames: Vec<ThumbstripFrame>,
+}
+
+/// Decodes `count` frames at even intervals of the video.
+///
+/// Each frame is taken from the middle of its interval, so we never pick the very first frame,
+/// which is very often just a black fade in.
+fn decode_frames(
+	video_file_path: &Path,
+	count: u32,
+	size: ThumbnailSize,
+) -> Result<(f64, Vec<(f64, DynamicImage)>), Error> {
+	if count == 0 {
+		return Err(Error::InvalidFrameCount);
+	}
+
+	// We want actual frames from the video, so embedded cover arts are ignored here
+	let mut decoder = FrameDecoder::new(video_file_path, true, false)?;
+
+	// We actually have to decode a frame to get some metadata before we can start decoding for real
+	decoder.decode_video_frame()?;
+
+	let duration = decoder.get_duration_secs().ok_or(Error::NoVideoDuration)?;
+
+	let mut frames = Vec::with_capacity(count as usize);
+
+	for index in 0..count {
+		let timestamp = duration * (f64::from(index) + 0.5) / f64::from(count);
+
+		if let Err(e) = decoder.seek(
+			#[allow(clippy::cast_possible_truncation)]
+			{
+				// This conversion is ok because we don't worry much about precision here
+				timestamp.round() as i64
+			},
+		) {
+			warn!(
+				"Failed to seek {} to {timestamp}s, stopping at {} frames: {e:#?}",
+				video_file_path.display(),
+				frames.len()
+			);
+
+			break;
+		}
+
+		let image = frame_to_image(
+			decoder.get_scaled_video_frame(Some(size), true)?,
+			video_file_path,
+		)?;
+
+		frames.push((timestamp, image));
+	}
+
+	if frames.is_empty() {
+		return Err(Error::SeekError);
+	}
+
+	// Frames usually have the same dimensions, but a rotation change mid video could make them differ
+	let (width, height) = frames[0].1.dimensions();
+	for (_, image) in frames.iter_mut().skip(1) {
+		if image.dimensions() != (width, height) {
+			*image = image.resize_exact(width, height, imageops::FilterType::Triangle);
+		}
+	}
+
+	Ok((duration, frames))
+}
+
+/// Lays out the frames in a grid and returns the sprite sheet with its timing index
+fn build_thumbstrip(duration: f64, frames: &[(f64, DynamicImage)]) -> (DynamicImage, Thumbstrip) {
+	let (frame_width, frame_height) = frames[0].1.dimensions();
+
+	#[allow(clippy::cast_possible_truncation)]
+	// There are never more frames than fit in an u32
+	let count = frames.len() as u32;
+	let columns = count.min(THUMBSTRIP_COLUMNS);
+	let rows = count.div_ceil(columns);
+
+	let mut sprite = RgbImage::new(frame_width * columns, frame_height * rows);
+
+	let frames = frames
+		.iter()
+		.zip(0..)
+		.map(|((timestamp, image), index)| {
+			let x = (index % columns) * frame_width;
+			let y = (index / columns) * frame_height;
+
+			imageops::replace(&mut sprite, &image.to_rgb8(), i64::from(x), i64::from(y));
+
+			ThumbstripFrame {
+				timestamp: *timestamp,
+				x,
+				y,
+			}
+		})
+		.collect();
+
+	(
+		DynamicImage::ImageRgb8(sprite),
+		Thumbstrip {
+			frame_width,
+			frame_height,
+			duration,
+			frames,
+		},
+	)
+}
+
+async fn write_to_file(output_path: &Path, bytes: Vec<u8>) -> Result<(), Error> {
+	let parent = output_path.parent().ok_or_else(|| {
+		FileIOError::from((
+			output_path,
+			io::Error::new(
+				io::ErrorKind::InvalidInput,
+				"Cannot determine parent directory",
+			),
+		))
+	})?;
+
+	fs::create_dir_all(parent)
+		.await
+		.map_err(|e| FileIOError::from((parent, e)))?;
+
+	fs::write(output_path, bytes)
+		.await
+		.map_err(|e| FileIOError::from((output_path, e)).into())
+}
+
+/// Generates a webp sprite sheet with `frame_count` frames at even intervals of the video,
+/// returning where each frame is in the sprite sheet.
+pub(crate) async fn generate_thumbstrip(
+	video_file_path: impl AsRef<Path> + Send,
+	output_path: impl AsRef<Path> + Send,
+	frame_count: u32,
+	frame_size: ThumbnailSize,
+	quality: f32,
+) -> Result<Thumbstrip, Error> {
+	if !(0.0..=100.0).contains(&quality) {
+		return Err(Error::InvalidQuality(quality));
+	}
+
+	let (webp, thumbstrip) = spawn_blocking({
+		let video_file_path = video_file_path.as_ref().to_path_buf();
+		move || -> Result<_, Error> {
+			let (duration, frames) = decode_frames(&video_file_path, frame_count, frame_size)?;
+			let (sprite, thumbstrip) = build_thumbstrip(duration, &frames);
+
+			// Type WebPMemory is !Send, so we copy it to a Vec<u8> before leaving the blocking task
+			Ok((
+				Encoder::from_image(&sprite)
+					.expect("Should not fail as the underlining DynamicImage is an RgbImage")
+					.encode(quality)
+					.deref()
+					.to_vec(),
+				thumbstrip,
+			))
+		}
+	})
+	.await??;
+
+	write_to_file(output_path.as_ref(), webp).await?;
+
+	Ok(thumbstrip)
+}
+
+/// Generates a looping animated webp with `frame_count` frames at even intervals of the video,
+/// each one shown for `frame_duration_ms` milliseconds.
+pub(crate) async fn generate_animated_preview(
+	video_file_path: impl AsRef<Path> + Send,
+	output_path: impl AsRef<Path> + Send,
+	frame_count: u32,
+	frame_size: ThumbnailSize,
+	frame_duration_ms: u32,
+	quality: f32,
+) -> Result<(), Error> {
+	if !(0.0..=100.0).contains(&quality) {
+		return Err(Error::InvalidQuality(quality));
+	}
+
+	let webp = spawn_blocking({
+		let video_file_path = video_file_path.as_ref().to_path_buf();
+		move || -> Result<_, Error> {
+			let (_, frames) = decode_frames(&video_file_path, frame_count, frame_size)?;
+			let (width, height) = frames[0].1.dimensions();
+
+			let mut config = WebPConfig::new().map_err(|()| {
+				Error::AnimatedWebPEncoding("failed to initialize webp config".to_string())
+			})?;
+			config.quality = quality;
+
+			let mut encoder = AnimEncoder::new(width, height, &config);
+			encoder.set_loop_count(0);
+
+			let frame_duration_ms = i32::try_from(frame_duration_ms)?;
+
+			for (index, (_, image)) in (0..).zip(&frames) {
+				encoder.add_frame(
+					AnimFrame::from_image(image, index * frame_duration_ms)
+						.map_err(|e| Error::AnimatedWebPEncoding(e.to_string()))?,
+				);
+			}
+
+			// Type WebPMemory is !Send, so we copy it to a Vec<u8> before leaving the blocking task
+			Ok(encoder.encode().deref().to_vec())
+		}
+	})
+	.await??;
+
+	write_to_file(output_path.as_ref(), webp).await
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn thumbstrip_index_matches_grid() {
+		let frames = (0..7)
+			.map(|index| {
+				(
+					f64::from(index) + 0.5,
+					DynamicImage::ImageRgb8(RgbImage::new(16, 9)),
+				)
+			})
+			.collect::<Vec<_>>();
+
+		let (sprite, thumbstrip) = build_thumbstrip(7.0, &frames);
+
+		assert_eq!(sprite.dimensions(), (16 * 5, 9 * 2));
+		assert_eq!(thumbstrip.frames.len(), 7);
+		assert_eq!(
+			thumbstrip.frames[6],
+			ThumbstripFrame {
+				timestamp: 6.5,
+				x: 16,
+				y: 9
+			}
+		);
+	}
+}
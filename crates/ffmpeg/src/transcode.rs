@@ -0,0 +1,830 @@
+//! On the fly transcoding of media files that the webview can't play into HLS segments.
+//!
+//! Every segment is transcoded on its own, so any of them can be requested at any time when the
+//! user seeks. We seek to the keyframe right before the segment start and drop every frame until
+//! the segment start, and as video is always re-encoded, each segment starts with a keyframe
+//! exactly at its boundary. Timestamps are kept relative to the start of the media, so segments
+//! line up with each other when played one after the other.
+
+use crate::{
+	codec_ctx::FFmpegCodecContext,
+	error::{Error, FFmpegError},
+	filter_graph::FFmpegFilterGraph,
+	format_ctx::FFmpegFormatContext,
+	utils::{check_error, from_path},
+	video_frame::{FFmpegFrame, FFmpegPacket},
+};
+
+use std::{
+	ffi::{c_char, c_int, CStr, CString},
+	fmt::Write,
+	path::Path,
+	ptr,
+};
+
+use ffmpeg_sys_next::{
+	av_buffersink_get_frame, av_buffersink_get_h, av_buffersink_get_sample_aspect_ratio,
+	av_buffersink_get_time_base, av_buffersink_get_w, av_buffersink_set_frame_size,
+	av_buffersrc_add_frame, av_channel_layout_default, av_channel_layout_describe,
+	av_find_best_stream, av_frame_unref, av_get_sample_fmt_name, av_guess_frame_rate,
+	av_interleaved_write_frame, av_opt_set, av_packet_rescale_ts, av_packet_unref,
+	av_write_trailer, avcodec_find_decoder, avcodec_find_encoder, avcodec_find_encoder_by_name,
+	avcodec_parameters_copy, avcodec_parameters_from_context, avformat_alloc_output_context2,
+	avformat_free_context, avformat_new_stream, avformat_seek_file, avformat_write_header,
+	avio_closep, avio_open, AVChannelLayout, AVChannelOrder, AVCodec, AVCodecID, AVFilterContext,
+	AVFormatContext, AVMediaType, AVPacket, AVPictureType, AVPixelFormat, AVRational,
+	AVSampleFormat, AVStream, AVERROR, AVERROR_EOF, AVIO_FLAG_WRITE, AV_DISPOSITION_ATTACHED_PIC,
+	AV_NOPTS_VALUE, AV_TIME_BASE, EAGAIN,
+};
+
+pub const HLS_SEGMENT_EXTENSION: &str = "ts";
+
+/// MPEG-TS timestamps are always in 90kHz units, so we just encode video in the same time base
+const VIDEO_TIME_BASE: AVRational = AVRational {
+	num: 1,
+	den: 90_000,
+};
+const AUDIO_SAMPLE_RATE: c_int = 48_000;
+const AUDIO_CHANNELS: c_int = 2;
+const AUDIO_BIT_RATE: i64 = 160_000;
+
+/// Number of segments needed to cover `duration` seconds of media
+pub fn hls_segment_count(duration: f64, segment_duration: f64) -> u32 {
+	if duration <= 0.0 || segment_duration <= 0.0 {
+		return 0;
+	}
+
+	#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+	{
+		// SAFETY: both values are positive, and it would take a media file
+		// billions of segments long to overflow
+		(duration / segment_duration).ceil() as u32
+	}
+}
+
+/// Builds a VOD playlist splitting `duration` seconds of media into segments of
+/// `segment_duration` seconds, named `<segment_uri_prefix><index>.ts`
+pub fn hls_playlist(duration: f64, segment_duration: f64, segment_uri_prefix: &str) -> String {
+	let mut playlist = format!(
+		"#EXTM3U\n\
+		#EXT-X-VERSION:3\n\
+		#EXT-X-TARGETDURATION:{}\n\
+		#EXT-X-MEDIA-SEQUENCE:0\n\
+		#EXT-X-PLAYLIST-TYPE:VOD\n",
+		segment_duration.ceil()
+	);
+
+	for index in 0..hls_segment_count(duration, segment_duration) {
+		let start = f64::from(index) * segment_duration;
+		// Writing to a String can't fail
+		let _ = write!(
+			playlist,
+			"#EXTINF:{:.6},\n{segment_uri_prefix}{index}.{HLS_SEGMENT_EXTENSION}\n",
+			segment_duration.min(duration - start)
+		);
+	}
+
+	playlist.push_str("#EXT-X-ENDLIST\n");
+
+	playlist
+}
+
+/// Part of the media covered by a segment, in seconds since the start of the media
+#[derive(Debug, Clone, Copy)]
+struct Window {
+	start: f64,
+	end: f64,
+	/// Some containers, like MPEG-TS, don't start at zero, so we subtract their start time
+	/// from every timestamp
+	offset: f64,
+}
+
+impl Window {
+	fn to_seconds(self, timestamp: i64, time_base: AVRational) -> f64 {
+		#[allow(clippy::cast_precision_loss)]
+		{
+			// SAFETY: the timestamp would need to be humongous for this cast to f64 to cause problems
+			timestamp as f64 * f64::from(time_base.num) / f64::from(time_base.den) - self.offset
+		}
+	}
+}
+
+fn from_seconds(seconds: f64, time_base: AVRational) -> i64 {
+	#[allow(clippy::cast_possible_truncation)]
+	{
+		// This conversion is ok because we round to the time base precision anyway
+		(seconds * f64::from(time_base.den) / f64::from(time_base.num)).round() as i64
+	}
+}
+
+struct FFmpegOutputContext(*mut AVFormatContext);
+
+impl FFmpegOutputContext {
+	fn create(format: &CStr, filename: &CStr) -> Result<Self, Error> {
+		let mut ptr = ptr::null_mut();
+
+		check_error(
+			unsafe {
+				avformat_alloc_output_context2(
+					&mut ptr,
+					ptr::null(),
+					format.as_ptr(),
+					filename.as_ptr(),
+				)
+			},
+			"Failed to allocate the output format context",
+		)?;
+
+		if ptr.is_null() {
+			return Err(FFmpegError::ContextAllocation.into());
+		}
+
+		Ok(Self(ptr))
+	}
+
+	fn as_ref(&self) -> &AVFormatContext {
+		unsafe { self.0.as_ref() }.expect("initialized on struct creation")
+	}
+
+	fn as_mut(&mut self) -> &mut AVFormatContext {
+		unsafe { self.0.as_mut() }.expect("initialized on struct creation")
+	}
+
+	fn new_stream(&mut self) -> Result<&mut AVStream, Error> {
+		Ok(
+			unsafe { avformat_new_stream(self.as_mut(), ptr::null()).as_mut() }
+				.ok_or(FFmpegError::NullError)?,
+		)
+	}
+
+	fn open(&mut self, filename: &CStr) -> Result<(), Error> {
+		check_error(
+			unsafe { avio_open(&mut self.as_mut().pb, filename.as_ptr(), AVIO_FLAG_WRITE) },
+			"Failed to open the output file",
+		)?;
+
+		check_error(
+			unsafe { avformat_write_header(self.as_mut(), ptr::null_mut()) },
+			"Failed to write the output header",
+		)
+	}
+
+	/// Writes a packet with timestamps in `time_base` to the output stream at `stream_index`
+	fn write_packet(
+		&mut self,
+		packet: &mut AVPacket,
+		stream_index: c_int,
+		time_base: AVRational,
+	) -> Result<(), Error> {
+		let stream_time_base = isize::try_from(stream_index)
+			.ok()
+			.and_then(|index| unsafe { (*self.as_ref().streams.offset(index)).as_ref() })
+			.map(|stream| stream.time_base)
+			.ok_or(FFmpegError::NullError)?;
+
+		packet.stream_index = stream_index;
+		unsafe { av_packet_rescale_ts(packet, time_base, stream_time_base) };
+
+		check_error(
+			unsafe { av_interleaved_write_frame(self.as_mut(), packet) },
+			"Failed to write packet to the output",
+		)
+	}
+
+	fn finish(&mut self) -> Result<(), Error> {
+		check_error(
+			unsafe { av_write_trailer(self.as_mut()) },
+			"Failed to write the output trailer",
+		)
+	}
+}
+
+impl Drop for FFmpegOutputContext {
+	fn drop(&mut self) {
+		if !self.0.is_null() {
+			unsafe {
+				if !(*self.0).pb.is_null() {
+					avio_closep(&mut (*self.0).pb);
+				}
+				avformat_free_context(self.0);
+			}
+			self.0 = ptr::null_mut();
+		}
+	}
+}
+
+pub(crate) fn open_decoder(stream: &AVStream) -> Result<FFmpegCodecContext, Error> {
+	let codec_params = unsafe { stream.codecpar.as_ref() }.ok_or(FFmpegError::NullError)?;
+	let codec = unsafe { avcodec_find_decoder(codec_params.codec_id).as_ref() }
+		.ok_or(FFmpegError::DecoderNotFound)?;
+
+	let mut decoder = FFmpegCodecContext::new()?;
+	decoder.parameters_to_context(codec_params)?;
+	decoder.as_mut().pkt_timebase = stream.time_base;
+	decoder.open2(codec)?;
+
+	Ok(decoder)
+}
+
+/// libx264 is preferred as it's the fastest one at a decent quality, but `FFmpeg` may have been
+/// built without it, so we fallback to whatever other H.264 encoder is available
+fn find_video_encoder() -> Result<&'static AVCodec, FFmpegError> {
+	unsafe { avcodec_find_encoder_by_name(c"libx264".as_ptr()).as_ref() }
+		.or_else(|| unsafe { avcodec_find_encoder(AVCodecID::AV_CODEC_ID_H264).as_ref() })
+		.ok_or(FFmpegError::EncoderNotFound)
+}
+
+/// Filters undoing the rotation of the display matrix, as MPEG-TS can't carry it, and whether
+/// they swap width and height
+fn rotation_filters(rotation_angle: f64) -> (Vec<(&'static CStr, Option<CString>)>, bool) {
+	// The display matrix rotation is counterclockwise, so we rotate clockwise to undo it
+	let theta = (-rotation_angle).rem_euclid(360.0).round();
+
+	if (theta - 90.0).abs() < 1.0 {
+		(vec![(c"transpose", Some(c"dir=clock".to_owned()))], true)
+	} else if (theta - 180.0).abs() < 1.0 {
+		(vec![(c"hflip", None), (c"vflip", None)], false)
+	} else if (theta - 270.0).abs() < 1.0 {
+		(vec![(c"transpose", Some(c"dir=cclock".to_owned()))], true)
+	} else {
+		(vec![], false)
+	}
+}
+
+fn describe_channel_layout(channel_layout: &AVChannelLayout) -> Option<String> {
+	let mut buffer: [c_char; 64] = [0; 64];
+
+	(unsafe { av_channel_layout_describe(channel_layout, buffer.as_mut_ptr(), buffer.len()) } > 0)
+		.then(|| {
+			unsafe { CStr::from_ptr(buffer.as_ptr()) }
+				.to_string_lossy()
+				.into_owned()
+		})
+}
+
+/// Decodes a stream of the input and encodes it again in a format every browser can play
+struct StreamTranscoder {
+	input_index: c_int,
+	input_time_base: AVRational,
+	decoder: FFmpegCodecContext,
+	filter_source: *mut AVFilterContext,
+	filter_sink: *mut AVFilterContext,
+	// Owns the filter contexts above
+	_filter_graph: FFmpegFilterGraph,
+	encoder: FFmpegCodecContext,
+	output_index: c_int,
+	frame: FFmpegFrame,
+	filtered_frame: FFmpegFrame,
+	packet: FFmpegPacket,
+	/// Set once we decoded a frame past the end of the segment
+	finished: bool,
+}
+
+impl StreamTranscoder {
+	/// Transcodes to H.264, scaled down to `max_height` if needed
+	fn video(
+		input: &mut FFmpegFormatContext,
+		index: u32,
+		max_height: u32,
+		output: &mut FFmpegOutputContext,
+	) -> Result<Self, Error> {
+		let rotation_angle = input.get_stream_rotation_angle(index);
+		let stream = ptr::from_mut(input.stream(index).ok_or(FFmpegError::NullError)?);
+		let stream_ref = unsafe { stream.as_ref() }.ok_or(FFmpegError::NullError)?;
+
+		let decoder = open_decoder(stream_ref)?;
+		let frame_rate = unsafe { av_guess_frame_rate(input.as_mut(), stream, ptr::null_mut()) };
+
+		let (mut filters, swaps_dimensions) = rotation_filters(rotation_angle);
+
+		let decoder_ctx = decoder.as_ref();
+		let height = if swaps_dimensions {
+			decoder_ctx.width
+		} else {
+			decoder_ctx.height
+		};
+		// H.264 with 4:2:0 chroma subsampling needs even dimensions
+		let height = (height.unsigned_abs().min(max_height) / 2) * 2;
+
+		filters.insert(0, (c"yadif", Some(c"deint=interlaced".to_owned())));
+		filters.push((c"scale", Some(CString::new(format!("w=-2:h={height}"))?)));
+		filters.push((c"format", Some(c"pix_fmts=yuv420p".to_owned())));
+
+		let source_args = format!(
+			"video_size={}x{}:pix_fmt={}:time_base={}/{}:pixel_aspect={}/{}",
+			decoder_ctx.width,
+			decoder_ctx.height,
+			// AVPixelFormat is an i32 enum, so it's safe to cast it to i32
+			decoder_ctx.pix_fmt as i32,
+			stream_ref.time_base.num,
+			stream_ref.time_base.den,
+			decoder_ctx.sample_aspect_ratio.num,
+			i32::max(decoder_ctx.sample_aspect_ratio.den, 1)
+		);
+
+		let (filter_graph, filter_source, filter_sink) =
+			FFmpegFilterGraph::chain_graph(c"buffer", &source_args, c"buffersink", &filters)?;
+
+		let codec = find_video_encoder()?;
+		let mut encoder = FFmpegCodecContext::new_encoder(codec)?;
+		{
+			let encoder_ctx = encoder.as_mut();
+			encoder_ctx.width = unsafe { av_buffersink_get_w(filter_sink) };
+			encoder_ctx.height = unsafe { av_buffersink_get_h(filter_sink) };
+			encoder_ctx.sample_aspect_ratio =
+				unsafe { av_buffersink_get_sample_aspect_ratio(filter_sink) };
+			encoder_ctx.pix_fmt = AVPixelFormat::AV_PIX_FMT_YUV420P;
+			encoder_ctx.time_base = VIDEO_TIME_BASE;
+			encoder_ctx.framerate = frame_rate;
+			// B-frames would give negative timestamps to the first frames of a segment,
+			// which the muxer would then shift, breaking the continuity between segments
+			encoder_ctx.max_b_frames = 0;
+		}
+		// Only libx264 has this option, other encoders just ignore it
+		unsafe {
+			av_opt_set(
+				encoder.as_mut().priv_data,
+				c"preset".as_ptr(),
+				c"veryfast".as_ptr(),
+				0,
+			)
+		};
+		encoder.open2(codec)?;
+
+		let output_stream = output.new_stream()?;
+		check_error(
+			unsafe { avcodec_parameters_from_context(output_stream.codecpar, encoder.as_ref()) },
+			"Failed to fill the output stream with the video encoder parameters",
+		)?;
+		output_stream.time_base = VIDEO_TIME_BASE;
+
+		Ok(Self {
+			input_index: stream_ref.index,
+			input_time_base: stream_ref.time_base,
+			decoder,
+			filter_source: ptr::from_mut(filter_source),
+			filter_sink: ptr::from_mut(filter_sink),
+			_filter_graph: filter_graph,
+			encoder,
+			output_index: output_stream.index,
+			frame: FFmpegFrame::new()?,
+			filtered_frame: FFmpegFrame::new()?,
+			packet: FFmpegPacket::new()?,
+			finished: false,
+		})
+	}
+
+	/// Transcodes to stereo AAC
+	fn audio(
+		input: &FFmpegFormatContext,
+		index: u32,
+		output: &mut FFmpegOutputContext,
+	) -> Result<Self, Error> {
+		let stream = input.stream(index).ok_or(FFmpegError::NullError)?;
+		let decoder = open_decoder(stream)?;
+
+		let decoder_ctx = decoder.as_ref();
+		let sample_format = unsafe { av_get_sample_fmt_name(decoder_ctx.sample_fmt).as_ref() }
+			.map(|name| unsafe { CStr::from_ptr(name) }.to_string_lossy())
+			.ok_or(FFmpegError::NullError)?;
+		let channel_layout =
+			if decoder_ctx.ch_layout.order == AVChannelOrder::AV_CHANNEL_ORDER_UNSPEC {
+				None
+			} else {
+				describe_channel_layout(&decoder_ctx.ch_layout)
+			}
+			.map_or_else(
+				|| format!("channels={}", decoder_ctx.ch_layout.nb_channels),
+				|channel_layout| format!("channel_layout={channel_layout}"),
+			);
+
+		let source_args = format!(
+			"time_base={}/{}:sample_rate={}:sample_fmt={sample_format}:{channel_layout}",
+			stream.time_base.num, stream.time_base.den, decoder_ctx.sample_rate,
+		);
+
+		// The graph inserts the resampler needed to go from the source format to this one
+		let (filter_graph, filter_source, filter_sink) = FFmpegFilterGraph::chain_graph(
+			c"abuffer",
+			&source_args,
+			c"abuffersink",
+			&[(
+				c"aformat",
+				Some(CString::new(format!(
+					"sample_fmts=fltp:sample_rates={AUDIO_SAMPLE_RATE}:channel_layouts=stereo"
+				))?),
+			)],
+		)?;
+
+		let codec = unsafe { avcodec_find_encoder(AVCodecID::AV_CODEC_ID_AAC).as_ref() }
+			.ok_or(FFmpegError::EncoderNotFound)?;
+		let mut encoder = FFmpegCodecContext::new_encoder(codec)?;
+		{
+			let encoder_ctx = encoder.as_mut();
+			encoder_ctx.sample_fmt = AVSampleFormat::AV_SAMPLE_FMT_FLTP;
+			encoder_ctx.sample_rate = AUDIO_SAMPLE_RATE;
+			unsafe { av_channel_layout_default(&mut encoder_ctx.ch_layout, AUDIO_CHANNELS) };
+			encoder_ctx.bit_rate = AUDIO_BIT_RATE;
+			encoder_ctx.time_base = AVRational {
+				num: 1,
+				den: AUDIO_SAMPLE_RATE,
+			};
+		}
+		encoder.open2(codec)?;
+
+		// AAC frames have a fixed amount of samples
+		unsafe {
+			av_buffersink_set_frame_size(filter_sink, u32::try_from(encoder.as_ref().frame_size)?);
+		};
+
+		let output_stream = output.new_stream()?;
+		check_error(
+			unsafe { avcodec_parameters_from_context(output_stream.codecpar, encoder.as_ref()) },
+			"Failed to fill the output stream with the audio encoder parameters",
+		)?;
+		output_stream.time_base = encoder.as_ref().time_base;
+
+		Ok(Self {
+			input_index: stream.index,
+			input_time_base: stream.time_base,
+			decoder,
+			filter_source: ptr::from_mut(filter_source),
+			filter_sink: ptr::from_mut(filter_sink),
+			_filter_graph: filter_graph,
+			encoder,
+			output_index: output_stream.index,
+			frame: FFmpegFrame::new()?,
+			filtered_frame: FFmpegFrame::new()?,
+			packet: FFmpegPacket::new()?,
+			finished: false,
+		})
+	}
+
+	/// Decodes a packet, or flushes the decoder if there's none, sending the decoded frames that
+	/// are within the segment to the filter graph
+	fn decode(
+		&mut self,
+		packet: *mut AVPacket,
+		window: Window,
+		output: &mut FFmpegOutputContext,
+	) -> Result<(), Error> {
+		match self.decoder.send_packet(packet) {
+			// We always drain the decoder after sending a packet, so it should never be full
+			Ok(_) | Err(FFmpegError::Again) => {}
+			Err(e) => {
+				return Err(Error::FFmpegWithReason(
+					e,
+					"Failed to send packet to decoder".to_string(),
+				))
+			}
+		}
+
+		loop {
+			match self.decoder.receive_frame(self.frame.as_mut()) {
+				Ok(true) => {}
+				Ok(false) | Err(FFmpegError::Again) => return Ok(()),
+				Err(e) => {
+					return Err(Error::FFmpegWithReason(
+						e,
+						"Failed to receive frame from decoder".to_string(),
+					))
+				}
+			}
+
+			let frame = self.frame.as_mut();
+			if frame.best_effort_timestamp == AV_NOPTS_VALUE {
+				continue;
+			}
+
+			let seconds = window.to_seconds(frame.best_effort_timestamp, self.input_time_base);
+			if seconds >= window.end {
+				self.finished = true;
+				continue;
+			}
+			if seconds < window.start {
+				continue;
+			}
+
+			frame.pts = frame.best_effort_timestamp;
+			check_error(
+				unsafe { av_buffersrc_add_frame(self.filter_source, frame) },
+				"Failed to write frame to filter graph",
+			)?;
+
+			self.encode_filtered_frames(window, output)?;
+		}
+	}
+
+	fn encode_filtered_frames(
+		&mut self,
+		window: Window,
+		output: &mut FFmpegOutputContext,
+	) -> Result<(), Error> {
+		let sink_time_base = unsafe { av_buffersink_get_time_base(self.filter_sink) };
+		let encoder_time_base = self.encoder.as_ref().time_base;
+
+		loop {
+			match unsafe { av_buffersink_get_frame(self.filter_sink, self.filtered_frame.as_mut()) }
+			{
+				ret if ret == AVERROR(EAGAIN) || ret == AVERROR_EOF => return Ok(()),
+				ret => check_error(ret, "Failed to get frame from filter graph")?,
+			}
+
+			let filtered_frame = self.filtered_frame.as_mut();
+			filtered_frame.pts = from_seconds(
+				window.to_seconds(filtered_frame.pts, sink_time_base),
+				encoder_time_base,
+			);
+			// Let the encoder pick the frame types, so only the first frame is a keyframe
+			filtered_frame.pict_type = AVPictureType::AV_PICTURE_TYPE_NONE;
+
+			let sent = self.encoder.send_frame(filtered_frame);
+			unsafe { av_frame_unref(filtered_frame) };
+			sent.map_err(|e| {
+				Error::FFmpegWithReason(e, "Failed to send frame to encoder".to_string())
+			})?;
+
+			self.write_encoded_packets(output)?;
+		}
+	}
+
+	fn write_encoded_packets(&mut self, output: &mut FFmpegOutputContext) -> Result<(), Error> {
+		let encoder_time_base = self.encoder.as_ref().time_base;
+
+		loop {
+			match self.encoder.receive_packet(self.packet.as_mut()) {
+				Ok(true) => {}
+				Ok(false) | Err(FFmpegError::Again) => return Ok(()),
+				Err(e) => {
+					return Err(Error::FFmpegWithReason(
+						e,
+						"Failed to receive packet from encoder".to_string(),
+					))
+				}
+			}
+
+			output.write_packet(self.packet.as_mut(), self.output_index, encoder_time_base)?;
+		}
+	}
+
+	fn flush(&mut self, window: Window, output: &mut FFmpegOutputContext) -> Result<(), Error> {
+		self.decode(ptr::null_mut(), window, output)?;
+
+		check_error(
+			unsafe { av_buffersrc_add_frame(self.filter_source, ptr::null_mut()) },
+			"Failed to flush filter graph",
+		)?;
+		self.encode_filtered_frames(window, output)?;
+
+		match self.encoder.send_frame(ptr::null()) {
+			Ok(_) | Err(FFmpegError::Again) => {}
+			Err(e) => {
+				return Err(Error::FFmpegWithReason(
+					e,
+					"Failed to flush encoder".to_string(),
+				))
+			}
+		}
+		self.write_encoded_packets(output)
+	}
+}
+
+/// Copies the packets of a stream that browsers can already play
+struct StreamCopy {
+	input_index: c_int,
+	input_time_base: AVRational,
+	output_index: c_int,
+	finished: bool,
+}
+
+impl StreamCopy {
+	fn new(
+		input: &FFmpegFormatContext,
+		index: u32,
+		output: &mut FFmpegOutputContext,
+	) -> Result<Self, Error> {
+		let stream = input.stream(index).ok_or(FFmpegError::NullError)?;
+
+		let output_stream = output.new_stream()?;
+		check_error(
+			unsafe { avcodec_parameters_copy(output_stream.codecpar, stream.codecpar) },
+			"Failed to copy the stream parameters to the output stream",
+		)?;
+		// The tag of the source container may be meaningless to the output one
+		if let Some(codec_params) = unsafe { output_stream.codecpar.as_mut() } {
+			codec_params.codec_tag = 0;
+		}
+
+		Ok(Self {
+			input_index: stream.index,
+			input_time_base: stream.time_base,
+			output_index: output_stream.index,
+			finished: false,
+		})
+	}
+
+	fn copy(
+		&mut self,
+		packet: &mut AVPacket,
+		window: Window,
+		output: &mut FFmpegOutputContext,
+	) -> Result<(), Error> {
+		if packet.pts == AV_NOPTS_VALUE {
+			return Ok(());
+		}
+
+		let seconds = window.to_seconds(packet.pts, self.input_time_base);
+		if seconds >= window.end {
+			self.finished = true;
+			return Ok(());
+		}
+		if seconds < window.start {
+			return Ok(());
+		}
+
+		let offset = from_seconds(window.offset, self.input_time_base);
+		packet.pts -= offset;
+		if packet.dts != AV_NOPTS_VALUE {
+			packet.dts -= offset;
+		}
+		packet.pos = -1;
+
+		output.write_packet(packet, self.output_index, self.input_time_base)
+	}
+}
+
+enum SegmentStream {
+	Transcode(Box<StreamTranscoder>),
+	Copy(StreamCopy),
+}
+
+impl SegmentStream {
+	const fn input_index(&self) -> c_int {
+		match self {
+			Self::Transcode(transcoder) => transcoder.input_index,
+			Self::Copy(copy) => copy.input_index,
+		}
+	}
+
+	const fn finished(&self) -> bool {
+		match self {
+			Self::Transcode(transcoder) => transcoder.finished,
+			Self::Copy(copy) => copy.finished,
+		}
+	}
+
+	fn process(
+		&mut self,
+		packet: &mut AVPacket,
+		window: Window,
+		output: &mut FFmpegOutputContext,
+	) -> Result<(), Error> {
+		match self {
+			Self::Transcode(transcoder) => transcoder.decode(packet, window, output),
+			Self::Copy(copy) => copy.copy(packet, window, output),
+		}
+	}
+
+	fn flush(&mut self, window: Window, output: &mut FFmpegOutputContext) -> Result<(), Error> {
+		match self {
+			Self::Transcode(transcoder) => transcoder.flush(window, output),
+			Self::Copy(_) => Ok(()),
+		}
+	}
+}
+
+fn find_best_stream(input: &mut FFmpegFormatContext, kind: AVMediaType) -> Option<u32> {
+	let index = u32::try_from(unsafe {
+		av_find_best_stream(input.as_mut(), kind, -1, -1, ptr::null_mut(), 0)
+	})
+	.ok()?;
+
+	// Cover arts show up as video streams with a single frame
+	input
+		.stream(index)
+		.filter(|stream| stream.disposition & AV_DISPOSITION_ATTACHED_PIC == 0)
+		.map(|_| index)
+}
+
+/// Transcodes `duration` seconds of media starting at `start` into a MPEG-TS file, with H.264
+/// video no taller than `max_height` and AAC audio, which is copied as is if it's already AAC
+pub(crate) fn transcode_segment(
+	input_path: &Path,
+	output_path: &Path,
+	start: f64,
+	duration: f64,
+	max_height: u32,
+) -> Result<(), Error> {
+	let mut input = FFmpegFormatContext::open_file(from_path(input_path)?.as_c_str())?;
+	input.find_stream_info()?;
+
+	let window = Window {
+		start,
+		end: start + duration,
+		#[allow(clippy::cast_precision_loss)]
+		offset: input
+			.start_time()
+			// SAFETY: the start time would need to be humongous for this cast to f64 to cause problems
+			.map_or(0.0, |start_time| {
+				start_time as f64 / f64::from(AV_TIME_BASE)
+			}),
+	};
+
+	let output_filename = from_path(output_path)?;
+	let mut output = FFmpegOutputContext::create(c"mpegts", &output_filename)?;
+
+	let mut streams = Vec::with_capacity(2);
+
+	if let Some(index) = find_best_stream(&mut input, AVMediaType::AVMEDIA_TYPE_VIDEO) {
+		streams.push(SegmentStream::Transcode(Box::new(StreamTranscoder::video(
+			&mut input,
+			index,
+			max_height,
+			&mut output,
+		)?)));
+	}
+
+	if let Some(index) = find_best_stream(&mut input, AVMediaType::AVMEDIA_TYPE_AUDIO) {
+		let is_aac = input
+			.stream(index)
+			.and_then(|stream| unsafe { stream.codecpar.as_ref() })
+			.is_some_and(|codec_params| codec_params.codec_id == AVCodecID::AV_CODEC_ID_AAC);
+
+		streams.push(if is_aac {
+			SegmentStream::Copy(StreamCopy::new(&input, index, &mut output)?)
+		} else {
+			SegmentStream::Transcode(Box::new(StreamTranscoder::audio(
+				&input,
+				index,
+				&mut output,
+			)?))
+		});
+	}
+
+	if streams.is_empty() {
+		return Err(FFmpegError::StreamNotFound.into());
+	}
+
+	output.open(&output_filename)?;
+
+	// Seeks to the keyframe right before the segment start, as we can only start decoding there
+	let target = from_seconds(
+		window.start + window.offset,
+		AVRational {
+			num: 1,
+			den: AV_TIME_BASE,
+		},
+	);
+	check_error(
+		unsafe { avformat_seek_file(input.as_mut(), -1, i64::MIN, target, target, 0) },
+		"Failed to seek to the segment start",
+	)?;
+
+	let mut packet = FFmpegPacket::new()?;
+	while !streams.iter().all(SegmentStream::finished) {
+		// Either an error or the end of the file, we just transcode what we have so far
+		if input.read_frame(packet.as_mut()).is_err() {
+			break;
+		}
+
+		let packet = packet.as_mut();
+		if let Some(stream) = streams
+			.iter_mut()
+			.find(|stream| stream.input_index() == packet.stream_index)
+		{
+			stream.process(packet, window, &mut output)?;
+		}
+
+		unsafe { av_packet_unref(packet) };
+	}
+
+	for stream in &mut streams {
+		stream.flush(window, &mut output)?;
+	}
+
+	output.finish()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn splits_playlist_in_segments() {
+		assert_eq!(hls_segment_count(0.0, 6.0), 0);
+		assert_eq!(hls_segment_count(12.0, 6.0), 2);
+		assert_eq!(hls_segment_count(12.5, 6.0), 3);
+
+		assert_eq!(
+			hls_playlist(8.5, 6.0, "42/"),
+			"#EXTM3U\n\
+			#EXT-X-VERSION:3\n\
+			#EXT-X-TARGETDURATION:6\n\
+			#EXT-X-MEDIA-SEQUENCE:0\n\
+			#EXT-X-PLAYLIST-TYPE:VOD\n\
+			#EXTINF:6.000000,\n42/0.ts\n\
+			#EXTINF:2.500000,\n42/1.ts\n\
+			#EXT-X-ENDLIST\n"
+		);
+	}
+}
@@ -1,5 +1,7 @@
 use crate::error::FFmpegError;
-use ffmpeg_sys_next::{av_frame_alloc, av_frame_free, AVFrame};
+use ffmpeg_sys_next::{
+	av_frame_alloc, av_frame_free, av_packet_alloc, av_packet_free, AVFrame, AVPacket,
+};
 
 pub struct FFmpegFrame(*mut AVFrame);
 
@@ -29,3 +31,28 @@ impl Drop for FFmpegFrame {
 		}
 	}
 }
+
+pub struct FFmpegPacket(*mut AVPacket);
+
+impl FFmpegPacket {
+	pub(crate) fn new() -> Result<Self, FFmpegError> {
+		let ptr = unsafe { av_packet_alloc() };
+		if ptr.is_null() {
+			return Err(FFmpegError::FrameAllocation);
+		}
+		Ok(Self(ptr))
+	}
+
+	pub(crate) fn as_mut(&mut self) -> &mut AVPacket {
+		unsafe { self.0.as_mut() }.expect("initialized on struct creation")
+	}
+}
+
+impl Drop for FFmpegPacket {
+	fn drop(&mut self) {
+		if !self.0.is_null() {
+			unsafe { av_packet_free(&mut self.0) };
+			self.0 = std::ptr::null_mut();
+		}
+	}
+}
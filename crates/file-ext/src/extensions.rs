@@ -94,6 +94,8 @@ extension_category_enum! {
 		Nef = [0x49, 0x49, 0x2A, 0x00, 0x08, 0x00, 0x00, 0x00, 0x4E, 0x45, 0x46, 0x00],
 		Arw = [0x49, 0x49, 0x2A, 0x00, 0x08],
 		Rw2 = [0x49, 0x49, 0x2A, 0x00, 0x18],
+		Cr3 = [0x00, 0x00, 0x00, 0x18, 0x66, 0x74, 0x79, 0x70, 0x63, 0x72, 0x78, 0x20],
+		Raf = [0x46, 0x55, 0x4A, 0x49, 0x46, 0x49, 0x4C, 0x4D],
 	}
 }
 
@@ -230,7 +232,7 @@ extension_category_enum! {
 
 // font extensions
 extension_category_enum! {
-	FontExtension _ALL_FONT_EXTENSIONS {
+	FontExtension ALL_FONT_EXTENSIONS {
 		Ttf = [0x00, 0x01, 0x00, 0x00, 0x00],
 		Otf = [0x4F, 0x54, 0x54, 0x4F, 0x00],
 		Woff = [0x77, 0x4F, 0x46, 0x46],
@@ -238,11 +240,16 @@ extension_category_enum! {
 	}
 }
 
-// font extensions
+// mesh extensions
 extension_category_enum! {
-	MeshExtension _ALL_MESH_EXTENSIONS {
+	MeshExtension ALL_MESH_EXTENSIONS {
 		Fbx = [0x46, 0x42, 0x58, 0x20],
 		Obj = [0x6F, 0x62, 0x6A],
+		// Only ASCII STL files have magic bytes, binary ones start with an arbitrary header
+		Stl = [0x73, 0x6F, 0x6C, 0x69, 0x64],
+		// glTF files are plain JSON, so only their extension tells them apart
+		Gltf = [],
+		Glb = [0x67, 0x6C, 0x54, 0x46],
 	}
 }
 
@@ -355,7 +362,7 @@ extension_category_enum! {
 
 // book extensions
 extension_category_enum! {
-	BookExtension _ALL_BOOK_EXTENSIONS {
+	BookExtension ALL_BOOK_EXTENSIONS {
 		Azw = [0x52, 0x49, 0x46, 0x46],
 		Azw3 = [0x52, 0x49, 0x46, 0x46],
 		Epub = [0x50, 0x4B, 0x03, 0x04],
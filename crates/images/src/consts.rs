@@ -17,6 +17,12 @@ pub const GENERIC_EXTENSIONS: [&str; 17] = [
 ];
 pub const SVG_EXTENSIONS: [&str; 2] = ["svg", "svgz"];
 pub const PDF_EXTENSIONS: [&str; 1] = ["pdf"];
+/// Font formats that we can render a glyph specimen for, compressed web fonts aren't supported
+pub const FONT_EXTENSIONS: [&str; 2] = ["ttf", "otf"];
+pub const MESH_EXTENSIONS: [&str; 4] = ["obj", "stl", "gltf", "glb"];
+pub const EPUB_EXTENSIONS: [&str; 1] = ["epub"];
+/// Camera RAW formats, decoded in pure Rust so they aren't behind a feature like HEIF
+pub const RAW_EXTENSIONS: [&str; 7] = ["dng", "cr2", "cr3", "nef", "arw", "raf", "rw2"];
 #[cfg(feature = "heif")]
 pub const HEIF_EXTENSIONS: [&str; 8] = [
 	"hif", "heif", "heifs", "heic", "heics", "avif", "avci", "avcs",
@@ -39,6 +45,23 @@ pub const SVG_TARGET_PX: f32 = 262_144_f32;
 pub const PDF_PORTRAIT_RENDER_WIDTH: pdfium_render::prelude::Pixels = 794;
 pub const PDF_LANDSCAPE_RENDER_WIDTH: pdfium_render::prelude::Pixels = 1123;
 
+/// The minimum size of the longest side of an embedded RAW preview for it to be used instead of
+/// developing the sensor data, as some cameras only embed tiny thumbnails.
+pub const RAW_MINIMUM_PREVIEW_SIZE: u32 = 1024;
+
+/// The size of the square glyph specimen rendered for fonts.
+pub const FONT_SPECIMEN_SIZE: u32 = 512;
+
+/// Lines shown below the big "Aa" in the glyph specimen.
+pub const FONT_SPECIMEN_LINES: [&str; 3] = ["ABCDEFGHIJKLM", "nopqrstuvwxyz", "0123456789"];
+
+/// The size of the square image meshes are rendered to.
+pub const MESH_RENDER_SIZE: u32 = 1024;
+
+/// We stop rendering after this many triangles, so huge scans don't take forever.
+/// The preview may miss some parts, but it's still way better than an icon.
+pub const MESH_TRIANGLE_LIMIT: usize = 2_000_000;
+
 #[cfg_attr(feature = "specta", derive(specta::Type))]
 #[cfg_attr(feature = "bincode", derive(bincode::Encode, bincode::Decode))]
 #[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
@@ -193,6 +216,7 @@ pub fn all_compatible_extensions() -> Vec<String> {
 		.chain(HEIF_EXTENSIONS)
 		.chain(SVG_EXTENSIONS)
 		.chain(PDF_EXTENSIONS)
+		.chain(RAW_EXTENSIONS)
 		.map(String::from)
 		.collect();
 
@@ -201,6 +225,7 @@ pub fn all_compatible_extensions() -> Vec<String> {
 		.into_iter()
 		.chain(SVG_EXTENSIONS)
 		.chain(PDF_EXTENSIONS)
+		.chain(RAW_EXTENSIONS)
 		.map(String::from)
 		.collect();
 
@@ -0,0 +1,155 @@
+use std::{
+	fs::File,
+	io::Read,
+	path::{Component, Path, PathBuf},
+};
+
+use crate::{Error, ImageHandler, Result};
+use image::DynamicImage;
+use zip::ZipArchive;
+
+/// Where the EPUB container points to the package document, as defined by the EPUB OCF spec
+const CONTAINER_PATH: &str = "META-INF/container.xml";
+
+#[derive(PartialEq, Eq)]
+pub struct EpubHandler {}
+
+impl ImageHandler for EpubHandler {
+	fn handle_image(&self, path: &Path) -> Result<DynamicImage> {
+		self.validate_size(path)?;
+
+		let file =
+			File::open(path).map_err(|e| Error::Io(e, path.to_path_buf().into_boxed_path()))?;
+		let mut archive = ZipArchive::new(file)?;
+
+		let container = read_entry(&mut archive, CONTAINER_PATH)?;
+		let package_path = find_package_path(&String::from_utf8_lossy(&container))?;
+
+		let package = read_entry(&mut archive, &package_path)?;
+		let cover_href = find_cover_href(&String::from_utf8_lossy(&package))?;
+
+		// Hrefs in the package document are relative to the package document itself
+		let cover_path = resolve_href(&package_path, &cover_href).ok_or(Error::NoEpubCover)?;
+
+		Ok(image::load_from_memory(&read_entry(
+			&mut archive,
+			&cover_path,
+		)?)?)
+	}
+}
+
+fn read_entry(archive: &mut ZipArchive<File>, name: &str) -> Result<Vec<u8>> {
+	let mut entry = archive.by_name(name)?;
+	let mut data = Vec::with_capacity(usize::try_from(entry.size())?);
+	entry
+		.read_to_end(&mut data)
+		.map_err(|e| Error::Io(e, PathBuf::from(name).into_boxed_path()))?;
+
+	Ok(data)
+}
+
+fn find_package_path(container: &str) -> Result<String> {
+	roxmltree::Document::parse(container)?
+		.descendants()
+		.find(|node| node.has_tag_name("rootfile"))
+		.and_then(|node| node.attribute("full-path"))
+		.map(str::to_string)
+		.ok_or(Error::NoEpubCover)
+}
+
+/// Looks for the cover image in the package document manifest, in order:
+/// - EPUB 3 items with the `cover-image` property
+/// - EPUB 2 items referenced by a `<meta name="cover">` element
+/// - Any image item that looks like a cover by its id or href
+fn find_cover_href(package: &str) -> Result<String> {
+	let doc = roxmltree::Document::parse(package)?;
+
+	let items = doc
+		.descendants()
+		.filter(|node| node.has_tag_name("item"))
+		.collect::<Vec<_>>();
+
+	let by_property = || {
+		items.iter().find(|item| {
+			item.attribute("properties")
+				.is_some_and(|properties| properties.split_whitespace().any(|p| p == "cover-image"))
+		})
+	};
+
+	let by_meta = || {
+		doc.descendants()
+			.find(|node| node.has_tag_name("meta") && node.attribute("name") == Some("cover"))
+			.and_then(|meta| meta.attribute("content"))
+			.and_then(|id| items.iter().find(|item| item.attribute("id") == Some(id)))
+	};
+
+	let by_name = || {
+		items.iter().find(|item| {
+			item.attribute("media-type")
+				.is_some_and(|media_type| media_type.starts_with("image/"))
+				&& [item.attribute("id"), item.attribute("href")]
+					.into_iter()
+					.flatten()
+					.any(|name| name.to_lowercase().contains("cover"))
+		})
+	};
+
+	by_property()
+		.or_else(by_meta)
+		.or_else(by_name)
+		.and_then(|item| item.attribute("href"))
+		.map(str::to_string)
+		.ok_or(Error::NoEpubCover)
+}
+
+/// Resolves an href relative to the package document, as a zip entry name
+fn resolve_href(package_path: &str, href: &str) -> Option<String> {
+	let mut resolved = Vec::new();
+
+	let base = Path::new(package_path)
+		.parent()
+		.unwrap_or_else(|| Path::new(""));
+	for component in base.join(href).components() {
+		match component {
+			Component::Normal(part) => resolved.push(part.to_str()?),
+			Component::ParentDir => {
+				resolved.pop()?;
+			}
+			_ => {}
+		}
+	}
+
+	Some(resolved.join("/"))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn finds_cover_in_epub2_and_epub3_packages() {
+		let epub3 = r#"<package xmlns="http://www.idpf.org/2007/opf"><manifest>
+			<item id="c1" href="text/ch1.xhtml" media-type="application/xhtml+xml"/>
+			<item id="img" href="images/front.jpg" media-type="image/jpeg" properties="cover-image"/>
+		</manifest></package>"#;
+		assert_eq!(
+			find_cover_href(epub3).ok().as_deref(),
+			Some("images/front.jpg")
+		);
+
+		let epub2 = r#"<package xmlns="http://www.idpf.org/2007/opf">
+			<metadata><meta name="cover" content="front"/></metadata>
+			<manifest><item id="front" href="../art.png" media-type="image/png"/></manifest>
+		</package>"#;
+		assert_eq!(find_cover_href(epub2).ok().as_deref(), Some("../art.png"));
+
+		assert_eq!(
+			resolve_href("OEBPS/content.opf", "../art.png").as_deref(),
+			Some("art.png")
+		);
+		assert_eq!(
+			resolve_href("content.opf", "images/front.jpg").as_deref(),
+			Some("images/front.jpg")
+		);
+	}
+}
@@ -33,10 +33,24 @@ pub enum Error {
 	Pixbuf,
 	#[error("error while loading the image (via the `image` crate): {0}")]
 	Image(#[from] image::ImageError),
-	// #[error("error while converting from raw")] // not enough rust support for it to be feasible
-	// RawConversion,
+	#[error("error with rawler: {0}")]
+	Raw(#[from] rawler::RawlerError),
+	#[error("error while converting from raw")]
+	RawConversion,
 	#[error("error while parsing integers")]
 	TryFromInt(#[from] TryFromIntError),
+	#[error("the font file has no font faces")]
+	NoFontFaces,
+	#[error("the mesh has no triangles to render")]
+	EmptyMesh,
+	#[error("error with gltf: {0}")]
+	Gltf(#[from] gltf::Error),
+	#[error("the ebook has no cover image")]
+	NoEpubCover,
+	#[error("error while reading the ebook archive: {0}")]
+	Zip(#[from] zip::result::ZipError),
+	#[error("error while parsing the ebook package: {0}")]
+	Xml(#[from] roxmltree::Error),
 }
 
 #[cfg(feature = "rspc")]
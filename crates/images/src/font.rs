@@ -0,0 +1,87 @@
+use std::{path::Path, sync::Arc};
+
+use crate::{
+	consts::{FONT_SPECIMEN_LINES, FONT_SPECIMEN_SIZE},
+	Error, ImageHandler, Result,
+};
+use image::DynamicImage;
+use resvg::{tiny_skia, usvg};
+
+#[derive(PartialEq, Eq)]
+pub struct FontHandler {}
+
+impl ImageHandler for FontHandler {
+	fn handle_image(&self, path: &Path) -> Result<DynamicImage> {
+		let data = self.get_data(path)?;
+
+		// Only the font being previewed is loaded, so the specimen can't be rendered with a fallback font
+		let mut fontdb = usvg::fontdb::Database::new();
+		fontdb.load_font_data(data);
+
+		let family = fontdb
+			.faces()
+			.next()
+			.and_then(|face| face.families.first())
+			.map(|(family, _)| family.clone())
+			.ok_or(Error::NoFontFaces)?;
+
+		let options = usvg::Options {
+			font_family: family.clone(),
+			fontdb: Arc::new(fontdb),
+			..Default::default()
+		};
+
+		let rtree = usvg::Tree::from_str(&specimen_svg(&family), &options)?;
+
+		let Some(mut pixmap) = tiny_skia::Pixmap::new(FONT_SPECIMEN_SIZE, FONT_SPECIMEN_SIZE)
+		else {
+			return Err(Error::Pixbuf);
+		};
+
+		resvg::render(
+			&rtree,
+			tiny_skia::Transform::default(),
+			&mut pixmap.as_mut(),
+		);
+
+		image::RgbaImage::from_raw(pixmap.width(), pixmap.height(), pixmap.data().into())
+			.map_or_else(
+				|| Err(Error::RgbImageConversion),
+				|x| Ok(DynamicImage::ImageRgba8(x)),
+			)
+	}
+}
+
+/// A glyph specimen, with a big "Aa" on top of a few lines with the alphabet and digits
+fn specimen_svg(family: &str) -> String {
+	let family = escape_xml(family);
+	let center = FONT_SPECIMEN_SIZE / 2;
+
+	let lines = FONT_SPECIMEN_LINES
+		.iter()
+		.zip((320..).step_by(50))
+		.map(|(line, y)| {
+			format!(
+				r#"<text x="{center}" y="{y}" font-size="36" text-anchor="middle">{line}</text>"#
+			)
+		})
+		.collect::<String>();
+
+	format!(
+		r#"<svg xmlns="http://www.w3.org/2000/svg" width="{FONT_SPECIMEN_SIZE}" height="{FONT_SPECIMEN_SIZE}">
+			<rect width="100%" height="100%" rx="24" fill="white"/>
+			<g font-family="{family}" fill="black">
+				<text x="{center}" y="230" font-size="180" text-anchor="middle">Aa</text>
+				{lines}
+			</g>
+		</svg>"#
+	)
+}
+
+fn escape_xml(value: &str) -> String {
+	value
+		.replace('&', "&amp;")
+		.replace('<', "&lt;")
+		.replace('>', "&gt;")
+		.replace('"', "&quot;")
+}
@@ -1,8 +1,12 @@
 use crate::{
 	consts,
+	epub::EpubHandler,
 	error::{Error, Result},
+	font::FontHandler,
 	generic::GenericHandler,
+	mesh::MeshHandler,
 	pdf::PdfHandler,
+	raw::RawHandler,
 	svg::SvgHandler,
 	ImageHandler,
 };
@@ -63,5 +67,37 @@ fn match_to_handler(ext: Option<&OsStr>) -> Result<Box<dyn ImageHandler>> {
 		handler = Some(Box::new(PdfHandler {}));
 	}
 
+	if consts::RAW_EXTENSIONS
+		.iter()
+		.map(OsString::from)
+		.any(|x| x == ext)
+	{
+		handler = Some(Box::new(RawHandler {}));
+	}
+
+	if consts::FONT_EXTENSIONS
+		.iter()
+		.map(OsString::from)
+		.any(|x| x == ext)
+	{
+		handler = Some(Box::new(FontHandler {}));
+	}
+
+	if consts::MESH_EXTENSIONS
+		.iter()
+		.map(OsString::from)
+		.any(|x| x == ext)
+	{
+		handler = Some(Box::new(MeshHandler {}));
+	}
+
+	if consts::EPUB_EXTENSIONS
+		.iter()
+		.map(OsString::from)
+		.any(|x| x == ext)
+	{
+		handler = Some(Box::new(EpubHandler {}));
+	}
+
 	handler.ok_or(Error::Unsupported)
 }
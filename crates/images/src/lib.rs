@@ -23,12 +23,16 @@
 use std::{fs, path::Path};
 
 mod consts;
+mod epub;
 mod error;
+mod font;
 mod generic;
 mod handler;
 #[cfg(feature = "heif")]
 mod heif;
+mod mesh;
 mod pdf;
+mod raw;
 mod svg;
 
 use consts::MAXIMUM_FILE_SIZE;
@@ -37,6 +41,7 @@ use consts::MAXIMUM_FILE_SIZE;
 pub use consts::{all_compatible_extensions, ConvertibleExtension};
 pub use error::{Error, Result};
 pub use handler::{convert_image, format_image};
+pub use pdf::load_pdfium;
 pub use image::DynamicImage;
 
 pub trait ImageHandler {
@@ -0,0 +1,422 @@
+use std::{ffi::OsStr, path::Path};
+
+use crate::{
+	consts::{MESH_RENDER_SIZE, MESH_TRIANGLE_LIMIT},
+	Error, ImageHandler, Result,
+};
+use image::{DynamicImage, Rgba, RgbaImage};
+
+type Vec3 = [f32; 3];
+type Triangle = [Vec3; 3];
+
+/// Renders meshes with a small software rasterizer: flat shaded triangles with a depth buffer,
+/// seen from above and to the side, so we don't depend on a GPU to generate thumbnails.
+#[derive(PartialEq, Eq)]
+pub struct MeshHandler {}
+
+impl ImageHandler for MeshHandler {
+	fn handle_image(&self, path: &Path) -> Result<DynamicImage> {
+		let triangles = match path
+			.extension()
+			.and_then(OsStr::to_str)
+			.map(str::to_ascii_lowercase)
+			.as_deref()
+		{
+			Some("obj") => parse_obj(&String::from_utf8_lossy(&self.get_data(path)?)),
+			Some("stl") => parse_stl(&self.get_data(path)?),
+			Some("gltf" | "glb") => {
+				self.validate_size(path)?;
+				load_gltf(path)?
+			}
+			_ => return Err(Error::Unsupported),
+		};
+
+		if triangles.is_empty() {
+			return Err(Error::EmptyMesh);
+		}
+
+		Ok(DynamicImage::ImageRgba8(render(
+			&triangles[..triangles.len().min(MESH_TRIANGLE_LIMIT)],
+			MESH_RENDER_SIZE,
+		)))
+	}
+}
+
+/// Wavefront OBJ, only vertex positions and faces matter to us
+fn parse_obj(data: &str) -> Vec<Triangle> {
+	let mut vertices = Vec::new();
+	let mut triangles = Vec::new();
+
+	for line in data.lines() {
+		let mut parts = line.split_whitespace();
+		match parts.next() {
+			Some("v") => {
+				let coords = parts
+					.take(3)
+					.filter_map(|part| part.parse().ok())
+					.collect::<Vec<f32>>();
+				if let [x, y, z] = coords[..] {
+					vertices.push([x, y, z]);
+				}
+			}
+			Some("f") => {
+				// Indices are 1-based, or relative to the end of the vertex list when negative,
+				// and may come with texture and normal indices as in `v/vt/vn`
+				let face = parts
+					.filter_map(|part| part.split('/').next()?.parse::<isize>().ok())
+					.filter_map(|index| {
+						if index < 0 {
+							vertices.len().checked_sub(index.unsigned_abs())
+						} else {
+							usize::try_from(index).ok()?.checked_sub(1)
+						}
+					})
+					.filter_map(|index| vertices.get(index).copied())
+					.collect::<Vec<_>>();
+
+				// Polygons are triangulated as a fan around their first vertex
+				for i in 1..face.len().saturating_sub(1) {
+					triangles.push([face[0], face[i], face[i + 1]]);
+				}
+			}
+			_ => {}
+		}
+	}
+
+	triangles
+}
+
+/// STL, either binary or ASCII
+fn parse_stl(data: &[u8]) -> Vec<Triangle> {
+	// Binary STL files have an 80 bytes header, a triangle count and then 50 bytes per triangle.
+	// ASCII ones start with `solid`, but some binary exporters also write that in the header,
+	// so the size is the most reliable way to tell them apart.
+	let binary_count = data
+		.get(80..84)
+		.and_then(|count| count.try_into().ok())
+		.map(u32::from_le_bytes)
+		.and_then(|count| usize::try_from(count).ok());
+
+	let is_binary = |count: &usize| {
+		count
+			.checked_mul(50)
+			.and_then(|size| size.checked_add(84))
+			.is_some_and(|size| size == data.len())
+	};
+
+	if let Some(count) = binary_count.filter(is_binary) {
+		return data[84..]
+			.chunks_exact(50)
+			.take(count)
+			.map(|chunk| {
+				// Skipping the facet normal, as we compute our own
+				let vertex = |offset: usize| {
+					let coord = |i: usize| {
+						let start = offset + i * 4;
+						f32::from_le_bytes([
+							chunk[start],
+							chunk[start + 1],
+							chunk[start + 2],
+							chunk[start + 3],
+						])
+					};
+					[coord(0), coord(1), coord(2)]
+				};
+				[vertex(12), vertex(24), vertex(36)]
+			})
+			.collect();
+	}
+
+	let vertices = String::from_utf8_lossy(data)
+		.lines()
+		.filter_map(|line| {
+			let mut parts = line.split_whitespace();
+			(parts.next() == Some("vertex")).then_some(())?;
+			let coords = parts
+				.filter_map(|part| part.parse().ok())
+				.collect::<Vec<f32>>();
+			match coords[..] {
+				[x, y, z] => Some([x, y, z]),
+				_ => None,
+			}
+		})
+		.collect::<Vec<_>>();
+
+	vertices
+		.chunks_exact(3)
+		.map(|triangle| [triangle[0], triangle[1], triangle[2]])
+		.collect()
+}
+
+/// glTF and GLB, with node transforms from the default scene applied
+fn load_gltf(path: &Path) -> Result<Vec<Triangle>> {
+	let (document, buffers, _) = gltf::import(path)?;
+
+	let Some(scene) = document
+		.default_scene()
+		.or_else(|| document.scenes().next())
+	else {
+		return Ok(vec![]);
+	};
+
+	let mut triangles = Vec::new();
+	let mut nodes = scene
+		.nodes()
+		.map(|node| (node, IDENTITY))
+		.collect::<Vec<_>>();
+
+	while let Some((node, parent_transform)) = nodes.pop() {
+		let transform = multiply(&parent_transform, &node.transform().matrix());
+
+		if let Some(mesh) = node.mesh() {
+			for primitive in mesh.primitives() {
+				if primitive.mode() != gltf::mesh::Mode::Triangles {
+					continue;
+				}
+
+				let reader = primitive.reader(|buffer| Some(&buffers[buffer.index()]));
+				let Some(positions) = reader.read_positions() else {
+					continue;
+				};
+
+				let positions = positions
+					.map(|position| apply(&transform, position))
+					.collect::<Vec<_>>();
+
+				let indices = reader.read_indices().map_or_else(
+					|| (0..positions.len()).collect::<Vec<_>>(),
+					|indices| {
+						indices
+							.into_u32()
+							.filter_map(|index| usize::try_from(index).ok())
+							.collect()
+					},
+				);
+
+				triangles.extend(indices.chunks_exact(3).filter_map(|triangle| {
+					Some([
+						*positions.get(triangle[0])?,
+						*positions.get(triangle[1])?,
+						*positions.get(triangle[2])?,
+					])
+				}));
+			}
+		}
+
+		nodes.extend(node.children().map(|child| (child, transform)));
+	}
+
+	Ok(triangles)
+}
+
+type Matrix = [[f32; 4]; 4];
+
+const IDENTITY: Matrix = [
+	[1.0, 0.0, 0.0, 0.0],
+	[0.0, 1.0, 0.0, 0.0],
+	[0.0, 0.0, 1.0, 0.0],
+	[0.0, 0.0, 0.0, 1.0],
+];
+
+/// Multiplies column-major matrices, as used by glTF
+fn multiply(a: &Matrix, b: &Matrix) -> Matrix {
+	let mut result = [[0.0; 4]; 4];
+	for (col, result_col) in result.iter_mut().enumerate() {
+		for (row, value) in result_col.iter_mut().enumerate() {
+			*value = (0..4).map(|k| a[k][row] * b[col][k]).sum();
+		}
+	}
+	result
+}
+
+fn apply(m: &Matrix, [x, y, z]: Vec3) -> Vec3 {
+	[
+		m[0][0] * x + m[1][0] * y + m[2][0] * z + m[3][0],
+		m[0][1] * x + m[1][1] * y + m[2][1] * z + m[3][1],
+		m[0][2] * x + m[1][2] * y + m[2][2] * z + m[3][2],
+	]
+}
+
+fn sub(a: Vec3, b: Vec3) -> Vec3 {
+	[a[0] - b[0], a[1] - b[1], a[2] - b[2]]
+}
+
+fn cross(a: Vec3, b: Vec3) -> Vec3 {
+	[
+		a[1] * b[2] - a[2] * b[1],
+		a[2] * b[0] - a[0] * b[2],
+		a[0] * b[1] - a[1] * b[0],
+	]
+}
+
+fn normalize(v: Vec3) -> Vec3 {
+	let length = (v[0] * v[0] + v[1] * v[1] + v[2] * v[2]).sqrt();
+	if length > f32::EPSILON {
+		[v[0] / length, v[1] / length, v[2] / length]
+	} else {
+		[0.0, 0.0, 0.0]
+	}
+}
+
+/// Rotates around the Y axis and then around the X axis, so we see the model from a 3/4 view
+fn rotate([x, y, z]: Vec3, yaw: f32, pitch: f32) -> Vec3 {
+	let (sin_yaw, cos_yaw) = yaw.sin_cos();
+	let (x, z) = (
+		x.mul_add(cos_yaw, z * sin_yaw),
+		z.mul_add(cos_yaw, -(x * sin_yaw)),
+	);
+
+	let (sin_pitch, cos_pitch) = pitch.sin_cos();
+	[
+		x,
+		y.mul_add(cos_pitch, -(z * sin_pitch)),
+		y.mul_add(sin_pitch, z * cos_pitch),
+	]
+}
+
+#[allow(
+	clippy::as_conversions,
+	clippy::cast_precision_loss,
+	clippy::cast_possible_truncation,
+	clippy::cast_sign_loss
+)]
+fn render(triangles: &[Triangle], size: u32) -> RgbaImage {
+	const BASE_COLOR: [f32; 3] = [186.0, 192.0, 204.0];
+	const AMBIENT: f32 = 0.3;
+
+	let light = normalize([-0.4, 0.6, 1.0]);
+	let yaw = 35_f32.to_radians();
+	let pitch = 25_f32.to_radians();
+
+	let rotated = triangles
+		.iter()
+		.map(|triangle| triangle.map(|vertex| rotate(vertex, yaw, pitch)))
+		.collect::<Vec<_>>();
+
+	// Fitting the model in the image, keeping some margin around it
+	let (min, max) =
+		rotated
+			.iter()
+			.flatten()
+			.fold(([f32::MAX; 3], [f32::MIN; 3]), |(min, max), vertex| {
+				(
+					[
+						min[0].min(vertex[0]),
+						min[1].min(vertex[1]),
+						min[2].min(vertex[2]),
+					],
+					[
+						max[0].max(vertex[0]),
+						max[1].max(vertex[1]),
+						max[2].max(vertex[2]),
+					],
+				)
+			});
+
+	let extent = (max[0] - min[0]).max(max[1] - min[1]).max(f32::EPSILON);
+	let scale = size as f32 * 0.9 / extent;
+	let center = [(min[0] + max[0]) / 2.0, (min[1] + max[1]) / 2.0];
+	let half_size = size as f32 / 2.0;
+
+	// Image Y goes down, while model Y goes up
+	let project = |[x, y, z]: Vec3| {
+		[
+			(x - center[0]).mul_add(scale, half_size),
+			(center[1] - y).mul_add(scale, half_size),
+			z,
+		]
+	};
+
+	let mut image = RgbaImage::new(size, size);
+	let mut depth = vec![f32::MIN; (size * size) as usize];
+
+	for triangle in &rotated {
+		let normal = normalize(cross(
+			sub(triangle[1], triangle[0]),
+			sub(triangle[2], triangle[0]),
+		));
+
+		// Winding order isn't reliable across exporters, so faces are lit from both sides
+		let diffuse = (normal[0] * light[0] + normal[1] * light[1] + normal[2] * light[2]).abs();
+		let shade = (1.0 - AMBIENT).mul_add(diffuse, AMBIENT);
+		let color = Rgba([
+			(BASE_COLOR[0] * shade) as u8,
+			(BASE_COLOR[1] * shade) as u8,
+			(BASE_COLOR[2] * shade) as u8,
+			255,
+		]);
+
+		let [a, b, c] = triangle.map(project);
+
+		let area = edge(a, b, c);
+		if area.abs() < f32::EPSILON {
+			continue;
+		}
+
+		let min_x = a[0].min(b[0]).min(c[0]).floor().max(0.0) as u32;
+		let max_x = a[0].max(b[0]).max(c[0]).ceil().min(size as f32 - 1.0) as u32;
+		let min_y = a[1].min(b[1]).min(c[1]).floor().max(0.0) as u32;
+		let max_y = a[1].max(b[1]).max(c[1]).ceil().min(size as f32 - 1.0) as u32;
+
+		for y in min_y..=max_y {
+			for x in min_x..=max_x {
+				let p = [x as f32 + 0.5, y as f32 + 0.5, 0.0];
+
+				let w0 = edge(b, c, p) / area;
+				let w1 = edge(c, a, p) / area;
+				let w2 = edge(a, b, p) / area;
+
+				if w0 < 0.0 || w1 < 0.0 || w2 < 0.0 {
+					continue;
+				}
+
+				let z = w2.mul_add(c[2], w0.mul_add(a[2], w1 * b[2]));
+				let depth_idx = (y * size + x) as usize;
+
+				// Bigger Z is closer to the camera
+				if z > depth[depth_idx] {
+					depth[depth_idx] = z;
+					image.put_pixel(x, y, color);
+				}
+			}
+		}
+	}
+
+	image
+}
+
+fn edge(a: [f32; 3], b: [f32; 3], p: [f32; 3]) -> f32 {
+	(b[0] - a[0]).mul_add(p[1] - a[1], -((b[1] - a[1]) * (p[0] - a[0])))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn parses_obj_and_ascii_stl() {
+		let obj = "v 0 0 0\nv 1 0 0\nv 1 1 0\nv 0 1 0\nf 1/1/1 2/2/2 3/3/3 -1\n";
+		assert_eq!(
+			parse_obj(obj),
+			vec![
+				[[0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [1.0, 1.0, 0.0]],
+				[[0.0, 0.0, 0.0], [1.0, 1.0, 0.0], [0.0, 1.0, 0.0]],
+			]
+		);
+
+		let stl = "solid test\nfacet normal 0 0 1\nouter loop\nvertex 0 0 0\nvertex 1 0 0\n\
+			vertex 0 1 0\nendloop\nendfacet\nendsolid test\n";
+		assert_eq!(
+			parse_stl(stl.as_bytes()),
+			vec![[[0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0]]]
+		);
+	}
+
+	#[test]
+	fn renders_something() {
+		let image = render(&[[[0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0]]], 64);
+
+		assert!(image.pixels().any(|pixel| pixel.0[3] == 255));
+		assert!(image.pixels().any(|pixel| pixel.0[3] == 0));
+	}
+}
@@ -76,14 +76,21 @@ static LANDSCAPE_CONFIG: Lazy<PdfRenderConfig> = Lazy::new(|| {
 	thumbnail_config(PdfRenderConfig::new().set_target_width(PDF_LANDSCAPE_RENDER_WIDTH))
 });
 
+/// Binds to the pdfium library shipped with the app, falling back to the system one
+pub fn load_pdfium() -> Result<Pdfium> {
+	Ok(Pdfium::new(
+		Pdfium::bind_to_library(PDFIUM_LIB.as_str()).or_else(|err| {
+			error!("{err:#?}");
+			Pdfium::bind_to_system_library()
+		})?,
+	))
+}
+
 pub struct PdfHandler {}
 
 impl ImageHandler for PdfHandler {
 	fn handle_image(&self, path: &Path) -> Result<DynamicImage> {
-		let pdfium = Pdfium::new(Pdfium::bind_to_library(PDFIUM_LIB.as_str()).or_else(|err| {
-			error!("{err:#?}");
-			Pdfium::bind_to_system_library()
-		})?);
+		let pdfium = load_pdfium()?;
 
 		let pdf = pdfium.load_pdf_from_file(path, None)?;
 		let first_page = pdf.pages().first()?;
@@ -0,0 +1,105 @@
+use std::path::Path;
+
+use crate::{consts::RAW_MINIMUM_PREVIEW_SIZE, Error, ImageHandler, Result};
+use image::DynamicImage;
+use rawler::{decoders::RawDecodeParams, imgop::develop::RawDevelop, rawsource::RawSource};
+use tracing::debug;
+
+#[derive(PartialEq, Eq)]
+pub struct RawHandler {}
+
+impl ImageHandler for RawHandler {
+	fn handle_image(&self, path: &Path) -> Result<DynamicImage> {
+		self.validate_size(path)?;
+
+		let source = RawSource::new(path)?;
+		let decoder = rawler::get_decoder(&source)?;
+		let params = RawDecodeParams::default();
+
+		let orientation = decoder.raw_metadata(&source, &params)?.exif.orientation;
+
+		// Most cameras embed a full size JPEG preview, which is way faster to decode than developing
+		// the sensor data, and it also has the camera's own color processing applied
+		let img = match decoder.preview_image(&source, &params) {
+			Ok(Some(preview))
+				if preview.width().max(preview.height()) >= RAW_MINIMUM_PREVIEW_SIZE =>
+			{
+				preview
+			}
+			Ok(_) => {
+				debug!(
+					"No usable embedded preview in '{}', decoding sensor data",
+					path.display()
+				);
+				develop(&source, decoder.as_ref(), &params)?
+			}
+			Err(e) => {
+				debug!(
+					"Failed to extract embedded preview from '{}', decoding sensor data: {e:#?}",
+					path.display()
+				);
+				develop(&source, decoder.as_ref(), &params)?
+			}
+		};
+
+		// Neither the embedded preview nor the sensor data are rotated, and the thumbnailer can't
+		// read the orientation from every RAW container, so we correct it here
+		Ok(correct_orientation(img, orientation))
+	}
+
+	// RAW files can't be decoded by any other handler, so we always decode them ourselves
+	fn convert_image(
+		&self,
+		_opposing_handler: Box<dyn ImageHandler>,
+		path: &Path,
+	) -> Result<DynamicImage> {
+		self.handle_image(path)
+	}
+}
+
+fn develop(
+	source: &RawSource,
+	decoder: &dyn rawler::Decoder,
+	params: &RawDecodeParams,
+) -> Result<DynamicImage> {
+	RawDevelop::default()
+		.develop_intermediate(&decoder.raw_image(source, params, false)?)?
+		.to_dynamic_image()
+		.ok_or(Error::RawConversion)
+}
+
+/// Applies the EXIF orientation, matching `sd_media_metadata::exif::Orientation`
+fn correct_orientation(img: DynamicImage, orientation: Option<u16>) -> DynamicImage {
+	match orientation {
+		Some(2) => img.fliph(),
+		Some(3) => img.rotate180(),
+		Some(4) => img.flipv(),
+		Some(5) => img.fliph().rotate270(),
+		Some(6) => img.rotate90(),
+		Some(7) => img.fliph().rotate90(),
+		Some(8) => img.rotate270(),
+		_ => img,
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use image::{GenericImageView, RgbImage};
+
+	#[test]
+	fn corrects_orientation() {
+		let img = DynamicImage::ImageRgb8(RgbImage::new(4, 2));
+
+		assert_eq!(correct_orientation(img.clone(), None).dimensions(), (4, 2));
+		assert_eq!(
+			correct_orientation(img.clone(), Some(3)).dimensions(),
+			(4, 2)
+		);
+		assert_eq!(
+			correct_orientation(img.clone(), Some(6)).dimensions(),
+			(2, 4)
+		);
+		assert_eq!(correct_orientation(img, Some(5)).dimensions(), (2, 4));
+	}
+}
@@ -0,0 +1,79 @@
+use crate::Result;
+
+use std::{borrow::Cow, path::Path};
+
+use lofty::{
+	error::ErrorKind,
+	file::TaggedFileExt,
+	probe::Probe,
+	tag::{Accessor, ItemKey, Tag},
+};
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use tokio::task::spawn_blocking;
+
+/// Tags of an audio file, from whichever tag format it uses (ID3, Vorbis comments, APE, MP4
+/// atoms and so on)
+#[derive(Default, Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Type)]
+pub struct AudioMetadata {
+	pub title: Option<String>,
+	pub artist: Option<String>,
+	pub album: Option<String>,
+	pub album_artist: Option<String>,
+	pub genre: Option<String>,
+	pub composer: Option<String>,
+	pub year: Option<u32>,
+	pub track_number: Option<u32>,
+	pub track_total: Option<u32>,
+	pub disc_number: Option<u32>,
+	pub disc_total: Option<u32>,
+}
+
+impl AudioMetadata {
+	/// Returns `None` if the file has no tags or isn't in a format we can read tags from
+	pub async fn from_path(path: impl AsRef<Path> + Send) -> Result<Option<Self>> {
+		let path = path.as_ref().to_owned();
+
+		spawn_blocking(move || {
+			match Probe::open(&path).and_then(|probe| probe.guess_file_type()?.read()) {
+				Ok(tagged_file) => Ok(tagged_file
+					.primary_tag()
+					.or_else(|| tagged_file.first_tag())
+					.map(Self::from_tag)
+					.filter(|metadata| metadata != &Self::default())),
+				Err(e)
+					if matches!(
+						e.kind(),
+						ErrorKind::UnknownFormat | ErrorKind::UnsupportedTag
+					) =>
+				{
+					Ok(None)
+				}
+				Err(e) => Err(e.into()),
+			}
+		})
+		.await?
+	}
+
+	fn from_tag(tag: &Tag) -> Self {
+		let text = |value: Option<Cow<'_, str>>| {
+			value
+				.map(|value| value.trim().to_string())
+				.filter(|value| !value.is_empty())
+		};
+
+		Self {
+			title: text(tag.title()),
+			artist: text(tag.artist()),
+			album: text(tag.album()),
+			album_artist: text(tag.get_string(&ItemKey::AlbumArtist).map(Into::into)),
+			genre: text(tag.genre()),
+			composer: text(tag.get_string(&ItemKey::Composer).map(Into::into)),
+			year: tag.year().filter(|year| *year > 0),
+			track_number: tag.track(),
+			track_total: tag.track_total(),
+			disc_number: tag.disk(),
+			disc_total: tag.disk_total(),
+		}
+	}
+}
@@ -0,0 +1,103 @@
+use crate::Result;
+
+use std::path::Path;
+
+use chrono::{DateTime, FixedOffset, NaiveDate, NaiveDateTime};
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use tokio::task::spawn_blocking;
+
+mod package;
+mod pdf;
+
+/// Metadata of a document or ebook, from PDF info dictionaries, OOXML and ODF core properties
+/// or EPUB package documents
+#[derive(Default, Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Type)]
+pub struct DocumentMetadata {
+	pub title: Option<String>,
+	pub author: Option<String>,
+	pub subject: Option<String>,
+	pub page_count: Option<u32>,
+	pub date_created: Option<DateTime<FixedOffset>>,
+}
+
+impl DocumentMetadata {
+	/// PDFs are told apart by their extension, every other format we support is a zip archive
+	/// and we find out which one it is by its contents.
+	///
+	/// Returns `None` if the document has no metadata at all
+	pub async fn from_path(path: impl AsRef<Path> + Send) -> Result<Option<Self>> {
+		let path = path.as_ref().to_owned();
+
+		spawn_blocking(move || {
+			let is_pdf = path
+				.extension()
+				.and_then(|extension| extension.to_str())
+				.is_some_and(|extension| extension.eq_ignore_ascii_case("pdf"));
+
+			if is_pdf {
+				pdf::extract(&path)
+			} else {
+				package::extract(&path)
+			}
+			.map(|metadata| metadata.filter(|metadata| metadata != &Self::default()))
+		})
+		.await?
+	}
+}
+
+fn text(value: &str) -> Option<String> {
+	let value = value.trim();
+	(!value.is_empty()).then(|| value.to_string())
+}
+
+/// Dates in XML metadata are supposed to be W3CDTF, but we find anything from a full RFC 3339
+/// timestamp to a lone year out there. Dates without a timezone are taken as UTC.
+fn parse_date(date: &str) -> Option<DateTime<FixedOffset>> {
+	let date = date.trim();
+
+	DateTime::parse_from_rfc3339(date).ok().or_else(|| {
+		NaiveDateTime::parse_from_str(date, "%Y-%m-%dT%H:%M:%S%.f")
+			.ok()
+			.or_else(|| {
+				NaiveDate::parse_from_str(date, "%Y-%m-%d")
+					.ok()
+					.or_else(|| {
+						date.parse()
+							.ok()
+							.and_then(|year| NaiveDate::from_ymd_opt(year, 1, 1))
+					})
+					.and_then(|date| date.and_hms_opt(0, 0, 0))
+			})
+			.map(|date_time| date_time.and_utc().fixed_offset())
+	})
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn parses_xml_dates() {
+		let date = |s: &str| parse_date(s).map(|date| date.to_rfc3339());
+
+		assert_eq!(
+			date("2023-04-05T10:20:30Z").as_deref(),
+			Some("2023-04-05T10:20:30+00:00")
+		);
+		assert_eq!(
+			date("2023-04-05T10:20:30+02:00").as_deref(),
+			Some("2023-04-05T10:20:30+02:00")
+		);
+		assert_eq!(
+			date(" 2023-04-05T10:20:30.125 ").as_deref(),
+			Some("2023-04-05T10:20:30.125+00:00")
+		);
+		assert_eq!(
+			date("2023-04-05").as_deref(),
+			Some("2023-04-05T00:00:00+00:00")
+		);
+		assert_eq!(date("1999").as_deref(), Some("1999-01-01T00:00:00+00:00"));
+		assert_eq!(date("last tuesday"), None);
+	}
+}
@@ -0,0 +1,265 @@
+//! Zip based formats: OOXML (`.docx`, `.xlsx`, `.pptx`), ODF (`.odt`, `.ods`, `.odp`) and EPUB
+
+use crate::Result;
+
+use sd_utils::error::FileIOError;
+
+use std::{
+	fs::File,
+	io::{Read, Seek},
+	path::Path,
+};
+
+use roxmltree::{Document, Node};
+use zip::{result::ZipError, ZipArchive};
+
+use super::{parse_date, text, DocumentMetadata};
+
+const OOXML_CORE_PATH: &str = "docProps/core.xml";
+const OOXML_APP_PATH: &str = "docProps/app.xml";
+const ODF_META_PATH: &str = "meta.xml";
+const EPUB_CONTAINER_PATH: &str = "META-INF/container.xml";
+
+/// Metadata entries are small XML files, anything bigger is skipped instead of being read in memory
+const MAX_ENTRY_SIZE: u64 = 8 * 1024 * 1024;
+
+pub(super) fn extract(path: &Path) -> Result<Option<DocumentMetadata>> {
+	let file = File::open(path).map_err(|e| FileIOError::from((path, e)))?;
+	let mut archive = ZipArchive::new(file)?;
+
+	if let Some(core) = read_entry(&mut archive, OOXML_CORE_PATH, path)? {
+		let app = read_entry(&mut archive, OOXML_APP_PATH, path)?;
+		return from_ooxml(&core, app.as_deref()).map(Some);
+	}
+
+	if let Some(meta) = read_entry(&mut archive, ODF_META_PATH, path)? {
+		return from_odf(&meta).map(Some);
+	}
+
+	if let Some(container) = read_entry(&mut archive, EPUB_CONTAINER_PATH, path)? {
+		let Some(package_path) = Document::parse(&container)?
+			.descendants()
+			.find(|node| node.has_tag_name("rootfile"))
+			.and_then(|node| node.attribute("full-path"))
+			.map(str::to_string)
+		else {
+			return Ok(None);
+		};
+
+		return read_entry(&mut archive, &package_path, path)?
+			.map(|package| from_epub_package(&package))
+			.transpose();
+	}
+
+	Ok(None)
+}
+
+fn read_entry<R: Read + Seek>(
+	archive: &mut ZipArchive<R>,
+	name: &str,
+	path: &Path,
+) -> Result<Option<String>> {
+	let entry = match archive.by_name(name) {
+		Ok(entry) => entry,
+		Err(ZipError::FileNotFound) => return Ok(None),
+		Err(e) => return Err(e.into()),
+	};
+
+	if entry.size() > MAX_ENTRY_SIZE {
+		return Ok(None);
+	}
+
+	// The size in the archive can't be trusted, so we never read more than that anyway
+	let mut data = Vec::new();
+	entry
+		.take(MAX_ENTRY_SIZE + 1)
+		.read_to_end(&mut data)
+		.map_err(|e| {
+			FileIOError::from((path, e, "Failed to read an entry of the document archive"))
+		})?;
+
+	if data.len() as u64 > MAX_ENTRY_SIZE {
+		return Ok(None);
+	}
+
+	Ok(Some(String::from_utf8_lossy(&data).into_owned()))
+}
+
+/// Text of the first element with this local name, whatever its namespace prefix is
+fn element_text(doc: &Document<'_>, name: &str) -> Option<String> {
+	doc.descendants()
+		.find(|node| node.has_tag_name(name))
+		.and_then(|node| node.text())
+		.and_then(text)
+}
+
+fn attribute<'a>(node: Node<'a, '_>, name: &str) -> Option<&'a str> {
+	node.attributes()
+		.find(|attribute| attribute.name() == name)
+		.map(|attribute| attribute.value())
+}
+
+/// Core properties are Dublin Core elements, the page count (or slide count, for presentations)
+/// is in the extended properties
+fn from_ooxml(core: &str, app: Option<&str>) -> Result<DocumentMetadata> {
+	let core = Document::parse(core)?;
+
+	let page_count = app
+		.map(Document::parse)
+		.transpose()?
+		.and_then(|app| element_text(&app, "Pages").or_else(|| element_text(&app, "Slides")))
+		.and_then(|count| count.parse().ok());
+
+	Ok(DocumentMetadata {
+		title: element_text(&core, "title"),
+		author: element_text(&core, "creator"),
+		subject: element_text(&core, "subject"),
+		page_count,
+		date_created: element_text(&core, "created").and_then(|date| parse_date(&date)),
+	})
+}
+
+/// ODF has both `meta:initial-creator` and `dc:creator`, the latter being whoever last saved the
+/// document, so it is only a fallback for the author
+fn from_odf(meta: &str) -> Result<DocumentMetadata> {
+	let meta = Document::parse(meta)?;
+
+	Ok(DocumentMetadata {
+		title: element_text(&meta, "title"),
+		author: element_text(&meta, "initial-creator").or_else(|| element_text(&meta, "creator")),
+		subject: element_text(&meta, "subject"),
+		page_count: meta
+			.descendants()
+			.find(|node| node.has_tag_name("document-statistic"))
+			.and_then(|node| attribute(node, "page-count"))
+			.and_then(|count| count.parse().ok()),
+		date_created: element_text(&meta, "creation-date").and_then(|date| parse_date(&date)),
+	})
+}
+
+/// Ebooks have no fixed page count, their pages depend on the reader
+fn from_epub_package(package: &str) -> Result<DocumentMetadata> {
+	let package = Document::parse(package)?;
+
+	Ok(DocumentMetadata {
+		title: element_text(&package, "title"),
+		author: element_text(&package, "creator"),
+		subject: element_text(&package, "subject"),
+		page_count: None,
+		date_created: element_text(&package, "date").and_then(|date| parse_date(&date)),
+	})
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	use std::io::{Cursor, Write};
+
+	use chrono::DateTime;
+	use zip::{write::FileOptions, ZipWriter};
+
+	#[test]
+	fn skips_oversized_entries() {
+		let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+		writer
+			.start_file(ODF_META_PATH, FileOptions::default())
+			.unwrap();
+		writer.write_all(b"<office:document-meta/>").unwrap();
+		writer
+			.start_file(OOXML_CORE_PATH, FileOptions::default())
+			.unwrap();
+		writer
+			.write_all(&vec![b' '; usize::try_from(MAX_ENTRY_SIZE).unwrap() + 1])
+			.unwrap();
+
+		let mut archive = ZipArchive::new(writer.finish().unwrap()).unwrap();
+		let read = |archive: &mut ZipArchive<_>, name| {
+			read_entry(archive, name, Path::new("document.zip")).unwrap()
+		};
+
+		assert_eq!(
+			read(&mut archive, ODF_META_PATH).as_deref(),
+			Some("<office:document-meta/>")
+		);
+		assert_eq!(read(&mut archive, OOXML_CORE_PATH), None);
+	}
+
+	#[test]
+	fn reads_ooxml_properties() {
+		let core = r#"<cp:coreProperties
+			xmlns:cp="http://schemas.openxmlformats.org/package/2006/metadata/core-properties"
+			xmlns:dc="http://purl.org/dc/elements/1.1/"
+			xmlns:dcterms="http://purl.org/dc/terms/">
+			<dc:title>Lease Agreement</dc:title>
+			<dc:creator>Jane Doe</dc:creator>
+			<cp:lastModifiedBy>John Doe</cp:lastModifiedBy>
+			<dcterms:created>2023-04-05T10:20:30Z</dcterms:created>
+		</cp:coreProperties>"#;
+		let app = r#"<Properties
+			xmlns="http://schemas.openxmlformats.org/officeDocument/2006/extended-properties">
+			<Pages>12</Pages>
+		</Properties>"#;
+
+		assert_eq!(
+			from_ooxml(core, Some(app)).ok(),
+			Some(DocumentMetadata {
+				title: Some("Lease Agreement".to_string()),
+				author: Some("Jane Doe".to_string()),
+				subject: None,
+				page_count: Some(12),
+				date_created: DateTime::parse_from_rfc3339("2023-04-05T10:20:30Z").ok(),
+			})
+		);
+	}
+
+	#[test]
+	fn reads_odf_meta() {
+		let meta = r#"<office:document-meta
+			xmlns:office="urn:oasis:names:tc:opendocument:xmlns:office:1.0"
+			xmlns:meta="urn:oasis:names:tc:opendocument:xmlns:meta:1.0"
+			xmlns:dc="http://purl.org/dc/elements/1.1/">
+			<office:meta>
+				<dc:title>  </dc:title>
+				<meta:initial-creator>Jane Doe</meta:initial-creator>
+				<dc:creator>John Doe</dc:creator>
+				<meta:creation-date>2023-04-05T10:20:30.5</meta:creation-date>
+				<meta:document-statistic meta:page-count="3" meta:word-count="420"/>
+			</office:meta>
+		</office:document-meta>"#;
+
+		assert_eq!(
+			from_odf(meta).ok(),
+			Some(DocumentMetadata {
+				title: None,
+				author: Some("Jane Doe".to_string()),
+				subject: None,
+				page_count: Some(3),
+				date_created: DateTime::parse_from_rfc3339("2023-04-05T10:20:30.5Z").ok(),
+			})
+		);
+	}
+
+	#[test]
+	fn reads_epub_package_metadata() {
+		let package = r#"<package xmlns="http://www.idpf.org/2007/opf">
+			<metadata xmlns:dc="http://purl.org/dc/elements/1.1/">
+				<dc:title>Moby Dick</dc:title>
+				<dc:creator>Herman Melville</dc:creator>
+				<dc:subject>Whaling</dc:subject>
+				<dc:date>1851</dc:date>
+			</metadata>
+		</package>"#;
+
+		assert_eq!(
+			from_epub_package(package).ok(),
+			Some(DocumentMetadata {
+				title: Some("Moby Dick".to_string()),
+				author: Some("Herman Melville".to_string()),
+				subject: Some("Whaling".to_string()),
+				page_count: None,
+				date_created: DateTime::parse_from_rfc3339("1851-01-01T00:00:00Z").ok(),
+			})
+		);
+	}
+}
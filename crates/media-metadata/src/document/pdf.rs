@@ -0,0 +1,102 @@
+use crate::Result;
+
+use std::path::Path;
+
+use chrono::{DateTime, FixedOffset, TimeZone};
+use pdfium_render::prelude::PdfDocumentMetadataTagType;
+
+use super::{text, DocumentMetadata};
+
+pub(super) fn extract(path: &Path) -> Result<Option<DocumentMetadata>> {
+	let pdfium = sd_images::load_pdfium()?;
+	let document = pdfium.load_pdf_from_file(path, None)?;
+
+	let metadata = document.metadata();
+	let tag = |tag_type| metadata.get(tag_type).and_then(|tag| text(tag.value()));
+
+	Ok(Some(DocumentMetadata {
+		title: tag(PdfDocumentMetadataTagType::Title),
+		author: tag(PdfDocumentMetadataTagType::Author),
+		subject: tag(PdfDocumentMetadataTagType::Subject),
+		page_count: Some(u32::from(document.pages().len())),
+		date_created: tag(PdfDocumentMetadataTagType::CreationDate)
+			.as_deref()
+			.and_then(parse_pdf_date),
+	}))
+}
+
+/// PDF dates are `D:YYYYMMDDHHmmSSOHH'mm'`, where everything after the year is optional and `O`
+/// is the relationship to UTC, being `+`, `-` or `Z`
+fn parse_pdf_date(date: &str) -> Option<DateTime<FixedOffset>> {
+	let date = date.trim();
+	let date = date.strip_prefix("D:").unwrap_or(date);
+
+	let (digits, offset) = date.split_at(
+		date.find(|c: char| !c.is_ascii_digit())
+			.unwrap_or(date.len()),
+	);
+
+	let field = |start: usize, default: u32| {
+		digits
+			.get(start..start + 2)
+			.map_or(Some(default), |field| field.parse().ok())
+	};
+
+	let offset_seconds = match offset.chars().next() {
+		Some(sign @ ('+' | '-')) => {
+			let (hours, minutes) = offset[1..].split_once('\'').unwrap_or((&offset[1..], ""));
+			let hours = hours.parse::<i32>().ok().filter(|hours| *hours <= 23)?;
+			let minutes = minutes
+				.trim_end_matches('\'')
+				.parse::<i32>()
+				.map_or(Some(0), |minutes| (minutes <= 59).then_some(minutes))?;
+
+			(hours * 3600 + minutes * 60) * if sign == '-' { -1 } else { 1 }
+		}
+		_ => 0,
+	};
+
+	FixedOffset::east_opt(offset_seconds)?
+		.with_ymd_and_hms(
+			digits.get(0..4)?.parse().ok()?,
+			field(4, 1)?,
+			field(6, 1)?,
+			field(8, 0)?,
+			field(10, 0)?,
+			field(12, 0)?,
+		)
+		.single()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn parses_pdf_dates() {
+		let date = |s: &str| parse_pdf_date(s).map(|date| date.to_rfc3339());
+
+		assert_eq!(
+			date("D:20230405102030+02'00'").as_deref(),
+			Some("2023-04-05T10:20:30+02:00")
+		);
+		assert_eq!(
+			date("D:20230405102030-03'30").as_deref(),
+			Some("2023-04-05T10:20:30-03:30")
+		);
+		assert_eq!(
+			date("D:20230405102030Z").as_deref(),
+			Some("2023-04-05T10:20:30+00:00")
+		);
+		assert_eq!(date("D:2023").as_deref(), Some("2023-01-01T00:00:00+00:00"));
+		assert_eq!(
+			date("202304051020").as_deref(),
+			Some("2023-04-05T10:20:00+00:00")
+		);
+		assert_eq!(date("D:20231405"), None);
+		// Offsets which would overflow, or just aren't valid ones
+		assert_eq!(date("D:20230405102030+99999999'00'"), None);
+		assert_eq!(date("D:20230405102030+02'75'"), None);
+		assert_eq!(date("D:"), None);
+	}
+}
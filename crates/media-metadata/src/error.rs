@@ -4,6 +4,16 @@ use sd_utils::error::FileIOError;
 pub enum Error {
 	#[error("error from the exif crate: {0}")]
 	Exif(#[from] exif::Error),
+	#[error("error from the lofty crate: {0}")]
+	Audio(#[from] lofty::error::LoftyError),
+	#[error("error from the images crate: {0}")]
+	Images(#[from] sd_images::Error),
+	#[error("error from pdfium: {0}")]
+	Pdf(#[from] pdfium_render::prelude::PdfiumError),
+	#[error("error while reading the document archive: {0}")]
+	Zip(#[from] zip::result::ZipError),
+	#[error("error while parsing the document metadata: {0}")]
+	Xml(#[from] roxmltree::Error),
 	#[cfg(feature = "ffmpeg")]
 	#[error("error from the ffmpeg crate: {0}")]
 	FFmpeg(#[from] sd_ffmpeg::Error),
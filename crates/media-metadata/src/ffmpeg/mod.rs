@@ -11,6 +11,7 @@ pub mod codec;
 pub mod metadata;
 pub mod program;
 pub mod stream;
+pub mod subtitle;
 pub mod subtitle_props;
 pub mod video_props;
 
@@ -52,7 +53,8 @@ mod extract_data {
 
 	use sd_ffmpeg::model::{
 		FFmpegAudioProps, FFmpegChapter, FFmpegCodec, FFmpegMediaData, FFmpegMetadata,
-		FFmpegProgram, FFmpegProps, FFmpegStream, FFmpegSubtitleProps, FFmpegVideoProps,
+		FFmpegProgram, FFmpegProps, FFmpegStream, FFmpegSubtitleCue, FFmpegSubtitleProps,
+		FFmpegSubtitleTrack, FFmpegVideoProps,
 	};
 
 	impl From<FFmpegMediaData> for super::FFmpegMetadata {
@@ -250,6 +252,40 @@ mod extract_data {
 		}
 	}
 
+	impl From<FFmpegSubtitleTrack> for super::subtitle::SubtitleTrack {
+		fn from(
+			FFmpegSubtitleTrack {
+				stream_id,
+				language,
+				title,
+				cues,
+			}: FFmpegSubtitleTrack,
+		) -> Self {
+			Self {
+				stream_id: Some(stream_id),
+				language,
+				title,
+				cues: cues.into_iter().map(Into::into).collect(),
+			}
+		}
+	}
+
+	impl From<FFmpegSubtitleCue> for super::subtitle::SubtitleCue {
+		fn from(
+			FFmpegSubtitleCue {
+				start_ms,
+				end_ms,
+				text,
+			}: FFmpegSubtitleCue,
+		) -> Self {
+			Self {
+				start_ms,
+				end_ms,
+				text,
+			}
+		}
+	}
+
 	impl From<FFmpegVideoProps> for super::video_props::VideoProps {
 		fn from(
 			FFmpegVideoProps {
@@ -0,0 +1,191 @@
+use crate::Result;
+
+use std::{fmt::Write, path::Path};
+
+use serde::{Deserialize, Serialize};
+use specta::Type;
+
+use super::chapter::Chapter;
+
+/// A text subtitle track, either embedded in a media file or from a sidecar subtitle file
+#[derive(Debug, Serialize, Deserialize, Type)]
+pub struct SubtitleTrack {
+	/// `None` for sidecar subtitle files
+	pub stream_id: Option<i32>,
+	pub language: Option<String>,
+	pub title: Option<String>,
+	pub cues: Vec<SubtitleCue>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Type)]
+pub struct SubtitleCue {
+	pub start_ms: i64,
+	pub end_ms: i64,
+	pub text: String,
+}
+
+impl SubtitleTrack {
+	/// Every text subtitle track of a media file. Sidecar subtitle files have a single one.
+	pub async fn from_path(path: impl AsRef<Path> + Send) -> Result<Vec<Self>> {
+		#[cfg(not(feature = "ffmpeg"))]
+		{
+			let _ = path;
+			Err(crate::Error::NoFFmpeg)
+		}
+
+		#[cfg(feature = "ffmpeg")]
+		{
+			sd_ffmpeg::extract_subtitles(path)
+				.await
+				.map(|tracks| tracks.into_iter().map(Into::into).collect())
+				.map_err(Into::into)
+		}
+	}
+
+	/// Every line of dialogue, for full text search
+	#[must_use]
+	pub fn text(&self) -> String {
+		self.cues
+			.iter()
+			.map(|cue| cue.text.as_str())
+			.collect::<Vec<_>>()
+			.join("\n")
+	}
+}
+
+fn write_timestamp(vtt: &mut String, ms: i64) {
+	let ms = ms.max(0);
+	let _ = write!(
+		vtt,
+		"{:02}:{:02}:{:02}.{:03}",
+		ms / 3_600_000,
+		ms / 60_000 % 60,
+		ms / 1000 % 60,
+		ms % 1000
+	);
+}
+
+fn write_cue(vtt: &mut String, start_ms: i64, end_ms: i64, text: &str) {
+	vtt.push('\n');
+	write_timestamp(vtt, start_ms);
+	vtt.push_str(" --> ");
+	write_timestamp(vtt, end_ms);
+	vtt.push('\n');
+
+	for line in text.lines().filter(|line| !line.trim().is_empty()) {
+		for c in line.chars() {
+			match c {
+				'&' => vtt.push_str("&amp;"),
+				'<' => vtt.push_str("&lt;"),
+				'>' => vtt.push_str("&gt;"),
+				_ => vtt.push(c),
+			}
+		}
+		vtt.push('\n');
+	}
+}
+
+/// Builds a `WebVTT` file out of subtitle cues
+#[must_use]
+pub fn cues_to_webvtt(cues: &[SubtitleCue]) -> String {
+	let mut vtt = String::from("WEBVTT\n");
+
+	for cue in cues {
+		write_cue(&mut vtt, cue.start_ms, cue.end_ms, &cue.text);
+	}
+
+	vtt
+}
+
+/// Builds a `WebVTT` chapters track, chapters without a title are numbered
+#[must_use]
+pub fn chapters_to_webvtt(chapters: &[Chapter]) -> String {
+	let mut vtt = String::from("WEBVTT\n");
+
+	for (i, chapter) in chapters.iter().enumerate() {
+		let title = chapter
+			.metadata
+			.title
+			.clone()
+			.unwrap_or_else(|| format!("Chapter {}", i + 1));
+
+		write_cue(
+			&mut vtt,
+			chapter_time_to_ms(chapter.start, chapter),
+			chapter_time_to_ms(chapter.end, chapter),
+			&title,
+		);
+	}
+
+	vtt
+}
+
+/// Chapter times are split in (high, low) parts and in the chapter's time base
+fn chapter_time_to_ms((high, low): (i32, u32), chapter: &Chapter) -> i64 {
+	let time = (i64::from(high) << 32) | i64::from(low);
+
+	if chapter.time_base_den == 0 {
+		return 0;
+	}
+
+	// i128 so multiplying before dividing can't overflow
+	let ms = i128::from(time) * i128::from(chapter.time_base_num) * 1000
+		/ i128::from(chapter.time_base_den);
+
+	i64::try_from(ms).unwrap_or(i64::MAX)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	use crate::ffmpeg::metadata::Metadata;
+
+	#[test]
+	fn builds_webvtt() {
+		let cues = [
+			SubtitleCue {
+				start_ms: 1_500,
+				end_ms: 3_661_001,
+				text: "Tom & Jerry\n<laughs>".to_string(),
+			},
+			SubtitleCue {
+				start_ms: -20,
+				end_ms: 10,
+				text: "Hi".to_string(),
+			},
+		];
+
+		assert_eq!(
+			cues_to_webvtt(&cues),
+			"WEBVTT\n\
+			\n00:00:01.500 --> 01:01:01.001\nTom &amp; Jerry\n&lt;laughs&gt;\n\
+			\n00:00:00.000 --> 00:00:00.010\nHi\n"
+		);
+	}
+
+	#[test]
+	fn builds_chapters_webvtt() {
+		let chapter = |id, start, end, title: Option<&str>| Chapter {
+			id,
+			start: (0, start),
+			end: (0, end),
+			time_base_den: 1000,
+			time_base_num: 1,
+			metadata: Metadata {
+				title: title.map(ToString::to_string),
+				..Default::default()
+			},
+		};
+
+		assert_eq!(
+			chapters_to_webvtt(&[
+				chapter(0, 0, 60_000, Some("Intro")),
+				chapter(1, 60_000, 90_500, None)
+			]),
+			"WEBVTT\n\
+			\n00:00:00.000 --> 00:01:00.000\nIntro\n\
+			\n00:01:00.000 --> 00:01:30.500\nChapter 2\n"
+		);
+	}
+}
@@ -29,10 +29,14 @@
 #![forbid(unsafe_code)]
 #![allow(clippy::missing_errors_doc, clippy::module_name_repetitions)]
 
+pub mod audio;
+pub mod document;
 mod error;
 pub mod exif;
 pub mod ffmpeg;
 
+pub use audio::AudioMetadata;
+pub use document::DocumentMetadata;
 pub use error::{Error, Result};
 pub use exif::ExifMetadata;
 pub use ffmpeg::FFmpegMetadata;
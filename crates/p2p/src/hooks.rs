@@ -4,6 +4,8 @@
 
 mod mdns;
 mod quic;
+mod rendezvous;
 
 pub use mdns::Mdns;
 pub use quic::{Libp2pPeerId, QuicHandle, QuicTransport, RelayServerEntry};
+pub use rendezvous::Rendezvous;
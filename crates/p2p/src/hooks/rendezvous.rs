@@ -0,0 +1,483 @@
+//! Discovery through a shared "rendezvous" directory.
+//!
+//! Every node publishes a signed record of its addresses into the directory and reads the records of the other nodes.
+//! This works anywhere a directory can be shared between machines (like a network share), even on networks where
+//! multicast, and therefore mDNS, is blocked.
+
+use std::{
+	collections::{HashMap, HashSet},
+	net::SocketAddr,
+	path::{Path, PathBuf},
+	sync::Arc,
+	time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use base64::{engine::general_purpose, Engine};
+use flume::{bounded, Receiver};
+use serde::{Deserialize, Serialize};
+use tokio::{fs, time::interval};
+use tracing::{debug, trace, warn};
+
+use crate::{
+	HookEvent, HookId, Identity, PeerConnectionCandidate, RemoteIdentity, ShutdownGuard, P2P,
+};
+
+/// The time between republishing our record and reading the records of the other nodes.
+const REFRESH_INTERVAL: Duration = Duration::from_secs(30);
+/// How long a record is valid for after it was published.
+const RECORD_TTL: Duration = Duration::from_secs(5 * 60);
+/// Records which expired this long ago are deleted, so the directory doesn't fill up with nodes which are gone for good.
+const RECORD_CLEANUP_AFTER: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+const RECORD_EXTENSION: &str = "json";
+
+/// Discovery of peers through a directory shared between them.
+#[derive(Debug)]
+pub struct Rendezvous {
+	p2p: Arc<P2P>,
+	hook_id: HookId,
+	dir: PathBuf,
+}
+
+impl Rendezvous {
+	pub fn spawn(p2p: Arc<P2P>, dir: PathBuf) -> Self {
+		let (tx, rx) = bounded(15);
+		let hook_id = p2p.register_hook("rendezvous", tx);
+
+		start(p2p.clone(), hook_id, dir.clone(), rx);
+
+		Self { p2p, hook_id, dir }
+	}
+
+	pub fn dir(&self) -> &Path {
+		&self.dir
+	}
+
+	pub async fn shutdown(self) {
+		self.p2p.unregister_hook(self.hook_id).await;
+	}
+}
+
+/// The addresses a node can be reached at, as published in the rendezvous directory.
+#[derive(Debug, Serialize, Deserialize)]
+struct Record {
+	identity: RemoteIdentity,
+	app_name: String,
+	addrs: Vec<SocketAddr>,
+	metadata: HashMap<String, String>,
+	/// Seconds since the Unix epoch
+	published_at: u64,
+}
+
+/// A [`Record`] signed with the identity of the node it's for, so it can't be forged by others with write access to
+/// the directory. `record` is kept serialized so the signature can be checked against the exact bytes that were signed.
+#[derive(Debug, Serialize, Deserialize)]
+struct SignedRecord {
+	record: String,
+	signature: String,
+}
+
+enum RecordState {
+	Valid(Box<Record>),
+	Expired { for_longer_than_cleanup: bool },
+	Invalid,
+}
+
+struct State {
+	hook_id: HookId,
+	p2p: Arc<P2P>,
+	dir: PathBuf,
+	record_path: PathBuf,
+	/// The peers currently discovered through the directory, so we know who to expire
+	discovered: HashSet<RemoteIdentity>,
+}
+
+fn start(p2p: Arc<P2P>, hook_id: HookId, dir: PathBuf, rx: Receiver<HookEvent>) {
+	let mut state = State {
+		hook_id,
+		record_path: record_path(&dir, p2p.remote_identity()),
+		dir,
+		p2p,
+		discovered: HashSet::new(),
+	};
+
+	tokio::spawn(async move {
+		let mut refresh = interval(REFRESH_INTERVAL);
+
+		loop {
+			tokio::select! {
+				Ok(event) = rx.recv_async() => match event {
+					HookEvent::MetadataModified | HookEvent::ListenerRegistered(_) | HookEvent::ListenerAddrAdded(_, _) | HookEvent::ListenerAddrRemoved(_, _) | HookEvent::ListenerUnregistered(_)  => publish(&state).await,
+					HookEvent::Shutdown { _guard } => {
+						shutdown(_guard, &state).await;
+						break;
+					},
+					_ => continue,
+				},
+				_ = refresh.tick() => {
+					publish(&state).await;
+					scan(&mut state).await;
+				}
+			};
+		}
+	});
+}
+
+fn record_path(dir: &Path, identity: RemoteIdentity) -> PathBuf {
+	// The regular encoding of identities contains `/`
+	dir.join(general_purpose::URL_SAFE_NO_PAD.encode(identity.get_bytes()))
+		.with_extension(RECORD_EXTENSION)
+}
+
+fn now() -> u64 {
+	SystemTime::now()
+		.duration_since(UNIX_EPOCH)
+		.map(|since_epoch| since_epoch.as_secs())
+		.unwrap_or_default()
+}
+
+async fn publish(state: &State) {
+	let record = Record {
+		identity: state.p2p.remote_identity(),
+		app_name: state.p2p.app_name().to_string(),
+		addrs: state
+			.p2p
+			.listeners()
+			.iter()
+			.flat_map(|listener| listener.addrs.clone())
+			.filter(|addr| !addr.ip().is_unspecified())
+			.collect(),
+		metadata: state.p2p.metadata().clone(),
+		published_at: now(),
+	};
+
+	let signed = match sign_record(state.p2p.identity(), &record) {
+		Ok(signed) => signed,
+		Err(err) => {
+			warn!("error serializing rendezvous record: {err}");
+			return;
+		}
+	};
+
+	// Written to a temporary file first, so other nodes never read a partially written record
+	let tmp_path = state.record_path.with_extension("tmp");
+	let result = async {
+		fs::create_dir_all(&state.dir).await?;
+		fs::write(&tmp_path, signed).await?;
+		fs::rename(&tmp_path, &state.record_path).await
+	}
+	.await;
+
+	match result {
+		Ok(()) => trace!(
+			"published rendezvous record to '{}'",
+			state.record_path.display()
+		),
+		Err(err) => warn!(
+			"error publishing rendezvous record to '{}': {err}",
+			state.record_path.display()
+		),
+	}
+}
+
+fn sign_record(identity: &Identity, record: &Record) -> Result<Vec<u8>, serde_json::Error> {
+	let record = serde_json::to_string(record)?;
+
+	serde_json::to_vec(&SignedRecord {
+		signature: general_purpose::STANDARD_NO_PAD.encode(identity.sign(record.as_bytes())),
+		record,
+	})
+}
+
+fn verify_record(state: &State, bytes: &[u8], now: u64) -> RecordState {
+	let Ok(signed) = serde_json::from_slice::<SignedRecord>(bytes) else {
+		return RecordState::Invalid;
+	};
+	let Ok(record) = serde_json::from_str::<Record>(&signed.record) else {
+		return RecordState::Invalid;
+	};
+	let Ok(signature) = general_purpose::STANDARD_NO_PAD.decode(&signed.signature) else {
+		return RecordState::Invalid;
+	};
+
+	if !record.identity.verify(signed.record.as_bytes(), &signature)
+		|| record.app_name != state.p2p.app_name()
+	{
+		return RecordState::Invalid;
+	}
+
+	let expires_at = record.published_at.saturating_add(RECORD_TTL.as_secs());
+	if expires_at < now {
+		return RecordState::Expired {
+			for_longer_than_cleanup: expires_at.saturating_add(RECORD_CLEANUP_AFTER.as_secs())
+				< now,
+		};
+	}
+
+	// A record from the future is from a node with a skewed clock, it could stay valid for too long
+	if record.published_at > now.saturating_add(RECORD_TTL.as_secs()) {
+		return RecordState::Invalid;
+	}
+
+	RecordState::Valid(Box::new(record))
+}
+
+async fn scan(state: &mut State) {
+	let mut entries = match fs::read_dir(&state.dir).await {
+		Ok(entries) => entries,
+		Err(err) => {
+			warn!(
+				"error reading rendezvous directory '{}': {err}",
+				state.dir.display()
+			);
+			return;
+		}
+	};
+
+	let now = now();
+	let mut discovered = HashSet::new();
+	loop {
+		let path = match entries.next_entry().await {
+			Ok(Some(entry)) => entry.path(),
+			Ok(None) => break,
+			Err(err) => {
+				warn!(
+					"error reading rendezvous directory '{}': {err}",
+					state.dir.display()
+				);
+				break;
+			}
+		};
+
+		if path == state.record_path
+			|| path.extension().and_then(|ext| ext.to_str()) != Some(RECORD_EXTENSION)
+		{
+			continue;
+		}
+
+		let Ok(bytes) = fs::read(&path).await else {
+			continue;
+		};
+
+		match verify_record(state, &bytes, now) {
+			RecordState::Valid(record) => {
+				// A copy of our own record, or a record copied under the file name of another node
+				if record.identity == state.p2p.remote_identity()
+					|| record_path(&state.dir, record.identity) != path
+				{
+					continue;
+				}
+
+				discovered.insert(record.identity);
+				state.p2p.clone().discover_peer(
+					state.hook_id,
+					record.identity,
+					record.metadata,
+					record
+						.addrs
+						.into_iter()
+						.map(PeerConnectionCandidate::SocketAddr)
+						.collect(),
+				);
+			}
+			RecordState::Expired {
+				for_longer_than_cleanup: true,
+			} => {
+				debug!(
+					"removing long expired rendezvous record '{}'",
+					path.display()
+				);
+				fs::remove_file(&path).await.ok();
+			}
+			RecordState::Expired { .. } => {}
+			RecordState::Invalid => {
+				debug!("ignoring invalid rendezvous record '{}'", path.display());
+			}
+		}
+	}
+
+	for identity in state.discovered.difference(&discovered) {
+		// Cloned out so the lock isn't held, as undiscovering can remove the peer
+		let peer = state.p2p.peers().get(identity).cloned();
+		if let Some(peer) = peer {
+			peer.undiscover_peer(state.hook_id);
+		}
+	}
+	state.discovered = discovered;
+}
+
+async fn shutdown(_guard: ShutdownGuard, state: &State) {
+	if let Err(err) = fs::remove_file(&state.record_path).await {
+		warn!(
+			"error removing rendezvous record '{}': {err}",
+			state.record_path.display()
+		);
+	}
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+	use super::*;
+
+	const APP_NAME: &str = "rendezvous";
+
+	fn test_state(dir: PathBuf) -> State {
+		let p2p = P2P::new(APP_NAME, Identity::new(), bounded(1).0);
+
+		State {
+			hook_id: p2p.register_hook("rendezvous", bounded(15).0),
+			record_path: record_path(&dir, p2p.remote_identity()),
+			dir,
+			p2p,
+			discovered: HashSet::new(),
+		}
+	}
+
+	fn record(identity: &Identity, published_at: u64) -> Record {
+		Record {
+			identity: identity.to_remote_identity(),
+			app_name: APP_NAME.to_string(),
+			addrs: vec!["192.168.1.2:7373".parse().unwrap()],
+			metadata: HashMap::new(),
+			published_at,
+		}
+	}
+
+	fn signed_record(identity: &Identity, published_at: u64) -> Vec<u8> {
+		sign_record(identity, &record(identity, published_at)).unwrap()
+	}
+
+	#[test]
+	fn forged_records_are_invalid() {
+		let state = test_state(PathBuf::new());
+		let (node, forger) = (Identity::new(), Identity::new());
+		let now = now();
+
+		// Signed by another node than the one it's for
+		let record = serde_json::to_string(&record(&node, now)).unwrap();
+		let forged = SignedRecord {
+			signature: general_purpose::STANDARD_NO_PAD.encode(forger.sign(record.as_bytes())),
+			record,
+		};
+		assert!(matches!(
+			verify_record(&state, &serde_json::to_vec(&forged).unwrap(), now),
+			RecordState::Invalid
+		));
+
+		// Changed after being signed
+		let mut tampered =
+			serde_json::from_slice::<SignedRecord>(&signed_record(&node, now)).unwrap();
+		tampered.record = tampered.record.replace("192.168.1.2", "192.168.1.3");
+		assert!(matches!(
+			verify_record(&state, &serde_json::to_vec(&tampered).unwrap(), now),
+			RecordState::Invalid
+		));
+
+		assert!(matches!(
+			verify_record(&state, &signed_record(&node, now), now),
+			RecordState::Valid(record) if record.identity == node.to_remote_identity()
+		));
+	}
+
+	#[test]
+	fn records_expire() {
+		let state = test_state(PathBuf::new());
+		let node = Identity::new();
+		let now = now();
+		let ttl = RECORD_TTL.as_secs();
+
+		assert!(matches!(
+			verify_record(&state, &signed_record(&node, now - ttl), now),
+			RecordState::Valid(_)
+		));
+		assert!(matches!(
+			verify_record(&state, &signed_record(&node, now - ttl - 1), now),
+			RecordState::Expired {
+				for_longer_than_cleanup: false
+			}
+		));
+		assert!(matches!(
+			verify_record(
+				&state,
+				&signed_record(&node, now - ttl - RECORD_CLEANUP_AFTER.as_secs() - 1),
+				now
+			),
+			RecordState::Expired {
+				for_longer_than_cleanup: true
+			}
+		));
+	}
+
+	#[test]
+	fn future_dated_records_are_invalid() {
+		let state = test_state(PathBuf::new());
+		let node = Identity::new();
+		let now = now();
+		let ttl = RECORD_TTL.as_secs();
+
+		// A slightly skewed clock is fine
+		assert!(matches!(
+			verify_record(&state, &signed_record(&node, now + ttl), now),
+			RecordState::Valid(_)
+		));
+		assert!(matches!(
+			verify_record(&state, &signed_record(&node, now + ttl + 1), now),
+			RecordState::Invalid
+		));
+	}
+
+	#[tokio::test]
+	async fn scan_only_discovers_records_under_their_own_name() {
+		let dir = std::env::temp_dir().join(format!("sd-rendezvous-{}", uuid::Uuid::new_v4()));
+		fs::create_dir_all(&dir).await.unwrap();
+
+		let mut state = test_state(dir.clone());
+		let (node, other_node, copied_node, gone_node) = (
+			Identity::new(),
+			Identity::new(),
+			Identity::new(),
+			Identity::new(),
+		);
+		let now = now();
+
+		fs::write(
+			record_path(&dir, node.to_remote_identity()),
+			signed_record(&node, now),
+		)
+		.await
+		.unwrap();
+		// A valid record, but copied under the file name of another node
+		fs::write(
+			record_path(&dir, other_node.to_remote_identity()),
+			signed_record(&copied_node, now),
+		)
+		.await
+		.unwrap();
+		// Our own record is never a peer
+		fs::write(&state.record_path, signed_record(state.p2p.identity(), now))
+			.await
+			.unwrap();
+		let gone_record_path = record_path(&dir, gone_node.to_remote_identity());
+		fs::write(
+			&gone_record_path,
+			signed_record(
+				&gone_node,
+				now - RECORD_TTL.as_secs() - RECORD_CLEANUP_AFTER.as_secs() - 1,
+			),
+		)
+		.await
+		.unwrap();
+
+		scan(&mut state).await;
+
+		assert_eq!(state.discovered, HashSet::from([node.to_remote_identity()]));
+		assert!(state.p2p.peers().contains_key(&node.to_remote_identity()));
+		assert!(!state
+			.p2p
+			.peers()
+			.contains_key(&copied_node.to_remote_identity()));
+		assert!(fs::metadata(&gone_record_path).await.is_err());
+
+		fs::remove_dir_all(&dir).await.unwrap();
+	}
+}
@@ -6,7 +6,7 @@ use std::{
 };
 
 use base64::{engine::general_purpose, Engine};
-use ed25519_dalek::{VerifyingKey, SECRET_KEY_LENGTH};
+use ed25519_dalek::{Signature, Signer, Verifier, VerifyingKey, SECRET_KEY_LENGTH};
 use rand_core::OsRng;
 use serde::{Deserialize, Serialize};
 use specta::Type;
@@ -63,6 +63,12 @@ impl Identity {
 	pub fn to_remote_identity(&self) -> RemoteIdentity {
 		RemoteIdentity(self.0.verifying_key())
 	}
+
+	/// Sign a message so anyone with the [`RemoteIdentity`] can check it came from us.
+	#[must_use]
+	pub fn sign(&self, message: &[u8]) -> Vec<u8> {
+		self.0.sign(message).to_bytes().to_vec()
+	}
 }
 
 #[derive(Copy, Clone, PartialEq, Eq, Type)]
@@ -163,6 +169,14 @@ impl RemoteIdentity {
 	pub fn verifying_key(&self) -> VerifyingKey {
 		self.0
 	}
+
+	/// Check a signature made with [`Identity::sign`] by the owner of this identity.
+	#[must_use]
+	pub fn verify(&self, message: &[u8], signature: &[u8]) -> bool {
+		Signature::from_slice(signature)
+			.map(|signature| self.0.verify(message, &signature).is_ok())
+			.unwrap_or(false)
+	}
 }
 
 impl From<ed25519_dalek::SigningKey> for Identity {
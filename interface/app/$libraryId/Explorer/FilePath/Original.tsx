@@ -8,7 +8,7 @@ import {
 	useState,
 	type VideoHTMLAttributes
 } from 'react';
-import { getItemFilePath, useLibraryContext } from '@sd/client';
+import { getItemFilePath, useLibraryContext, useLibraryQuery } from '@sd/client';
 import i18n from '~/app/I18n';
 import { PDFViewer, TextViewer } from '~/components';
 import { useLocale } from '~/hooks';
@@ -36,6 +36,7 @@ interface OriginalRendererProps {
 	pauseVideo?: boolean;
 	blackBars?: boolean;
 	blackBarsSize?: number;
+	tracks?: MediaTrack[];
 	onLoad?(): void;
 	onError?(e: ErrorEvent | SyntheticEvent<Element, Event>): void;
 }
@@ -50,6 +51,9 @@ export function Original({
 	const [error, setError] = useState(false);
 	if (error) throw new Error('onError');
 
+	// Set when the webview can't play the original media, so we play it transcoded instead
+	const [transcode, setTranscode] = useState(false);
+
 	const Renderer = useMemo(() => {
 		const kind = originalRendererKind(itemData);
 		return ORIGINAL_RENDERERS[kind];
@@ -61,20 +65,100 @@ export function Original({
 	const { library } = useLibraryContext();
 	const { parent } = useExplorerContext();
 
-	const src = useMemo(() => {
-		const locationId =
-			itemData.locationId ?? (parent?.type === 'Location' ? parent.location.id : null);
+	const locationId =
+		itemData.locationId ?? (parent?.type === 'Location' ? parent.location.id : null);
 
+	const { src, transcodeSrc } = useMemo(() => {
 		if (filePath && (itemData.extension !== 'pdf' || pdfViewerEnabled())) {
 			if ('id' in filePath && locationId)
-				return platform.getFileUrl(library.uuid, locationId, filePath.id);
-			else if ('path' in filePath) return platform.getFileUrlByPath(filePath.path);
+				return {
+					src: platform.getFileUrl(library.uuid, locationId, filePath.id),
+					transcodeSrc:
+						(itemData.kind === 'Video' || itemData.kind === 'Audio') && canPlayHls()
+							? platform.getTranscodeUrl(library.uuid, locationId, filePath.id)
+							: undefined
+				};
+			else if ('path' in filePath) return { src: platform.getFileUrlByPath(filePath.path) };
 		}
-	}, [itemData, filePath, library.uuid, parent, platform]);
+
+		return {};
+	}, [itemData, filePath, locationId, library.uuid, platform]);
+
+	const tracks = useMediaTracks(itemData, filePath, locationId, props.mediaControls ?? false);
 
 	if (src === undefined) throw new Error('no src!');
 
-	return <Renderer src={src} itemData={itemData} onError={() => setError(true)} {...props} />;
+	return (
+		<Renderer
+			src={transcode && transcodeSrc ? transcodeSrc : src}
+			itemData={itemData}
+			onError={() => (transcodeSrc && !transcode ? setTranscode(true) : setError(true))}
+			tracks={tracks}
+			{...props}
+		/>
+	);
+}
+
+interface MediaTrack {
+	src: string;
+	kind: 'subtitles' | 'chapters';
+	label: string;
+	srcLang?: string;
+}
+
+// Subtitles and chapters of a video, only fetched when its controls are shown
+function useMediaTracks(
+	itemData: ExplorerItemData,
+	filePath: ReturnType<typeof getItemFilePath>,
+	locationId: number | null,
+	enabled: boolean
+) {
+	const platform = usePlatform();
+	const { library } = useLibraryContext();
+
+	const filePathId = filePath && 'id' in filePath ? filePath.id : null;
+	const objectId = filePath && 'object_id' in filePath ? filePath.object_id : null;
+	const hasTracks =
+		enabled &&
+		itemData.kind === 'Video' &&
+		filePathId !== null &&
+		locationId !== null &&
+		objectId !== null;
+
+	const subtitles = useLibraryQuery(['files.getSubtitles', objectId ?? -1], {
+		enabled: hasTracks
+	});
+	const mediaData = useLibraryQuery(['files.getMediaData', objectId ?? -1], {
+		enabled: hasTracks
+	});
+
+	return useMemo(() => {
+		if (!hasTracks || filePathId === null || locationId === null) return [];
+
+		const tracks: MediaTrack[] = (subtitles.data ?? []).map((subtitle, i) => ({
+			src: platform.getMediaTrackUrl(library.uuid, locationId, filePathId, `${subtitle.id}.vtt`),
+			kind: 'subtitles',
+			label:
+				subtitle.title ??
+				subtitle.language ??
+				subtitle.sidecar ??
+				`${i18n.t('subtitles')} ${i + 1}`,
+			srcLang: subtitle.language ?? undefined
+		}));
+
+		if (mediaData.data && 'FFmpeg' in mediaData.data && mediaData.data.FFmpeg.chapters.length > 0)
+			tracks.push({
+				src: platform.getMediaTrackUrl(library.uuid, locationId, filePathId, 'chapters.vtt'),
+				kind: 'chapters',
+				label: i18n.t('chapters')
+			});
+
+		return tracks;
+	}, [hasTracks, filePathId, locationId, subtitles.data, mediaData.data, library.uuid, platform]);
+}
+
+function canPlayHls() {
+	return document.createElement('video').canPlayType('application/vnd.apple.mpegurl') !== '';
 }
 
 const TEXT_RENDERER: OriginalRenderer = (props) => (
@@ -127,6 +211,7 @@ const ORIGINAL_RENDERERS: {
 			onError={props.onError}
 			paused={props.pauseVideo}
 			controls={props.mediaControls}
+			tracks={props.tracks}
 			blackBars={props.blackBars}
 			blackBarsSize={props.blackBarsSize}
 			className={clsx(
@@ -189,9 +274,10 @@ interface VideoProps extends VideoHTMLAttributes<HTMLVideoElement> {
 	paused?: boolean;
 	blackBars?: boolean;
 	blackBarsSize?: number;
+	tracks?: MediaTrack[];
 }
 
-const Video = ({ paused, blackBars, blackBarsSize, className, ...props }: VideoProps) => {
+const Video = ({ paused, blackBars, blackBarsSize, tracks, className, ...props }: VideoProps) => {
 	const { t } = useLocale();
 
 	const ref = useRef<HTMLVideoElement>(null);
@@ -240,6 +326,7 @@ const Video = ({ paused, blackBars, blackBarsSize, className, ...props }: VideoP
 				}
 			}}
 		>
+			{tracks?.map((track) => <track key={track.src} {...track} />)}
 			<p>{t('video_preview_not_supported')}</p>
 		</video>
 	);
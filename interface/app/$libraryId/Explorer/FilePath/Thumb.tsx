@@ -26,6 +26,8 @@ export interface ThumbProps {
 	data: ExplorerItem;
 	loadOriginal?: boolean;
 	size?: number;
+	// Size in pixels the thumbnail is displayed at, when not fixed by `size`
+	thumbnailSize?: number;
 	cover?: boolean;
 	frame?: boolean;
 	onLoad?: (state: ThumbType) => void;
@@ -87,8 +89,13 @@ export const FileThumb = forwardRef<HTMLImageElement, ThumbProps>((props, ref) =
 				break;
 
 			case 'thumbnail':
-				if (itemData.thumbnailKey.length > 0)
-					return platform.getThumbnailUrlByThumbKey(itemData.thumbnailKey);
+				if (itemData.thumbnailKey.length > 0) {
+					const thumbnailSize = props.size ?? props.thumbnailSize;
+					return platform.getThumbnailUrlByThumbKey(
+						itemData.thumbnailKey,
+						thumbnailSize ? Math.ceil(thumbnailSize * window.devicePixelRatio) : undefined
+					);
+				}
 
 				break;
 			case 'icon':
@@ -102,7 +109,16 @@ export const FileThumb = forwardRef<HTMLImageElement, ThumbProps>((props, ref) =
 					itemData.isDir
 				);
 		}
-	}, [filePath, isDark, library.uuid, itemData, platform, thumbType]);
+	}, [
+		filePath,
+		isDark,
+		library.uuid,
+		itemData,
+		platform,
+		thumbType,
+		props.size,
+		props.thumbnailSize
+	]);
 
 	const onLoad = (s: 'original' | 'thumbnail' | 'icon') => {
 		setLoadState((state) => ({ ...state, [s]: 'loaded' }));
@@ -1,7 +1,9 @@
 import dayjs from 'dayjs';
 import {
+	AudioMetadata,
 	capitalize,
 	CoordinatesFormat,
+	DocumentMetadata,
 	ExifMetadata,
 	FFmpegMetadata,
 	humanizeSize,
@@ -209,6 +211,50 @@ const FFmpegMediaData = (data: FFmpegMetadata) => {
 	);
 };
 
+const AudioMediaData = (tags: AudioMetadata, ffmpeg: FFmpegMetadata | null) => {
+	const { t } = useLocale();
+
+	const numberOf = (number: number | null, total: number | null) =>
+		number && total ? `${number} / ${total}` : number;
+
+	return (
+		<>
+			<MetaData label={t('title')} value={tags.title} />
+			<MetaData label={t('artist')} value={tags.artist} />
+			<MetaData label={t('album')} value={tags.album} />
+			{tags.album_artist && tags.album_artist !== tags.artist && (
+				<MetaData label={t('album_artist')} value={tags.album_artist} />
+			)}
+			<MetaData label={t('year')} value={tags.year} />
+			<MetaData label={t('track')} value={numberOf(tags.track_number, tags.track_total)} />
+			{tags.disc_number && (
+				<MetaData label={t('disc')} value={numberOf(tags.disc_number, tags.disc_total)} />
+			)}
+			<MetaData label={t('genre')} value={tags.genre} />
+			{tags.composer && <MetaData label={t('composer')} value={tags.composer} />}
+			{ffmpeg && FFmpegMediaData(ffmpeg)}
+		</>
+	);
+};
+
+const DocumentMediaData = (data: DocumentMetadata) => {
+	const { t, dateFormat } = useLocale();
+
+	return (
+		<>
+			<MetaData label={t('title')} value={data.title} />
+			<MetaData label={t('author')} value={data.author} />
+			{data.subject && <MetaData label={t('subject')} value={data.subject} />}
+			<MetaData label={t('pages')} value={data.page_count} />
+			<MetaData
+				label={t('date_created')}
+				tooltipValue={data.date_created}
+				value={data.date_created && dayjs(data.date_created).format(dateFormat)}
+			/>
+		</>
+	);
+};
+
 interface Props {
 	data: RemoteMediaData;
 }
@@ -225,7 +271,13 @@ export const MediaData = ({ data }: Props) => {
 				variant="apple"
 				title={t('more_info')}
 			>
-				{'Exif' in data ? ExifMediaData(data.Exif) : FFmpegMediaData(data.FFmpeg)}
+				{'Exif' in data
+					? ExifMediaData(data.Exif)
+					: 'Audio' in data
+						? AudioMediaData(data.Audio.tags, data.Audio.ffmpeg)
+						: 'Document' in data
+							? DocumentMediaData(data.Document)
+							: FFmpegMediaData(data.FFmpeg)}
 			</Accordion>
 		</div>
 	);
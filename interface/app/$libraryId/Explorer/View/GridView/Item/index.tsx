@@ -72,6 +72,8 @@ const InnerDroppable = () => {
 
 const ItemFileThumb = () => {
 	const frame = useFrame();
+	const explorer = useExplorerContext();
+	const explorerSettings = explorer.useSettingsSnapshot();
 
 	const item = useGridViewItemContext();
 	const isLabel = item.data.type === 'Label';
@@ -87,6 +89,7 @@ const ItemFileThumb = () => {
 			cover={isLabel}
 			blackBars
 			extension
+			thumbnailSize={explorerSettings.gridItemSize}
 			className={clsx(
 				isLabel ? [frame.className, '!size-[90%] !rounded-md'] : 'px-2 py-1',
 				item.cut && 'opacity-60'
@@ -93,7 +93,9 @@ export const RenamableItemText = ({
 							generate_preview_media: null,
 							sync_preview_media: null,
 							hidden: null,
-							indexer_rules_ids: []
+							indexer_rules_ids: [],
+							symlink_policy: null,
+							desktop_tags_policy: null
 						});
 
 						break;
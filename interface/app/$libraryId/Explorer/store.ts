@@ -157,13 +157,21 @@ export const filePathOrderingKeysSchema = z.union([
 	z.literal('dateIndexed').describe(i18n.t('date_indexed')),
 	z.literal('dateCreated').describe(i18n.t('date_created')),
 	z.literal('object.dateAccessed').describe(i18n.t('date_accessed')),
-	z.literal('object.mediaData.epochTime').describe(i18n.t('date_taken'))
+	z.literal('object.mediaData.epochTime').describe(i18n.t('date_taken')),
+	z.literal('object.audioData.artist').describe(i18n.t('artist')),
+	z.literal('object.audioData.album').describe(i18n.t('album')),
+	z.literal('object.audioData.year').describe(i18n.t('year')),
+	z.literal('object.audioData.trackNumber').describe(i18n.t('track_number'))
 ]);
 
 export const objectOrderingKeysSchema = z.union([
 	z.literal('dateAccessed').describe(i18n.t('date_accessed')),
 	z.literal('kind').describe(i18n.t('kind')),
-	z.literal('mediaData.epochTime').describe(i18n.t('date_taken'))
+	z.literal('mediaData.epochTime').describe(i18n.t('date_taken')),
+	z.literal('audioData.artist').describe(i18n.t('artist')),
+	z.literal('audioData.album').describe(i18n.t('album')),
+	z.literal('audioData.year').describe(i18n.t('year')),
+	z.literal('audioData.trackNumber').describe(i18n.t('track_number'))
 ]);
 
 export const nonIndexedPathOrderingSchema = z.union([
@@ -1,5 +1,6 @@
 import {
 	Copy,
+	Eraser,
 	Fingerprint,
 	Folder,
 	Icon,
@@ -29,7 +30,9 @@ const JobIcon: Record<string, Icon> = {
 	file_identifier: Fingerprint,
 	file_copier: Copy,
 	file_deleter: Trash,
+	file_eraser: Eraser,
 	file_cutter: Scissors,
+	file_deduplicator: Copy,
 	object_validator: Fingerprint
 };
 
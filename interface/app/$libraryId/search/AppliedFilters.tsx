@@ -87,7 +87,11 @@ export function FilterArg({ arg, onDelete }: { arg: SearchFilterArgs; onDelete?:
 	);
 
 	function isFilterDescriptionDisplayed() {
-		if (filter?.translationKey === 'hidden' || filter?.translationKey === 'favorite') {
+		if (
+			filter?.translationKey === 'hidden' ||
+			filter?.translationKey === 'favorite' ||
+			filter?.translationKey === 'world_writable'
+		) {
 			return false;
 		} else {
 			return true;
@@ -1,15 +1,30 @@
 import {
+	CalendarBlank,
 	CircleDashed,
 	Cube,
+	Files,
 	Folder,
 	Heart,
 	Icon,
+	LockOpen,
+	MusicNotes,
 	SelectionSlash,
 	Tag,
-	Textbox
+	Textbox,
+	User,
+	UserCircle,
+	Users,
+	VinylRecord
 } from '@phosphor-icons/react';
 import { useState } from 'react';
-import { InOrNotIn, ObjectKind, SearchFilterArgs, TextMatch, useLibraryQuery } from '@sd/client';
+import {
+	InOrNotIn,
+	ObjectKind,
+	Range,
+	SearchFilterArgs,
+	TextMatch,
+	useLibraryQuery
+} from '@sd/client';
 import { Button, Input } from '@sd/ui';
 import i18n from '~/app/I18n';
 import { Icon as SDIcon } from '~/components';
@@ -412,6 +427,53 @@ function createBooleanFilter(
 	};
 }
 
+function createRangeFilter(
+	filter: Omit<
+		ReturnType<typeof createFilter<any, Range<number>>>,
+		| 'conditions'
+		| 'getCondition'
+		| 'argsToOptions'
+		| 'setCondition'
+		| 'applyAdd'
+		| 'applyRemove'
+		| 'create'
+		| 'merge'
+	> & {
+		create(value: Range<number>): SearchFilterArgs;
+	}
+): ReturnType<typeof createFilter<(typeof filterTypeCondition)['optionalRange'], Range<number>>> {
+	return {
+		...filter,
+		conditions: filterTypeCondition.optionalRange,
+		create: (from) => filter.create({ from }),
+		getCondition: (data) => ('from' in data ? 'from' : 'to'),
+		setCondition: (data, condition) => {
+			const value = 'from' in data ? data.from : data.to;
+
+			return condition === 'from' ? { from: value } : { to: value };
+		},
+		argsToOptions: (data) => {
+			const value = 'from' in data ? data.from : data.to;
+
+			return [
+				{
+					type: filter.name,
+					name: value.toString(),
+					value
+				}
+			];
+		},
+		applyAdd: (data, { value }) => {
+			if ('from' in data) data.from = value;
+			else data.to = value;
+
+			return data;
+		},
+		applyRemove: () => undefined,
+		merge: (_, right) => right
+	};
+}
+
 export const filterRegistry = [
 	createInOrNotInFilter({
 		name: i18n.t('location'),
@@ -546,6 +608,91 @@ export const filterRegistry = [
 		useOptions: ({ search }) => [{ name: search, value: search, icon: Textbox }],
 		Render: ({ filter, search }) => <FilterOptionText filter={filter} search={search} />
 	}),
+	createTextMatchFilter({
+		name: i18n.t('subtitles'),
+		translationKey: 'subtitles',
+		icon: Textbox,
+		extract: (arg) => {
+			if ('object' in arg && 'subtitles' in arg.object) return arg.object.subtitles;
+		},
+		create: (subtitles) => ({ object: { subtitles } }),
+		useOptions: ({ search }) => [{ name: search, value: search, icon: Textbox }],
+		Render: ({ filter, search }) => <FilterOptionText filter={filter} search={search} />
+	}),
+	createTextMatchFilter({
+		name: i18n.t('artist'),
+		translationKey: 'artist',
+		icon: MusicNotes,
+		extract: (arg) => {
+			if ('object' in arg && 'artist' in arg.object) return arg.object.artist;
+		},
+		create: (artist) => ({ object: { artist } }),
+		useOptions: ({ search }) => [{ name: search, value: search, icon: MusicNotes }],
+		Render: ({ filter, search }) => <FilterOptionText filter={filter} search={search} />
+	}),
+	createTextMatchFilter({
+		name: i18n.t('album'),
+		translationKey: 'album',
+		icon: VinylRecord,
+		extract: (arg) => {
+			if ('object' in arg && 'album' in arg.object) return arg.object.album;
+		},
+		create: (album) => ({ object: { album } }),
+		useOptions: ({ search }) => [{ name: search, value: search, icon: VinylRecord }],
+		Render: ({ filter, search }) => <FilterOptionText filter={filter} search={search} />
+	}),
+	createTextMatchFilter({
+		name: i18n.t('author'),
+		translationKey: 'author',
+		icon: UserCircle,
+		extract: (arg) => {
+			if ('object' in arg && 'author' in arg.object) return arg.object.author;
+		},
+		create: (author) => ({ object: { author } }),
+		useOptions: ({ search }) => [{ name: search, value: search, icon: UserCircle }],
+		Render: ({ filter, search }) => <FilterOptionText filter={filter} search={search} />
+	}),
+	createRangeFilter({
+		name: i18n.t('page_count'),
+		translationKey: 'page_count',
+		icon: Files,
+		extract: (arg) => {
+			if ('object' in arg && 'pageCount' in arg.object) return arg.object.pageCount;
+		},
+		create: (pageCount) => ({ object: { pageCount } }),
+		useOptions: ({ search }) => {
+			const pageCount = Number.parseInt(search);
+
+			return Number.isNaN(pageCount) ? [] : [{ name: search, value: pageCount, icon: Files }];
+		},
+		Render: ({ filter, options, search }) => (
+			<FilterOptionList filter={filter} options={options} search={search} />
+		)
+	}),
+	createInOrNotInFilter({
+		name: i18n.t('year'),
+		translationKey: 'year',
+		icon: CalendarBlank,
+		extract: (arg) => {
+			if ('object' in arg && 'year' in arg.object) return arg.object.year;
+		},
+		create: (year) => ({ object: { year } }),
+		argsToOptions(values) {
+			return values.map((value) => ({
+				type: this.name,
+				name: value.toString(),
+				value
+			}));
+		},
+		useOptions: ({ search }) => {
+			const year = Number.parseInt(search);
+
+			return Number.isNaN(year) ? [] : [{ name: search, value: year, icon: CalendarBlank }];
+		},
+		Render: ({ filter, options, search }) => (
+			<FilterOptionList filter={filter} options={options} search={search} />
+		)
+	}),
 	createInOrNotInFilter({
 		name: i18n.t('extension'),
 		translationKey: 'extension',
@@ -583,6 +730,62 @@ export const filterRegistry = [
 		},
 		Render: ({ filter, search }) => <FilterOptionBoolean filter={filter} search={search} />
 	}),
+	createInOrNotInFilter({
+		name: i18n.t('owner'),
+		translationKey: 'owner',
+		icon: User,
+		extract: (arg) => {
+			if ('filePath' in arg && 'owner' in arg.filePath) return arg.filePath.owner;
+		},
+		create: (owner) => ({ filePath: { owner } }),
+		argsToOptions(values) {
+			return values.map((value) => ({
+				type: this.name,
+				name: value,
+				value
+			}));
+		},
+		useOptions: ({ search }) => [{ name: search, value: search, icon: User }],
+		Render: ({ filter, search }) => <FilterOptionText filter={filter} search={search} />
+	}),
+	createInOrNotInFilter({
+		name: i18n.t('owner_group'),
+		translationKey: 'owner_group',
+		icon: Users,
+		extract: (arg) => {
+			if ('filePath' in arg && 'ownerGroup' in arg.filePath) return arg.filePath.ownerGroup;
+		},
+		create: (ownerGroup) => ({ filePath: { ownerGroup } }),
+		argsToOptions(values) {
+			return values.map((value) => ({
+				type: this.name,
+				name: value,
+				value
+			}));
+		},
+		useOptions: ({ search }) => [{ name: search, value: search, icon: Users }],
+		Render: ({ filter, search }) => <FilterOptionText filter={filter} search={search} />
+	}),
+	createBooleanFilter({
+		name: i18n.t('world_writable'),
+		translationKey: 'world_writable',
+		icon: LockOpen,
+		extract: (arg) => {
+			if ('filePath' in arg && 'worldWritable' in arg.filePath)
+				return arg.filePath.worldWritable;
+		},
+		create: (worldWritable) => ({ filePath: { worldWritable } }),
+		useOptions: () => {
+			return [
+				{
+					name: i18n.t('world_writable'),
+					value: true,
+					icon: LockOpen
+				}
+			];
+		},
+		Render: ({ filter, search }) => <FilterOptionBoolean filter={filter} search={search} />
+	}),
 	createBooleanFilter({
 		name: i18n.t('favorite'),
 		translationKey: 'favorite',
@@ -14,6 +14,7 @@ import {
 	Receipt,
 	ShieldCheck,
 	TagSimple,
+	Trash,
 	User
 } from '@phosphor-icons/react';
 import clsx from 'clsx';
@@ -118,6 +119,10 @@ export default () => {
 						<Icon component={TagSimple} />
 						{t('tags')}
 					</SidebarLink>
+					<SidebarLink to="library/trash">
+						<Icon component={Trash} />
+						{t('trash')}
+					</SidebarLink>
 					{/* <SidebarLink to="library/saved-searches">
 						<Icon component={MagnifyingGlass} />
 						Saved Searches
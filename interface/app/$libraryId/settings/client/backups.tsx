@@ -66,7 +66,13 @@ export const Component = () => {
 					<div className="flex h-[45px] space-x-2 p-2">
 						<Button
 							disabled={doRestore.isLoading}
-							onClick={() => doRestore.mutate(backup.path)}
+							onClick={() =>
+								doRestore.mutate({
+									path: backup.path,
+									passphrase: null,
+									as_new_library: false
+								})
+							}
 							variant="gray"
 						>
 							{t('restore')}
@@ -83,12 +83,14 @@ export const Component = () => {
 				p2p_discovery: null,
 				p2p_remote_access: null,
 				p2p_manual_peers: null,
+				p2p_rendezvous_dir: null,
 				image_labeler_version: value.image_labeler_version ?? null
 			});
 
 			if (value.background_processing_percentage != undefined) {
 				await updateThumbnailerPreferences.mutateAsync({
-					background_processing_percentage: value.background_processing_percentage
+					background_processing_percentage: value.background_processing_percentage,
+					generate_video_previews: null
 				});
 			}
 		}
@@ -230,6 +232,24 @@ export const Component = () => {
 					/>
 				</div>
 			</Setting>
+			{/* Video Previews */}
+			<Setting
+				mini
+				title={t('video_previews')}
+				description={t('video_previews_description')}
+			>
+				<Switch
+					size="md"
+					checked={node.data?.preferences.thumbnailer.generate_video_previews ?? false}
+					onCheckedChange={async (checked) => {
+						await updateThumbnailerPreferences.mutateAsync({
+							background_processing_percentage: watchBackgroundProcessingPercentage,
+							generate_video_previews: checked
+						});
+						node.refetch();
+					}}
+				/>
+			</Setting>
 			{/* Image Labeler */}
 			{/* <Setting
 				mini
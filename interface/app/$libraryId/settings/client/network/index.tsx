@@ -70,7 +70,8 @@ export const Component = () => {
 					])
 					.optional(),
 				enable_remote_access: z.boolean().optional(),
-				p2p_manual_peers: z.array(z.string()).optional()
+				p2p_manual_peers: z.array(z.string()).optional(),
+				p2p_rendezvous_dir: z.string().optional()
 			})
 			.strict(),
 		reValidateMode: 'onChange',
@@ -81,7 +82,8 @@ export const Component = () => {
 			relay_disabled: node.data?.p2p.disable_relay || false,
 			discovery: node.data?.p2p.discovery || 'Everyone',
 			enable_remote_access: node.data?.p2p.disable_relay || false,
-			p2p_manual_peers: node.data?.p2p.manual_peers || []
+			p2p_manual_peers: node.data?.p2p.manual_peers || [],
+			p2p_rendezvous_dir: node.data?.p2p.rendezvous_dir ?? ''
 		}
 	});
 
@@ -96,6 +98,7 @@ export const Component = () => {
 				p2p_discovery: value.discovery ?? null,
 				p2p_remote_access: value.enable_remote_access ?? null,
 				p2p_manual_peers: value.p2p_manual_peers?.flatMap((v) => (v ? [v] : [])) ?? null,
+				p2p_rendezvous_dir: value.p2p_rendezvous_dir ?? null,
 				image_labeler_version: null
 			});
 		}
@@ -407,6 +410,18 @@ export const Component = () => {
 						</div>
 					</div>
 
+					<Setting
+						mini
+						title={t('rendezvous_directory')}
+						description={t('rendezvous_directory_description')}
+					>
+						<Input
+							className="w-60"
+							placeholder="/Volumes/Shared/Spacedrive"
+							{...form.register('p2p_rendezvous_dir')}
+						/>
+					</Setting>
+
 					<NodesPanel />
 				</>
 			) : null}
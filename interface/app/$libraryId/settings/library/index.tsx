@@ -11,6 +11,7 @@ export default [
 			{ path: 'sync', lazy: () => import('./sync') },
 			{ path: 'general', lazy: () => import('./general') },
 			{ path: 'tags', lazy: () => import('./tags') },
+			{ path: 'trash', lazy: () => import('./trash') },
 			// { path: 'saved-searches', lazy: () => import('./saved-searches') },
 			//this is for edit in tags context menu
 			{ path: 'tags/:id', lazy: () => import('./tags') },
@@ -13,6 +13,8 @@ import {
 	InputField,
 	Label,
 	RadioGroupField,
+	Select,
+	SelectOption,
 	SwitchField,
 	toast,
 	Tooltip,
@@ -37,7 +39,13 @@ const schema = z.object({
 	indexerRulesIds: z.array(z.number()),
 	locationType: z.string(),
 	syncPreviewMedia: z.boolean().nullable(),
-	generatePreviewMedia: z.boolean().nullable()
+	generatePreviewMedia: z.boolean().nullable(),
+	symlinkPolicy: z.union([z.literal('Ignore'), z.literal('IndexAsAlias'), z.literal('Follow')]),
+	desktopTagsPolicy: z.union([
+		z.literal('Ignore'),
+		z.literal('Import'),
+		z.literal('ImportAndWriteBack')
+	])
 });
 
 export const Component = () => {
@@ -68,7 +76,9 @@ const EditLocationForm = () => {
 			path: locationData?.path ?? '',
 			hidden: locationData?.hidden ?? false,
 			syncPreviewMedia: locationData?.sync_preview_media ?? false,
-			generatePreviewMedia: locationData?.generate_preview_media ?? false
+			generatePreviewMedia: locationData?.generate_preview_media ?? false,
+			symlinkPolicy: locationData?.symlink_policy ?? 'Ignore',
+			desktopTagsPolicy: locationData?.desktop_tags_policy ?? 'Ignore'
 		}
 	});
 
@@ -90,7 +100,9 @@ const EditLocationForm = () => {
 			hidden: data.hidden,
 			indexer_rules_ids: data.indexerRulesIds,
 			sync_preview_media: data.syncPreviewMedia,
-			generate_preview_media: data.generatePreviewMedia
+			generate_preview_media: data.generatePreviewMedia,
+			symlink_policy: data.symlinkPolicy,
+			desktop_tags_policy: data.desktopTagsPolicy
 		})
 	);
 
@@ -170,6 +182,48 @@ const EditLocationForm = () => {
 						</Label>
 						<SwitchField {...form.register('hidden')} size="sm" />
 					</ToggleSection>
+					<ToggleSection>
+						<Label className="grow">
+							{t('symlinks_label')}{' '}
+							<Tooltip label={t('symlinks_info')}>
+								<Info className="inline" />
+							</Tooltip>
+						</Label>
+						<Controller
+							name="symlinkPolicy"
+							control={form.control}
+							render={({ field }) => (
+								<Select {...field} size="sm">
+									<SelectOption value="Ignore">{t('symlinks_ignore')}</SelectOption>
+									<SelectOption value="IndexAsAlias">
+										{t('symlinks_index_as_alias')}
+									</SelectOption>
+									<SelectOption value="Follow">{t('symlinks_follow')}</SelectOption>
+								</Select>
+							)}
+						/>
+					</ToggleSection>
+					<ToggleSection>
+						<Label className="grow">
+							{t('desktop_tags_label')}{' '}
+							<Tooltip label={t('desktop_tags_info')}>
+								<Info className="inline" />
+							</Tooltip>
+						</Label>
+						<Controller
+							name="desktopTagsPolicy"
+							control={form.control}
+							render={({ field }) => (
+								<Select {...field} size="sm">
+									<SelectOption value="Ignore">{t('desktop_tags_ignore')}</SelectOption>
+									<SelectOption value="Import">{t('desktop_tags_import')}</SelectOption>
+									<SelectOption value="ImportAndWriteBack">
+										{t('desktop_tags_import_and_write_back')}
+									</SelectOption>
+								</Select>
+							)}
+						/>
+					</ToggleSection>
 				</div>
 				<Divider />
 				<Controller
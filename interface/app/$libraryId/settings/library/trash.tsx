@@ -0,0 +1,106 @@
+import { Trash } from '@phosphor-icons/react';
+import dayjs from 'dayjs';
+import { humanizeSize, useLibraryMutation, useLibraryQuery } from '@sd/client';
+import { Button, Card, Input } from '@sd/ui';
+import { useLocale } from '~/hooks';
+
+import { Heading } from '../Layout';
+import Setting from '../Setting';
+
+export const Component = () => {
+	const { t } = useLocale();
+	const trash = useLibraryQuery(['trash.list', null]);
+	const config = useLibraryQuery(['trash.getConfig']);
+	const setConfig = useLibraryMutation('trash.setConfig');
+	const doRestore = useLibraryMutation('trash.restore');
+	const doDelete = useLibraryMutation('trash.delete');
+	const doEmpty = useLibraryMutation('trash.empty');
+
+	return (
+		<>
+			<Heading
+				title={t('trash')}
+				description={t('trash_description')}
+				rightArea={
+					<Button
+						disabled={doEmpty.isLoading || !trash.data?.length}
+						variant="colored"
+						size="md"
+						className="border-red-500 bg-red-500"
+						onClick={() => doEmpty.mutate(null)}
+					>
+						{t('empty_trash')}
+					</Button>
+				}
+			/>
+
+			{config.data && (
+				<Setting
+					mini
+					title={t('trash_retention_days')}
+					description={t('trash_retention_days_description')}
+				>
+					<Input
+						key={config.data.retention_days ?? 'forever'}
+						type="number"
+						min={1}
+						className="w-24"
+						defaultValue={config.data.retention_days ?? ''}
+						onBlur={(e) => {
+							const days = parseInt(e.target.value);
+							setConfig.mutate({ retention_days: days > 0 ? days : null });
+						}}
+					/>
+				</Setting>
+			)}
+
+			{trash.data?.length === 0 && (
+				<p className="text-center text-sm text-ink-dull">{t('trash_is_empty')}</p>
+			)}
+
+			{trash.data?.map((item) => {
+				const size = item.size_in_bytes_bytes && humanizeSize(item.size_in_bytes_bytes);
+
+				return (
+					<Card key={item.id} className="hover:bg-app-box/70">
+						<Trash className="mr-3 size-10 self-center" />
+						<div className="grid min-w-[110px] grid-cols-1">
+							<h1 className="truncate pt-0.5 text-sm font-semibold">
+								{item.extension ? `${item.name}.${item.extension}` : item.name}
+							</h1>
+							<p className="mt-0.5 select-text truncate text-sm text-ink-dull">
+								{t('trashed_from', {
+									location: item.location.name,
+									path: item.materialized_path
+								})}
+								{' · '}
+								{dayjs(item.date_trashed).fromNow()}
+								{size &&
+									` · ${size.value} ${t(`size_${size.unit.toLowerCase()}`)}`}
+							</p>
+						</div>
+						<div className="flex grow" />
+						<div className="flex h-[45px] space-x-2 p-2">
+							<Button
+								disabled={doRestore.isLoading}
+								onClick={() => doRestore.mutate([item.id])}
+								variant="gray"
+							>
+								{t('restore')}
+							</Button>
+							<Button
+								disabled={doDelete.isLoading}
+								onClick={() => doDelete.mutate([item.id])}
+								size="sm"
+								variant="colored"
+								className="border-red-500 bg-red-500"
+							>
+								{t('delete_forever')}
+							</Button>
+						</div>
+					</Card>
+				);
+			})}
+		</>
+	);
+};
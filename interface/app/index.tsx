@@ -235,10 +235,12 @@ function RemoteLayout() {
 		() =>
 			({
 				...platform,
-				getThumbnailUrlByThumbKey: (thumbKey) =>
+				getThumbnailUrlByThumbKey: (thumbKey, size) =>
 					platform.constructRemoteRspcPath(
 						params.node,
-						`thumbnail/${thumbKey.map((i) => encodeURIComponent(i)).join('/')}.webp`
+						`thumbnail/${thumbKey.map((i) => encodeURIComponent(i)).join('/')}.webp${
+							size ? `?size=${size}` : ''
+						}`
 					),
 				getFileUrl: (libraryId, locationLocalId, filePathId) =>
 					platform.constructRemoteRspcPath(
@@ -247,6 +249,20 @@ function RemoteLayout() {
 							locationLocalId
 						)}/${encodeURIComponent(filePathId)}`
 					),
+				getTranscodeUrl: (libraryId, locationLocalId, filePathId) =>
+					platform.constructRemoteRspcPath(
+						params.node,
+						`transcode/${encodeURIComponent(libraryId)}/${encodeURIComponent(
+							locationLocalId
+						)}/${encodeURIComponent(filePathId)}`
+					),
+				getMediaTrackUrl: (libraryId, locationLocalId, filePathId, track) =>
+					platform.constructRemoteRspcPath(
+						params.node,
+						`tracks/${encodeURIComponent(libraryId)}/${encodeURIComponent(
+							locationLocalId
+						)}/${encodeURIComponent(filePathId)}/${encodeURIComponent(track)}`
+					),
 				getFileUrlByPath: (path) =>
 					platform.constructRemoteRspcPath(
 						params.node,
@@ -17,8 +17,18 @@ export type OpenWithApplication = { url: string; name: string };
 // This could be Tauri or web.
 export type Platform = {
 	platform: 'web' | 'tauri'; // This represents the specific platform implementation
-	getThumbnailUrlByThumbKey: (thumbKey: string[]) => string;
+	// `size` is the size in pixels the thumbnail is displayed at, so a smaller one can be served
+	getThumbnailUrlByThumbKey: (thumbKey: string[], size?: number) => string;
 	getFileUrl: (libraryId: string, locationLocalId: number, filePathId: number) => string;
+	// HLS playlist of the file transcoded on the fly, for media the webview can't play as is
+	getTranscodeUrl: (libraryId: string, locationLocalId: number, filePathId: number) => string;
+	// WebVTT track of a video, either `chapters.vtt` or `<subtitle id>.vtt`
+	getMediaTrackUrl: (
+		libraryId: string,
+		locationLocalId: number,
+		filePathId: number,
+		track: string
+	) => string;
 	getFileUrlByPath: (path: string) => string;
 	getRemoteRspcEndpoint: (remote_identity: string) => {
 		url: string;
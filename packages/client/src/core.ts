@@ -5,6 +5,7 @@ export type Procedures = {
     queries: 
         { key: "auth.me", input: never, result: { id: string; email: string } } | 
         { key: "backups.getAll", input: never, result: GetAll } | 
+        { key: "backups.getConfig", input: LibraryArgs<null>, result: BackupConfig } | 
         { key: "buildInfo", input: never, result: BuildInfo } | 
         { key: "cloud.getApiOrigin", input: never, result: string } | 
         { key: "cloud.library.get", input: LibraryArgs<null>, result: CloudLibrary | null } | 
@@ -15,6 +16,7 @@ export type Procedures = {
         { key: "files.getConvertibleImageExtensions", input: never, result: string[] } | 
         { key: "files.getMediaData", input: LibraryArgs<number>, result: MediaData } | 
         { key: "files.getPath", input: LibraryArgs<number>, result: string | null } | 
+        { key: "files.getSubtitles", input: LibraryArgs<number>, result: SubtitleTrackInfo[] } | 
         { key: "invalidation.test-invalidate", input: never, result: number } | 
         { key: "jobs.isActive", input: LibraryArgs<null>, result: boolean } | 
         { key: "jobs.reports", input: LibraryArgs<null>, result: JobGroup[] } | 
@@ -25,8 +27,11 @@ export type Procedures = {
         { key: "labels.list", input: LibraryArgs<null>, result: Label[] } | 
         { key: "labels.listWithThumbnails", input: LibraryArgs<string>, result: ExplorerItem[] } | 
         { key: "library.kindStatistics", input: LibraryArgs<null>, result: KindStatistics } | 
+        { key: "library.largestPaths", input: LibraryArgs<LargestPathsArgs>, result: ExplorerItem[] } | 
         { key: "library.list", input: never, result: LibraryConfigWrapped[] } | 
         { key: "library.statistics", input: LibraryArgs<null>, result: StatisticsResponse } | 
+        { key: "library.storageHistory", input: LibraryArgs<StorageHistoryArgs>, result: StorageSnapshot[] } | 
+        { key: "library.storageTree", input: LibraryArgs<StorageTreeArgs>, result: StorageTreeNode } | 
         { key: "locations.get", input: LibraryArgs<number>, result: Location | null } | 
         { key: "locations.getWithRules", input: LibraryArgs<number>, result: LocationWithIndexerRule | null } | 
         { key: "locations.indexer_rules.get", input: LibraryArgs<number>, result: IndexerRule } | 
@@ -41,27 +46,33 @@ export type Procedures = {
         { key: "notifications.dismissAll", input: never, result: null } | 
         { key: "notifications.get", input: never, result: Notification[] } | 
         { key: "p2p.listeners", input: never, result: Listeners } | 
+        { key: "p2p.remoteAccess.list", input: never, result: RemoteAccessGrant[] } | 
         { key: "p2p.state", input: never, result: JsonValue } | 
         { key: "preferences.get", input: LibraryArgs<null>, result: LibraryPreferences } | 
+        { key: "search.fuzzyPaths", input: LibraryArgs<FuzzyPathSearchArgs>, result: SearchData<ExplorerItem> } | 
         { key: "search.objects", input: LibraryArgs<ObjectSearchArgs>, result: SearchData<ExplorerItem> } | 
         { key: "search.objectsCount", input: LibraryArgs<{ filters?: SearchFilterArgs[] }>, result: number } | 
         { key: "search.paths", input: LibraryArgs<FilePathSearchArgs>, result: SearchData<ExplorerItem> } | 
         { key: "search.pathsCount", input: LibraryArgs<{ filters?: SearchFilterArgs[] }>, result: number } | 
         { key: "search.saved.get", input: LibraryArgs<number>, result: SavedSearch | null } | 
         { key: "search.saved.list", input: LibraryArgs<null>, result: SavedSearch[] } | 
+        { key: "search.similar", input: LibraryArgs<SimilarSearchArgs>, result: SimilarItem[][] } | 
         { key: "sync.enabled", input: LibraryArgs<null>, result: boolean } | 
         { key: "sync.messages", input: LibraryArgs<null>, result: CRDTOperation[] } | 
         { key: "tags.get", input: LibraryArgs<number>, result: Tag | null } | 
         { key: "tags.getForObject", input: LibraryArgs<number>, result: Tag[] } | 
         { key: "tags.getWithObjects", input: LibraryArgs<number[]>, result: { [key in number]: ({ object: { id: number }; date_created: string | null })[] } } | 
         { key: "tags.list", input: LibraryArgs<null>, result: Tag[] } | 
+        { key: "trash.getConfig", input: LibraryArgs<null>, result: TrashConfig } | 
+        { key: "trash.list", input: LibraryArgs<number | null>, result: ({ id: number; pub_id: number[]; materialized_path: string; name: string; extension: string; is_dir: boolean; size_in_bytes_bytes: number[] | null; date_trashed: string; location_id: number; location: { id: number; name: string | null; path: string | null } })[] } | 
         { key: "volumes.list", input: never, result: Volume[] },
     mutations: 
         { key: "api.sendFeedback", input: Feedback, result: null } | 
         { key: "auth.logout", input: never, result: null } | 
-        { key: "backups.backup", input: LibraryArgs<null>, result: string } | 
+        { key: "backups.backup", input: LibraryArgs<string | null>, result: null } | 
         { key: "backups.delete", input: string, result: null } | 
-        { key: "backups.restore", input: string, result: null } | 
+        { key: "backups.restore", input: RestoreArgs, result: null } | 
+        { key: "backups.setConfig", input: LibraryArgs<SetConfigArgs>, result: null } | 
         { key: "cloud.library.create", input: LibraryArgs<null>, result: null } | 
         { key: "cloud.library.join", input: string, result: LibraryConfigWrapped } | 
         { key: "cloud.library.sync", input: LibraryArgs<null>, result: null } | 
@@ -81,8 +92,9 @@ export type Procedures = {
         { key: "files.createFile", input: LibraryArgs<CreateFileArgs>, result: string } | 
         { key: "files.createFolder", input: LibraryArgs<CreateFolderArgs>, result: string } | 
         { key: "files.cutFiles", input: LibraryArgs<OldFileCutterJobInit>, result: null } | 
+        { key: "files.deduplicateFiles", input: LibraryArgs<OldFileDeduplicatorJobInit>, result: null } | 
         { key: "files.deleteFiles", input: LibraryArgs<OldFileDeleterJobInit>, result: null } | 
-        { key: "files.eraseFiles", input: LibraryArgs<OldFileEraserJobInit>, result: null } | 
+        { key: "files.eraseFiles", input: LibraryArgs<FileEraserJobInit>, result: null } | 
         { key: "files.moveToTrash", input: LibraryArgs<OldFileDeleterJobInit>, result: null } | 
         { key: "files.removeAccessTime", input: LibraryArgs<number[]>, result: null } | 
         { key: "files.renameFile", input: LibraryArgs<RenameFileArgs>, result: null } | 
@@ -120,6 +132,11 @@ export type Procedures = {
         { key: "p2p.acceptSpacedrop", input: [string, string | null], result: null } | 
         { key: "p2p.cancelSpacedrop", input: string, result: null } | 
         { key: "p2p.debugConnect", input: RemoteIdentity, result: string } | 
+        { key: "p2p.pairing.cancel", input: LibraryArgs<null>, result: null } | 
+        { key: "p2p.pairing.join", input: JoinPairingArgs, result: LibraryConfigWrapped } | 
+        { key: "p2p.pairing.start", input: LibraryArgs<null>, result: PairingCode } | 
+        { key: "p2p.remoteAccess.grant", input: RemoteAccessGrant, result: null } | 
+        { key: "p2p.remoteAccess.revoke", input: RemoteIdentity, result: null } | 
         { key: "p2p.spacedrop", input: SpacedropArgs, result: string } | 
         { key: "preferences.update", input: LibraryArgs<LibraryPreferences>, result: null } | 
         { key: "search.saved.create", input: LibraryArgs<{ name: string; target?: SearchTarget; search?: string | null; filters?: string | null; description?: string | null; icon?: string | null }>, result: null } | 
@@ -130,7 +147,11 @@ export type Procedures = {
         { key: "tags.create", input: LibraryArgs<TagCreateArgs>, result: Tag } | 
         { key: "tags.delete", input: LibraryArgs<number>, result: null } | 
         { key: "tags.update", input: LibraryArgs<TagUpdateArgs>, result: null } | 
-        { key: "toggleFeatureFlag", input: BackendFeature, result: null },
+        { key: "toggleFeatureFlag", input: BackendFeature, result: null } | 
+        { key: "trash.delete", input: LibraryArgs<number[]>, result: null } | 
+        { key: "trash.empty", input: LibraryArgs<number | null>, result: null } | 
+        { key: "trash.restore", input: LibraryArgs<number[]>, result: null } | 
+        { key: "trash.setConfig", input: LibraryArgs<TrashConfig>, result: null },
     subscriptions: 
         { key: "auth.loginSession", input: never, result: Response } | 
         { key: "invalidation.listen", input: never, result: InvalidateOperationEvent[] } | 
@@ -149,6 +170,14 @@ export type Procedures = {
 
 export type Args = { search?: string | null; filters?: string | null; name?: string | null; icon?: string | null; description?: string | null }
 
+export type AudioDataOrder = { field: "artist"; value: SortOrder } | { field: "album"; value: SortOrder } | { field: "year"; value: SortOrder } | { field: "trackNumber"; value: SortOrder }
+
+/**
+ * Tags of an audio file, from whichever tag format it uses (ID3, Vorbis comments, APE, MP4
+ * atoms and so on)
+ */
+export type AudioMetadata = { title: string | null; artist: string | null; album: string | null; album_artist: string | null; genre: string | null; composer: string | null; year: number | null; track_number: number | null; track_total: number | null; disc_number: number | null; disc_total: number | null }
+
 export type AudioProps = { delay: number; padding: number; sample_rate: number | null; sample_format: string | null; bit_per_sample: number | null; channel_layout: string | null }
 
 /**
@@ -158,7 +187,34 @@ export type AudioProps = { delay: number; padding: number; sample_rate: number |
  */
 export type BackendFeature = "cloudSync"
 
-export type Backup = ({ id: string; timestamp: string; library_id: string; library_name: string }) & { path: string }
+export type Backup = ({ id: string; timestamp: string; library_id: string; library_name: string; encrypted: boolean; base_id: string | null }) & { path: string }
+
+export type BackupConfig = { 
+/**
+ * Directory backups are written to, defaults to the `backups` directory in the node data directory.
+ */
+destination: string | null; 
+/**
+ * Hours between scheduled backups, they are disabled while this is `None`.
+ */
+interval_hours: number | null; 
+/**
+ * How many incremental backups are made after each full backup.
+ */
+incremental_backups: number; 
+/**
+ * The latest backup of each of the last `keep_daily` days is kept.
+ */
+keep_daily: number; 
+/**
+ * The latest backup of each of the last `keep_weekly` weeks is kept.
+ * Backups are never deleted while both `keep_daily` and `keep_weekly` are zero.
+ */
+keep_weekly: number; 
+/**
+ * Encrypts backups with the passphrase set through `backups.setConfig`.
+ */
+encrypted: boolean }
 
 export type BuildInfo = { version: string; commit: string }
 
@@ -168,7 +224,11 @@ export type CRDTOperationData = { c: { [key in string]: JsonValue } } | { u: { f
 
 export type CameraData = { device_make: string | null; device_model: string | null; color_space: string | null; color_profile: ColorProfile | null; focal_length: number | null; shutter_speed: number | null; flash: Flash | null; orientation: Orientation; lens_make: string | null; lens_model: string | null; bit_depth: number | null; zoom: number | null; iso: number | null; software: string | null; serial_number: string | null; lens_serial_number: string | null; contrast: number | null; saturation: number | null; sharpness: number | null; composite: Composite | null }
 
-export type ChangeNodeNameArgs = { name: string | null; p2p_port: Port | null; p2p_disabled: boolean | null; p2p_ipv6_disabled: boolean | null; p2p_relay_disabled: boolean | null; p2p_discovery: P2PDiscoveryState | null; p2p_remote_access: boolean | null; p2p_manual_peers: string[] | null; image_labeler_version: string | null }
+export type ChangeNodeNameArgs = { name: string | null; p2p_port: Port | null; p2p_disabled: boolean | null; p2p_ipv6_disabled: boolean | null; p2p_relay_disabled: boolean | null; p2p_discovery: P2PDiscoveryState | null; p2p_remote_access: boolean | null; p2p_manual_peers: string[] | null; 
+/**
+ * An empty path stops using a rendezvous directory
+ */
+p2p_rendezvous_dir: string | null; image_labeler_version: string | null }
 
 export type Chapter = { id: number; start: [number, number]; end: [number, number]; time_base_den: number; time_base_num: number; metadata: Metadata }
 
@@ -222,8 +282,38 @@ export type CreateLibraryArgs = { name: LibraryName; default_locations: DefaultL
 
 export type CursorOrderItem<T> = { order: SortOrder; data: T }
 
+/**
+ * How a duplicated file is replaced.
+ */
+export type DedupMethod = 
+/**
+ * The copies share their data blocks but stay independent files, needs Btrfs or XFS
+ */
+"Reflink" | 
+/**
+ * The copies become the same file, so changing one of them changes all of them
+ */
+"Hardlink"
+
 export type DefaultLocations = { desktop: boolean; documents: boolean; downloads: boolean; pictures: boolean; music: boolean; videos: boolean }
 
+/**
+ * What the file identifier and tag changes do with the tags other tools keep in extended attributes.
+ */
+export type DesktopTagsPolicy = 
+/**
+ * Tags in extended attributes are left alone
+ */
+"Ignore" | 
+/**
+ * Tags in extended attributes are added to the identified objects
+ */
+"Import" | 
+/**
+ * Tags are imported, and changes to the tags of objects are written back to their files
+ */
+"ImportAndWriteBack"
+
 /**
  * The method used for the discovery of this peer.
  * *Technically* you can have multiple under the hood but this simplifies things for the UX.
@@ -232,6 +322,12 @@ export type DiscoveryMethod = "Relay" | "Local" | "Manual"
 
 export type DiskType = "SSD" | "HDD" | "Removable"
 
+/**
+ * Metadata of a document or ebook, from PDF info dictionaries, OOXML and ODF core properties
+ * or EPUB package documents
+ */
+export type DocumentMetadata = { title: string | null; author: string | null; subject: string | null; page_count: number | null; date_created: string | null }
+
 export type DoubleClickAction = "openFile" | "quickPreview"
 
 export type EditLibraryArgs = { id: string; name: LibraryName | null; description: MaybeUndefined<string> }
@@ -283,15 +379,25 @@ export type FfmpegMediaVideoProps = { id: number; pixel_format: string | null; c
 
 export type FileCreateContextTypes = "empty" | "text"
 
-export type FilePath = { id: number; pub_id: number[]; is_dir: boolean | null; cas_id: string | null; integrity_checksum: string | null; location_id: number | null; materialized_path: string | null; name: string | null; extension: string | null; hidden: boolean | null; size_in_bytes: string | null; size_in_bytes_bytes: number[] | null; inode: number[] | null; object_id: number | null; key_id: number | null; date_created: string | null; date_modified: string | null; date_indexed: string | null }
+export type FileEraserJobInit = { location_id: number; file_path_ids: number[]; passes: string }
+
+export type FilePath = { id: number; pub_id: number[]; is_dir: boolean | null; cas_id: string | null; integrity_checksum: string | null; location_id: number | null; materialized_path: string | null; name: string | null; extension: string | null; hidden: boolean | null; size_in_bytes: string | null; size_in_bytes_bytes: number[] | null; inode: number[] | null; symlink_target: string | null; object_id: number | null; key_id: number | null; permissions: string | null; owner: string | null; owner_group: string | null; xattrs: string | null; date_created: string | null; date_modified: string | null; date_indexed: string | null }
 
 export type FilePathCursor = { isDir: boolean; variant: FilePathCursorVariant }
 
 export type FilePathCursorVariant = "none" | { name: CursorOrderItem<string> } | { sizeInBytes: SortOrder } | { dateCreated: CursorOrderItem<string> } | { dateModified: CursorOrderItem<string> } | { dateIndexed: CursorOrderItem<string> } | { object: FilePathObjectCursor }
 
-export type FilePathFilterArgs = { locations: InOrNotIn<number> } | { path: { location_id: number; path: string; include_descendants: boolean } } | { name: TextMatch } | { extension: InOrNotIn<string> } | { createdAt: Range<string> } | { modifiedAt: Range<string> } | { indexedAt: Range<string> } | { hidden: boolean }
+export type FilePathFilterArgs = { locations: InOrNotIn<number> } | { path: { location_id: number; path: string; include_descendants: boolean } } | { name: TextMatch } | { extension: InOrNotIn<string> } | { createdAt: Range<string> } | { modifiedAt: Range<string> } | { indexedAt: Range<string> } | { hidden: boolean } | 
+/**
+ * Files anyone on the system can write to, according to their unix permissions
+ */
+{ worldWritable: boolean } | { owner: InOrNotIn<string> } | { ownerGroup: InOrNotIn<string> } | 
+/**
+ * Files with the named extended attribute, among the ones we index
+ */
+{ extendedAttribute: string }
 
-export type FilePathForFrontend = { id: number; pub_id: number[]; is_dir: boolean | null; cas_id: string | null; integrity_checksum: string | null; location_id: number | null; materialized_path: string | null; name: string | null; extension: string | null; hidden: boolean | null; size_in_bytes: string | null; size_in_bytes_bytes: number[] | null; inode: number[] | null; object_id: number | null; object: { id: number; pub_id: number[]; kind: number | null; key_id: number | null; hidden: boolean | null; favorite: boolean | null; important: boolean | null; note: string | null; date_created: string | null; date_accessed: string | null; tags: ({ object_id: number; tag_id: number; tag: Tag; date_created: string | null })[]; exif_data: { resolution: number[] | null; media_date: number[] | null; media_location: number[] | null; camera_data: number[] | null; artist: string | null; description: string | null; copyright: string | null; exif_version: string | null } | null } | null; key_id: number | null; date_created: string | null; date_modified: string | null; date_indexed: string | null }
+export type FilePathForFrontend = { id: number; pub_id: number[]; is_dir: boolean | null; cas_id: string | null; integrity_checksum: string | null; location_id: number | null; materialized_path: string | null; name: string | null; extension: string | null; hidden: boolean | null; size_in_bytes: string | null; size_in_bytes_bytes: number[] | null; inode: number[] | null; symlink_target: string | null; object_id: number | null; object: { id: number; pub_id: number[]; kind: number | null; key_id: number | null; hidden: boolean | null; favorite: boolean | null; important: boolean | null; has_thumbstrip: boolean | null; has_video_preview: boolean | null; perceptual_hash: number[] | null; note: string | null; date_created: string | null; date_accessed: string | null; tags: ({ object_id: number; tag_id: number; tag: Tag; date_created: string | null })[]; exif_data: { resolution: number[] | null; media_date: number[] | null; media_location: number[] | null; camera_data: number[] | null; artist: string | null; description: string | null; copyright: string | null; exif_version: string | null } | null } | null; key_id: number | null; permissions: string | null; owner: string | null; owner_group: string | null; xattrs: string | null; date_created: string | null; date_modified: string | null; date_indexed: string | null }
 
 export type FilePathObjectCursor = { dateAccessed: CursorOrderItem<string> } | { kind: CursorOrderItem<number> }
 
@@ -348,6 +454,8 @@ export type FromPattern = { pattern: string; replace_all: boolean }
 
 export type FullRescanArgs = { location_id: number; reidentify_objects: boolean }
 
+export type FuzzyPathSearchArgs = { query: string; take?: number | null; filters?: SearchFilterArgs[] }
+
 export type GenerateLabelsForLocationArgs = { id: number; path: string; regenerate?: boolean }
 
 export type GenerateThumbsForLocationArgs = { id: number; path: string; regenerate?: boolean }
@@ -384,6 +492,12 @@ export type JobReport = { id: string; name: string; action: string | null; data:
 
 export type JobStatus = "Queued" | "Running" | "Completed" | "Canceled" | "Failed" | "Paused" | "CompletedWithErrors"
 
+export type JoinPairingArgs = { 
+/**
+ * The node to pair with, not needed when `code` is the payload of a pairing QR code
+ */
+identity: RemoteIdentity | null; code: string }
+
 export type JsonValue = null | boolean | number | string | JsonValue[] | { [key in string]: JsonValue }
 
 export type KindStatistic = { kind: number; name: string; count: number; total_bytes: string }
@@ -394,6 +508,8 @@ export type Label = { id: number; name: string; date_created: string | null; dat
 
 export type LabelWithObjects = { id: number; name: string; date_created: string | null; date_modified: string | null; label_objects: { object: { id: number; file_paths: FilePath[] } }[] }
 
+export type LargestPathsArgs = { location_id?: number | null; is_dir: boolean; take: number }
+
 /**
  * Can wrap a query argument to require it to contain a `library_id` and provide helpers for working with libraries.
  */
@@ -419,7 +535,15 @@ instance_id: number;
  * cloud_id is the ID of the cloud library this library is linked to.
  * If this is set we can assume the library is synced with the Cloud.
  */
-cloud_id?: string | null; generate_sync_operations?: boolean; version: LibraryConfigVersion }
+cloud_id?: string | null; generate_sync_operations?: boolean; 
+/**
+ * How and where the library is backed up.
+ */
+backup?: BackupConfig; 
+/**
+ * How long trashed files are kept.
+ */
+trash?: TrashConfig; version: LibraryConfigVersion }
 
 export type LibraryConfigVersion = "V0" | "V1" | "V2" | "V3" | "V4" | "V5" | "V6" | "V7" | "V8" | "V9" | "V10" | "V11"
 
@@ -435,7 +559,7 @@ export type ListenerState = { type: "Listening" } | { type: "Error"; error: stri
 
 export type Listeners = { ipv4: ListenerState; ipv6: ListenerState; relay: ListenerState }
 
-export type Location = { id: number; pub_id: number[]; name: string | null; path: string | null; total_capacity: number | null; available_capacity: number | null; size_in_bytes: number[] | null; is_archived: boolean | null; generate_preview_media: boolean | null; sync_preview_media: boolean | null; hidden: boolean | null; date_created: string | null; scan_state: number; instance_id: number | null }
+export type Location = { id: number; pub_id: number[]; name: string | null; path: string | null; total_capacity: number | null; available_capacity: number | null; size_in_bytes: number[] | null; is_archived: boolean | null; generate_preview_media: boolean | null; sync_preview_media: boolean | null; hidden: boolean | null; date_created: string | null; scan_state: number; symlink_policy: number | null; desktop_tags_policy: number | null; instance_id: number | null; volume_id: number | null }
 
 /**
  * `LocationCreateArgs` is the argument received from the client using `rspc` to create a new location.
@@ -454,13 +578,13 @@ export type LocationSettings = { explorer: ExplorerSettings<FilePathOrder> }
  * It is important to note that only the indexer rule ids in this vector will be used from now on.
  * Old rules that aren't in this vector will be purged.
  */
-export type LocationUpdateArgs = { id: number; name: string | null; generate_preview_media: boolean | null; sync_preview_media: boolean | null; hidden: boolean | null; indexer_rules_ids: number[]; path: string | null }
+export type LocationUpdateArgs = { id: number; name: string | null; generate_preview_media: boolean | null; sync_preview_media: boolean | null; hidden: boolean | null; indexer_rules_ids: number[]; path: string | null; symlink_policy: SymlinkPolicy | null; desktop_tags_policy: DesktopTagsPolicy | null }
 
-export type LocationWithIndexerRule = { id: number; pub_id: number[]; name: string | null; path: string | null; total_capacity: number | null; available_capacity: number | null; size_in_bytes: number[] | null; is_archived: boolean | null; generate_preview_media: boolean | null; sync_preview_media: boolean | null; hidden: boolean | null; date_created: string | null; instance_id: number | null; indexer_rules: IndexerRule[] }
+export type LocationWithIndexerRule = { id: number; pub_id: number[]; name: string | null; path: string | null; total_capacity: number | null; available_capacity: number | null; size_in_bytes: number[] | null; is_archived: boolean | null; generate_preview_media: boolean | null; sync_preview_media: boolean | null; hidden: boolean | null; date_created: string | null; symlink_policy: SymlinkPolicy; desktop_tags_policy: DesktopTagsPolicy; instance_id: number | null; indexer_rules: IndexerRule[] }
 
 export type MaybeUndefined<T> = null | T
 
-export type MediaData = { Exif: ExifMetadata } | { FFmpeg: FFmpegMetadata }
+export type MediaData = { Exif: ExifMetadata } | { FFmpeg: FFmpegMetadata } | { Audio: { tags: AudioMetadata; ffmpeg: FFmpegMetadata | null } } | { Document: DocumentMetadata }
 
 /**
  * This can be either naive with no TZ (`YYYY-MM-DD HH-MM-SS`) or UTC (`YYYY-MM-DD HH-MM-SS ±HHMM`),
@@ -473,6 +597,10 @@ export type MediaLocation = { latitude: number; longitude: number; pluscode: Plu
 export type Metadata = { album: string | null; album_artist: string | null; artist: string | null; comment: string | null; composer: string | null; copyright: string | null; creation_time: string | null; date: string | null; disc: number | null; encoder: string | null; encoded_by: string | null; filename: string | null; genre: string | null; language: string | null; performer: string | null; publisher: string | null; service_name: string | null; service_provider: string | null; title: string | null; track: number | null; variant_bit_rate: number | null; custom: { [key in string]: string } }
 
 export type NodeConfigP2P = { discovery?: P2PDiscoveryState; port: Port; disabled: boolean; disable_ipv6: boolean; disable_relay: boolean; enable_remote_access: boolean; 
+/**
+ * The remote nodes allowed to use this node's rspc router and custom URIs, and what they are allowed to do.
+ */
+remote_access?: RemoteAccessGrant[]; 
 /**
  * A list of peer addresses to try and manually connect to, instead of relying on discovery.
  * 
@@ -483,7 +611,12 @@ export type NodeConfigP2P = { discovery?: P2PDiscoveryState; port: Port; disable
  * - `[::1]` or `[::1]:3000`
  * which is why we use `String` not `SocketAddr`
  */
-manual_peers?: string[] }
+manual_peers?: string[]; 
+/**
+ * A directory shared with other nodes (like a network share) where nodes publish signed records of their addresses
+ * to find each other, for networks where mDNS doesn't work.
+ */
+rendezvous_dir?: string | null }
 
 export type NodePreferences = { thumbnailer: ThumbnailerPreferences }
 
@@ -514,31 +647,35 @@ export type NotificationId = { type: "library"; id: [string, number] } | { type:
 
 export type NotificationKind = "info" | "success" | "error" | "warning"
 
-export type Object = { id: number; pub_id: number[]; kind: number | null; key_id: number | null; hidden: boolean | null; favorite: boolean | null; important: boolean | null; note: string | null; date_created: string | null; date_accessed: string | null }
+export type Object = { id: number; pub_id: number[]; kind: number | null; key_id: number | null; hidden: boolean | null; favorite: boolean | null; important: boolean | null; has_thumbstrip: boolean | null; has_video_preview: boolean | null; perceptual_hash: number[] | null; note: string | null; date_created: string | null; date_accessed: string | null }
 
 export type ObjectCursor = "none" | { dateAccessed: CursorOrderItem<string> } | { kind: CursorOrderItem<number> }
 
-export type ObjectFilterArgs = { favorite: boolean } | { hidden: ObjectHiddenFilter } | { kind: InOrNotIn<number> } | { tags: InOrNotIn<number> } | { labels: InOrNotIn<number> } | { dateAccessed: Range<string> }
+export type ObjectFilterArgs = { favorite: boolean } | { hidden: ObjectHiddenFilter } | { kind: InOrNotIn<number> } | { tags: InOrNotIn<number> } | { labels: InOrNotIn<number> } | { dateAccessed: Range<string> } | { subtitles: TextMatch } | { artist: TextMatch } | { album: TextMatch } | { year: InOrNotIn<number> } | { trackNumber: InOrNotIn<number> } | { author: TextMatch } | { pageCount: Range<number> }
 
 export type ObjectHiddenFilter = "exclude" | "include"
 
-export type ObjectOrder = { field: "dateAccessed"; value: SortOrder } | { field: "kind"; value: SortOrder } | { field: "mediaData"; value: ExifDataOrder }
+export type ObjectOrder = { field: "dateAccessed"; value: SortOrder } | { field: "kind"; value: SortOrder } | { field: "mediaData"; value: ExifDataOrder } | { field: "audioData"; value: AudioDataOrder }
 
 export type ObjectSearchArgs = { take: number; orderAndPagination?: OrderAndPagination<number, ObjectOrder, ObjectCursor> | null; filters?: SearchFilterArgs[] }
 
 export type ObjectValidatorArgs = { id: number; path: string }
 
-export type ObjectWithFilePaths = { id: number; pub_id: number[]; kind: number | null; key_id: number | null; hidden: boolean | null; favorite: boolean | null; important: boolean | null; note: string | null; date_created: string | null; date_accessed: string | null; file_paths: ({ id: number; pub_id: number[]; is_dir: boolean | null; cas_id: string | null; integrity_checksum: string | null; location_id: number | null; materialized_path: string | null; name: string | null; extension: string | null; hidden: boolean | null; size_in_bytes: string | null; size_in_bytes_bytes: number[] | null; inode: number[] | null; object_id: number | null; object: { id: number; pub_id: number[]; kind: number | null; key_id: number | null; hidden: boolean | null; favorite: boolean | null; important: boolean | null; note: string | null; date_created: string | null; date_accessed: string | null; exif_data: { resolution: number[] | null; media_date: number[] | null; media_location: number[] | null; camera_data: number[] | null; artist: string | null; description: string | null; copyright: string | null; exif_version: string | null } | null; ffmpeg_data: { id: number; formats: string; bit_rate: number[]; duration: number[] | null; start_time: number[] | null; chapters: FfmpegMediaChapter[]; programs: ({ program_id: number; streams: ({ stream_id: number; name: string | null; codec: { id: number; kind: string | null; sub_kind: string | null; tag: string | null; name: string | null; profile: string | null; bit_rate: number; video_props: FfmpegMediaVideoProps | null; audio_props: FfmpegMediaAudioProps | null; stream_id: number; program_id: number; ffmpeg_data_id: number } | null; aspect_ratio_num: number; aspect_ratio_den: number; frames_per_second_num: number; frames_per_second_den: number; time_base_real_den: number; time_base_real_num: number; dispositions: string | null; title: string | null; encoder: string | null; language: string | null; duration: number[] | null; metadata: number[] | null; program_id: number; ffmpeg_data_id: number })[]; name: string | null; metadata: number[] | null; ffmpeg_data_id: number })[]; title: string | null; creation_time: string | null; date: string | null; album_artist: string | null; disc: string | null; track: string | null; album: string | null; artist: string | null; metadata: number[] | null; object_id: number } | null } | null; key_id: number | null; date_created: string | null; date_modified: string | null; date_indexed: string | null })[] }
+export type ObjectWithFilePaths = { id: number; pub_id: number[]; kind: number | null; key_id: number | null; hidden: boolean | null; favorite: boolean | null; important: boolean | null; has_thumbstrip: boolean | null; has_video_preview: boolean | null; perceptual_hash: number[] | null; note: string | null; date_created: string | null; date_accessed: string | null; file_paths: ({ id: number; pub_id: number[]; is_dir: boolean | null; cas_id: string | null; integrity_checksum: string | null; location_id: number | null; materialized_path: string | null; name: string | null; extension: string | null; hidden: boolean | null; size_in_bytes: string | null; size_in_bytes_bytes: number[] | null; inode: number[] | null; symlink_target: string | null; object_id: number | null; object: { id: number; pub_id: number[]; kind: number | null; key_id: number | null; hidden: boolean | null; favorite: boolean | null; important: boolean | null; has_thumbstrip: boolean | null; has_video_preview: boolean | null; perceptual_hash: number[] | null; note: string | null; date_created: string | null; date_accessed: string | null; exif_data: { resolution: number[] | null; media_date: number[] | null; media_location: number[] | null; camera_data: number[] | null; artist: string | null; description: string | null; copyright: string | null; exif_version: string | null } | null; ffmpeg_data: { id: number; formats: string; bit_rate: number[]; duration: number[] | null; start_time: number[] | null; chapters: FfmpegMediaChapter[]; programs: ({ program_id: number; streams: ({ stream_id: number; name: string | null; codec: { id: number; kind: string | null; sub_kind: string | null; tag: string | null; name: string | null; profile: string | null; bit_rate: number; video_props: FfmpegMediaVideoProps | null; audio_props: FfmpegMediaAudioProps | null; stream_id: number; program_id: number; ffmpeg_data_id: number } | null; aspect_ratio_num: number; aspect_ratio_den: number; frames_per_second_num: number; frames_per_second_den: number; time_base_real_den: number; time_base_real_num: number; dispositions: string | null; title: string | null; encoder: string | null; language: string | null; duration: number[] | null; metadata: number[] | null; program_id: number; ffmpeg_data_id: number })[]; name: string | null; metadata: number[] | null; ffmpeg_data_id: number })[]; title: string | null; creation_time: string | null; date: string | null; album_artist: string | null; disc: string | null; track: string | null; album: string | null; artist: string | null; metadata: number[] | null; object_id: number } | null } | null; key_id: number | null; permissions: string | null; owner: string | null; owner_group: string | null; xattrs: string | null; date_created: string | null; date_modified: string | null; date_indexed: string | null })[] }
 
-export type ObjectWithFilePaths2 = { id: number; pub_id: number[]; kind: number | null; key_id: number | null; hidden: boolean | null; favorite: boolean | null; important: boolean | null; note: string | null; date_created: string | null; date_accessed: string | null; file_paths: ({ id: number; pub_id: number[]; is_dir: boolean | null; cas_id: string | null; integrity_checksum: string | null; location_id: number | null; materialized_path: string | null; name: string | null; extension: string | null; hidden: boolean | null; size_in_bytes: string | null; size_in_bytes_bytes: number[] | null; inode: number[] | null; object_id: number | null; object: { id: number; pub_id: number[]; kind: number | null; key_id: number | null; hidden: boolean | null; favorite: boolean | null; important: boolean | null; note: string | null; date_created: string | null; date_accessed: string | null; exif_data: { resolution: number[] | null; media_date: number[] | null; media_location: number[] | null; camera_data: number[] | null; artist: string | null; description: string | null; copyright: string | null; exif_version: string | null } | null; ffmpeg_data: { id: number; formats: string; bit_rate: number[]; duration: number[] | null; start_time: number[] | null; chapters: FfmpegMediaChapter[]; programs: ({ program_id: number; streams: ({ stream_id: number; name: string | null; codec: { id: number; kind: string | null; sub_kind: string | null; tag: string | null; name: string | null; profile: string | null; bit_rate: number; video_props: FfmpegMediaVideoProps | null; audio_props: FfmpegMediaAudioProps | null; stream_id: number; program_id: number; ffmpeg_data_id: number } | null; aspect_ratio_num: number; aspect_ratio_den: number; frames_per_second_num: number; frames_per_second_den: number; time_base_real_den: number; time_base_real_num: number; dispositions: string | null; title: string | null; encoder: string | null; language: string | null; duration: number[] | null; metadata: number[] | null; program_id: number; ffmpeg_data_id: number })[]; name: string | null; metadata: number[] | null; ffmpeg_data_id: number })[]; title: string | null; creation_time: string | null; date: string | null; album_artist: string | null; disc: string | null; track: string | null; album: string | null; artist: string | null; metadata: number[] | null; object_id: number } | null } | null; key_id: number | null; date_created: string | null; date_modified: string | null; date_indexed: string | null })[] }
+export type ObjectWithFilePaths2 = { id: number; pub_id: number[]; kind: number | null; key_id: number | null; hidden: boolean | null; favorite: boolean | null; important: boolean | null; has_thumbstrip: boolean | null; has_video_preview: boolean | null; perceptual_hash: number[] | null; note: string | null; date_created: string | null; date_accessed: string | null; file_paths: ({ id: number; pub_id: number[]; is_dir: boolean | null; cas_id: string | null; integrity_checksum: string | null; location_id: number | null; materialized_path: string | null; name: string | null; extension: string | null; hidden: boolean | null; size_in_bytes: string | null; size_in_bytes_bytes: number[] | null; inode: number[] | null; symlink_target: string | null; object_id: number | null; object: { id: number; pub_id: number[]; kind: number | null; key_id: number | null; hidden: boolean | null; favorite: boolean | null; important: boolean | null; has_thumbstrip: boolean | null; has_video_preview: boolean | null; perceptual_hash: number[] | null; note: string | null; date_created: string | null; date_accessed: string | null; exif_data: { resolution: number[] | null; media_date: number[] | null; media_location: number[] | null; camera_data: number[] | null; artist: string | null; description: string | null; copyright: string | null; exif_version: string | null } | null; ffmpeg_data: { id: number; formats: string; bit_rate: number[]; duration: number[] | null; start_time: number[] | null; chapters: FfmpegMediaChapter[]; programs: ({ program_id: number; streams: ({ stream_id: number; name: string | null; codec: { id: number; kind: string | null; sub_kind: string | null; tag: string | null; name: string | null; profile: string | null; bit_rate: number; video_props: FfmpegMediaVideoProps | null; audio_props: FfmpegMediaAudioProps | null; stream_id: number; program_id: number; ffmpeg_data_id: number } | null; aspect_ratio_num: number; aspect_ratio_den: number; frames_per_second_num: number; frames_per_second_den: number; time_base_real_den: number; time_base_real_num: number; dispositions: string | null; title: string | null; encoder: string | null; language: string | null; duration: number[] | null; metadata: number[] | null; program_id: number; ffmpeg_data_id: number })[]; name: string | null; metadata: number[] | null; ffmpeg_data_id: number })[]; title: string | null; creation_time: string | null; date: string | null; album_artist: string | null; disc: string | null; track: string | null; album: string | null; artist: string | null; metadata: number[] | null; object_id: number } | null } | null; key_id: number | null; permissions: string | null; owner: string | null; owner_group: string | null; xattrs: string | null; date_created: string | null; date_modified: string | null; date_indexed: string | null })[] }
 
 export type OldFileCopierJobInit = { source_location_id: number; target_location_id: number; sources_file_path_ids: number[]; target_location_relative_directory_path: string }
 
 export type OldFileCutterJobInit = { source_location_id: number; target_location_id: number; sources_file_path_ids: number[]; target_location_relative_directory_path: string }
 
-export type OldFileDeleterJobInit = { location_id: number; file_path_ids: number[] }
+export type OldFileDeduplicatorJobInit = { location_id: number; method: DedupMethod; 
+/**
+ * Only report what would be deduplicated, without touching any file
+ */
+dry_run: boolean }
 
-export type OldFileEraserJobInit = { location_id: number; file_path_ids: number[]; passes: string }
+export type OldFileDeleterJobInit = { location_id: number; file_path_ids: number[] }
 
 /**
  * Represents the operating system which the remote peer is running.
@@ -554,6 +691,11 @@ export type P2PDiscoveryState = "Everyone" | "ContactsOnly" | "Disabled"
 
 export type P2PEvent = { type: "PeerChange"; identity: RemoteIdentity; connection: ConnectionMethod; discovery: DiscoveryMethod; metadata: PeerMetadata; addrs: string[] } | { type: "PeerDelete"; identity: RemoteIdentity } | { type: "SpacedropRequest"; id: string; identity: RemoteIdentity; peer_name: string; files: string[] } | { type: "SpacedropProgress"; id: string; percent: number } | { type: "SpacedropTimedOut"; id: string } | { type: "SpacedropRejected"; id: string }
 
+/**
+ * What the user has to give to the joining node, typed in or scanned as a QR code
+ */
+export type PairingCode = { code: string; payload: string }
+
 export type PeerMetadata = { name: string; operating_system: OperatingSystem | null; device_model: HardwareModel | null; version: string | null }
 
 export type PlusCode = string
@@ -566,6 +708,32 @@ export type Props = { Video: VideoProps } | { Audio: AudioProps } | { Subtitle:
 
 export type Range<T> = { from: T } | { to: T }
 
+/**
+ * Allows a remote node to use this node's rspc router and custom URIs over P2P
+ */
+export type RemoteAccessGrant = { identity: RemoteIdentity; scope: RemoteAccessScope }
+
+/**
+ * What a remote node holding a [`RemoteAccessGrant`] is allowed to do
+ */
+export type RemoteAccessScope = 
+/**
+ * Every query and subscription, but no mutations
+ */
+{ type: "ReadOnly" } | 
+/**
+ * Every procedure of these libraries, including mutations
+ */
+{ type: "Libraries"; library_ids: string[] } | 
+/**
+ * Browsing and fetching the files of these locations of a library
+ */
+{ type: "Locations"; library_id: string; location_ids: number[] } | 
+/**
+ * The same access the local interface has
+ */
+{ type: "Full" }
+
 export type RemoteIdentity = string
 
 export type RenameFileArgs = { location_id: number; kind: RenameKind }
@@ -582,6 +750,8 @@ export type Resolution = { width: number; height: number }
 
 export type Response = { Start: { user_code: string; verification_url: string; verification_url_complete: string } } | "Complete" | { Error: string }
 
+export type RestoreArgs = { path: string; passphrase: string | null; as_new_library: boolean }
+
 export type RuleKind = "AcceptFilesByGlob" | "RejectFilesByGlob" | "AcceptIfChildrenDirectoriesArePresent" | "RejectIfChildrenDirectoriesArePresent" | "IgnoredByGit"
 
 export type SavedSearch = { id: number; pub_id: number[]; target: string | null; search: string | null; filters: string | null; name: string | null; icon: string | null; description: string | null; date_created: string | null; date_modified: string | null }
@@ -592,10 +762,26 @@ export type SearchFilterArgs = { filePath: FilePathFilterArgs } | { object: Obje
 
 export type SearchTarget = "paths" | "objects"
 
+export type SetConfigArgs = { config: BackupConfig; passphrase: string | null }
+
 export type SetFavoriteArgs = { id: number; favorite: boolean }
 
 export type SetNoteArgs = { id: number; note: string | null }
 
+export type SimilarItem = { 
+/**
+ * Hamming distance between the perceptual hashes of this object and the first
+ * object in its group
+ */
+distance: number; item: ExplorerItem }
+
+export type SimilarSearchArgs = { 
+/**
+ * Objects that look like this one, if not set every group of similar objects
+ * in the library is returned instead
+ */
+objectId?: number | null; maxDistance?: number | null; take?: number | null }
+
 export type SingleInvalidateOperationEvent = { 
 /**
  * This fields are intentionally private.
@@ -610,10 +796,50 @@ export type Statistics = { id: number; date_captured: string; total_object_count
 
 export type StatisticsResponse = { statistics: Statistics | null }
 
+export type StorageHistoryArgs = { location_id?: number | null; since?: string | null }
+
+export type StorageSnapshot = { date_captured: string; location_id: number; kind: number; file_count: number; total_bytes: string }
+
+export type StorageTreeArgs = { location_id: number; 
+/**
+ * Materialized path of the directory to use as the tree root, defaults to the location root
+ */
+path?: string | null; depth?: number | null; max_children?: number | null }
+
+export type StorageTreeNode = { name: string; 
+/**
+ * Materialized path for this directory's children, can be used as the `path` of a new tree
+ */
+path: string; total_bytes: string; 
+/**
+ * Space taken by the files directly inside this directory and by
+ * any subdirectory left out due to the `depth` or `max_children` limits
+ */
+other_bytes: string; children: StorageTreeNode[] }
+
 export type Stream = { id: number; name: string | null; codec: Codec | null; aspect_ratio_num: number; aspect_ratio_den: number; frames_per_second_num: number; frames_per_second_den: number; time_base_real_den: number; time_base_real_num: number; dispositions: string[]; metadata: Metadata }
 
 export type SubtitleProps = { width: number; height: number }
 
+export type SubtitleTrackInfo = { id: number; streamId: number | null; sidecar: string | null; language: string | null; title: string | null }
+
+/**
+ * What the indexer and the watcher do with the symbolic links found in a location.
+ */
+export type SymlinkPolicy = 
+/**
+ * Symbolic links are skipped
+ */
+"Ignore" | 
+/**
+ * The link itself is indexed as an alias, recording the path it points to
+ */
+"IndexAsAlias" | 
+/**
+ * The link is indexed as what it points to, as long as it stays within the location
+ */
+"Follow"
+
 export type SyncStatus = { ingest: boolean; cloud_send: boolean; cloud_receive: boolean; cloud_ingest: boolean }
 
 export type SystemLocations = { desktop: string | null; documents: string | null; downloads: string | null; pictures: string | null; music: string | null; videos: string | null }
@@ -632,10 +858,23 @@ export type TestingParams = { id: string; path: string }
 
 export type TextMatch = { contains: string } | { startsWith: string } | { endsWith: string } | { equals: string }
 
-export type ThumbnailerPreferences = { background_processing_percentage: number }
+export type ThumbnailerPreferences = { background_processing_percentage: number; generate_video_previews?: boolean }
 
-export type UpdateThumbnailerPreferences = { background_processing_percentage: number }
+/**
+ * Trash settings of a library, stored in its config file.
+ */
+export type TrashConfig = { 
+/**
+ * Days trashed files are kept before being deleted for good, they are kept forever while this is `None`.
+ */
+retention_days: number | null }
+
+export type UpdateThumbnailerPreferences = { background_processing_percentage: number; generate_video_previews: boolean | null }
 
 export type VideoProps = { pixel_format: string | null; color_range: string | null; bits_per_channel: number | null; color_space: string | null; color_primaries: string | null; color_transfer: string | null; field_order: string | null; chroma_location: string | null; width: number; height: number; aspect_ratio_num: number | null; aspect_ratio_den: number | null; properties: string[] }
 
-export type Volume = { name: string; mount_points: string[]; total_capacity: string; available_capacity: string; disk_type: DiskType; file_system: string | null; is_root_filesystem: boolean }
+export type Volume = { name: string; mount_points: string[]; total_capacity: string; available_capacity: string; disk_type: DiskType; file_system: string | null; is_root_filesystem: boolean; 
+/**
+ * Filesystem UUID or serial number, when the platform exposes one
+ */
+identifier: string | null }
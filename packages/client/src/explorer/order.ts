@@ -57,13 +57,21 @@ export const filePathOrderingKeysSchema = z.union([
 	z.literal('dateIndexed').describe('Date Indexed'),
 	z.literal('dateCreated').describe('Date Created'),
 	z.literal('object.dateAccessed').describe('Date Accessed'),
-	z.literal('object.mediaData.epochTime').describe('Date Taken')
+	z.literal('object.mediaData.epochTime').describe('Date Taken'),
+	z.literal('object.audioData.artist').describe('Artist'),
+	z.literal('object.audioData.album').describe('Album'),
+	z.literal('object.audioData.year').describe('Year'),
+	z.literal('object.audioData.trackNumber').describe('Track Number')
 ]);
 
 export const objectOrderingKeysSchema = z.union([
 	z.literal('dateAccessed').describe('Date Accessed'),
 	z.literal('kind').describe('Kind'),
-	z.literal('mediaData.epochTime').describe('Date Taken')
+	z.literal('mediaData.epochTime').describe('Date Taken'),
+	z.literal('audioData.artist').describe('Artist'),
+	z.literal('audioData.album').describe('Album'),
+	z.literal('audioData.year').describe('Year'),
+	z.literal('audioData.trackNumber').describe('Track Number')
 ]);
 
 export const nonIndexedPathOrderingSchema = z.union([
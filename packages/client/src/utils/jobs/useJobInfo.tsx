@@ -1,5 +1,5 @@
 import { TextItems } from '.';
-import { formatNumber } from '../..';
+import { formatNumber, humanizeSize } from '../..';
 import { JobProgressEvent, JobReport } from '../../core';
 
 interface JobNiceData {
@@ -181,6 +181,29 @@ export function useJobInfo(job: JobReport, realtimeUpdate: JobProgressEvent | nu
 				} ${isRunning ? `of ${job.task_count}` : ``} ${plural(job.task_count, 'file')}`,
 				textItems: [[{ text: job.status }]]
 			};
+		case 'file_eraser':
+			return {
+				...data,
+				name: `${
+					isQueued ? 'Erase' : isRunning ? 'Erasing' : 'Erased'
+				} ${output?.erased_files ?? completedTaskCount} ${plural(
+					output?.erased_files ?? completedTaskCount,
+					'file'
+				)}`,
+				textItems: [
+					output
+						? [
+								{
+									text: `${formatNumber(output.verified_files)} verified`
+								},
+								{ text: `${humanizeSize(output.erased_bytes)} erased` },
+								...(output.ineffective_overwrite
+									? [{ text: 'Overwriting may not be effective on this drive' }]
+									: [])
+							]
+						: [{ text: job.status }]
+				]
+			};
 		case 'file_deleter':
 			return {
 				...data,
@@ -197,6 +220,38 @@ export function useJobInfo(job: JobReport, realtimeUpdate: JobProgressEvent | nu
 				} ${completedTaskCount} ${plural(completedTaskCount, 'file')}`,
 				textItems: [[{ text: job.status }]]
 			};
+		case 'file_deduplicator': {
+			const dryRun = (meta?.output as any)?.init?.dry_run;
+			return {
+				...data,
+				name: `${
+					isQueued
+						? 'Deduplicate'
+						: isRunning
+							? 'Deduplicating'
+							: dryRun
+								? 'Checked'
+								: 'Deduplicated'
+				} ${completedTaskCount} ${plural(completedTaskCount, 'object')}`,
+				textItems: [
+					output
+						? [
+								{
+									text: `${formatNumber(output.deduplicated.length)} ${plural(
+										output.deduplicated.length,
+										'file'
+									)} ${dryRun ? 'can be linked' : 'linked'}`
+								},
+								{
+									text: `${humanizeSize(output.reclaimed_bytes)} ${
+										dryRun ? 'reclaimable' : 'reclaimed'
+									}`
+								}
+							]
+						: [{ text: job.status }]
+				]
+			};
+		}
 		case 'object_validator':
 			return {
 				...data,